@@ -0,0 +1,91 @@
+//! An opponent that plays to the player's level as the game goes: after each of its moves the engine's
+//! own score says how the player is doing, and the engine is made weaker when the player is losing badly
+//! and stronger when the player is ahead.
+//!
+//! Strength comes in [`LEVELS`], each a `UCI_Elo` for engines that can limit their strength and a depth
+//! and move time for the ones that can't.
+
+use crate::uci::{Limits, Score};
+
+/// How strong the engine plays at one step of the ladder.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Level {
+    pub elo: u32,
+    pub depth: u8,
+    // milliseconds per move
+    pub time: u64,
+}
+
+/// The levels from weakest to strongest.
+pub const LEVELS: [Level; 8] = [
+    Level { elo: 1350, depth: 1, time: 200 },
+    Level { elo: 1500, depth: 2, time: 400 },
+    Level { elo: 1650, depth: 3, time: 600 },
+    Level { elo: 1800, depth: 4, time: 800 },
+    Level { elo: 1950, depth: 5, time: 1_000 },
+    Level { elo: 2100, depth: 6, time: 1_200 },
+    Level { elo: 2250, depth: 7, time: 1_400 },
+    Level { elo: 2400, depth: 8, time: 1_600 },
+];
+
+/// Centipawns the player can be behind before the engine eases off.
+pub const EASE_OFF: i32 = 250;
+/// Centipawns the player can be ahead before the engine tightens up.
+pub const TIGHTEN: i32 = 100;
+
+/// Where on [`LEVELS`] the engine is.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Adaptive {
+    level: usize,
+}
+
+impl Default for Adaptive {
+    /// The middle of the ladder.
+    fn default() -> Self {
+        Adaptive::new(LEVELS.len() / 2 - 1)
+    }
+}
+
+impl Adaptive {
+    /// Starts at `level`, an index into [`LEVELS`] (clamped to the last).
+    pub fn new(level: usize) -> Self {
+        Adaptive { level: level.min(LEVELS.len() - 1) }
+    }
+
+    /// The index into [`LEVELS`].
+    pub fn index(&self) -> usize {
+        self.level
+    }
+
+    pub fn level(&self) -> Level {
+        LEVELS[self.level]
+    }
+
+    /// Moves one level up or down by the engine's `score` after its move, from its own side. True if the
+    /// level changed, the engine then needs the new [`Adaptive::options`].
+    pub fn update(&mut self, score: Score) -> bool {
+        let player = -score.centipawns();
+        let level = if player <= -EASE_OFF {
+            self.level.saturating_sub(1)
+        } else if player >= TIGHTEN {
+            (self.level + 1).min(LEVELS.len() - 1)
+        } else {
+            self.level
+        };
+
+        let changed = level != self.level;
+        self.level = level;
+        changed
+    }
+
+    /// The limits of the engine's next search.
+    pub fn limits(&self) -> Limits {
+        let level = self.level();
+        Limits::default().time(level.time).depth(level.depth)
+    }
+
+    /// The UCI options that hold the engine to the level's elo.
+    pub fn options(&self) -> [(&'static str, String); 2] {
+        [("UCI_LimitStrength", String::from("true")), ("UCI_Elo", self.level().elo.to_string())]
+    }
+}
@@ -0,0 +1,142 @@
+use crate::chess::{Color, Game};
+use crate::result::{GameResult, TerminationReason};
+use crate::uci::Score;
+
+/// When an engine game is stopped early and scored by the engines' evaluations.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Adjudication {
+    /// A side resigns once both engines agree it is this many centipawns behind...
+    pub resign_score: i32,
+    /// ...for this many moves in a row.
+    pub resign_moves: u32,
+    /// A game is drawn once both engines score it within this many centipawns...
+    pub draw_score: i32,
+    /// ...for this many moves in a row,
+    pub draw_moves: u32,
+    /// ...and the game has reached this move number.
+    pub draw_after: u16,
+    /// Positions with at most this many pieces, kings included, are looked up in the tablebase.
+    pub tablebase_pieces: u32,
+}
+
+impl Default for Adjudication {
+    fn default() -> Self {
+        Adjudication {
+            resign_score: 1_000,
+            resign_moves: 3,
+            draw_score: 10,
+            draw_moves: 8,
+            draw_after: 40,
+            tablebase_pieces: 7,
+        }
+    }
+}
+
+/// A tablebase verdict, for the side to move.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Wdl {
+    Win,
+    Draw,
+    Loss,
+}
+
+/// Why a game was adjudicated.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AdjudicationReason {
+    Resignation,
+    DrawScore,
+    Tablebase,
+}
+
+/// Looks up a position in an endgame tablebase, `None` if it isn't covered.
+pub type TablebaseProbe = Box<dyn Fn(&Game) -> Option<Wdl> + Send>;
+
+/// Follows an engine game move by move and decides when to stop it.
+pub struct Adjudicator {
+    rules: Adjudication,
+    // plies in a row each side was losing, and the game was level
+    white_losing: u32,
+    black_losing: u32,
+    drawish: u32,
+    tablebase: Option<TablebaseProbe>,
+}
+
+impl Adjudicator {
+    pub fn new(rules: Adjudication) -> Self {
+        Adjudicator { rules, white_losing: 0, black_losing: 0, drawish: 0, tablebase: None }
+    }
+
+    /// Adjudicates positions with few enough pieces by `probe`.
+    pub fn with_tablebase(mut self, probe: impl Fn(&Game) -> Option<Wdl> + Send + 'static) -> Self {
+        self.tablebase = Some(Box::new(probe));
+        self
+    }
+
+    pub fn rules(&self) -> Adjudication {
+        self.rules
+    }
+
+    /// Forgets the moves seen so far, for a new game.
+    pub fn reset(&mut self) {
+        (self.white_losing, self.black_losing, self.drawish) = (0, 0, 0);
+    }
+
+    /// Counts a move, `game` is the position after it and `score` is what the engine
+    /// that played it reported, from its own point of view.
+    ///
+    /// Since the engines take turns, a side has to be behind for twice the move count
+    /// in plies, so both engines agree on it.
+    pub fn update(&mut self, game: &Game, score: Option<Score>) -> Option<GameResult> {
+        if let Some(probe) = &self.tablebase {
+            if pieces(game) <= self.rules.tablebase_pieces {
+                if let Some(wdl) = probe(game) {
+                    let winner = match wdl {
+                        Wdl::Win => { Some(game.turn) }
+                        Wdl::Draw => { None }
+                        Wdl::Loss => { Some(!game.turn) }
+                    };
+
+                    return Some(GameResult { winner, reason: TerminationReason::Adjudication(AdjudicationReason::Tablebase) });
+                }
+            }
+        }
+
+        // an engine that didn't say breaks the streak
+        let Some(score) = score else {
+            self.reset();
+            return None;
+        };
+
+        // the mover is the side that isn't to move anymore
+        let white = match !game.turn {
+            Color::White => { score.centipawns() }
+            Color::Black => { -score.centipawns() }
+        };
+
+        let count = |streak: &mut u32, holds: bool| { *streak = if holds { *streak + 1 } else { 0 }; *streak };
+        let white_losing = count(&mut self.white_losing, white <= -self.rules.resign_score);
+        let black_losing = count(&mut self.black_losing, white >= self.rules.resign_score);
+        let drawish = count(&mut self.drawish, white.abs() <= self.rules.draw_score);
+
+        if white_losing >= 2 * self.rules.resign_moves {
+            return Some(GameResult::win(Color::Black, TerminationReason::Adjudication(AdjudicationReason::Resignation)));
+        }
+        if black_losing >= 2 * self.rules.resign_moves {
+            return Some(GameResult::win(Color::White, TerminationReason::Adjudication(AdjudicationReason::Resignation)));
+        }
+        if drawish >= 2 * self.rules.draw_moves && game.fm_clock() >= self.rules.draw_after {
+            return Some(GameResult::draw(TerminationReason::Adjudication(AdjudicationReason::DrawScore)));
+        }
+
+        None
+    }
+}
+
+// pieces on the board, kings included
+fn pieces(game: &Game) -> u32 {
+    let count = |x: crate::chess::MaterialCount| x.pawns + x.minors() + x.rooks + x.queens;
+    count(game.material(Color::White)) + count(game.material(Color::Black)) + 2
+}
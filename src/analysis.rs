@@ -0,0 +1,271 @@
+use crate::chess::{Color, Game, Move};
+use crate::error::UciError;
+use crate::history::History;
+use crate::pgn::{parse_san, san, Pgn, Variation, VariationMove};
+use crate::uci::{Limits, Score, ThreadedUci, ThreadedUciBuilder, Uci, UciInfo, UciPosition};
+
+// how deep an Analyser searches each position
+const ANALYSIS_DEPTH: u8 = 20;
+// evaluations are clamped to this before computing centipawn loss,
+// so missing a faster mate in a won position isn't a blunder
+const MAX_EVAL: i32 = 1_000;
+
+/// How much a move worsened the position.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MoveClass {
+    Good,
+    Inaccuracy,
+    Mistake,
+    Blunder,
+}
+
+impl MoveClass {
+    /// Classifies a move by its centipawn loss.
+    pub fn from_cp_loss(cp_loss: i32) -> Self {
+        match cp_loss {
+            300.. => { MoveClass::Blunder }
+            100.. => { MoveClass::Mistake }
+            50.. => { MoveClass::Inaccuracy }
+            _ => { MoveClass::Good }
+        }
+    }
+
+    /// The annotation glyph written after the move, empty for good moves.
+    pub fn glyph(self) -> &'static str {
+        match self {
+            MoveClass::Good => { "" }
+            MoveClass::Inaccuracy => { "?!" }
+            MoveClass::Mistake => { "?" }
+            MoveClass::Blunder => { "??" }
+        }
+    }
+
+    /// The numeric annotation glyph of [`MoveClass::glyph`], as written in pgn.
+    pub fn nag(self) -> Option<u8> {
+        match self {
+            MoveClass::Good => { None }
+            MoveClass::Inaccuracy => { Some(6) }
+            MoveClass::Mistake => { Some(2) }
+            MoveClass::Blunder => { Some(4) }
+        }
+    }
+}
+
+/// A move of the game with the engine's opinion of it.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnalysedMove {
+    pub mv: Move,
+    pub san: String,
+    pub color: Color,
+    // evaluations in centipawns from white's point of view
+    pub eval_before: i32,
+    pub eval_after: i32,
+    pub cp_loss: i32,
+    pub class: MoveClass,
+    // the engine's preferred move in san, if it differs from the played move
+    pub best: Option<String>,
+}
+
+/// An annotated move list with an accuracy score per side.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Report {
+    pub moves: Vec<AnalysedMove>,
+    pub white_accuracy: f32,
+    pub black_accuracy: f32,
+}
+
+impl Report {
+    pub fn accuracy(&self, color: Color) -> f32 {
+        match color {
+            Color::White => { self.white_accuracy }
+            Color::Black => { self.black_accuracy }
+        }
+    }
+
+    /// The evaluation of every position of the game in centipawns from white's point of view, the start first.
+    pub fn evaluations(&self) -> Vec<i32> {
+        let mut evals: Vec<i32> = self.moves.iter().map(|x| x.eval_before).collect();
+        evals.extend(self.moves.last().map(|x| x.eval_after));
+
+        evals
+    }
+
+    /// Number of moves `color` played of the given class.
+    pub fn count(&self, color: Color, class: MoveClass) -> usize {
+        self.moves.iter().filter(|x| x.color == color && x.class == class).count()
+    }
+
+    /// Writes the report into the game it was made from: a glyph on every inaccuracy, mistake and blunder,
+    /// with the engine's move as a variation. Moves that already have the glyph are left alone.
+    pub fn annotate(&self, pgn: &mut Pgn) {
+        for (ply, analysed) in self.moves.iter().enumerate() {
+            let Some(nag) = analysed.class.nag() else { continue; };
+            if ply >= pgn.history.len() || pgn.notes(ply).is_some_and(|x| x.nags.contains(&nag)) { continue; }
+
+            let best = analysed.best.as_deref().and_then(|x| parse_san(&pgn.history.position(ply), x));
+            let notes = pgn.notes_mut(ply);
+
+            // one move assessment per move
+            notes.nags.retain(|x| !(1..=6).contains(x));
+            notes.nags.push(nag);
+
+            if let Some(best) = best {
+                let comment = format!("{} was best", analysed.best.as_deref().unwrap_or_default());
+                notes.variations.push(Variation { moves: vec![VariationMove { mv: best, notes: Default::default() }] });
+
+                match &mut notes.comment {
+                    Some(text) => {
+                        text.push(' ');
+                        text.push_str(&comment);
+                    }
+                    None => { notes.comment = Some(comment); }
+                }
+            }
+        }
+    }
+}
+
+/// Runs the engine over every position of `history` to `depth`,
+/// `progress` is called with (positions done, total positions).
+pub fn analyse_game(engine: &mut Uci, history: &History, depth: u8,
+                    mut progress: impl FnMut(usize, usize)) -> Result<Report, UciError> {
+    let limits = Limits::default().depth(depth);
+    engine.new_game()?;
+    let positions: Vec<Game> = (0..=history.len()).map(|x| history.position(x)).collect();
+
+    // (eval from white's view, best move)
+    let mut evals = Vec::new();
+    for (i, game) in positions.iter().enumerate() {
+        progress(i, positions.len());
+
+        // the engine has no move to give in finished games
+        let eval = if game.is_in_check(game.turn) && game.is_in_checkmate(game.turn) {
+            (white_view(game.turn, Score::Mate(0).centipawns()), None)
+        } else if game.is_stalemate() || game.is_draw() {
+            (0, None)
+        } else {
+            let evaluation = engine.evaluate(game, limits)?;
+            let cp = evaluation.score.map(|x| x.centipawns()).unwrap_or(0);
+
+            (white_view(game.turn, cp), Some(evaluation.best))
+        };
+
+        evals.push(eval);
+    }
+    progress(positions.len(), positions.len());
+
+    let mut moves = Vec::new();
+    for (i, mv) in history.moves().enumerate() {
+        let game = &positions[i];
+        let (before, best) = evals[i];
+        let (after, _) = evals[i + 1];
+
+        // loss from the mover's point of view
+        let sign = if game.turn == Color::White { 1 } else { -1 };
+        let cp_loss = (sign * (before.clamp(-MAX_EVAL, MAX_EVAL) - after.clamp(-MAX_EVAL, MAX_EVAL))).max(0);
+
+        moves.push(AnalysedMove {
+            mv,
+            san: san(game, mv),
+            color: game.turn,
+            eval_before: before,
+            eval_after: after,
+            cp_loss,
+            class: MoveClass::from_cp_loss(cp_loss),
+            best: best.filter(|x| *x != mv).map(|x| san(game, x)),
+        });
+    }
+
+    Ok(Report {
+        white_accuracy: accuracy(&moves, Color::White),
+        black_accuracy: accuracy(&moves, Color::Black),
+        moves,
+    })
+}
+
+/// An engine analysing the position on the board as a game goes on, like for an evaluation bar. It is an
+/// engine process of its own, so it never takes thinking time from an engine playing the game or
+/// disturbs its search, and the two can be different engines.
+pub struct Analyser {
+    engine: ThreadedUci,
+    position: Option<UciPosition>,
+    // the last scored info line for the position
+    info: Option<UciInfo>,
+    error: Option<UciError>,
+}
+
+impl Analyser {
+    /// Starts the engine from `builder`, its results are never held back.
+    pub fn new(builder: ThreadedUciBuilder) -> Self {
+        Analyser { engine: builder.instant().build(), position: None, info: None, error: None }
+    }
+
+    /// Analyses `position` from now on, unless it's the one already being analysed.
+    pub fn follow(&mut self, position: impl Into<UciPosition>) {
+        let position = position.into();
+        if self.position.as_ref() == Some(&position) { return; }
+
+        self.engine.cancel();
+        self.engine.recommend_move(position.clone(), Limits::default().depth(ANALYSIS_DEPTH));
+        (self.position, self.info) = (Some(position), None);
+    }
+
+    /// Stops analysing, until the next [`Analyser::follow`].
+    pub fn pause(&mut self) {
+        if self.position.is_none() { return; }
+
+        self.engine.cancel();
+        self.position = None;
+    }
+
+    /// The latest scored info line for the position, after reading what the engine sent since the last call.
+    pub fn info(&mut self) -> Option<&UciInfo> {
+        while let Some(info) = self.engine.try_info() {
+            if info.score.is_some() { self.info = Some(info); }
+        }
+        // the best move isn't needed, only an error that ended the search
+        if let Some(Err(err)) = self.engine.try_result() { self.error = Some(err); }
+
+        self.info.as_ref()
+    }
+
+    /// The latest score from white's point of view.
+    pub fn white_score(&mut self) -> Option<Score> {
+        let turn = self.position.as_ref()?.game().turn;
+        self.info()?.score.map(|x| x.white_view(turn))
+    }
+
+    /// The error that stopped the engine, if it has failed.
+    pub fn error(&self) -> Option<&UciError> {
+        self.error.as_ref()
+    }
+}
+
+fn white_view(turn: Color, cp: i32) -> i32 {
+    if turn == Color::White { cp } else { -cp }
+}
+
+/// Winning chances (0 to 100) for white at `cp`, based on lichess' model.
+pub fn win_percent(cp: i32) -> f32 {
+    let cp = cp.clamp(-MAX_EVAL, MAX_EVAL) as f32;
+    50.0 + 50.0 * (2.0 / (1.0 + (-0.003_682_08 * cp).exp()) - 1.0)
+}
+
+// average of lichess' per move accuracy, from the drop in winning chances
+fn accuracy(moves: &[AnalysedMove], color: Color) -> f32 {
+    let sign = if color == Color::White { 1 } else { -1 };
+
+    let accuracies: Vec<f32> = moves.iter()
+        .filter(|x| x.color == color)
+        .map(|x| {
+            let drop = (win_percent(sign * x.eval_before) - win_percent(sign * x.eval_after)).max(0.0);
+            (103.166_8 * (-0.043_54 * drop).exp() - 3.166_9).clamp(0.0, 100.0)
+        })
+        .collect();
+
+    if accuracies.is_empty() { return 100.0; }
+    accuracies.iter().sum::<f32>() / accuracies.len() as f32
+}
@@ -0,0 +1,168 @@
+use std::path::{Path, PathBuf};
+use crate::pgn::Pgn;
+use crate::platform::since_epoch;
+use crate::result::GameResult;
+use crate::study::Study;
+
+/// A directory of finished games, one pgn file per game.
+/// Metadata (date, players, result, time control) is kept in the pgn tags.
+pub struct Archive {
+    dir: PathBuf,
+}
+
+/// A game stored in an [`Archive`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ArchivedGame {
+    pub path: PathBuf,
+    pub pgn: Pgn,
+}
+
+impl Archive {
+    /// Opens the archive in `dir`, creating the directory if needed.
+    pub fn open(dir: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+
+        Ok(Archive { dir })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Writes `pgn` as a new file and returns its path.
+    pub fn save(&self, pgn: &Pgn) -> std::io::Result<PathBuf> {
+        let millis = since_epoch().unwrap_or_default().as_millis();
+
+        let mut path = self.dir.join(format!("{}.pgn", millis));
+        let mut i = 1;
+        while path.exists() {
+            path = self.dir.join(format!("{}-{}.pgn", millis, i));
+            i += 1;
+        }
+
+        std::fs::write(&path, pgn.to_string())?;
+        Ok(path)
+    }
+
+    /// All readable games, newest first. Files that are not valid pgn are skipped.
+    pub fn games(&self) -> std::io::Result<Vec<ArchivedGame>> {
+        let mut games = Vec::new();
+
+        for entry in std::fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|x| x != "pgn") { continue; }
+
+            let Ok(text) = std::fs::read_to_string(&path) else { continue; };
+            let Ok(pgn) = Pgn::parse(&text) else { continue; };
+
+            games.push(ArchivedGame { path, pgn });
+        }
+
+        // file names are timestamps
+        games.sort_by(|a, b| b.path.cmp(&a.path));
+        Ok(games)
+    }
+
+    /// Deletes the game's file and its study, if it has one.
+    pub fn delete(&self, game: &ArchivedGame) -> std::io::Result<()> {
+        std::fs::remove_file(&game.path)?;
+
+        match std::fs::remove_file(Study::sidecar_path(&game.path)) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => { Err(err) }
+            _ => { Ok(()) }
+        }
+    }
+}
+
+impl ArchivedGame {
+    /// Writes the game back to its file, after its pgn was edited.
+    pub fn save(&self) -> std::io::Result<()> {
+        std::fs::write(&self.path, self.pgn.to_string())
+    }
+
+    /// Whether every word of `filter` appears in one of the game's tags, ignoring case.
+    pub fn matches(&self, filter: &str) -> bool {
+        let filter = filter.to_lowercase();
+
+        filter.split_whitespace().all(|word| {
+            self.pgn.tags.iter().any(|(_, value)| value.to_lowercase().contains(word))
+        })
+    }
+
+    /// How the game ended, None if it is unfinished, see [`GameResult::from_pgn`].
+    pub fn result(&self) -> Option<GameResult> {
+        GameResult::from_pgn(&self.pgn)
+    }
+
+    /// A one line summary, like "2023.04.10 Player - Engine 1-0".
+    pub fn summary(&self) -> String {
+        let tag = |name| self.pgn.tag(name).unwrap_or("?");
+
+        format!("{} {} - {} {}", tag("Date"), tag("White"), tag("Black"), tag("Result"))
+    }
+}
+
+/// Who played a game and where, kept in its pgn tags. Empty fields are unknown, "?" in the pgn.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameInfo {
+    pub white: String,
+    pub black: String,
+    pub event: String,
+    pub round: String,
+}
+
+impl GameInfo {
+    /// The players, event and round tags of `pgn`, empty where a tag is missing or "?".
+    pub fn from_pgn(pgn: &Pgn) -> Self {
+        let tag = |name| pgn.tag(name).filter(|x| *x != "?").unwrap_or_default().to_string();
+
+        GameInfo { white: tag("White"), black: tag("Black"), event: tag("Event"), round: tag("Round") }
+    }
+
+    /// This, with the fields left empty filled from `defaults`.
+    pub fn or(self, defaults: GameInfo) -> Self {
+        let pick = |x: String, default| if x.trim().is_empty() { default } else { x };
+
+        GameInfo {
+            white: pick(self.white, defaults.white),
+            black: pick(self.black, defaults.black),
+            event: pick(self.event, defaults.event),
+            round: pick(self.round, defaults.round),
+        }
+    }
+
+    /// The same game with the players swapped, for a rematch.
+    pub fn swapped(self) -> Self {
+        GameInfo { white: self.black, black: self.white, ..self }
+    }
+
+    /// Writes the fields into the tags of `pgn`, "?" for the empty ones.
+    pub fn apply(&self, pgn: &mut Pgn) {
+        for (name, value) in [("Event", &self.event), ("Round", &self.round), ("White", &self.white), ("Black", &self.black)] {
+            let value = value.trim();
+            pgn.set_tag(name, if value.is_empty() { "?" } else { value });
+        }
+    }
+}
+
+/// Today's date in the pgn format, YYYY.MM.DD (UTC), or ????.??.?? without a clock.
+pub fn pgn_date() -> String {
+    let Some(now) = since_epoch() else { return String::from("????.??.??"); };
+    let days = now.as_secs() as i64 / 86_400;
+
+    // civil from days, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}.{:02}.{:02}", year, month, day)
+}
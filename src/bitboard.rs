@@ -0,0 +1,181 @@
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not};
+use crate::chess::{Color, Piece};
+
+/// A set of squares, bit `n` is the square with index `n` (a1 = 0, h8 = 63).
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Bitboard(pub u64);
+
+impl Bitboard {
+    pub const EMPTY: Bitboard = Bitboard(0);
+    /// The dark squares, a1 is dark.
+    pub const DARK: Bitboard = Bitboard(0xAA55_AA55_AA55_AA55);
+
+    pub const fn square(square: usize) -> Self {
+        Bitboard(1 << square)
+    }
+
+    pub fn contains(self, square: usize) -> bool {
+        self.0 & (1 << square) != 0
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn count(self) -> u32 {
+        self.0.count_ones()
+    }
+
+    /// The squares in the set, lowest index first.
+    pub fn squares(self) -> impl Iterator<Item = usize> {
+        let mut bits = self.0;
+
+        std::iter::from_fn(move || {
+            if bits == 0 { return None; }
+
+            let square = bits.trailing_zeros() as usize;
+            bits &= bits - 1;
+            Some(square)
+        })
+    }
+}
+
+impl BitAnd for Bitboard {
+    type Output = Bitboard;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        Bitboard(self.0 & rhs.0)
+    }
+}
+
+impl BitAndAssign for Bitboard {
+    fn bitand_assign(&mut self, rhs: Self) {
+        self.0 &= rhs.0;
+    }
+}
+
+impl BitOr for Bitboard {
+    type Output = Bitboard;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Bitboard(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for Bitboard {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl Not for Bitboard {
+    type Output = Bitboard;
+
+    fn not(self) -> Self::Output {
+        Bitboard(!self.0)
+    }
+}
+
+const KNIGHT_DELTAS: [(isize, isize); 8] = [(1, 2), (2, 1), (2, -1), (1, -2), (-1, -2), (-2, -1), (-2, 1), (-1, 2)];
+const KING_DELTAS: [(isize, isize); 8] = [(1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1), (0, -1), (1, -1)];
+
+pub(crate) const ROOK_DIRECTIONS: [(isize, isize); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+pub(crate) const BISHOP_DIRECTIONS: [(isize, isize); 4] = [(1, 1), (-1, 1), (-1, -1), (1, -1)];
+
+const fn step_table(deltas: [(isize, isize); 8]) -> [u64; 64] {
+    let mut table = [0; 64];
+
+    let mut square = 0;
+    while square < 64 {
+        let (x, y) = ((square % 8) as isize, (square / 8) as isize);
+
+        let mut i = 0;
+        while i < 8 {
+            let (nx, ny) = (x + deltas[i].0, y + deltas[i].1);
+            if nx >= 0 && nx < 8 && ny >= 0 && ny < 8 {
+                table[square] |= 1 << (ny * 8 + nx);
+            }
+            i += 1;
+        }
+
+        square += 1;
+    }
+
+    table
+}
+
+const KNIGHT_ATTACKS: [u64; 64] = step_table(KNIGHT_DELTAS);
+const KING_ATTACKS: [u64; 64] = step_table(KING_DELTAS);
+
+pub fn knight_attacks(square: usize) -> Bitboard {
+    Bitboard(KNIGHT_ATTACKS[square])
+}
+
+pub fn king_attacks(square: usize) -> Bitboard {
+    Bitboard(KING_ATTACKS[square])
+}
+
+/// The squares a pawn of `color` on `square` attacks.
+pub fn pawn_attacks(square: usize, color: Color) -> Bitboard {
+    let (x, y) = ((square % 8) as isize, (square / 8) as isize);
+    let ny = if color == Color::White { y + 1 } else { y - 1 };
+
+    let mut attacks = Bitboard::EMPTY;
+    if !(0..8).contains(&ny) { return attacks; }
+
+    for nx in [x - 1, x + 1] {
+        if (0..8).contains(&nx) { attacks |= Bitboard::square((ny * 8 + nx) as usize); }
+    }
+
+    attacks
+}
+
+/// The squares `piece` on `square` attacks, sliding pieces stop at the first occupied square.
+pub fn piece_attacks(piece: Piece, square: usize, occupied: Bitboard) -> Bitboard {
+    match piece {
+        Piece::WPawn | Piece::BPawn => { pawn_attacks(square, piece.color()) }
+        Piece::WKnight | Piece::BKnight => { knight_attacks(square) }
+        Piece::WBishop | Piece::BBishop => { bishop_attacks(square, occupied) }
+        Piece::WRook | Piece::BRook => { rook_attacks(square, occupied) }
+        Piece::WQueen | Piece::BQueen => { queen_attacks(square, occupied) }
+        Piece::WKing | Piece::BKing => { king_attacks(square) }
+    }
+}
+
+/// Squares reached from `square` in direction `(dx, dy)`, up to and including the first occupied square.
+pub fn ray(square: usize, (dx, dy): (isize, isize), occupied: Bitboard) -> Bitboard {
+    let (mut x, mut y) = ((square % 8) as isize + dx, (square / 8) as isize + dy);
+
+    let mut ray = Bitboard::EMPTY;
+    while (0..8).contains(&x) && (0..8).contains(&y) {
+        let to = (y * 8 + x) as usize;
+        ray |= Bitboard::square(to);
+
+        if occupied.contains(to) { break; }
+        x += dx;
+        y += dy;
+    }
+
+    ray
+}
+
+/// The squares strictly between `a` and `b`, empty unless they share a rank, file or diagonal.
+pub fn between(a: usize, b: usize) -> Bitboard {
+    let (dx, dy) = ((b % 8) as isize - (a % 8) as isize, (b / 8) as isize - (a / 8) as isize);
+    if a == b || (dx != 0 && dy != 0 && dx.abs() != dy.abs()) { return Bitboard::EMPTY; }
+
+    let direction = (dx.signum(), dy.signum());
+    ray(a, direction, Bitboard::square(b)) & !Bitboard::square(b)
+}
+
+pub fn rook_attacks(square: usize, occupied: Bitboard) -> Bitboard {
+    ROOK_DIRECTIONS.iter().fold(Bitboard::EMPTY, |acc, dir| acc | ray(square, *dir, occupied))
+}
+
+pub fn bishop_attacks(square: usize, occupied: Bitboard) -> Bitboard {
+    BISHOP_DIRECTIONS.iter().fold(Bitboard::EMPTY, |acc, dir| acc | ray(square, *dir, occupied))
+}
+
+pub fn queen_attacks(square: usize, occupied: Bitboard) -> Bitboard {
+    rook_attacks(square, occupied) | bishop_attacks(square, occupied)
+}
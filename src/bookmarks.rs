@@ -0,0 +1,104 @@
+use std::path::{Path, PathBuf};
+use crate::chess::Game;
+use crate::epd::Epd;
+
+/// A position saved under a name, to start games or analysis from.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Bookmark {
+    pub name: String,
+    pub tags: Vec<String>,
+    pub position: Game,
+}
+
+impl Bookmark {
+    pub fn new(name: impl Into<String>, tags: Vec<String>, position: Game) -> Self {
+        Bookmark { name: name.into(), tags, position }
+    }
+
+    /// Whether every word of `filter` appears in the name or one of the tags, ignoring case.
+    pub fn matches(&self, filter: &str) -> bool {
+        let filter = filter.to_lowercase();
+
+        filter.split_whitespace().all(|word| {
+            self.name.to_lowercase().contains(word) || self.tags.iter().any(|x| x.to_lowercase().contains(word))
+        })
+    }
+
+    /// The bookmark as an EPD line: the name is the `id`, the tags the `c0` comment and the clocks `hmvc` and `fmvn`.
+    pub fn to_epd(&self) -> Epd {
+        let fen = self.position.as_fen();
+        let clocks: Vec<&str> = fen.split(' ').skip(4).collect();
+
+        let mut epd = Epd::new(self.position);
+        epd.set_operation("id", vec![self.name.clone()]);
+        if !self.tags.is_empty() { epd.set_operation("c0", self.tags.clone()); }
+        if let [halfmove, fullmove] = clocks[..] {
+            epd.set_operation("hmvc", vec![halfmove.to_string()]);
+            epd.set_operation("fmvn", vec![fullmove.to_string()]);
+        }
+
+        epd
+    }
+
+    /// Reads a bookmark written by [`Bookmark::to_epd`], a position without an `id` is named by its FEN.
+    pub fn from_epd(epd: &Epd) -> Self {
+        let name = epd.id().map(String::from).unwrap_or_else(|| epd.game.as_fen());
+        let tags = epd.operation("c0").unwrap_or_default().to_vec();
+
+        Bookmark { name, tags, position: epd.game }
+    }
+}
+
+/// A file of bookmarks, one EPD line each.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct BookmarkLibrary {
+    path: PathBuf,
+    pub bookmarks: Vec<Bookmark>,
+}
+
+impl BookmarkLibrary {
+    /// Reads the library at `path`, a missing file is an empty library. Lines that can't be read are skipped.
+    pub fn open(path: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let path = path.into();
+
+        let bookmarks = match std::fs::read_to_string(&path) {
+            Ok(text) => {
+                text.lines().filter(|x| !x.trim().is_empty()).filter_map(|x| Epd::parse(x).ok()).map(|x| Bookmark::from_epd(&x)).collect()
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => { Vec::new() }
+            Err(err) => { return Err(err); }
+        };
+
+        Ok(BookmarkLibrary { path, bookmarks })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        let lines: Vec<String> = self.bookmarks.iter().map(|x| format!("{}\n", x.to_epd())).collect();
+        std::fs::write(&self.path, lines.concat())
+    }
+
+    /// Adds `bookmark` and saves the library.
+    pub fn add(&mut self, bookmark: Bookmark) -> std::io::Result<()> {
+        self.bookmarks.push(bookmark);
+        self.save()
+    }
+
+    /// Removes the bookmark at `index` and saves the library.
+    pub fn remove(&mut self, index: usize) -> std::io::Result<()> {
+        self.bookmarks.remove(index);
+        self.save()
+    }
+
+    /// Every tag used in the library, sorted.
+    pub fn tags(&self) -> Vec<&str> {
+        let mut tags: Vec<&str> = self.bookmarks.iter().flat_map(|x| x.tags.iter().map(|x| x.as_str())).collect();
+        tags.sort_unstable();
+        tags.dedup();
+
+        tags
+    }
+}
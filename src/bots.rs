@@ -0,0 +1,175 @@
+//! Weak opponents with a character, picking their moves straight from the legal moves instead of
+//! searching, so there is someone to play without an engine installed.
+//!
+//! Each [`Personality`] scores every legal move its own way and a [`Bot`] plays the best one, with
+//! ties (and a good share of the rest, for the weaker ones) broken at random. `chess --bot <name>`
+//! runs one behind enough of UCI for the GUI to play it like any other engine, see [`run_bot`].
+
+use std::io::{BufRead, Write};
+use crate::chess::{Game, Move, MoveFlags, MoveResult};
+use crate::engine::evaluate;
+use crate::square::Square;
+use crate::uci::{EngineConfig, UciPosition};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Personality {
+    /// Any legal move, all equally likely.
+    Random,
+    /// Takes the most valuable piece it can, whatever it costs, and moves at random otherwise.
+    Greedy,
+    /// Checks and heads for the enemy king, throwing pieces at it without a thought for their safety.
+    Sacrificer,
+    /// Looks one move ahead, badly: a blunder or a random move every few moves.
+    Martin,
+}
+
+impl Personality {
+    pub const ALL: [Personality; 4] = [Personality::Random, Personality::Greedy, Personality::Sacrificer, Personality::Martin];
+
+    /// The name shown for the bot.
+    pub fn name(self) -> &'static str {
+        match self {
+            Personality::Random => { "Random mover" }
+            Personality::Greedy => { "Greedy" }
+            Personality::Sacrificer => { "Sacrificer" }
+            Personality::Martin => { "Martin" }
+        }
+    }
+
+    /// The name given to `--bot`.
+    pub fn key(self) -> &'static str {
+        match self {
+            Personality::Random => { "random" }
+            Personality::Greedy => { "greedy" }
+            Personality::Sacrificer => { "sacrificer" }
+            Personality::Martin => { "martin" }
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Personality> {
+        Personality::ALL.into_iter().find(|x| x.key() == key)
+    }
+
+    /// How to start the bot as an engine: this program with `--bot`. None if the program can't tell
+    /// where it is.
+    pub fn config(self) -> Option<EngineConfig> {
+        let program = std::env::current_exe().ok()?;
+        Some(EngineConfig::new(self.name(), program.to_string_lossy()).args(["--bot", self.key()]))
+    }
+
+    // how much the bot likes `mv`, `noise` is a random number from 0 to 1 to blunder with
+    fn score(self, game: &Game, mv: Move, noise: f64) -> f64 {
+        let flags = game.move_flags(mv);
+        let taken = match game.board[mv.to] {
+            Some(piece) => { piece.value() }
+            None if flags.contains(MoveFlags::EN_PASSANT) => { 1 }
+            None => { 0 }
+        } as f64;
+
+        let mut after = *game;
+        let result = after.make_move(mv).expect("bots only score legal moves");
+        if result == MoveResult::Checkmate && self != Personality::Random { return 1_000.0; }
+
+        match self {
+            Personality::Random => { 0.0 }
+            Personality::Greedy => { taken }
+            Personality::Sacrificer => {
+                let king = game.find_king(!game.turn).map(Square::new);
+                // closer to the king is better, 7 squares away is as good as anywhere
+                let near = king.map(|king| {
+                    let distance = mv.to.file().index().abs_diff(king.file().index()).max(mv.to.rank().index().abs_diff(king.rank().index()));
+                    7 - distance.min(7)
+                }).unwrap_or(0) as f64;
+                let check = if result == MoveResult::Check { 5.0 } else { 0.0 };
+
+                check + taken + near / 2.0
+            }
+            Personality::Martin => {
+                // one in four moves are played without looking
+                if noise < 0.25 { return 0.0; }
+                // the evaluation is from the opponent's side after the move, in pawns
+                -evaluate(&after) as f64 / 100.0 + noise * 6.0
+            }
+        }
+    }
+}
+
+/// A bot ready to play, with its own random numbers.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Bot {
+    pub personality: Personality,
+    seed: u64,
+}
+
+impl Bot {
+    pub fn new(personality: Personality) -> Self {
+        let nanos = crate::platform::since_epoch().map(|x| x.as_nanos()).unwrap_or(0);
+        Bot::with_seed(personality, nanos as u64)
+    }
+
+    /// A bot that plays the same moves every time, for tests.
+    pub fn with_seed(personality: Personality, seed: u64) -> Self {
+        Bot { personality, seed: seed | 1 }
+    }
+
+    // xorshift, from 0 to 1
+    fn random(&mut self) -> f64 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+
+        (self.seed >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// The bot's move in `game`, None if there are no legal moves.
+    pub fn choose(&mut self, game: &Game) -> Option<Move> {
+        let mut best: Option<(f64, Move)> = None;
+
+        for mv in game.legal_moves() {
+            let noise = self.random();
+            // the tiebreak is small enough not to outweigh anything the personality cares about
+            let score = self.personality.score(game, mv, noise) + self.random() / 1_000.0;
+
+            if best.is_none_or(|(best, _)| score > best) { best = Some((score, mv)); }
+        }
+
+        best.map(|x| x.1)
+    }
+}
+
+/// Plays `personality` over the part of UCI a GUI needs to play against it: `uci`, `isready`,
+/// `ucinewgame`, `position`, `go` (answered right away, whatever the limits) and `quit`. There are no
+/// options and no `info` lines.
+pub fn run_bot(personality: Personality, input: impl BufRead, mut output: impl Write) -> std::io::Result<()> {
+    let mut bot = Bot::new(personality);
+    let mut position = UciPosition::Fen(Game::default());
+
+    for line in input.lines() {
+        let line = line?;
+
+        match line.split_whitespace().next().unwrap_or_default() {
+            "uci" => {
+                writeln!(output, "id name {}", personality.name())?;
+                writeln!(output, "id author Zillorz")?;
+                writeln!(output, "uciok")?;
+            }
+            "isready" => { writeln!(output, "readyok")?; }
+            "position" => {
+                match UciPosition::parse(&line) {
+                    Some(parsed) => { position = parsed; }
+                    None => { writeln!(output, "info string invalid position: {}", line)?; }
+                }
+            }
+            "go" => {
+                // 0000 is the null move, there is nothing to play
+                let mv = bot.choose(&position.game()).map(|x| x.to_string()).unwrap_or_else(|| String::from("0000"));
+                writeln!(output, "bestmove {}", mv)?;
+            }
+            "quit" => { break; }
+            _ => {}
+        }
+        output.flush()?;
+    }
+
+    Ok(())
+}
@@ -1,1049 +1,1558 @@
-use std::collections::HashSet;
-use std::ops::{Index, IndexMut, Not};
-use bitflags::bitflags;
-
-bitflags! {
-    #[repr(transparent)]
-    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-    struct CastleFlags: u8 {
-        const NONE = 0;
-
-        const WK = 1 << 0;
-        const WQ = 1 << 1;
-        const BK = 1 << 2;
-        const BQ = 1 << 3;
-
-        const W = Self::WK.bits() | Self::WQ.bits();
-        const B = Self::BK.bits() | Self::BQ.bits();
-
-        const ALL = Self::W.bits() | Self::B.bits();
-    }
-}
-
-impl Default for CastleFlags {
-    fn default() -> Self {
-        CastleFlags::ALL
-    }
-}
-
-#[repr(u8)]
-#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
-pub(crate) enum Piece {
-    WPawn,
-    WKnight,
-    WBishop,
-    WRook,
-    WQueen,
-    WKing,
-    BPawn,
-    BKnight,
-    BBishop,
-    BRook,
-    BQueen,
-    BKing
-}
-
-impl Piece {
-    // assuming in bounds
-    // Note: does not check pawn movement, as pawn movement is far too complex
-    // Note: does not check castling, as castling is also far too complex
-    fn can_move(&self, relative_x: isize, relative_y: isize) -> bool {
-        match self {
-            Piece::WPawn | Piece::BPawn => { true }
-            Piece::WKnight | Piece::BKnight => { (relative_x.abs() == 2 && relative_y.abs() == 1) || (relative_y.abs() == 2 && relative_x.abs() == 1) }
-            Piece::WBishop | Piece::BBishop => { relative_x.abs() == relative_y.abs() }
-            Piece::WRook | Piece::BRook => { (relative_x == 0 && relative_y != 0) || (relative_x != 0 && relative_y == 0) }
-            Piece::WKing | Piece::BKing => { relative_x.abs() <= 1 && relative_y.abs() <= 1 }
-            Piece::WQueen | Piece::BQueen => {
-                (relative_x == 0 && relative_y != 0) || (relative_x != 0 && relative_y == 0) || relative_x.abs() == relative_y.abs()
-            }
-        }
-    }
-
-    pub(crate) fn color(&self) -> Color {
-        match self {
-            Piece::WPawn | Piece::WKnight | Piece::WBishop | Piece::WRook | Piece::WQueen | Piece::WKing => {
-                Color::White
-            }
-            Piece::BPawn | Piece::BKnight | Piece::BBishop | Piece::BRook | Piece::BQueen | Piece::BKing => {
-                Color::Black
-            }
-        }
-    }
-
-    pub(crate) fn from_promotion(prm: Promotion, color: Color) -> Piece {
-        match (prm, color) {
-            (Promotion::Knight, Color::White) => { Piece::WKnight }
-            (Promotion::Bishop, Color::White) => { Piece::WBishop }
-            (Promotion::Rook, Color::White) => { Piece::WRook }
-            (Promotion::Queen, Color::White) => { Piece::WQueen }
-            (Promotion::Knight, Color::Black) => { Piece::BKnight }
-            (Promotion::Bishop, Color::Black) => { Piece::BBishop }
-            (Promotion::Rook, Color::Black) => { Piece::BRook }
-            (Promotion::Queen, Color::Black) => { Piece::BQueen }
-        }
-    }
-
-    fn from_letter(letter: char) -> Option<Piece> {
-        let piece = match letter {
-            'p' => { Piece::BPawn }
-            'n' => { Piece::BKnight }
-            'b' => { Piece::BBishop }
-            'r' => { Piece::BRook }
-            'q' => { Piece::BQueen }
-            'k' => { Piece::BKing }
-
-            'P' => { Piece::WPawn }
-            'N' => { Piece::WKnight }
-            'B' => { Piece::WBishop }
-            'R' => { Piece::WRook }
-            'Q' => { Piece::WQueen }
-            'K' => { Piece::WKing }
-            _ => { return None; }
-        };
-
-        Some(piece)
-    }
-
-    fn to_letter(self) -> char {
-        match self {
-            Piece::BPawn => { 'p' }
-            Piece::BKnight => { 'n' }
-            Piece::BBishop => { 'b' }
-            Piece::BRook => { 'r' }
-            Piece::BQueen => { 'q' }
-            Piece::BKing => { 'k' }
-
-            Piece::WPawn => { 'P' }
-            Piece::WKnight => { 'N' }
-            Piece::WBishop => { 'B' }
-            Piece::WRook => { 'R' }
-            Piece::WQueen => { 'Q' }
-            Piece::WKing => { 'K' }
-        }
-    }
-}
-
-#[repr(transparent)]
-#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
-pub(crate) struct Board([Option<Piece>; 64]);
-
-impl Default for Board {
-    fn default() -> Self {
-        // keep in mind, this is upside down
-        // or just use the fen
-        Board([
-            Some(Piece::WRook), Some(Piece::WKnight), Some(Piece::WBishop),
-            Some(Piece::WQueen), Some(Piece::WKing), Some(Piece::WBishop), Some(Piece::WKnight), Some(Piece::WRook),
-
-            Some(Piece::WPawn), Some(Piece::WPawn), Some(Piece::WPawn), Some(Piece::WPawn),
-            Some(Piece::WPawn), Some(Piece::WPawn), Some(Piece::WPawn), Some(Piece::WPawn),
-
-            None, None, None, None, None, None, None, None,
-            None, None, None, None, None, None, None, None,
-            None, None, None, None, None, None, None, None,
-            None, None, None, None, None, None, None, None,
-
-            Some(Piece::BPawn), Some(Piece::BPawn), Some(Piece::BPawn), Some(Piece::BPawn),
-            Some(Piece::BPawn), Some(Piece::BPawn), Some(Piece::BPawn), Some(Piece::BPawn),
-
-            Some(Piece::BRook), Some(Piece::BKnight), Some(Piece::BBishop),
-            Some(Piece::BQueen), Some(Piece::BKing), Some(Piece::BBishop), Some(Piece::BKnight), Some(Piece::BRook)
-        ])
-    }
-}
-
-impl Board {
-    fn from_fen_board(fen_board: &str) -> Option<Board> {
-        let rows = fen_board.split('/').rev().flat_map(|x| x.chars());
-
-        let mut vec = Vec::new();
-        for char in rows {
-            if char.is_ascii_digit() {
-                for _ in 0..char as u8 - b'0' { vec.push(None); }
-            } else {
-                vec.push(Piece::from_letter(char));
-            }
-        }
-
-        let b: [Option<Piece>; 64] = vec.try_into().ok()?;
-        Some(Board(b))
-    }
-
-    fn into_fen_board(self) -> String {
-        let mut str = String::new();
-
-        for y in (0..8).rev() {
-            let ym = y * 8;
-            let mut none_inr = 0;
-
-            for x in 0..8 {
-                if let Some(piece) = self[ym + x] {
-                    if none_inr != 0 { str.push(char::from(none_inr as u8 + b'0')); }
-                    str.push(piece.to_letter());
-
-                    none_inr = 0;
-                } else {
-                    none_inr += 1;
-                }
-            }
-
-            if none_inr != 0 { str.push(char::from(none_inr as u8 + b'0')); }
-            if y != 0 { str.push('/') }
-        }
-
-        str
-    }
-}
-
-impl Index<usize> for Board {
-    type Output = Option<Piece>;
-
-    fn index(&self, index: usize) -> &Self::Output {
-        &self.0[index]
-    }
-}
-
-impl IndexMut<usize> for Board {
-    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
-        &mut self.0[index]
-    }
-}
-
-#[repr(u8)]
-#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
-pub(crate) enum EnPassant {
-    A2, B2, C2, D2, E2, F2, G2, H2,
-    A5, B5, C5, D5, E5, F5, G5, H5
-}
-
-impl EnPassant {
-    pub(crate) fn location(self) -> usize {
-        match self {
-            EnPassant::A2 => { 16 }
-            EnPassant::B2 => { 17 }
-            EnPassant::C2 => { 18 }
-            EnPassant::D2 => { 19 }
-            EnPassant::E2 => { 20 }
-            EnPassant::F2 => { 21 }
-            EnPassant::G2 => { 22 }
-            EnPassant::H2 => { 23 }
-            EnPassant::A5 => { 40 }
-            EnPassant::B5 => { 41 }
-            EnPassant::C5 => { 42 }
-            EnPassant::D5 => { 43 }
-            EnPassant::E5 => { 44 }
-            EnPassant::F5 => { 45 }
-            EnPassant::G5 => { 46 }
-            EnPassant::H5 => { 47 }
-        }
-    }
-
-    // From the location the pawn moves from
-    fn from_pawn_location(location: usize) -> Option<EnPassant> {
-        let ret = match location {
-            8 => { EnPassant::A2 }
-            9 => { EnPassant::B2 }
-            10 => { EnPassant::C2 }
-            11 => { EnPassant::D2 }
-            12 => { EnPassant::E2 }
-            13 => { EnPassant::F2 }
-            14 => { EnPassant::G2 }
-            15 => { EnPassant::H2 }
-            48 => { EnPassant::A5 }
-            49 => { EnPassant::B5 }
-            50 => { EnPassant::C5 }
-            51 => { EnPassant::D5 }
-            52 => { EnPassant::E5 }
-            53 => { EnPassant::F5 }
-            54 => { EnPassant::G5 }
-            55 => { EnPassant::H5 }
-            _ => { return None; }
-        };
-
-        Some(ret)
-    }
-
-    fn from_take_location(location: usize) -> Option<EnPassant> {
-        let ret = match location {
-            16 => { EnPassant::A2 }
-            17 => { EnPassant::B2 }
-            18 => { EnPassant::C2 }
-            19 => { EnPassant::D2 }
-            20 => { EnPassant::E2 }
-            21 => { EnPassant::F2 }
-            22 => { EnPassant::G2 }
-            23 => { EnPassant::H2 }
-            40 => { EnPassant::A5 }
-            41 => { EnPassant::B5 }
-            42 => { EnPassant::C5 }
-            43 => { EnPassant::D5 }
-            44 => { EnPassant::E5 }
-            45 => { EnPassant::F5 }
-            46 => { EnPassant::G5 }
-            47 => { EnPassant::H5 }
-            _ => { return None; }
-        };
-
-        Some(ret)
-    }
-
-    pub(crate) fn pawn_lost_pos(self) -> usize {
-        if self.location() > 24 {
-            self.location() - 8
-        } else { self.location() + 8 }
-    }
-}
-
-#[repr(u8)]
-#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
-pub(crate) enum Color {
-    White, Black
-}
-
-impl Not for Color {
-    type Output = Color;
-
-    fn not(self) -> Self::Output {
-        match self {
-            Color::White => { Color::Black }
-            Color::Black => { Color::White }
-        }
-    }
-}
-
-#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
-pub(crate) struct Game {
-    pub(crate) board: Board,
-    // clears after every move
-    pub(crate) en_passant: Option<EnPassant>,
-    castle: CastleFlags,
-    pub(crate) turn: Color,
-    // resets on pawn move
-    hm_clock: u8,
-    fm_clock: u16
-}
-
-impl Default for Game {
-    fn default() -> Self {
-        Game {
-            board: Board::default(),
-            en_passant: None,
-            castle: CastleFlags::ALL,
-            turn: Color::White,
-            hm_clock: 0,
-            fm_clock: 1,
-        }
-    }
-}
-
-pub(crate) const PROMOTIONS: [Promotion; 4] = [Promotion::Bishop, Promotion::Rook, Promotion::Knight, Promotion::Queen];
-#[repr(u8)]
-#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
-pub(crate) enum Promotion {
-    Knight, Bishop, Rook, Queen
-}
-
-#[repr(u8)]
-#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
-pub(crate) enum MoveResult {
-    Valid,
-    Check,
-    Checkmate,
-    Stalemate,
-    Draw,
-    MissingPromotion,
-    Illegal,
-    Impossible,
-}
-
-impl MoveResult {
-    pub(crate) fn is_ok(self) -> bool {
-        match self {
-            MoveResult::Valid => { true }
-            MoveResult::Check => { true }
-            MoveResult::Checkmate => { true }
-            MoveResult::Stalemate => { true }
-            MoveResult::Draw => { true }
-            MoveResult::MissingPromotion => { false }
-            MoveResult::Illegal => { false }
-            MoveResult::Impossible => { false }
-        }
-    }
-}
-
-impl Game {
-    // creates fen representation of game
-    pub(crate) fn as_fen(&self) -> String {
-        let mut fen = self.board.into_fen_board();
-
-        fen.push(' ');
-        match self.turn {
-            Color::White => { fen.push('w'); }
-            Color::Black => { fen.push('b'); }
-        }
-
-        fen.push(' ');
-        if self.castle & CastleFlags::WK == CastleFlags::WK { fen.push('K') }
-        if self.castle & CastleFlags::WQ == CastleFlags::WQ { fen.push('Q') }
-        if self.castle & CastleFlags::BK == CastleFlags::BK { fen.push('k') }
-        if self.castle & CastleFlags::BQ == CastleFlags::BQ { fen.push('q') }
-
-        if self.castle == CastleFlags::NONE { fen.push('-') }
-
-        fen.push(' ');
-        if let Some(en_passant) = self.en_passant {
-            let y = char::from((en_passant.location() / 8 + '1' as usize) as u8);
-            let x = char::from((en_passant.location() % 8 + 'a' as usize) as u8);
-
-            fen.push(x);
-            fen.push(y);
-        } else {
-            fen.push('-');
-        }
-
-        fen.push(' ');
-        fen.push_str(&self.hm_clock.to_string());
-        fen.push(' ');
-        fen.push_str(&self.fm_clock.to_string());
-
-        fen
-    }
-
-    pub(crate) fn from_fen(fen: impl AsRef<str>) -> Option<Self> {
-        let mut parts = fen.as_ref().split(' ');
-
-        let board = parts.next()?;
-        let turn = parts.next()?;
-        let castle = parts.next()?;
-        let en_passant = parts.next()?;
-        let hm = parts.next().unwrap_or("0");
-        let fm = parts.next().unwrap_or("1");
-
-        let mut cle = CastleFlags::NONE;
-        for i in castle.chars() {
-            match i {
-                'K' => { cle |= CastleFlags::WK; }
-                'Q' => { cle |= CastleFlags::WQ; }
-                'k' => { cle |= CastleFlags::BK; }
-                'q' => { cle |= CastleFlags::BQ; }
-                '-' => { break }
-                _ => {}
-            }
-        }
-
-        let en_p = if en_passant == "-" { None } else {
-            let mut iter = en_passant.chars();
-
-            let x = iter.next()? as usize - 'a' as usize;
-            let y = (iter.next()? as usize - '1' as usize) * 8;
-
-            EnPassant::from_take_location(y + x)
-        };
-
-        Some(Self {
-            board: Board::from_fen_board(board)?,
-            en_passant: en_p,
-            castle: cle,
-            turn: if turn == "w" { Color::White } else { Color::Black },
-            hm_clock: hm.parse().ok()?,
-            fm_clock: fm.parse().ok()?
-        })
-    }
-
-    pub(crate) fn find_king(&self, player: Color) -> Option<usize> {
-        for (p, pi) in self.board.0.iter().copied().enumerate() {
-            let Some(piece) = pi else { continue; };
-
-            if piece.color() == player && (piece == Piece::WKing || piece == Piece::BKing) {
-                return Some(p);
-            }
-        }
-        None
-    }
-
-    pub(crate) fn is_in_check(&self, player: Color) -> bool {
-        // check test
-        // Use is_legal_checkless to see if player can check another, as you don't actually take in a check,
-        // just threaten to do so, so pins don't matter
-        // both players can't be in check, so we assume the opponent of the 'player' is not in check
-
-        let mut kpos = self.find_king(player).unwrap();
-        let mut game = *self;
-        game.turn = !player;
-
-        let mut in_check = false;
-        for (pos, piece) in self.board.0.iter().copied().enumerate() {
-            let Some(piece) = piece else { continue; };
-
-            if piece.color() != player && piece != Piece::WKing && piece != Piece::BKing {
-                // promotion just in case check is from pawn about to promote
-                let res = game.is_legal_checkless(pos, kpos, Some(Promotion::Queen), false);
-
-                if res == MoveResult::Valid {
-                    in_check = true;
-                    break;
-                }
-            }
-        }
-
-        in_check
-    }
-
-    pub(crate) fn is_in_checkmate(&self, player: Color) -> bool {
-        // checkmate test
-        // both players can't be in check, so we assume the opponent of the 'player' is not in check
-        // we also assume it is 'player' turn, as it cannot be the opponents turn while player is in check
-        // similar to check test!
-
-        let mut kpos = self.find_king(player).unwrap();
-        let mut game = *self;
-        game.turn = !player;
-
-        let mut threat_squares = HashSet::new();
-        let mut block_pos = Vec::new();
-
-        for (pos, piece) in self.board.0.iter().copied().enumerate() {
-            let Some(piece) = piece else { continue; };
-
-            if piece.color() != player && piece != Piece::WKing && piece != Piece::BKing {
-                // promotion just in case check is from pawn about to promote
-                let res = game.is_legal_checkless(pos, kpos, Some(Promotion::Queen), false);
-
-                if res == MoveResult::Valid {
-                    match piece {
-                        Piece::WPawn | Piece::WKnight | Piece::BPawn | Piece::BKnight => {
-                            // only threat square is the one the piece is on
-                            threat_squares.insert(pos);
-                        }
-                        Piece::WBishop | Piece::WRook | Piece::WQueen | Piece::BBishop | Piece::BRook | Piece::BQueen => {
-                            let (mut ox, mut oy) = ((pos % 8) as isize, (pos / 8) as isize);
-                            let (nx, ny) = ((kpos % 8) as isize, (kpos / 8) as isize);
-
-                            let rx = (nx - ox).signum();
-                            let ry = (ny - oy).signum();
-
-                            // no path tracing bounds checks as those were already done in the is_legal_checkless method
-                            while ox != nx && oy != ny {
-                                threat_squares.insert((oy * 8 + ox) as usize);
-
-                                ox += rx;
-                                oy += ry;
-                            }
-                        }
-                        _ => { }
-                    }
-                }
-            }  else if piece.color() == player && piece != Piece::WKing && piece != Piece::BKing {
-                block_pos.push(pos);
-            }
-        }
-
-        let mut escapable = false;
-        game.turn = player;
-
-        let legal_move_wcheck = |from: usize, to: usize| -> bool {
-            let legal = game.is_legal_checkless(from, to, Some(Promotion::Queen), false) == MoveResult::Valid;
-
-            if legal {
-                let mut n_board = game;
-                n_board.move_unchecked(from, to, Some(Promotion::Queen));
-
-                // cannot play a move which puts self in check (or a move which keeps self in check)
-                return !n_board.is_in_check(game.turn);
-            }
-
-            legal
-        };
-
-        // try all king moves
-        // straight king moves
-        escapable |= legal_move_wcheck(kpos, kpos.saturating_add(1));
-        escapable |= legal_move_wcheck(kpos, kpos.saturating_sub(1));
-        escapable |= legal_move_wcheck(kpos, kpos.saturating_add(8));
-        escapable |= legal_move_wcheck(kpos, kpos.saturating_sub(8));
-
-        // diagonal king moves
-        escapable |= legal_move_wcheck(kpos, kpos.saturating_sub(9));
-        escapable |= legal_move_wcheck(kpos, kpos.saturating_sub(7));
-        escapable |= legal_move_wcheck(kpos, kpos.saturating_add(9));
-        escapable |= legal_move_wcheck(kpos, kpos.saturating_add(7));
-
-        // try en passant!!
-        if let Some(en_passant) = game.en_passant {
-            threat_squares.insert(en_passant.location());
-        }
-
-        // try blocking all checks
-        for spos in block_pos {
-            for ts in &threat_squares {
-                escapable |= legal_move_wcheck(spos, *ts);
-
-                if escapable {
-                    break;
-                }
-            }
-        }
-
-        !escapable
-    }
-
-    pub(crate) fn is_draw(&self) -> bool {
-        if self.hm_clock == 100 { return true; }
-
-        let pieces: Vec<(usize, Piece)> = self.board.0.iter().enumerate()
-            .filter_map(|x| {
-                if x.1.is_some() { Some((x.0, x.1.unwrap())) }
-                else { None }
-            }).collect();
-
-
-        if pieces.len() == 2 {
-            return true;
-        } else if pieces.len() == 3 {
-            // gets piece which isnt a king
-            let (_, nk) = *pieces.iter().find(|x| x.1 != Piece::BKing && x.1 != Piece::WKing).unwrap();
-
-            if nk == Piece::WKnight || nk == Piece::WBishop || nk == Piece::BKnight || nk == Piece::BBishop {
-                return true;
-            }
-        } else if pieces.len() == 4 {
-            // gets last 2 pieces which arent kings
-            let nk: Vec<(usize, Piece)> = pieces.iter().filter(|x| x.1 != Piece::BKing && x.1 != Piece::WKing).copied().collect();
-
-            if (nk[0].1 == Piece::BBishop || nk[0].1 == Piece::WBishop) && (nk[1].1 == Piece::BBishop || nk[1].1 == Piece::WBishop) &&
-                (nk[0].1.color() != nk[1].1.color()) && (nk[0].0 % 2 == nk[1].0 % 2) {
-
-                return true;
-            }
-        }
-
-        false
-    }
-
-    pub(crate) fn is_stalemate(&self) -> bool {
-        if self.is_in_check(self.turn) { return false; }
-
-        for (pos, piece) in self.board.0.iter().copied().enumerate() {
-            let Some(piece) = piece else { continue; };
-
-            if piece.color() == self.turn {
-                // not in stalemate or check, valid move!
-                if !self.all_legal_moves(pos).is_empty() { return false; }
-            }
-        }
-
-        // No legal moves
-        true
-    }
-    
-    pub(crate) fn all_legal_moves(&self, loc: usize) -> Vec<usize> {
-        let Some(piece) = self.board[loc] else {
-            return Vec::new();
-        };
-
-        if piece.color() != self.turn { return Vec::new(); }
-
-        let legal_move = |to: usize| -> bool {
-            let legal = self.is_legal_checkless(loc, to, Some(Promotion::Queen), false) == MoveResult::Valid;
-
-            if legal {
-                let mut n_board = *self;
-                n_board.move_unchecked(loc, to, Some(Promotion::Queen));
-
-                // cannot play a move which puts self in check (or a move which keeps self in check)
-                return !n_board.is_in_check(self.turn);
-            }
-
-            legal
-        };
-
-        let mut list = Vec::new();
-
-        let mut test_move = |to: isize| -> bool {
-            if to < 0 { return false; }
-            if legal_move(to as usize) { list.push(to as usize); return true; }
-            false
-        };
-
-        let loc = loc as isize;
-        match piece {
-            // try move twice, move once, take, and en passant (regular taking moves check for en passant!)
-            Piece::WPawn  => {
-                test_move(loc + 8);
-                test_move(loc + 16);
-                test_move(loc + 7);
-                test_move(loc + 9);
-            }
-            Piece::BPawn => {
-                test_move(loc - 8);
-                test_move(loc - 16);
-                test_move(loc - 7);
-                test_move(loc - 9);
-            }
-            // try all knight moves
-            Piece::WKnight | Piece::BKnight => {
-                test_move(loc + 15);
-                test_move(loc + 17);
-
-                test_move(loc - 15);
-                test_move(loc - 17);
-
-                test_move(loc + 10);
-                test_move(loc - 6);
-
-                test_move(loc - 10);
-                test_move(loc + 6);
-            }
-            // try all bishop moves
-            Piece::WBishop | Piece::BBishop => {
-                let mut rx = 1;
-                let mut ry = 1;
-
-                for i in 0..4 {
-                    if i == 1 { rx = -1; }
-                    if i == 2 { ry = -1; }
-                    if i == 3 { rx = 1; }
-
-                    let (mut lx, mut ly) = (loc % 8, loc / 8);
-                    lx += rx;
-                    ly += ry;
-
-                    while test_move(ly * 8 + lx) {
-                        lx += rx;
-                        ly += ry;
-                    }
-                }
-            }
-            // try all rook moves
-            Piece::WRook | Piece::BRook => {
-                let mut rx = 1;
-                let mut ry = 0;
-
-                for i in 0..4 {
-                    if i == 1 { rx = -1; }
-                    if i == 2 { rx = 0; ry = 1; }
-                    if i == 3 { ry = -1; }
-
-                    let (mut lx, mut ly) = (loc % 8, loc / 8);
-                    lx += rx;
-                    ly += ry;
-
-                    while test_move(ly * 8 + lx) {
-                        lx += rx;
-                        ly += ry;
-                    }
-                }
-            }
-            // try all rook and bishop moves
-            Piece::WQueen | Piece::BQueen => {
-                let mut rx = 1;
-                let mut ry = 1;
-
-                for i in 0..8 {
-                    if i == 1 { rx = -1; }
-                    if i == 2 { ry = -1; }
-                    if i == 3 { rx = 1; }
-                    if i == 4 { ry = 0; }
-                    if i == 5 { rx = -1; }
-                    if i == 6 { rx = 0; ry = 1; }
-                    if i == 7 { ry = -1; }
-
-                    let (mut lx, mut ly) = (loc % 8, loc / 8);
-                    lx += rx;
-                    ly += ry;
-
-                    while test_move(ly * 8 + lx) {
-                        lx += rx;
-                        ly += ry;
-                    }
-                }
-            }
-            // castle + king moves
-            Piece::WKing | Piece::BKing => {
-                test_move(loc + 1);
-                test_move(loc - 1);
-                test_move(loc + 8);
-                test_move(loc - 8);
-
-                test_move(loc + 7);
-                test_move(loc + 9);
-                test_move(loc - 7);
-                test_move(loc - 9);
-
-                // castling
-                test_move(loc - 2);
-                test_move(loc + 2);
-            }
-        }
-
-        list
-    }
-
-    // validates a moves legality (does not factor in checks/pins)
-    // NOTE: checkless validation (except castling, which validates no checks in path)
-    fn is_legal_checkless(&self, from: usize, to: usize, promotion: Option<Promotion>, king_check: bool) -> MoveResult {
-        // move must be in the board
-        if from > 63 || to > 63 {
-            return MoveResult::Impossible;
-        }
-
-        let Some(piece) = self.board[from] else {
-            // can't move a piece that isn't there ??
-            return MoveResult::Impossible;
-        };
-
-        // Must move your own pieces
-        if piece.color() != self.turn { return MoveResult::Impossible; }
-
-        let (ox, oy) = ((from % 8) as isize, (from / 8) as isize);
-        let (nx, ny) = ((to % 8) as isize, (to / 8) as isize);
-
-        // make sure move does not take own piece (or enemy king (checkmate?))
-        if let Some(piece) = self.board[to] {
-            if piece.color() == self.turn || (king_check && piece == Piece::BKing) {
-                return MoveResult::Illegal;
-            }
-        }
-
-        // check if movement pattern is valid for piece
-        if piece == Piece::BPawn || piece == Piece::WPawn {
-            let rx = (nx - ox).abs();
-            let ry = (ny - oy).abs();
-
-            let take = rx == 1 && ry == 1 && self.board[to].is_some();
-            let en_passant = self.en_passant.map(|x| x.location() == to).unwrap_or(false) && rx == 1 && ry == 1;
-            let regular = ry == 1 && rx == 0 && self.board[to].is_none();
-
-            let occupied = self.board[((oy + (ny - oy).signum()) * 8 + ox) as usize].is_some() || self.board[to].is_some();
-            let first = ry == 2 && rx == 0 && ((piece == Piece::BPawn && oy == 6)  || (piece == Piece::WPawn && oy == 1)) && !occupied;
-
-            let dir = (ny - oy).is_positive() ^ (piece == Piece::BPawn);
-
-            if !(take || en_passant || regular || first) || !dir {
-                return MoveResult::Illegal;
-            }
-        } else if (piece == Piece::BKing || piece == Piece::WKing) && (nx - ox).abs() == 2 && ny == oy {
-            if self.is_in_check(self.turn) { return MoveResult::Illegal; }
-            // Determine which side we are castling
-            let mut game = *self;
-            match (piece, nx - ox) {
-                // black king-side
-                (Piece::BKing, 2) => {
-                    if self.castle & CastleFlags::BK == CastleFlags::NONE { return MoveResult::Illegal; }
-                    if self.board[61].is_some() || self.board[62].is_some() { return MoveResult::Illegal; }
-
-                    game.move_unchecked(60, 61, None);
-                    if game.is_in_check(self.turn) { return MoveResult::Illegal; }
-                }
-                // black queen-side
-                (Piece::BKing, -2) => {
-                    if self.castle & CastleFlags::BQ == CastleFlags::NONE { return MoveResult::Illegal; }
-                    if self.board[57].is_some() || self.board[58].is_some() || self.board[59].is_some() { return MoveResult::Illegal; }
-
-                    game.move_unchecked(60, 59, None);
-                    if game.is_in_check(self.turn) { return MoveResult::Illegal; }
-                }
-                // white king-side
-                (Piece::WKing, 2) => {
-                    if self.castle & CastleFlags::WK == CastleFlags::NONE { return MoveResult::Illegal; }
-                    if self.board[5].is_some() || self.board[6].is_some() { return MoveResult::Illegal; }
-
-                    game.move_unchecked(4, 5, None);
-                    if game.is_in_check(self.turn) { return MoveResult::Illegal; }
-                }
-                // white queen-side
-                (Piece::WKing, -2) => {
-                    if self.castle & CastleFlags::WQ == CastleFlags::NONE { return MoveResult::Illegal; }
-                    if self.board[1].is_some() || self.board[2].is_some() || self.board[3].is_some(){ return MoveResult::Illegal; }
-
-                    game.move_unchecked(4, 3, None);
-                    if game.is_in_check(self.turn) { return MoveResult::Illegal; }
-                }
-
-                _ => { return MoveResult::Illegal; }
-            }
-            return MoveResult::Valid;
-        } else if !piece.can_move(nx - ox, ny - oy) {
-            return MoveResult::Illegal;
-        }
-
-        // path trace queen, bishop, and rook moves
-        // if any piece is in the way, the move is invalid (castles are king moves)
-        if piece == Piece::BRook || piece == Piece::WRook || piece == Piece::BBishop || piece == Piece::WBishop || piece == Piece::BQueen || piece == Piece::WQueen  {
-            let rx = (nx - ox).signum();
-            let ry = (ny - oy).signum();
-
-            let mut ocx = ox + rx;
-            let mut ocy = oy + ry;
-
-            while ocx != nx || ocy != ny {
-                if !(0..=7).contains(&ocy) || !(0..=7).contains(&ocx) { return MoveResult::Illegal; }
-                if self.board[(ocy * 8 + ocx) as usize].is_some() { return MoveResult::Illegal; }
-
-                ocx += rx;
-                ocy += ry;
-            }
-        }
-
-        // if double pawn movement, make sure it is the first pawn move (can't en passant)
-        if (ny - oy).abs() == 2 && ((piece == Piece::BPawn && oy != 6) || (piece == Piece::WPawn && oy != 1)) {
-            return MoveResult::Illegal;
-        }
-
-        // make sure pawn doesn't move to last (0 or 7) rank without promoting (can't en passant)
-        if ((piece == Piece::BPawn && ny == 0) || (piece == Piece::WPawn && ny == 7)) && promotion.is_none()  {
-            return MoveResult::MissingPromotion;
-        }
-
-        MoveResult::Valid
-    }
-
-    pub(crate) fn is_legal_move(&self, from: usize, to: usize, promotion: Option<Promotion>) -> MoveResult {
-        let res = self.is_legal_checkless(from, to, promotion, true);
-        if res != MoveResult::Valid { return res; }
-
-        // Any move at this point is valid (omitting check)
-        let mut n_board = *self;
-        n_board.move_unchecked(from, to, promotion);
-
-        // cannot play a move which puts self in check (or a move which keeps self in check)
-        if n_board.is_in_check(self.turn) {
-            return MoveResult::Illegal;
-        }
-
-        // Last 4 move types
-        // 1) Draw - Analyze material on n_board,
-        // if material is king v king, king & bishop v king, king & knight v king,
-        // king and bishop vs king and bishop (same color bishops)
-        // or if 50 move rule is done (100 moves on halfmove clock)
-        if n_board.is_draw() {
-            return MoveResult::Draw;
-        }
-
-        // 2) Stalemate, use move_gen on every piece, generating all legal moves,
-        // if no legal moves are possible and not in check, stalemate
-        if !n_board.is_in_check(!self.turn) {
-            if n_board.is_stalemate() {
-                return MoveResult::Stalemate;
-            }
-
-            MoveResult::Valid
-        } else {
-            // 3) Checkmate
-            // Check if game is over for opponent
-            if n_board.is_in_checkmate(!self.turn) {
-                return MoveResult::Checkmate;
-            }
-
-            // 4) Check
-            // Opponent is in check
-            MoveResult::Check
-        }
-    }
-
-    pub(crate) fn move_checked(&mut self, from: usize, to: usize, promotion: Option<Promotion>) -> MoveResult {
-        let res = self.is_legal_move(from, to, promotion);
-
-        if res == MoveResult::Illegal || res == MoveResult::Impossible || res == MoveResult::MissingPromotion { return res; }
-        self.move_unchecked(from, to, promotion);
-
-        res
-    }
-
-    // WARNING: does not check for legality of move
-    // returns false if piece did not exist
-    // NOTE: this method updates en passant, castling,
-    // clocks, turns, and promotions, also verifies promotions (pawn and last ranks)
-    fn move_unchecked(&mut self, from: usize, to: usize, promotion: Option<Promotion>) -> bool {
-        let Some(piece) = self.board[from] else { return false; };
-
-        if self.turn == Color::Black { self.fm_clock += 1; }
-
-        // check for en passant? both offering and taking
-        if piece == Piece::BPawn || piece == Piece::WPawn {
-            if let Some(en_p) = self.en_passant {
-                if en_p.location() == to {
-                    self.board[en_p.pawn_lost_pos()] = None;
-                }
-            }
-
-            let offering = (to as isize - from as isize).abs() == 16 && EnPassant::from_pawn_location(from).is_some();
-            if offering { self.en_passant = EnPassant::from_pawn_location(from); }
-            else { self.en_passant = None; }
-            self.hm_clock = 0;
-        } else {
-            // en passant is only available for one move
-            self.en_passant = None;
-            self.hm_clock += 1;
-        }
-
-        // check for forfeiting castling rights
-        if let Some(piece) = self.board[from] {
-            match piece {
-                Piece::WRook => {
-                    if from == 0 { self.castle -= CastleFlags::WQ; }
-                    else if from == 7 { self.castle -= CastleFlags::WK; }
-                }
-                Piece::WKing => { self.castle -= CastleFlags::W; }
-                Piece::BRook => {
-                    if from == 56 { self.castle -= CastleFlags::BQ; }
-                    else if from == 63 { self.castle -= CastleFlags::BK; }
-                }
-                Piece::BKing => { self.castle -= CastleFlags::B; }
-                _ => { }
-            }
-        }
-
-        // taking a rook also takes castling rights
-        if self.board[to].some_and(|x| *x == Piece::BRook || *x == Piece::WRook) {
-            if to == 0 { self.castle -= CastleFlags::WQ; }
-            else if to == 7 { self.castle -= CastleFlags::WK; }
-            else if to == 56 { self.castle -= CastleFlags::BQ; }
-            else if to == 63 { self.castle -= CastleFlags::BK; }
-        }
-
-        if self.board[to].is_some() { self.hm_clock = 0; }
-
-        #[allow(clippy::unnecessary_unwrap)]
-        if (piece == Piece::BPawn || piece == Piece::WPawn) && promotion.is_some() && (to >= 56 || to <= 7) {
-            self.board[to] = Some(Piece::from_promotion(promotion.unwrap(), self.turn));
-        } else if (piece == Piece::WKing || piece == Piece::BKing) && (to % 8).abs_diff(from % 8) == 2 {
-            let (rook_from, rook_to) = if to % 8 > from % 8 {
-                (from + 3, to - 1)
-            } else {
-                (from - 4, to + 1)
-            };
-
-            self.board[to] = self.board[from];
-            self.board[rook_to] = self.board[rook_from];
-
-            self.board[rook_from] = None;
-
-        } else {
-            self.board[to] = self.board[from];
-        }
-
-        self.board[from] = None;
-        self.turn = !self.turn;
-
-        true
-    }
-}
-
-pub(crate) trait IsSomeAnd {
-    type Item;
-
-    fn some_and(&self, f: impl FnOnce(&Self::Item) -> bool) -> bool;
-}
-
-impl<T> IsSomeAnd for Option<T> {
-    type Item = T;
-
-    fn some_and(&self, f: impl FnOnce(&T) -> bool) -> bool {
-        match self {
-            None => false,
-            Some(x) => f(x),
-        }
-    }
-}
+use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::ops::{Index, IndexMut, Not};
+use bitflags::bitflags;
+use crate::bitboard::{between, bishop_attacks, king_attacks, knight_attacks, pawn_attacks, piece_attacks, ray, rook_attacks, Bitboard, BISHOP_DIRECTIONS, ROOK_DIRECTIONS};
+use crate::error::{FenError, FenErrorReason, IllegalMove, IllegalReason, PositionProblem};
+use crate::square::{Rank, Square};
+use crate::zobrist::{BLACK_TO_MOVE, CASTLING, EN_PASSANT_FILE, PIECE_SQUARE};
+
+bitflags! {
+    #[repr(transparent)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    struct CastleFlags: u8 {
+        const NONE = 0;
+
+        const WK = 1 << 0;
+        const WQ = 1 << 1;
+        const BK = 1 << 2;
+        const BQ = 1 << 3;
+
+        const W = Self::WK.bits() | Self::WQ.bits();
+        const B = Self::BK.bits() | Self::BQ.bits();
+
+        const ALL = Self::W.bits() | Self::B.bits();
+    }
+}
+
+impl Default for CastleFlags {
+    fn default() -> Self {
+        CastleFlags::ALL
+    }
+}
+
+/// A colored chess piece.
+#[repr(u8)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Piece {
+    WPawn,
+    WKnight,
+    WBishop,
+    WRook,
+    WQueen,
+    WKing,
+    BPawn,
+    BKnight,
+    BBishop,
+    BRook,
+    BQueen,
+    BKing
+}
+
+impl Piece {
+    // assuming in bounds
+    // Note: does not check pawn movement, as pawn movement is far too complex
+    // Note: does not check castling, as castling is also far too complex
+    fn can_move(&self, relative_x: isize, relative_y: isize) -> bool {
+        match self {
+            Piece::WPawn | Piece::BPawn => { true }
+            Piece::WKnight | Piece::BKnight => { (relative_x.abs() == 2 && relative_y.abs() == 1) || (relative_y.abs() == 2 && relative_x.abs() == 1) }
+            Piece::WBishop | Piece::BBishop => { relative_x.abs() == relative_y.abs() }
+            Piece::WRook | Piece::BRook => { (relative_x == 0 && relative_y != 0) || (relative_x != 0 && relative_y == 0) }
+            Piece::WKing | Piece::BKing => { relative_x.abs() <= 1 && relative_y.abs() <= 1 }
+            Piece::WQueen | Piece::BQueen => {
+                (relative_x == 0 && relative_y != 0) || (relative_x != 0 && relative_y == 0) || relative_x.abs() == relative_y.abs()
+            }
+        }
+    }
+
+    /// The side this piece belongs to.
+    pub fn color(&self) -> Color {
+        match self {
+            Piece::WPawn | Piece::WKnight | Piece::WBishop | Piece::WRook | Piece::WQueen | Piece::WKing => {
+                Color::White
+            }
+            Piece::BPawn | Piece::BKnight | Piece::BBishop | Piece::BRook | Piece::BQueen | Piece::BKing => {
+                Color::Black
+            }
+        }
+    }
+
+    // centipawns for exchanges, the king is worth more than anything it could take
+    fn exchange_value(self) -> i32 {
+        if self == Piece::WKing || self == Piece::BKing { 20_000 } else { self.value() as i32 * 100 }
+    }
+
+    /// The piece a pawn of `color` becomes when promoting to `prm`.
+    pub fn from_promotion(prm: Promotion, color: Color) -> Piece {
+        match (prm, color) {
+            (Promotion::Knight, Color::White) => { Piece::WKnight }
+            (Promotion::Bishop, Color::White) => { Piece::WBishop }
+            (Promotion::Rook, Color::White) => { Piece::WRook }
+            (Promotion::Queen, Color::White) => { Piece::WQueen }
+            (Promotion::Knight, Color::Black) => { Piece::BKnight }
+            (Promotion::Bishop, Color::Black) => { Piece::BBishop }
+            (Promotion::Rook, Color::Black) => { Piece::BRook }
+            (Promotion::Queen, Color::Black) => { Piece::BQueen }
+        }
+    }
+
+    /// The usual material points: pawn 1, knight and bishop 3, rook 5, queen 9, king 0.
+    pub fn value(&self) -> u32 {
+        match self {
+            Piece::WPawn | Piece::BPawn => { 1 }
+            Piece::WKnight | Piece::BKnight | Piece::WBishop | Piece::BBishop => { 3 }
+            Piece::WRook | Piece::BRook => { 5 }
+            Piece::WQueen | Piece::BQueen => { 9 }
+            Piece::WKing | Piece::BKing => { 0 }
+        }
+    }
+
+    fn from_letter(letter: char) -> Option<Piece> {
+        let piece = match letter {
+            'p' => { Piece::BPawn }
+            'n' => { Piece::BKnight }
+            'b' => { Piece::BBishop }
+            'r' => { Piece::BRook }
+            'q' => { Piece::BQueen }
+            'k' => { Piece::BKing }
+
+            'P' => { Piece::WPawn }
+            'N' => { Piece::WKnight }
+            'B' => { Piece::WBishop }
+            'R' => { Piece::WRook }
+            'Q' => { Piece::WQueen }
+            'K' => { Piece::WKing }
+            _ => { return None; }
+        };
+
+        Some(piece)
+    }
+
+    fn to_letter(self) -> char {
+        match self {
+            Piece::BPawn => { 'p' }
+            Piece::BKnight => { 'n' }
+            Piece::BBishop => { 'b' }
+            Piece::BRook => { 'r' }
+            Piece::BQueen => { 'q' }
+            Piece::BKing => { 'k' }
+
+            Piece::WPawn => { 'P' }
+            Piece::WKnight => { 'N' }
+            Piece::WBishop => { 'B' }
+            Piece::WRook => { 'R' }
+            Piece::WQueen => { 'Q' }
+            Piece::WKing => { 'K' }
+        }
+    }
+}
+
+/// The 64 squares of a chess board, indexed from a1 (0) to h8 (63) rank by rank.
+#[repr(transparent)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Board([Option<Piece>; 64]);
+
+impl Default for Board {
+    fn default() -> Self {
+        // keep in mind, this is upside down
+        // or just use the fen
+        Board([
+            Some(Piece::WRook), Some(Piece::WKnight), Some(Piece::WBishop),
+            Some(Piece::WQueen), Some(Piece::WKing), Some(Piece::WBishop), Some(Piece::WKnight), Some(Piece::WRook),
+
+            Some(Piece::WPawn), Some(Piece::WPawn), Some(Piece::WPawn), Some(Piece::WPawn),
+            Some(Piece::WPawn), Some(Piece::WPawn), Some(Piece::WPawn), Some(Piece::WPawn),
+
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+            None, None, None, None, None, None, None, None,
+
+            Some(Piece::BPawn), Some(Piece::BPawn), Some(Piece::BPawn), Some(Piece::BPawn),
+            Some(Piece::BPawn), Some(Piece::BPawn), Some(Piece::BPawn), Some(Piece::BPawn),
+
+            Some(Piece::BRook), Some(Piece::BKnight), Some(Piece::BBishop),
+            Some(Piece::BQueen), Some(Piece::BKing), Some(Piece::BBishop), Some(Piece::BKnight), Some(Piece::BRook)
+        ])
+    }
+}
+
+impl Board {
+    // offset is the position of the board field in the whole fen, for error reporting
+    fn from_fen_board(fen_board: &str, offset: usize) -> Result<Board, FenError> {
+        let mut rows = Vec::new();
+        let mut row_offset = offset;
+        for row in fen_board.split('/') {
+            rows.push((row_offset, row));
+            row_offset += row.len() + 1;
+        }
+
+        let mut vec = Vec::new();
+        for (row_offset, row) in rows.into_iter().rev() {
+            for (j, char) in row.char_indices() {
+                if char.is_ascii_digit() {
+                    for _ in 0..char as u8 - b'0' { vec.push(None); }
+                } else {
+                    let piece = Piece::from_letter(char)
+                        .ok_or(FenError::new(row_offset + j, FenErrorReason::InvalidPiece(char)))?;
+                    vec.push(Some(piece));
+                }
+            }
+        }
+
+        let len = vec.len();
+        let b: [Option<Piece>; 64] = vec.try_into()
+            .map_err(|_| FenError::new(offset, FenErrorReason::WrongSquareCount(len)))?;
+        Ok(Board(b))
+    }
+
+    fn into_fen_board(self) -> String {
+        let mut str = String::new();
+
+        for y in (0..8).rev() {
+            let ym = y * 8;
+            let mut none_inr = 0;
+
+            for x in 0..8 {
+                if let Some(piece) = self[ym + x] {
+                    if none_inr != 0 { str.push(char::from(none_inr as u8 + b'0')); }
+                    str.push(piece.to_letter());
+
+                    none_inr = 0;
+                } else {
+                    none_inr += 1;
+                }
+            }
+
+            if none_inr != 0 { str.push(char::from(none_inr as u8 + b'0')); }
+            if y != 0 { str.push('/') }
+        }
+
+        str
+    }
+}
+
+// serialized as the board field of a fen, serde can't handle arrays this large
+#[cfg(feature = "serde")]
+impl serde::Serialize for Board {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.into_fen_board())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Board {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let fen_board = String::deserialize(deserializer)?;
+        Board::from_fen_board(&fen_board, 0).map_err(serde::de::Error::custom)
+    }
+}
+
+impl Index<usize> for Board {
+    type Output = Option<Piece>;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.0[index]
+    }
+}
+
+impl Index<Square> for Board {
+    type Output = Option<Piece>;
+
+    fn index(&self, index: Square) -> &Self::Output {
+        &self.0[index.index()]
+    }
+}
+
+impl IndexMut<Square> for Board {
+    fn index_mut(&mut self, index: Square) -> &mut Self::Output {
+        &mut self.0[index.index()]
+    }
+}
+
+impl IndexMut<usize> for Board {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.0[index]
+    }
+}
+
+/// One of the two sides.
+#[repr(u8)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Color {
+    White, Black
+}
+
+impl Not for Color {
+    type Output = Color;
+
+    fn not(self) -> Self::Output {
+        match self {
+            Color::White => { Color::Black }
+            Color::Black => { Color::White }
+        }
+    }
+}
+
+/// A full chess position: the board, side to move, castling rights, en passant and clocks.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Game {
+    pub board: Board,
+    // clears after every move
+    pub en_passant: Option<Square>,
+    castle: CastleFlags,
+    pub turn: Color,
+    // resets on pawn move
+    hm_clock: u8,
+    fm_clock: u16
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Game {
+            board: Board::default(),
+            en_passant: None,
+            castle: CastleFlags::ALL,
+            turn: Color::White,
+            hm_clock: 0,
+            fm_clock: 1,
+        }
+    }
+}
+
+/// Promotions in the order they are offered by the promotion picker.
+pub const PROMOTIONS: [Promotion; 4] = [Promotion::Bishop, Promotion::Rook, Promotion::Knight, Promotion::Queen];
+/// The piece a pawn can promote to.
+#[repr(u8)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Promotion {
+    Knight, Bishop, Rook, Queen
+}
+
+bitflags! {
+    /// What kind of move a [`Move`] is, filled in by [`Game::generate_legal_moves`] and [`Game::move_flags`].
+    #[repr(transparent)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct MoveFlags: u8 {
+        const CAPTURE = 1 << 0;
+        const EN_PASSANT = 1 << 1;
+        const CASTLE = 1 << 2;
+        const DOUBLE_PUSH = 1 << 3;
+        const PROMOTION = 1 << 4;
+    }
+}
+
+/// A move from one square to another, with the piece to promote to if a pawn reaches the last rank.
+/// Displays in long algebraic notation, like e2e4 or e7e8q.
+///
+/// The flags follow from the position, so moves compare and hash by their squares and promotion only.
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Move {
+    pub from: Square,
+    pub to: Square,
+    pub promotion: Option<Promotion>,
+    pub flags: MoveFlags,
+}
+
+impl Move {
+    pub fn new(from: impl Into<Square>, to: impl Into<Square>, promotion: Option<Promotion>) -> Self {
+        Move { from: from.into(), to: to.into(), promotion, flags: MoveFlags::empty() }
+    }
+
+    pub fn with_promotion(mut self, promotion: Promotion) -> Self {
+        self.promotion = Some(promotion);
+        self
+    }
+
+    pub fn with_flags(mut self, flags: MoveFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    pub fn is_capture(self) -> bool {
+        self.flags.contains(MoveFlags::CAPTURE)
+    }
+
+    /// Standard algebraic notation for the move, which must be legal in `game`.
+    pub fn san(self, game: &Game) -> String {
+        crate::pgn::san(game, self)
+    }
+}
+
+impl PartialEq for Move {
+    fn eq(&self, other: &Self) -> bool {
+        (self.from, self.to, self.promotion) == (other.from, other.to, other.promotion)
+    }
+}
+
+impl Eq for Move {}
+
+impl Hash for Move {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self.from, self.to, self.promotion).hash(state);
+    }
+}
+
+impl Display for Move {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.from, self.to)?;
+
+        match self.promotion {
+            Some(Promotion::Queen) => { write!(f, "q") }
+            Some(Promotion::Knight) => { write!(f, "n") }
+            Some(Promotion::Rook) => { write!(f, "r") }
+            Some(Promotion::Bishop) => { write!(f, "b") }
+            None => { Ok(()) }
+        }
+    }
+}
+
+/// The outcome of validating or playing a move.
+#[repr(u8)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MoveResult {
+    Valid,
+    Check,
+    Checkmate,
+    Stalemate,
+    Draw,
+    MissingPromotion,
+    Illegal,
+    Impossible,
+}
+
+/// Why a position is drawn, stalemate aside.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DrawReason {
+    FiftyMoveRule,
+    InsufficientMaterial,
+    /// Mate is still on the board, but locked pawns keep anything from breaking through.
+    DeadPosition,
+}
+
+/// How many of each piece one side has, kings left out.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MaterialCount {
+    pub pawns: u32,
+    pub knights: u32,
+    pub bishops: u32,
+    pub rooks: u32,
+    pub queens: u32,
+}
+
+impl MaterialCount {
+    /// The material in points, see [`Piece::value`].
+    pub fn points(&self) -> u32 {
+        self.pawns + (self.knights + self.bishops) * 3 + self.rooks * 5 + self.queens * 9
+    }
+
+    /// Knights and bishops.
+    pub fn minors(&self) -> u32 {
+        self.knights + self.bishops
+    }
+}
+
+impl MoveResult {
+    /// Whether the move was (or would be) played.
+    pub fn is_ok(self) -> bool {
+        match self {
+            MoveResult::Valid => { true }
+            MoveResult::Check => { true }
+            MoveResult::Checkmate => { true }
+            MoveResult::Stalemate => { true }
+            MoveResult::Draw => { true }
+            MoveResult::MissingPromotion => { false }
+            MoveResult::Illegal => { false }
+            MoveResult::Impossible => { false }
+        }
+    }
+}
+
+impl Game {
+    /// Creates the FEN representation of the game, which is also valid X-FEN.
+    pub fn as_fen(&self) -> String {
+        self.fen_with_castling(['K', 'Q', 'k', 'q'])
+    }
+
+    /// The position as Shredder-FEN, castling rights are rook files (`HAha`) instead of `KQkq`.
+    pub fn as_shredder_fen(&self) -> String {
+        self.fen_with_castling(['H', 'A', 'h', 'a'])
+    }
+
+    // castling letters in WK, WQ, BK, BQ order
+    fn fen_with_castling(&self, letters: [char; 4]) -> String {
+        let mut fen = self.board.into_fen_board();
+
+        fen.push(' ');
+        match self.turn {
+            Color::White => { fen.push('w'); }
+            Color::Black => { fen.push('b'); }
+        }
+
+        fen.push(' ');
+        if self.castle & CastleFlags::WK == CastleFlags::WK { fen.push(letters[0]) }
+        if self.castle & CastleFlags::WQ == CastleFlags::WQ { fen.push(letters[1]) }
+        if self.castle & CastleFlags::BK == CastleFlags::BK { fen.push(letters[2]) }
+        if self.castle & CastleFlags::BQ == CastleFlags::BQ { fen.push(letters[3]) }
+
+        if self.castle == CastleFlags::NONE { fen.push('-') }
+
+        fen.push(' ');
+        if let Some(en_passant) = self.en_passant {
+            fen.push_str(&en_passant.to_string());
+        } else {
+            fen.push('-');
+        }
+
+        fen.push(' ');
+        fen.push_str(&self.hm_clock.to_string());
+        fen.push(' ');
+        fen.push_str(&self.fm_clock.to_string());
+
+        fen
+    }
+
+    /// Parses a FEN string, the clock fields are optional.
+    /// Castling rights may also be given as X-FEN or Shredder-FEN rook files, as long as the rooks start in the corners.
+    pub fn from_fen(fen: impl AsRef<str>) -> Result<Self, FenError> {
+        let game = Game::parse_fen(fen.as_ref(), &[Rank::new(2), Rank::new(5)])?;
+
+        let problems = game.validate();
+        if !problems.is_empty() { return Err(FenError::new(0, FenErrorReason::IllegalPosition(problems))); }
+
+        Ok(game)
+    }
+
+    // the position of a fen as it is, without validating it. an en passant square must be on one of `en_passant_ranks`
+    pub(crate) fn parse_fen(fen: &str, en_passant_ranks: &[Rank]) -> Result<Self, FenError> {
+        let mut parts = fen.split(' ').scan(0, |offset, part| {
+            let start = *offset;
+            *offset += part.len() + 1;
+            Some((start, part))
+        });
+
+        let missing = |field| FenError::new(fen.len(), FenErrorReason::MissingField(field));
+
+        let (board_pos, board) = parts.next().ok_or(missing("board"))?;
+        let (turn_pos, turn) = parts.next().ok_or(missing("turn"))?;
+        let (castle_pos, castle) = parts.next().ok_or(missing("castling"))?;
+        let (en_passant_pos, en_passant) = parts.next().ok_or(missing("en passant"))?;
+        let (hm_pos, hm) = parts.next().unwrap_or((fen.len(), "0"));
+        let (fm_pos, fm) = parts.next().unwrap_or((fen.len(), "1"));
+
+        let mut cle = CastleFlags::NONE;
+        for (i, c) in castle.char_indices() {
+            match c {
+                'K' | 'H' => { cle |= CastleFlags::WK; }
+                'Q' | 'A' => { cle |= CastleFlags::WQ; }
+                'k' | 'h' => { cle |= CastleFlags::BK; }
+                'q' | 'a' => { cle |= CastleFlags::BQ; }
+                '-' => { break }
+                c => { return Err(FenError::new(castle_pos + i, FenErrorReason::InvalidCastling(c))); }
+            }
+        }
+
+        let en_p = if en_passant == "-" { None } else {
+            // a pawn that just moved two squares passed the third or the sixth rank, in chess
+            let square = en_passant.parse::<Square>().ok().filter(|x| en_passant_ranks.contains(&x.rank()));
+            Some(square.ok_or(FenError::new(en_passant_pos, FenErrorReason::InvalidEnPassant))?)
+        };
+
+        let turn = match turn {
+            "w" => { Color::White }
+            "b" => { Color::Black }
+            _ => { return Err(FenError::new(turn_pos, FenErrorReason::InvalidTurn)); }
+        };
+
+        Ok(Self {
+            board: Board::from_fen_board(board, board_pos)?,
+            en_passant: en_p,
+            castle: cle,
+            turn,
+            hm_clock: hm.parse().map_err(|_| FenError::new(hm_pos, FenErrorReason::InvalidClock))?,
+            fm_clock: fm.parse().map_err(|_| FenError::new(fm_pos, FenErrorReason::InvalidClock))?
+        })
+    }
+
+    /// Everything that makes the position impossible to reach, empty if it is fine.
+    pub fn validate(&self) -> Vec<PositionProblem> {
+        let mut problems = Vec::new();
+
+        for color in [Color::White, Color::Black] {
+            let (pawn, king) = match color {
+                Color::White => { (Piece::WPawn, Piece::WKing) }
+                Color::Black => { (Piece::BPawn, Piece::BKing) }
+            };
+
+            let kings = self.pieces(king).count() as usize;
+            if kings != 1 { problems.push(PositionProblem::KingCount(color, kings)); }
+            if self.pieces(pawn).count() > 8 { problems.push(PositionProblem::TooManyPawns(color)); }
+        }
+
+        for pos in (self.pieces(Piece::WPawn) | self.pieces(Piece::BPawn)).squares() {
+            if !(8..56).contains(&pos) { problems.push(PositionProblem::PawnOnBackRank(pos)); }
+        }
+
+        // (right, king, king square, rook, rook square)
+        let rights = [
+            (CastleFlags::WK, Piece::WKing, 4, Piece::WRook, 7),
+            (CastleFlags::WQ, Piece::WKing, 4, Piece::WRook, 0),
+            (CastleFlags::BK, Piece::BKing, 60, Piece::BRook, 63),
+            (CastleFlags::BQ, Piece::BKing, 60, Piece::BRook, 56),
+        ];
+
+        for (right, king, king_pos, rook, rook_pos) in rights {
+            if !self.castle.contains(right) { continue; }
+
+            let missing_king = PositionProblem::CastlingWithoutKing(king.color());
+            if self.board[king_pos] != Some(king) && !problems.contains(&missing_king) { problems.push(missing_king); }
+            if self.board[rook_pos] != Some(rook) { problems.push(PositionProblem::CastlingWithoutRook(rook_pos)); }
+        }
+
+        if let Some(en_passant) = self.en_passant {
+            let to = en_passant.index();
+
+            // the pawn that moved two squares sits past the en passant square, and came from the square before it
+            let (ranks, pawn, step) = match self.turn {
+                Color::White => { (40..48, Piece::BPawn, -1) }
+                Color::Black => { (16..24, Piece::WPawn, 1) }
+            };
+            // off the board for a square on the first or last rank
+            let pushed = match (en_passant.offset(0, step), en_passant.offset(0, -step)) {
+                (Some(past), Some(before)) => { self.board[past] == Some(pawn) && self.board[before].is_none() }
+                _ => { false }
+            };
+
+            if !ranks.contains(&to) || !pushed || self.board[to].is_some() {
+                problems.push(PositionProblem::EnPassantWithoutPawn);
+            }
+        }
+
+        let kings_ok = !problems.iter().any(|x| matches!(x, PositionProblem::KingCount(_, _)));
+        if kings_ok && self.is_in_check(!self.turn) { problems.push(PositionProblem::OpponentInCheck); }
+
+        problems
+    }
+
+    /// Takes the piece off `square`, along with the castling rights that needed it there.
+    pub fn remove_piece(&mut self, square: Square) -> Option<Piece> {
+        let piece = self.board[square].take();
+
+        self.castle -= match (piece, square) {
+            (Some(Piece::WKing), _) => { CastleFlags::W }
+            (Some(Piece::BKing), _) => { CastleFlags::B }
+            (Some(Piece::WRook), Square::A1) => { CastleFlags::WQ }
+            (Some(Piece::WRook), Square::H1) => { CastleFlags::WK }
+            (Some(Piece::BRook), Square::A8) => { CastleFlags::BQ }
+            (Some(Piece::BRook), Square::H8) => { CastleFlags::BK }
+            _ => { CastleFlags::NONE }
+        };
+
+        piece
+    }
+
+    /// Moves the piece on `from` to `to` whatever the rules say, like on an analysis board, and gives the
+    /// move to the other side. Whatever stood on `to` is taken, and castling rights that needed either piece
+    /// where it was are lost. Returns the piece taken, or None with the board untouched if `from` is empty.
+    pub fn free_move(&mut self, from: Square, to: Square) -> Option<Option<Piece>> {
+        if from == to || self.board[from].is_none() { return None; }
+
+        let piece = self.remove_piece(from)?;
+        let taken = self.remove_piece(to);
+        self.board[to] = Some(piece);
+
+        if piece.color() == Color::Black { self.fm_clock = self.fm_clock.saturating_add(1); }
+        self.hm_clock = if taken.is_some() || matches!(piece, Piece::WPawn | Piece::BPawn) { 0 } else { self.hm_clock.saturating_add(1) };
+        (self.en_passant, self.turn) = (None, !piece.color());
+
+        Some(taken)
+    }
+
+    /// Half moves since the last capture or pawn move.
+    pub fn hm_clock(&self) -> u8 {
+        self.hm_clock
+    }
+
+    /// The full move number, starting at 1 and incremented after black moves.
+    pub fn fm_clock(&self) -> u16 {
+        self.fm_clock
+    }
+
+    /// The square of `player`'s king.
+    pub fn find_king(&self, player: Color) -> Option<usize> {
+        for (p, pi) in self.board.0.iter().copied().enumerate() {
+            let Some(piece) = pi else { continue; };
+
+            if piece.color() == player && (piece == Piece::WKing || piece == Piece::BKing) {
+                return Some(p);
+            }
+        }
+        None
+    }
+
+    /// The squares holding `piece`.
+    pub fn pieces(&self, piece: Piece) -> Bitboard {
+        let mut bb = Bitboard::EMPTY;
+
+        for (pos, p) in self.board.0.iter().enumerate() {
+            if *p == Some(piece) { bb |= Bitboard::square(pos); }
+        }
+
+        bb
+    }
+
+    /// The fastest forced mate for the side to move in at most `n` moves, see [`solver::find_mate`](crate::solver::find_mate).
+    pub fn find_mate_in(&self, n: u32) -> Option<Vec<Move>> {
+        crate::solver::find_mate(self, n)
+    }
+
+    /// A Zobrist hash of the position: the pieces, the side to move, the castling rights and an en passant
+    /// square a pawn can take on. The clocks are left out, so transpositions hash the same.
+    pub fn zobrist(&self) -> u64 {
+        let mut hash = CASTLING[self.castle.bits() as usize];
+
+        for (square, piece) in self.board.0.iter().enumerate() {
+            if let Some(piece) = piece { hash ^= PIECE_SQUARE[*piece as usize * 64 + square]; }
+        }
+
+        if self.turn == Color::Black { hash ^= BLACK_TO_MOVE; }
+
+        // only when a pawn stands beside the one that moved two squares
+        let pawn = if self.turn == Color::White { Piece::WPawn } else { Piece::BPawn };
+        if let (Some(target), Some(pushed)) = (self.en_passant, self.en_passant_pawn()) {
+            if [pushed.offset(-1, 0), pushed.offset(1, 0)].into_iter().flatten().any(|x| self.board[x] == Some(pawn)) {
+                hash ^= EN_PASSANT_FILE[target.file().index()];
+            }
+        }
+
+        hash
+    }
+
+    /// The squares holding any piece.
+    pub fn occupied(&self) -> Bitboard {
+        let mut bb = Bitboard::EMPTY;
+
+        for (pos, p) in self.board.0.iter().enumerate() {
+            if p.is_some() { bb |= Bitboard::square(pos); }
+        }
+
+        bb
+    }
+
+    /// The squares of `color`'s pieces attacking `square`, pins are ignored.
+    pub fn attackers(&self, square: usize, color: Color) -> Bitboard {
+        self.attackers_through(square, color, self.occupied())
+    }
+
+    // the attackers among the pieces on `occupied`, which are also the only ones blocking lines
+    fn attackers_through(&self, square: usize, color: Color, occupied: Bitboard) -> Bitboard {
+        let (pawn, knight, bishop, rook, queen, king) = match color {
+            Color::White => { (Piece::WPawn, Piece::WKnight, Piece::WBishop, Piece::WRook, Piece::WQueen, Piece::WKing) }
+            Color::Black => { (Piece::BPawn, Piece::BKnight, Piece::BBishop, Piece::BRook, Piece::BQueen, Piece::BKing) }
+        };
+
+        let queens = self.pieces(queen);
+
+        // a pawn of color attacks square if a pawn of the other color on square would attack it
+        let attackers = (pawn_attacks(square, !color) & self.pieces(pawn))
+            | (knight_attacks(square) & self.pieces(knight))
+            | (king_attacks(square) & self.pieces(king))
+            | (bishop_attacks(square, occupied) & (self.pieces(bishop) | queens))
+            | (rook_attacks(square, occupied) & (self.pieces(rook) | queens));
+
+        attackers & occupied
+    }
+
+    /// Static exchange evaluation: the material `mv` wins in centipawns, negative if it loses material,
+    /// when both sides go on taking on its target square with their cheapest piece for as long as that
+    /// pays. Pieces behind the ones taking join in, checks and pins are left out. A quiet move scores 0,
+    /// or what it loses if the piece can be taken.
+    pub fn see(&self, mv: Move) -> i32 {
+        let (from, to) = (mv.from.index(), mv.to.index());
+        let Some(mut attacker) = self.board[from] else { return 0; };
+
+        let mut occupied = self.occupied() & !Bitboard::square(from);
+        let mut gains = vec![self.captured_piece(mv).map(Piece::exchange_value).unwrap_or(0)];
+
+        if let Some(pushed) = self.en_passant_pawn().filter(|_| self.en_passant == Some(mv.to) && self.board[to].is_none()) {
+            occupied &= !Bitboard::square(pushed.index());
+        }
+        if let Some(promotion) = mv.promotion.filter(|_| attacker == Piece::WPawn || attacker == Piece::BPawn) {
+            attacker = Piece::from_promotion(promotion, attacker.color());
+            gains[0] += attacker.exchange_value() - Piece::WPawn.exchange_value();
+        }
+
+        // every capture on `to` in turn, what the side taking has won so far if the exchange stopped there
+        let mut side = !attacker.color();
+        while let Some(square) = self.attackers_through(to, side, occupied).squares().min_by_key(|x| self.board[*x].map(Piece::exchange_value)) {
+            gains.push(attacker.exchange_value() - gains[gains.len() - 1]);
+
+            attacker = self.board[square].expect("attackers stand on a square");
+            occupied &= !Bitboard::square(square);
+            side = !side;
+        }
+
+        // either side stops taking once going on would lose
+        for i in (1..gains.len()).rev() {
+            gains[i - 1] = -(-gains[i - 1]).max(gains[i]);
+        }
+
+        gains[0]
+    }
+
+    /// The squares of `color`'s pieces attacking `square`, pins are ignored.
+    pub fn attackers_of(&self, square: usize, color: Color) -> Vec<usize> {
+        self.attackers(square, color).squares().collect()
+    }
+
+    /// Whether any of `color`'s pieces attack `square`.
+    pub fn is_attacked(&self, square: usize, color: Color) -> bool {
+        !self.attackers(square, color).is_empty()
+    }
+
+    /// For every square, the number of `color`'s pieces attacking it.
+    /// Squares holding `color`'s own pieces count as attacked when they are defended.
+    pub fn attack_map(&self, color: Color) -> [u8; 64] {
+        let mut map = [0; 64];
+
+        for (pos, piece) in self.board.0.iter().copied().enumerate() {
+            let Some(piece) = piece else { continue; };
+            if piece.color() != color { continue; }
+
+            let (x, y) = ((pos % 8) as isize, (pos / 8) as isize);
+            let mut attack = |dx: isize, dy: isize| -> bool {
+                let (nx, ny) = (x + dx, y + dy);
+                if !(0..8).contains(&nx) || !(0..8).contains(&ny) { return false; }
+
+                let to = (ny * 8 + nx) as usize;
+                map[to] += 1;
+
+                // sliding pieces stop at the first piece in the way
+                self.board[to].is_none()
+            };
+
+            match piece {
+                Piece::WPawn => { attack(-1, 1); attack(1, 1); }
+                Piece::BPawn => { attack(-1, -1); attack(1, -1); }
+                Piece::WKnight | Piece::BKnight => {
+                    for (dx, dy) in [(1, 2), (2, 1), (2, -1), (1, -2), (-1, -2), (-2, -1), (-2, 1), (-1, 2)] {
+                        attack(dx, dy);
+                    }
+                }
+                Piece::WKing | Piece::BKing => {
+                    for (dx, dy) in [(1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1), (0, -1), (1, -1)] {
+                        attack(dx, dy);
+                    }
+                }
+                _ => {
+                    let straight = matches!(piece, Piece::WRook | Piece::BRook | Piece::WQueen | Piece::BQueen);
+                    let diagonal = matches!(piece, Piece::WBishop | Piece::BBishop | Piece::WQueen | Piece::BQueen);
+
+                    for (dx, dy) in [(1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1), (0, -1), (1, -1)] {
+                        if (dx == 0 || dy == 0) && !straight { continue; }
+                        if dx != 0 && dy != 0 && !diagonal { continue; }
+
+                        let mut i = 1;
+                        while attack(dx * i, dy * i) { i += 1; }
+                    }
+                }
+            }
+        }
+
+        map
+    }
+
+    /// The square of the pawn an en passant capture would take: the one past the en passant square,
+    /// seen from the side to move.
+    pub fn en_passant_pawn(&self) -> Option<Square> {
+        let ranks = if self.turn == Color::White { -1 } else { 1 };
+        self.en_passant?.offset(0, ranks)
+    }
+
+    /// The piece `mv` would take, including a pawn taken en passant.
+    pub fn captured_piece(&self, mv: Move) -> Option<Piece> {
+        let (from, to) = (mv.from.index(), mv.to.index());
+
+        if let Some(en_passant) = self.en_passant {
+            if en_passant == mv.to && self.board[from].is_some_and(|x| x == Piece::WPawn || x == Piece::BPawn) {
+                return self.board[self.en_passant_pawn()?];
+            }
+        }
+
+        self.board[to]
+    }
+
+    /// `color`'s pieces the opponent can win material by taking, judged by [`Game::see`]: undefended,
+    /// attacked by a cheaper piece, or attacked more often than they are defended.
+    pub fn hanging_pieces(&self, color: Color) -> Vec<usize> {
+        (0..64)
+            .filter(|x| self.board[*x].is_some_and(|p| p.color() == color && p != Piece::WKing && p != Piece::BKing))
+            .filter(|x| self.attackers(*x, !color).squares().any(|from| self.see(Move::new(from, *x, None)) > 0))
+            .collect()
+    }
+
+    /// `color`'s pieces pinned to their king, as (pinned piece, pinning attacker) squares.
+    pub fn pinned_pieces(&self, color: Color) -> Vec<(usize, usize)> {
+        self.pins(color).collect()
+    }
+
+    // pinned_pieces without collecting them
+    fn pins(&self, color: Color) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let king = self.find_king(color);
+        let (bishop, rook, queen) = match color {
+            Color::White => { (Piece::BBishop, Piece::BRook, Piece::BQueen) }
+            Color::Black => { (Piece::WBishop, Piece::WRook, Piece::WQueen) }
+        };
+
+        let occupied = self.occupied();
+        let queens = self.pieces(queen);
+        let lines = [(ROOK_DIRECTIONS, self.pieces(rook) | queens), (BISHOP_DIRECTIONS, self.pieces(bishop) | queens)];
+
+        lines.into_iter()
+            .flat_map(|(directions, sliders)| directions.map(|x| (x, sliders)))
+            .filter_map(move |(direction, sliders)| {
+                // the first piece seen from the king is ours, the one behind it attacks along the same line
+                let blocker = (ray(king?, direction, occupied) & occupied).squares().next()?;
+                if !self.board[blocker].is_some_and(|x| x.color() == color) { return None; }
+
+                let attacker = (ray(blocker, direction, occupied) & occupied).squares().next()?;
+                sliders.contains(attacker).then_some((blocker, attacker))
+            })
+    }
+
+    /// Whether `player`'s king is attacked.
+    pub fn is_in_check(&self, player: Color) -> bool {
+        let Some(kpos) = self.find_king(player) else { return false; };
+
+        self.is_attacked(kpos, !player)
+    }
+
+    /// Whether `mv`, a legal move for the side to move, checks the opponent. Only the moved piece (or the
+    /// rook of a castle) and the lines it opens are looked at, the move isn't played.
+    pub fn gives_check(&self, mv: Move) -> bool {
+        let (from, to) = (mv.from.index(), mv.to.index());
+        let Some(piece) = self.board[from] else { return false; };
+        let Some(king) = self.find_king(!self.turn) else { return false; };
+
+        let pawn = piece == Piece::WPawn || piece == Piece::BPawn;
+        let mut occupied = (self.occupied() & !Bitboard::square(from)) | Bitboard::square(to);
+        // our pieces that left their squares
+        let mut moved = Bitboard::square(from);
+
+        if let Some(lost) = self.en_passant_pawn().filter(|_| pawn && self.en_passant == Some(mv.to)) {
+            occupied &= !Bitboard::square(lost.index());
+        }
+
+        let landed = match mv.promotion {
+            Some(promotion) if pawn && (to >= 56 || to <= 7) => { Piece::from_promotion(promotion, self.turn) }
+            _ => { piece }
+        };
+
+        if (piece == Piece::WKing || piece == Piece::BKing) && mv.to.file().distance(mv.from.file()) == 2 {
+            let (rook_from, rook_to) = if mv.to.file() > mv.from.file() { (from + 3, to - 1) } else { (from - 4, to + 1) };
+            occupied = (occupied & !Bitboard::square(rook_from)) | Bitboard::square(rook_to);
+            moved |= Bitboard::square(rook_from);
+
+            if rook_attacks(rook_to, occupied).contains(king) { return true; }
+        }
+
+        if piece_attacks(landed, to, occupied).contains(king) { return true; }
+
+        // discovered checks, by sliders that stayed where they were
+        let (bishop, rook, queen) = match self.turn {
+            Color::White => { (Piece::WBishop, Piece::WRook, Piece::WQueen) }
+            Color::Black => { (Piece::BBishop, Piece::BRook, Piece::BQueen) }
+        };
+        let queens = self.pieces(queen);
+
+        !(rook_attacks(king, occupied) & (self.pieces(rook) | queens) & !moved).is_empty()
+            || !(bishop_attacks(king, occupied) & (self.pieces(bishop) | queens) & !moved).is_empty()
+    }
+
+    /// Whether `player`, who must be the side to move, is in check without a legal move.
+    pub fn is_in_checkmate(&self, player: Color) -> bool {
+        self.is_in_check(player) && self.legal_moves().next().is_none()
+    }
+
+    /// Whether the game is drawn by the fifty move rule, insufficient material or a dead position.
+    pub fn is_draw(&self) -> bool {
+        self.draw_reason().is_some()
+    }
+
+    /// Why the game is drawn, if it is, stalemate aside.
+    pub fn draw_reason(&self) -> Option<DrawReason> {
+        if self.hm_clock >= 100 {
+            Some(DrawReason::FiftyMoveRule)
+        } else if self.insufficient_material(Color::White) && self.insufficient_material(Color::Black) {
+            Some(DrawReason::InsufficientMaterial)
+        } else if self.is_dead_position() {
+            Some(DrawReason::DeadPosition)
+        } else {
+            None
+        }
+    }
+
+    /// Whether every pawn is locked against an enemy pawn and neither king nor bishop can ever capture,
+    /// be captured or reach the enemy king. Errs towards playing on, other pieces are never dead.
+    pub fn is_dead_position(&self) -> bool {
+        let colors = [Color::White, Color::Black];
+        let pawns = [self.pieces(Piece::WPawn), self.pieces(Piece::BPawn)];
+        let bishops = [self.pieces(Piece::WBishop), self.pieces(Piece::BBishop)];
+        let kings = [self.pieces(Piece::WKing), self.pieces(Piece::BKing)];
+
+        let all_pawns = pawns[0] | pawns[1];
+        if all_pawns.is_empty() || self.occupied() != all_pawns | bishops[0] | bishops[1] | kings[0] | kings[1] { return false; }
+
+        let mut attacked = [Bitboard::EMPTY; 2];
+        for i in 0..2 {
+            for pos in pawns[i].squares() {
+                let ahead = if colors[i] == Color::White { pos + 8 } else { pos - 8 };
+                let attacks = pawn_attacks(pos, colors[i]);
+
+                if !pawns[1 - i].contains(ahead) || !(attacks & (pawns[1 - i] | bishops[1 - i])).is_empty() { return false; }
+                attacked[i] |= attacks;
+            }
+        }
+
+        // everything a piece can walk to, stepping from square to square
+        let flood = |start: Bitboard, step: &dyn Fn(usize) -> Bitboard| {
+            let mut region = start;
+            loop {
+                let next = region.squares().fold(region, |acc, x| acc | (step(x) & !all_pawns));
+                if next == region { return region; }
+                region = next;
+            }
+        };
+
+        let mut king_regions = [Bitboard::EMPTY; 2];
+        for i in 0..2 {
+            king_regions[i] = flood(kings[i], &|x| king_attacks(x) & !attacked[1 - i]);
+
+            // an undefended pawn next to the king's region can be taken
+            let reachable = king_regions[i].squares().fold(Bitboard::EMPTY, |acc, x| acc | king_attacks(x));
+            if !(reachable & pawns[1 - i] & !attacked[1 - i]).is_empty() { return false; }
+        }
+
+        for i in 0..2 {
+            for pos in bishops[i].squares() {
+                let region = flood(Bitboard::square(pos), &|x| bishop_attacks(x, all_pawns));
+                let targets = region.squares().fold(Bitboard::EMPTY, |acc, x| acc | bishop_attacks(x, all_pawns));
+
+                if !(region & (attacked[1 - i] | king_regions[1 - i])).is_empty() || !(targets & pawns[1 - i]).is_empty() {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    /// How many pawns, knights, bishops, rooks and queens `color` has.
+    pub fn material(&self, color: Color) -> MaterialCount {
+        let mut count = MaterialCount::default();
+
+        for piece in self.board.0.iter().flatten().filter(|x| x.color() == color) {
+            match piece {
+                Piece::WPawn | Piece::BPawn => { count.pawns += 1; }
+                Piece::WKnight | Piece::BKnight => { count.knights += 1; }
+                Piece::WBishop | Piece::BBishop => { count.bishops += 1; }
+                Piece::WRook | Piece::BRook => { count.rooks += 1; }
+                Piece::WQueen | Piece::BQueen => { count.queens += 1; }
+                Piece::WKing | Piece::BKing => {}
+            }
+        }
+
+        count
+    }
+
+    /// White's material minus black's, in points.
+    pub fn material_diff(&self) -> i32 {
+        self.material(Color::White).points() as i32 - self.material(Color::Black).points() as i32
+    }
+
+    /// Whether `color` can never checkmate, whatever the other side plays; used to adjudicate a flag fall.
+    pub fn insufficient_material(&self, color: Color) -> bool {
+        let (mine, theirs) = (self.material(color), self.material(!color));
+
+        if mine.pawns + mine.rooks + mine.queens > 0 { return false; }
+
+        if mine.knights > 0 {
+            // a lone knight can only mate a king walled in by its own pawns, knights, bishops or rooks
+            // (kn vs k+n is not dead, knn vs k neither)
+            return mine.knights == 1 && mine.bishops == 0 && theirs.pawns + theirs.minors() + theirs.rooks == 0;
+        }
+
+        if mine.bishops > 0 {
+            // bishops on a single square color, counting the other side's, never cover the king's orthogonal
+            // neighbours, so mate needs the other side's pawns or knights to wall it in
+            let all_bishops = self.pieces(Piece::WBishop) | self.pieces(Piece::BBishop);
+            let one_color = (all_bishops & Bitboard::DARK).is_empty() || (all_bishops & !Bitboard::DARK).is_empty();
+
+            return one_color && theirs.pawns + theirs.knights == 0;
+        }
+
+        true
+    }
+
+    /// Whether the side to move has no legal moves while not in check.
+    pub fn is_stalemate(&self) -> bool {
+        !self.is_in_check(self.turn) && self.legal_moves().next().is_none()
+    }
+
+    /// All squares the piece on `loc` can legally move to.
+    pub fn all_legal_moves(&self, loc: usize) -> Vec<usize> {
+        self.legal_targets(loc).collect()
+    }
+
+    // the squares of all_legal_moves, found as they are asked for
+    fn legal_targets(&self, loc: usize) -> impl Iterator<Item = usize> + '_ {
+        let piece = self.board[loc].filter(|x| x.color() == self.turn);
+        let king = piece.is_some_and(|x| x == Piece::WKing || x == Piece::BKing);
+        let pawn = piece.is_some_and(|x| x == Piece::WPawn || x == Piece::BPawn);
+
+        // out of check, only king moves, moves of a pinned piece and en passant (which takes two pieces
+        // off a rank) can expose the king, the rest need no trial move
+        let in_check = piece.is_some() && self.is_in_check(self.turn);
+        let pin = piece.and_then(|_| self.pins(self.turn).find(|x| x.0 == loc)).map(|x| x.1);
+
+        let legal_move = move |to: usize| -> bool {
+            let legal = self.is_legal_checkless(loc, to, Some(Promotion::Queen), false).is_ok();
+            let en_passant = pawn && self.en_passant == Some(Square::new(to));
+
+            if legal && !in_check && !king && !en_passant {
+                return match (pin, self.find_king(self.turn)) {
+                    (Some(attacker), Some(kpos)) => { to == attacker || between(kpos, attacker).contains(to) }
+                    _ => { true }
+                };
+            }
+
+            if legal {
+                let mut n_board = *self;
+                n_board.move_unchecked(loc, to, Some(Promotion::Queen));
+
+                // cannot play a move which puts self in check (or a move which keeps self in check)
+                return !n_board.is_in_check(self.turn);
+            }
+
+            legal
+        };
+
+        self.candidate_targets(loc).squares().filter(move |x| legal_move(*x))
+    }
+
+    // every square the piece on loc could reach on an empty enough board, is_legal_checkless sorts them out
+    fn candidate_targets(&self, loc: usize) -> Bitboard {
+        let piece = self.board[loc].filter(|x| x.color() == self.turn);
+        let king = piece.is_some_and(|x| x == Piece::WKing || x == Piece::BKing);
+        let pawn = piece.is_some_and(|x| x == Piece::WPawn || x == Piece::BPawn);
+
+        let mut targets = piece.map(|x| piece_attacks(x, loc, self.occupied())).unwrap_or_default();
+
+        if pawn {
+            let forward = if piece == Some(Piece::WPawn) { loc + 8 } else { loc.wrapping_sub(8) };
+            let double = if piece == Some(Piece::WPawn) { loc + 16 } else { loc.wrapping_sub(16) };
+
+            for to in [forward, double] {
+                if to < 64 { targets |= Bitboard::square(to); }
+            }
+        } else if king {
+            // castling, lands two squares to either side
+            for to in [Square::new(loc).offset(2, 0), Square::new(loc).offset(-2, 0)].into_iter().flatten() {
+                targets |= Bitboard::square(to.index());
+            }
+        }
+
+        targets
+    }
+
+    /// The flags of `mv` in this position, from the pieces on its squares.
+    pub fn move_flags(&self, mv: Move) -> MoveFlags {
+        let mut flags = MoveFlags::empty();
+        let Some(piece) = self.board[mv.from] else { return flags; };
+        let (from, to) = (mv.from.index(), mv.to.index());
+
+        if piece == Piece::WPawn || piece == Piece::BPawn {
+            if self.en_passant == Some(mv.to) && mv.from.file() != mv.to.file() { flags |= MoveFlags::EN_PASSANT | MoveFlags::CAPTURE; }
+            if from.abs_diff(to) == 16 { flags |= MoveFlags::DOUBLE_PUSH; }
+            if to >= 56 || to <= 7 { flags |= MoveFlags::PROMOTION; }
+        }
+        if (piece == Piece::WKing || piece == Piece::BKing) && mv.to.file().distance(mv.from.file()) == 2 { flags |= MoveFlags::CASTLE; }
+        if self.board[to].is_some() { flags |= MoveFlags::CAPTURE; }
+
+        flags
+    }
+
+    /// Every legal move for the side to move, a move for each promotion piece.
+    pub fn generate_legal_moves(&self) -> Vec<Move> {
+        self.legal_moves().collect()
+    }
+
+    /// The moves of [`Game::generate_legal_moves`], generated one at a time without collecting them.
+    pub fn legal_moves(&self) -> impl Iterator<Item = Move> + '_ {
+        Square::all().flat_map(move |x| self.legal_moves_from(x))
+    }
+
+    /// The legal moves of the piece on `square`, a move for each promotion piece. Nothing if the
+    /// square is empty or it isn't that piece's turn.
+    pub fn legal_moves_from(&self, square: Square) -> impl Iterator<Item = Move> + '_ {
+        self.moves_to(square, self.legal_targets(square.index()))
+    }
+
+    // the moves of legal_moves that leave the mover's king attacked as well, for rules where kings can be taken
+    pub(crate) fn pseudo_legal_moves(&self) -> impl Iterator<Item = Move> + '_ {
+        Square::all().flat_map(move |square| {
+            let from = square.index();
+            let targets = self.candidate_targets(from).squares()
+                .filter(move |to| self.is_legal_checkless(from, *to, Some(Promotion::Queen), false).is_ok());
+
+            self.moves_to(square, targets)
+        })
+    }
+
+    // the moves of the piece on square to each of targets, a move for each promotion piece
+    fn moves_to<'a>(&'a self, square: Square, targets: impl Iterator<Item = usize> + 'a) -> impl Iterator<Item = Move> + 'a {
+        let pawn = self.board[square].is_some_and(|x| x == Piece::WPawn || x == Piece::BPawn);
+
+        targets.flat_map(move |to| {
+            let promoting = pawn && (to >= 56 || to <= 7);
+            let promotions = PROMOTIONS.into_iter().map(Some).filter(move |_| promoting);
+
+            promotions.chain(std::iter::once(None).filter(move |_| !promoting)).map(move |promotion| {
+                let mv = Move::new(square, to, promotion);
+                mv.with_flags(self.move_flags(mv))
+            })
+        })
+    }
+
+    // validates a moves legality (does not factor in checks/pins)
+    // NOTE: checkless validation (except castling, which validates no checks in path)
+    fn is_legal_checkless(&self, from: usize, to: usize, promotion: Option<Promotion>, king_check: bool) -> Result<(), IllegalReason> {
+        // move must be in the board
+        if from > 63 || to > 63 {
+            return Err(IllegalReason::OffBoard);
+        }
+
+        let Some(piece) = self.board[from] else {
+            // can't move a piece that isn't there ??
+            return Err(IllegalReason::NoPiece);
+        };
+
+        // Must move your own pieces
+        if piece.color() != self.turn { return Err(IllegalReason::OpponentsPiece); }
+
+        let (origin, target) = (Square::new(from), Square::new(to));
+        let (ox, oy) = (origin.file().index() as isize, origin.rank().index() as isize);
+        let (nx, ny) = (target.file().index() as isize, target.rank().index() as isize);
+
+        // make sure move does not take own piece (or enemy king (checkmate?))
+        if let Some(piece) = self.board[to] {
+            if piece.color() == self.turn { return Err(IllegalReason::OwnPiece); }
+            if king_check && piece == Piece::BKing { return Err(IllegalReason::KingCapture); }
+        }
+
+        // check if movement pattern is valid for piece
+        if piece == Piece::BPawn || piece == Piece::WPawn {
+            let rx = (nx - ox).abs();
+            let ry = (ny - oy).abs();
+
+            let take = rx == 1 && ry == 1 && self.board[to].is_some();
+            let en_passant = self.en_passant == Some(target) && rx == 1 && ry == 1;
+            let regular = ry == 1 && rx == 0 && self.board[to].is_none();
+
+            let occupied = origin.offset(0, (ny - oy).signum()).is_some_and(|x| self.board[x].is_some()) || self.board[to].is_some();
+            let first = ry == 2 && rx == 0 && ((piece == Piece::BPawn && oy == 6)  || (piece == Piece::WPawn && oy == 1)) && !occupied;
+
+            let dir = (ny - oy).is_positive() ^ (piece == Piece::BPawn);
+
+            if !(take || en_passant || regular || first) || !dir {
+                // straight ahead onto or past a piece
+                let blocked = rx == 0 && dir && (ry == 1 || (ry == 2 && (oy == 1 || oy == 6))) && occupied;
+                return Err(if blocked { IllegalReason::PathBlocked } else { IllegalReason::WrongPattern });
+            }
+        } else if (piece == Piece::BKing || piece == Piece::WKing) && (nx - ox).abs() == 2 && ny == oy {
+            if self.is_in_check(self.turn) { return Err(IllegalReason::CastleOutOfCheck); }
+            // Determine which side we are castling
+            let mut game = *self;
+            match (piece, nx - ox) {
+                // black king-side
+                (Piece::BKing, 2) => {
+                    if self.castle & CastleFlags::BK == CastleFlags::NONE { return Err(IllegalReason::CastlingRightsLost); }
+                    if self.board[61].is_some() || self.board[62].is_some() { return Err(IllegalReason::PathBlocked); }
+
+                    game.move_unchecked(60, 61, None);
+                    if game.is_in_check(self.turn) { return Err(IllegalReason::CastleThroughCheck); }
+                }
+                // black queen-side
+                (Piece::BKing, -2) => {
+                    if self.castle & CastleFlags::BQ == CastleFlags::NONE { return Err(IllegalReason::CastlingRightsLost); }
+                    if self.board[57].is_some() || self.board[58].is_some() || self.board[59].is_some() { return Err(IllegalReason::PathBlocked); }
+
+                    game.move_unchecked(60, 59, None);
+                    if game.is_in_check(self.turn) { return Err(IllegalReason::CastleThroughCheck); }
+                }
+                // white king-side
+                (Piece::WKing, 2) => {
+                    if self.castle & CastleFlags::WK == CastleFlags::NONE { return Err(IllegalReason::CastlingRightsLost); }
+                    if self.board[5].is_some() || self.board[6].is_some() { return Err(IllegalReason::PathBlocked); }
+
+                    game.move_unchecked(4, 5, None);
+                    if game.is_in_check(self.turn) { return Err(IllegalReason::CastleThroughCheck); }
+                }
+                // white queen-side
+                (Piece::WKing, -2) => {
+                    if self.castle & CastleFlags::WQ == CastleFlags::NONE { return Err(IllegalReason::CastlingRightsLost); }
+                    if self.board[1].is_some() || self.board[2].is_some() || self.board[3].is_some(){ return Err(IllegalReason::PathBlocked); }
+
+                    game.move_unchecked(4, 3, None);
+                    if game.is_in_check(self.turn) { return Err(IllegalReason::CastleThroughCheck); }
+                }
+
+                _ => { return Err(IllegalReason::WrongPattern); }
+            }
+            return Ok(());
+        } else if !piece.can_move(nx - ox, ny - oy) {
+            return Err(IllegalReason::WrongPattern);
+        }
+
+        // path trace queen, bishop, and rook moves
+        // if any piece is in the way, the move is invalid (castles are king moves)
+        if piece == Piece::BRook || piece == Piece::WRook || piece == Piece::BBishop || piece == Piece::WBishop || piece == Piece::BQueen || piece == Piece::WQueen  {
+            let rx = (nx - ox).signum();
+            let ry = (ny - oy).signum();
+
+            let mut ocx = ox + rx;
+            let mut ocy = oy + ry;
+
+            while ocx != nx || ocy != ny {
+                let Some(square) = origin.offset(ocx - ox, ocy - oy) else { return Err(IllegalReason::WrongPattern); };
+                if self.board[square].is_some() { return Err(IllegalReason::PathBlocked); }
+
+                ocx += rx;
+                ocy += ry;
+            }
+        }
+
+        // if double pawn movement, make sure it is the first pawn move (can't en passant)
+        if (ny - oy).abs() == 2 && ((piece == Piece::BPawn && oy != 6) || (piece == Piece::WPawn && oy != 1)) {
+            return Err(IllegalReason::WrongPattern);
+        }
+
+        // make sure pawn doesn't move to last (0 or 7) rank without promoting (can't en passant)
+        if ((piece == Piece::BPawn && ny == 0) || (piece == Piece::WPawn && ny == 7)) && promotion.is_none()  {
+            return Err(IllegalReason::MissingPromotion);
+        }
+
+        Ok(())
+    }
+
+    /// Validates a move without playing it.
+    pub fn is_legal_move(&self, from: usize, to: usize, promotion: Option<Promotion>) -> MoveResult {
+        if let Err(reason) = self.is_legal_checkless(from, to, promotion, true) { return reason.move_result(); }
+
+        // Any move at this point is valid (omitting check)
+        let mut n_board = *self;
+        n_board.move_unchecked(from, to, promotion);
+
+        // cannot play a move which puts self in check (or a move which keeps self in check)
+        if n_board.is_in_check(self.turn) {
+            return MoveResult::Illegal;
+        }
+
+        // Last 4 move types
+        // 1) Draw - Analyze material on n_board, if neither side can ever mate,
+        // the pawns are locked into a dead position,
+        // or if 50 move rule is done (100 moves on halfmove clock)
+        if n_board.is_draw() {
+            return MoveResult::Draw;
+        }
+
+        // 2) Stalemate, use move_gen on every piece, generating all legal moves,
+        // if no legal moves are possible and not in check, stalemate
+        if !self.gives_check(Move::new(from, to, promotion)) {
+            if n_board.is_stalemate() {
+                return MoveResult::Stalemate;
+            }
+
+            MoveResult::Valid
+        } else {
+            // 3) Checkmate
+            // Check if game is over for opponent
+            if n_board.is_in_checkmate(!self.turn) {
+                return MoveResult::Checkmate;
+            }
+
+            // 4) Check
+            // Opponent is in check
+            MoveResult::Check
+        }
+    }
+
+    /// Why the side to move can't play `from` to `to`, None if it can. A pawn reaching the last rank is
+    /// taken to promote.
+    pub fn explain_illegal(&self, from: Square, to: Square) -> Option<IllegalReason> {
+        let pawn = matches!(self.board[from], Some(Piece::WPawn | Piece::BPawn));
+        let promotion = (pawn && matches!(to.rank().index(), 0 | 7)).then_some(Promotion::Queen);
+
+        self.illegal_reason(from.index(), to.index(), promotion)
+    }
+
+    // the rules the move breaks, checking the pieces' own rules first and then the king's safety
+    fn illegal_reason(&self, from: usize, to: usize, promotion: Option<Promotion>) -> Option<IllegalReason> {
+        if let Err(reason) = self.is_legal_checkless(from, to, promotion, true) { return Some(reason); }
+
+        let mut after = *self;
+        after.move_unchecked(from, to, promotion);
+        if !after.is_in_check(self.turn) { return None; }
+
+        let king = self.find_king(self.turn)?;
+        if from == king { return Some(IllegalReason::KingInCheck); }
+
+        // pinned, if what pinned it now gives the check
+        let pinner = self.pins(self.turn).find(|x| x.0 == from).map(|x| x.1);
+        if pinner.is_some_and(|x| after.attackers(king, !self.turn).contains(x)) { return Some(IllegalReason::Pinned); }
+
+        Some(if self.is_in_check(self.turn) { IllegalReason::StillInCheck } else { IllegalReason::KingInCheck })
+    }
+
+    /// Plays a move if it is legal, the board is untouched otherwise.
+    pub fn move_checked(&mut self, from: usize, to: usize, promotion: Option<Promotion>) -> MoveResult {
+        let res = self.is_legal_move(from, to, promotion);
+
+        if res == MoveResult::Illegal || res == MoveResult::Impossible || res == MoveResult::MissingPromotion { return res; }
+        self.move_unchecked(from, to, promotion);
+
+        res
+    }
+
+    /// Plays `mv` if it is legal, returning why it was rejected otherwise.
+    pub fn make_move(&mut self, mv: Move) -> Result<MoveResult, IllegalMove> {
+        let res = self.move_checked(mv.from.index(), mv.to.index(), mv.promotion);
+        if res.is_ok() { return Ok(res); }
+
+        // the board is untouched, so the reason can be worked out from it now
+        Err(IllegalMove { mv, reason: res, explanation: self.illegal_reason(mv.from.index(), mv.to.index(), mv.promotion) })
+    }
+
+    // takes the castling rights of kings and rooks that are off their squares, for rules that move pieces
+    // other than by playing moves
+    pub(crate) fn drop_lost_castling(&mut self) {
+        let sides = [
+            (CastleFlags::WK, 4, 7, Piece::WKing, Piece::WRook), (CastleFlags::WQ, 4, 0, Piece::WKing, Piece::WRook),
+            (CastleFlags::BK, 60, 63, Piece::BKing, Piece::BRook), (CastleFlags::BQ, 60, 56, Piece::BKing, Piece::BRook),
+        ];
+
+        for (flag, king_square, rook_square, king, rook) in sides {
+            if self.board[king_square] != Some(king) || self.board[rook_square] != Some(rook) { self.castle -= flag; }
+        }
+    }
+
+    // plays a move the generator made without checking it again, searches play millions of these
+    pub(crate) fn play_unchecked(&mut self, mv: Move) {
+        self.move_unchecked(mv.from.index(), mv.to.index(), mv.promotion);
+    }
+
+    // WARNING: does not check for legality of move
+    // returns false if piece did not exist
+    // NOTE: this method updates en passant, castling,
+    // clocks, turns, and promotions, also verifies promotions (pawn and last ranks)
+    fn move_unchecked(&mut self, from: usize, to: usize, promotion: Option<Promotion>) -> bool {
+        let Some(piece) = self.board[from] else { return false; };
+
+        if self.turn == Color::Black { self.fm_clock = self.fm_clock.saturating_add(1); }
+
+        // check for en passant? both offering and taking
+        if piece == Piece::BPawn || piece == Piece::WPawn {
+            if let Some(lost) = self.en_passant_pawn().filter(|_| self.en_passant == Some(Square::new(to))) {
+                self.board[lost] = None;
+            }
+
+            // a two square push can be taken on the square it passed
+            let offering = from.abs_diff(to) == 16;
+            self.en_passant = offering.then(|| Square::new((from + to) / 2));
+            self.hm_clock = 0;
+        } else {
+            // en passant is only available for one move
+            self.en_passant = None;
+            self.hm_clock = self.hm_clock.saturating_add(1);
+        }
+
+        // check for forfeiting castling rights
+        if let Some(piece) = self.board[from] {
+            match piece {
+                Piece::WRook => {
+                    if from == 0 { self.castle -= CastleFlags::WQ; }
+                    else if from == 7 { self.castle -= CastleFlags::WK; }
+                }
+                Piece::WKing => { self.castle -= CastleFlags::W; }
+                Piece::BRook => {
+                    if from == 56 { self.castle -= CastleFlags::BQ; }
+                    else if from == 63 { self.castle -= CastleFlags::BK; }
+                }
+                Piece::BKing => { self.castle -= CastleFlags::B; }
+                _ => { }
+            }
+        }
+
+        // taking a rook also takes castling rights
+        if self.board[to].some_and(|x| *x == Piece::BRook || *x == Piece::WRook) {
+            if to == 0 { self.castle -= CastleFlags::WQ; }
+            else if to == 7 { self.castle -= CastleFlags::WK; }
+            else if to == 56 { self.castle -= CastleFlags::BQ; }
+            else if to == 63 { self.castle -= CastleFlags::BK; }
+        }
+
+        if self.board[to].is_some() { self.hm_clock = 0; }
+
+        #[allow(clippy::unnecessary_unwrap)]
+        if (piece == Piece::BPawn || piece == Piece::WPawn) && promotion.is_some() && (to >= 56 || to <= 7) {
+            self.board[to] = Some(Piece::from_promotion(promotion.unwrap(), self.turn));
+        } else if (piece == Piece::WKing || piece == Piece::BKing) && Square::new(to).file().distance(Square::new(from).file()) == 2 {
+            let (rook_from, rook_to) = if Square::new(to).file() > Square::new(from).file() {
+                (from + 3, to - 1)
+            } else {
+                (from - 4, to + 1)
+            };
+
+            self.board[to] = self.board[from];
+            self.board[rook_to] = self.board[rook_from];
+
+            self.board[rook_from] = None;
+
+        } else {
+            self.board[to] = self.board[from];
+        }
+
+        self.board[from] = None;
+        self.turn = !self.turn;
+
+        true
+    }
+}
+
+pub(crate) trait IsSomeAnd {
+    type Item;
+
+    fn some_and(&self, f: impl FnOnce(&Self::Item) -> bool) -> bool;
+}
+
+impl<T> IsSomeAnd for Option<T> {
+    type Item = T;
+
+    fn some_and(&self, f: impl FnOnce(&T) -> bool) -> bool {
+        match self {
+            None => false,
+            Some(x) => f(x),
+        }
+    }
+}
@@ -0,0 +1,11 @@
+//! Famous games bundled with the crate, annotated, to replay.
+
+use crate::pgn::Pgn;
+
+const CLASSICS: &str = include_str!("classics.pgn");
+
+/// The bundled games: the Immortal, Opera and Evergreen games and the Game of the Century. The
+/// `Annotator` tag holds the name the game is known by.
+pub fn classics() -> Vec<Pgn> {
+    Pgn::parse_many(CLASSICS).expect("the bundled games are valid pgn")
+}
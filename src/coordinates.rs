@@ -0,0 +1,81 @@
+use std::path::{Path, PathBuf};
+
+/// How long a round of coordinate training lasts, in seconds.
+pub const ROUND_SECONDS: f64 = 30.0;
+/// How many rounds the high score table keeps.
+pub const MAX_SCORES: usize = 10;
+
+/// A finished round of coordinate training: squares found, wrong clicks, and when it was played.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CoordinateScore {
+    pub hits: u32,
+    pub misses: u32,
+    // seconds since the unix epoch
+    pub time: u64,
+}
+
+impl CoordinateScore {
+    // more squares first, fewer mistakes break ties, then the older round
+    fn beats(&self, other: &CoordinateScore) -> bool {
+        (other.hits, self.misses, self.time) < (self.hits, other.misses, other.time)
+    }
+}
+
+/// The best rounds, best first, kept in a file as `hits misses time` lines.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct HighScores {
+    path: PathBuf,
+    pub scores: Vec<CoordinateScore>,
+}
+
+impl HighScores {
+    /// Reads the table at `path`, a missing file is an empty table. Lines that can't be read are skipped.
+    pub fn open(path: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let path = path.into();
+
+        let scores = match std::fs::read_to_string(&path) {
+            Ok(text) => { text.lines().filter_map(parse_score).collect() }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => { Vec::new() }
+            Err(err) => { return Err(err); }
+        };
+
+        let mut table = HighScores { path, scores: Vec::new() };
+        for score in scores { table.insert(score); }
+
+        Ok(table)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        let lines: Vec<String> = self.scores.iter().map(|x| format!("{} {} {}\n", x.hits, x.misses, x.time)).collect();
+        std::fs::write(&self.path, lines.concat())
+    }
+
+    /// Adds `score` and saves the table. Returns its place (0 is the best), None if it didn't make the table.
+    pub fn add(&mut self, score: CoordinateScore) -> std::io::Result<Option<usize>> {
+        let place = self.insert(score);
+        if place.is_some() { self.save()?; }
+
+        Ok(place)
+    }
+
+    fn insert(&mut self, score: CoordinateScore) -> Option<usize> {
+        let place = self.scores.iter().position(|x| score.beats(x)).unwrap_or(self.scores.len());
+        if place >= MAX_SCORES { return None; }
+
+        self.scores.insert(place, score);
+        self.scores.truncate(MAX_SCORES);
+        Some(place)
+    }
+}
+
+fn parse_score(line: &str) -> Option<CoordinateScore> {
+    let mut fields = line.split_whitespace().map(|x| x.parse::<u64>().ok());
+    let (Some(Some(hits)), Some(Some(misses)), Some(Some(time))) = (fields.next(), fields.next(), fields.next()) else { return None; };
+
+    Some(CoordinateScore { hits: hits.try_into().ok()?, misses: misses.try_into().ok()?, time })
+}
@@ -0,0 +1,245 @@
+//! Correspondence games: slow games with days for every move, kept on disk from one run to the next.
+//!
+//! Each game is a pgn file in a [`Correspondence`] directory. Besides the usual tags it has `Player`,
+//! the side played from here, `Opponent` and `OpponentType` (`engine` or `remote`), `DaysPerMove`, and
+//! `LastMove`, the seconds since the unix epoch when the last move (or the start) was made. An engine
+//! opponent's moves are made by this program, a remote one moves by writing the file, through a folder
+//! shared with the other player. A [`ReplyWatcher`] notices either kind of reply.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use crate::archive::pgn_date;
+use crate::chess::{Color, Game, Move, MoveResult};
+use crate::error::{IllegalMove, IllegalReason};
+use crate::history::History;
+use crate::pgn::Pgn;
+use crate::platform::since_epoch;
+use crate::result::{GameResult, TerminationReason};
+
+const DAY: u64 = 86_400;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum OpponentKind {
+    /// An engine, or a bot, named by the `Opponent` tag.
+    Engine,
+    /// Someone else, moving in the same file from elsewhere.
+    Remote,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct CorrespondenceGame {
+    // the file the game is kept in, empty until the game is created in a `Correspondence`
+    pub path: PathBuf,
+    pub pgn: Pgn,
+    pub player: Color,
+    pub opponent: String,
+    pub kind: OpponentKind,
+    pub days_per_move: u32,
+    // seconds since the epoch
+    pub last_move: u64,
+}
+
+impl CorrespondenceGame {
+    /// A game from the starting position begun at `now`, seconds since the epoch.
+    pub fn new(player: Color, opponent: impl Into<String>, kind: OpponentKind, days_per_move: u32, now: u64) -> Self {
+        let opponent = opponent.into();
+
+        let mut pgn = Pgn::new(History::new(Game::default()));
+        let (white, black) = if player == Color::White { ("Player", opponent.as_str()) } else { (opponent.as_str(), "Player") };
+        pgn.set_tag("Event", "Correspondence game");
+        pgn.set_tag("Date", pgn_date());
+        pgn.set_tag("White", white);
+        pgn.set_tag("Black", black);
+        pgn.set_tag("Result", "*");
+        pgn.set_tag("TimeControl", format!("1/{}", days_per_move as u64 * DAY));
+
+        CorrespondenceGame { path: PathBuf::new(), pgn, player, opponent, kind, days_per_move, last_move: now }
+    }
+
+    /// Reads a game written by [`CorrespondenceGame::to_pgn`]. None if it isn't a pgn or lacks the
+    /// correspondence tags.
+    pub fn parse(path: impl Into<PathBuf>, text: &str) -> Option<Self> {
+        let pgn = Pgn::parse(text).ok()?;
+
+        let player = match pgn.tag("Player")? {
+            "White" => { Color::White }
+            "Black" => { Color::Black }
+            _ => { return None; }
+        };
+        let kind = match pgn.tag("OpponentType")? {
+            "engine" => { OpponentKind::Engine }
+            "remote" => { OpponentKind::Remote }
+            _ => { return None; }
+        };
+        let opponent = pgn.tag("Opponent")?.to_string();
+        let days_per_move = pgn.tag("DaysPerMove")?.parse().ok()?;
+        let last_move = pgn.tag("LastMove")?.parse().ok()?;
+
+        Some(CorrespondenceGame { path: path.into(), pgn, player, opponent, kind, days_per_move, last_move })
+    }
+
+    /// The game as a pgn, with the correspondence tags filled in.
+    pub fn to_pgn(&self) -> String {
+        let mut pgn = self.pgn.clone();
+        pgn.set_tag("Player", format!("{:?}", self.player));
+        pgn.set_tag("Opponent", self.opponent.as_str());
+        pgn.set_tag("OpponentType", match self.kind {
+            OpponentKind::Engine => { "engine" }
+            OpponentKind::Remote => { "remote" }
+        });
+        pgn.set_tag("DaysPerMove", self.days_per_move.to_string());
+        pgn.set_tag("LastMove", self.last_move.to_string());
+
+        pgn.to_string()
+    }
+
+    pub fn game(&self) -> Game {
+        self.pgn.history.current()
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.pgn.tag("Result").is_some_and(|x| x != "*")
+    }
+
+    pub fn awaits_player(&self) -> bool {
+        !self.is_over() && self.game().turn == self.player
+    }
+
+    /// The opponent is an engine and it's its move.
+    pub fn awaits_engine(&self) -> bool {
+        !self.is_over() && self.kind == OpponentKind::Engine && self.game().turn != self.player
+    }
+
+    /// When the side to move runs out of time, in seconds since the epoch.
+    pub fn deadline(&self) -> u64 {
+        self.last_move + self.days_per_move as u64 * DAY
+    }
+
+    pub fn time_left(&self, now: u64) -> Duration {
+        Duration::from_secs(self.deadline().saturating_sub(now))
+    }
+
+    /// Plays `mv` for the side to move at `now`, ending the game on a mate or a draw. A game that is
+    /// over, lost on time by now as well, takes no more moves.
+    pub fn play(&mut self, mv: Move, now: u64) -> Result<MoveResult, IllegalMove> {
+        if self.check_time(now) || self.is_over() {
+            return Err(IllegalMove { mv, reason: MoveResult::Illegal, explanation: Some(IllegalReason::GameOver) });
+        }
+
+        let result = self.pgn.history.push(mv)?;
+        self.last_move = now;
+
+        if let Some(end) = GameResult::of(&self.pgn.history) { self.end(end); }
+
+        Ok(result)
+    }
+
+    /// Ends the game as lost on time for the side to move if its deadline has passed. True if it did.
+    pub fn check_time(&mut self, now: u64) -> bool {
+        if self.is_over() || now <= self.deadline() { return false; }
+
+        self.end(GameResult::win(!self.game().turn, TerminationReason::Timeout));
+        true
+    }
+
+    /// The player gives the game up.
+    pub fn resign(&mut self) {
+        if !self.is_over() { self.end(GameResult::win(!self.player, TerminationReason::Resignation)); }
+    }
+
+    /// How the game ended, None while it goes on.
+    pub fn result(&self) -> Option<GameResult> {
+        GameResult::from_pgn(&self.pgn)
+    }
+
+    fn end(&mut self, result: GameResult) {
+        result.write_tags(&mut self.pgn);
+    }
+}
+
+/// A directory of correspondence games, one pgn file each.
+pub struct Correspondence {
+    dir: PathBuf,
+}
+
+impl Correspondence {
+    /// Opens the games in `dir`, creating the directory if needed.
+    pub fn open(dir: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+
+        Ok(Correspondence { dir })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Gives `game` a file of its own and writes it there.
+    pub fn create(&self, game: &mut CorrespondenceGame) -> std::io::Result<()> {
+        let millis = since_epoch().unwrap_or_default().as_millis();
+
+        let mut path = self.dir.join(format!("{}.pgn", millis));
+        let mut i = 1;
+        while path.exists() {
+            path = self.dir.join(format!("{}-{}.pgn", millis, i));
+            i += 1;
+        }
+
+        game.path = path;
+        self.save(game)
+    }
+
+    pub fn save(&self, game: &CorrespondenceGame) -> std::io::Result<()> {
+        std::fs::write(&game.path, game.to_pgn())
+    }
+
+    pub fn delete(&self, game: &CorrespondenceGame) -> std::io::Result<()> {
+        std::fs::remove_file(&game.path)
+    }
+
+    /// Every readable game, the ones waiting on the player first and then by deadline. Files that aren't
+    /// correspondence games are skipped.
+    pub fn games(&self) -> std::io::Result<Vec<CorrespondenceGame>> {
+        let mut games = Vec::new();
+
+        for entry in std::fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|x| x != "pgn") { continue; }
+
+            let Ok(text) = std::fs::read_to_string(&path) else { continue; };
+            if let Some(game) = CorrespondenceGame::parse(path, &text) { games.push(game); }
+        }
+
+        games.sort_by_key(|x| (x.is_over(), !x.awaits_player(), x.deadline()));
+        Ok(games)
+    }
+}
+
+/// Remembers how far along each game was, to tell when the opponent has replied.
+#[derive(Clone, Default, Debug)]
+pub struct ReplyWatcher {
+    plies: HashMap<PathBuf, usize>,
+}
+
+impl ReplyWatcher {
+    pub fn new() -> Self {
+        ReplyWatcher::default()
+    }
+
+    /// The games among `games` that have moved on since the last look and now wait on the player. Games
+    /// seen for the first time only count from the next look on.
+    pub fn replies<'a>(&mut self, games: &'a [CorrespondenceGame]) -> Vec<&'a CorrespondenceGame> {
+        let mut replies = Vec::new();
+
+        for game in games {
+            let plies = game.pgn.history.len();
+            let before = self.plies.insert(game.path.clone(), plies);
+
+            if before.is_some_and(|x| x < plies) && game.awaits_player() { replies.push(game); }
+        }
+
+        replies
+    }
+}
@@ -0,0 +1,753 @@
+//! The built-in engine: an alpha-beta search with a transposition table, null move pruning, late move
+//! reductions, aspiration windows and a quiescence search. Each of them can be tuned or turned off with
+//! [`EngineOptions`], or the UCI options of the same names, to see what it is worth. An NNUE net (see
+//! [`crate::nnue`]) can take the place of the handcrafted evaluation.
+
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::ScopedJoinHandle;
+use std::time::Duration;
+use crate::chess::{Color, Game, Move, MoveFlags, Piece, Promotion};
+use crate::error::EngineOptionError;
+use crate::nnue::{Accumulator, Network};
+use crate::platform::since_epoch;
+use crate::square::Square;
+use crate::uci::{Evaluation, Limits, Score, UciInfo, UciPosition};
+
+const MATE: i32 = 30_000;
+// scores past this are mates, MATE less the plies to it
+const MATE_BOUND: i32 = MATE - 1_000;
+const INFINITY: i32 = 32_000;
+const MAX_DEPTH: u32 = 64;
+const MAX_PLY: u32 = 128;
+// nodes between looks at the clock and the stop flag
+const CHECK_EVERY: u64 = 1_024;
+// there is no clock in the browser, nodes stand in for milliseconds there
+const NODES_PER_MS: u64 = 200;
+const MAX_HASH_MB: i64 = 1_024;
+// how much shallower the search after a null move is
+const NULL_REDUCTION: i32 = 2;
+// moves searched at full depth before the quiet ones are reduced
+const FULL_DEPTH_MOVES: usize = 3;
+
+/// The tunable search features of the built-in engine, the UCI option names are in brackets.
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EngineOptions {
+    // transposition table size in megabytes (Hash)
+    pub hash_mb: usize,
+    // null move pruning (NullMove)
+    pub null_move: bool,
+    // late move reductions (LMR)
+    pub lmr: bool,
+    // aspiration windows (Aspiration), this many centipawns either side of the last score (AspirationWindow)
+    pub aspiration: bool,
+    pub aspiration_window: i32,
+    // plies of captures searched past the depth (QuiescenceDepth), 0 turns the quiescence search off
+    pub quiescence_depth: u32,
+    // the NNUE net to evaluate with (EvalFile), None for the handcrafted evaluation
+    pub eval_file: Option<PathBuf>,
+}
+
+impl Default for EngineOptions {
+    fn default() -> Self {
+        EngineOptions { hash_mb: 16, null_move: true, lmr: true, aspiration: true, aspiration_window: 50, quiescence_depth: 8, eval_file: None }
+    }
+}
+
+impl EngineOptions {
+    /// The `option` lines a UCI engine announces after `uci`, with these options as the defaults.
+    pub fn uci_options(&self) -> Vec<String> {
+        vec![
+            format!("option name Hash type spin default {} min 1 max {}", self.hash_mb, MAX_HASH_MB),
+            format!("option name NullMove type check default {}", self.null_move),
+            format!("option name LMR type check default {}", self.lmr),
+            format!("option name Aspiration type check default {}", self.aspiration),
+            format!("option name AspirationWindow type spin default {} min 1 max 1000", self.aspiration_window),
+            format!("option name QuiescenceDepth type spin default {} min 0 max 32", self.quiescence_depth),
+            format!("option name EvalFile type string default {}", self.eval_file.as_ref().map(|x| x.display().to_string()).unwrap_or_else(|| String::from("<empty>"))),
+        ]
+    }
+
+    /// Sets the option named `name` like `setoption`, names are case insensitive.
+    pub fn set_option(&mut self, name: &str, value: &str) -> Result<(), EngineOptionError> {
+        let invalid = || EngineOptionError::InvalidValue(name.to_string(), value.to_string());
+        let check = || match value.trim().to_lowercase().as_str() {
+            "true" => { Ok(true) }
+            "false" => { Ok(false) }
+            _ => { Err(invalid()) }
+        };
+        let spin = |min: i64, max: i64| value.trim().parse::<i64>().ok().filter(|x| (min..=max).contains(x)).ok_or_else(invalid);
+
+        match name.trim().to_lowercase().as_str() {
+            "hash" => { self.hash_mb = spin(1, MAX_HASH_MB)? as usize; }
+            "nullmove" => { self.null_move = check()?; }
+            "lmr" => { self.lmr = check()?; }
+            "aspiration" => { self.aspiration = check()?; }
+            "aspirationwindow" => { self.aspiration_window = spin(1, 1_000)? as i32; }
+            "quiescencedepth" => { self.quiescence_depth = spin(0, 32)? as u32; }
+            "evalfile" => { self.eval_file = Some(value.trim()).filter(|x| !x.is_empty() && *x != "<empty>").map(PathBuf::from); }
+            _ => { return Err(EngineOptionError::Unknown(name.to_string())); }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum Bound {
+    Exact,
+    // the score is at least this
+    Lower,
+    // the score is at most this
+    Upper,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Entry {
+    key: u64,
+    depth: i32,
+    score: i32,
+    bound: Bound,
+    best: Option<Move>,
+}
+
+// positions searched before, by zobrist hash, a newer entry replaces whatever was in its slot
+struct TranspositionTable {
+    entries: Vec<Option<Entry>>,
+}
+
+impl TranspositionTable {
+    fn new(mb: usize) -> Self {
+        let count = (mb * 1024 * 1024 / std::mem::size_of::<Option<Entry>>()).max(1);
+        TranspositionTable { entries: vec![None; count] }
+    }
+
+    fn slot(&self, key: u64) -> usize {
+        (key % self.entries.len() as u64) as usize
+    }
+
+    fn get(&self, key: u64) -> Option<Entry> {
+        self.entries[self.slot(key)].filter(|x| x.key == key)
+    }
+
+    fn insert(&mut self, entry: Entry) {
+        let slot = self.slot(entry.key);
+        self.entries[slot] = Some(entry);
+    }
+
+    fn clear(&mut self) {
+        self.entries.fill(None);
+    }
+}
+
+/// Stops a search of an [`Engine`] from another thread, the search returns the best move found so far. With
+/// no search running it stops the next one.
+#[derive(Clone, Debug)]
+pub struct EngineStopper(Arc<AtomicBool>);
+
+impl EngineStopper {
+    pub fn stop(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+/// The built-in engine. The transposition table is kept from one search to the next, until
+/// [`Engine::new_game`].
+pub struct Engine {
+    options: EngineOptions,
+    table: TranspositionTable,
+    stop: Arc<AtomicBool>,
+    // the rest is for the running search
+    nodes: u64,
+    seldepth: u32,
+    // since the epoch
+    deadline: Option<Duration>,
+    node_limit: Option<u64>,
+    stopped: bool,
+    // hashes of the positions before the one searched, the game's and then the search's
+    path: Vec<u64>,
+    // two quiet moves per ply that caused a cutoff
+    killers: Vec<[Option<Move>; 2]>,
+    root_best: Option<Move>,
+    network: Option<Network>,
+    // the net's accumulator for every ply, kept up to date as moves are made
+    accumulators: Vec<Accumulator>,
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Engine::new(EngineOptions::default())
+    }
+}
+
+impl Engine {
+    /// An engine with `options`, if its `eval_file` can't be loaded it evaluates without a net.
+    pub fn new(options: EngineOptions) -> Self {
+        let table = TranspositionTable::new(options.hash_mb);
+        let network = options.eval_file.as_ref().and_then(|x| Network::open(x).ok());
+
+        Engine {
+            options,
+            table,
+            stop: Arc::new(AtomicBool::new(false)),
+            nodes: 0,
+            seldepth: 0,
+            deadline: None,
+            node_limit: None,
+            stopped: false,
+            path: Vec::new(),
+            killers: vec![[None; 2]; MAX_PLY as usize + 1],
+            root_best: None,
+            network,
+            accumulators: Vec::new(),
+        }
+    }
+
+    pub fn options(&self) -> &EngineOptions {
+        &self.options
+    }
+
+    /// Sets an option like [`EngineOptions::set_option`], a new `Hash` size clears the table. A net that
+    /// can't be loaded leaves the engine on the handcrafted evaluation.
+    pub fn set_option(&mut self, name: &str, value: &str) -> Result<(), EngineOptionError> {
+        let (hash_mb, eval_file) = (self.options.hash_mb, self.options.eval_file.clone());
+        self.options.set_option(name, value)?;
+
+        if self.options.hash_mb != hash_mb { self.table = TranspositionTable::new(self.options.hash_mb); }
+        if self.options.eval_file != eval_file {
+            self.network = None;
+
+            if let Some(path) = self.options.eval_file.clone() {
+                match Network::open(&path) {
+                    Ok(network) => { self.network = Some(network); }
+                    Err(err) => {
+                        self.options.eval_file = None;
+                        return Err(EngineOptionError::Network(path.display().to_string(), err.to_string()));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The net the engine evaluates with, None when it uses the handcrafted evaluation.
+    pub fn network(&self) -> Option<&Network> {
+        self.network.as_ref()
+    }
+
+    pub fn stopper(&self) -> EngineStopper {
+        EngineStopper(self.stop.clone())
+    }
+
+    /// Forgets everything learned in earlier searches.
+    pub fn new_game(&mut self) {
+        self.table.clear();
+    }
+
+    /// Searches `position` within `limits`, calling `info` after every depth. Returns the best move and
+    /// its score, None if the side to move has no legal move. Without a depth or time limit the search goes
+    /// on until it is stopped.
+    pub fn search(&mut self, position: impl Into<UciPosition>, limits: Limits, mut info: impl FnMut(&UciInfo)) -> Option<Evaluation> {
+        let (game, path) = match position.into() {
+            UciPosition::Fen(game) => { (game, Vec::new()) }
+            UciPosition::Moves(history) => { (history.current(), (0..history.len()).map(|x| history.position(x).zobrist()).collect()) }
+        };
+
+        // a stop that came before the search started ends it right away, and is only cleared once it's done
+        let Some(first) = game.legal_moves().next() else {
+            self.stop.store(false, Ordering::SeqCst);
+            return None;
+        };
+        let start = since_epoch();
+        let time = limits.move_time(game.turn);
+
+        (self.nodes, self.seldepth, self.stopped, self.path, self.root_best) = (0, 0, false, path, None);
+        self.deadline = start.zip(time).map(|(start, time)| start + Duration::from_millis(time));
+        self.node_limit = time.filter(|_| start.is_none()).map(|x| x * NODES_PER_MS);
+        self.killers.fill([None; 2]);
+        if let Some(network) = &self.network {
+            self.accumulators = vec![network.accumulator(&game); MAX_PLY as usize + 1];
+        }
+
+        let max_depth = limits.max_depth().map(u32::from).unwrap_or(MAX_DEPTH).min(MAX_DEPTH);
+        let mut best = Evaluation { best: first, score: None };
+        let mut score = 0;
+
+        for depth in 1..=max_depth {
+            let result = self.aspiration_search(&game, depth as i32, score);
+
+            // an unfinished depth can't be trusted, unless it's the only one
+            if self.stopped && depth > 1 { break; }
+            score = result;
+            if let Some(mv) = self.root_best { best.best = mv; }
+            best.score = Some(to_score(score));
+
+            let elapsed = start.zip(since_epoch()).map(|(start, now)| now.saturating_sub(start).as_millis() as u64);
+            info(&UciInfo {
+                depth: Some(depth),
+                seldepth: Some(self.seldepth),
+                score: best.score,
+                nodes: Some(self.nodes),
+                nps: elapsed.filter(|x| *x > 0).map(|x| self.nodes * 1000 / x),
+                time: elapsed,
+                pv: self.principal_variation(&game, best.best, depth).iter().map(Move::to_string).collect(),
+                ..Default::default()
+            });
+
+            // a mate found within the depth can't get any shorter
+            if self.stopped || (score.abs() > MATE_BOUND && (MATE - score.abs()) as u32 <= depth) { break; }
+        }
+
+        self.stop.store(false, Ordering::SeqCst);
+        Some(best)
+    }
+
+    fn aspiration_search(&mut self, game: &Game, depth: i32, last: i32) -> i32 {
+        if !self.options.aspiration || depth < 4 || last.abs() > MATE_BOUND {
+            return self.negamax(game, depth, 0, -INFINITY, INFINITY, true);
+        }
+
+        // widened each time the score falls outside, until it's a full window
+        let mut window = self.options.aspiration_window;
+        loop {
+            let (alpha, beta) = if window > 1_000 { (-INFINITY, INFINITY) } else { (last - window, last + window) };
+            let score = self.negamax(game, depth, 0, alpha, beta, true);
+
+            if self.stopped || (alpha < score && score < beta) || window > 1_000 { return score; }
+            window *= 4;
+        }
+    }
+
+    // whether the search has to stop, only looked at every few nodes
+    fn out_of_time(&mut self) -> bool {
+        if self.stopped { return true; }
+        if !self.nodes.is_multiple_of(CHECK_EVERY) { return false; }
+
+        let late = self.deadline.zip(since_epoch()).is_some_and(|(deadline, now)| now >= deadline);
+        let over = self.node_limit.is_some_and(|x| self.nodes >= x);
+        self.stopped = late || over || self.stop.load(Ordering::SeqCst);
+
+        self.stopped
+    }
+
+    // a draw by repetition, against the positions since the last capture or pawn move
+    fn repeated(&self, hash: u64, halfmoves: u8) -> bool {
+        self.path.iter().rev().take(halfmoves as usize).any(|x| *x == hash)
+    }
+
+    fn negamax(&mut self, game: &Game, mut depth: i32, ply: u32, mut alpha: i32, beta: i32, null_allowed: bool) -> i32 {
+        if self.out_of_time() { return 0; }
+        self.nodes += 1;
+
+        let hash = game.zobrist();
+        if ply > 0 && (game.hm_clock() >= 100 || self.repeated(hash, game.hm_clock())) { return 0; }
+        if ply >= MAX_PLY { return self.evaluate(game, ply); }
+
+        let in_check = game.is_in_check(game.turn);
+        if in_check { depth += 1; }
+        if depth <= 0 { return self.quiescence(game, ply, 0, alpha, beta); }
+
+        let entry = self.table.get(hash);
+        if let Some(entry) = entry.filter(|x| ply > 0 && x.depth >= depth) {
+            let score = from_table(entry.score, ply);
+            match entry.bound {
+                Bound::Exact => { return score; }
+                Bound::Lower if score >= beta => { return score; }
+                Bound::Upper if score <= alpha => { return score; }
+                _ => {}
+            }
+        }
+
+        // a free move for the opponent, if they still can't get under beta then no real move will
+        if self.options.null_move && null_allowed && ply > 0 && depth >= 3 && !in_check && has_pieces(game, game.turn) && self.evaluate(game, ply) >= beta {
+            let mut null = *game;
+            (null.turn, null.en_passant) = (!game.turn, None);
+            self.update_accumulator(game, &null, ply);
+
+            self.path.push(hash);
+            let score = -self.negamax(&null, depth - 1 - NULL_REDUCTION, ply + 1, -beta, -beta + 1, false);
+            self.path.pop();
+
+            if self.stopped { return 0; }
+            if score >= beta { return beta; }
+        }
+
+        let mut moves: Vec<Move> = game.legal_moves().collect();
+        if moves.is_empty() { return if in_check { -MATE + ply as i32 } else { 0 }; }
+        self.order(game, &mut moves, entry.and_then(|x| x.best), ply);
+
+        let original_alpha = alpha;
+        let (mut best_score, mut best_move) = (-INFINITY, None);
+
+        self.path.push(hash);
+        for (i, mv) in moves.iter().enumerate() {
+            let mut child = *game;
+            child.play_unchecked(*mv);
+            self.update_accumulator(game, &child, ply);
+
+            let quiet = !mv.flags.contains(MoveFlags::CAPTURE) && mv.promotion.is_none();
+            let score = if i == 0 {
+                -self.negamax(&child, depth - 1, ply + 1, -beta, -alpha, true)
+            } else {
+                // later moves only have to be shown worse than the best so far, late quiet ones at a lower depth.
+                // one that looks better is searched again properly
+                let reduce = self.options.lmr && depth >= 3 && i >= FULL_DEPTH_MOVES && quiet && !in_check && !child.is_in_check(child.turn);
+                let reduced = depth - 1 - i32::from(reduce);
+
+                let score = -self.negamax(&child, reduced, ply + 1, -alpha - 1, -alpha, true);
+                if score > alpha && (reduce || score < beta) {
+                    -self.negamax(&child, depth - 1, ply + 1, -beta, -alpha, true)
+                } else {
+                    score
+                }
+            };
+
+            if self.stopped {
+                self.path.pop();
+                return 0;
+            }
+
+            if score > best_score { (best_score, best_move) = (score, Some(*mv)); }
+            if score > alpha {
+                alpha = score;
+                if ply == 0 { self.root_best = Some(*mv); }
+            }
+            if alpha >= beta {
+                if quiet { self.add_killer(ply, *mv); }
+                break;
+            }
+        }
+        self.path.pop();
+
+        let bound = if best_score <= original_alpha { Bound::Upper } else if best_score >= beta { Bound::Lower } else { Bound::Exact };
+        self.table.insert(Entry { key: hash, depth, score: to_table(best_score, ply), bound, best: best_move });
+
+        best_score
+    }
+
+    // captures and queen promotions until the position is quiet, or the quiescence depth
+    fn quiescence(&mut self, game: &Game, ply: u32, qdepth: u32, mut alpha: i32, beta: i32) -> i32 {
+        if self.out_of_time() { return 0; }
+        self.nodes += 1;
+        self.seldepth = self.seldepth.max(ply);
+
+        let stand_pat = self.evaluate(game, ply);
+        if stand_pat >= beta || qdepth >= self.options.quiescence_depth || ply >= MAX_PLY { return stand_pat; }
+        alpha = alpha.max(stand_pat);
+
+        // captures that lose material by the exchange are left out
+        let mut captures: Vec<(i32, Move)> = game.legal_moves()
+            .filter(|x| x.flags.contains(MoveFlags::CAPTURE) || x.promotion == Some(Promotion::Queen))
+            .map(|x| (game.see(x), x))
+            .filter(|x| x.0 >= 0)
+            .collect();
+        captures.sort_by_key(|x| -x.0);
+
+        let mut best = stand_pat;
+        for (_, mv) in captures {
+            let mut child = *game;
+            child.play_unchecked(mv);
+            self.update_accumulator(game, &child, ply);
+
+            let score = -self.quiescence(&child, ply + 1, qdepth + 1, -beta, -alpha);
+            if self.stopped { return 0; }
+
+            best = best.max(score);
+            alpha = alpha.max(score);
+            if alpha >= beta { break; }
+        }
+
+        best
+    }
+
+    // the table's move first, then captures of the most valuable piece by the least valuable, killers
+    // and the remaining quiet moves
+    fn order(&self, game: &Game, moves: &mut [Move], table_move: Option<Move>, ply: u32) {
+        let killers = self.killers[ply as usize];
+
+        moves.sort_by_cached_key(|mv| {
+            let score = if Some(*mv) == table_move {
+                1_000_000
+            } else if let Some(victim) = game.captured_piece(*mv) {
+                let attacker = game.board[mv.from].map(piece_value).unwrap_or(0);
+                100_000 + piece_value(victim) * 10 - attacker
+            } else if mv.promotion == Some(Promotion::Queen) {
+                90_000
+            } else if killers.contains(&Some(*mv)) {
+                50_000
+            } else {
+                0
+            };
+
+            -score
+        });
+    }
+
+    // the net's evaluation when there is one, from the accumulator for `ply`
+    fn evaluate(&self, game: &Game, ply: u32) -> i32 {
+        match &self.network {
+            Some(network) => { network.output(&self.accumulators[ply as usize], game.turn) }
+            None => { evaluate(game) }
+        }
+    }
+
+    // the accumulator for `child`, a move after `game` at `ply`, from the one for `game`. Going back a move
+    // costs nothing, the accumulator for the ply before is still there
+    fn update_accumulator(&mut self, game: &Game, child: &Game, ply: u32) {
+        let Some(network) = &self.network else { return; };
+
+        let (before, after) = self.accumulators.split_at_mut(ply as usize + 1);
+        after[0].clone_from(&before[ply as usize]);
+        network.update(&mut after[0], game, child);
+    }
+
+    fn add_killer(&mut self, ply: u32, mv: Move) {
+        let killers = &mut self.killers[ply as usize];
+        if killers[0] != Some(mv) { *killers = [Some(mv), killers[0]]; }
+    }
+
+    // the best moves stored in the table from the root on, as far as they go and stay legal
+    fn principal_variation(&self, game: &Game, best: Move, depth: u32) -> Vec<Move> {
+        let mut position = *game;
+        let mut line = Vec::new();
+        let mut next = Some(best);
+
+        while let Some(mv) = next.filter(|_| line.len() < depth as usize) {
+            if !position.legal_moves().any(|x| x == mv) { break; }
+
+            position.play_unchecked(mv);
+            line.push(mv);
+            next = self.table.get(position.zobrist()).and_then(|x| x.best);
+        }
+
+        line
+    }
+}
+
+/// Runs the engine as a UCI engine, reading commands from `input` and answering on `output` until `quit`
+/// or the end of the input. Searches run on their own thread, so `isready` and `stop` are answered during
+/// one, any other command waits for the search to finish.
+pub fn run_uci(input: impl BufRead, output: impl Write + Send) -> std::io::Result<()> {
+    let output = Mutex::new(output);
+    let send = |line: &str| -> std::io::Result<()> {
+        let mut output = output.lock().expect("nothing panics while writing");
+        writeln!(output, "{}", line)?;
+        output.flush()
+    };
+
+    std::thread::scope(|scope| {
+        // the engine is handed to the search thread and back again when it's done
+        let mut idle = Some(Engine::default());
+        let mut search: Option<(EngineStopper, ScopedJoinHandle<std::io::Result<Engine>>)> = None;
+        let mut position = UciPosition::Fen(Game::default());
+
+        for line in input.lines() {
+            let line = line?;
+            let command = line.split_whitespace().next().unwrap_or_default();
+
+            if command == "isready" {
+                send("readyok")?;
+                continue;
+            }
+            if let Some((stopper, handle)) = search.take() {
+                if matches!(command, "stop" | "quit") { stopper.stop(); }
+                idle = Some(handle.join().expect("the search doesn't panic")?);
+            }
+            let engine = idle.as_mut().expect("no search is running");
+
+            match command {
+                "uci" => {
+                    send(&format!("id name Chess {}", env!("CARGO_PKG_VERSION")))?;
+                    send("id author Zillorz")?;
+                    for option in engine.options().uci_options() { send(&option)?; }
+                    send("uciok")?;
+                }
+                "setoption" => {
+                    // setoption name <name> value <value>, either can have spaces
+                    let rest = line.split_once("name").map(|x| x.1).unwrap_or_default();
+                    let (name, value) = rest.split_once(" value").unwrap_or((rest, ""));
+
+                    if let Err(err) = engine.set_option(name.trim(), value.trim()) { send(&format!("info string {}", err))?; }
+                }
+                "ucinewgame" => { engine.new_game(); }
+                "position" => {
+                    match UciPosition::parse(&line) {
+                        Some(parsed) => { position = parsed; }
+                        None => { send(&format!("info string invalid position: {}", line))?; }
+                    }
+                }
+                "go" => {
+                    let mut engine = idle.take().expect("no search is running");
+                    let (limits, position) = (Limits::parse(&line), position.clone());
+
+                    let stopper = engine.stopper();
+                    let handle = scope.spawn(move || {
+                        let result = engine.search(position, limits, |info| { let _ = send(&info.to_uci()); });
+                        // 0000 is the null move, there is nothing to play
+                        send(&format!("bestmove {}", result.map(|x| x.best.to_string()).unwrap_or_else(|| String::from("0000"))))?;
+
+                        Ok(engine)
+                    });
+                    search = Some((stopper, handle));
+                }
+                "quit" => { break; }
+                _ => {}
+            }
+        }
+
+        if let Some((stopper, handle)) = search {
+            stopper.stop();
+            handle.join().expect("the search doesn't panic")?;
+        }
+
+        Ok(())
+    })
+}
+
+// whether `color` has more than pawns, without them a null move could miss a zugzwang
+fn has_pieces(game: &Game, color: Color) -> bool {
+    let material = game.material(color);
+    material.knights + material.bishops + material.rooks + material.queens > 0
+}
+
+// mate scores are stored relative to the position, not the root
+fn to_table(score: i32, ply: u32) -> i32 {
+    if score > MATE_BOUND { score + ply as i32 } else if score < -MATE_BOUND { score - ply as i32 } else { score }
+}
+
+fn from_table(score: i32, ply: u32) -> i32 {
+    if score > MATE_BOUND { score - ply as i32 } else if score < -MATE_BOUND { score + ply as i32 } else { score }
+}
+
+fn to_score(score: i32) -> Score {
+    if score > MATE_BOUND {
+        Score::Mate((MATE - score + 1) / 2)
+    } else if score < -MATE_BOUND {
+        Score::Mate(-(MATE + score + 1) / 2)
+    } else {
+        Score::Centipawns(score)
+    }
+}
+
+fn piece_value(piece: Piece) -> i32 {
+    match piece {
+        Piece::WPawn | Piece::BPawn => { 100 }
+        Piece::WKnight | Piece::BKnight => { 320 }
+        Piece::WBishop | Piece::BBishop => { 330 }
+        Piece::WRook | Piece::BRook => { 500 }
+        Piece::WQueen | Piece::BQueen => { 900 }
+        Piece::WKing | Piece::BKing => { 0 }
+    }
+}
+
+// piece square tables from white's side, with the eighth rank first like a board is drawn
+const PAWN_TABLE: [i32; 64] = [
+     0,   0,   0,   0,   0,   0,   0,   0,
+    50,  50,  50,  50,  50,  50,  50,  50,
+    10,  10,  20,  30,  30,  20,  10,  10,
+     5,   5,  10,  25,  25,  10,   5,   5,
+     0,   0,   0,  20,  20,   0,   0,   0,
+     5,  -5, -10,   0,   0, -10,  -5,   5,
+     5,  10,  10, -20, -20,  10,  10,   5,
+     0,   0,   0,   0,   0,   0,   0,   0,
+];
+
+const KNIGHT_TABLE: [i32; 64] = [
+   -50, -40, -30, -30, -30, -30, -40, -50,
+   -40, -20,   0,   0,   0,   0, -20, -40,
+   -30,   0,  10,  15,  15,  10,   0, -30,
+   -30,   5,  15,  20,  20,  15,   5, -30,
+   -30,   0,  15,  20,  20,  15,   0, -30,
+   -30,   5,  10,  15,  15,  10,   5, -30,
+   -40, -20,   0,   5,   5,   0, -20, -40,
+   -50, -40, -30, -30, -30, -30, -40, -50,
+];
+
+const BISHOP_TABLE: [i32; 64] = [
+   -20, -10, -10, -10, -10, -10, -10, -20,
+   -10,   0,   0,   0,   0,   0,   0, -10,
+   -10,   0,   5,  10,  10,   5,   0, -10,
+   -10,   5,   5,  10,  10,   5,   5, -10,
+   -10,   0,  10,  10,  10,  10,   0, -10,
+   -10,  10,  10,  10,  10,  10,  10, -10,
+   -10,   5,   0,   0,   0,   0,   5, -10,
+   -20, -10, -10, -10, -10, -10, -10, -20,
+];
+
+const ROOK_TABLE: [i32; 64] = [
+     0,   0,   0,   0,   0,   0,   0,   0,
+     5,  10,  10,  10,  10,  10,  10,   5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+     0,   0,   0,   5,   5,   0,   0,   0,
+];
+
+const QUEEN_TABLE: [i32; 64] = [
+   -20, -10, -10,  -5,  -5, -10, -10, -20,
+   -10,   0,   0,   0,   0,   0,   0, -10,
+   -10,   0,   5,   5,   5,   5,   0, -10,
+    -5,   0,   5,   5,   5,   5,   0,  -5,
+     0,   0,   5,   5,   5,   5,   0,  -5,
+   -10,   5,   5,   5,   5,   5,   0, -10,
+   -10,   0,   5,   0,   0,   0,   0, -10,
+   -20, -10, -10,  -5,  -5, -10, -10, -20,
+];
+
+// the king hides while there are queens on the board
+const KING_TABLE: [i32; 64] = [
+   -30, -40, -40, -50, -50, -40, -40, -30,
+   -30, -40, -40, -50, -50, -40, -40, -30,
+   -30, -40, -40, -50, -50, -40, -40, -30,
+   -30, -40, -40, -50, -50, -40, -40, -30,
+   -20, -30, -30, -40, -40, -30, -30, -20,
+   -10, -20, -20, -20, -20, -20, -20, -10,
+    20,  20,   0,   0,   0,   0,  20,  20,
+    20,  30,  10,   0,   0,  10,  30,  20,
+];
+
+// and walks to the center once they are gone
+const KING_ENDGAME_TABLE: [i32; 64] = [
+   -50, -40, -30, -20, -20, -30, -40, -50,
+   -30, -20, -10,   0,   0, -10, -20, -30,
+   -30, -10,  20,  30,  30,  20, -10, -30,
+   -30, -10,  30,  40,  40,  30, -10, -30,
+   -30, -10,  30,  40,  40,  30, -10, -30,
+   -30, -10,  20,  30,  30,  20, -10, -30,
+   -30, -30,   0,   0,   0,   0, -30, -30,
+   -50, -30, -30, -30, -30, -30, -30, -50,
+];
+
+/// The built-in engine's evaluation of `game` in centipawns, from the side to move's point of view:
+/// material and a table of good squares for every piece.
+pub fn evaluate(game: &Game) -> i32 {
+    let endgame = game.pieces(Piece::WQueen).is_empty() && game.pieces(Piece::BQueen).is_empty();
+    let mut score = 0;
+
+    for (square, piece) in Square::all().filter_map(|x| game.board[x].map(|piece| (x, piece))) {
+        let table = match piece {
+            Piece::WPawn | Piece::BPawn => { &PAWN_TABLE }
+            Piece::WKnight | Piece::BKnight => { &KNIGHT_TABLE }
+            Piece::WBishop | Piece::BBishop => { &BISHOP_TABLE }
+            Piece::WRook | Piece::BRook => { &ROOK_TABLE }
+            Piece::WQueen | Piece::BQueen => { &QUEEN_TABLE }
+            Piece::WKing | Piece::BKing if endgame => { &KING_ENDGAME_TABLE }
+            Piece::WKing | Piece::BKing => { &KING_TABLE }
+        };
+
+        // the tables start from the eighth rank, black's are mirrored
+        let (file, rank) = (square.file().index(), square.rank().index());
+        let index = if piece.color() == Color::White { (7 - rank) * 8 + file } else { rank * 8 + file };
+        let value = piece_value(piece) + table[index];
+
+        score += if piece.color() == Color::White { value } else { -value };
+    }
+
+    if game.turn == Color::White { score } else { -score }
+}
@@ -0,0 +1,227 @@
+use std::fmt::{Display, Formatter};
+use crate::chess::{Game, Move};
+use crate::engine::Engine;
+use crate::error::{EpdError, EpdErrorReason, UciError};
+use crate::pgn::{parse_san, san};
+use crate::uci::{Limits, Uci};
+
+/// A position in Extended Position Description: a FEN without clocks, followed by operations like `bm` and `id`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Epd {
+    pub game: Game,
+    // (opcode, operands), kept in file order
+    pub operations: Vec<(String, Vec<String>)>,
+}
+
+impl Epd {
+    pub fn new(game: Game) -> Self {
+        Epd { game, operations: Vec::new() }
+    }
+
+    pub fn operation(&self, opcode: &str) -> Option<&[String]> {
+        self.operations.iter().find(|x| x.0 == opcode).map(|x| x.1.as_slice())
+    }
+
+    /// Sets an operation, replacing its operands if it exists.
+    pub fn set_operation(&mut self, opcode: impl Into<String>, operands: Vec<String>) {
+        let opcode = opcode.into();
+
+        if let Some(operation) = self.operations.iter_mut().find(|x| x.0 == opcode) {
+            operation.1 = operands;
+        } else {
+            self.operations.push((opcode, operands));
+        }
+    }
+
+    pub fn id(&self) -> Option<&str> {
+        self.operation("id").and_then(|x| x.first()).map(|x| x.as_str())
+    }
+
+    /// The legal moves listed by the `bm` (best move) operation.
+    pub fn best_moves(&self) -> Vec<Move> {
+        self.moves("bm")
+    }
+
+    /// The legal moves listed by the `am` (avoid move) operation.
+    pub fn avoid_moves(&self) -> Vec<Move> {
+        self.moves("am")
+    }
+
+    fn moves(&self, opcode: &str) -> Vec<Move> {
+        self.operation(opcode).unwrap_or_default().iter().filter_map(|x| parse_san(&self.game, x)).collect()
+    }
+
+    /// Parses a single EPD line.
+    pub fn parse(line: &str) -> Result<Epd, EpdError> {
+        Epd::parse_line(line, 1)
+    }
+
+    /// Parses every non empty line of `text`.
+    pub fn parse_many(text: &str) -> Result<Vec<Epd>, EpdError> {
+        text.lines().enumerate()
+            .filter(|x| !x.1.trim().is_empty())
+            .map(|(line_no, line)| Epd::parse_line(line, line_no + 1))
+            .collect()
+    }
+
+    fn parse_line(line: &str, line_no: usize) -> Result<Epd, EpdError> {
+        let mut rest = line.trim();
+
+        // board, turn, castling and en passant
+        let mut fields = Vec::new();
+        for _ in 0..4 {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            fields.push(&rest[..end]);
+            rest = rest[end..].trim_start();
+        }
+
+        let operations = parse_operations(rest).ok_or(EpdError::new(line_no, EpdErrorReason::UnterminatedString))?;
+
+        // the clocks come from operations, if at all
+        let clock = |opcode: &str, default: &'static str| operations.iter()
+            .find(|x| x.0 == opcode)
+            .and_then(|x| x.1.first().map(|x| x.as_str()))
+            .unwrap_or(default);
+
+        let fen = format!("{} {} {}", fields.join(" "), clock("hmvc", "0"), clock("fmvn", "1"));
+        let game = Game::from_fen(fen).map_err(|x| EpdError::new(line_no, EpdErrorReason::Fen(x)))?;
+
+        Ok(Epd { game, operations })
+    }
+}
+
+// `opcode operand operand; opcode "quoted operand";`, None if a string is never closed
+fn parse_operations(text: &str) -> Option<Vec<(String, Vec<String>)>> {
+    let mut operations = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    loop {
+        while chars.next_if(|x| x.is_whitespace()).is_some() {}
+        if chars.peek().is_none() { return Some(operations); }
+
+        let mut opcode = String::new();
+        while let Some(c) = chars.next_if(|x| !x.is_whitespace() && *x != ';') {
+            opcode.push(c);
+        }
+
+        let mut operands = Vec::new();
+        loop {
+            while chars.next_if(|x| x.is_whitespace()).is_some() {}
+
+            match chars.next() {
+                // a missing final semicolon is forgiven
+                None | Some(';') => { break; }
+                Some('"') => {
+                    let mut operand = String::new();
+                    loop {
+                        match chars.next()? {
+                            '"' => { break; }
+                            c => { operand.push(c); }
+                        }
+                    }
+                    operands.push(operand);
+                }
+                Some(c) => {
+                    let mut operand = String::from(c);
+                    while let Some(c) = chars.next_if(|x| !x.is_whitespace() && *x != ';') {
+                        operand.push(c);
+                    }
+                    operands.push(operand);
+                }
+            }
+        }
+
+        if !opcode.is_empty() { operations.push((opcode, operands)); }
+    }
+}
+
+impl Display for Epd {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // drop the two clock fields
+        let fen = self.game.as_fen();
+        let fields: Vec<&str> = fen.split(' ').take(4).collect();
+        write!(f, "{}", fields.join(" "))?;
+
+        for (opcode, operands) in &self.operations {
+            write!(f, " {}", opcode)?;
+
+            for operand in operands {
+                if opcode == "id" || operand.is_empty() || operand.contains(|x: char| x.is_whitespace() || x == ';') {
+                    write!(f, " \"{}\"", operand)?;
+                } else {
+                    write!(f, " {}", operand)?;
+                }
+            }
+
+            write!(f, ";")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// How one suite position went.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SuiteResult {
+    pub id: Option<String>,
+    pub played: String,
+    pub passed: bool,
+}
+
+/// The results of [`run_suite`], one per position with a `bm` or `am` operation.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct SuiteReport {
+    pub results: Vec<SuiteResult>,
+}
+
+impl SuiteReport {
+    pub fn passed(&self) -> usize {
+        self.results.iter().filter(|x| x.passed).count()
+    }
+}
+
+impl Display for SuiteReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, result) in self.results.iter().enumerate() {
+            let id = result.id.clone().unwrap_or_else(|| format!("#{}", i + 1));
+            writeln!(f, "{}: {} {}", id, result.played, if result.passed { "ok" } else { "failed" })?;
+        }
+
+        write!(f, "{}/{} passed", self.passed(), self.results.len())
+    }
+}
+
+/// Feeds every position with a `bm` or `am` operation to `search`. A position passes when the
+/// move found is one of its best moves and none of its avoid moves.
+pub fn run_suite<E>(epds: &[Epd], mut search: impl FnMut(&Game) -> Result<Move, E>) -> Result<SuiteReport, E> {
+    let mut report = SuiteReport::default();
+
+    for epd in epds {
+        let (best, avoid) = (epd.best_moves(), epd.avoid_moves());
+        if best.is_empty() && avoid.is_empty() { continue; }
+
+        let mv = search(&epd.game)?;
+        let passed = (best.is_empty() || best.contains(&mv)) && !avoid.contains(&mv);
+
+        report.results.push(SuiteResult { id: epd.id().map(String::from), played: san(&epd.game, mv), passed });
+    }
+
+    Ok(report)
+}
+
+/// [`run_suite`] with a UCI engine given `time` milliseconds per position.
+pub fn run_uci_suite(engine: &mut Uci, epds: &[Epd], time: u64) -> Result<SuiteReport, UciError> {
+    run_suite(epds, |game| {
+        // suite positions come from unrelated games
+        engine.new_game()?;
+        engine.recommend_move(game, Limits::default().time(time))
+    })
+}
+
+/// [`run_suite`] with the built-in engine given `time` milliseconds per position.
+pub fn run_engine_suite(engine: &mut Engine, epds: &[Epd], time: u64) -> Result<SuiteReport, UciError> {
+    run_suite(epds, |game| {
+        engine.new_game();
+        engine.search(*game, Limits::default().time(time), |_| {}).map(|x| x.best).ok_or(UciError::NoMove)
+    })
+}
@@ -0,0 +1,514 @@
+use std::fmt::{Display, Formatter};
+use crate::chess::{Color, Move, MoveResult};
+use crate::square::Square;
+
+/// Any error produced by this crate.
+#[derive(Debug)]
+pub enum ChessError {
+    Fen(FenError),
+    Uci(UciError),
+    IllegalMove(IllegalMove),
+    Pgn(PgnError),
+    Epd(EpdError),
+    Import(ImportError),
+}
+
+impl Display for ChessError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChessError::Fen(err) => { write!(f, "{}", err) }
+            ChessError::Uci(err) => { write!(f, "{}", err) }
+            ChessError::IllegalMove(err) => { write!(f, "{}", err) }
+            ChessError::Pgn(err) => { write!(f, "{}", err) }
+            ChessError::Epd(err) => { write!(f, "{}", err) }
+            ChessError::Import(err) => { write!(f, "{}", err) }
+        }
+    }
+}
+
+impl std::error::Error for ChessError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ChessError::Fen(err) => { Some(err) }
+            ChessError::Uci(err) => { Some(err) }
+            ChessError::IllegalMove(err) => { Some(err) }
+            ChessError::Pgn(err) => { Some(err) }
+            ChessError::Epd(err) => { Some(err) }
+            ChessError::Import(err) => { Some(err) }
+        }
+    }
+}
+
+impl From<FenError> for ChessError {
+    fn from(value: FenError) -> Self {
+        ChessError::Fen(value)
+    }
+}
+
+impl From<UciError> for ChessError {
+    fn from(value: UciError) -> Self {
+        ChessError::Uci(value)
+    }
+}
+
+impl From<PgnError> for ChessError {
+    fn from(value: PgnError) -> Self {
+        ChessError::Pgn(value)
+    }
+}
+
+impl From<EpdError> for ChessError {
+    fn from(value: EpdError) -> Self {
+        ChessError::Epd(value)
+    }
+}
+
+impl From<ImportError> for ChessError {
+    fn from(value: ImportError) -> Self {
+        ChessError::Import(value)
+    }
+}
+
+impl From<IllegalMove> for ChessError {
+    fn from(value: IllegalMove) -> Self {
+        ChessError::IllegalMove(value)
+    }
+}
+
+/// A FEN string that could not be parsed, `position` is the byte offset of the offending field.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct FenError {
+    pub position: usize,
+    pub reason: FenErrorReason,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum FenErrorReason {
+    MissingField(&'static str),
+    InvalidPiece(char),
+    // number of squares described by the board field
+    WrongSquareCount(usize),
+    InvalidTurn,
+    InvalidCastling(char),
+    InvalidEnPassant,
+    InvalidClock,
+    // parsed fine, but could never come up in a game
+    IllegalPosition(Vec<PositionProblem>),
+}
+
+impl FenError {
+    pub(crate) fn new(position: usize, reason: FenErrorReason) -> Self {
+        FenError { position, reason }
+    }
+}
+
+impl Display for FenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid fen at {}: ", self.position)?;
+
+        match &self.reason {
+            FenErrorReason::MissingField(field) => { write!(f, "missing {} field", field) }
+            FenErrorReason::InvalidPiece(c) => { write!(f, "unknown piece '{}'", c) }
+            FenErrorReason::WrongSquareCount(n) => { write!(f, "board describes {} squares instead of 64", n) }
+            FenErrorReason::InvalidTurn => { write!(f, "side to move must be 'w' or 'b'") }
+            FenErrorReason::InvalidCastling(c) => { write!(f, "unknown castling right '{}'", c) }
+            FenErrorReason::InvalidEnPassant => { write!(f, "invalid en passant square") }
+            FenErrorReason::InvalidClock => { write!(f, "invalid move clock") }
+            FenErrorReason::IllegalPosition(problems) => {
+                write!(f, "impossible position: ")?;
+
+                for (i, problem) in problems.iter().enumerate() {
+                    if i != 0 { write!(f, ", ")?; }
+                    write!(f, "{}", problem)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for FenError {}
+
+/// Something about a position that no game could lead to, found by [`Game::validate`](crate::Game::validate).
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum PositionProblem {
+    // number of kings found instead of one
+    KingCount(Color, usize),
+    TooManyPawns(Color),
+    PawnOnBackRank(usize),
+    // a castling right without the king on its starting square
+    CastlingWithoutKing(Color),
+    // a castling right without a rook on this square
+    CastlingWithoutRook(usize),
+    // en passant square without a pawn that just moved past it
+    EnPassantWithoutPawn,
+    // the side that just moved left its king in check
+    OpponentInCheck,
+    // a king in check where the variant allows no checks
+    ChecksNotAllowed(Color),
+}
+
+impl Display for PositionProblem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PositionProblem::KingCount(color, n) => { write!(f, "{:?} has {} kings", color, n) }
+            PositionProblem::TooManyPawns(color) => { write!(f, "{:?} has more than 8 pawns", color) }
+            PositionProblem::PawnOnBackRank(pos) => { write!(f, "pawn on {}", Square::new(*pos)) }
+            PositionProblem::CastlingWithoutKing(color) => { write!(f, "{:?} can castle but its king has moved", color) }
+            PositionProblem::CastlingWithoutRook(pos) => { write!(f, "castling right without a rook on {}", Square::new(*pos)) }
+            PositionProblem::EnPassantWithoutPawn => { write!(f, "en passant square without a pawn that just moved") }
+            PositionProblem::OpponentInCheck => { write!(f, "side not to move is in check") }
+            PositionProblem::ChecksNotAllowed(color) => { write!(f, "{:?} is in check, which the variant doesn't allow", color) }
+        }
+    }
+}
+
+/// A failure talking to a UCI engine.
+#[derive(Debug)]
+pub enum UciError {
+    // engine process could not be started
+    Spawn(std::io::Error),
+    Io(std::io::Error),
+    // engine closed its output
+    EngineExited,
+    // engine replied with a move that could not be parsed
+    InvalidMove(String),
+    // engine thread is gone
+    Disconnected,
+    // engine did not answer in time
+    Timeout,
+    // engine answered `bestmove (none)`, there is no move to play
+    NoMove,
+    // no engine processes on this platform, like in the browser
+    Unsupported,
+    // the engine doesn't list the variant in its UCI_Variant option
+    UnsupportedVariant(String),
+}
+
+impl Display for UciError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UciError::Spawn(err) => { write!(f, "could not start engine: {}", err) }
+            UciError::Io(err) => { write!(f, "engine communication failed: {}", err) }
+            UciError::EngineExited => { write!(f, "engine exited unexpectedly") }
+            UciError::InvalidMove(mv) => { write!(f, "engine sent an unreadable move '{}'", mv) }
+            UciError::Disconnected => { write!(f, "engine thread stopped") }
+            UciError::Timeout => { write!(f, "engine did not answer in time") }
+            UciError::NoMove => { write!(f, "engine has no move in this position") }
+            UciError::Unsupported => { write!(f, "engines can't be started on this platform") }
+            UciError::UnsupportedVariant(variant) => { write!(f, "engine doesn't play {}", variant) }
+        }
+    }
+}
+
+impl std::error::Error for UciError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            UciError::Spawn(err) | UciError::Io(err) => { Some(err) }
+            _ => { None }
+        }
+    }
+}
+
+impl From<std::io::Error> for UciError {
+    fn from(value: std::io::Error) -> Self {
+        UciError::Io(value)
+    }
+}
+
+/// A move that was rejected, `reason` is one of the failing [`MoveResult`]s and `explanation` the rule
+/// it broke.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct IllegalMove {
+    pub mv: Move,
+    pub reason: MoveResult,
+    pub explanation: Option<IllegalReason>,
+}
+
+impl Display for IllegalMove {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(explanation) = self.explanation {
+            return write!(f, "illegal move {} -> {}: {}", self.mv.from, self.mv.to, explanation);
+        }
+
+        let reason = match self.reason {
+            MoveResult::MissingPromotion => { "a promotion piece is required" }
+            MoveResult::Impossible => { "there is no piece of the side to move on the starting square" }
+            _ => { "the move is not legal in this position" }
+        };
+
+        write!(f, "illegal move {} -> {}: {}", self.mv.from, self.mv.to, reason)
+    }
+}
+
+/// The rule an illegal move breaks, see [`crate::Game::explain_illegal`].
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum IllegalReason {
+    OffBoard,
+    /// There is no piece on the starting square.
+    NoPiece,
+    /// The piece on the starting square belongs to the side not to move.
+    OpponentsPiece,
+    /// The target square holds one of the mover's own pieces.
+    OwnPiece,
+    KingCapture,
+    /// The piece doesn't move that way.
+    WrongPattern,
+    /// Another piece stands in the way, for castling as well.
+    PathBlocked,
+    /// The king or the rook has moved.
+    CastlingRightsLost,
+    CastleOutOfCheck,
+    /// The king would cross an attacked square.
+    CastleThroughCheck,
+    /// The piece would leave the line between its king and the piece pinning it.
+    Pinned,
+    /// The king is in check and the move doesn't deal with it.
+    StillInCheck,
+    /// The king would be in check after the move, by moving into it or otherwise.
+    KingInCheck,
+    /// A pawn reaching the last rank has to be told what to become.
+    MissingPromotion,
+    /// The move checks the other king, in a variant without checks.
+    GivesCheck,
+    /// The game has already ended.
+    GameOver,
+}
+
+impl IllegalReason {
+    /// The [`MoveResult`] a move breaking this rule gets.
+    pub fn move_result(self) -> MoveResult {
+        match self {
+            IllegalReason::OffBoard | IllegalReason::NoPiece | IllegalReason::OpponentsPiece => { MoveResult::Impossible }
+            IllegalReason::MissingPromotion => { MoveResult::MissingPromotion }
+            _ => { MoveResult::Illegal }
+        }
+    }
+}
+
+impl Display for IllegalReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let reason = match self {
+            IllegalReason::OffBoard => { "the move leaves the board" }
+            IllegalReason::NoPiece => { "there is no piece on the starting square" }
+            IllegalReason::OpponentsPiece => { "that piece belongs to the other side" }
+            IllegalReason::OwnPiece => { "a piece can't capture its own side" }
+            IllegalReason::KingCapture => { "the king can't be captured" }
+            IllegalReason::WrongPattern => { "the piece doesn't move that way" }
+            IllegalReason::PathBlocked => { "the path is blocked" }
+            IllegalReason::CastlingRightsLost => { "castling rights are lost" }
+            IllegalReason::CastleOutOfCheck => { "you can't castle out of check" }
+            IllegalReason::CastleThroughCheck => { "the king can't castle through check" }
+            IllegalReason::Pinned => { "the piece is pinned" }
+            IllegalReason::StillInCheck => { "your king is in check" }
+            IllegalReason::KingInCheck => { "your king would be in check" }
+            IllegalReason::MissingPromotion => { "a promotion piece is required" }
+            IllegalReason::GivesCheck => { "checks aren't allowed" }
+            IllegalReason::GameOver => { "the game is over" }
+        };
+
+        write!(f, "{}", reason)
+    }
+}
+
+impl std::error::Error for IllegalMove {}
+
+/// A PGN game that could not be read, `line` starts at 1.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PgnError {
+    pub line: usize,
+    pub reason: PgnErrorReason,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum PgnErrorReason {
+    Empty,
+    InvalidTag,
+    Fen(FenError),
+    // san that is not a legal move in the position
+    UnknownMove(String),
+    // a Variant or Rules tag naming rules that don't exist
+    UnknownRules(String),
+}
+
+impl PgnError {
+    pub(crate) fn new(line: usize, reason: PgnErrorReason) -> Self {
+        PgnError { line, reason }
+    }
+}
+
+impl Display for PgnError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid pgn on line {}: ", self.line)?;
+
+        match &self.reason {
+            PgnErrorReason::Empty => { write!(f, "no game found") }
+            PgnErrorReason::InvalidTag => { write!(f, "malformed tag pair") }
+            PgnErrorReason::Fen(err) => { write!(f, "{}", err) }
+            PgnErrorReason::UnknownMove(mv) => { write!(f, "'{}' is not a legal move", mv) }
+            PgnErrorReason::UnknownRules(rules) => { write!(f, "unknown rules '{}'", rules) }
+        }
+    }
+}
+
+impl std::error::Error for PgnError {}
+
+/// An EPD line that could not be read, `line` starts at 1.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct EpdError {
+    pub line: usize,
+    pub reason: EpdErrorReason,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum EpdErrorReason {
+    Fen(FenError),
+    // a quoted operand without its closing quote
+    UnterminatedString,
+}
+
+impl EpdError {
+    pub(crate) fn new(line: usize, reason: EpdErrorReason) -> Self {
+        EpdError { line, reason }
+    }
+}
+
+impl Display for EpdError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid epd on line {}: ", self.line)?;
+
+        match &self.reason {
+            EpdErrorReason::Fen(err) => { write!(f, "{}", err) }
+            EpdErrorReason::UnterminatedString => { write!(f, "unterminated string") }
+        }
+    }
+}
+
+impl std::error::Error for EpdError {}
+
+/// Games that could not be downloaded from a chess site.
+#[derive(Debug)]
+pub enum ImportError {
+    // curl, which does the downloading, could not be started
+    Curl(std::io::Error),
+    // curl's message when the request failed, like a timeout or no connection
+    Request(String),
+    // the site answered with this http status
+    Status(u16),
+    // the site doesn't know the user, or the name can't be one
+    UnknownUser(String),
+    // no processes on this platform, like in the browser
+    Unsupported,
+}
+
+impl Display for ImportError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportError::Curl(err) => { write!(f, "could not start curl: {}", err) }
+            ImportError::Request(message) => { write!(f, "download failed: {}", message) }
+            ImportError::Status(status) => { write!(f, "the site answered with status {}", status) }
+            ImportError::UnknownUser(name) => { write!(f, "no user named '{}'", name) }
+            ImportError::Unsupported => { write!(f, "games can't be downloaded on this platform") }
+        }
+    }
+}
+
+impl std::error::Error for ImportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ImportError::Curl(err) => { Some(err) }
+            _ => { None }
+        }
+    }
+}
+
+/// A `setoption` the built-in engine can't apply.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum EngineOptionError {
+    // no option has this name
+    Unknown(String),
+    // (option, value) for a value of the wrong type or out of range
+    InvalidValue(String, String),
+    // (path, why) for an EvalFile that couldn't be loaded, the handcrafted evaluation is used instead
+    Network(String, String),
+}
+
+impl Display for EngineOptionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EngineOptionError::Unknown(name) => { write!(f, "no option named '{}'", name) }
+            EngineOptionError::InvalidValue(name, value) => { write!(f, "'{}' is not a valid value for {}", value, name) }
+            EngineOptionError::Network(path, err) => { write!(f, "{}: {}", path, err) }
+        }
+    }
+}
+
+impl std::error::Error for EngineOptionError {}
+
+/// A net file the built-in engine can't use.
+#[derive(Debug)]
+pub enum NetworkError {
+    Io(std::io::Error),
+    // the file is this many bytes, which doesn't fit any hidden layer size
+    Size(usize),
+}
+
+impl Display for NetworkError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NetworkError::Io(err) => { write!(f, "could not read the net: {}", err) }
+            NetworkError::Size(len) => { write!(f, "a net can't be {} bytes long", len) }
+        }
+    }
+}
+
+impl std::error::Error for NetworkError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            NetworkError::Io(err) => { Some(err) }
+            _ => { None }
+        }
+    }
+}
+
+/// A link to a game that can't be opened.
+#[derive(Debug)]
+pub enum LinkError {
+    // not a chess:// game link
+    Scheme,
+    Fen(FenError),
+    // a move that is unreadable or illegal where it comes
+    Move(String),
+}
+
+impl Display for LinkError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LinkError::Scheme => { write!(f, "not a chess:// game link") }
+            LinkError::Fen(err) => { write!(f, "{}", err) }
+            LinkError::Move(mv) => { write!(f, "'{}' can't be played in the game", mv) }
+        }
+    }
+}
+
+impl std::error::Error for LinkError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LinkError::Fen(err) => { Some(err) }
+            _ => { None }
+        }
+    }
+}
+
+/// A square name that is not a file letter followed by a rank number, like `e4`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseSquareError(pub String);
+
+impl Display for ParseSquareError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "'{}' is not a square", self.0)
+    }
+}
+
+impl std::error::Error for ParseSquareError {}
@@ -0,0 +1,79 @@
+//! An opening explorer: the moves played from a position, how often and how the games went, from the
+//! archive (see [`PositionIndex`](crate::position_index::PositionIndex)) or lichess' opening explorer.
+//!
+//! Lichess is asked through `curl`, like the [importer](crate::import), one position per request.
+
+use crate::chess::{Game, Move, Piece};
+use crate::error::ImportError;
+use crate::import::curl;
+use crate::json::{parse_json, Json};
+use crate::position_index::Continuation;
+use crate::square::{File, Square};
+use crate::uci::parse_move_string;
+
+// the most moves asked for at once
+const MOVES: usize = 12;
+
+/// The lichess database to ask.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum LichessDatabase {
+    /// Games played on lichess.
+    Lichess,
+    /// Over the board games between masters.
+    Masters,
+}
+
+impl LichessDatabase {
+    pub const ALL: [LichessDatabase; 2] = [LichessDatabase::Lichess, LichessDatabase::Masters];
+    pub const NAMES: [&'static str; 2] = ["Lichess", "Masters"];
+
+    /// The explorer's address for `position`.
+    pub fn url(self, position: &Game) -> String {
+        let fen = position.as_fen().replace(' ', "%20");
+
+        match self {
+            LichessDatabase::Lichess => {
+                format!("https://explorer.lichess.ovh/lichess?variant=standard&fen={}&moves={}&topGames=0&recentGames=0", fen, MOVES)
+            }
+            LichessDatabase::Masters => { format!("https://explorer.lichess.ovh/masters?fen={}&moves={}&topGames=0", fen, MOVES) }
+        }
+    }
+}
+
+/// The moves played from `position` in `database`, most played first.
+pub fn lichess_moves(database: LichessDatabase, position: &Game) -> Result<Vec<Continuation>, ImportError> {
+    let (status, body) = curl(&database.url(position), "application/json")?;
+    if status != 200 { return Err(ImportError::Status(status)); }
+
+    parse_lichess_moves(position, &body).ok_or_else(|| ImportError::Request("unexpected reply from the explorer".to_string()))
+}
+
+/// Reads the moves of an explorer reply about `position`. None if it isn't one.
+pub fn parse_lichess_moves(position: &Game, text: &str) -> Option<Vec<Continuation>> {
+    let Json::Object(root) = parse_json(&mut text.chars().peekable())? else { return None; };
+    let Some((_, Json::Array(moves))) = root.into_iter().find(|x| x.0 == "moves") else { return None; };
+
+    moves.into_iter().map(|mv| {
+        let Json::Object(fields) = mv else { return None; };
+        let field = |name: &str| fields.iter().find(|x| x.0 == name).map(|x| &x.1);
+        let count = |name: &str| match field(name) {
+            Some(Json::Number(x)) => { Some(*x as usize) }
+            _ => { None }
+        };
+
+        let Some(Json::String(uci)) = field("uci") else { return None; };
+        let (white_wins, draws, black_wins) = (count("white")?, count("draws")?, count("black")?);
+
+        Some(Continuation { mv: castle_to_king_square(position, parse_move_string(uci)?), games: white_wins + draws + black_wins,
+                            white_wins, draws, black_wins })
+    }).collect()
+}
+
+// lichess writes castling as the king taking its own rook, e1h1, the move here is the king's two steps
+fn castle_to_king_square(position: &Game, mv: Move) -> Move {
+    let (Some(piece), Some(target)) = (position.board[mv.from], position.board[mv.to]) else { return mv; };
+    if !matches!(piece, Piece::WKing | Piece::BKing) || target.color() != piece.color() { return mv; }
+
+    let file = if mv.to.file().index() > mv.from.file().index() { File::new(6) } else { File::new(2) };
+    Move::new(mv.from, Square::at(file, mv.from.rank()), None)
+}
@@ -0,0 +1,195 @@
+//! Random legal positions, for property tests of the move generator and the FEN code.
+//!
+//! [`Game::random_legal_position`] scatters pieces over the board within some [`PositionConstraints`]
+//! and keeps only positions [`Game::validate`] accepts. [`invariant_violations`] checks the things that
+//! have to hold in every such position, so a test only has to generate positions and assert that
+//! nothing is broken. Generation is seeded by an [`Rng`], a failing seed gives the same position again.
+
+use crate::chess::{Color, Game, Move, Piece};
+use crate::square::{Rank, Square};
+
+/// A small seeded random number generator (xorshift). Not for anything but tests.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // xorshift never leaves zero, and small seeds start out poorly mixed
+        Rng { state: seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1 }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+
+        self.state
+    }
+
+    /// A number from 0 up to `n`, `n` left out.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n.max(1) as u64) as usize
+    }
+
+    pub fn chance(&mut self, percent: usize) -> bool {
+        self.below(100) < percent
+    }
+}
+
+/// What positions [`Game::random_legal_position`] makes.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PositionConstraints {
+    /// The pieces besides the two kings, at least...
+    pub min_pieces: usize,
+    /// ...and at most, no more than 30.
+    pub max_pieces: usize,
+    pub pawns: bool,
+    /// Castling rights, where king and rook are still at home.
+    pub castling: bool,
+    /// An en passant square, where a pawn could just have moved two squares.
+    pub en_passant: bool,
+    /// Whether the side to move may be in check.
+    pub checks: bool,
+    /// The side to move, either if None.
+    pub turn: Option<Color>,
+}
+
+impl Default for PositionConstraints {
+    fn default() -> Self {
+        PositionConstraints { min_pieces: 0, max_pieces: 16, pawns: true, castling: true, en_passant: true, checks: true, turn: None }
+    }
+}
+
+const WHITE_PIECES: [Piece; 5] = [Piece::WPawn, Piece::WKnight, Piece::WBishop, Piece::WRook, Piece::WQueen];
+const BLACK_PIECES: [Piece; 5] = [Piece::BPawn, Piece::BKnight, Piece::BBishop, Piece::BRook, Piece::BQueen];
+// (right, king, king square, rook, rook square)
+const CASTLING: [(char, Piece, usize, Piece, usize); 4] = [
+    ('K', Piece::WKing, 4, Piece::WRook, 7), ('Q', Piece::WKing, 4, Piece::WRook, 0),
+    ('k', Piece::BKing, 60, Piece::BRook, 63), ('q', Piece::BKing, 60, Piece::BRook, 56),
+];
+
+impl Game {
+    /// A random position within `constraints` that [`Game::validate`] accepts, with random castling
+    /// rights, en passant square and clocks where they are allowed.
+    pub fn random_legal_position(rng: &mut Rng, constraints: PositionConstraints) -> Game {
+        loop {
+            if let Some(game) = random_position(rng, constraints) { return game; }
+        }
+    }
+}
+
+// one try at a position, None if it came out illegal
+fn random_position(rng: &mut Rng, constraints: PositionConstraints) -> Option<Game> {
+    let mut board: [Option<Piece>; 64] = [None; 64];
+
+    // castling needs king and rook at home, which they rarely are by chance
+    let mut home = |square| if constraints.castling && rng.chance(25) { square } else { rng.below(64) };
+    let white_king = home(4);
+    let black_king = home(60);
+    let (a, b) = (Square::new(white_king), Square::new(black_king));
+    if a.file().index().abs_diff(b.file().index()) <= 1 && a.rank().index().abs_diff(b.rank().index()) <= 1 { return None; }
+    board[white_king] = Some(Piece::WKing);
+    board[black_king] = Some(Piece::BKing);
+
+    let max = constraints.max_pieces.min(30);
+    let min = constraints.min_pieces.min(max);
+    let count = min + rng.below(max - min + 1);
+    let kinds = if constraints.pawns { 0 } else { 1 };
+
+    // pieces besides the king and pawns of each side, white's and black's
+    let mut pieces = [0, 0];
+    let mut pawns = [0, 0];
+
+    for (_, king, king_square, rook, rook_square) in CASTLING {
+        if board[king_square] != Some(king) || board[rook_square].is_some() || pieces[0] + pieces[1] == count || !rng.chance(50) { continue; }
+
+        board[rook_square] = Some(rook);
+        pieces[if rook == Piece::WRook { 0 } else { 1 }] += 1;
+    }
+
+    while pieces[0] + pieces[1] < count {
+        let side = rng.below(2);
+        let piece = if side == 0 { WHITE_PIECES } else { BLACK_PIECES }[kinds + rng.below(5 - kinds)];
+        let pawn = matches!(piece, Piece::WPawn | Piece::BPawn);
+
+        if pieces[side] == 15 || (pawn && pawns[side] == 8) { continue; }
+
+        let square = if pawn { 8 + rng.below(48) } else { rng.below(64) };
+        if board[square].is_some() { continue; }
+
+        board[square] = Some(piece);
+        pieces[side] += 1;
+        if pawn { pawns[side] += 1; }
+    }
+
+    let turn = constraints.turn.unwrap_or(if rng.chance(50) { Color::White } else { Color::Black });
+
+    let castling: String = CASTLING.iter()
+        .filter(|(_, king, king_square, rook, rook_square)| board[*king_square] == Some(*king) && board[*rook_square] == Some(*rook))
+        .filter(|_| constraints.castling && rng.chance(50))
+        .map(|x| x.0)
+        .collect();
+
+    // a pawn of the side that just moved, with the two squares it came over empty
+    let (pawn, rank, step): (Piece, usize, isize) = match turn {
+        Color::White => { (Piece::BPawn, 4, 8) }
+        Color::Black => { (Piece::WPawn, 3, -8) }
+    };
+    let pushed: Vec<usize> = (0..64).filter(|x| board[*x] == Some(pawn) && Square::new(*x).rank() == Rank::new(rank))
+        .filter(|x| board[x.wrapping_add_signed(step)].is_none() && board[x.wrapping_add_signed(2 * step)].is_none())
+        .collect();
+    let en_passant = if constraints.en_passant && !pushed.is_empty() && rng.chance(30) {
+        Some(Square::new(pushed[rng.below(pushed.len())].wrapping_add_signed(step)))
+    } else {
+        None
+    };
+
+    let mut game = Game::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").ok()?;
+    for (square, piece) in board.into_iter().enumerate() { game.board[square] = piece; }
+    let placement = game.as_fen().split_whitespace().next()?.to_string();
+
+    let half_moves = if en_passant.is_some() { 0 } else { rng.below(50) };
+    let fen = format!("{} {} {} {} {} {}", placement, if turn == Color::White { 'w' } else { 'b' },
+                      if castling.is_empty() { "-" } else { &castling }, en_passant.map_or("-".to_string(), |x| x.to_string()),
+                      half_moves, 1 + rng.below(100));
+
+    let game = Game::from_fen(fen).ok()?;
+    if !constraints.checks && game.is_in_check(game.turn) { return None; }
+
+    Some(game)
+}
+
+/// What is wrong with the position or the moves generated for it, empty if nothing is. Checks that the
+/// position is valid, survives a FEN round trip, that every legal move is accepted and leaves a valid
+/// position, and that every other move the pieces could make leaves the mover's king attacked.
+pub fn invariant_violations(game: &Game) -> Vec<String> {
+    let mut violations: Vec<String> = game.validate().into_iter().map(|x| format!("invalid position: {}", x)).collect();
+    let fen = game.as_fen();
+
+    match Game::from_fen(&fen) {
+        Ok(parsed) if parsed == *game => {}
+        Ok(parsed) => { violations.push(format!("{} reads back as {}", fen, parsed.as_fen())); }
+        Err(err) => { violations.push(format!("{} doesn't read back: {}", fen, err)); }
+    }
+
+    let legal: Vec<Move> = game.legal_moves().collect();
+    for mv in &legal {
+        let mut after = *game;
+        match after.make_move(*mv) {
+            Ok(_) if after.validate().is_empty() => {}
+            Ok(_) => { violations.push(format!("{} leads to an invalid position, {}", mv, after.as_fen())); }
+            Err(err) => { violations.push(format!("{} is generated but refused: {}", mv, err)); }
+        }
+    }
+
+    for mv in game.pseudo_legal_moves().filter(|x| !legal.contains(x)) {
+        let mut after = *game;
+        after.play_unchecked(mv);
+        if !after.is_in_check(game.turn) { violations.push(format!("{} is left out but doesn't leave the king attacked", mv)); }
+    }
+
+    violations
+}
@@ -0,0 +1,316 @@
+use std::collections::HashMap;
+use std::sync::mpsc::{channel, Receiver};
+use macroquad::prelude::*;
+use macroquad::hash;
+use macroquad::ui::{root_ui, widgets};
+use chess::analysis::{win_percent, Analyser};
+use chess::archive::Archive;
+use chess::pgn::san;
+use chess::explorer::{lichess_moves, LichessDatabase};
+use chess::position_index::{Continuation, PositionIndex, PositionSearch};
+use chess::uci::{EngineConfig, ThreadedUciBuilder};
+use chess::{Game, Move, Piece, Promotion, Square};
+use crate::gui::archive::engine_line;
+use crate::gui::{draw_pv, mouse_square, square_corner, ARCHIVE_DIR};
+use crate::gui::assets::Assets;
+use crate::gui::i18n::{tr, tr_color, trf};
+
+// lines of a position search: moves played from the position and games that reached it
+const SEARCH_MOVES: usize = 6;
+const SEARCH_GAMES: usize = 5;
+
+// a board to try ideas on, not a game: either side's pieces can be moved at any time and the engine
+// analyses whatever is on the board. with legal moves off, pieces go wherever they are put
+pub(crate) async fn analysis_board(assets: &Assets, engine: Option<EngineConfig>, start: Game) {
+    let renderer = assets.renderer;
+
+    request_new_screen_size(1280.0, 768.0);
+    next_frame().await;
+
+    let mut analyser = Analyser::new(match engine {
+        Some(config) => { ThreadedUciBuilder::new().config(config) }
+        None => { ThreadedUciBuilder::new() }
+    });
+
+    // every position the board has been in, the last one is on the board
+    let mut positions = vec![start];
+    let mut legal = true;
+    let mut selected: Option<Square> = None;
+    let mut status = String::new();
+    // the archive, indexed when it's first needed, and the search of the position on the board if shown
+    let mut index: Option<Result<PositionIndex, String>> = None;
+    let mut show_games = false;
+    let mut found: Option<(u64, PositionSearch)> = None;
+    let mut explorer = Explorer::default();
+
+    loop {
+        clear_background(GRAY);
+
+        let position = *positions.last().expect("the start is never taken back");
+        renderer.draw_position(&position, 96.0);
+
+        if let Some(square) = selected {
+            let corner = square_corner(square);
+            draw_rectangle_lines(corner.x, corner.y, 96.0, 96.0, 4.0, YELLOW);
+        }
+
+        draw_text(tr("analysis.title"), 778.0, 30.0, 28.0, WHITE);
+        draw_text(&trf("analysis.to_move", &[tr_color(position.turn)]), 778.0, 58.0, 24.0, WHITE);
+
+        // the engine only gets positions it can make sense of
+        let problems = position.validate();
+        if problems.is_empty() {
+            analyser.follow(position);
+
+            // white's share of the bar is its winning chances
+            let white = analyser.white_score().map(|x| win_percent(x.centipawns()) / 100.0).unwrap_or(0.5) * 768.0;
+            draw_rectangle(768.0, 0.0, 10.0, 768.0, BLACK);
+            draw_rectangle(768.0, 768.0 - white, 10.0, white, WHITE);
+
+            if let Some(err) = analyser.error() {
+                draw_text(&err.to_string(), 778.0, 90.0, 20.0, WHITE);
+            } else {
+                draw_text(&engine_line(&position, analyser.info(), None), 778.0, 90.0, 20.0, WHITE);
+            }
+            if let Some(info) = analyser.info() { draw_pv(&info.pv_moves(&position)); }
+        } else {
+            analyser.pause();
+
+            draw_text(tr("analysis.illegal"), 778.0, 90.0, 20.0, WHITE);
+            for (i, problem) in problems.iter().enumerate().take(4) {
+                draw_text(&problem.to_string(), 778.0, 112.0 + i as f32 * 22.0, 20.0, WHITE);
+            }
+        }
+
+        if let Some(mv) = free_board_move(&position, &mut selected) {
+            match play(position, mv, legal) {
+                Ok((next, text)) => {
+                    positions.push(next);
+                    status = text;
+                }
+                Err(err) => { status = err; }
+            }
+        }
+
+        widgets::Checkbox::new(hash!()).label(tr("analysis.legal")).pos(vec2(778.0, 200.0)).ui(&mut root_ui(), &mut legal);
+
+        if root_ui().button(vec2(778.0, 250.0), tr("analysis.pass")) {
+            // the other side moves next, en passant goes with the move given up
+            let mut next = position;
+            (next.turn, next.en_passant) = (!next.turn, None);
+            positions.push(next);
+            status = trf("analysis.passes", &[tr_color(position.turn)]);
+        }
+
+        if (root_ui().button(vec2(778.0, 300.0), tr("analysis.undo")) || is_key_pressed(KeyCode::Backspace)) && positions.len() > 1 {
+            positions.pop();
+            (selected, status) = (None, String::new());
+        }
+
+        if root_ui().button(vec2(778.0, 350.0), tr("analysis.reset")) {
+            positions.truncate(1);
+            (selected, status) = (None, String::new());
+        }
+
+        if root_ui().button(vec2(778.0, 390.0), tr(if show_games { "analysis.hide_games" } else { "analysis.find_games" })) {
+            show_games = !show_games;
+        }
+        if show_games || explorer.source == 0 {
+            index.get_or_insert_with(|| Archive::open(ARCHIVE_DIR).and_then(|x| PositionIndex::from_archive(&x)).map_err(|x| x.to_string()));
+        }
+
+        if problems.is_empty() {
+            if let Some(mv) = explorer.draw(index.as_ref(), &position) {
+                match play(position, mv, true) {
+                    Ok((next, text)) => {
+                        positions.push(next);
+                        (selected, status) = (None, text);
+                    }
+                    Err(err) => { status = err; }
+                }
+            }
+        }
+
+        if !status.is_empty() {
+            draw_text(&status, 778.0, 440.0, 20.0, WHITE);
+        }
+
+        match index.as_ref().filter(|_| show_games) {
+            Some(Ok(index)) => {
+                let hash = position.zobrist();
+                if found.as_ref().is_none_or(|x| x.0 != hash) { found = Some((hash, index.search(&position))); }
+                if let Some((_, search)) = &found { draw_search(index, &position, search); }
+            }
+            Some(Err(err)) => { draw_text(&trf("analysis.archive_failed", &[err]), 778.0, 470.0, 18.0, WHITE); }
+            None => {}
+        }
+
+        if root_ui().button(vec2(778.0, 710.0), tr("back")) || is_key_pressed(KeyCode::Escape) {
+            return;
+        }
+
+        next_frame().await;
+    }
+}
+
+// the games that reached the position on the board and the moves played from it, scored for the side to move
+fn draw_search(index: &PositionIndex, position: &Game, search: &PositionSearch) {
+    let games = search.occurrences.len();
+    let title = match games {
+        0 => { tr("analysis.not_found").to_string() }
+        1 => { tr("analysis.found_once").to_string() }
+        _ => { trf("analysis.found", &[&games.to_string()]) }
+    };
+    draw_text(&title, 778.0, 470.0, 22.0, WHITE);
+
+    let mut y = 494.0;
+    for continuation in search.continuations.iter().take(SEARCH_MOVES) {
+        let score = continuation.score(position.turn).map(|x| format!(", {:.0}%", x * 100.0)).unwrap_or_default();
+        draw_text(&trf("analysis.continuation", &[&san(position, continuation.mv), &continuation.games.to_string(), &score]), 778.0, y, 18.0, WHITE);
+        y += 20.0;
+    }
+
+    y += 6.0;
+    for occurrence in search.occurrences.iter().take(SEARCH_GAMES) {
+        let game = &index.games[occurrence.game];
+        draw_text(&trf("analysis.occurrence", &[&game.summary(), &index.move_number(occurrence).to_string()]), 778.0, y, 16.0, LIGHTGRAY);
+        y += 18.0;
+    }
+}
+
+// locale keys of the explorer's sources, the archive and then the lichess databases
+const EXPLORER_SOURCES: [&str; 3] = ["explorer.my_games", "explorer.lichess", "explorer.masters"];
+const EXPLORER_MOVES: usize = 12;
+
+type ExplorerReply = Result<Vec<Continuation>, String>;
+
+// moves played from the position on the board with their results, clickable to play them. lichess is
+// asked once per position, one request at a time
+#[derive(Default)]
+struct Explorer {
+    // into `EXPLORER_SOURCES`
+    source: usize,
+    // the replies by (source, zobrist hash of the position)
+    replies: HashMap<(usize, u64), ExplorerReply>,
+    request: Option<Receiver<((usize, u64), ExplorerReply)>>,
+}
+
+impl Explorer {
+    // the moves of the current source, None while they're on their way
+    fn moves(&mut self, index: Option<&Result<PositionIndex, String>>, position: &Game) -> Option<ExplorerReply> {
+        let key = (self.source, position.zobrist());
+        if self.source == 0 {
+            let index = index?;
+            let reply = self.replies.entry(key).or_insert_with(|| index.as_ref().map(|x| x.search(position).continuations).map_err(String::clone));
+            return Some(reply.clone());
+        }
+
+        if let Some(Ok((key, reply))) = self.request.as_ref().map(|x| x.try_recv()) {
+            self.replies.insert(key, reply);
+            self.request = None;
+        }
+        if let Some(reply) = self.replies.get(&key) { return Some(reply.clone()); }
+
+        if self.request.is_none() {
+            let (s, rx) = channel();
+            let (database, position) = (LichessDatabase::ALL[self.source - 1], *position);
+
+            chess::platform::spawn(move || { let _ = s.send((key, lichess_moves(database, &position).map_err(|x| x.to_string()))); });
+            self.request = Some(rx);
+        }
+        None
+    }
+
+    // the column right of the analysis, returns the move clicked
+    fn draw(&mut self, index: Option<&Result<PositionIndex, String>>, position: &Game) -> Option<Move> {
+        draw_text(tr("explorer.title"), 1034.0, 30.0, 28.0, WHITE);
+        if root_ui().button(vec2(1034.0, 44.0), trf("explorer.source", &[tr(EXPLORER_SOURCES[self.source])])) {
+            self.source = (self.source + 1) % EXPLORER_SOURCES.len();
+        }
+
+        let moves = match self.moves(index, position) {
+            None => {
+                draw_text(tr("explorer.asking"), 1034.0, 100.0, 20.0, WHITE);
+                return None;
+            }
+            Some(Err(err)) => {
+                draw_text(&err, 1034.0, 100.0, 18.0, WHITE);
+                return None;
+            }
+            Some(Ok(moves)) => { moves }
+        };
+
+        let games: usize = moves.iter().map(|x| x.games).sum();
+        draw_text(&trf("explorer.games", &[&games.to_string()]), 1034.0, 100.0, 20.0, WHITE);
+        draw_text(tr("explorer.columns"), 1100.0, 124.0, 16.0, LIGHTGRAY);
+
+        let mut clicked = None;
+        for (i, continuation) in moves.iter().take(EXPLORER_MOVES).enumerate() {
+            let y = 134.0 + i as f32 * 30.0;
+            if root_ui().button(vec2(1034.0, y), san(position, continuation.mv)) { clicked = Some(continuation.mv); }
+
+            let finished = (continuation.white_wins + continuation.draws + continuation.black_wins).max(1) as f32;
+            let percent = |x: usize| x as f32 * 100.0 / finished;
+            draw_text(&format!("{}  {:.0} / {:.0} / {:.0}", continuation.games, percent(continuation.white_wins),
+                               percent(continuation.draws), percent(continuation.black_wins)), 1100.0, y + 16.0, 18.0, WHITE);
+        }
+        if moves.is_empty() {
+            draw_text(tr("explorer.none"), 1034.0, 134.0, 20.0, WHITE);
+        }
+
+        clicked
+    }
+}
+
+// `mv` played on `position` by whichever side the piece is, and a line saying what happened
+fn play(position: Game, mv: Move, legal: bool) -> Result<(Game, String), String> {
+    let mut next = position;
+    let mover = position.board[mv.from].map(|x| x.color()).ok_or_else(|| tr("analysis.no_piece").to_string())?;
+
+    if !legal {
+        let taken = next.free_move(mv.from, mv.to).flatten();
+        // a pawn left on the last rank would make the position impossible
+        if let Some(promotion) = mv.promotion { next.board[mv.to] = Some(Piece::from_promotion(promotion, mover)); }
+
+        let text = match taken {
+            Some(piece) => { trf("analysis.takes", &[tr_color(mover), &mv.to_string(), &format!("{:?}", piece)]) }
+            None => { format!("{} {}", tr_color(mover), mv) }
+        };
+        return Ok((next, text));
+    }
+
+    // either side can move, the other one is passed the turn first
+    if mover != next.turn {
+        (next.turn, next.en_passant) = (mover, None);
+        if !next.validate().is_empty() { return Err(trf("analysis.in_check", &[tr_color(!mover)])); }
+    }
+
+    let text = san(&next, mv);
+    next.make_move(mv).map_err(|x| x.to_string())?;
+
+    Ok((next, trf("analysis.played", &[tr_color(mover), &text])))
+}
+
+// a click on any piece, either side's, and then the square to put it on. pawns reaching the end promote to queens
+fn free_board_move(position: &Game, selected: &mut Option<Square>) -> Option<Move> {
+    if is_mouse_button_pressed(MouseButton::Right) { *selected = None; }
+    if !is_mouse_button_pressed(MouseButton::Left) { return None; }
+
+    let square = mouse_square()?;
+    let color = |x: Square| position.board[x].map(|x| x.color());
+
+    match selected.take() {
+        Some(from) if from != square && color(from) != color(square) => {
+            let pawn = matches!(position.board[from], Some(Piece::WPawn | Piece::BPawn));
+            let promotion = (pawn && !(8..56).contains(&square.index())).then_some(Promotion::Queen);
+
+            Some(Move::new(from, square, promotion))
+        }
+        Some(from) if from == square => { None }
+        // nothing picked yet, or another piece of the same side instead
+        _ => {
+            if position.board[square].is_some() { *selected = Some(square); }
+            None
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use macroquad::prelude::*;
+use chess::{Game, Piece, Square};
+use crate::gui::render::PieceSprites;
+
+// seconds a piece takes to move at normal speed
+const ANIMATION_TIME: f32 = 0.1;
+// seconds a mated king takes to fall over
+pub(crate) const MATE_TIME: f32 = 0.8;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub(crate) enum AnimationSpeed {
+    Instant,
+    Fast,
+    #[default]
+    Normal,
+    Slow,
+}
+
+impl AnimationSpeed {
+    pub(crate) const ALL: [AnimationSpeed; 4] = [AnimationSpeed::Instant, AnimationSpeed::Fast, AnimationSpeed::Normal, AnimationSpeed::Slow];
+    pub(crate) const NAMES: [&'static str; 4] = ["Instant", "Fast", "Normal", "Slow"];
+    // locale keys of the names shown in the settings, `NAMES` are what's saved
+    pub(crate) const KEYS: [&'static str; 4] = ["speed.instant", "speed.fast", "speed.normal", "speed.slow"];
+
+    // seconds a single piece move takes
+    pub(crate) fn move_time(self) -> f32 {
+        match self {
+            AnimationSpeed::Instant => { 0.0 }
+            AnimationSpeed::Fast => { ANIMATION_TIME / 2.0 }
+            AnimationSpeed::Normal => { ANIMATION_TIME }
+            AnimationSpeed::Slow => { ANIMATION_TIME * 2.0 }
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub(crate) enum Easing {
+    Linear,
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    fn apply(self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => { t }
+            Easing::EaseOut => { 1.0 - (1.0 - t).powi(3) }
+            Easing::EaseInOut => {
+                if t < 0.5 { 4.0 * t * t * t } else { 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0 }
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) enum AnimationType {
+    // end_pos, no_render_pos
+    Move(f32, f32, usize, usize),
+    // same as move, but along the knight's L, the long leg first
+    KnightMove(f32, f32, usize, usize),
+    Disappear,
+    // a move that wasn't allowed: the piece shakes and the square it was sent to flashes.
+    // target position, no_render_pos, flash color
+    Reject(f32, f32, usize, usize, Color),
+}
+
+#[derive(Debug)]
+pub(crate) struct Animation {
+    animation_type: AnimationType,
+    piece: Piece,
+    position: (f32, f32),
+    // seconds to wait before starting, so animations can be chained
+    delay: f32,
+    elapsed: f32,
+    duration: f32,
+    easing: Easing,
+}
+
+impl Animation {
+    fn new(animation_type: AnimationType, piece: Piece, position: (f32, f32), duration: f32, easing: Easing) -> Self {
+        Animation { animation_type, piece, position, delay: 0.0, elapsed: 0.0, duration, easing }
+    }
+
+    // start after the given animation has finished
+    fn after(mut self, other: &Animation) -> Self {
+        self.delay = other.delay + other.duration;
+        self
+    }
+
+    // moves the animation on by `frame_time` seconds and draws it, false once it's over
+    pub(crate) fn draw_frame(&mut self, pieces: &PieceSprites, frame_time: f32) -> bool {
+        self.elapsed += frame_time;
+
+        if self.elapsed >= self.delay + self.duration {
+            // animation is over
+            return false;
+        }
+
+        let progress = self.easing.apply(((self.elapsed - self.delay) / self.duration).max(0.0));
+
+        match self.animation_type {
+            AnimationType::Move(ex, ey, _, _) => {
+                pieces.draw(self.piece,
+                                 (ex - self.position.0) * progress + self.position.0,
+                                 (ey - self.position.1) * progress + self.position.1,
+                                 WHITE);
+            }
+            AnimationType::KnightMove(ex, ey, _, _) => {
+                let (dx, dy) = (ex - self.position.0, ey - self.position.1);
+
+                // the long leg is two thirds of the path
+                let (long, short) = ((progress * 1.5).min(1.0), (progress * 3.0 - 2.0).max(0.0));
+                let (x, y) = if dx.abs() > dy.abs() {
+                    (dx * long, dy * short)
+                } else {
+                    (dx * short, dy * long)
+                };
+
+                pieces.draw(self.piece, self.position.0 + x, self.position.1 + y, WHITE);
+            }
+            AnimationType::Disappear => {
+                pieces.draw(self.piece, self.position.0, self.position.1,
+                                 Color::new(1.0, 1.0, 1.0, 1.0 - progress))
+            }
+            AnimationType::Reject(tx, ty, _, _, mut color) => {
+                let size = pieces.size();
+
+                color.a *= 1.0 - progress;
+                draw_rectangle(tx, ty, size, size, color);
+
+                // three shakes, dying down
+                let shake = (progress * std::f32::consts::TAU * 3.0).sin() * (1.0 - progress) * size / 10.0;
+                pieces.draw(self.piece, self.position.0 + shake, self.position.1, WHITE);
+            }
+        }
+
+        true
+    }
+
+    pub(crate) fn render_exception(&self) -> Option<(usize, usize)> {
+        match self.animation_type {
+            AnimationType::Move(_, _, ux, uy) | AnimationType::KnightMove(_, _, ux, uy) | AnimationType::Reject(_, _, ux, uy, _) => { Some((ux, uy)) }
+            _ => { None }
+        }
+    }
+
+    pub(crate) fn is_reject(&self) -> bool {
+        matches!(self.animation_type, AnimationType::Reject(..))
+    }
+}
+
+pub(crate) fn primary_animation(game: &Game, from: usize, to: usize, time: f32,
+                                render_location: impl FnOnce(usize) -> (f32, f32) + Copy,
+                                block_location: impl FnOnce(usize) -> (usize, usize)) -> Option<Animation> {
+    let piece = game.board[from]?;
+
+    let (ex, ey) = render_location(to);
+    let (ux, uy) = block_location(to);
+
+    let animation_type = match piece {
+        Piece::WKnight | Piece::BKnight => { AnimationType::KnightMove(ex, ey, ux, uy) }
+        _ => { AnimationType::Move(ex, ey, ux, uy) }
+    };
+
+    Some(Animation::new(animation_type, piece, render_location(from), time, Easing::EaseInOut))
+}
+
+// the rook of a castle or a captured piece, both waiting for the primary animation to finish
+pub(crate) fn secondary_animation(game: &Game, from: usize, to: usize, primary: Option<&Animation>, time: f32,
+                                  render_location: impl FnOnce(usize) -> (f32, f32) + Copy,
+                                  block_location: impl FnOnce(usize) -> (usize, usize)) -> Option<Animation> {
+    let piece = game.board[from]?;
+
+    let chain = |animation: Animation| match primary {
+        Some(primary) => { animation.after(primary) }
+        None => { animation }
+    };
+
+    // check if move is en_passant
+    if let Some(lost_pos) = game.en_passant_pawn().filter(|_| game.en_passant == Some(Square::new(to))) {
+        if piece == Piece::BPawn || piece == Piece::WPawn {
+            let lost = game.board[lost_pos]?;
+
+            return Some(chain(Animation::new(AnimationType::Disappear, lost,
+                                             render_location(lost_pos.index()), time, Easing::EaseOut)));
+        }
+    }
+
+    if (piece == Piece::BKing || piece == Piece::WKing) && Square::new(to).file().distance(Square::new(from).file()) == 2 {
+        let (rook_from, rook_to) = if Square::new(to).file() > Square::new(from).file() {
+            (from + 3, to - 1)
+        } else {
+            (from - 4, to + 1)
+        };
+
+        let (ex, ey) = render_location(rook_to);
+        let (ux, uy) = block_location(rook_to);
+
+        let rook = game.board[rook_from]?;
+
+        return Some(chain(Animation::new(AnimationType::Move(ex, ey, ux, uy), rook,
+                                         render_location(rook_from), time, Easing::EaseInOut)));
+    }
+
+    if let Some(taken) = game.board[to] {
+        return Some(chain(Animation::new(AnimationType::Disappear, taken, render_location(to), time, Easing::EaseOut)));
+    }
+
+    None
+}
+
+// a glow around the king in check at `center`, pulsing with `time` (seconds)
+pub(crate) fn draw_check_glow(center: (f32, f32), radius: f32, glow: Color, time: f64) {
+    let pulse = 0.75 + 0.25 * (time * 5.0).sin() as f32;
+
+    // rings on top of each other, brightest in the middle
+    for i in 0..4 {
+        let color = Color { a: glow.a * pulse * 0.5, ..glow };
+        draw_circle(center.0, center.1, radius * (1.0 - i as f32 * 0.2), color);
+    }
+}
+
+// a mated king lying on its side, `progress` is 0 as it starts falling and 1 (or more) once it has fallen
+pub(crate) fn draw_toppled_king(pieces: &PieceSprites, king: Piece, corner: (f32, f32), progress: f32) {
+    let t = Easing::EaseOut.apply(progress.clamp(0.0, 1.0));
+    let size = pieces.size();
+
+    // falls over to the right and sinks toward the bottom of its square, fading a little
+    pieces.draw_ex(king, corner.0 + t * size / 8.0, corner.1 + t * size / 6.0, Color::new(1.0, 1.0, 1.0, 1.0 - t * 0.4),
+                   DrawTextureParams { rotation: t * std::f32::consts::FRAC_PI_2, ..Default::default() });
+}
+
+// the piece on `from` refusing to go to `to`
+pub(crate) fn reject_animation(game: &Game, from: usize, to: usize, flash: Color, time: f32,
+                               render_location: impl FnOnce(usize) -> (f32, f32) + Copy,
+                               block_location: impl FnOnce(usize) -> (usize, usize)) -> Option<Animation> {
+    let piece = game.board[from]?;
+
+    let (tx, ty) = render_location(to);
+    let (ux, uy) = block_location(from);
+
+    Some(Animation::new(AnimationType::Reject(tx, ty, ux, uy, flash), piece, render_location(from), time * 4.0, Easing::Linear))
+}
@@ -0,0 +1,455 @@
+use macroquad::prelude::*;
+use macroquad::hash;
+use macroquad::ui::{root_ui, widgets};
+use chess::archive::{Archive, ArchivedGame, GameInfo};
+use chess::bookmarks::{Bookmark, BookmarkLibrary};
+use chess::pgn::{san, san_line, Pgn};
+use chess::study::{Arrow, Highlight, MarkColor, Study};
+use chess::tree::NodePath;
+use chess::uci::{AnalysisCache, CachedAnalysis, Limits, Score, ThreadedUci, UciInfo};
+use chess::{Color, Game, Move, Square};
+use crate::gui::{ARCHIVE_DIR, BOOKMARKS_FILE, board_move, draw_markup, draw_pv, mouse_square, show_error, square_corner};
+use crate::gui::assets::Assets;
+use crate::gui::i18n::{tr, trf};
+
+const GAMES_PER_PAGE: usize = 12;
+// the longest engine mates the solver proves, longer ones take too long to search between frames
+const PROOF_MOVES: i32 = 3;
+
+// browse, filter, replay and delete archived games
+// returns a position to play a game from, picked while replaying one
+pub(crate) async fn archive_browser(assets: &Assets) -> Option<Game> {
+    request_new_screen_size(1024.0, 768.0);
+    next_frame().await;
+
+    let archive = match Archive::open(ARCHIVE_DIR) {
+        Ok(archive) => { archive }
+        Err(err) => {
+            show_error(&trf("archive.open_failed", &[&err.to_string()])).await;
+            return None;
+        }
+    };
+
+    let mut games = archive.games().unwrap_or_default();
+    let mut filter = String::new();
+    let mut page = 0;
+
+    loop {
+        clear_background(GRAY);
+
+        widgets::InputText::new(hash!())
+            .label(tr("archive.filter"))
+            .position(vec2(10.0, 10.0))
+            .size(vec2(600.0, 40.0))
+            .ui(&mut root_ui(), &mut filter);
+
+        let matching: Vec<usize> = (0..games.len()).filter(|x| games[*x].matches(&filter)).collect();
+        let pages = matching.len().div_ceil(GAMES_PER_PAGE).max(1);
+        page = page.min(pages - 1);
+
+        if matching.is_empty() {
+            draw_text(tr("archive.none"), 10.0, 90.0, 32.0, WHITE);
+        }
+
+        let mut opened = None;
+        for (i, index) in matching.iter().skip(page * GAMES_PER_PAGE).take(GAMES_PER_PAGE).enumerate() {
+            if root_ui().button(vec2(10.0, 60.0 + i as f32 * 50.0), games[*index].summary().as_str()) {
+                opened = Some(*index);
+            }
+        }
+
+        if let Some(index) = opened {
+            match review_game(assets, &mut games[index], true, NodePath::default()).await {
+                ReviewExit::Delete => { if archive.delete(&games[index]).is_ok() { games.remove(index); } }
+                ReviewExit::Play(position) => { return Some(position); }
+                ReviewExit::Back => {}
+            }
+
+            // the key that closed the game shouldn't close the browser too
+            next_frame().await;
+            continue;
+        }
+
+        if root_ui().button(vec2(10.0, 710.0), tr("archive.prev")) && page > 0 { page -= 1; }
+        draw_text(&format!("{}/{}", page + 1, pages), 120.0, 745.0, 32.0, WHITE);
+        if root_ui().button(vec2(200.0, 710.0), tr("archive.next")) && page + 1 < pages { page += 1; }
+
+        if root_ui().button(vec2(880.0, 710.0), tr("back")) || is_key_pressed(KeyCode::Escape) {
+            return None;
+        }
+
+        next_frame().await;
+    }
+}
+
+// edits the players, event and round of a game. true if the edit was kept, false if it was cancelled
+pub(crate) async fn edit_game_info(info: &mut GameInfo) -> bool {
+    request_new_screen_size(480.0, 360.0);
+    next_frame().await;
+
+    let mut edited = info.clone();
+
+    loop {
+        clear_background(GRAY);
+
+        root_ui().input_text(hash!(), tr("details.white"), &mut edited.white);
+        root_ui().input_text(hash!(), tr("details.black"), &mut edited.black);
+        root_ui().input_text(hash!(), tr("details.event"), &mut edited.event);
+        root_ui().input_text(hash!(), tr("details.round"), &mut edited.round);
+        root_ui().label(None, tr("details.filled_in"));
+
+        if root_ui().button(None, tr("details.done")) || is_key_pressed(KeyCode::Enter) {
+            *info = edited;
+            return true;
+        }
+        if root_ui().button(None, tr("details.cancel")) || is_key_pressed(KeyCode::Escape) {
+            return false;
+        }
+
+        next_frame().await;
+    }
+}
+
+// how a replayed game was left
+pub(crate) enum ReviewExit {
+    Back,
+    Delete,
+    // a live game from the shown position was asked for
+    Play(Game),
+}
+
+// replays a game from `start` and edits its variations, notes and marks. `saved` games are written back
+// to their file after every edit and can be deleted
+pub(crate) async fn review_game(assets: &Assets, game: &mut ArchivedGame, saved: bool, start: NodePath) -> ReviewExit {
+    let renderer = assets.renderer;
+
+    request_new_screen_size(1024.0, 768.0);
+    next_frame().await;
+
+    let save = |game: &ArchivedGame| if saved { game.save() } else { Ok(()) };
+    let save_study = |study: &Study, game: &ArchivedGame| if saved { study.save(&game.path) } else { Ok(()) };
+
+    let mut path = start;
+    let mut selected: Option<usize> = None;
+    let mut status = String::new();
+
+    let mut use_engine = false;
+    let mut engine: Option<ThreadedUci> = None;
+    // the node the engine is searching, its latest info and best move
+    let mut searched: Option<NodePath> = None;
+    let mut engine_info: Option<UciInfo> = None;
+    let mut engine_best: Option<String> = None;
+    let mut engine_move: Option<Move> = None;
+    // positions already searched show their result again instead of searching
+    let mut cache = AnalysisCache::new();
+    // the solver's line for a mate the engine found: the node, the engine's mate and the line
+    let mut proof: Option<(NodePath, i32, String)> = None;
+
+    // study mode edits the note of the shown node and draws marks with the right mouse button
+    let mut studying = false;
+    let mut study = if saved { Study::load(&game.path).unwrap_or_default() } else { Study::default() };
+    let mut note = String::new();
+    let mut note_path: Option<NodePath> = None;
+    let mut mark_start: Option<usize> = None;
+
+    loop {
+        clear_background(GRAY);
+
+        let position = game.pgn.position_at(&path).unwrap_or_else(|| game.pgn.history.start());
+
+        renderer.draw_position(&position, 96.0);
+        let moves = game.pgn.moves_to(&path).unwrap_or_default();
+        if let Some(markup) = study.markup(&moves) {
+            draw_markup(markup);
+        }
+        if let Some(square) = selected {
+            let corner = square_corner(Square::new(square));
+            draw_rectangle_lines(corner.x, corner.y, 96.0, 96.0, 4.0, YELLOW);
+        }
+
+        let mut y = 10.0;
+        let tags = [("White", "review.white"), ("Black", "review.black"), ("Date", "review.date"), ("Result", "review.result"), ("TimeControl", "review.time_control")];
+        for (tag, key) in tags {
+            draw_text(&trf(key, &[game.pgn.tag(tag).unwrap_or("?")]), 778.0, y + 20.0, 24.0, WHITE);
+            y += 28.0;
+        }
+        // how it ended, from the termination tag or the final position
+        if let Some(result) = game.result() {
+            draw_text(&result.to_string(), 778.0, y + 20.0, 20.0, WHITE);
+            y += 28.0;
+        }
+        let line_name = if path.is_main_line() { tr("review.main_line").to_string() } else { trf("review.variation", &[&path.branches.len().to_string()]) };
+        draw_text(&line_name, 778.0, y + 20.0, 24.0, WHITE);
+
+        if let Some(clicked) = draw_line_moves(&game.pgn, &path, Rect::new(778.0, 350.0, 240.0, 140.0)) {
+            path = clicked;
+        }
+
+        if note_path.as_ref() != Some(&path) {
+            note = game.pgn.comment_at(&path).unwrap_or_default();
+            note_path = Some(path.clone());
+        }
+
+        if studying {
+            widgets::InputText::new(hash!()).position(vec2(778.0, 515.0)).size(vec2(240.0, 36.0)).ui(&mut root_ui(), &mut note);
+
+            if note != game.pgn.comment_at(&path).unwrap_or_default() && game.pgn.set_comment_at(&path, Some(note.clone())) {
+                if let Err(err) = save(game) { status = trf("review.save_failed", &[&err.to_string()]); }
+            }
+
+            if let Some((square, release)) = mark_square(&mut mark_start) {
+                let markup = study.markup_mut(&moves);
+                match release {
+                    Some(from) if from != square => { markup.toggle_arrow(Arrow { from, to: square, color: mark_color() }); }
+                    _ => { markup.toggle_highlight(Highlight { square, color: mark_color() }); }
+                }
+
+                if let Err(err) = save_study(&study, game) { status = trf("review.study_save_failed", &[&err.to_string()]); }
+            }
+        } else {
+            draw_note(&note, Rect::new(778.0, 496.0, 240.0, 60.0));
+        }
+
+        let mut edited = None;
+        if let Some(mv) = board_move(&position, &mut selected) {
+            edited = game.pgn.play_at(&path, mv);
+        }
+
+        // the arrow keys move the note's cursor while studying
+        let keys = selected.is_none() && !studying;
+        if root_ui().button(vec2(778.0, 200.0), "<") || (is_key_pressed(KeyCode::Left) && keys) {
+            if let Some(previous) = game.pgn.previous(&path) { path = previous; }
+        }
+        if root_ui().button(vec2(840.0, 200.0), ">") || (is_key_pressed(KeyCode::Right) && keys) {
+            if let Some(next) = game.pgn.next(&path) { path = next; }
+        }
+
+        if !path.is_main_line() {
+            if root_ui().button(vec2(778.0, 240.0), tr("review.promote")) {
+                edited = game.pgn.promote(&path);
+            }
+            if root_ui().button(vec2(880.0, 240.0), tr("review.remove_line")) {
+                edited = game.pgn.delete_variation(&path);
+            }
+        }
+
+        // the other moves played from here
+        let alternatives = game.pgn.line(&path).and_then(|x| x.get(path.ply).cloned());
+        for (i, variation) in alternatives.iter().flat_map(|x| &x.notes.variations).enumerate().take(3) {
+            let Some(first) = variation.moves.first() else { continue; };
+
+            if root_ui().button(vec2(778.0 + i as f32 * 80.0, 560.0), san(&position, first.mv).as_str()) {
+                let mut branches = path.branches.clone();
+                branches.push((path.ply, i));
+                path = NodePath { branches, ply: 1 };
+            }
+        }
+
+        widgets::Checkbox::new(hash!()).label(tr("review.engine")).pos(vec2(778.0, 280.0)).ui(&mut root_ui(), &mut use_engine);
+        widgets::Checkbox::new(hash!()).label(tr("review.study")).pos(vec2(900.0, 280.0)).ui(&mut root_ui(), &mut studying);
+        if use_engine {
+            let engine = engine.get_or_insert_with(ThreadedUci::new);
+            let position = game.pgn.position_at(&path).unwrap_or(position);
+
+            if searched.as_ref() != Some(&path) {
+                engine.cancel();
+                (engine_info, engine_best, engine_move) = match cache.get(&position) {
+                    Some(cached) => { (cached.info.clone(), Some(cached.best.san(&position)), Some(cached.best)) }
+                    None => {
+                        engine.recommend_move(position, Limits::default().depth(20));
+                        (None, None, None)
+                    }
+                };
+                searched = Some(path.clone());
+            }
+
+            while let Some(info) = engine.try_info() {
+                if info.score.is_some() { engine_info = Some(info); }
+            }
+            match engine.try_result() {
+                Some(Ok(mv)) => {
+                    (engine_best, engine_move) = (Some(mv.san(&position)), Some(mv));
+                    cache.insert(&position, CachedAnalysis { info: engine_info.clone(), best: mv });
+                }
+                Some(Err(err)) => { engine_best = Some(err.to_string()); }
+                None => {}
+            }
+
+            draw_text(&engine_line(&position, engine_info.as_ref(), engine_best.as_deref()), 778.0, 330.0, 20.0, WHITE);
+
+            // a short mate is searched for again without the engine, to show it is forced
+            if let Some(Score::Mate(n)) = engine_info.as_ref().and_then(|x| x.score) {
+                let proved = proof.as_ref().is_some_and(|x| x.0 == path && x.1 == n);
+                if (1..=PROOF_MOVES).contains(&n) && !proved {
+                    let text = match position.find_mate_in(n as u32) {
+                        Some(line) => { trf("review.forced", &[&san_line(&position, &line)]) }
+                        None => { trf("review.no_mate", &[&n.to_string()]) }
+                    };
+                    proof = Some((path.clone(), n, text));
+                }
+            }
+            if let Some((_, _, text)) = proof.as_ref().filter(|x| x.0 == path) {
+                draw_text(text, 778.0, 347.0, 16.0, WHITE);
+            }
+            if let Some(info) = &engine_info { draw_pv(&info.pv_moves(&position)); }
+
+            // the engine's move goes into the tree like one played on the board
+            if let Some(mv) = engine_move.filter(|_| root_ui().button(vec2(900.0, 200.0), tr("review.best"))) {
+                edited = game.pgn.play_at(&path, mv);
+            }
+        } else if let (Some(engine), Some(_)) = (&engine, searched.take()) {
+            engine.cancel();
+        }
+
+        if let Some(next) = edited {
+            path = next;
+            status = match save(game) {
+                Ok(()) => { String::new() }
+                Err(err) => { trf("review.save_failed", &[&err.to_string()]) }
+            };
+        }
+
+        if !status.is_empty() {
+            draw_text(&status, 10.0, 760.0, 20.0, WHITE);
+        }
+
+        if root_ui().button(vec2(778.0, 610.0), tr("review.analyse")) {
+            let history = game.pgn.history.clone();
+            if let Some(ply) = crate::gui::report::analysis_report(history, saved.then_some(&mut *game)).await {
+                path = NodePath::main_line(ply);
+            }
+
+            next_frame().await;
+            continue;
+        }
+
+        if root_ui().button(vec2(900.0, 610.0), tr("review.bookmark")) {
+            let source = if saved { game.summary() } else { game.pgn.tag("Event").unwrap_or("Analysis").to_string() };
+            let tags = if saved { vec!["archive".to_string()] } else { Vec::new() };
+            let bookmark = Bookmark::new(format!("{}, move {}", source, position.fm_clock()), tags, position);
+
+            status = match BookmarkLibrary::open(BOOKMARKS_FILE).and_then(|mut x| x.add(bookmark)) {
+                Ok(()) => { tr("review.bookmarked").to_string() }
+                Err(err) => { trf("review.bookmark_failed", &[&err.to_string()]) }
+            };
+        }
+
+        if root_ui().button(vec2(900.0, 660.0), tr("review.play")) {
+            return ReviewExit::Play(position);
+        }
+
+        if saved && root_ui().button(vec2(778.0, 660.0), tr("review.delete")) {
+            return ReviewExit::Delete;
+        } else if root_ui().button(vec2(778.0, 710.0), tr("back")) || (is_key_pressed(KeyCode::Escape) && keys) {
+            return ReviewExit::Back;
+        }
+        if is_key_pressed(KeyCode::Escape) { selected = None; }
+
+        next_frame().await;
+    }
+}
+
+// the moves of the line `path` is on, wrapped to `area`, returns the one clicked
+fn draw_line_moves(pgn: &Pgn, path: &NodePath, area: Rect) -> Option<NodePath> {
+    let (Some(line), Some(mut game)) = (pgn.line(path), pgn.position_at(&NodePath { ply: 0, ..path.clone() })) else { return None; };
+    let (mut x, mut y) = (area.x, area.y);
+    let mut clicked = None;
+
+    // rows scroll by so the current move stays in view
+    let rows = (area.h / 22.0) as usize;
+    let mut row: usize = 0;
+    let mut words = Vec::new();
+
+    for (ply, mv) in line.iter().enumerate() {
+        let number = match game.turn {
+            Color::White => { format!("{}. ", game.fm_clock()) }
+            Color::Black if ply == 0 => { format!("{}... ", game.fm_clock()) }
+            Color::Black => { String::new() }
+        };
+        let marker = if mv.notes.variations.is_empty() { "" } else { "+" };
+        let word = format!("{}{}{}", number, san(&game, mv.mv), marker);
+        game.move_checked(mv.mv.from.index(), mv.mv.to.index(), mv.mv.promotion);
+
+        let width = measure_text(&word, None, 20, 1.0).width + 8.0;
+        if x + width > area.x + area.w && x > area.x {
+            (x, y, row) = (area.x, y + 22.0, row + 1);
+        }
+        words.push((word, x, y, row, ply + 1));
+        x += width;
+    }
+
+    let current_row = words.iter().find(|x| x.4 == path.ply).map(|x| x.3).unwrap_or(0);
+    let skip = (current_row + 1).saturating_sub(rows);
+    let (mx, my) = mouse_position();
+
+    for (word, x, y, row, ply) in words {
+        if row < skip || row >= skip + rows { continue; }
+        let y = y - skip as f32 * 22.0;
+
+        draw_text(&word, x, y + 16.0, 20.0, if ply == path.ply { YELLOW } else { WHITE });
+
+        let width = measure_text(&word, None, 20, 1.0).width;
+        if is_mouse_button_pressed(MouseButton::Left) && (x..x + width).contains(&mx) && (y..y + 22.0).contains(&my) {
+            clicked = Some(NodePath { ply, ..path.clone() });
+        }
+    }
+
+    clicked
+}
+
+// a note wrapped to `area`, cut off once it runs out of rows
+fn draw_note(note: &str, area: Rect) {
+    let rows = (area.h / 20.0) as usize;
+    let mut lines: Vec<String> = Vec::new();
+
+    for word in note.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if measure_text(&format!("{} {}", line, word), None, 18, 1.0).width <= area.w => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => { lines.push(word.to_string()); }
+        }
+    }
+
+    for (i, line) in lines.iter().take(rows).enumerate() {
+        draw_text(line, area.x, area.y + 14.0 + i as f32 * 20.0, 18.0, WHITE);
+    }
+}
+
+// the engine's score from white's side and its best move once the search is done
+pub(crate) fn engine_line(position: &Game, info: Option<&UciInfo>, best: Option<&str>) -> String {
+    let score = info.and_then(|x| x.score).map(|x| x.white_view(position.turn));
+
+    let mut text = match (score, info.and_then(|x| x.depth)) {
+        (Some(score), Some(depth)) => { trf("review.score_depth", &[&score.to_string(), &depth.to_string()]) }
+        (Some(score), None) => { score.to_string() }
+        _ => { tr("review.thinking").to_string() }
+    };
+    if let Some(best) = best { text.push_str(&trf("review.best_move", &[best])); }
+
+    text
+}
+
+// a right click on the board: (square released on, square pressed on) once the button is released
+fn mark_square(start: &mut Option<usize>) -> Option<(usize, Option<usize>)> {
+    let square = mouse_square().map(|x| x.index());
+
+    if is_mouse_button_pressed(MouseButton::Right) { *start = square; }
+    if !is_mouse_button_released(MouseButton::Right) { return None; }
+
+    Some((square?, start.take()))
+}
+
+// green unless shift (red), control (yellow) or alt (blue) is held, like on lichess
+fn mark_color() -> MarkColor {
+    if is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift) {
+        MarkColor::Red
+    } else if is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl) {
+        MarkColor::Yellow
+    } else if is_key_down(KeyCode::LeftAlt) || is_key_down(KeyCode::RightAlt) {
+        MarkColor::Blue
+    } else {
+        MarkColor::Green
+    }
+}
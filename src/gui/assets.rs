@@ -0,0 +1,121 @@
+use macroquad::audio::{load_sound, Sound};
+use macroquad::prelude::*;
+use crate::gui::i18n::tr;
+use crate::gui::render::{Renderer, TEXTURES};
+
+// the sounds every pack has
+const SOUNDS: [&str; 5] = ["default", "capture", "castle", "check", "error"];
+// sounds a pack may leave out, each falls back to one of the above
+const EXTRA_SOUNDS: [&str; 3] = ["promotion", "low_time", "game_end"];
+// quiet moves of a single piece, pawn to king, played instead of the default where a pack has them
+const PIECE_SOUNDS: [&str; 6] = ["move_pawn", "move_knight", "move_bishop", "move_rook", "move_queen", "move_king"];
+// where textures and sounds come from unless the settings say otherwise
+pub(crate) const DEFAULT_TEXTURES: &str = "assets";
+pub(crate) const DEFAULT_SOUNDS: &str = "assets";
+// each folder in here is a sound pack, with the same file names as the sounds in assets/
+pub(crate) const SOUND_PACKS_DIR: &str = "sounds";
+
+#[derive(Copy, Clone)]
+pub(crate) struct Sounds {
+    // a move that isn't anything else
+    pub(crate) default: Sound,
+    pub(crate) capture: Sound,
+    pub(crate) castle: Sound,
+    pub(crate) check: Sound,
+    // a move that was refused
+    pub(crate) error: Sound,
+    pub(crate) promotion: Sound,
+    // little time is left to move
+    pub(crate) low_time: Sound,
+    pub(crate) game_end: Sound,
+    // quiet moves of each piece, pawn to king, the default where the pack has none
+    pub(crate) pieces: [Sound; 6],
+}
+
+// every texture and sound, loaded once at startup and handed to the screens that draw boards or play
+// games. only loaded again when the texture folder or the sound pack changes
+pub(crate) struct Assets {
+    pub(crate) renderer: Renderer,
+    pub(crate) sounds: Sounds,
+    // the folders the textures and sounds came from
+    textures: String,
+    sound_dir: String,
+}
+
+impl Assets {
+    // loads everything behind a progress bar, the error names the file that couldn't be read
+    pub(crate) async fn load(textures: &str, sound_dir: &str) -> Result<Assets, String> {
+        let total = TEXTURES.len() + SOUNDS.len() + EXTRA_SOUNDS.len() + PIECE_SOUNDS.len();
+
+        let mut images = Vec::with_capacity(TEXTURES.len());
+        for (i, name) in TEXTURES.iter().enumerate() {
+            let path = format!("{}/{}.png", textures, name);
+            loading_screen(&path, i as f32 / total as f32).await;
+            images.push(load_image(&path).await.map_err(|x| x.to_string())?);
+        }
+
+        let mut sounds = Vec::with_capacity(SOUNDS.len());
+        for (i, name) in SOUNDS.iter().enumerate() {
+            let path = format!("{}/{}.ogg", sound_dir, name);
+            loading_screen(&path, (TEXTURES.len() + i) as f32 / total as f32).await;
+            sounds.push(load_sound(&path).await.map_err(|x| x.to_string())?);
+        }
+
+        // the rest are optional, a missing file is no error
+        let mut optional = Vec::with_capacity(EXTRA_SOUNDS.len() + PIECE_SOUNDS.len());
+        for (i, name) in EXTRA_SOUNDS.iter().chain(PIECE_SOUNDS.iter()).enumerate() {
+            let path = format!("{}/{}.ogg", sound_dir, name);
+            loading_screen(&path, (TEXTURES.len() + SOUNDS.len() + i) as f32 / total as f32).await;
+            optional.push(load_sound(&path).await.ok());
+        }
+
+        let (default, check) = (sounds[0], sounds[3]);
+        let pieces = std::array::from_fn(|i| optional[EXTRA_SOUNDS.len() + i].unwrap_or(default));
+
+        Ok(Assets {
+            renderer: Renderer::from_images(&images),
+            sounds: Sounds {
+                default, capture: sounds[1], castle: sounds[2], check, error: sounds[4],
+                promotion: optional[0].unwrap_or(default),
+                low_time: optional[1].unwrap_or(check),
+                game_end: optional[2].unwrap_or(check),
+                pieces,
+            },
+            textures: textures.to_string(),
+            sound_dir: sound_dir.to_string(),
+        })
+    }
+
+    // loads everything again if the textures or the sounds came from somewhere else, keeping these on an error
+    pub(crate) async fn reload(&mut self, textures: &str, sound_dir: &str) -> Result<(), String> {
+        if textures == self.textures && sound_dir == self.sound_dir { return Ok(()); }
+
+        *self = Assets::load(textures, sound_dir).await?;
+        Ok(())
+    }
+}
+
+// the names of the sound packs in `SOUND_PACKS_DIR`, sorted
+pub(crate) fn sound_packs() -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(SOUND_PACKS_DIR) else { return Vec::new(); };
+
+    let mut packs: Vec<String> = entries.flatten()
+        .filter(|x| x.path().is_dir())
+        .filter_map(|x| x.file_name().into_string().ok())
+        .collect();
+    packs.sort();
+    packs
+}
+
+// `path` being loaded with `progress` (0 to 1) of the whole lot done
+async fn loading_screen(path: &str, progress: f32) {
+    clear_background(GRAY);
+
+    let (width, height) = (screen_width(), screen_height());
+    draw_text(tr("loading"), 20.0, height / 2.0 - 30.0, 40.0, WHITE);
+    draw_rectangle(20.0, height / 2.0, width - 40.0, 24.0, DARKGRAY);
+    draw_rectangle(20.0, height / 2.0, (width - 40.0) * progress, 24.0, WHITE);
+    draw_text(path, 20.0, height / 2.0 + 50.0, 20.0, WHITE);
+
+    next_frame().await;
+}
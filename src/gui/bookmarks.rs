@@ -0,0 +1,126 @@
+use std::path::PathBuf;
+use macroquad::prelude::*;
+use macroquad::hash;
+use macroquad::ui::{root_ui, widgets};
+use chess::archive::ArchivedGame;
+use chess::bookmarks::{Bookmark, BookmarkLibrary};
+use chess::tree::NodePath;
+use chess::{Game, History, Pgn};
+use crate::gui::archive::ReviewExit;
+use crate::gui::{BOOKMARKS_FILE, show_error};
+use crate::gui::assets::Assets;
+use crate::gui::i18n::{tr, trf};
+
+const BOOKMARKS_PER_PAGE: usize = 8;
+
+// browse the saved positions and add new ones from a fen, returns the position to start a game from
+pub(crate) async fn bookmarks_menu(assets: &Assets) -> Option<Game> {
+    let renderer = assets.renderer;
+
+    request_new_screen_size(1024.0, 768.0);
+    next_frame().await;
+
+    let mut library = match BookmarkLibrary::open(BOOKMARKS_FILE) {
+        Ok(library) => { library }
+        Err(err) => {
+            show_error(&trf("bookmarks.open_failed", &[&err.to_string()])).await;
+            return None;
+        }
+    };
+
+    let mut filter = String::new();
+    let mut page = 0;
+    let mut selected: Option<usize> = None;
+
+    let mut fen = String::new();
+    let mut name = String::new();
+    let mut tags = String::new();
+    let mut status = String::new();
+
+    loop {
+        clear_background(GRAY);
+
+        renderer.draw_position(&selected.map(|x| library.bookmarks[x].position).unwrap_or_default(), 96.0);
+
+        widgets::InputText::new(hash!())
+            .label(tr("bookmarks.filter"))
+            .position(vec2(778.0, 10.0))
+            .size(vec2(240.0, 36.0))
+            .ui(&mut root_ui(), &mut filter);
+
+        let matching: Vec<usize> = (0..library.bookmarks.len()).filter(|x| library.bookmarks[*x].matches(&filter)).collect();
+        let pages = matching.len().div_ceil(BOOKMARKS_PER_PAGE).max(1);
+        page = page.min(pages - 1);
+
+        if library.bookmarks.is_empty() {
+            draw_text(tr("bookmarks.none"), 778.0, 80.0, 24.0, WHITE);
+        }
+
+        for (i, index) in matching.iter().skip(page * BOOKMARKS_PER_PAGE).take(BOOKMARKS_PER_PAGE).enumerate() {
+            let label: String = library.bookmarks[*index].name.chars().take(18).collect();
+            if root_ui().button(vec2(778.0, 56.0 + i as f32 * 40.0), label.as_str()) {
+                selected = Some(*index);
+            }
+        }
+
+        if root_ui().button(vec2(778.0, 380.0), "<") && page > 0 { page -= 1; }
+        draw_text(&format!("{}/{}", page + 1, pages), 840.0, 405.0, 24.0, WHITE);
+        if root_ui().button(vec2(900.0, 380.0), ">") && page + 1 < pages { page += 1; }
+
+        if let Some(index) = selected {
+            let bookmark = &library.bookmarks[index];
+            draw_text(&bookmark.tags.join(", "), 778.0, 445.0, 20.0, WHITE);
+
+            if root_ui().button(vec2(778.0, 460.0), tr("bookmarks.play")) {
+                return Some(bookmark.position);
+            }
+
+            if root_ui().button(vec2(880.0, 460.0), tr("bookmarks.analyse")) {
+                let mut pgn = Pgn::new(History::new(bookmark.position));
+                pgn.set_tag("Event", bookmark.name.clone());
+
+                // never saved, so it doesn't need a file
+                let exit = crate::gui::archive::review_game(assets, &mut ArchivedGame { path: PathBuf::new(), pgn }, false, NodePath::default()).await;
+                if let ReviewExit::Play(position) = exit { return Some(position); }
+                next_frame().await;
+                continue;
+            }
+
+            if root_ui().button(vec2(778.0, 500.0), tr("bookmarks.delete")) {
+                if let Err(err) = library.remove(index) { status = trf("bookmarks.save_failed", &[&err.to_string()]); }
+                selected = None;
+            }
+        }
+
+        widgets::InputText::new(hash!()).label(tr("bookmarks.fen")).position(vec2(778.0, 550.0)).size(vec2(240.0, 36.0)).ui(&mut root_ui(), &mut fen);
+        widgets::InputText::new(hash!()).label(tr("bookmarks.name")).position(vec2(778.0, 590.0)).size(vec2(240.0, 36.0)).ui(&mut root_ui(), &mut name);
+        widgets::InputText::new(hash!()).label(tr("bookmarks.tags")).position(vec2(778.0, 630.0)).size(vec2(240.0, 36.0)).ui(&mut root_ui(), &mut tags);
+
+        if root_ui().button(vec2(778.0, 665.0), tr("bookmarks.add")) {
+            match Game::from_fen(fen.trim()) {
+                Ok(position) => {
+                    let name = if name.trim().is_empty() { fen.trim().to_string() } else { name.trim().to_string() };
+                    let tags = tags.split(',').map(|x| x.trim().to_string()).filter(|x| !x.is_empty()).collect();
+
+                    status = match library.add(Bookmark::new(name, tags, position)) {
+                        Ok(()) => { String::new() }
+                        Err(err) => { trf("bookmarks.save_failed", &[&err.to_string()]) }
+                    };
+                    selected = Some(library.bookmarks.len() - 1);
+                    fen.clear();
+                }
+                Err(err) => { status = trf("bookmarks.bad_fen", &[&err.to_string()]); }
+            }
+        }
+
+        if !status.is_empty() {
+            draw_text(&status, 10.0, 760.0, 20.0, WHITE);
+        }
+
+        if root_ui().button(vec2(778.0, 710.0), tr("back")) || is_key_pressed(KeyCode::Escape) {
+            return None;
+        }
+
+        next_frame().await;
+    }
+}
@@ -0,0 +1,67 @@
+use chess::{Color, History, Piece, Promotion};
+
+// pieces each side has taken and the material lead, fed with the moves as they are played
+#[derive(Default)]
+pub(crate) struct Captures {
+    // taken by white, taken by black
+    taken: [Vec<Piece>; 2],
+    // white's points minus black's
+    advantage: i32,
+    // history entries already counted
+    seen: usize,
+}
+
+fn index(color: Color) -> usize {
+    match color {
+        Color::White => { 0 }
+        Color::Black => { 1 }
+    }
+}
+
+impl Captures {
+    // counts the moves played since the last update
+    pub(crate) fn update(&mut self, history: &History) {
+        // moves were taken back, count again from the start
+        if history.len() < self.seen { *self = Captures::default(); }
+        // odds games start out uneven
+        if self.seen == 0 { self.advantage = history.start().material_diff(); }
+
+        let start = history.start().turn;
+
+        for (i, entry) in history.entries().iter().enumerate().skip(self.seen) {
+            let mover = if i % 2 == 0 { start } else { !start };
+            let sign = if mover == Color::White { 1 } else { -1 };
+
+            // a promoted pawn is worth its new piece
+            if let Some(promotion) = entry.mv.promotion { self.advantage += sign * (promotion_value(promotion) - 1); }
+
+            if let Some(piece) = entry.captured {
+                self.advantage += sign * piece.value() as i32;
+
+                let taken = &mut self.taken[index(mover)];
+                taken.push(piece);
+                // most valuable first
+                taken.sort_by_key(|x| std::cmp::Reverse(x.value()));
+            }
+        }
+
+        self.seen = history.len();
+    }
+
+    pub(crate) fn taken(&self, color: Color) -> &[Piece] {
+        &self.taken[index(color)]
+    }
+
+    // how many points `color` is ahead by, negative when behind
+    pub(crate) fn advantage(&self, color: Color) -> i32 {
+        if color == Color::White { self.advantage } else { -self.advantage }
+    }
+}
+
+fn promotion_value(promotion: Promotion) -> i32 {
+    match promotion {
+        Promotion::Knight | Promotion::Bishop => { 3 }
+        Promotion::Rook => { 5 }
+        Promotion::Queen => { 9 }
+    }
+}
@@ -0,0 +1,57 @@
+use std::path::PathBuf;
+use macroquad::prelude::*;
+use macroquad::ui::root_ui;
+use chess::archive::ArchivedGame;
+use chess::classics::classics;
+use chess::tree::NodePath;
+use chess::Game;
+use crate::gui::archive::{review_game, ReviewExit};
+use crate::gui::assets::Assets;
+use crate::gui::i18n::tr;
+
+// pick one of the famous games and replay it with its annotations
+// returns a position to play a game from, picked while replaying one
+pub(crate) async fn classics_menu(assets: &Assets) -> Option<Game> {
+    request_new_screen_size(1024.0, 768.0);
+    next_frame().await;
+
+    let games = classics();
+
+    loop {
+        clear_background(GRAY);
+        draw_text(tr("classics.title"), 10.0, 40.0, 40.0, WHITE);
+
+        let mut opened = None;
+        for (i, pgn) in games.iter().enumerate() {
+            let label = format!(
+                "{}: {} - {}, {} {}",
+                pgn.tag("Annotator").unwrap_or("?"),
+                pgn.tag("White").unwrap_or("?"),
+                pgn.tag("Black").unwrap_or("?"),
+                pgn.tag("Event").unwrap_or("?"),
+                pgn.tag("Date").and_then(|x| x.split('.').next()).unwrap_or("?"),
+            );
+            if root_ui().button(vec2(10.0, 60.0 + i as f32 * 50.0), label.as_str()) {
+                opened = Some(i);
+            }
+        }
+
+        if let Some(index) = opened {
+            // a copy, so notes and lines added while replaying are gone next time
+            let mut game = ArchivedGame { path: PathBuf::new(), pgn: games[index].clone() };
+            if let ReviewExit::Play(position) = review_game(assets, &mut game, false, NodePath::default()).await {
+                return Some(position);
+            }
+
+            // the key that closed the game shouldn't close the browser too
+            next_frame().await;
+            continue;
+        }
+
+        if root_ui().button(vec2(880.0, 710.0), tr("back")) || is_key_pressed(KeyCode::Escape) {
+            return None;
+        }
+
+        next_frame().await;
+    }
+}
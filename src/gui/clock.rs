@@ -0,0 +1,57 @@
+use std::time::Duration;
+use macroquad::prelude::*;
+
+// a gap between frames longer than this means the game was out of sight (minimised, a hidden tab, the
+// window being dragged), none of it counts
+const MAX_STEP: f64 = 0.25;
+
+// the time a game runs on: animations, the engine's thinking and move times. it stands still while the
+// game is paused and skips the time the window wasn't drawing, so nothing jumps ahead or runs out in the
+// background
+pub(crate) struct GameClock {
+    now: f64,
+    step: f32,
+    // wall time of the last tick
+    last: f64,
+    paused: bool,
+}
+
+impl GameClock {
+    pub(crate) fn new() -> Self {
+        GameClock { now: 0.0, step: 0.0, last: get_time(), paused: false }
+    }
+
+    // moves the clock on by the time since the last tick, once a frame
+    pub(crate) fn tick(&mut self) {
+        let wall = get_time();
+        let step = wall - self.last;
+        self.last = wall;
+
+        self.step = if self.paused || step > MAX_STEP { 0.0 } else { step as f32 };
+        self.now += f64::from(self.step);
+    }
+
+    // seconds of game time so far
+    pub(crate) fn now(&self) -> f64 {
+        self.now
+    }
+
+    // game time of this frame, what `get_frame_time` is in real time
+    pub(crate) fn frame_time(&self) -> f32 {
+        self.step
+    }
+
+    // game time since `since`, the time a move took when it's played
+    pub(crate) fn since(&self, since: f64) -> Duration {
+        Duration::from_secs_f64((self.now - since).max(0.0))
+    }
+
+    pub(crate) fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub(crate) fn resume(&mut self) {
+        // the time away isn't the next frame's
+        (self.paused, self.last) = (false, get_time());
+    }
+}
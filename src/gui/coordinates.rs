@@ -0,0 +1,137 @@
+use macroquad::prelude::*;
+use macroquad::rand::{gen_range, srand};
+use macroquad::ui::root_ui;
+use chess::coordinates::{CoordinateScore, HighScores, ROUND_SECONDS};
+use chess::platform::since_epoch;
+use chess::{Game, Square};
+use crate::gui::{COORDINATES_FILE, mouse_square, show_error, square_corner};
+use crate::gui::assets::Assets;
+use crate::gui::i18n::{tr, trf};
+
+// seconds a clicked square stays green or red
+const FLASH_TIME: f64 = 0.3;
+
+// locale keys of the sides a round can be played from
+const SIDES: [&str; 3] = ["side.white", "side.black", "side.random"];
+
+// a random square, never `previous` twice in a row
+fn random_square(previous: Option<Square>) -> Square {
+    loop {
+        let square = Square::new(gen_range(0, 64));
+        if Some(square) != previous { return square; }
+    }
+}
+
+// the square in the place of `square` once the board is turned around
+fn turned(square: Square, flipped: bool) -> Square {
+    if flipped { Square::new(63 - square.index()) } else { square }
+}
+
+// names a square at a time to click on the board, as many as possible in a round, and keeps the best rounds
+pub(crate) async fn coordinates_trainer(assets: &Assets) {
+    let renderer = assets.renderer;
+
+    request_new_screen_size(1024.0, 768.0);
+    next_frame().await;
+
+    let mut scores = match HighScores::open(COORDINATES_FILE) {
+        Ok(scores) => { scores }
+        Err(err) => {
+            show_error(&trf("coordinates.open_failed", &[&err.to_string()])).await;
+            return;
+        }
+    };
+
+    srand(since_epoch().map(|x| x.as_nanos() as u64).unwrap_or_else(|| (get_time() * 1e6) as u64));
+
+    let mut flipped = false;
+    // the time the round started, None between rounds
+    let mut started: Option<f64> = None;
+    let mut target = random_square(None);
+    let (mut hits, mut misses) = (0, 0);
+    // the last click and whether it was right, shown for a moment
+    let mut flash: Option<(Square, bool, f64)> = None;
+    let mut status = String::new();
+    // the place of the last round in the table
+    let mut placed: Option<usize> = None;
+
+    loop {
+        clear_background(GRAY);
+
+        // the start position shows which way round the board is
+        renderer.draw_board(&Game::default(), 96.0, flipped);
+
+        if let Some((square, right, time)) = flash.filter(|x| get_time() - x.2 < FLASH_TIME) {
+            let corner = square_corner(turned(square, flipped));
+            let color = if right { Color::new(0.0, 0.8, 0.0, 0.5) } else { Color::new(0.9, 0.0, 0.0, 0.5) };
+            draw_rectangle(corner.x, corner.y, 96.0, 96.0, Color { a: color.a * (1.0 - ((get_time() - time) / FLASH_TIME) as f32), ..color });
+        }
+
+        if let Some(start) = started {
+            let left = ROUND_SECONDS - (get_time() - start);
+
+            draw_text(&target.to_string(), 820.0, 120.0, 96.0, WHITE);
+            draw_text(&format!("{:.1}s", left.max(0.0)), 778.0, 180.0, 32.0, WHITE);
+            draw_text(&trf("coordinates.score", &[&hits.to_string(), &misses.to_string()]), 778.0, 215.0, 24.0, WHITE);
+
+            if is_mouse_button_pressed(MouseButton::Left) {
+                if let Some(square) = mouse_square().map(|x| turned(x, flipped)) {
+                    let right = square == target;
+                    if right { hits += 1; } else { misses += 1; }
+
+                    flash = Some((square, right, get_time()));
+                    if right { target = random_square(Some(target)); }
+                }
+            }
+
+            if left <= 0.0 {
+                let score = CoordinateScore { hits, misses, time: since_epoch().map(|x| x.as_secs()).unwrap_or_default() };
+                (started, placed) = (None, None);
+
+                status = match scores.add(score) {
+                    Ok(Some(place)) => {
+                        placed = Some(place);
+                        trf("coordinates.placed", &[&hits.to_string(), &(place + 1).to_string()])
+                    }
+                    Ok(None) => { trf("coordinates.squares", &[&hits.to_string()]) }
+                    Err(err) => { trf("coordinates.save_failed", &[&err.to_string()]) }
+                };
+            }
+        } else {
+            draw_text(tr("coordinates.title"), 778.0, 40.0, 40.0, WHITE);
+            draw_text(&trf("coordinates.rules", &[&ROUND_SECONDS.to_string()]), 778.0, 70.0, 18.0, WHITE);
+
+            // a round starts with the side picked at the bottom
+            let pressed: Vec<bool> = SIDES.iter().enumerate().map(|(i, x)| root_ui().button(vec2(778.0, 90.0 + i as f32 * 50.0), tr(x))).collect();
+            if let Some(side) = pressed.iter().position(|x| *x) {
+                flipped = match side {
+                    0 => { false }
+                    1 => { true }
+                    _ => { gen_range(0, 2) == 1 }
+                };
+                (started, hits, misses, flash) = (Some(get_time()), 0, 0, None);
+                target = random_square(None);
+                status.clear();
+            }
+        }
+
+        draw_text(tr("coordinates.high_scores"), 778.0, 270.0, 28.0, WHITE);
+        if scores.scores.is_empty() {
+            draw_text(tr("coordinates.none"), 778.0, 300.0, 20.0, WHITE);
+        }
+        for (i, score) in scores.scores.iter().enumerate() {
+            let color = if placed == Some(i) { YELLOW } else { WHITE };
+            draw_text(&format!("{}. {}", i + 1, trf("coordinates.score", &[&score.hits.to_string(), &score.misses.to_string()])), 778.0, 300.0 + i as f32 * 24.0, 20.0, color);
+        }
+
+        if !status.is_empty() {
+            draw_text(&status, 778.0, 600.0, 20.0, WHITE);
+        }
+
+        if root_ui().button(vec2(778.0, 710.0), tr("back")) || is_key_pressed(KeyCode::Escape) {
+            return;
+        }
+
+        next_frame().await;
+    }
+}
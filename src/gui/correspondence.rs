@@ -0,0 +1,364 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use macroquad::audio::play_sound_once;
+use macroquad::prelude::*;
+use macroquad::hash;
+use macroquad::ui::root_ui;
+use chess::correspondence::{Correspondence, CorrespondenceGame, OpponentKind, ReplyWatcher};
+use chess::platform::{notify, since_epoch};
+use chess::uci::{EngineConfig, Limits, ThreadedUci};
+use chess::{Color, Move, Square};
+use crate::gui::{board_move, square_corner, CORRESPONDENCE_DIR};
+use crate::gui::assets::Assets;
+use crate::gui::i18n::{tr, trf};
+use crate::gui::settings::Settings;
+
+// seconds between looks at the folder for moves made elsewhere
+const POLL: f64 = 5.0;
+const GAMES_PER_PAGE: usize = 12;
+// time left to move under which opening a game plays the low time sound
+const LOW_TIME: Duration = Duration::from_secs(86_400);
+const DAYS: [u32; 6] = [1, 2, 3, 5, 7, 14];
+const DAY_NAMES: [&str; 6] = ["1", "2", "3", "5", "7", "14"];
+
+fn now() -> u64 {
+    since_epoch().map(|x| x.as_secs()).unwrap_or(0)
+}
+
+// the correspondence games, read again every few seconds. engine opponents move from here, whichever
+// screen is open, and replies are announced with a desktop notification if the settings ask for one
+pub(crate) struct Postbox {
+    store: Option<Correspondence>,
+    pub(crate) games: Vec<CorrespondenceGame>,
+    watcher: ReplyWatcher,
+    // when the games were last read, None to read them on the next update
+    last_poll: Option<f64>,
+    // the game an engine is searching and the engine
+    search: Option<(PathBuf, ThreadedUci)>,
+    // games whose engine couldn't be started or failed, not tried again until the next run
+    failed: HashSet<PathBuf>,
+    pub(crate) error: Option<String>,
+}
+
+impl Postbox {
+    pub(crate) fn open() -> Postbox {
+        let (store, error) = match Correspondence::open(CORRESPONDENCE_DIR) {
+            Ok(store) => { (Some(store), None) }
+            Err(err) => { (None, Some(trf("correspondence.open_failed", &[&err.to_string()]))) }
+        };
+
+        Postbox { store, games: Vec::new(), watcher: ReplyWatcher::new(), last_poll: None, search: None, failed: HashSet::new(), error }
+    }
+
+    // called every frame by the screens that keep the games going
+    pub(crate) fn update(&mut self, settings: &Settings) {
+        if self.last_poll.is_none_or(|x| get_time() - x >= POLL) { self.refresh(settings); }
+        self.think(settings);
+    }
+
+    pub(crate) fn game(&self, path: &Path) -> Option<&CorrespondenceGame> {
+        self.games.iter().find(|x| x.path == path)
+    }
+
+    pub(crate) fn create(&mut self, mut game: CorrespondenceGame) -> Result<(), String> {
+        let store = self.store.as_ref().ok_or_else(|| tr("correspondence.unavailable").to_string())?;
+        store.create(&mut game).map_err(|x| x.to_string())?;
+
+        self.last_poll = None;
+        Ok(())
+    }
+
+    pub(crate) fn play(&mut self, path: &Path, mv: Move) -> Result<(), String> {
+        self.change(path, |game| game.play(mv, now()).map(|_| ()).map_err(|x| x.to_string()))
+    }
+
+    pub(crate) fn resign(&mut self, path: &Path) -> Result<(), String> {
+        self.change(path, |game| { game.resign(); Ok(()) })
+    }
+
+    pub(crate) fn delete(&mut self, path: &Path) -> Result<(), String> {
+        let (Some(store), Some(game)) = (&self.store, self.game(path)) else { return Ok(()); };
+        store.delete(game).map_err(|x| x.to_string())?;
+
+        self.games.retain(|x| x.path != path);
+        Ok(())
+    }
+
+    // edits the game at `path` and writes it back
+    fn change(&mut self, path: &Path, edit: impl FnOnce(&mut CorrespondenceGame) -> Result<(), String>) -> Result<(), String> {
+        let store = self.store.as_ref().ok_or_else(|| tr("correspondence.unavailable").to_string())?;
+        let Some(game) = self.games.iter_mut().find(|x| x.path == path) else { return Ok(()); };
+
+        edit(game)?;
+        store.save(game).map_err(|x| x.to_string())
+    }
+
+    // reads the games again, ending the ones out of time and announcing replies
+    fn refresh(&mut self, settings: &Settings) {
+        self.last_poll = Some(get_time());
+        let Some(store) = &self.store else { return; };
+
+        let mut games = match store.games() {
+            Ok(games) => { games }
+            Err(err) => {
+                self.error = Some(err.to_string());
+                return;
+            }
+        };
+
+        let now = now();
+        for game in games.iter_mut() {
+            if game.check_time(now) {
+                if let Err(err) = store.save(game) { self.error = Some(err.to_string()); }
+            }
+        }
+
+        for game in self.watcher.replies(&games) {
+            if !settings.notifications { continue; }
+
+            if let Err(err) = notify(tr("correspondence.notify_title"), &trf("correspondence.notify", &[&game.opponent])) {
+                self.error = Some(trf("correspondence.notify_failed", &[&err.to_string()]));
+            }
+        }
+
+        self.games = games;
+    }
+
+    // plays the move of an engine that has finished, or starts one on the next game waiting on its engine
+    fn think(&mut self, settings: &Settings) {
+        if let Some((path, engine)) = &self.search {
+            let Some(result) = engine.try_result() else { return; };
+            let path = path.clone();
+            self.search = None;
+
+            if let Err(err) = result.map_err(|x| x.to_string()).and_then(|mv| self.engine_move(&path, mv)) {
+                self.failed.insert(path);
+                self.error = Some(err);
+            }
+            return;
+        }
+
+        let Some(game) = self.games.iter().find(|x| x.awaits_engine() && !self.failed.contains(&x.path)) else { return; };
+
+        let mut configs = std::iter::once(EngineConfig::uci_script()).chain(settings.opponents());
+        let Some(config) = configs.find(|x| x.name == game.opponent) else {
+            self.error = Some(trf("correspondence.no_engine", &[&game.opponent]));
+            self.failed.insert(game.path.clone());
+            return;
+        };
+
+        let engine = settings.reply_delay.builder().config(config).build();
+        engine.new_game();
+        engine.recommend_move(&game.pgn.history, Limits::default().time(1_500));
+        self.search = Some((game.path.clone(), engine));
+    }
+
+    fn engine_move(&mut self, path: &Path, mv: Move) -> Result<(), String> {
+        // the player may have resigned or deleted it meanwhile
+        if !self.game(path).is_some_and(CorrespondenceGame::awaits_engine) { return Ok(()); }
+
+        self.change(path, |game| {
+            let fen = game.game().as_fen();
+            game.play(mv, now()).map(|_| ()).map_err(|err| trf("game.bad_engine_move", &[&mv.to_string(), &fen, &err.to_string()]))
+        })
+    }
+}
+
+// "2d 5h left"
+fn time_left(left: Duration) -> String {
+    let seconds = left.as_secs();
+    trf("correspondence.left", &[&(seconds / 86_400).to_string(), &(seconds % 86_400 / 3_600).to_string()])
+}
+
+fn state(game: &CorrespondenceGame) -> String {
+    if game.is_over() {
+        let result = game.result().map_or_else(|| "*".to_string(), |x| format!("{} ({})", x.pgn_result(), x));
+        trf("correspondence.over", &[&result])
+    } else if game.awaits_player() {
+        trf("correspondence.your_move", &[&time_left(game.time_left(now()))])
+    } else {
+        trf("correspondence.their_move", &[&time_left(game.time_left(now()))])
+    }
+}
+
+fn summary(game: &CorrespondenceGame) -> String {
+    let color = if game.player == Color::White { tr("correspondence.white") } else { tr("correspondence.black") };
+    trf("correspondence.summary", &[&game.opponent, color, &(game.pgn.history.len() / 2 + 1).to_string(), &state(game)])
+}
+
+// the games, the ones waiting on the player first
+pub(crate) async fn correspondence_menu(assets: &Assets, settings: &Settings, postbox: &mut Postbox) {
+    let mut page = 0;
+
+    request_new_screen_size(1024.0, 768.0);
+    next_frame().await;
+
+    loop {
+        clear_background(GRAY);
+        postbox.update(settings);
+
+        draw_text(tr("correspondence.title"), 10.0, 40.0, 32.0, WHITE);
+
+        let pages = postbox.games.len().div_ceil(GAMES_PER_PAGE).max(1);
+        page = page.min(pages - 1);
+
+        if postbox.games.is_empty() {
+            draw_text(tr("correspondence.none"), 10.0, 90.0, 32.0, WHITE);
+        }
+
+        let mut opened = None;
+        for (i, game) in postbox.games.iter().skip(page * GAMES_PER_PAGE).take(GAMES_PER_PAGE).enumerate() {
+            if root_ui().button(vec2(10.0, 60.0 + i as f32 * 50.0), summary(game).as_str()) {
+                opened = Some(game.path.clone());
+            }
+        }
+
+        if let Some(error) = &postbox.error {
+            draw_text(error, 10.0, 690.0, 20.0, WHITE);
+        }
+
+        if let Some(path) = opened {
+            correspondence_game(assets, settings, postbox, &path).await;
+            request_new_screen_size(1024.0, 768.0);
+            // the key that closed the game shouldn't close the list too
+            next_frame().await;
+            continue;
+        }
+
+        if root_ui().button(vec2(10.0, 710.0), tr("correspondence.prev")) && page > 0 { page -= 1; }
+        draw_text(&format!("{}/{}", page + 1, pages), 120.0, 745.0, 32.0, WHITE);
+        if root_ui().button(vec2(200.0, 710.0), tr("correspondence.next")) && page + 1 < pages { page += 1; }
+
+        if root_ui().button(vec2(600.0, 710.0), tr("correspondence.new")) {
+            new_game_menu(settings, postbox).await;
+            request_new_screen_size(1024.0, 768.0);
+            next_frame().await;
+            continue;
+        }
+
+        if root_ui().button(vec2(880.0, 710.0), tr("back")) || is_key_pressed(KeyCode::Escape) {
+            return;
+        }
+
+        next_frame().await;
+    }
+}
+
+// picks the opponent, the days for each move and the side to play
+async fn new_game_menu(settings: &Settings, postbox: &mut Postbox) {
+    request_new_screen_size(640.0, 360.0);
+
+    let engines: Vec<String> = std::iter::once(EngineConfig::uci_script()).chain(settings.opponents()).map(|x| x.name).collect();
+    // the engines, then someone else
+    let mut names: Vec<&str> = engines.iter().map(String::as_str).collect();
+    names.push(tr("correspondence.remote"));
+
+    let mut opponent = 0;
+    let mut remote_name = String::new();
+    let mut days = 2;
+    let mut white = true;
+    let mut status = String::new();
+
+    loop {
+        clear_background(GRAY);
+        postbox.update(settings);
+
+        root_ui().combo_box(hash!(), tr("correspondence.opponent"), &names, &mut opponent);
+        let remote = opponent == engines.len();
+        if remote {
+            root_ui().input_text(hash!(), tr("correspondence.remote_name"), &mut remote_name);
+        }
+        root_ui().combo_box(hash!(), tr("correspondence.days"), &DAY_NAMES, &mut days);
+        root_ui().checkbox(hash!(), tr("menu.white"), &mut white);
+
+        if root_ui().button(None, tr("correspondence.start")) {
+            let (name, kind) = match remote {
+                true => { (remote_name.trim().to_string(), OpponentKind::Remote) }
+                false => { (engines[opponent].clone(), OpponentKind::Engine) }
+            };
+            let player = if white { Color::White } else { Color::Black };
+
+            if name.is_empty() {
+                status = tr("correspondence.no_name").to_string();
+            } else {
+                match postbox.create(CorrespondenceGame::new(player, name, kind, DAYS[days], now())) {
+                    Ok(()) => { return; }
+                    Err(err) => { status = err; }
+                }
+            }
+        }
+
+        if !status.is_empty() {
+            root_ui().label(None, &status);
+        }
+
+        if root_ui().button(None, tr("back")) || is_key_pressed(KeyCode::Escape) {
+            return;
+        }
+
+        next_frame().await;
+    }
+}
+
+// one game: a move when it's the player's, resigning while it goes on and deleting once it's over
+async fn correspondence_game(assets: &Assets, settings: &Settings, postbox: &mut Postbox, path: &Path) {
+    let renderer = assets.renderer;
+    let mut selected = None;
+    let mut status = String::new();
+
+    if postbox.game(path).is_some_and(|x| x.awaits_player() && x.time_left(now()) < LOW_TIME) {
+        play_sound_once(assets.sounds.low_time);
+    }
+
+    request_new_screen_size(1024.0, 768.0);
+    next_frame().await;
+
+    loop {
+        clear_background(GRAY);
+        postbox.update(settings);
+
+        // gone, deleted from outside
+        let Some(game) = postbox.game(path).cloned() else { return; };
+
+        let position = game.game();
+        renderer.draw_position(&position, 96.0);
+        if let Some(square) = selected {
+            let corner = square_corner(Square::new(square));
+            draw_rectangle_lines(corner.x, corner.y, 96.0, 96.0, 4.0, YELLOW);
+        }
+
+        if game.awaits_player() {
+            if let Some(mv) = board_move(&position, &mut selected) {
+                match postbox.play(path, mv) {
+                    Ok(()) => { status.clear(); }
+                    Err(err) => { status = err; }
+                }
+            }
+        }
+
+        draw_text(&game.opponent, 778.0, 30.0, 24.0, WHITE);
+        draw_text(&state(&game), 778.0, 58.0, 20.0, WHITE);
+        draw_text(&trf("correspondence.days_per_move", &[&game.days_per_move.to_string()]), 778.0, 86.0, 20.0, WHITE);
+
+        let message = if status.is_empty() { postbox.error.as_deref().unwrap_or_default() } else { status.as_str() };
+        draw_text(&message.chars().take(30).collect::<String>(), 778.0, 640.0, 20.0, WHITE);
+
+        if !game.is_over() && root_ui().button(vec2(778.0, 660.0), tr("correspondence.resign")) {
+            if let Err(err) = postbox.resign(path) { status = err; }
+        }
+
+        if game.is_over() && root_ui().button(vec2(778.0, 660.0), tr("correspondence.delete")) {
+            match postbox.delete(path) {
+                Ok(()) => { return; }
+                Err(err) => { status = err; }
+            }
+        }
+
+        if root_ui().button(vec2(778.0, 710.0), tr("back")) || is_key_pressed(KeyCode::Escape) {
+            return;
+        }
+
+        next_frame().await;
+    }
+}
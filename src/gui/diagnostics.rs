@@ -0,0 +1,84 @@
+use std::sync::mpsc::{channel, Receiver};
+use std::time::Duration;
+use macroquad::prelude::*;
+use macroquad::ui::root_ui;
+use chess::uci::{EngineConfig, EngineDiagnostics};
+use crate::gui::i18n::{tr, trf};
+
+// how long the engine gets to answer each step of the handshake
+const DIAGNOSE_TIMEOUT: Duration = Duration::from_secs(5);
+// characters that fit across the screen at the font size used
+const LINE_WIDTH: usize = 88;
+
+// the handshake on another thread, it can take a while
+fn start_diagnosis(config: &EngineConfig) -> Receiver<EngineDiagnostics> {
+    let (s, rx) = channel();
+    let config = config.clone();
+    chess::platform::spawn(move || { let _ = s.send(config.diagnose(DIAGNOSE_TIMEOUT)); });
+
+    rx
+}
+
+// `text` cut to the screen's width
+fn fit(text: &str) -> String {
+    if text.chars().count() <= LINE_WIDTH { return text.to_string(); }
+    text.chars().take(LINE_WIDTH - 3).chain("...".chars()).collect()
+}
+
+// starts the engine and shows everything that happened, to find out why it doesn't play
+pub(crate) async fn engine_diagnostics(config: &EngineConfig) {
+    request_new_screen_size(640.0, 640.0);
+
+    let mut receiver = Some(start_diagnosis(config));
+    let mut diagnostics: Option<EngineDiagnostics> = None;
+
+    loop {
+        clear_background(GRAY);
+        draw_text(&trf("diagnostics.title", &[&config.name]), 10.0, 30.0, 28.0, WHITE);
+
+        if let Some(result) = receiver.as_ref().and_then(|x| x.try_recv().ok()) {
+            (diagnostics, receiver) = (Some(result), None);
+        }
+
+        match &diagnostics {
+            None => { draw_text(&trf("diagnostics.starting", &[&config.path]), 10.0, 60.0, 18.0, WHITE); }
+            Some(found) => {
+                let mut lines = vec![
+                    (trf("diagnostics.command", &[&found.command]), WHITE),
+                    (trf("diagnostics.working_dir", &[&found.working_dir.as_ref().map(|x| x.display().to_string()).unwrap_or_else(|| tr("diagnostics.unknown").to_string())]), WHITE),
+                    match &found.resolved_path {
+                        Some(path) => { (trf("diagnostics.runs", &[&path.display().to_string()]), WHITE) }
+                        None => { (trf("diagnostics.not_found", &[&config.path]), ORANGE) }
+                    },
+                    match &found.outcome {
+                        Ok(name) => { (trf("diagnostics.ok", &[name]), GREEN) }
+                        Err(err) => { (trf("diagnostics.failed", &[&err.to_string()]), ORANGE) }
+                    },
+                    (String::new(), WHITE),
+                    (tr("diagnostics.handshake").to_string(), YELLOW),
+                ];
+
+                // the end of each is where things went wrong
+                lines.extend(found.transcript.iter().skip(found.transcript.len().saturating_sub(12)).map(|x| (x.clone(), WHITE)));
+                lines.push((String::new(), WHITE));
+                lines.push((tr("diagnostics.stderr").to_string(), YELLOW));
+                if found.stderr.is_empty() { lines.push((tr("diagnostics.nothing").to_string(), WHITE)); }
+                lines.extend(found.stderr.iter().skip(found.stderr.len().saturating_sub(8)).map(|x| (x.clone(), WHITE)));
+
+                for (i, (line, color)) in lines.iter().enumerate() {
+                    draw_text(&fit(line), 10.0, 60.0 + i as f32 * 18.0, 16.0, *color);
+                }
+            }
+        }
+
+        if receiver.is_none() && root_ui().button(vec2(10.0, 600.0), tr("diagnostics.again")) {
+            (receiver, diagnostics) = (Some(start_diagnosis(config)), None);
+        }
+
+        if root_ui().button(vec2(120.0, 600.0), tr("back")) || is_key_pressed(KeyCode::Escape) {
+            return;
+        }
+
+        next_frame().await;
+    }
+}
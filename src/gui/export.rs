@@ -0,0 +1,105 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use macroquad::prelude::*;
+use chess::image::{write_png, GifWriter};
+use chess::{Game, History, Move, Piece, Square};
+use crate::gui::EXPORT_DIR;
+use crate::gui::assets::Assets;
+use crate::gui::i18n::{tr, trf};
+
+// pixels a square takes in a picture
+const SQUARE: f32 = 64.0;
+// pictures of a move sliding across the board in a gif
+const MOVE_FRAMES: usize = 4;
+// hundredths of a second a sliding frame and a position are shown, the final position stays longer
+const FRAME_DELAY: u16 = 4;
+const POSITION_DELAY: u16 = 80;
+const FINAL_DELAY: u16 = 300;
+
+// the pieces `mv` slides across the board as (piece, from, to), the rook as well when castling
+fn sliding(game: &Game, mv: Move) -> Vec<(Piece, Square, Square)> {
+    let Some(piece) = game.board[mv.from] else { return Vec::new(); };
+    let mut pieces = vec![(piece, mv.from, mv.to)];
+
+    if matches!(piece, Piece::WKing | Piece::BKing) && mv.from.file().distance(mv.to.file()) == 2 {
+        let (from, to) = if mv.to.file() > mv.from.file() { (mv.from.index() + 3, mv.to.index() - 1) } else { (mv.from.index() - 4, mv.to.index() + 1) };
+        if let Some(rook) = game.board[from] { pieces.push((rook, Square::new(from), Square::new(to))); }
+    }
+
+    pieces
+}
+
+// the top left corner of `square` in a picture, white at the bottom
+fn corner(square: Square) -> Vec2 {
+    vec2(square.file().index() as f32 * SQUARE, (7 - square.rank().index()) as f32 * SQUARE)
+}
+
+// `draw` drawn into `target`, as rgba rows from the top. each picture takes a frame, `progress` is shown meanwhile
+async fn render(target: RenderTarget, progress: &str, draw: impl FnOnce()) -> Vec<u8> {
+    let size = SQUARE * 8.0;
+    set_camera(&Camera2D { render_target: Some(target), ..Camera2D::from_display_rect(Rect::new(0.0, 0.0, size, size)) });
+    clear_background(WHITE);
+    draw();
+
+    set_default_camera();
+    clear_background(GRAY);
+    draw_text(progress, 10.0, 40.0, 32.0, WHITE);
+    next_frame().await;
+
+    // the texture's rows come bottom first
+    let image = target.texture.get_texture_data();
+    image.bytes.chunks_exact(image.width as usize * 4).rev().flatten().copied().collect()
+}
+
+// saves the final position of `history` as a png, or the whole game as a gif with its moves sliding, as
+// `name` in the export folder
+pub(crate) async fn export_game(assets: &Assets, history: &History, name: &str, gif: bool) -> Result<PathBuf, String> {
+    let renderer = assets.renderer;
+
+    std::fs::create_dir_all(EXPORT_DIR).map_err(|x| x.to_string())?;
+    let path = Path::new(EXPORT_DIR).join(format!("{}.{}", name, if gif { "gif" } else { "png" }));
+    let file = BufWriter::new(File::create(&path).map_err(|x| x.to_string())?);
+
+    let size = (SQUARE * 8.0) as u32;
+    let target = render_target(size, size);
+    let board = |game: &Game| renderer.draw_position(game, SQUARE);
+
+    if !gif {
+        let pixels = render(target, tr("export.picture"), || board(&history.current())).await;
+        write_png(file, size, size, &pixels).map_err(|x| x.to_string())?;
+        return Ok(path);
+    }
+
+    let mut writer = GifWriter::new(file, size as u16, size as u16).map_err(|x| x.to_string())?;
+    for (ply, entry) in history.entries().iter().enumerate() {
+        let before = history.position(ply);
+        let progress = trf("export.move", &[&(ply + 1).to_string(), &history.len().to_string()]);
+
+        let pixels = render(target, &progress, || board(&before)).await;
+        writer.frame(&pixels, POSITION_DELAY).map_err(|x| x.to_string())?;
+
+        // the moving pieces leave their squares and slide over the rest
+        let pieces = sliding(&before, entry.mv);
+        let mut left = before;
+        for (_, from, _) in &pieces { left.board[*from] = None; }
+
+        for frame in 1..MOVE_FRAMES {
+            let t = frame as f32 / MOVE_FRAMES as f32;
+            let pixels = render(target, &progress, || {
+                board(&left);
+                for (piece, from, to) in &pieces {
+                    let at = corner(*from).lerp(corner(*to), t);
+                    renderer.pieces.draw_sized(*piece, at.x, at.y, SQUARE);
+                }
+            }).await;
+            writer.frame(&pixels, FRAME_DELAY).map_err(|x| x.to_string())?;
+        }
+    }
+
+    let pixels = render(target, tr("export.final"), || board(&history.current())).await;
+    writer.frame(&pixels, FINAL_DELAY).map_err(|x| x.to_string())?;
+    writer.finish().map_err(|x| x.to_string())?;
+
+    Ok(path)
+}
@@ -0,0 +1,48 @@
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use chess::Color;
+use chess::locale::{fill, Locale};
+
+static LOCALES: OnceLock<Vec<Locale>> = OnceLock::new();
+// index into `locales()` of the language being shown
+static LANGUAGE: AtomicUsize = AtomicUsize::new(0);
+
+// the built in locales, english first
+pub(crate) fn locales() -> &'static [Locale] {
+    LOCALES.get_or_init(Locale::built_in)
+}
+
+pub(crate) fn language() -> usize {
+    LANGUAGE.load(Ordering::Relaxed)
+}
+
+// switches every screen to the language called `name`, english if there's none by that name
+pub(crate) fn set_language(name: Option<&str>) {
+    let index = name.and_then(|name| locales().iter().position(|x| x.name() == name)).unwrap_or(0);
+    LANGUAGE.store(index, Ordering::Relaxed);
+}
+
+// the text for `key` in the language being shown, the english one if it has none and the key itself if
+// neither has it
+pub(crate) fn tr(key: &'static str) -> &'static str {
+    let locales = locales();
+    locales[language()].get(key).or_else(|| locales[0].get(key)).unwrap_or(key)
+}
+
+// `tr(key)` with its `{}`s filled in with `args`
+pub(crate) fn trf(key: &'static str, args: &[&str]) -> String {
+    fill(tr(key), args)
+}
+
+// `tr` of each key, for combo boxes
+pub(crate) fn tr_all(keys: &[&'static str]) -> Vec<&'static str> {
+    keys.iter().map(|x| tr(x)).collect()
+}
+
+// "White" or "Black" in the language being shown
+pub(crate) fn tr_color(color: Color) -> &'static str {
+    match color {
+        Color::White => { tr("side.white") }
+        Color::Black => { tr("side.black") }
+    }
+}
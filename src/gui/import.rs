@@ -0,0 +1,101 @@
+use std::sync::mpsc::{Receiver, TryRecvError};
+use macroquad::prelude::*;
+use macroquad::hash;
+use macroquad::ui::root_ui;
+use chess::archive::Archive;
+use chess::import::{download_games, game_url, ImportProgress, Site};
+use crate::gui::ARCHIVE_DIR;
+use crate::gui::i18n::{tr, trf};
+
+enum ImportMessage {
+    Progress(ImportProgress),
+    // (games saved, games that were in the archive already)
+    Done(Result<(usize, usize), String>),
+}
+
+// downloads the games and saves the ones the archive doesn't have yet
+fn start_import(site: Site, username: String, max: usize) -> Receiver<ImportMessage> {
+    let (s, rx) = std::sync::mpsc::channel();
+
+    chess::platform::spawn(move || {
+        let result = download_games(site, &username, max, |progress| { let _ = s.send(ImportMessage::Progress(progress)); })
+            .map_err(|x| x.to_string())
+            .and_then(|games| {
+                let archive = Archive::open(ARCHIVE_DIR).map_err(|x| x.to_string())?;
+                let archived = archive.games().map_err(|x| x.to_string())?;
+                let known: Vec<String> = archived.iter().filter_map(|x| game_url(&x.pgn).map(String::from)).collect();
+
+                // oldest first, so the newest game gets the newest file
+                let new: Vec<_> = games.iter().rev().filter(|x| game_url(x).is_none_or(|url| !known.iter().any(|x| x == url))).collect();
+                for pgn in &new {
+                    archive.save(pgn).map_err(|x| x.to_string())?;
+                }
+
+                Ok((new.len(), games.len() - new.len()))
+            });
+
+        let _ = s.send(ImportMessage::Done(result));
+    });
+
+    rx
+}
+
+// downloads a player's recent games from lichess or chess.com into the archive
+pub(crate) async fn import_menu() {
+    request_new_screen_size(640.0, 360.0);
+
+    let mut site = 0;
+    let mut username = String::new();
+    let mut max_games = "20".to_string();
+
+    let mut receiver: Option<Receiver<ImportMessage>> = None;
+    let mut status = String::new();
+
+    loop {
+        clear_background(GRAY);
+
+        if let Some(rx) = &receiver {
+            loop {
+                match rx.try_recv() {
+                    Ok(ImportMessage::Progress(ImportProgress::Requesting(url))) => { status = trf("import.downloading", &[&url]); }
+                    Ok(ImportMessage::Progress(ImportProgress::RateLimited(wait))) => {
+                        status = trf("import.rate_limited", &[&wait.as_secs().to_string()]);
+                    }
+                    Ok(ImportMessage::Progress(ImportProgress::Games(games))) => { status = trf("import.downloaded", &[&games.to_string()]); }
+                    Ok(ImportMessage::Done(Ok((saved, known)))) => {
+                        status = trf("import.done", &[&saved.to_string(), &known.to_string()]);
+                    }
+                    Ok(ImportMessage::Done(Err(err))) => { status = trf("import.failed", &[&err]); }
+                    Err(TryRecvError::Empty) => { break; }
+                    Err(TryRecvError::Disconnected) => {
+                        receiver = None;
+                        break;
+                    }
+                }
+            }
+        }
+
+        root_ui().combo_box(hash!(), tr("import.site"), &Site::NAMES, &mut site);
+        root_ui().input_text(hash!(), tr("import.username"), &mut username);
+        root_ui().input_text(hash!(), tr("import.games"), &mut max_games);
+
+        if receiver.is_none() && root_ui().button(None, tr("import.import")) {
+            match max_games.trim().parse::<usize>() {
+                _ if username.trim().is_empty() => { status = tr("import.no_username").to_string(); }
+                Ok(max) if max > 0 => { receiver = Some(start_import(Site::ALL[site], username.trim().to_string(), max)); }
+                _ => { status = tr("import.bad_games").to_string(); }
+            }
+        }
+
+        if !status.is_empty() {
+            root_ui().label(None, &status);
+        }
+
+        // a running import finishes in the background
+        if root_ui().button(None, tr("back")) || is_key_pressed(KeyCode::Escape) {
+            return;
+        }
+
+        next_frame().await;
+    }
+}
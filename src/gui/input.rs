@@ -0,0 +1,175 @@
+use macroquad::prelude::*;
+
+// seconds a press has to be held still to count as a long press
+const LONG_PRESS: f64 = 0.5;
+// how far a press can wander, in board pixels, before it becomes a drag
+const DRAG_DISTANCE: f32 = 16.0;
+
+// what the mouse or a finger did this frame, in board coordinates
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub(crate) enum PointerEvent {
+    // pressed down, taps and clicks act here
+    Press(Vec2),
+    // let go after dragging
+    Drop(Vec2),
+    // a finger held still for a while, or a right click
+    LongPress(Vec2),
+}
+
+// mouse and touch input as one pointer, touches arrive through macroquad's mouse emulation
+#[derive(Default)]
+pub(crate) struct Pointer {
+    // where and when the current press started
+    down: Option<(Vec2, f64)>,
+    dragging: bool,
+    long_pressed: bool,
+    // the last press came from a finger, which is less precise than a mouse
+    pub(crate) touch: bool,
+}
+
+impl Pointer {
+    pub(crate) fn update(&mut self, camera: &Camera2D) -> Option<PointerEvent> {
+        let position = camera.screen_to_world(mouse_position().into());
+
+        if is_mouse_button_pressed(MouseButton::Right) {
+            return Some(PointerEvent::LongPress(position));
+        }
+
+        if is_mouse_button_pressed(MouseButton::Left) {
+            self.down = Some((position, get_time()));
+            self.dragging = false;
+            self.long_pressed = false;
+            self.touch = !touches().is_empty();
+
+            return Some(PointerEvent::Press(position));
+        }
+
+        let (start, since) = self.down?;
+
+        if is_mouse_button_released(MouseButton::Left) {
+            self.down = None;
+            return if self.dragging { Some(PointerEvent::Drop(position)) } else { None };
+        }
+
+        if start.distance(position) > DRAG_DISTANCE {
+            self.dragging = true;
+        }
+
+        // the mouse has the right button for this
+        if self.touch && !self.dragging && !self.long_pressed && get_time() - since > LONG_PRESS {
+            self.long_pressed = true;
+            return Some(PointerEvent::LongPress(start));
+        }
+
+        None
+    }
+
+    // where a dragged piece is drawn, while one is being dragged
+    pub(crate) fn drag_position(&self, camera: &Camera2D) -> Option<Vec2> {
+        self.down.filter(|_| self.dragging).map(|_| camera.screen_to_world(mouse_position().into()))
+    }
+}
+
+// shows a `width` by `height` layout as large as the window allows, centered, on any screen shape
+pub(crate) fn board_camera(width: f32, height: f32) -> Camera2D {
+    let (aspect, layout) = (screen_width() / screen_height(), width / height);
+    let (w, h) = if aspect > layout { (height * aspect, height) } else { (width, width / aspect) };
+
+    Camera2D::from_display_rect(Rect::new((width - w) / 2.0, (height - h) / 2.0, w, h))
+}
+
+// `camera` turned by `degrees` around `center`, which stays where it was on screen
+pub(crate) fn rotated_around(camera: &Camera2D, center: Vec2, degrees: f32) -> Camera2D {
+    Camera2D {
+        rotation: degrees,
+        target: center,
+        offset: camera.zoom * (center - camera.target),
+        ..*camera
+    }
+}
+
+// how far the board can be zoomed in
+const MAX_ZOOM: f32 = 4.0;
+// zoom for one step of the mouse wheel or the zoom keys
+pub(crate) const ZOOM_STEP: f32 = 1.2;
+
+// zoom and pan of the board on top of the layout's camera, for small windows
+pub(crate) struct BoardView {
+    zoom: f32,
+    // the board point in the middle of the view, relative to the layout's middle
+    offset: Vec2,
+    // distance between two fingers and the board point between them, while pinching
+    pinch: Option<(f32, Vec2)>,
+    // the middle mouse button drags the board around, from this board point
+    pan: Option<Vec2>,
+}
+
+impl Default for BoardView {
+    fn default() -> Self {
+        BoardView { zoom: 1.0, offset: Vec2::ZERO, pinch: None, pan: None }
+    }
+}
+
+impl BoardView {
+    // `layout` zoomed and panned, what the board is drawn and clicked through
+    pub(crate) fn camera(&self, layout: &Camera2D) -> Camera2D {
+        Camera2D { zoom: layout.zoom * self.zoom, target: layout.target + self.offset, ..*layout }
+    }
+
+    // the mouse wheel zooms around the pointer if `wheel_zooms`, two fingers pinch and pan, the middle button
+    // drags. true while fingers are pinching, when they don't count as a tap
+    pub(crate) fn update(&mut self, layout: &Camera2D, wheel_zooms: bool) -> bool {
+        let camera = self.camera(layout);
+        let mouse = camera.screen_to_world(mouse_position().into());
+
+        let wheel = if wheel_zooms { mouse_wheel().1 } else { 0.0 };
+        if wheel != 0.0 {
+            self.zoom_around(mouse, if wheel > 0.0 { ZOOM_STEP } else { 1.0 / ZOOM_STEP }, layout);
+        }
+
+        if is_mouse_button_pressed(MouseButton::Middle) { self.pan = Some(mouse); }
+        if is_mouse_button_released(MouseButton::Middle) { self.pan = None; }
+        if let Some(start) = self.pan {
+            // the point grabbed stays under the pointer
+            self.offset += start - mouse;
+            self.clamp(layout);
+        }
+
+        let touches = touches();
+        if touches.len() != 2 {
+            self.pinch = None;
+            return false;
+        }
+
+        let (a, b) = (touches[0].position, touches[1].position);
+        let (distance, middle) = (a.distance(b), camera.screen_to_world((a + b) / 2.0));
+
+        if let Some((before, grabbed)) = self.pinch.filter(|x| x.0 > 0.0) {
+            self.offset += grabbed - middle;
+            self.zoom_around(grabbed, distance / before, layout);
+        }
+        self.pinch = Some((distance, self.camera(layout).screen_to_world((a + b) / 2.0)));
+
+        true
+    }
+
+    // zooms by `factor`, keeping the board point `point` where it is on screen
+    pub(crate) fn zoom_around(&mut self, point: Vec2, factor: f32, layout: &Camera2D) {
+        let zoom = (self.zoom * factor).clamp(1.0, MAX_ZOOM);
+        let center = layout.target + self.offset;
+
+        self.offset = point - (point - center) * (self.zoom / zoom) - layout.target;
+        self.zoom = zoom;
+        self.clamp(layout);
+    }
+
+    pub(crate) fn reset(&mut self) {
+        *self = BoardView::default();
+    }
+
+    // the view never leaves what the layout shows, and is the plain layout once zoomed all the way out
+    fn clamp(&mut self, layout: &Camera2D) {
+        let reach = layout.zoom.abs().recip() * (1.0 - 1.0 / self.zoom);
+        self.offset = self.offset.clamp(-reach, reach);
+    }
+}
@@ -0,0 +1,137 @@
+pub(crate) mod analysis;
+pub(crate) mod animation;
+pub(crate) mod archive;
+pub(crate) mod assets;
+pub(crate) mod bookmarks;
+pub(crate) mod captures;
+pub(crate) mod classics;
+pub(crate) mod clock;
+pub(crate) mod coordinates;
+pub(crate) mod correspondence;
+pub(crate) mod diagnostics;
+pub(crate) mod export;
+pub(crate) mod i18n;
+pub(crate) mod import;
+pub(crate) mod input;
+pub(crate) mod pause;
+pub(crate) mod render;
+pub(crate) mod report;
+pub(crate) mod settings;
+pub(crate) mod simul;
+pub(crate) mod stats;
+pub(crate) mod theme;
+pub(crate) mod tournament;
+pub(crate) mod trainer;
+pub(crate) mod tutorial;
+
+use macroquad::prelude::*;
+use macroquad::ui::root_ui;
+use chess::study::{Arrow, MarkColor, Markup};
+use chess::{File, Game, Move, Piece, Promotion, Rank, Square};
+
+pub(crate) const ARCHIVE_DIR: &str = "archive";
+pub(crate) const BOOKMARKS_FILE: &str = "bookmarks.epd";
+pub(crate) const COORDINATES_FILE: &str = "coordinates.txt";
+pub(crate) const CORRESPONDENCE_DIR: &str = "correspondence";
+pub(crate) const EXPORT_DIR: &str = "exports";
+// moves of an engine line drawn as arrows
+const PV_ARROWS: usize = 4;
+
+// the top left corner of `square` on a board drawn by `Renderer::draw_position`
+pub(crate) fn square_corner(square: Square) -> Vec2 {
+    vec2(square.file().index() as f32 * 96.0, (7 - square.rank().index()) as f32 * 96.0)
+}
+
+// the square under the mouse on a board drawn by `Renderer::draw_position`
+pub(crate) fn mouse_square() -> Option<Square> {
+    let (x, y) = mouse_position();
+    if !(0.0..768.0).contains(&x) || !(0.0..768.0).contains(&y) { return None; }
+
+    Some(Square::at(File::new((x / 96.0) as usize), Rank::new(7 - (y / 96.0) as usize)))
+}
+
+// picks a piece with one click and its square with the next, on a board drawn by `Renderer::draw_position`
+pub(crate) fn board_move(position: &Game, selected: &mut Option<usize>) -> Option<Move> {
+    if is_mouse_button_pressed(MouseButton::Right) { *selected = None; }
+    if !is_mouse_button_pressed(MouseButton::Left) { return None; }
+
+    let square = mouse_square()?.index();
+
+    let own = position.board[square].is_some_and(|x| x.color() == position.turn);
+    match selected.take() {
+        Some(from) if from != square && !own => {
+            let pawn = matches!(position.board[from], Some(Piece::WPawn | Piece::BPawn));
+            let promotion = (pawn && !(8..56).contains(&square)).then_some(Promotion::Queen);
+
+            Some(Move::new(from, square, promotion))
+        }
+        _ => {
+            if own { *selected = Some(square); }
+            None
+        }
+    }
+}
+
+// arrows and highlighted squares over a board drawn by `Renderer::draw_position`
+pub(crate) fn draw_markup(markup: &Markup) {
+    let color = |color: MarkColor, alpha: f32| match color {
+        MarkColor::Green => { macroquad::color::Color::new(0.1, 0.7, 0.2, alpha) }
+        MarkColor::Red => { macroquad::color::Color::new(0.9, 0.1, 0.1, alpha) }
+        MarkColor::Yellow => { macroquad::color::Color::new(0.95, 0.8, 0.1, alpha) }
+        MarkColor::Blue => { macroquad::color::Color::new(0.1, 0.4, 0.9, alpha) }
+    };
+    let center = |square: usize| square_corner(Square::new(square)) + vec2(48.0, 48.0);
+
+    for highlight in &markup.highlights {
+        let corner = center(highlight.square) - vec2(48.0, 48.0);
+        draw_rectangle(corner.x, corner.y, 96.0, 96.0, color(highlight.color, 0.45));
+    }
+
+    for arrow in &markup.arrows {
+        let (from, to) = (center(arrow.from), center(arrow.to));
+        let direction = (to - from).normalize_or_zero();
+        let normal = vec2(-direction.y, direction.x);
+        let head = to - direction * 30.0;
+        let arrow_color = color(arrow.color, 0.75);
+
+        draw_line(from.x, from.y, head.x, head.y, 14.0, arrow_color);
+        draw_triangle(to, head + normal * 22.0, head - normal * 22.0, arrow_color);
+    }
+}
+
+// the start of an engine line as arrows, blue for the side to move and red for the replies, each numbered
+// in the order it is played. over a board drawn by `Renderer::draw_position`
+pub(crate) fn draw_pv(moves: &[Move]) {
+    let moves = &moves[..moves.len().min(PV_ARROWS)];
+    let arrows = moves.iter().enumerate().map(|(i, x)| {
+        Arrow { from: x.from.index(), to: x.to.index(), color: if i % 2 == 0 { MarkColor::Blue } else { MarkColor::Red } }
+    });
+    draw_markup(&Markup { arrows: arrows.collect(), highlights: Vec::new() });
+
+    for (i, mv) in moves.iter().enumerate() {
+        let (from, to) = (square_corner(mv.from), square_corner(mv.to));
+        let label = (from + to) / 2.0 + vec2(48.0, 48.0);
+
+        draw_circle(label.x, label.y, 13.0, WHITE);
+        draw_text(&(i + 1).to_string(), label.x - 6.0, label.y + 7.0, 24.0, BLACK);
+    }
+}
+
+// puts `text` on the system clipboard, where the browser allows it
+pub(crate) fn copy_to_clipboard(text: &str) {
+    // SAFETY: the miniquad context is only used here, on the main thread, the way macroquad's own text boxes do
+    unsafe { get_internal_gl().quad_context.clipboard_set(text); }
+}
+
+pub(crate) async fn show_error(message: &str) {
+    loop {
+        clear_background(GRAY);
+        draw_text(message, 10.0, 40.0, 32.0, WHITE);
+
+        if root_ui().button(vec2(10.0, 80.0), i18n::tr("back")) || is_key_pressed(KeyCode::Escape) {
+            return;
+        }
+
+        next_frame().await;
+    }
+}
@@ -0,0 +1,53 @@
+use macroquad::prelude::*;
+use macroquad::ui::root_ui;
+use chess::share::{game_link, lichess_url};
+use chess::History;
+use crate::gui::copy_to_clipboard;
+use crate::gui::i18n::tr;
+
+pub(crate) enum PauseExit {
+    Resume,
+    Leave,
+}
+
+// the game waits while this is open. the position or the game so far can be shared from here
+pub(crate) async fn pause_menu(history: &History) -> PauseExit {
+    request_new_screen_size(640.0, 360.0);
+    next_frame().await;
+
+    let position = history.current();
+    let mut status = String::new();
+
+    loop {
+        clear_background(GRAY);
+
+        root_ui().label(None, tr("pause.title"));
+
+        if root_ui().button(None, tr("pause.resume")) || is_key_pressed(KeyCode::P) || is_key_pressed(KeyCode::Escape) {
+            return PauseExit::Resume;
+        }
+
+        // (button, what it copies, what's said once it's copied)
+        let links = [
+            ("pause.copy_lichess", lichess_url(&position), "pause.copied_lichess"),
+            ("pause.copy_game", game_link(history), "pause.copied_game"),
+            ("pause.copy_fen", position.as_fen(), "pause.copied_fen"),
+        ];
+        for (button, text, copied) in links {
+            if root_ui().button(None, tr(button)) {
+                copy_to_clipboard(&text);
+                status = tr(copied).to_string();
+            }
+        }
+
+        if root_ui().button(None, tr("pause.leave")) {
+            return PauseExit::Leave;
+        }
+
+        if !status.is_empty() {
+            root_ui().label(None, &status);
+        }
+
+        next_frame().await;
+    }
+}
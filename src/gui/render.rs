@@ -0,0 +1,120 @@
+use macroquad::prelude::*;
+use chess::{File, Game, Piece, Rank, Square};
+
+// the pictures a renderer is made of: the pieces in `Piece` order, then the two squares
+pub(crate) const TEXTURES: [&str; 14] = ["wP", "wN", "wB", "wR", "wQ", "wK", "bP", "bN", "bB", "bR", "bQ", "bK", "square_1", "square_2"];
+const COLUMNS: usize = 7;
+// transparent pixels between the pictures, so scaled draws don't pick up their neighbours
+const GUTTER: usize = 2;
+
+// a sprite for every piece, indexed by `Piece as usize`, all in the renderer's atlas
+#[derive(Copy, Clone)]
+pub(crate) struct PieceSprites {
+    texture: Texture2D,
+    sources: [Rect; 12],
+}
+
+impl PieceSprites {
+    // the width of a piece as it was loaded
+    pub(crate) fn size(&self) -> f32 {
+        self.sources[0].w
+    }
+
+    // `piece` at its own size
+    pub(crate) fn draw(&self, piece: Piece, x: f32, y: f32, color: Color) {
+        self.draw_ex(piece, x, y, color, DrawTextureParams::default());
+    }
+
+    // `piece` with `params`, whose source is filled in
+    pub(crate) fn draw_ex(&self, piece: Piece, x: f32, y: f32, color: Color, params: DrawTextureParams) {
+        draw_texture_ex(self.texture, x, y, color, DrawTextureParams { source: Some(self.sources[piece as usize]), ..params });
+    }
+
+    // a see-through `piece`, left behind on the square a piece is dragged from
+    pub(crate) fn draw_ghost(&self, piece: Piece, x: f32, y: f32, tint: Color) {
+        self.draw_ex(piece, x, y, tint, DrawTextureParams::default());
+    }
+
+    // `piece` scaled to `size` across
+    pub(crate) fn draw_sized(&self, piece: Piece, x: f32, y: f32, size: f32) {
+        self.draw_ex(piece, x, y, WHITE, DrawTextureParams { dest_size: Some(vec2(size, size)), ..Default::default() });
+    }
+}
+
+// what boards are drawn with. every piece and square texture is packed into one atlas, so a whole
+// board is drawn without switching textures and macroquad batches it into a handful of draw calls
+#[derive(Copy, Clone)]
+pub(crate) struct Renderer {
+    pub(crate) pieces: PieceSprites,
+    // the dark (square_1) and light (square_2) squares in the atlas
+    squares: [Rect; 2],
+}
+
+impl Renderer {
+    // `images` of each of `TEXTURES`, in that order
+    pub(crate) fn from_images(images: &[Image]) -> Renderer {
+        // every cell fits the largest picture
+        let cell_width = images.iter().map(|x| x.width as usize).max().unwrap() + GUTTER;
+        let cell_height = images.iter().map(|x| x.height as usize).max().unwrap() + GUTTER;
+        let rows = TEXTURES.len().div_ceil(COLUMNS);
+
+        let mut atlas = Image::gen_image_color((cell_width * COLUMNS) as u16, (cell_height * rows) as u16, BLANK);
+        let stride = atlas.width as usize * 4;
+        let mut sources = [Rect::default(); 14];
+
+        for (i, image) in images.iter().enumerate() {
+            let (x, y) = ((i % COLUMNS) * cell_width, (i / COLUMNS) * cell_height);
+            let row = image.width as usize * 4;
+
+            for (line, pixels) in image.bytes.chunks_exact(row).enumerate() {
+                let start = (y + line) * stride + x * 4;
+                atlas.bytes[start..start + row].copy_from_slice(pixels);
+            }
+
+            sources[i] = Rect::new(x as f32, y as f32, image.width as f32, image.height as f32);
+        }
+
+        let texture = Texture2D::from_image(&atlas);
+        let mut pieces = [Rect::default(); 12];
+        pieces.copy_from_slice(&sources[..12]);
+
+        Renderer { pieces: PieceSprites { texture, sources: pieces }, squares: [sources[12], sources[13]] }
+    }
+
+    // the light or dark square, `size` across
+    pub(crate) fn draw_square(&self, light: bool, x: f32, y: f32, size: f32) {
+        let source = self.squares[usize::from(light)];
+        draw_texture_ex(self.pieces.texture, x, y, WHITE, DrawTextureParams { dest_size: Some(vec2(size, size)), source: Some(source), ..Default::default() });
+    }
+
+    // an outline around the square `size` across at `x`, `y`, like the one under a dragged piece
+    pub(crate) fn draw_square_outline(&self, x: f32, y: f32, size: f32, color: Color) {
+        draw_rectangle_lines(x, y, size, size, size / 12.0, color);
+    }
+
+    // draws a static position (no animations) with white at the bottom
+    pub(crate) fn draw_position(&self, game: &Game, square_size: f32) {
+        self.draw_board(game, square_size, false);
+    }
+
+    // draws a static position, black at the bottom if `flipped`
+    pub(crate) fn draw_board(&self, game: &Game, square_size: f32, flipped: bool) {
+        self.draw_board_at(game, Vec2::ZERO, square_size, flipped);
+    }
+
+    // `draw_board` with the board's top left corner at `origin`
+    pub(crate) fn draw_board_at(&self, game: &Game, origin: Vec2, square_size: f32, flipped: bool) {
+        for y in 0..8 {
+            for x in 0..8 {
+                let (bx, by) = if flipped { (7 - x, y) } else { (x, 7 - y) };
+                let (dx, dy) = (origin.x + bx as f32 * square_size, origin.y + by as f32 * square_size);
+
+                self.draw_square((x + y) % 2 == 1, dx, dy, square_size);
+
+                if let Some(piece) = game.board[Square::at(File::new(x), Rank::new(y))] {
+                    self.pieces.draw_sized(piece, dx, dy, square_size);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use std::sync::mpsc::{Receiver, TryRecvError};
+use macroquad::prelude::*;
+use macroquad::hash;
+use macroquad::ui::root_ui;
+use chess::archive::ArchivedGame;
+use chess::analysis::{analyse_game, win_percent, MoveClass, Report};
+use chess::error::UciError;
+use chess::uci::Uci;
+use chess::{Color, History};
+use crate::gui::i18n::{tr, tr_color, trf};
+
+const LINE_HEIGHT: f32 = 30.0;
+
+enum ReportMessage {
+    Progress(usize, usize),
+    Done(Result<Report, UciError>),
+}
+
+fn start_analysis(history: History, depth: u8) -> Receiver<ReportMessage> {
+    let (s, rx) = std::sync::mpsc::channel();
+
+    chess::platform::spawn(move || {
+        let report = Uci::new().and_then(|mut engine| {
+            analyse_game(&mut engine, &history, depth, |done, total| {
+                let _ = s.send(ReportMessage::Progress(done, total));
+            })
+        });
+
+        let _ = s.send(ReportMessage::Done(report));
+    });
+
+    rx
+}
+
+fn class_color(class: MoveClass) -> macroquad::color::Color {
+    match class {
+        MoveClass::Good => { WHITE }
+        MoveClass::Inaccuracy => { YELLOW }
+        MoveClass::Mistake => { ORANGE }
+        MoveClass::Blunder => { RED }
+    }
+}
+
+// runs the engine over the game and shows the annotated move list, which can be saved into the archived game.
+// returns the ply picked on the evaluation graph, to show that position
+pub(crate) async fn analysis_report(history: History, mut archived: Option<&mut ArchivedGame>) -> Option<usize> {
+    let mut depth = 14.0;
+    let mut receiver: Option<Receiver<ReportMessage>> = None;
+    let mut progress = (0, history.len() + 1);
+    let mut report: Option<Result<Report, UciError>> = None;
+    let mut scroll = 0.0;
+    let mut status = String::new();
+
+    loop {
+        clear_background(GRAY);
+        let (width, height) = (screen_width(), screen_height());
+
+        if let Some(rx) = &receiver {
+            loop {
+                match rx.try_recv() {
+                    Ok(ReportMessage::Progress(done, total)) => { progress = (done, total); }
+                    Ok(ReportMessage::Done(res)) => { report = Some(res); }
+                    Err(TryRecvError::Empty) => { break; }
+                    Err(TryRecvError::Disconnected) => {
+                        receiver = None;
+                        break;
+                    }
+                }
+            }
+        }
+
+        match &report {
+            None if receiver.is_none() => {
+                root_ui().slider(hash!(), tr("report.depth"), 6.0..24.0, &mut depth);
+
+                if root_ui().button(None, tr("report.analyse")) {
+                    receiver = Some(start_analysis(history.clone(), depth.round() as u8));
+                }
+            }
+            None => {
+                let (done, total) = progress;
+                draw_text(&trf("report.progress", &[&done.to_string(), &total.to_string()]), 10.0, 40.0, 32.0, WHITE);
+
+                draw_rectangle(10.0, 60.0, width - 20.0, 20.0, DARKGRAY);
+                draw_rectangle(10.0, 60.0, (width - 20.0) * done as f32 / total.max(1) as f32, 20.0, GREEN);
+            }
+            Some(Err(err)) => {
+                draw_text(&trf("report.failed", &[&err.to_string()]), 10.0, 40.0, 32.0, WHITE);
+            }
+            Some(Ok(report)) => {
+                let mut y = 40.0;
+                for color in [Color::White, Color::Black] {
+                    let counts = [MoveClass::Inaccuracy, MoveClass::Mistake, MoveClass::Blunder].map(|x| report.count(color, x).to_string());
+                    let text = trf("report.summary", &[tr_color(color), &format!("{:.1}", report.accuracy(color)), &counts[0], &counts[1], &counts[2]]);
+
+                    draw_text(&text, 10.0, y, 28.0, WHITE);
+                    y += LINE_HEIGHT;
+                }
+
+                let times: Vec<Option<f32>> = history.entries().iter().map(|x| x.time.map(|x| x.as_secs_f32())).collect();
+                if times.iter().any(Option::is_some) {
+                    draw_text(tr("report.time"), 10.0, y, 28.0, WHITE);
+                    draw_heatmap(&times, Rect::new(10.0, y + 10.0, width - 20.0, 40.0));
+                    y += LINE_HEIGHT + 50.0;
+                }
+
+                draw_text(tr("report.evaluation"), 10.0, y, 28.0, WHITE);
+                if let Some(ply) = draw_eval_graph(&report.evaluations(), Rect::new(10.0, y + 10.0, width - 20.0, 100.0)) {
+                    return Some(ply);
+                }
+                y += LINE_HEIGHT + 110.0;
+
+                let top = y + 10.0;
+                let rows = report.moves.len().div_ceil(2);
+                let max_scroll = (rows as f32 * LINE_HEIGHT - (height - top - 60.0)).max(0.0);
+                scroll = (scroll - mouse_wheel().1 * LINE_HEIGHT).clamp(0.0, max_scroll);
+
+                for (i, mv) in report.moves.iter().enumerate() {
+                    let row = i / 2;
+                    let y = top + row as f32 * LINE_HEIGHT - scroll + LINE_HEIGHT;
+                    if y < top || y > height - 60.0 { continue; }
+
+                    let column = if mv.color == Color::White { 0.0 } else { width / 2.0 };
+                    if mv.color == Color::White || i == 0 {
+                        draw_text(&format!("{}.", row + 1), 10.0, y, 28.0, WHITE);
+                    }
+
+                    let mut text = format!("{}{}", mv.san, mv.class.glyph());
+                    if let Some(best) = mv.best.as_ref().filter(|_| mv.class != MoveClass::Good) {
+                        text.push_str(&format!(" {}", trf("report.best", &[best])));
+                    }
+
+                    if let Some(time) = times.get(i).copied().flatten() {
+                        text.push_str(&format!(" {:.1}s", time));
+                    }
+
+                    draw_text(&text, column + 70.0, y, 28.0, class_color(mv.class));
+                }
+            }
+        }
+
+        if let (Some(Ok(report)), Some(game)) = (&report, archived.as_deref_mut()) {
+            if root_ui().button(vec2(80.0, height - 50.0), tr("report.annotate")) {
+                report.annotate(&mut game.pgn);
+
+                status = match game.save() {
+                    Ok(()) => { tr("report.annotated").to_string() }
+                    Err(err) => { trf("report.annotate_failed", &[&err.to_string()]) }
+                };
+            }
+        }
+
+        if !status.is_empty() {
+            draw_text(&status, 260.0, height - 30.0, 24.0, WHITE);
+        }
+
+        if root_ui().button(vec2(10.0, height - 50.0), tr("back")) || is_key_pressed(KeyCode::Escape) {
+            return None;
+        }
+
+        next_frame().await;
+    }
+}
+
+// one cell per move, alternating between the top and bottom row like the two players, redder the longer it took
+fn draw_heatmap(times: &[Option<f32>], area: Rect) {
+    let longest = times.iter().flatten().fold(0.0f32, |a, b| a.max(*b)).max(0.1);
+    let columns = times.len().div_ceil(2).max(1);
+    let (w, h) = (area.w / columns as f32, area.h / 2.0);
+
+    for (i, time) in times.iter().enumerate() {
+        let (x, y) = (area.x + (i / 2) as f32 * w, area.y + (i % 2) as f32 * h);
+
+        let color = match time {
+            Some(time) => {
+                let heat = time / longest;
+                macroquad::color::Color::new(heat, 0.2, 1.0 - heat, 1.0)
+            }
+            None => { DARKGRAY }
+        };
+        draw_rectangle(x, y, w, h, color);
+    }
+}
+
+// white's winning chances over the game, white's share filled in from the bottom. the position under the
+// mouse is marked, a click on it returns its ply
+fn draw_eval_graph(evals: &[i32], area: Rect) -> Option<usize> {
+    draw_rectangle(area.x, area.y, area.w, area.h, DARKGRAY);
+    if evals.is_empty() { return None; }
+
+    let step = area.w / (evals.len() - 1).max(1) as f32;
+    let point = |ply: usize| vec2(area.x + ply as f32 * step, area.y + area.h * (1.0 - win_percent(evals[ply]) / 100.0));
+    let bottom = area.y + area.h;
+
+    for ply in 1..evals.len() {
+        let (a, b) = (point(ply - 1), point(ply));
+
+        draw_triangle(a, b, vec2(a.x, bottom), WHITE);
+        draw_triangle(b, vec2(b.x, bottom), vec2(a.x, bottom), WHITE);
+        draw_line(a.x, a.y, b.x, b.y, 2.0, GRAY);
+    }
+    draw_line(area.x, area.y + area.h / 2.0, area.x + area.w, area.y + area.h / 2.0, 1.0, GRAY);
+
+    let (mx, my) = mouse_position();
+    if !area.contains(vec2(mx, my)) { return None; }
+
+    let ply = (((mx - area.x) / step).round() as usize).min(evals.len() - 1);
+    let marker = point(ply);
+    draw_line(marker.x, area.y, marker.x, bottom, 2.0, BLUE);
+    draw_circle(marker.x, marker.y, 4.0, BLUE);
+
+    let label = format!("{}{:.2}", if evals[ply] > 0 { "+" } else { "" }, evals[ply] as f32 / 100.0);
+    let x = if marker.x + 80.0 > area.x + area.w { marker.x - 80.0 } else { marker.x + 6.0 };
+    draw_text(&label, x, area.y + 20.0, 24.0, BLUE);
+
+    is_mouse_button_pressed(MouseButton::Left).then_some(ply)
+}
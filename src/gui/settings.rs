@@ -0,0 +1,451 @@
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
+use std::time::Duration;
+use macroquad::prelude::*;
+use macroquad::hash;
+use macroquad::ui::root_ui;
+use chess::adjudication::Adjudication;
+use chess::bots::Personality;
+use chess::error::UciError;
+use chess::uci::{EngineConfig, ThreadedUciBuilder};
+use crate::gui::animation::AnimationSpeed;
+use crate::gui::assets::{sound_packs, DEFAULT_SOUNDS, DEFAULT_TEXTURES, SOUND_PACKS_DIR};
+use crate::gui::diagnostics::engine_diagnostics;
+use crate::gui::i18n::{language, locales, set_language, tr, tr_all, trf};
+use crate::gui::show_error;
+
+pub(crate) const SETTINGS_FILE: &str = "settings.txt";
+
+// how long a new engine gets to answer `uci`
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+// how long the engine's moves are held back
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub(crate) enum ReplyDelay {
+    Instant,
+    #[default]
+    OneSecond,
+    // a random pause, like a person thinking
+    HumanLike,
+}
+
+impl ReplyDelay {
+    pub(crate) const ALL: [ReplyDelay; 3] = [ReplyDelay::Instant, ReplyDelay::OneSecond, ReplyDelay::HumanLike];
+    pub(crate) const NAMES: [&'static str; 3] = ["Instant", "At least 1s", "Human-like"];
+    pub(crate) const KEYS: [&'static str; 3] = ["delay.instant", "delay.one_second", "delay.human_like"];
+
+    pub(crate) fn builder(self) -> ThreadedUciBuilder {
+        match self {
+            ReplyDelay::Instant => { ThreadedUciBuilder::new().instant() }
+            ReplyDelay::OneSecond => { ThreadedUciBuilder::new().min_delay(Duration::from_secs(1)) }
+            ReplyDelay::HumanLike => {
+                ThreadedUciBuilder::new()
+                    .min_delay(Duration::from_millis(500))
+                    .random_delay(Duration::ZERO, Duration::from_millis(2_500))
+            }
+        }
+    }
+}
+
+// options picked from the settings screen
+#[derive(Clone, Default)]
+pub(crate) struct Settings {
+    // the name of the language shown, None for english
+    pub(crate) language: Option<String>,
+    pub(crate) colorblind: bool,
+    pub(crate) high_contrast: bool,
+    // the folder piece and square textures are loaded from, None for assets/
+    pub(crate) textures: Option<String>,
+    // the folder in sounds/ the sounds are loaded from, None for assets/
+    pub(crate) sound_pack: Option<String>,
+    pub(crate) animation_speed: AnimationSpeed,
+    pub(crate) reply_delay: ReplyDelay,
+    // rules for ending engine games early, none to always play them out
+    pub(crate) adjudication: Option<Adjudication>,
+    // a desktop notification when a correspondence opponent replies
+    pub(crate) notifications: bool,
+    // engines to play against, besides the uci script
+    pub(crate) engines: Vec<EngineConfig>,
+}
+
+impl Settings {
+    // the engines from the settings, then the built-in bots
+    pub(crate) fn opponents(&self) -> Vec<EngineConfig> {
+        self.engines.iter().cloned().chain(Personality::ALL.into_iter().filter_map(Personality::config)).collect()
+    }
+
+    // the saved settings, or the defaults if there are none
+    pub(crate) fn load() -> Settings {
+        std::fs::read_to_string(SETTINGS_FILE).map(|x| Settings::parse(&x)).unwrap_or_default()
+    }
+
+    pub(crate) fn save(&self) -> std::io::Result<()> {
+        std::fs::write(SETTINGS_FILE, self.to_string())
+    }
+
+    pub(crate) fn texture_dir(&self) -> &str {
+        self.textures.as_deref().unwrap_or(DEFAULT_TEXTURES)
+    }
+
+    pub(crate) fn sound_dir(&self) -> String {
+        match &self.sound_pack {
+            Some(pack) => { format!("{}/{}", SOUND_PACKS_DIR, pack) }
+            None => { DEFAULT_SOUNDS.to_string() }
+        }
+    }
+
+    // `key = value` lines, each `[engine]` line starts a new engine
+    fn parse(text: &str) -> Settings {
+        let mut settings = Settings::default();
+
+        for line in text.lines().map(str::trim) {
+            if line == "[engine]" {
+                settings.engines.push(EngineConfig::default());
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else { continue; };
+            let (key, value) = (key.trim(), value.trim());
+
+            match (key, settings.engines.last_mut()) {
+                ("name", Some(engine)) => { engine.name = value.to_string(); }
+                ("path", Some(engine)) => { engine.path = value.to_string(); }
+                ("arg", Some(engine)) => { engine.args.push(value.to_string()); }
+                ("dir", Some(engine)) => { engine.working_dir = Some(value.into()); }
+                ("env", Some(engine)) => {
+                    if let Some((name, value)) = value.split_once('=') {
+                        engine.env.push((name.trim().to_string(), value.trim().to_string()));
+                    }
+                }
+                ("option", Some(engine)) => {
+                    if let Some((name, value)) = value.split_once('=') {
+                        engine.options.push((name.trim().to_string(), value.trim().to_string()));
+                    }
+                }
+                ("language", None) => { settings.language = Some(value.to_string()).filter(|x| !x.is_empty()); }
+                ("colorblind", None) => { settings.colorblind = value == "true"; }
+                ("high_contrast", None) => { settings.high_contrast = value == "true"; }
+                ("textures", None) => { settings.textures = Some(value.to_string()).filter(|x| !x.is_empty()); }
+                ("sound_pack", None) => { settings.sound_pack = Some(value.to_string()).filter(|x| !x.is_empty()); }
+                ("animation_speed", None) => {
+                    if let Some(i) = AnimationSpeed::NAMES.iter().position(|x| *x == value) {
+                        settings.animation_speed = AnimationSpeed::ALL[i];
+                    }
+                }
+                ("reply_delay", None) => {
+                    if let Some(i) = ReplyDelay::NAMES.iter().position(|x| *x == value) {
+                        settings.reply_delay = ReplyDelay::ALL[i];
+                    }
+                }
+                ("adjudication", None) => { settings.adjudication = parse_adjudication(value); }
+                ("notifications", None) => { settings.notifications = value == "true"; }
+                _ => {}
+            }
+        }
+
+        settings.engines.retain(|x| !x.path.is_empty());
+        settings
+    }
+}
+
+impl std::fmt::Display for Settings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(language) = &self.language {
+            writeln!(f, "language = {}", language)?;
+        }
+        writeln!(f, "colorblind = {}", self.colorblind)?;
+        writeln!(f, "high_contrast = {}", self.high_contrast)?;
+        if let Some(textures) = &self.textures {
+            writeln!(f, "textures = {}", textures)?;
+        }
+        if let Some(pack) = &self.sound_pack {
+            writeln!(f, "sound_pack = {}", pack)?;
+        }
+
+        let speed = AnimationSpeed::ALL.iter().position(|x| *x == self.animation_speed).unwrap_or(0);
+        writeln!(f, "animation_speed = {}", AnimationSpeed::NAMES[speed])?;
+
+        let delay = ReplyDelay::ALL.iter().position(|x| *x == self.reply_delay).unwrap_or(0);
+        writeln!(f, "reply_delay = {}", ReplyDelay::NAMES[delay])?;
+
+        match self.adjudication {
+            Some(x) => {
+                writeln!(f, "adjudication = {} {} {} {} {} {}", x.resign_score, x.resign_moves, x.draw_score, x.draw_moves,
+                         x.draw_after, x.tablebase_pieces)?;
+            }
+            None => { writeln!(f, "adjudication = off")?; }
+        }
+        writeln!(f, "notifications = {}", self.notifications)?;
+
+        for engine in &self.engines {
+            writeln!(f, "\n[engine]")?;
+            writeln!(f, "name = {}", engine.name)?;
+            writeln!(f, "path = {}", engine.path)?;
+
+            for arg in &engine.args {
+                writeln!(f, "arg = {}", arg)?;
+            }
+
+            if let Some(dir) = &engine.working_dir {
+                writeln!(f, "dir = {}", dir.display())?;
+            }
+
+            for (name, value) in &engine.env {
+                writeln!(f, "env = {} = {}", name, value)?;
+            }
+
+            for (name, value) in &engine.options {
+                writeln!(f, "option = {} = {}", name, value)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// `resign_score resign_moves draw_score draw_moves draw_after tablebase_pieces`, or `off`
+fn parse_adjudication(value: &str) -> Option<Adjudication> {
+    let numbers: Vec<i32> = value.split_whitespace().map(str::parse).collect::<Result<_, _>>().ok()?;
+    let &[resign_score, resign_moves, draw_score, draw_moves, draw_after, tablebase_pieces] = numbers.as_slice() else { return None; };
+
+    Some(Adjudication {
+        resign_score,
+        resign_moves: resign_moves.try_into().ok()?,
+        draw_score,
+        draw_moves: draw_moves.try_into().ok()?,
+        draw_after: draw_after.try_into().ok()?,
+        tablebase_pieces: tablebase_pieces.try_into().ok()?,
+    })
+}
+
+// the engine being typed into the settings screen
+#[derive(Default)]
+struct NewEngine {
+    name: String,
+    path: String,
+    args: String,
+    options: String,
+    working_dir: String,
+    env: String,
+}
+
+impl NewEngine {
+    // args split on whitespace, options and environment variables as `Name=Value` separated by `;`
+    fn config(&self) -> EngineConfig {
+        let mut config = EngineConfig::new(self.name.trim(), self.path.trim())
+            .args(self.args.split_whitespace());
+
+        for option in self.options.split(';') {
+            if let Some((name, value)) = option.split_once('=') {
+                config = config.option(name.trim(), value.trim());
+            }
+        }
+
+        for variable in self.env.split(';') {
+            if let Some((name, value)) = variable.split_once('=') {
+                config = config.env(name.trim(), value.trim());
+            }
+        }
+
+        if !self.working_dir.trim().is_empty() { config = config.working_dir(self.working_dir.trim()); }
+
+        config
+    }
+}
+
+pub(crate) async fn settings_menu(settings: &mut Settings) {
+    request_new_screen_size(640.0, 640.0);
+
+    let mut new_engine = NewEngine::default();
+    let mut status = String::new();
+    let mut textures = settings.textures.clone().unwrap_or_default();
+    // read once, the first entry is assets/
+    let packs = sound_packs();
+    let mut handshake: Option<(EngineConfig, Receiver<Result<String, UciError>>)> = None;
+
+    loop {
+        clear_background(GRAY);
+
+        // switched as soon as it's picked, this screen included
+        let names: Vec<&str> = locales().iter().map(|x| x.name()).collect();
+        let mut picked = language();
+        root_ui().combo_box(hash!(), tr("settings.language"), &names, &mut picked);
+        if picked != language() {
+            settings.language = (picked > 0).then(|| names[picked].to_string());
+            set_language(settings.language.as_deref());
+        }
+
+        root_ui().label(None, tr("settings.accessibility"));
+        root_ui().checkbox(hash!(), tr("settings.colorblind"), &mut settings.colorblind);
+        root_ui().checkbox(hash!(), tr("settings.high_contrast"), &mut settings.high_contrast);
+
+        root_ui().label(None, tr("settings.board"));
+        root_ui().input_text(hash!(), tr("settings.textures"), &mut textures);
+        settings.textures = Some(textures.trim().to_string()).filter(|x| !x.is_empty());
+
+        root_ui().label(None, tr("settings.sounds"));
+        let names: Vec<&str> = std::iter::once(tr("settings.default_sounds")).chain(packs.iter().map(String::as_str)).collect();
+        let mut pack = settings.sound_pack.as_ref().and_then(|x| packs.iter().position(|y| y == x)).map_or(0, |x| x + 1);
+        root_ui().combo_box(hash!(), tr("settings.sound_pack"), &names, &mut pack);
+        settings.sound_pack = pack.checked_sub(1).map(|x| packs[x].clone());
+
+        root_ui().label(None, tr("settings.animations"));
+        let mut speed = AnimationSpeed::ALL.iter().position(|x| *x == settings.animation_speed).unwrap_or(0);
+        root_ui().combo_box(hash!(), tr("settings.speed"), &tr_all(&AnimationSpeed::KEYS), &mut speed);
+        settings.animation_speed = AnimationSpeed::ALL[speed];
+
+        root_ui().label(None, tr("settings.engines"));
+        let mut delay = ReplyDelay::ALL.iter().position(|x| *x == settings.reply_delay).unwrap_or(0);
+        root_ui().combo_box(hash!(), tr("settings.reply_delay"), &tr_all(&ReplyDelay::KEYS), &mut delay);
+        settings.reply_delay = ReplyDelay::ALL[delay];
+
+        let mut adjudicate = settings.adjudication.is_some();
+        root_ui().checkbox(hash!(), tr("settings.adjudicate"), &mut adjudicate);
+        match (adjudicate, settings.adjudication) {
+            (true, None) => { settings.adjudication = Some(Adjudication::default()); }
+            (false, Some(_)) => { settings.adjudication = None; }
+            _ => {}
+        }
+        if let Some(x) = settings.adjudication {
+            let numbers = [x.resign_score.to_string(), x.resign_moves.to_string(), x.draw_score.to_string(), x.draw_moves.to_string(), x.draw_after.to_string()];
+            root_ui().label(None, &trf("settings.adjudication", &numbers.each_ref().map(String::as_str)));
+        }
+        root_ui().checkbox(hash!(), tr("settings.notifications"), &mut settings.notifications);
+
+        let (mut removed, mut diagnosed) = (None, None);
+        for (i, engine) in settings.engines.iter().enumerate() {
+            if root_ui().button(None, tr("settings.remove")) {
+                removed = Some(i);
+            }
+            root_ui().same_line(0.0);
+            if root_ui().button(None, tr("settings.diagnose")) {
+                diagnosed = Some(engine.clone());
+            }
+            root_ui().same_line(0.0);
+            match &engine.working_dir {
+                Some(dir) => { root_ui().label(None, &trf("settings.engine_in", &[&engine.name, &engine.path, &dir.display().to_string()])); }
+                None => { root_ui().label(None, &format!("{} ({})", engine.name, engine.path)); }
+            }
+        }
+        if let Some(i) = removed {
+            settings.engines.remove(i);
+        }
+
+        root_ui().input_text(hash!(), tr("settings.name"), &mut new_engine.name);
+        root_ui().input_text(hash!(), tr("settings.path"), &mut new_engine.path);
+        root_ui().input_text(hash!(), tr("settings.arguments"), &mut new_engine.args);
+        root_ui().input_text(hash!(), tr("settings.options"), &mut new_engine.options);
+        root_ui().input_text(hash!(), tr("settings.working_dir"), &mut new_engine.working_dir);
+        root_ui().input_text(hash!(), tr("settings.env"), &mut new_engine.env);
+
+        // the engine typed in, to see why it isn't accepted
+        if handshake.is_none() && root_ui().button(None, tr("settings.diagnose_engine")) && !new_engine.path.trim().is_empty() {
+            diagnosed = Some(new_engine.config());
+        }
+        if handshake.is_none() && root_ui().button(None, tr("settings.add_engine")) && !new_engine.path.trim().is_empty() {
+            let config = new_engine.config();
+            let (s, rx) = channel();
+
+            // the handshake can take up to the timeout, don't freeze the screen meanwhile
+            let check = config.clone();
+            chess::platform::spawn(move || { let _ = s.send(check.validate(HANDSHAKE_TIMEOUT)); });
+
+            status = trf("settings.checking", &[&config.path]);
+            handshake = Some((config, rx));
+        }
+
+        if let Some((config, rx)) = &handshake {
+            let result = match rx.try_recv() {
+                Ok(result) => { Some(result) }
+                Err(TryRecvError::Disconnected) => { Some(Err(UciError::Disconnected)) }
+                Err(TryRecvError::Empty) => { None }
+            };
+
+            match result {
+                Some(Ok(id)) => {
+                    let mut config = config.clone();
+                    if config.name.is_empty() { config.name = id; }
+
+                    status = trf("settings.added", &[&config.name]);
+                    settings.engines.push(config);
+                    new_engine = NewEngine::default();
+                    handshake = None;
+                }
+                Some(Err(err)) => {
+                    status = trf("settings.not_uci", &[&err.to_string()]);
+                    handshake = None;
+                }
+                None => {}
+            }
+        }
+
+        if !status.is_empty() {
+            root_ui().label(None, &status);
+        }
+
+        if let Some(config) = diagnosed {
+            engine_diagnostics(&config).await;
+            request_new_screen_size(640.0, 640.0);
+            next_frame().await;
+            continue;
+        }
+
+        if root_ui().button(None, tr("back")) || is_key_pressed(KeyCode::Escape) {
+            if let Err(err) = settings.save() {
+                // let the key that closed this screen go first
+                next_frame().await;
+                show_error(&trf("settings.save_failed", &[&err.to_string()])).await;
+            }
+            return;
+        }
+
+        next_frame().await;
+    }
+}
+
+// picks the engine to carry on a game with, and optionally a UCI_Elo for it. None if cancelled
+pub(crate) async fn swap_engine(settings: &Settings, current: &EngineConfig) -> Option<EngineConfig> {
+    request_new_screen_size(640.0, 360.0);
+    next_frame().await;
+
+    let mut engines = vec![EngineConfig::uci_script()];
+    engines.extend(settings.opponents());
+    let names: Vec<&str> = std::iter::once(tr("uci_script")).chain(engines[1..].iter().map(|x| x.name.as_str())).collect();
+
+    let mut picked = engines.iter().position(|x| x.path == current.path && x.args == current.args).unwrap_or(0);
+    let mut elo = String::new();
+    let mut status = String::new();
+
+    loop {
+        clear_background(GRAY);
+
+        root_ui().label(None, tr("swap.title"));
+        root_ui().combo_box(hash!(), tr("swap.engine"), &names, &mut picked);
+        root_ui().input_text(hash!(), tr("swap.elo"), &mut elo);
+
+        if root_ui().button(None, tr("swap.swap")) {
+            let mut config = engines[picked].clone();
+
+            match elo.trim() {
+                "" => { return Some(config); }
+                text => match text.parse::<u32>() {
+                    Ok(elo) => {
+                        config.options.retain(|x| !x.0.eq_ignore_ascii_case("UCI_Elo") && !x.0.eq_ignore_ascii_case("UCI_LimitStrength"));
+                        config = config.option("UCI_LimitStrength", "true").option("UCI_Elo", elo.to_string());
+                        return Some(config);
+                    }
+                    Err(_) => { status = trf("swap.not_elo", &[text]); }
+                }
+            }
+        }
+
+        if !status.is_empty() {
+            root_ui().label(None, &status);
+        }
+
+        if root_ui().button(None, tr("swap.cancel")) || is_key_pressed(KeyCode::Escape) {
+            return None;
+        }
+
+        next_frame().await;
+    }
+}
@@ -0,0 +1,197 @@
+use macroquad::prelude::*;
+use macroquad::hash;
+use macroquad::ui::root_ui;
+use chess::archive::Archive;
+use chess::simul::{Outcome, Simul};
+use chess::uci::{EngineConfig, Limits, ThreadedUci};
+use chess::{Color, Game, Square};
+use crate::gui::{board_move, square_corner, ARCHIVE_DIR};
+use crate::gui::assets::Assets;
+use crate::gui::i18n::{tr, trf};
+use crate::gui::settings::Settings;
+
+const COUNTS: [usize; 4] = [2, 4, 6, 8];
+const COUNT_NAMES: [&str; 4] = ["2", "4", "6", "8"];
+// squares of the boards in the panel, two boards to a row
+const THUMBNAIL: f32 = 15.0;
+const THUMBNAIL_GAP: f32 = 6.0;
+
+// picks the number of boards and whether the player takes black on every other one
+pub(crate) async fn simul_menu(assets: &Assets, settings: &Settings, engine: Option<EngineConfig>) {
+    request_new_screen_size(640.0, 360.0);
+
+    let mut count = 1;
+    let mut alternate = true;
+
+    loop {
+        clear_background(GRAY);
+
+        root_ui().combo_box(hash!(), tr("simul.boards"), &COUNT_NAMES, &mut count);
+        root_ui().checkbox(hash!(), tr("simul.alternate"), &mut alternate);
+
+        if root_ui().button(None, tr("simul.start")) {
+            simul(assets, settings, engine.clone(), Simul::new(COUNTS[count], Game::default(), alternate)).await;
+            request_new_screen_size(640.0, 360.0);
+        }
+
+        if root_ui().button(None, tr("back")) || is_key_pressed(KeyCode::Escape) {
+            return;
+        }
+
+        next_frame().await;
+    }
+}
+
+// the top left corner of board `i` in the panel
+fn thumbnail_corner(i: usize) -> Vec2 {
+    let size = THUMBNAIL * 8.0 + THUMBNAIL_GAP;
+    vec2(778.0 + (i % 2) as f32 * size, 110.0 + (i / 2) as f32 * size)
+}
+
+// every board at once: the one being played is drawn big, the rest in the panel, and each has an
+// engine of its own so they all think at the same time
+async fn simul(assets: &Assets, settings: &Settings, engine: Option<EngineConfig>, mut simul: Simul) {
+    let renderer = assets.renderer;
+    let count = simul.boards.len();
+
+    let name = engine.as_ref().map(|x| x.name.clone()).filter(|x| !x.is_empty()).unwrap_or_else(|| tr("engine").to_string());
+    let config = engine.unwrap_or_else(EngineConfig::uci_script);
+    let engines: Vec<ThreadedUci> = (0..count).map(|_| {
+        let engine = settings.reply_delay.builder().config(config.clone()).build();
+        engine.new_game();
+        engine
+    }).collect();
+    let limits = Limits::default().time(1_500);
+
+    // per board: a search was asked for, what went wrong with its engine and whether it was archived
+    let mut thinking = vec![false; count];
+    let mut errors: Vec<Option<String>> = vec![None; count];
+    let mut archived = vec![false; count];
+
+    let mut current = 0;
+    let mut selected = None;
+    let mut status = String::new();
+
+    request_new_screen_size(1024.0, 768.0);
+    next_frame().await;
+
+    loop {
+        clear_background(GRAY);
+
+        for (i, board) in simul.boards.iter_mut().enumerate() {
+            if !board.is_engines_turn() || errors[i].is_some() { continue; }
+
+            if !thinking[i] {
+                engines[i].recommend_move(&board.history, limits);
+                thinking[i] = true;
+            }
+
+            match engines[i].try_result() {
+                Some(Ok(mv)) => {
+                    thinking[i] = false;
+                    let fen = board.game().as_fen();
+                    if let Err(err) = board.play(mv) {
+                        errors[i] = Some(trf("game.bad_engine_move", &[&mv.to_string(), &fen, &err.to_string()]));
+                    }
+                }
+                Some(Err(err)) => { (thinking[i], errors[i]) = (false, Some(err.to_string())); }
+                None => {}
+            }
+        }
+
+        // finished games go to the archive like any other
+        for (i, board) in simul.boards.iter().enumerate() {
+            if board.outcome.is_none() || archived[i] { continue; }
+
+            archived[i] = true;
+            if let Err(err) = Archive::open(ARCHIVE_DIR).and_then(|x| x.save(&board.pgn(&name))) {
+                status = trf("game.archive_failed", &[&err.to_string()]);
+            }
+        }
+
+        let board = &simul.boards[current];
+        let game = board.game();
+        renderer.draw_position(&game, 96.0);
+        if let Some(square) = selected {
+            let corner = square_corner(Square::new(square));
+            draw_rectangle_lines(corner.x, corner.y, 96.0, 96.0, 4.0, YELLOW);
+        }
+
+        if board.is_players_turn() {
+            if let Some(mv) = board_move(&game, &mut selected) {
+                match simul.boards[current].play(mv) {
+                    Ok(_) => {
+                        status.clear();
+                        // on to the next board that is waiting, like walking round the tables
+                        current = simul.next_waiting(current).unwrap_or(current);
+                        selected = None;
+                    }
+                    Err(err) => { status = err.to_string(); }
+                }
+            }
+        }
+
+        let board = &simul.boards[current];
+        let state = match (board.outcome, &errors[current]) {
+            (Some(Outcome::Won), _) => { tr("simul.won").to_string() }
+            (Some(Outcome::Drawn), _) => { tr("simul.drawn").to_string() }
+            (Some(Outcome::Lost), _) => { tr("simul.lost").to_string() }
+            (None, Some(err)) => { err.chars().take(30).collect() }
+            (None, None) if board.is_players_turn() && board.player == Color::White => { tr("simul.your_move_white").to_string() }
+            (None, None) if board.is_players_turn() => { tr("simul.your_move_black").to_string() }
+            (None, None) => { tr("simul.thinking").to_string() }
+        };
+
+        let (won, drawn, lost) = simul.tally();
+        draw_text(&trf("simul.board", &[&(current + 1).to_string(), &count.to_string()]), 778.0, 30.0, 24.0, WHITE);
+        draw_text(&state, 778.0, 58.0, 20.0, WHITE);
+        draw_text(&trf("simul.score", &[&won.to_string(), &drawn.to_string(), &lost.to_string()]), 778.0, 86.0, 20.0, WHITE);
+
+        for (i, board) in simul.boards.iter().enumerate() {
+            let corner = thumbnail_corner(i);
+            let size = THUMBNAIL * 8.0;
+            renderer.draw_board_at(&board.game(), corner, THUMBNAIL, false);
+
+            if i == current {
+                draw_rectangle_lines(corner.x, corner.y, size, size, 4.0, YELLOW);
+            } else if board.is_players_turn() {
+                draw_rectangle_lines(corner.x, corner.y, size, size, 3.0, GREEN);
+            }
+
+            let (x, y) = mouse_position();
+            if is_mouse_button_pressed(MouseButton::Left) && Rect::new(corner.x, corner.y, size, size).contains(vec2(x, y)) {
+                (current, selected) = (i, None);
+            }
+
+            let score = match board.outcome {
+                Some(Outcome::Won) => { "1" }
+                Some(Outcome::Drawn) => { "½" }
+                Some(Outcome::Lost) => { "0" }
+                None => { continue; }
+            };
+            draw_rectangle(corner.x, corner.y + size / 2.0 - 16.0, size, 32.0, macroquad::color::Color::new(0.1, 0.1, 0.1, 0.6));
+            draw_text(score, corner.x + size / 2.0 - 8.0, corner.y + size / 2.0 + 10.0, 32.0, WHITE);
+        }
+
+        if !status.is_empty() {
+            draw_text(&status, 778.0, 640.0, 20.0, WHITE);
+        }
+
+        if (root_ui().button(vec2(778.0, 660.0), tr("simul.next")) || is_key_pressed(KeyCode::Tab)) && !simul.is_finished() {
+            current = simul.next_waiting(current).unwrap_or(current);
+            selected = None;
+        }
+
+        if simul.boards[current].outcome.is_none() && root_ui().button(vec2(778.0, 685.0), tr("simul.resign")) {
+            engines[current].cancel();
+            thinking[current] = false;
+            simul.boards[current].resign();
+        }
+
+        if root_ui().button(vec2(778.0, 710.0), tr("back")) || is_key_pressed(KeyCode::Escape) {
+            return;
+        }
+
+        next_frame().await;
+    }
+}
@@ -0,0 +1,137 @@
+use macroquad::prelude::*;
+use macroquad::ui::root_ui;
+use chess::rating::Rating;
+use chess::uci::EngineConfig;
+use crate::gui::i18n::{tr, trf};
+
+pub(crate) const RATING_FILE: &str = "rating.txt";
+
+// a game against an engine and the player's rating after it
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) struct RatedGame {
+    pub(crate) rating: Rating,
+    pub(crate) opponent: String,
+    pub(crate) opponent_rating: f64,
+    // 1 for a win, 0.5 for a draw
+    pub(crate) score: f64,
+}
+
+// every rated game, oldest first
+#[derive(Clone, PartialEq, Debug, Default)]
+pub(crate) struct RatingLog {
+    pub(crate) games: Vec<RatedGame>,
+}
+
+impl RatingLog {
+    pub(crate) fn load() -> RatingLog {
+        std::fs::read_to_string(RATING_FILE).map(|x| RatingLog::parse(&x)).unwrap_or_default()
+    }
+
+    pub(crate) fn save(&self) -> std::io::Result<()> {
+        let lines: Vec<String> = self.games.iter()
+            .map(|x| format!("{} {} {} {} {} {}\n", x.rating.rating, x.rating.deviation, x.rating.volatility, x.opponent_rating, x.score, x.opponent))
+            .collect();
+
+        std::fs::write(RATING_FILE, lines.concat())
+    }
+
+    // `rating deviation volatility opponent_rating score opponent` lines, the name can have spaces
+    fn parse(text: &str) -> RatingLog {
+        let games = text.lines().filter_map(|line| {
+            let mut fields = line.splitn(6, ' ');
+            let mut number = || fields.next()?.parse::<f64>().ok();
+
+            let rating = Rating { rating: number()?, deviation: number()?, volatility: number()? };
+            let (opponent_rating, score) = (number()?, number()?);
+
+            Some(RatedGame { rating, opponent: fields.next().unwrap_or_default().to_string(), opponent_rating, score })
+        }).collect();
+
+        RatingLog { games }
+    }
+
+    pub(crate) fn current(&self) -> Rating {
+        self.games.last().map(|x| x.rating).unwrap_or_default()
+    }
+
+    // rates a finished game, each game is its own rating period
+    pub(crate) fn record(&mut self, opponent: &str, opponent_rating: f64, score: f64) -> Rating {
+        let rating = self.current().update(&[(Rating::fixed(opponent_rating), score)]);
+        self.games.push(RatedGame { rating, opponent: opponent.to_string(), opponent_rating, score });
+
+        rating
+    }
+}
+
+// the strength an engine is set to play at, games against engines without one aren't rated
+pub(crate) fn engine_rating(config: &EngineConfig) -> Option<f64> {
+    config.options.iter().find(|x| x.0.eq_ignore_ascii_case("UCI_Elo")).and_then(|x| x.1.trim().parse().ok())
+}
+
+// the player's rating, record and rating graph
+pub(crate) async fn stats_screen() {
+    request_new_screen_size(800.0, 600.0);
+    next_frame().await;
+
+    let log = RatingLog::load();
+    let rating = log.current();
+
+    let wins = log.games.iter().filter(|x| x.score == 1.0).count();
+    let losses = log.games.iter().filter(|x| x.score == 0.0).count();
+    let draws = log.games.len() - wins - losses;
+
+    loop {
+        clear_background(GRAY);
+
+        draw_text(&trf("stats.rating", &[&format!("{:.0}", rating.rating), &format!("{:.0}", 2.0 * rating.deviation)]), 10.0, 40.0, 40.0, WHITE);
+        let record = [log.games.len(), wins, draws, losses].map(|x| x.to_string());
+        draw_text(&trf("stats.games", &record.each_ref().map(String::as_str)), 10.0, 80.0, 28.0, WHITE);
+
+        if log.games.is_empty() {
+            draw_text(tr("stats.unrated"), 10.0, 120.0, 24.0, WHITE);
+        } else {
+            draw_graph(&log, Rect::new(10.0, 110.0, 780.0, 420.0));
+        }
+
+        if root_ui().button(vec2(10.0, 550.0), tr("back")) || is_key_pressed(KeyCode::Escape) {
+            return;
+        }
+
+        next_frame().await;
+    }
+}
+
+// the rating after each game, with a band two deviations wide either side
+fn draw_graph(log: &RatingLog, area: Rect) {
+    let points: Vec<Rating> = std::iter::once(Rating::default()).chain(log.games.iter().map(|x| x.rating)).collect();
+
+    let low = points.iter().map(|x| x.rating - 2.0 * x.deviation).fold(f64::MAX, f64::min);
+    let high = points.iter().map(|x| x.rating + 2.0 * x.deviation).fold(f64::MIN, f64::max);
+    let y = |rating: f64| area.y + area.h - ((rating - low) / (high - low).max(1.0)) as f32 * area.h;
+    let step = area.w / (points.len() - 1).max(1) as f32;
+
+    draw_rectangle(area.x, area.y, area.w, area.h, DARKGRAY);
+
+    for (i, point) in points.iter().enumerate() {
+        let x = area.x + i as f32 * step;
+        let (top, bottom) = (y(point.rating + 2.0 * point.deviation), y(point.rating - 2.0 * point.deviation));
+        draw_line(x, top, x, bottom, 3.0, Color::new(1.0, 1.0, 1.0, 0.25));
+
+        if i > 0 {
+            let last = &points[i - 1];
+            draw_line(x - step, y(last.rating), x, y(point.rating), 2.0, WHITE);
+        }
+    }
+
+    draw_text(&format!("{:.0}", high), area.x + 4.0, area.y + 20.0, 20.0, WHITE);
+    draw_text(&format!("{:.0}", low), area.x + 4.0, area.y + area.h - 6.0, 20.0, WHITE);
+}
+
+// saves a rated game, returning the rating before and after it
+pub(crate) fn rate_game(opponent: &str, opponent_rating: f64, score: f64) -> std::io::Result<(Rating, Rating)> {
+    let mut log = RatingLog::load();
+    let before = log.current();
+    let after = log.record(opponent, opponent_rating, score);
+
+    log.save().map(|_| (before, after))
+}
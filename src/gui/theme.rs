@@ -0,0 +1,82 @@
+use macroquad::prelude::*;
+use crate::gui::settings::Settings;
+
+// highlight and board colors used while drawing a game
+#[derive(Copy, Clone)]
+pub(crate) struct Theme {
+    pub(crate) selected: Color,
+    pub(crate) move_dot: Color,
+    pub(crate) capture_dot: Color,
+    pub(crate) check: Color,
+    pub(crate) draw: Color,
+    pub(crate) last_move: Color,
+    pub(crate) threat: Color,
+    pub(crate) hanging: Color,
+    // lines from a pinning piece to the piece it pins
+    pub(crate) pin: Color,
+    // beginner assist: the glow under pieces that can move, and the tint of ones that can't
+    pub(crate) movable: Color,
+    pub(crate) unmovable: Color,
+    pub(crate) cursor: Color,
+    // the tint of a dragged piece left on its square, and the outline of the square it would land on
+    pub(crate) ghost: Color,
+    pub(crate) drop_target: Color,
+    // squares marked with a long press or right click
+    pub(crate) annotation: Color,
+    pub(crate) banner: Color,
+    // plain (light, dark) squares instead of the board textures
+    pub(crate) squares: Option<(Color, Color)>,
+}
+
+impl Theme {
+    pub(crate) const DEFAULT: Theme = Theme {
+        selected: Color::new(0.20, 0.20, 0.20, 0.2),
+        move_dot: Color::new(0.10, 0.10, 0.10, 0.4),
+        capture_dot: Color::new(0.92, 0.20, 0.20, 0.5),
+        check: Color::new(0.92, 0.20, 0.20, 0.5),
+        draw: Color::new(0.10, 0.10, 0.10, 0.4),
+        last_move: Color::new(0.95, 0.90, 0.20, 0.3),
+        threat: Color::new(0.95, 0.55, 0.10, 0.25),
+        hanging: Color::new(0.92, 0.20, 0.20, 0.5),
+        pin: Color::new(0.55, 0.15, 0.75, 0.6),
+        movable: Color::new(0.95, 0.95, 0.60, 0.35),
+        unmovable: Color::new(0.55, 0.55, 0.55, 0.6),
+        cursor: Color::new(0.15, 0.45, 0.95, 0.8),
+        ghost: Color::new(1.00, 1.00, 1.00, 0.35),
+        drop_target: Color::new(0.10, 0.10, 0.10, 0.5),
+        annotation: Color::new(0.10, 0.65, 0.25, 0.8),
+        banner: Color::new(0.10, 0.10, 0.10, 0.4),
+        squares: None,
+    };
+
+    // okabe-ito colors, told apart with deuteranopia and protanopia
+    pub(crate) const COLORBLIND: Theme = Theme {
+        capture_dot: Color::new(0.84, 0.37, 0.00, 0.6),
+        check: Color::new(0.84, 0.37, 0.00, 0.6),
+        last_move: Color::new(0.34, 0.71, 0.91, 0.35),
+        threat: Color::new(0.00, 0.45, 0.70, 0.25),
+        hanging: Color::new(0.94, 0.89, 0.26, 0.8),
+        pin: Color::new(0.90, 0.62, 0.00, 0.7),
+        movable: Color::new(0.34, 0.71, 0.91, 0.4),
+        cursor: Color::new(0.80, 0.47, 0.65, 0.9),
+        drop_target: Color::new(0.00, 0.45, 0.70, 0.8),
+        annotation: Color::new(0.00, 0.62, 0.45, 0.9),
+        ..Theme::DEFAULT
+    };
+
+    pub(crate) fn new(settings: &Settings) -> Theme {
+        let mut theme = if settings.colorblind { Theme::COLORBLIND } else { Theme::DEFAULT };
+
+        if settings.high_contrast {
+            theme.squares = Some((WHITE, Color::new(0.45, 0.45, 0.45, 1.0)));
+
+            for color in [&mut theme.selected, &mut theme.move_dot, &mut theme.capture_dot, &mut theme.check,
+                          &mut theme.draw, &mut theme.last_move, &mut theme.threat, &mut theme.hanging, &mut theme.pin, &mut theme.movable,
+                          &mut theme.drop_target] {
+                color.a = (color.a * 2.0).min(0.9);
+            }
+        }
+
+        theme
+    }
+}
@@ -0,0 +1,260 @@
+use macroquad::prelude::*;
+use macroquad::hash;
+use macroquad::ui::root_ui;
+use chess::sprt::Sprt;
+use chess::tournament::{OpeningSuite, RunningTournament, Tournament, TournamentResults};
+use chess::uci::Limits;
+use crate::gui::i18n::{tr, trf};
+use crate::gui::settings::Settings;
+use crate::gui::EXPORT_DIR;
+
+// finished games listed under the crosstable
+const RECENT_GAMES: usize = 12;
+
+// picks engines and options for a round robin, then shows it live while it plays
+pub(crate) async fn tournament_menu(settings: &Settings) {
+    request_new_screen_size(800.0, 720.0);
+
+    let mut playing: Vec<bool> = settings.engines.iter().map(|_| true).collect();
+    let mut rounds = "1".to_string();
+    let mut move_time = "100".to_string();
+    // a clock in seconds replaces the move time, the increment is in milliseconds
+    let mut clock = String::new();
+    let mut increment = "0".to_string();
+    let mut save_to = format!("{}/tournament.pgn", EXPORT_DIR);
+    let mut openings = String::new();
+
+    let threads = std::thread::available_parallelism().map(|x| x.get()).unwrap_or(1);
+    let concurrency_names: Vec<String> = (1..=threads).map(|x| x.to_string()).collect();
+    let concurrency_names: Vec<&str> = concurrency_names.iter().map(String::as_str).collect();
+    let mut concurrency = 0;
+
+    let mut use_sprt = false;
+    // elo0, elo1, alpha, beta
+    let mut sprt_fields = ["0".to_string(), "5".to_string(), "0.05".to_string(), "0.05".to_string()];
+
+    let mut status = String::new();
+    let mut running: Option<RunningTournament> = None;
+    // the file finished games are added to, fixed once the tournament starts
+    let mut pgn_file = String::new();
+    let mut recent: Vec<String> = Vec::new();
+
+    loop {
+        clear_background(GRAY);
+
+        if let Some(tournament) = &running {
+            while let Some(game) = tournament.try_game() {
+                match game {
+                    Ok(game) => {
+                        let results = tournament.results();
+                        let opening = game.opening.as_ref().map(|x| format!(", {}", x)).unwrap_or_default();
+
+                        recent.push(format!("{} - {} {} ({}{})", results.names[game.white], results.names[game.black],
+                                            game.result.pgn_result(), game.result.reason, opening));
+
+                        if let Err(err) = append_pgn(&pgn_file, &game.pgn.to_string()) {
+                            status = trf("tournament.save_game_failed", &[&pgn_file, &err.to_string()]);
+                        }
+                    }
+                    Err(err) => { status = trf("tournament.stopped", &[&err.to_string()]); }
+                }
+            }
+            if recent.len() > RECENT_GAMES { recent.drain(..recent.len() - RECENT_GAMES); }
+
+            let results = tournament.results();
+            let key = if tournament.is_finished() { "tournament.finished" } else { "tournament.playing" };
+            draw_text(&trf(key, &[&results.games.len().to_string(), &tournament.total().to_string()]), 10.0, 30.0, 28.0, WHITE);
+
+            // drawn cell by cell, the ui font isn't monospaced
+            let crosstable = results.crosstable();
+            let ratings = results.ratings();
+            for (engine, name) in crosstable.names.iter().enumerate() {
+                let y = 70.0 + engine as f32 * 24.0;
+                draw_text(&format!("{}. {}", engine + 1, name), 10.0, y, 24.0, WHITE);
+
+                for opponent in 0..crosstable.names.len() {
+                    let cell = if engine == opponent { "-".to_string() } else { crosstable.score(engine, opponent).to_string() };
+                    draw_text(&cell, 260.0 + opponent as f32 * 60.0, y, 24.0, WHITE);
+                }
+                draw_text(&crosstable.total(engine).to_string(), 280.0 + crosstable.names.len() as f32 * 60.0, y, 24.0, WHITE);
+                draw_text(&ratings[engine].to_string(), 360.0 + crosstable.names.len() as f32 * 60.0, y, 24.0, WHITE);
+            }
+
+            let top = 100.0 + results.names.len() as f32 * 24.0;
+            for (i, line) in recent.iter().enumerate() {
+                draw_text(line, 10.0, top + i as f32 * 22.0, 20.0, WHITE);
+            }
+
+            if results.sprt.is_some() {
+                draw_llr(&results, Rect::new(400.0, 400.0, 380.0, 200.0));
+            }
+
+            if !status.is_empty() {
+                draw_text(&status, 10.0, 640.0, 24.0, WHITE);
+            }
+
+            if root_ui().button(vec2(80.0, 660.0), tr("tournament.export")) {
+                status = match export_results(&results) {
+                    Ok(path) => { trf("tournament.exported", &[&path, &path, &path, &path]) }
+                    Err(err) => { trf("tournament.export_failed", &[&err.to_string()]) }
+                };
+            }
+
+            if root_ui().button(vec2(10.0, 660.0), tr("back")) || is_key_pressed(KeyCode::Escape) {
+                // games in progress are finished in the background
+                tournament.stop();
+                return;
+            }
+
+            next_frame().await;
+            continue;
+        }
+
+        root_ui().label(None, tr("tournament.engines"));
+        if settings.engines.is_empty() {
+            root_ui().label(None, tr("tournament.no_engines"));
+        }
+        for (i, engine) in settings.engines.iter().enumerate() {
+            root_ui().checkbox(hash!(("engine", i)), &engine.name, &mut playing[i]);
+        }
+
+        root_ui().input_text(hash!(), tr("tournament.rounds"), &mut rounds);
+        root_ui().input_text(hash!(), tr("tournament.move_time"), &mut move_time);
+        root_ui().input_text(hash!(), tr("tournament.clock"), &mut clock);
+        root_ui().input_text(hash!(), tr("tournament.increment"), &mut increment);
+        root_ui().input_text(hash!(), tr("tournament.openings"), &mut openings);
+        root_ui().combo_box(hash!(), tr("tournament.concurrency"), &concurrency_names, &mut concurrency);
+        root_ui().input_text(hash!(), tr("tournament.save_to"), &mut save_to);
+
+        root_ui().checkbox(hash!(), tr("tournament.sprt"), &mut use_sprt);
+        if use_sprt {
+            for (field, label) in sprt_fields.iter_mut().zip(["elo0", "elo1", "alpha", "beta"]) {
+                root_ui().input_text(hash!(label), label, field);
+            }
+        }
+
+        if root_ui().button(None, tr("tournament.start")) {
+            let engines: Vec<_> = settings.engines.iter().zip(&playing).filter(|x| *x.1).map(|x| x.0.clone()).collect();
+
+            let suite = match openings.trim() {
+                "" => { Ok(OpeningSuite::default()) }
+                path => {
+                    std::fs::read_to_string(path).map_err(|x| x.to_string())
+                        .and_then(|x| OpeningSuite::parse(&x).map_err(|x| x.to_string()))
+                }
+            };
+
+            let sprt: Result<Vec<f64>, _> = sprt_fields.iter().map(|x| x.trim().parse::<f64>()).collect();
+            let sprt = match sprt.as_deref() {
+                Ok(&[elo0, elo1, alpha, beta]) if elo0 < elo1 && alpha > 0.0 && alpha < 1.0 && beta > 0.0 && beta < 1.0 => {
+                    Some(Sprt::new(elo0, elo1).alpha(alpha).beta(beta))
+                }
+                _ => { None }
+            };
+
+            // milliseconds on each clock and the increment, None for a fixed move time
+            let clock = match clock.trim() {
+                "" => { Ok(None) }
+                seconds => {
+                    match (seconds.parse::<f64>(), increment.trim().parse::<u64>()) {
+                        (Ok(seconds), Ok(increment)) if seconds > 0.0 => { Ok(Some(((seconds * 1_000.0) as u64, increment))) }
+                        _ => { Err(()) }
+                    }
+                }
+            };
+
+            match (suite, rounds.trim().parse::<usize>(), move_time.trim().parse::<u64>()) {
+                _ if engines.len() < 2 => { status = tr("tournament.too_few").to_string(); }
+                _ if use_sprt && sprt.is_none() => { status = tr("tournament.bad_sprt").to_string(); }
+                _ if clock.is_err() => { status = tr("tournament.bad_clock").to_string(); }
+                (Err(err), _, _) => { status = trf("tournament.openings_failed", &[&err]); }
+                (_, Err(_), _) | (_, Ok(0), _) => { status = tr("tournament.bad_rounds").to_string(); }
+                (_, _, Err(_)) => { status = tr("tournament.bad_move_time").to_string(); }
+                (Ok(suite), Ok(rounds), Ok(move_time)) => {
+                    let limits = match clock {
+                        Ok(Some((clock, increment))) => { Limits::default().w_time(clock).b_time(clock).w_inc(increment).b_inc(increment) }
+                        _ => { Limits::default().time(move_time) }
+                    };
+                    let tournament = Tournament::new(engines)
+                        .openings(suite)
+                        .rounds(rounds)
+                        .limits(limits)
+                        .adjudication(settings.adjudication)
+                        .concurrency(concurrency + 1)
+                        .sprt(sprt.filter(|_| use_sprt));
+
+                    status.clear();
+                    pgn_file = save_to.trim().to_string();
+                    running = Some(tournament.start());
+                }
+            }
+        }
+
+        if !status.is_empty() {
+            root_ui().label(None, &status);
+        }
+
+        if root_ui().button(None, tr("back")) || is_key_pressed(KeyCode::Escape) {
+            return;
+        }
+
+        next_frame().await;
+    }
+}
+
+// adds a game to the end of `path`, creating the file and its folder if needed. nothing for an empty path
+fn append_pgn(path: &str, pgn: &str) -> std::io::Result<()> {
+    use std::io::Write;
+
+    if path.is_empty() { return Ok(()); }
+    if let Some(dir) = std::path::Path::new(path).parent().filter(|x| !x.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+    // a blank line between games
+    writeln!(file, "{}\n", pgn.trim_end())
+}
+
+// writes the standings, pair scores and crosstable as csv and everything as json to the export folder,
+// returning where without the extension
+fn export_results(results: &TournamentResults) -> std::io::Result<String> {
+    std::fs::create_dir_all(EXPORT_DIR)?;
+    let path = format!("{}/tournament-results", EXPORT_DIR);
+
+    std::fs::write(format!("{}.csv", path), results.to_csv())?;
+    std::fs::write(format!("{}-pairs.csv", path), results.pairs_csv())?;
+    std::fs::write(format!("{}-crosstable.csv", path), results.crosstable().to_csv())?;
+    std::fs::write(format!("{}.json", path), results.to_json())?;
+
+    Ok(path)
+}
+
+// the log likelihood ratio after each game, between the bounds that end the test
+fn draw_llr(results: &TournamentResults, area: Rect) {
+    let Some(sprt) = results.sprt else { return; };
+    let (lower, upper) = sprt.bounds();
+    let llr = results.llr.last().copied().unwrap_or(0.0);
+
+    let text = match results.verdict {
+        Some(verdict) => { format!("LLR {:.2} ({:.2}, {:.2}), {}", llr, lower, upper, verdict) }
+        None => { format!("LLR {:.2} ({:.2}, {:.2})", llr, lower, upper) }
+    };
+    draw_text(&text, area.x, area.y - 8.0, 24.0, WHITE);
+
+    draw_rectangle(area.x, area.y, area.w, area.h, DARKGRAY);
+
+    // the bounds sit at the top and bottom, the ratio can run a little past them
+    let y = |llr: f64| area.y + area.h / 2.0 - (llr / upper.max(-lower) * 0.45 * area.h as f64) as f32;
+    draw_line(area.x, y(upper), area.x + area.w, y(upper), 1.0, GREEN);
+    draw_line(area.x, y(0.0), area.x + area.w, y(0.0), 1.0, LIGHTGRAY);
+    draw_line(area.x, y(lower), area.x + area.w, y(lower), 1.0, RED);
+
+    let step = area.w / results.llr.len().max(1) as f32;
+    let mut last = (area.x, y(0.0));
+    for (i, llr) in results.llr.iter().enumerate() {
+        let point = (area.x + (i + 1) as f32 * step, y(*llr).clamp(area.y, area.y + area.h));
+        draw_line(last.0, last.1, point.0, point.1, 2.0, WHITE);
+        last = point;
+    }
+}
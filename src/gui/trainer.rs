@@ -0,0 +1,172 @@
+use std::path::PathBuf;
+use macroquad::prelude::*;
+use macroquad::hash;
+use macroquad::ui::root_ui;
+use chess::pgn::{san, Pgn};
+use chess::platform::since_epoch;
+use chess::repertoire::{Answer, Repertoire};
+use chess::study::{Arrow, MarkColor, Markup};
+use chess::{Color, Move, Square};
+use crate::gui::{board_move, draw_markup, show_error, square_corner};
+use crate::gui::assets::Assets;
+use crate::gui::i18n::{tr, tr_color, trf};
+
+// seconds before the repertoire's reply is played
+const REPLY_DELAY: f64 = 0.5;
+
+// picks a repertoire file and the color to train, or the coordinates minigame
+pub(crate) async fn trainer_menu(assets: &Assets) {
+    request_new_screen_size(640.0, 360.0);
+
+    let mut file = String::new();
+    let mut white = true;
+    let mut status = String::new();
+
+    loop {
+        clear_background(GRAY);
+
+        root_ui().input_text(hash!(), tr("trainer.file"), &mut file);
+        root_ui().checkbox(hash!(), tr("trainer.white"), &mut white);
+
+        if root_ui().button(None, tr("trainer.train")) {
+            let path = PathBuf::from(file.trim());
+            match std::fs::read_to_string(&path).map_err(|x| x.to_string()).and_then(|x| Pgn::parse(&x).map_err(|x| x.to_string())) {
+                Ok(pgn) => {
+                    let color = if white { Color::White } else { Color::Black };
+                    train(assets, Repertoire::new(pgn, color), path).await;
+                    request_new_screen_size(640.0, 360.0);
+                }
+                Err(err) => { status = trf("trainer.open_failed", &[&err]); }
+            }
+        }
+
+        if root_ui().button(None, tr("trainer.coordinates")) {
+            crate::gui::coordinates::coordinates_trainer(assets).await;
+            request_new_screen_size(640.0, 360.0);
+        }
+
+        if !status.is_empty() {
+            root_ui().label(None, &status);
+        }
+
+        if root_ui().button(None, tr("back")) || is_key_pressed(KeyCode::Escape) {
+            return;
+        }
+
+        next_frame().await;
+    }
+}
+
+fn now() -> u64 {
+    since_epoch().map(|x| x.as_secs()).unwrap_or_default()
+}
+
+// plays the repertoire's lines one after another, most overdue first
+async fn train(assets: &Assets, mut repertoire: Repertoire, path: PathBuf) {
+    if repertoire.cards.is_empty() {
+        show_error(tr("trainer.empty")).await;
+        return;
+    }
+
+    let renderer = assets.renderer;
+
+    request_new_screen_size(1024.0, 768.0);
+    next_frame().await;
+
+    let schedule_path = Repertoire::schedule_path(&path);
+    if let Ok(text) = std::fs::read_to_string(&schedule_path) {
+        repertoire.load_schedule(&text);
+    }
+
+    let mut card = repertoire.next_card(now()).unwrap_or(0);
+    let mut game = repertoire.pgn.history.start();
+    let mut ply = 0;
+    let mut failed = false;
+    let mut hint: Option<Move> = None;
+    let mut selected = None;
+    let mut status = String::new();
+    let mut last_move_time = get_time();
+
+    loop {
+        clear_background(GRAY);
+
+        let line = repertoire.cards[card].moves.clone();
+        let finished = ply >= line.len();
+
+        renderer.draw_position(&game, 96.0);
+        if let Some(hint) = hint {
+            draw_markup(&Markup { arrows: vec![Arrow { from: hint.from.index(), to: hint.to.index(), color: MarkColor::Green }], highlights: Vec::new() });
+        }
+        if let Some(square) = selected {
+            let corner = square_corner(Square::new(square));
+            draw_rectangle_lines(corner.x, corner.y, 96.0, 96.0, 4.0, YELLOW);
+        }
+
+        if !finished && game.turn != repertoire.color {
+            if get_time() - last_move_time > REPLY_DELAY {
+                game.make_move(line[ply]).expect("repertoire moves are legal");
+                ply += 1;
+                last_move_time = get_time();
+            }
+        } else if !finished {
+            if let Some(mv) = board_move(&game, &mut selected) {
+                let answer = repertoire.check(card, ply, mv);
+                let expected = san(&game, line[ply]);
+
+                match answer {
+                    Answer::Correct => {
+                        status = trf("trainer.correct", &[&san(&game, mv)]);
+                        game.make_move(mv).expect("repertoire moves are legal");
+                        (ply, hint, last_move_time) = (ply + 1, None, get_time());
+                    }
+                    Answer::OtherLine => { status = trf("trainer.other_line", &[&expected]); }
+                    Answer::Wrong => {
+                        status = trf("trainer.wrong", &[&expected]);
+                        (failed, hint) = (true, Some(line[ply]));
+                    }
+                }
+
+                if ply >= line.len() {
+                    repertoire.review(card, !failed, now());
+                    status = tr(if failed { "trainer.done_failed" } else { "trainer.done" }).to_string();
+
+                    if let Err(err) = std::fs::write(&schedule_path, repertoire.schedule()) {
+                        status = trf("trainer.save_failed", &[&err.to_string()]);
+                    }
+                }
+            }
+        }
+
+        let time = now();
+        draw_text(&trf("trainer.training", &[tr_color(repertoire.color)]), 778.0, 30.0, 24.0, WHITE);
+        draw_text(&trf("trainer.due", &[&repertoire.cards.len().to_string(), &repertoire.due(time).to_string()]), 778.0, 58.0, 24.0, WHITE);
+        draw_text(&trf("trainer.move", &[&ply.div_ceil(2).to_string(), &line.len().div_ceil(2).to_string()]), 778.0, 86.0, 24.0, WHITE);
+
+        // wrapped by hand, statuses are a few words long
+        let mut y = 130.0;
+        let mut row = String::new();
+        for word in status.split(' ') {
+            if !row.is_empty() && measure_text(&format!("{} {}", row, word), None, 20, 1.0).width > 240.0 {
+                draw_text(&row, 778.0, y, 20.0, WHITE);
+                (row, y) = (String::new(), y + 22.0);
+            }
+            if !row.is_empty() { row.push(' '); }
+            row.push_str(word);
+        }
+        draw_text(&row, 778.0, y, 20.0, WHITE);
+
+        if finished && root_ui().button(vec2(778.0, 660.0), tr("trainer.next")) {
+            card = repertoire.next_card(time).unwrap_or(0);
+            (game, ply, failed, hint, selected) = (repertoire.pgn.history.start(), 0, false, None, None);
+            status.clear();
+            last_move_time = get_time();
+        }
+
+        if root_ui().button(vec2(778.0, 710.0), tr("back")) || is_key_pressed(KeyCode::Escape) {
+            return;
+        }
+
+        next_frame().await;
+    }
+}
+
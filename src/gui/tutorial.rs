@@ -0,0 +1,93 @@
+use macroquad::prelude::*;
+use macroquad::ui::root_ui;
+use chess::study::{Arrow, MarkColor, Markup};
+use chess::tutorial::{lessons, Attempt, Scenario};
+use chess::Square;
+use crate::gui::{board_move, draw_markup, square_corner};
+use crate::gui::assets::Assets;
+use crate::gui::i18n::{tr, trf};
+
+// the lessons one after another, each asking for a move at a time
+pub(crate) async fn tutorial(assets: &Assets) {
+    let renderer = assets.renderer;
+    let lessons = lessons();
+
+    request_new_screen_size(1024.0, 768.0);
+    next_frame().await;
+
+    let mut lesson = 0;
+    let mut scenario = Scenario::new(lessons[lesson].clone());
+    let mut selected = None;
+    let mut show_hint = false;
+    let mut status = String::new();
+
+    loop {
+        clear_background(GRAY);
+
+        renderer.draw_position(scenario.game(), 96.0);
+        if let (Some(step), true) = (scenario.step(), show_hint) {
+            draw_markup(&Markup { arrows: vec![Arrow { from: step.hint.from.index(), to: step.hint.to.index(), color: MarkColor::Green }], highlights: Vec::new() });
+        }
+        if let Some(square) = selected {
+            let corner = square_corner(Square::new(square));
+            draw_rectangle_lines(corner.x, corner.y, 96.0, 96.0, 4.0, YELLOW);
+        }
+
+        if !scenario.is_finished() {
+            if let Some(mv) = board_move(scenario.game(), &mut selected) {
+                match scenario.try_move(mv) {
+                    Attempt::Correct => {
+                        show_hint = false;
+                        status = if scenario.is_finished() { tr("tutorial.done") } else { tr("tutorial.right") }.to_string();
+                    }
+                    Attempt::Wrong => { (show_hint, status) = (true, tr("tutorial.wrong").to_string()); }
+                    Attempt::Illegal => { status = tr("tutorial.illegal").to_string(); }
+                }
+            }
+        }
+
+        let title = scenario.lesson().title;
+        draw_text(&trf("tutorial.lesson", &[&(lesson + 1).to_string(), &lessons.len().to_string()]), 778.0, 30.0, 24.0, WHITE);
+        draw_text(title, 778.0, 58.0, 24.0, WHITE);
+        draw_text(&trf("tutorial.step", &[&scenario.progress().to_string(), &scenario.lesson().steps.len().to_string()]), 778.0, 86.0, 20.0, WHITE);
+
+        // the prompt, then how the last move went
+        let mut y = 130.0;
+        for text in [scenario.step().map(|x| x.prompt).unwrap_or_default(), &status] {
+            let mut row = String::new();
+            for word in text.split(' ') {
+                if !row.is_empty() && measure_text(&format!("{} {}", row, word), None, 20, 1.0).width > 240.0 {
+                    draw_text(&row, 778.0, y, 20.0, WHITE);
+                    (row, y) = (String::new(), y + 22.0);
+                }
+                if !row.is_empty() { row.push(' '); }
+                row.push_str(word);
+            }
+            draw_text(&row, 778.0, y, 20.0, WHITE);
+            y += 44.0;
+        }
+
+        let mut switch_to = None;
+        if root_ui().button(vec2(778.0, 560.0), tr("tutorial.restart")) {
+            switch_to = Some(lesson);
+        }
+        if lesson > 0 && root_ui().button(vec2(778.0, 590.0), tr("tutorial.previous")) {
+            switch_to = Some(lesson - 1);
+        }
+        if lesson + 1 < lessons.len() && root_ui().button(vec2(778.0, 620.0), tr("tutorial.next")) {
+            switch_to = Some(lesson + 1);
+        }
+        if let Some(next) = switch_to {
+            lesson = next;
+            scenario = Scenario::new(lessons[lesson].clone());
+            (selected, show_hint) = (None, false);
+            status.clear();
+        }
+
+        if root_ui().button(vec2(778.0, 710.0), tr("back")) || is_key_pressed(KeyCode::Escape) {
+            return;
+        }
+
+        next_frame().await;
+    }
+}
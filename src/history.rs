@@ -0,0 +1,122 @@
+use std::time::Duration;
+use crate::chess::{Color, Game, Move, MoveResult, Piece};
+use crate::error::IllegalMove;
+
+/// A move that was played, along with how it ended up.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HistoryEntry {
+    pub mv: Move,
+    pub result: MoveResult,
+    // the piece the move took, if any
+    pub captured: Option<Piece>,
+    // how long the mover thought, and what was left on their clock afterwards
+    pub time: Option<Duration>,
+    pub clock: Option<Duration>,
+}
+
+/// The moves of a game and the position it started from.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct History {
+    start: Game,
+    entries: Vec<HistoryEntry>,
+    // position after the last entry
+    current: Game,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        History::new(Game::default())
+    }
+}
+
+impl History {
+    pub fn new(start: Game) -> Self {
+        History {
+            start,
+            entries: Vec::new(),
+            current: start,
+        }
+    }
+
+    /// Plays `mv` on the current position and records it.
+    pub fn push(&mut self, mv: Move) -> Result<MoveResult, IllegalMove> {
+        let captured = self.current.captured_piece(mv);
+        let result = self.current.make_move(mv)?;
+        self.entries.push(HistoryEntry { mv, result, captured, time: None, clock: None });
+
+        Ok(result)
+    }
+
+    /// Like [`History::push`], recording that the mover spent `time` on the move.
+    pub fn push_timed(&mut self, mv: Move, time: Duration) -> Result<MoveResult, IllegalMove> {
+        let result = self.push(mv)?;
+        self.set_times(self.len() - 1, Some(time), None);
+
+        Ok(result)
+    }
+
+    /// Sets the think time and remaining clock of the move at `ply`, if there is one.
+    pub fn set_times(&mut self, ply: usize, time: Option<Duration>, clock: Option<Duration>) {
+        if let Some(entry) = self.entries.get_mut(ply) {
+            (entry.time, entry.clock) = (time, clock);
+        }
+    }
+
+    /// Takes back the last move.
+    pub fn undo(&mut self) -> Option<HistoryEntry> {
+        let entry = self.entries.pop()?;
+        self.current = self.position(self.entries.len());
+
+        Some(entry)
+    }
+
+    /// Takes back `side`'s last move and every move after it, so `side` is to move again. Returns the
+    /// moves taken back, the last one played first, nothing if `side` hasn't moved.
+    pub fn take_back(&mut self, side: Color) -> Vec<HistoryEntry> {
+        let first = self.start.turn;
+        let Some(ply) = (0..self.len()).rev().find(|x| (if x % 2 == 0 { first } else { !first }) == side) else { return Vec::new(); };
+
+        let taken: Vec<HistoryEntry> = self.entries.drain(ply..).rev().collect();
+        self.current = self.position(ply);
+
+        taken
+    }
+
+    pub fn start(&self) -> Game {
+        self.start
+    }
+
+    pub fn current(&self) -> Game {
+        self.current
+    }
+
+    pub fn entries(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+
+    pub fn moves(&self) -> impl Iterator<Item = Move> + '_ {
+        self.entries.iter().map(|x| x.mv)
+    }
+
+    /// Number of half moves played.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The position after `ply` half moves, clamped to the current position.
+    pub fn position(&self, ply: usize) -> Game {
+        let mut game = self.start;
+
+        for entry in self.entries.iter().take(ply) {
+            game.move_checked(entry.mv.from.index(), entry.mv.to.index(), entry.mv.promotion);
+        }
+
+        game
+    }
+}
@@ -0,0 +1,217 @@
+//! Writing pictures of the board: PNG for a single position and animated GIF for a whole game.
+//!
+//! Both take RGBA pixels, four bytes a pixel and rows from the top. The PNG is stored without
+//! compression, and GIF frames are brought down to a fixed palette of 252 colors (6 levels of red and
+//! blue, 7 of green), which is plenty for a board's few textures.
+
+use std::io::{self, Write};
+
+// levels of red, green and blue in the gif palette
+const LEVELS: (u32, u32, u32) = (6, 7, 6);
+// the largest code of gif's lzw compression
+const MAX_CODE: u16 = 4095;
+
+/// Writes `rgba`, `width` by `height` pixels, as a PNG file.
+pub fn write_png(mut out: impl Write, width: u32, height: u32, rgba: &[u8]) -> io::Result<()> {
+    check_size(width, height, rgba)?;
+    out.write_all(b"\x89PNG\r\n\x1a\n")?;
+
+    let mut header = Vec::new();
+    header.extend(width.to_be_bytes());
+    header.extend(height.to_be_bytes());
+    // 8 bits a channel, rgba, deflate, no filters or interlacing
+    header.extend([8, 6, 0, 0, 0]);
+    write_chunk(&mut out, b"IHDR", &header)?;
+
+    // every row starts with its filter, none
+    let mut raw = Vec::with_capacity(rgba.len() + height as usize);
+    for row in rgba.chunks_exact(width as usize * 4) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+
+    // a zlib stream of stored deflate blocks
+    let mut data = vec![0x78, 0x01];
+    let mut blocks = raw.chunks(u16::MAX as usize).peekable();
+    while let Some(block) = blocks.next() {
+        let length = block.len() as u16;
+        data.push(if blocks.peek().is_none() { 1 } else { 0 });
+        data.extend(length.to_le_bytes());
+        data.extend((!length).to_le_bytes());
+        data.extend_from_slice(block);
+    }
+    data.extend(adler32(&raw).to_be_bytes());
+
+    write_chunk(&mut out, b"IDAT", &data)?;
+    write_chunk(&mut out, b"IEND", &[])
+}
+
+/// An animated GIF, written a frame at a time. Every frame covers the whole picture.
+pub struct GifWriter<W: Write> {
+    out: W,
+    width: u16,
+    height: u16,
+}
+
+impl<W: Write> GifWriter<W> {
+    /// Starts a GIF of `width` by `height` pixels that plays over and over.
+    pub fn new(mut out: W, width: u16, height: u16) -> io::Result<Self> {
+        out.write_all(b"GIF89a")?;
+        out.write_all(&width.to_le_bytes())?;
+        out.write_all(&height.to_le_bytes())?;
+        // a global palette of 256 colors, 8 bits a channel
+        out.write_all(&[0xf7, 0, 0])?;
+        out.write_all(&palette())?;
+        // loop forever
+        out.write_all(b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00")?;
+
+        Ok(GifWriter { out, width, height })
+    }
+
+    /// Adds a frame of RGBA pixels, shown for `delay` hundredths of a second.
+    pub fn frame(&mut self, rgba: &[u8], delay: u16) -> io::Result<()> {
+        check_size(self.width.into(), self.height.into(), rgba)?;
+
+        // graphic control: leave the frame in place, the delay, no transparency
+        self.out.write_all(&[0x21, 0xf9, 4, 0x04])?;
+        self.out.write_all(&delay.to_le_bytes())?;
+        self.out.write_all(&[0, 0])?;
+
+        self.out.write_all(&[0x2c, 0, 0, 0, 0])?;
+        self.out.write_all(&self.width.to_le_bytes())?;
+        self.out.write_all(&self.height.to_le_bytes())?;
+        self.out.write_all(&[0])?;
+
+        let indices: Vec<u8> = rgba.chunks_exact(4).map(|x| palette_index(x[0], x[1], x[2])).collect();
+        self.out.write_all(&[8])?;
+        for block in lzw(&indices).chunks(255) {
+            self.out.write_all(&[block.len() as u8])?;
+            self.out.write_all(block)?;
+        }
+        self.out.write_all(&[0])
+    }
+
+    /// Ends the GIF and gives back where it was written to.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.out.write_all(&[0x3b])?;
+        self.out.flush()?;
+
+        Ok(self.out)
+    }
+}
+
+fn check_size(width: u32, height: u32, rgba: &[u8]) -> io::Result<()> {
+    if width == 0 || height == 0 || rgba.len() != width as usize * height as usize * 4 {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("{} bytes are not a {}x{} picture", rgba.len(), width, height)));
+    }
+
+    Ok(())
+}
+
+fn write_chunk(out: &mut impl Write, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    out.write_all(&(data.len() as u32).to_be_bytes())?;
+    out.write_all(kind)?;
+    out.write_all(data)?;
+
+    let crc = crc32(kind.iter().chain(data));
+    out.write_all(&crc.to_be_bytes())
+}
+
+fn crc32<'a>(bytes: impl IntoIterator<Item = &'a u8>) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+        }
+    }
+
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in bytes {
+        a = (a + u32::from(*byte)) % 65521;
+        b = (b + a) % 65521;
+    }
+
+    (b << 16) | a
+}
+
+// every color of the palette as rgb, padded with black to 256
+fn palette() -> Vec<u8> {
+    let (r, g, b) = LEVELS;
+    let level = |x: u32, levels: u32| (x * 255 / (levels - 1)) as u8;
+
+    let mut colors: Vec<u8> = (0..r * g * b)
+        .flat_map(|i| [level(i / (g * b), r), level(i / b % g, g), level(i % b, b)])
+        .collect();
+    colors.resize(256 * 3, 0);
+
+    colors
+}
+
+// the palette color closest to (r, g, b)
+fn palette_index(r: u8, g: u8, b: u8) -> u8 {
+    let (lr, lg, lb) = LEVELS;
+    let nearest = |x: u8, levels: u32| (u32::from(x) * (levels - 1) + 127) / 255;
+
+    (nearest(r, lr) * lg * lb + nearest(g, lg) * lb + nearest(b, lb)) as u8
+}
+
+// gif's variable width lzw of 8 bit `indices`, the codes packed from the lowest bit
+fn lzw(indices: &[u8]) -> Vec<u8> {
+    let (clear, end) = (256u16, 257u16);
+    let mut table: std::collections::HashMap<(u16, u8), u16> = std::collections::HashMap::new();
+    let (mut next, mut size) = (258u16, 9u32);
+
+    let mut out = Vec::new();
+    let (mut bits, mut count) = (0u32, 0u32);
+    let mut emit = |code: u16, size: u32, out: &mut Vec<u8>| {
+        bits |= u32::from(code) << count;
+        count += size;
+        while count >= 8 {
+            out.push(bits as u8);
+            (bits, count) = (bits >> 8, count - 8);
+        }
+    };
+
+    emit(clear, size, &mut out);
+
+    let mut pixels = indices.iter();
+    if let Some(first) = pixels.next() {
+        let mut prefix = u16::from(*first);
+
+        for &pixel in pixels {
+            if let Some(code) = table.get(&(prefix, pixel)) {
+                prefix = *code;
+                continue;
+            }
+
+            emit(prefix, size, &mut out);
+            // codes get a bit wider once the next one wouldn't fit
+            if next > (1 << size) - 1 && size < 12 { size += 1; }
+
+            if next <= MAX_CODE {
+                table.insert((prefix, pixel), next);
+                next += 1;
+            } else {
+                // the table is full, start over
+                emit(clear, size, &mut out);
+                table.clear();
+                (next, size) = (258, 9);
+            }
+
+            prefix = u16::from(pixel);
+        }
+
+        emit(prefix, size, &mut out);
+        if next > (1 << size) - 1 && size < 12 { size += 1; }
+    }
+
+    emit(end, size, &mut out);
+    if count > 0 { out.push(bits as u8); }
+
+    out
+}
@@ -0,0 +1,138 @@
+//! Downloads a player's games from Lichess or Chess.com through their public APIs.
+//!
+//! The requests are made with `curl`, which comes with Windows, macOS and most Linux installs,
+//! so the crate doesn't need an http or tls stack of its own.
+
+use std::time::Duration;
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+use crate::error::ImportError;
+use crate::pgn::Pgn;
+
+// time between two requests, both sites ask clients not to make them in parallel
+const REQUEST_GAP: Duration = Duration::from_secs(1);
+// lichess asks for a full minute of waiting after a 429
+const RATE_LIMIT_WAIT: Duration = Duration::from_secs(60);
+#[cfg(not(target_arch = "wasm32"))]
+const USER_AGENT: &str = "Zillorz-chess game importer";
+
+/// The sites games can be imported from.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Site {
+    Lichess,
+    ChessCom,
+}
+
+impl Site {
+    pub const ALL: [Site; 2] = [Site::Lichess, Site::ChessCom];
+    pub const NAMES: [&'static str; 2] = ["Lichess", "Chess.com"];
+}
+
+/// What a download is doing, for showing its progress.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ImportProgress {
+    Requesting(String),
+    // the site asked for a pause, downloading goes on after it
+    RateLimited(Duration),
+    // games read so far
+    Games(usize),
+}
+
+/// Downloads up to `max` of `username`'s most recent games, newest first.
+/// Games that can't be read, like ones from variants, are skipped.
+pub fn download_games(site: Site, username: &str, max: usize,
+                      mut progress: impl FnMut(ImportProgress)) -> Result<Vec<Pgn>, ImportError> {
+    // names are letters, digits, - and _ on both sites, anything else would end up in the url
+    if username.is_empty() || !username.chars().all(|x| x.is_ascii_alphanumeric() || x == '-' || x == '_') {
+        return Err(ImportError::UnknownUser(username.to_string()));
+    }
+
+    match site {
+        Site::Lichess => {
+            let url = format!("https://lichess.org/api/games/user/{}?max={}&clocks=true&opening=true", username, max);
+            let text = request(&url, username, &mut progress)?;
+
+            let games: Vec<Pgn> = Pgn::parse_each(&text).into_iter().flatten().take(max).collect();
+            progress(ImportProgress::Games(games.len()));
+
+            Ok(games)
+        }
+        Site::ChessCom => {
+            let url = format!("https://api.chess.com/pub/player/{}/games/archives", username.to_lowercase());
+            let archives = request(&url, username, &mut progress)?;
+
+            // a json list of monthly archive urls, oldest first
+            let months: Vec<&str> = archives.split('"').filter(|x| x.starts_with("https://") && x.contains("/games/")).collect();
+
+            let mut games = Vec::new();
+            for month in months.iter().rev() {
+                if games.len() >= max { break; }
+                std::thread::sleep(REQUEST_GAP);
+
+                let text = request(&format!("{}/pgn", month), username, &mut progress)?;
+                let mut month_games: Vec<Pgn> = Pgn::parse_each(&text).into_iter().flatten().collect();
+
+                month_games.reverse();
+                games.extend(month_games.into_iter().take(max - games.len()));
+                progress(ImportProgress::Games(games.len()));
+            }
+
+            Ok(games)
+        }
+    }
+}
+
+/// The address of the game on the site it was played on, from its `Site` (lichess) or `Link` (chess.com) tag.
+pub fn game_url(pgn: &Pgn) -> Option<&str> {
+    [pgn.tag("Link"), pgn.tag("Site")].into_iter().flatten().find(|x| x.starts_with("https://"))
+}
+
+// the body of a GET, waiting out one rate limit
+fn request(url: &str, username: &str, progress: &mut impl FnMut(ImportProgress)) -> Result<String, ImportError> {
+    for attempt in 0..2 {
+        progress(ImportProgress::Requesting(url.to_string()));
+        let (status, body) = curl(url, "application/x-chess-pgn")?;
+
+        match status {
+            200 => { return Ok(body); }
+            404 => { return Err(ImportError::UnknownUser(username.to_string())); }
+            429 if attempt == 0 => {
+                progress(ImportProgress::RateLimited(RATE_LIMIT_WAIT));
+                std::thread::sleep(RATE_LIMIT_WAIT);
+            }
+            status => { return Err(ImportError::Status(status)); }
+        }
+    }
+
+    Err(ImportError::Status(429))
+}
+
+// (http status, body) of a GET asking for `accept`
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn curl(url: &str, accept: &str) -> Result<(u16, String), ImportError> {
+    let mut command = std::process::Command::new("curl");
+    command.args(["-sSL", "--max-time", "300", "-A", USER_AGENT, "-H", &format!("Accept: {}", accept), "-w", "\n%{http_code}", url]);
+
+    #[cfg(windows)]
+    command.creation_flags(0x08000000);
+
+    let output = command.output().map_err(ImportError::Curl)?;
+    if !output.status.success() {
+        return Err(ImportError::Request(String::from_utf8_lossy(&output.stderr).trim().to_string()));
+    }
+
+    // the status is written after the body
+    let text = String::from_utf8_lossy(&output.stdout);
+    let (body, status) = text.rsplit_once('\n').unwrap_or(("", &text));
+
+    match status.trim().parse() {
+        Ok(status) => { Ok((status, body.to_string())) }
+        Err(_) => { Err(ImportError::Request(format!("unexpected reply from curl '{}'", status))) }
+    }
+}
+
+// browsers can't start processes
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn curl(_url: &str, _accept: &str) -> Result<(u16, String), ImportError> {
+    Err(ImportError::Unsupported)
+}
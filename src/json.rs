@@ -0,0 +1,87 @@
+//! Reading and writing the little json the crate deals with, without a json library.
+
+/// A json value, as much of one as the files and replies read here need: `true`, `false` and `null`
+/// are read over as `Other`.
+pub(crate) enum Json {
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+    Number(f64),
+    Other,
+}
+
+pub(crate) fn parse_json(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<Json> {
+    while chars.next_if(|x| x.is_whitespace()).is_some() {}
+
+    match chars.next()? {
+        '"' => {
+            let mut string = String::new();
+            loop {
+                match chars.next()? {
+                    '"' => { return Some(Json::String(string)); }
+                    '\\' => {
+                        match chars.next()? {
+                            'n' => { string.push('\n'); }
+                            'r' => { string.push('\r'); }
+                            't' => { string.push('\t'); }
+                            'u' => {
+                                let code: String = (0..4).filter_map(|_| chars.next()).collect();
+                                string.push(u32::from_str_radix(&code, 16).ok().and_then(char::from_u32).unwrap_or('\u{fffd}'));
+                            }
+                            other => { string.push(other); }
+                        }
+                    }
+                    other => { string.push(other); }
+                }
+            }
+        }
+        '[' => {
+            let mut values = Vec::new();
+            loop {
+                while chars.next_if(|x| x.is_whitespace() || *x == ',').is_some() {}
+                if chars.next_if_eq(&']').is_some() { return Some(Json::Array(values)); }
+
+                values.push(parse_json(chars)?);
+            }
+        }
+        '{' => {
+            let mut fields = Vec::new();
+            loop {
+                while chars.next_if(|x| x.is_whitespace() || *x == ',').is_some() {}
+                if chars.next_if_eq(&'}').is_some() { return Some(Json::Object(fields)); }
+
+                let Json::String(name) = parse_json(chars)? else { return None; };
+                while chars.next_if(|x| x.is_whitespace() || *x == ':').is_some() {}
+                fields.push((name, parse_json(chars)?));
+            }
+        }
+        first => {
+            let mut word = first.to_string();
+            while let Some(x) = chars.next_if(|x| !matches!(x, ',' | ']' | '}') && !x.is_whitespace()) {
+                word.push(x);
+            }
+
+            Some(word.parse().map(Json::Number).unwrap_or(Json::Other))
+        }
+    }
+}
+
+/// `string` as a json string, quoted and escaped.
+pub(crate) fn quote(string: &str) -> String {
+    let mut json = String::from("\"");
+
+    for c in string.chars() {
+        match c {
+            '"' => { json += "\\\""; }
+            '\\' => { json += "\\\\"; }
+            '\n' => { json += "\\n"; }
+            '\r' => { json += "\\r"; }
+            '\t' => { json += "\\t"; }
+            c if c.is_control() => { json += &format!("\\u{:04x}", c as u32); }
+            c => { json.push(c); }
+        }
+    }
+
+    json.push('"');
+    json
+}
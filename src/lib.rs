@@ -0,0 +1,58 @@
+//! Chess rules, move generation, and FEN handling, plus a small UCI client
+//! for talking to external engines.
+//!
+//! The macroquad frontend lives in the `chess` binary and is only built with the
+//! `gui` feature, so depending on this crate with `default-features = false`
+//! does not pull in any graphics code.
+//!
+//! With the `serde` feature, games, moves and [`History`] implement `Serialize` and `Deserialize`.
+
+pub mod adaptive;
+pub mod adjudication;
+pub mod analysis;
+pub mod archive;
+pub mod bitboard;
+pub mod bookmarks;
+pub mod bots;
+pub mod classics;
+pub mod coordinates;
+pub mod correspondence;
+pub mod engine;
+pub mod epd;
+pub mod explorer;
+mod chess;
+pub mod error;
+pub mod generate;
+pub mod history;
+pub mod image;
+pub mod import;
+mod json;
+pub mod locale;
+pub mod nnue;
+pub mod odds;
+pub mod pgn;
+pub mod platform;
+pub mod position_index;
+pub mod rating;
+pub mod repertoire;
+pub mod result;
+pub mod rules;
+pub mod share;
+pub mod simul;
+pub mod simulate;
+pub mod solver;
+pub mod square;
+pub mod sprt;
+pub mod study;
+pub mod tournament;
+pub mod tree;
+pub mod tutorial;
+pub mod uci;
+pub mod variant;
+pub mod zobrist;
+
+pub use crate::chess::{Board, Color, DrawReason, Game, MaterialCount, Move, MoveFlags, MoveResult, Piece, Promotion, PROMOTIONS};
+pub use crate::square::{File, Rank, Square};
+pub use crate::error::ChessError;
+pub use crate::history::{History, HistoryEntry};
+pub use crate::pgn::Pgn;
@@ -0,0 +1,67 @@
+//! Text shown to the user, by language.
+//!
+//! A locale file has a `key = text` line for each piece of text and `#` comment lines. Its `language`
+//! key is the language's name for itself, which is how it is picked in the settings. A `{}` in a text
+//! is filled in with an argument (a name, a number) by [`fill`], in the order they are given.
+
+use std::collections::HashMap;
+
+/// The locales that come with the program, English first.
+pub const BUILT_IN: [&str; 2] = [include_str!("../locales/en.txt"), include_str!("../locales/de.txt")];
+
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Locale {
+    texts: HashMap<String, String>,
+}
+
+impl Locale {
+    /// Reads a locale file, lines that aren't `key = text` are skipped.
+    pub fn parse(text: &str) -> Self {
+        let texts = text.lines()
+            .map(str::trim)
+            .filter(|x| !x.starts_with('#'))
+            .filter_map(|x| x.split_once('='))
+            .map(|(key, text)| (key.trim().to_string(), text.trim().to_string()))
+            .collect();
+
+        Locale { texts }
+    }
+
+    /// Every locale in [`BUILT_IN`], in the same order.
+    pub fn built_in() -> Vec<Self> {
+        BUILT_IN.iter().map(|x| Locale::parse(x)).collect()
+    }
+
+    /// The language's own name, empty if the file doesn't say.
+    pub fn name(&self) -> &str {
+        self.get("language").unwrap_or_default()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.texts.get(key).map(String::as_str)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.texts.keys().map(String::as_str)
+    }
+}
+
+/// `text` with each `{}` replaced by the next of `args`. Placeholders past the last argument are left as
+/// they are.
+pub fn fill(text: &str, args: &[&str]) -> String {
+    let mut filled = String::with_capacity(text.len());
+    let mut args = args.iter();
+    let mut rest = text;
+
+    while let Some(at) = rest.find("{}") {
+        filled.push_str(&rest[..at]);
+        match args.next() {
+            Some(arg) => { filled.push_str(arg); }
+            None => { filled.push_str("{}"); }
+        }
+        rest = &rest[at + 2..];
+    }
+    filled.push_str(rest);
+
+    filled
+}
@@ -1,25 +1,161 @@
 #![allow(unused)]
 #![windows_subsystem = "windows"]
 
-mod uci;
-mod chess;
+mod gui;
 
+use std::cell::Cell;
 use std::collections::HashMap;
 use std::time::Duration;
-use macroquad::audio::{load_sound, play_sound_once, Sound};
+use macroquad::audio::{play_sound_once, Sound};
 use macroquad::{color, hash};
-use crate::uci::{Limits, ThreadedUci};
+use chess::adaptive::{Adaptive, LEVELS};
+use chess::uci::{EngineConfig, Limits, ThreadedUciBuilder, Uci};
+use chess::engine::Engine;
+use chess::epd::{run_engine_suite, run_uci_suite, Epd, SuiteReport};
 
 use macroquad::prelude::*;
 use macroquad::ui::{root_ui, Skin};
-use crate::chess::{Piece, Game, IsSomeAnd, MoveResult, Promotion, PROMOTIONS};
+use chess::{Piece, Game, History, Move, MoveResult, Pgn, Promotion, PROMOTIONS, File, Rank, Square};
+use chess::adjudication::Adjudicator;
+use chess::error::{ChessError, IllegalReason};
+use chess::analysis::{win_percent, Analyser};
+use chess::odds::Odds;
+use chess::archive::{pgn_date, Archive, ArchivedGame, GameInfo};
+use chess::rating::Rating;
+use chess::result::{GameResult, TerminationReason};
+use chess::tree::NodePath;
+use crate::gui::ARCHIVE_DIR;
+use crate::gui::archive::ReviewExit;
+use crate::gui::assets::{Assets, Sounds};
+use crate::gui::animation::{draw_check_glow, draw_toppled_king, primary_animation, reject_animation, secondary_animation, Animation, MATE_TIME};
+use crate::gui::captures::Captures;
+use crate::gui::clock::GameClock;
+use crate::gui::i18n::{tr, tr_all, trf};
+use crate::gui::input::{board_camera, rotated_around, BoardView, Pointer, PointerEvent, ZOOM_STEP};
+use crate::gui::pause::PauseExit;
+use crate::gui::settings::Settings;
+use crate::gui::stats::{engine_rating, rate_game};
+use crate::gui::theme::Theme;
+
+// engine info lines kept for the log panel
+const LOG_LINES: usize = 20;
+// seconds the "pass the device" screen stays up in hot seat games
+const PASS_TIME: f32 = 1.5;
+// width of the captured pieces column right of the board
+const PANEL: f32 = 160.0;
+// seconds the reason for a refused move stays up
+const REJECTION_TIME: f64 = 2.5;
+// locale keys of `Odds::NAMES`
+const ODDS_KEYS: [&str; 5] = ["odds.none", "odds.knight", "odds.rook", "odds.queen", "odds.move"];
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Mode {
+    Engine,
+    TwoPlayer,
+    EngineVsEngine,
+}
+
+impl Mode {
+    const ALL: [Mode; 3] = [Mode::Engine, Mode::TwoPlayer, Mode::EngineVsEngine];
+    // locale keys of their names
+    const KEYS: [&'static str; 3] = ["mode.engine", "mode.two_player", "mode.engine_vs_engine"];
+}
 
-const TL_GRAY: Color = Color::new(0.20, 0.20, 0.20, 0.2);
-const TD_GRAY: Color = Color::new(0.10, 0.10, 0.10, 0.4);
-const TD_RED: Color = Color::new(0.92, 0.20, 0.20, 0.5);
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Orientation {
+    WhiteBottom,
+    BlackBottom,
+    // the player's side, or the side to move when the engine plays itself
+    FollowPlayer,
+}
+
+impl Orientation {
+    const ALL: [Orientation; 3] = [Orientation::WhiteBottom, Orientation::BlackBottom, Orientation::FollowPlayer];
+    const KEYS: [&'static str; 3] = ["orientation.white", "orientation.black", "orientation.follow"];
+}
 
-#[macroquad::main("Chess")]
-async fn main() {
+// the color played against the engine
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Side {
+    White,
+    Black,
+    Random,
+}
+
+impl Side {
+    const ALL: [Side; 3] = [Side::White, Side::Black, Side::Random];
+    const KEYS: [&'static str; 3] = ["side.white", "side.black", "side.random"];
+
+    // a coin flip for random, made as each game starts
+    fn pick(self) -> chess::Color {
+        match self {
+            Side::White => { chess::Color::White }
+            Side::Black => { chess::Color::Black }
+            Side::Random => { if macroquad::rand::gen_range(0, 2) == 0 { chess::Color::White } else { chess::Color::Black } }
+        }
+    }
+}
+
+// what comes after a game
+enum NextGame {
+    // a position picked while reviewing it
+    From(Game),
+    // the same start again with the colors swapped
+    Rematch,
+}
+
+fn main() {
+    // `chess --uci` is the built-in engine on stdin and stdout, for other GUIs, without a window
+    if std::env::args().skip(1).any(|x| x == "--uci") {
+        if let Err(err) = chess::engine::run_uci(std::io::stdin().lock(), std::io::stdout()) {
+            eprintln!("{}", err);
+        }
+        return;
+    }
+
+    // `chess --bot <name>` is one of the built-in bots, the same way
+    let mut args = std::env::args().skip_while(|x| x != "--bot").skip(1);
+    if let Some(name) = args.next() {
+        match chess::bots::Personality::from_key(&name) {
+            Some(bot) => {
+                if let Err(err) = chess::bots::run_bot(bot, std::io::stdin().lock(), std::io::stdout()) { eprintln!("{}", err); }
+            }
+            None => { eprintln!("there is no bot called {}", name); }
+        }
+        return;
+    }
+
+    // `chess --epd <file> [ms]` scores the engine on a test suite, `ms` milliseconds a position
+    let mut args = std::env::args().skip_while(|x| x != "--epd").skip(1);
+    if let Some(path) = args.next() {
+        let time = args.next().and_then(|x| x.parse().ok()).unwrap_or(1_000);
+
+        match std::fs::read_to_string(&path).map(|x| run_epd(&x, time)) {
+            Ok(Ok(report)) => { println!("{}", report); }
+            Ok(Err(err)) => { eprintln!("{}", err); }
+            Err(err) => { eprintln!("could not read {}: {}", path, err); }
+        }
+        return;
+    }
+
+    // a chess:// link, from the system when one is opened, starts on the game it links to
+    let link = std::env::args().skip(1).find(|x| x.starts_with(chess::share::SCHEME));
+    macroquad::Window::new("Chess", run(link));
+}
+
+// the suite on the engine from the uci script, or the built-in engine when that doesn't start
+fn run_epd(text: &str, time: u64) -> Result<SuiteReport, ChessError> {
+    let epds = Epd::parse_many(text)?;
+
+    let report = match Uci::new() {
+        Ok(mut engine) => { run_uci_suite(&mut engine, &epds, time)? }
+        Err(_) => { run_engine_suite(&mut Engine::default(), &epds, time)? }
+    };
+
+    Ok(report)
+}
+
+async fn run(link: Option<String>) {
     request_new_screen_size(480.0, 360.0);
     next_frame().await;
 
@@ -44,138 +180,394 @@ async fn main() {
         ..default
     });
 
-    let mut two_player= false;
-    let mut white = true;
-    let mut flip = false;
+    // there are no engines in the browser
+    let mut mode = if cfg!(target_arch = "wasm32") { 1 } else { 0 };
+    let mut side = 0;
+    let mut info = GameInfo::default();
+    let mut orientation = 2;
+    let mut show_threats = false;
+    let mut show_assist = false;
+    let mut adaptive = false;
+    let mut settings = Settings::load();
+    gui::i18n::set_language(settings.language.as_deref());
+    // correspondence games go on, and engines reply to them, while the menu is open
+    let mut postbox = gui::correspondence::Postbox::open();
+    let mut engine: usize = 0;
+    let mut odds = 0;
+
+    // every texture and sound, read once here instead of by each screen
+    let mut assets = match Assets::load(settings.texture_dir(), &settings.sound_dir()).await {
+        Ok(assets) => { assets }
+        Err(err) => {
+            gui::show_error(&trf("error.assets", &[&err])).await;
+            return;
+        }
+    };
+
+    if let Some(link) = link {
+        match chess::share::parse_link(&link) {
+            Ok(history) => { gui::analysis::analysis_board(&assets, None, history.current()).await; }
+            Err(err) => { gui::show_error(&trf("error.link", &[&err.to_string()])).await; }
+        }
+        request_new_screen_size(480.0, 360.0);
+    }
+
+    macroquad::rand::srand(chess::platform::since_epoch().map(|x| x.as_nanos() as u64).unwrap_or_default());
 
     loop {
         clear_background(GRAY);
+        postbox.update(&settings);
 
-        if root_ui().button(None, "Play") {
-           play_game(two_player, if white { chess::Color::White } else { chess::Color::Black}, !flip && !white).await;
+        // the position to start a game from, the odds it gives and the side played. games from a bookmark or
+        // the archive use the options below like Play
+        let mut start = None;
+        let player = Side::ALL[side];
+
+        if root_ui().button(None, tr("menu.play")) {
+            // odds are given by the side you are not playing
+            let color = player.pick();
+            start = Some((Odds::ALL[odds].start(!color), Odds::ALL[odds], color));
         }
 
-        root_ui().checkbox(hash!(), "Two player?", &mut two_player);
-        root_ui().checkbox(hash!(), "Are you playing with white?", &mut white);
-        root_ui().checkbox(hash!(), "Is white always on the bottom?", &mut flip);
+        // the players, event and round written into the archived games
+        if root_ui().button(None, tr("menu.details")) {
+            gui::archive::edit_game_info(&mut info).await;
+            request_new_screen_size(480.0, 360.0);
+            next_frame().await;
+            continue;
+        }
+
+        if root_ui().button(None, tr("menu.bookmarks")) {
+            start = gui::bookmarks::bookmarks_menu(&assets).await.map(|x| (x, Odds::None, player.pick()));
+            request_new_screen_size(480.0, 360.0);
+        }
+
+        if root_ui().button(None, tr("menu.tournament")) {
+            gui::tournament::tournament_menu(&settings).await;
+            request_new_screen_size(480.0, 360.0);
+        }
+
+        if root_ui().button(None, tr("menu.archive")) {
+            start = gui::archive::archive_browser(&assets).await.map(|x| (x, Odds::None, player.pick()));
+            request_new_screen_size(480.0, 360.0);
+        }
+
+        if root_ui().button(None, tr("menu.classics")) {
+            start = gui::classics::classics_menu(&assets).await.map(|x| (x, Odds::None, player.pick()));
+            request_new_screen_size(480.0, 360.0);
+        }
+
+        // a finished game's review or a rematch can start the next one
+        let mut details = info.clone();
+        while let Some((position, odds, color)) = start.take() {
+            // the first entry is the uci script
+            let config = engine.checked_sub(1).and_then(|x| settings.opponents().get(x).cloned());
+            let next = play_game(&assets, Mode::ALL[mode], color, Orientation::ALL[orientation], show_threats, show_assist, adaptive, &settings, config,
+                                 position, odds, &details).await;
+
+            start = match next {
+                Some(NextGame::From(position)) => { Some((position, Odds::None, color)) }
+                // the odds move to the other side along with the player
+                Some(NextGame::Rematch) if odds != Odds::None => {
+                    details = details.swapped();
+                    Some((odds.start(color), odds, !color))
+                }
+                Some(NextGame::Rematch) => {
+                    details = details.swapped();
+                    Some((position, odds, !color))
+                }
+                None => { None }
+            };
+            request_new_screen_size(480.0, 360.0);
+        }
+
+        if root_ui().button(None, tr("menu.simul")) {
+            let config = engine.checked_sub(1).and_then(|x| settings.opponents().get(x).cloned());
+            gui::simul::simul_menu(&assets, &settings, config).await;
+            request_new_screen_size(480.0, 360.0);
+        }
+
+        if root_ui().button(None, tr("menu.correspondence")) {
+            gui::correspondence::correspondence_menu(&assets, &settings, &mut postbox).await;
+            request_new_screen_size(480.0, 360.0);
+        }
+        let waiting: Vec<&str> = postbox.games.iter().filter(|x| x.awaits_player()).take(3).map(|x| x.opponent.as_str()).collect();
+        if !waiting.is_empty() {
+            root_ui().label(None, &trf("menu.your_move", &[&waiting.join(", ")]));
+        }
+
+        if root_ui().button(None, tr("menu.analysis")) {
+            let config = engine.checked_sub(1).and_then(|x| settings.opponents().get(x).cloned());
+            gui::analysis::analysis_board(&assets, config, Game::default()).await;
+            request_new_screen_size(480.0, 360.0);
+        }
+
+        if root_ui().button(None, tr("menu.trainer")) {
+            gui::trainer::trainer_menu(&assets).await;
+            request_new_screen_size(480.0, 360.0);
+        }
+
+        if root_ui().button(None, tr("menu.tutorial")) {
+            gui::tutorial::tutorial(&assets).await;
+            request_new_screen_size(480.0, 360.0);
+        }
+
+        if root_ui().button(None, tr("menu.import")) {
+            gui::import::import_menu().await;
+            request_new_screen_size(480.0, 360.0);
+        }
+
+        if root_ui().button(None, tr("menu.stats")) {
+            gui::stats::stats_screen().await;
+            request_new_screen_size(480.0, 360.0);
+        }
+
+        if root_ui().button(None, tr("menu.settings")) {
+            gui::settings::settings_menu(&mut settings).await;
+            // a new texture folder or sound pack is loaded right away, the old ones stay if it can't be
+            if let Err(err) = assets.reload(settings.texture_dir(), &settings.sound_dir()).await {
+                gui::show_error(&trf("error.textures", &[&err])).await;
+            }
+            request_new_screen_size(480.0, 360.0);
+        }
+
+        let opponents = settings.opponents();
+        let engines: Vec<&str> = std::iter::once(tr("uci_script")).chain(opponents.iter().map(|x| x.name.as_str())).collect();
+        engine = engine.min(engines.len() - 1);
+
+        root_ui().combo_box(hash!(), tr("menu.mode"), &tr_all(&Mode::KEYS), &mut mode);
+        root_ui().combo_box(hash!(), tr("menu.board"), &tr_all(&Orientation::KEYS), &mut orientation);
+        root_ui().combo_box(hash!(), tr("menu.engine"), &engines, &mut engine);
+        root_ui().combo_box(hash!(), tr("menu.odds"), &tr_all(&ODDS_KEYS), &mut odds);
+        root_ui().combo_box(hash!(), tr("menu.side"), &tr_all(&Side::KEYS), &mut side);
+        root_ui().checkbox(hash!(), tr("menu.threats"), &mut show_threats);
+        root_ui().checkbox(hash!(), tr("menu.assist"), &mut show_assist);
+        root_ui().checkbox(hash!(), tr("menu.adaptive"), &mut adaptive);
         next_frame().await;
     }
 }
 
-async fn play_game(two_player: bool, player_color: chess::Color, flipped: bool) {
-    let wp = load_texture("assets/wP.png").await.unwrap();
-    let wn = load_texture("assets/wN.png").await.unwrap();
-    let wb = load_texture("assets/wB.png").await.unwrap();
-    let wr = load_texture("assets/wR.png").await.unwrap();
-    let wq = load_texture("assets/wQ.png").await.unwrap();
-    let wk = load_texture("assets/wK.png").await.unwrap();
-
-    let bp = load_texture("assets/bP.png").await.unwrap();
-    let bn = load_texture("assets/bN.png").await.unwrap();
-    let bb = load_texture("assets/bB.png").await.unwrap();
-    let br = load_texture("assets/bR.png").await.unwrap();
-    let bq = load_texture("assets/bQ.png").await.unwrap();
-    let bk = load_texture("assets/bK.png").await.unwrap();
-
-    let default = load_sound("assets/default.ogg").await.unwrap();
-    let castle = load_sound("assets/castle.ogg").await.unwrap();
-    let capture = load_sound("assets/capture.ogg").await.unwrap();
-
-    let check_sound = load_sound("assets/check.ogg").await.unwrap();
-
-    let sounds = [default, capture, castle];
-
-    let square_1 = load_texture("assets/square_1.png").await.unwrap();
-    let square_2 = load_texture("assets/square_2.png").await.unwrap();
-
-    let get_texture = |piece: Piece| -> Texture2D {
-        match piece {
-            Piece::WPawn => { wp }
-            Piece::WKnight => { wn }
-            Piece::WBishop => { wb }
-            Piece::WRook => { wr }
-            Piece::WQueen => { wq }
-            Piece::WKing => { wk }
-            Piece::BPawn => { bp }
-            Piece::BKnight => { bn }
-            Piece::BBishop => { bb }
-            Piece::BRook => { br }
-            Piece::BQueen => { bq }
-            Piece::BKing => { bk }
-        }
+// returns a position to play the next game from, picked while reviewing this one
+#[allow(clippy::too_many_arguments)]
+async fn play_game(assets: &Assets, mode: Mode, player_color: chess::Color, orientation: Orientation, mut show_threats: bool, mut show_assist: bool,
+                   adaptive: bool, settings: &Settings, engine: Option<EngineConfig>, start: Game, odds: Odds, info: &GameInfo) -> Option<NextGame> {
+    let theme = Theme::new(settings);
+    let move_time = settings.animation_speed.move_time();
+    let renderer = assets.renderer;
+    let pieces = renderer.pieces;
+
+    let sounds = assets.sounds;
+    let (check_sound, error_sound) = (sounds.check, sounds.error);
+
+    let mut game = start;
+    let mut history = History::new(game);
+    let mut archived = false;
+    // the archived copy of the finished game, to save its analysis into
+    let mut saved_game: Option<ArchivedGame> = None;
+
+    let engine_moves = |turn: chess::Color| match mode {
+        Mode::Engine => { turn != player_color }
+        Mode::TwoPlayer => { false }
+        Mode::EngineVsEngine => { true }
     };
-
-    let mut game = Game::default();
-
-    // let two_player = true;
-    // let player_color = chess::Color::Black;
-    // let flipped = false;
+    let human_moves = |turn: chess::Color| !engine_moves(turn);
 
     let screen_size = 1024.0;
     let square_size = screen_size / 8.0;
-    request_new_screen_size(screen_size, screen_size);
+    request_new_screen_size(screen_size + PANEL, screen_size);
     next_frame().await;
 
     let mut selected_piece = None;
+    // why the last move was refused, where the pointer was and when, shown for a moment
+    let mut rejection: Option<(String, Vec2, f64)> = None;
+
+    let mut engine_name = engine.as_ref().map(|x| x.name.clone()).unwrap_or_else(|| tr("engine").to_string());
+    // whether O swapped the engine, the game then has no single engine to name
+    let mut swapped = false;
+    // an engine whose strength follows the game, against the player only
+    let mut adaptive = adaptive.then(Adaptive::default).filter(|_| mode == Mode::Engine);
+    // games against an engine set to a known strength change the player's rating, (before, after)
+    let mut engine_rating = engine.as_ref().and_then(engine_rating).filter(|_| mode == Mode::Engine && adaptive.is_none());
+    let mut rating_change: Option<(Rating, Rating)> = None;
+    // the eval bar (E) runs the same engine again as a process of its own, so looking at it never slows the
+    // playing engine's search or runs its clock
+    let analysis_engine = engine.clone();
+    let mut analyser: Option<Analyser> = None;
+    let mut show_eval = false;
+    // the engine playing, which O swaps for another mid game
+    let mut current_engine = engine.unwrap_or_else(EngineConfig::uci_script);
+    let mut sf = settings.reply_delay.builder().config(current_engine.clone()).build();
+    sf.new_game();
+    let mut limits = Limits::default().time(1_500);
+    if let Some(adaptive) = &adaptive {
+        for (name, value) in adaptive.options() { sf.set_option(name, &value); }
+        limits = adaptive.limits();
+    }
 
-    let sf = ThreadedUci::new_delay(Duration::from_millis(1_000));
-    let limits = Limits::default().time(1_500);
-
-    if game.turn == !player_color && !two_player {
-        sf.recommend_move(game, limits);
+    if engine_moves(game.turn) {
+        sf.recommend_move(&history, limits);
     }
 
-    let mut winner = None;
-    let mut draw = false;
+    let mut result: Option<GameResult> = None;
+    let mut engine_error: Option<String> = None;
+
+    // the engine's info lines, shown with I
+    let mut engine_log: Vec<String> = Vec::new();
+    let mut show_log = false;
+
+    // the ply being played, when it came up (for the engine's thinking and each move's time) and how deep the engine got
+    let mut clock = GameClock::new();
+    let mut thinking_ply = history.len();
+    let mut thinking_since = clock.now();
+    let mut thinking_depth = None;
+
+    // engine games can be stopped early once their result is clear, `search_score` is the latest
+    // score of the search in progress
+    let mut adjudicator = settings.adjudication.filter(|_| mode == Mode::EngineVsEngine).map(Adjudicator::new);
+    let mut search_score = None;
 
     let mut animations: Vec<Animation> = Vec::new();
+    let mut view = BoardView::default();
+    // what became of the last picture or gif saved of the game
+    let mut export_status: Option<String> = None;
+    // when the king was mated, and (corner, king, seconds since) to draw it fallen over this frame
+    let mut mated_since: Option<f64> = None;
+    let mut toppled_king: Option<((f32, f32), Piece, f32)> = None;
+    // frame rate in a corner, toggled with F3
+    let mut show_frame_time = false;
 
     // convert y and x
-    let yc = |y: usize| if !flipped { 7 - y } else { y };
-    let xc = |x: usize| if flipped { 7 - x } else { x };
+    // keep the side to move at the bottom when the engine plays itself, or for each player on one screen
+    let hot_seat = orientation == Orientation::FollowPlayer && mode == Mode::TwoPlayer;
+    let auto_flip = hot_seat || (orientation == Orientation::FollowPlayer && mode == Mode::EngineVsEngine);
+    let mut pass_time = 0.0;
+
+    // the board can be flipped mid game, so the transforms read it live
+    let flipped = Cell::new(match orientation {
+        Orientation::WhiteBottom => { false }
+        Orientation::BlackBottom => { true }
+        Orientation::FollowPlayer if mode == Mode::Engine => { player_color == chess::Color::Black }
+        Orientation::FollowPlayer if auto_flip => { game.turn == chess::Color::Black }
+        Orientation::FollowPlayer => { false }
+    });
+    let yc = |y: usize| if !flipped.get() { 7 - y } else { y };
+    let xc = |x: usize| if flipped.get() { 7 - x } else { x };
 
-    let rp = |u: usize| (xc(u % 8) as f32 * square_size, yc(u / 8) as f32 * square_size);
-    let bp = |s: usize| (xc(s % 8), yc(s / 8));
+    // seconds left of the rotation after a flip
+    let flip_total = move_time * 3.0;
+    let mut flip_time = 0.0;
 
-    let mut promotion_square: Option<usize> = None;
+    // screen square of a board square and back
+    let bp = |s: usize| (xc(Square::new(s).file().index()), yc(Square::new(s).rank().index()));
+    let rp = |u: usize| (bp(u).0 as f32 * square_size, bp(u).1 as f32 * square_size);
+    let board_square = |x: usize, y: usize| Square::at(File::new(xc(x)), Rank::new(yc(y))).index();
 
-    let handle_move = |a1: Option<Animation>, a2: Option<Animation>, mut sound: Sound, res: MoveResult,
-                       game: &Game, animations: &mut Vec<Animation>, winner: &mut Option<chess::Color>, draw: &mut bool| {
-        if !res.is_ok() { return; }
+    // (from, to) of a pawn waiting for its promotion piece, the board is left alone until one is picked
+    let mut promotion_square: Option<(usize, usize)> = None;
 
-        if res == MoveResult::Checkmate { *winner = Some(!game.turn); }
-        else if res == MoveResult::Check {
-            let pos = game.find_king(game.turn).unwrap();
+    // keyboard cursor in screen squares, only drawn once the keyboard has been used
+    let mut cursor = (xc(4), yc(1));
+    let mut keyboard = false;
+    // typed move entry, opened with tab
+    let mut san_entry: Option<String> = None;
+    let mut san_error = false;
 
-            let px = xc(pos % 8);
-            let py = yc(pos / 8);
+    // mouse and touch, squares marked with a long press are cleared by the next move
+    let mut pointer = Pointer::default();
+    let mut marks: Vec<usize> = Vec::new();
+    let mut marks_ply = history.len();
 
-            let ca = check_animation(game.turn, ((px as f32 + 0.5) * square_size, (py as f32 + 0.5) * square_size), square_size / 2.0);
-            animations.push(ca);
+    let mut captures = Captures::default();
+    // an earlier position being looked at mid game, (ply, position). the board can't be played on meanwhile
+    let mut browsing: Option<(usize, Game)> = None;
 
-            sound = check_sound;
-        } else if res == MoveResult::Stalemate || res == MoveResult::Draw {
-            *draw = true;
-        }
+    let handle_move = |a1: Option<Animation>, a2: Option<Animation>, mut sound: Sound, res: MoveResult,
+                       history: &History, animations: &mut Vec<Animation>, result: &mut Option<GameResult>| {
+        if !res.is_ok() { return; }
+        // a piece still shaking from a refused move has moved on
+        animations.retain(|x| !x.is_reject());
 
-        if let Some(a) = a1 { animations.push(a); }
+        // the king in check glows while it stays in check, drawn from the position each frame
+        if res == MoveResult::Check { sound = check_sound; }
+        *result = GameResult::of(history);
+
+        // the moving piece is drawn over the one it captures
         if let Some(a) = a2 { animations.push(a); }
+        if let Some(a) = a1 { animations.push(a); }
         play_sound_once(sound);
     };
 
     loop {
+        clock.tick();
         clear_background(WHITE);
 
-        if game.turn == !player_color && !two_player {
-            if let Some((s_pos, e_pos, pr, alg)) = sf.try_result() {
-                let a1 = primary_animation(&game, s_pos, e_pos, rp, bp);
-                let a2 = secondary_animation(&game, s_pos, e_pos, rp, bp);
-                let mut sound = get_sound(&game, s_pos, e_pos, sounds);
+        // the board fills the window whatever its shape, like on a phone, and can be zoomed into on top of
+        // that. the panel stays put over it
+        let layout = board_camera(screen_size + PANEL, screen_size);
+        // the panel is over the board when zoomed in, clicks on it aren't for the board and the wheel steps through the game
+        let on_panel = layout.screen_to_world(mouse_position().into()).x >= screen_size;
+        let pinching = view.update(&layout, !on_panel);
+        let camera = view.camera(&layout);
+        set_camera(&camera);
+
+        if flip_time > 0.0 {
+            // rotate from the old orientation back to upright
+            let t = flip_time / flip_total;
+            set_camera(&rotated_around(&camera, vec2(screen_size / 2.0, screen_size / 2.0), 180.0 * t * t));
+
+            flip_time -= clock.frame_time();
+        }
 
-                let res = game.move_checked(s_pos, e_pos, pr);
-                assert!(res.is_ok(), "Move {} was illegal at fen={}", alg, game.as_fen());
+        // before the engine's move is taken, so the last infos of its search count for adjudication
+        if history.len() != thinking_ply {
+            (thinking_ply, thinking_since, thinking_depth) = (history.len(), clock.now(), None);
+        }
 
-                handle_move(a1, a2, sound, res, &game, &mut animations, &mut winner, &mut draw);
+        while let Some(info) = sf.try_info() {
+            thinking_depth = info.depth.or(thinking_depth);
+            search_score = info.score.or(search_score);
+
+            // long pvs are cut to the panel's width
+            let line: String = info.to_string().chars().take(60).collect();
+            if !line.is_empty() { engine_log.push(line); }
+        }
+        if engine_log.len() > LOG_LINES { engine_log.drain(..engine_log.len() - LOG_LINES); }
+
+        if engine_moves(game.turn) {
+            match sf.try_result() {
+                Some(Ok(mv)) => {
+                    let (s_pos, e_pos) = (mv.from.index(), mv.to.index());
+                    let a1 = primary_animation(&game, s_pos, e_pos, move_time, rp, bp);
+                    let a2 = secondary_animation(&game, s_pos, e_pos, a1.as_ref(), move_time, rp, bp);
+                    let mut sound = get_sound(&game, s_pos, e_pos, sounds);
+
+                    match game.make_move(mv) {
+                        Ok(res) => {
+                            let _ = history.push_timed(mv, clock.since(thinking_since));
+                            handle_move(a1, a2, sound, res, &history, &mut animations, &mut result);
+
+                            let score = search_score.take();
+                            // the next search is at the level the score calls for
+                            if let (Some(adaptive), Some(score)) = (adaptive.as_mut(), score) {
+                                if adaptive.update(score) {
+                                    for (name, value) in adaptive.options() { sf.set_option(name, &value); }
+                                    limits = adaptive.limits();
+                                }
+                            }
+                            if let Some(verdict) = adjudicator.as_mut().filter(|_| result.is_none()).and_then(|x| x.update(&game, score)) {
+                                result = Some(verdict);
+                            }
+
+                            if engine_moves(game.turn) && result.is_none() { sf.recommend_move(&history, limits); }
+                        }
+                        Err(err) => {
+                            engine_error = Some(trf("game.bad_engine_move", &[&mv.to_string(), &game.as_fen(), &err.to_string()]));
+                        }
+                    }
+                }
+                // keep the first error, later ones are usually caused by it
+                Some(Err(err)) => { engine_error.get_or_insert(err.to_string()); }
+                None => {}
             }
         }
 
@@ -184,76 +576,675 @@ async fn play_game(two_player: bool, player_color: chess::Color, flipped: bool)
             let mut x = 0.0;
 
             for ix in 0..8 {
-                if (iy + ix) % 2 == 0 {
-                    draw_texture(square_2, x, y, WHITE);
-                } else {
-                    draw_texture(square_1, x, y, WHITE);
+                match (theme.squares, (iy + ix) % 2 == 0) {
+                    (Some((light, _)), true) => { draw_rectangle(x, y, square_size, square_size, light); }
+                    (Some((_, dark)), false) => { draw_rectangle(x, y, square_size, square_size, dark); }
+                    (None, light) => { renderer.draw_square(light, x, y, square_size); }
                 }
 
                 x += square_size;
             }
         }
 
-        if let Some(winner) = winner {
+        // what the board shows, the game as it stands unless an earlier position is being looked at
+        let shown = browsing.map_or(game, |x| x.1);
+        let last = match browsing {
+            Some((ply, _)) => { ply.checked_sub(1).and_then(|x| history.entries().get(x)) }
+            None => { history.entries().last() }
+        };
+
+        if let Some(last) = last {
+            for pos in [last.mv.from, last.mv.to] {
+                let (dx, dy) = rp(pos.index());
+                draw_rectangle(dx, dy, square_size, square_size, theme.last_move);
+            }
+        }
+
+        if history.len() != marks_ply {
+            marks.clear();
+            marks_ply = history.len();
+        }
+
+        for pos in &marks {
+            let (dx, dy) = rp(*pos);
+            draw_circle_lines(dx + square_size / 2.0, dy + square_size / 2.0, square_size * 0.45, square_size / 16.0, theme.annotation);
+        }
+
+        if is_key_pressed(KeyCode::T) && san_entry.is_none() { show_threats = !show_threats; }
+        if is_key_pressed(KeyCode::B) && san_entry.is_none() { show_assist = !show_assist; }
+        if is_key_pressed(KeyCode::F3) { show_frame_time = !show_frame_time; }
+
+        if show_threats {
+            // threats against whoever is on move in two player games
+            let me = if mode == Mode::Engine { player_color } else { shown.turn };
+            let attacks = shown.attack_map(!me);
+
+            for (pos, count) in attacks.iter().enumerate() {
+                if *count == 0 { continue; }
+
+                let (dx, dy) = rp(pos);
+                draw_rectangle(dx, dy, square_size, square_size, theme.threat);
+            }
+
+            for pos in shown.hanging_pieces(me) {
+                let (dx, dy) = rp(pos);
+                draw_rectangle_lines(dx, dy, square_size, square_size, square_size / 16.0, theme.hanging);
+            }
+
+            for (pinned, attacker) in shown.pinned_pieces(me) {
+                let ((px, py), (ax, ay)) = (rp(pinned), rp(attacker));
+                let half = square_size / 2.0;
+                draw_line(ax + half, ay + half, px + half, py + half, square_size / 12.0, theme.pin);
+            }
+        }
+
+        // a mated king topples over, one that lost another way (adjudication) is just marked
+        let winner = result.and_then(|x| x.winner);
+        let mated = winner.filter(|x| browsing.is_none() && game.is_in_checkmate(!*x)).and_then(|x| game.find_king(!x));
+        if mated.is_none() { mated_since = None; }
+
+        if browsing.is_some() {
+            // the game hadn't ended yet on the board shown, only a check is marked
+            if let Some(king) = shown.find_king(shown.turn).filter(|_| shown.is_in_check(shown.turn)) {
+                let (px, py) = bp(king);
+                draw_check_glow(((px as f32 + 0.5) * square_size, (py as f32 + 0.5) * square_size), square_size / 2.0, theme.check, clock.now());
+            }
+        } else if let Some(pos) = mated {
+            let since = *mated_since.get_or_insert(clock.now());
+            let (px, py) = bp(pos);
+
+            draw_check_glow(((px as f32 + 0.5) * square_size, (py as f32 + 0.5) * square_size), square_size / 2.0, theme.check, clock.now());
+            toppled_king = Some((rp(pos), game.board[pos].unwrap(), (clock.now() - since) as f32));
+        } else if let Some(winner) = winner {
             let pos = game.find_king(!winner).unwrap();
 
-            let px = xc(pos % 8);
-            let py = yc(pos / 8);
+            let (px, py) = bp(pos);
 
-            draw_circle((px as f32 + 0.5) * square_size, (py as f32 + 0.5) * square_size, square_size / 2.0, TD_RED);
-        } else if draw {
+            draw_circle((px as f32 + 0.5) * square_size, (py as f32 + 0.5) * square_size, square_size / 2.0, theme.check);
+        } else if game.is_in_check(game.turn) {
+            let (px, py) = bp(game.find_king(game.turn).unwrap());
+
+            draw_check_glow(((px as f32 + 0.5) * square_size, (py as f32 + 0.5) * square_size), square_size / 2.0, theme.check, clock.now());
+        } else if result.is_some() {
             let pos = game.find_king(chess::Color::White).unwrap();
 
-            let px = xc(pos % 8);
-            let py = yc(pos / 8);
+            let (px, py) = bp(pos);
 
-            draw_circle((px as f32 + 0.5) * square_size, (py as f32 + 0.5) * square_size, square_size / 2.0, TD_GRAY);
+            draw_circle((px as f32 + 0.5) * square_size, (py as f32 + 0.5) * square_size, square_size / 2.0, theme.draw);
 
             let pos = game.find_king(chess::Color::Black).unwrap();
 
-            let px = xc(pos % 8);
-            let py = yc(pos / 8);
+            let (px, py) = bp(pos);
 
-            draw_circle((px as f32 + 0.5) * square_size, (py as f32 + 0.5) * square_size, square_size / 2.0, TD_GRAY);
+            draw_circle((px as f32 + 0.5) * square_size, (py as f32 + 0.5) * square_size, square_size / 2.0, theme.draw);
         }
 
+        // moves played meanwhile aren't on the board shown
+        if browsing.is_some() { animations.clear(); }
+
         // play all animations
         let mut i = 0;
         while animations.len() > i {
             let animation = &mut animations[i];
 
-            if animation.draw_frame(get_texture) {
+            if animation.draw_frame(&pieces, clock.frame_time()) {
                 i += 1;
             } else {
                 animations.remove(i);
             }
         }
 
-        for x in 0..8 {
-            'outer: for y in 0..8 {
-                let piece = game.board[yc(y) * 8 + xc(x)];
+        // a piece being dragged follows the pointer instead
+        let dragged = pointer.drag_position(&camera).and(selected_piece);
+
+        // screen squares whose piece is drawn some other way this frame: moving, dragged or mated
+        let mut hidden = [[false; 8]; 8];
+        for (x, y) in animations.iter().filter_map(|x| x.render_exception()).chain(dragged) {
+            hidden[x][y] = true;
+        }
+        if let Some(pos) = mated {
+            let (x, y) = bp(pos);
+            hidden[x][y] = true;
+        }
+
+        // beginner assist: on a human's turn, the pieces with a legal move pulse and the rest are dimmed
+        let mut movable = [false; 64];
+        let assist = show_assist && browsing.is_none() && human_moves(game.turn) && result.is_none();
+        if assist {
+            for mv in game.legal_moves() { movable[mv.from.index()] = true; }
+        }
+        let pulse = 0.5 + 0.5 * (clock.now() * 3.0).sin() as f32;
+
+        for (x, column) in hidden.iter().enumerate() {
+            for (y, hidden) in column.iter().enumerate() {
+                if *hidden { continue; }
+
+                let pos = board_square(x, y);
+                if let Some(piece) = shown.board[pos] {
+                    let (dx, dy) = (square_size * x as f32, square_size * y as f32);
+                    let tint = match (assist && piece.color() == game.turn, movable[pos]) {
+                        (false, _) => { WHITE }
+                        (true, true) => {
+                            let glow = Color { a: theme.movable.a * pulse, ..theme.movable };
+                            draw_circle(dx + square_size / 2.0, dy + square_size / 2.0, square_size * 0.42, glow);
+                            WHITE
+                        }
+                        (true, false) => { theme.unmovable }
+                    };
+                    pieces.draw(piece, dx, dy, tint);
+                }
+            }
+        }
+
+        // the dragged piece leaves a ghost on the square it came from
+        if let Some((x, y)) = dragged {
+            if let Some(piece) = game.board[board_square(x, y)] {
+                pieces.draw_ghost(piece, square_size * x as f32, square_size * y as f32, theme.ghost);
+            }
+        }
+
+        if let Some((corner, king, time)) = toppled_king.take() {
+            draw_toppled_king(&pieces, king, corner, time / MATE_TIME);
+        }
+
+        set_camera(&camera);
+
+        if let (Some(p), Some((x, y))) = (pointer.drag_position(&camera), dragged) {
+            // the square the piece would be dropped on
+            if (0.0..screen_size).contains(&p.x) && (0.0..screen_size).contains(&p.y) {
+                let (tx, ty) = ((p.x / square_size).floor(), (p.y / square_size).floor());
+                renderer.draw_square_outline(tx * square_size, ty * square_size, square_size, theme.drop_target);
+            }
+
+            if let Some(piece) = game.board[board_square(x, y)] {
+                pieces.draw(piece, p.x - square_size / 2.0, p.y - square_size / 2.0, WHITE);
+            }
+        }
+
+        set_camera(&layout);
+
+        // taken pieces beside each player's edge of the board, most valuable first
+        captures.update(&history);
+        draw_rectangle(screen_size, 0.0, PANEL, screen_size, GRAY);
+
+        if is_key_pressed(KeyCode::E) && san_entry.is_none() {
+            show_eval = !show_eval;
+            if let Some(analyser) = analyser.as_mut().filter(|_| !show_eval) { analyser.pause(); }
+        }
+
+        if show_eval {
+            let analyser = analyser.get_or_insert_with(|| Analyser::new(match &analysis_engine {
+                Some(config) => { ThreadedUciBuilder::new().config(config.clone()) }
+                None => { ThreadedUciBuilder::new() }
+            }));
+            // a finished game has nothing left to analyse
+            if result.is_none() { analyser.follow(&history); } else { analyser.pause(); }
+
+            // white's share of the bar is its winning chances, from white's edge of the board
+            let score = analyser.white_score();
+            let white = score.map(|x| win_percent(x.centipawns()) / 100.0).unwrap_or(0.5) * screen_size;
+            let x = screen_size + PANEL - 12.0;
+            draw_rectangle(x, 0.0, 12.0, screen_size, BLACK);
+            draw_rectangle(x, if flipped.get() { 0.0 } else { screen_size - white }, 12.0, white, WHITE);
+
+            let text = match (analyser.error(), score) {
+                (Some(err), _) => { err.to_string().chars().take(18).collect() }
+                (None, Some(score)) => { score.to_string() }
+                (None, None) => { String::from("...") }
+            };
+            draw_text(&text, screen_size + 10.0, screen_size / 2.0 + 70.0, 24.0, WHITE);
+        }
+
+        for color in [chess::Color::White, chess::Color::Black] {
+            let bottom = (color == chess::Color::White) != flipped.get();
+            let size = PANEL / 4.0;
+
+            for (i, piece) in captures.taken(color).iter().enumerate() {
+                // rows grow away from the player's edge
+                let row = (i / 4) as f32;
+                let y = if bottom { screen_size - 50.0 - (row + 1.0) * size } else { 50.0 + row * size };
+
+                pieces.draw_sized(*piece, screen_size + (i % 4) as f32 * size, y, size);
+            }
+
+            let advantage = captures.advantage(color);
+            if advantage > 0 {
+                let y = if bottom { screen_size - 15.0 } else { 35.0 };
+                draw_text(&format!("+{}", advantage), screen_size + 10.0, y, 32.0, WHITE);
+            }
+        }
+
+        // middle of the panel, clear of both players' captures
+        if let Some(adaptive) = &adaptive {
+            let middle = screen_size / 2.0;
+            let level = (adaptive.index() + 1).to_string();
+            draw_text(&trf("game.adaptive", &[&level, &LEVELS.len().to_string()]), screen_size + 10.0, middle - 80.0, 24.0, WHITE);
+            draw_text(&trf("game.adaptive_elo", &[&adaptive.level().elo.to_string()]), screen_size + 10.0, middle - 54.0, 24.0, WHITE);
+        }
+        if let Some((before, after)) = rating_change {
+            let middle = screen_size / 2.0;
+            draw_text(tr("game.rating"), screen_size + 10.0, middle - 10.0, 28.0, WHITE);
+            draw_text(&format!("{:.0} ({:+.0})", after.rating, after.rating - before.rating), screen_size + 10.0, middle + 20.0, 28.0, WHITE);
+        }
+
+        if is_key_pressed(KeyCode::I) && san_entry.is_none() { show_log = !show_log; }
 
-                let dx = (square_size) * x as f32;
-                let dy = (square_size) * y as f32;
+        if show_log {
+            let (width, height) = (screen_size * 0.6, 40.0 + LOG_LINES as f32 * 22.0);
+            draw_rectangle(screen_size - width, 0.0, width, height, theme.banner);
+            draw_text(tr("game.engine_output"), screen_size - width + 10.0, 28.0, 28.0, WHITE);
 
-                for animation in &animations {
-                    if let Some(r) = animation.render_exception() {
-                        if r.0 == x && r.1 == y { continue 'outer; }
+            for (i, line) in engine_log.iter().enumerate() {
+                draw_text(line, screen_size - width + 10.0, 58.0 + i as f32 * 22.0, 20.0, WHITE);
+            }
+        }
+
+        if engine_moves(game.turn) && result.is_none() && engine_error.is_none() {
+            // on the engine's edge of the board
+            let bottom = (game.turn == chess::Color::White) != flipped.get();
+            let y = if bottom { screen_size - 50.0 } else { 10.0 };
+
+            draw_rectangle(10.0, y, 300.0, 40.0, theme.banner);
+
+            // spinner, the brightest dot going round
+            let lead = (clock.now() * 8.0) as usize % 8;
+            for i in 0..8 {
+                let angle = i as f32 * std::f32::consts::FRAC_PI_4;
+                let alpha = 1.0 - ((lead + 8 - i) % 8) as f32 / 8.0;
+                draw_circle(30.0 + angle.cos() * 10.0, y + 20.0 + angle.sin() * 10.0, 3.0, Color::new(1.0, 1.0, 1.0, alpha));
+            }
+
+            let elapsed = clock.now() - thinking_since;
+            let text = match thinking_depth {
+                Some(depth) => { trf("game.thinking_depth", &[&depth.to_string(), &format!("{:.1}", elapsed)]) }
+                None => { trf("game.thinking", &[&format!("{:.1}", elapsed)]) }
+            };
+            draw_text(&text, 52.0, y + 28.0, 28.0, WHITE);
+        }
+
+        if let Some(verdict) = result.filter(|x| matches!(x.reason, TerminationReason::Adjudication(_))) {
+            draw_rectangle(0.0, 0.0, screen_size, 40.0, theme.banner);
+            draw_text(&trf("game.adjudicated", &[&verdict.to_string()]), 10.0, 28.0, 28.0, WHITE);
+        }
+
+        if let Some(err) = &engine_error {
+            draw_rectangle(0.0, 0.0, screen_size, 40.0, theme.banner);
+            draw_text(err, 10.0, 28.0, 28.0, WHITE);
+        }
+
+        if let Some(ended) = result.filter(|_| !archived) {
+            archived = true;
+            play_sound_once(sounds.game_end);
+
+            let mut pgn = Pgn::new(history.clone());
+            let (white_name, black_name) = match mode {
+                Mode::TwoPlayer => { ("Player 1", "Player 2") }
+                _ => { ("Player", "Player") }
+            };
+            let mut info = info.clone().or(GameInfo {
+                white: white_name.to_string(), black: black_name.to_string(), event: "Casual game".to_string(), round: String::new(),
+            });
+            // an engine's side is named by the engine, as it introduced itself unless it was swapped mid game
+            let engine_id = sf.engine_name().filter(|_| !swapped).unwrap_or_else(|| engine_name.clone());
+            if engine_moves(chess::Color::White) { info.white = engine_id.clone(); }
+            if engine_moves(chess::Color::Black) { info.black = engine_id; }
+
+            info.apply(&mut pgn);
+            pgn.set_tag("Date", pgn_date());
+            ended.write_tags(&mut pgn);
+            if mode == Mode::EngineVsEngine {
+                // one engine plays both sides at the same limits
+                pgn.set_tag("TimeControl", limits.pgn_time_control());
+                if let Some(id) = sf.engine_name() {
+                    let (name, version) = chess::uci::split_engine_id(&id);
+                    for side in ["White", "Black"] {
+                        pgn.set_tag(format!("{}Engine", side), name);
+                        if let Some(version) = version { pgn.set_tag(format!("{}EngineVersion", side), version); }
+                    }
+                }
+            } else {
+                pgn.set_tag("TimeControl", "-");
+            }
+            if let Some(handicap) = odds.tag(!player_color) { pgn.set_tag("Handicap", handicap); }
+
+            match Archive::open(ARCHIVE_DIR).and_then(|x| x.save(&pgn)) {
+                Ok(path) => { saved_game = Some(ArchivedGame { path, pgn }); }
+                Err(err) => { engine_error.get_or_insert(trf("game.archive_failed", &[&err.to_string()])); }
+            }
+
+            if let Some(opponent) = engine_rating {
+                match rate_game(&engine_name, opponent, ended.score(player_color).into()) {
+                    Ok(change) => { rating_change = Some(change); }
+                    Err(err) => { engine_error.get_or_insert(trf("game.rating_failed", &[&err.to_string()])); }
+                }
+            }
+        }
+
+        // keyboard play: arrows move the cursor, file letters and rank digits jump to it,
+        // enter acts as a click and tab opens typed move entry
+        let mut typed: Vec<char> = std::iter::from_fn(get_char_pressed).collect();
+        typed.reverse();
+        let typing = san_entry.is_some();
+
+        if is_key_pressed(KeyCode::O) && !typing && mode != Mode::TwoPlayer && result.is_none() {
+            // the time spent picking doesn't count towards the move being thought about
+            clock.pause();
+            sf.set_paused(true);
+            let picked = gui::settings::swap_engine(settings, &current_engine).await;
+            clock.resume();
+            sf.set_paused(false);
+
+            if let Some(config) = picked {
+                // dropping the old engine quits its process, the new one is sent the whole game so far
+                sf = settings.reply_delay.builder().config(config.clone()).build();
+                sf.new_game();
+                if let Some(adaptive) = &adaptive {
+                    for (name, value) in adaptive.options() { sf.set_option(name, &value); }
+                }
+                if engine_moves(game.turn) { sf.recommend_move(&history, limits); }
+
+                // the game is no longer against one engine of a known strength
+                let name = if config.name.is_empty() { "Engine" } else { config.name.as_str() };
+                if name != engine_name { engine_name = format!("{} / {}", engine_name, name); }
+                swapped = true;
+                engine_rating = None;
+
+                (thinking_depth, search_score, engine_error) = (None, None, None);
+                engine_log.clear();
+                current_engine = config;
+            }
+
+            request_new_screen_size(screen_size + PANEL, screen_size);
+            next_frame().await;
+            continue;
+        }
+
+        // P pauses the game, the pause menu has links to share it
+        if is_key_pressed(KeyCode::P) && !typing {
+            // everything stands still while paused, the engine's reply included
+            clock.pause();
+            sf.set_paused(true);
+            let exit = gui::pause::pause_menu(&history).await;
+            clock.resume();
+            sf.set_paused(false);
+
+            if let PauseExit::Leave = exit { return None; }
+
+            request_new_screen_size(screen_size + PANEL, screen_size);
+            next_frame().await;
+            continue;
+        }
+
+        // backspace takes back the player's last move, and the engine's reply if it has made one
+        if is_key_pressed(KeyCode::Backspace) && !typing && mode == Mode::Engine && result.is_none() && promotion_square.is_none() {
+            let taken = history.take_back(player_color);
+
+            if let Some(mine) = taken.last() {
+                // the search in progress is of a position that's gone, the next one is sent the game as it is now
+                sf.cancel();
+                game = history.current();
+                (selected_piece, thinking_depth, search_score, browsing) = (None, None, None, None);
+                animations.clear();
+
+                // the player's time on the move carries on from where it was
+                thinking_ply = history.len();
+                thinking_since = clock.now() - mine.time.map(|x| x.as_secs_f64()).unwrap_or_default();
+            }
+        }
+
+        // shift with the left and right arrows steps through the game, the arrows alone move the cursor
+        let shift = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
+
+        let mut flip = auto_flip && animations.is_empty() && result.is_none() && flipped.get() != (game.turn == chess::Color::Black);
+        let turning = flip;
+
+        if let Some(text) = &mut san_entry {
+            for c in typed.into_iter().filter(|x| x.is_ascii_alphanumeric() || "=+#-".contains(*x)) {
+                text.push(c);
+                san_error = false;
+            }
+
+            if is_key_pressed(KeyCode::Backspace) {
+                text.pop();
+                san_error = false;
+            }
+
+            let can_move = result.is_none() && promotion_square.is_none() && human_moves(game.turn);
+
+            if is_key_pressed(KeyCode::Escape) {
+                san_entry = None;
+            } else if is_key_pressed(KeyCode::Enter) {
+                match chess::pgn::parse_san(&game, text).filter(|_| can_move) {
+                    Some(mv) => {
+                        let (from, to) = (mv.from.index(), mv.to.index());
+                        let a1 = primary_animation(&game, from, to, move_time, rp, bp);
+                        let a2 = secondary_animation(&game, from, to, a1.as_ref(), move_time, rp, bp);
+                        let sound = get_sound(&game, from, to, sounds);
+
+                        if let Ok(res) = game.make_move(mv) {
+                            let _ = history.push_timed(mv, clock.since(thinking_since));
+                            handle_move(a1, a2, sound, res, &history, &mut animations, &mut result);
+
+                            if engine_moves(game.turn) && result.is_none() { sf.recommend_move(&history, limits); }
+                            selected_piece = None;
+                        }
+
+                        san_entry = None;
+                    }
+                    None => {
+                        san_error = true;
+                        play_sound_once(error_sound);
+                    }
+                }
+            }
+        } else {
+            if is_key_pressed(KeyCode::Left) && !shift { cursor.0 = cursor.0.saturating_sub(1); keyboard = true; }
+            if is_key_pressed(KeyCode::Right) && !shift { cursor.0 = (cursor.0 + 1).min(7); keyboard = true; }
+            if is_key_pressed(KeyCode::Up) { cursor.1 = cursor.1.saturating_sub(1); keyboard = true; }
+            if is_key_pressed(KeyCode::Down) { cursor.1 = (cursor.1 + 1).min(7); keyboard = true; }
+
+            for c in typed {
+                match c {
+                    // shift+f, plain f jumps to the f file
+                    'F' => { flip = true; }
+                    'a'..='h' => {
+                        cursor.0 = xc(c as usize - 'a' as usize);
+                        keyboard = true;
+                    }
+                    '1'..='8' => {
+                        cursor.1 = yc(c as usize - '1' as usize);
+                        keyboard = true;
+                    }
+                    _ => {}
+                }
+            }
+
+            if is_key_pressed(KeyCode::Tab) {
+                san_entry = Some(String::new());
+                san_error = false;
+            }
+        }
+
+        // looking back through the game, with the wheel over the panel or shift and the arrows. stepping
+        // forward past the last move, end or the button go back to the game
+        let wheel = if on_panel { mouse_wheel().1 } else { 0.0 };
+        let back = wheel > 0.0 || (shift && is_key_pressed(KeyCode::Left));
+        let forward = wheel < 0.0 || (shift && is_key_pressed(KeyCode::Right));
+        let step: isize = if typing || promotion_square.is_some() { 0 } else if back { -1 } else if forward { 1 } else { 0 };
+        let live = browsing.is_some() && (root_ui().button(vec2(screen_size + 10.0, screen_size / 2.0 + 110.0), tr("game.live"))
+            || (is_key_pressed(KeyCode::End) && !typing));
+
+        if step != 0 || live {
+            let ply = if live { history.len() } else { browsing.map_or(history.len(), |x| x.0).saturating_add_signed(step).min(history.len()) };
+            browsing = (ply < history.len()).then(|| (ply, history.position(ply)));
+            selected_piece = None;
+        }
+        if let Some((ply, _)) = browsing {
+            draw_text(&trf("game.browsing", &[&ply.to_string(), &history.len().to_string()]), screen_size + 10.0, screen_size / 2.0 + 100.0, 24.0, WHITE);
+        }
+
+        if flip {
+            // screen squares are rotated half a turn
+            let rotate = |(x, y): (usize, usize)| (7 - x, 7 - y);
+
+            flipped.set(!flipped.get());
+            selected_piece = selected_piece.map(rotate);
+            cursor = rotate(cursor);
+            animations.clear();
+            flip_time = flip_total;
+
+            if hot_seat && turning { pass_time = PASS_TIME; }
+        }
+
+        if let Some(text) = &san_entry {
+            draw_rectangle(10.0, screen_size - 50.0, 320.0, 40.0, if san_error { theme.check } else { theme.banner });
+            draw_text(&trf("game.move_entry", &[text]), 20.0, screen_size - 22.0, 28.0, WHITE);
+        }
+
+        // the rest is drawn on the board
+        set_camera(&camera);
+
+        if keyboard {
+            let (dx, dy) = (cursor.0 as f32 * square_size, cursor.1 as f32 * square_size);
+            draw_rectangle_lines(dx, dy, square_size, square_size, square_size / 12.0, theme.cursor);
+        }
+
+        // + and - zoom into the middle of the view, 0 goes back to the whole board
+        if !typing {
+            if is_key_pressed(KeyCode::Equal) || is_key_pressed(KeyCode::KpAdd) { view.zoom_around(camera.target, ZOOM_STEP, &layout); }
+            if is_key_pressed(KeyCode::Minus) || is_key_pressed(KeyCode::KpSubtract) { view.zoom_around(camera.target, 1.0 / ZOOM_STEP, &layout); }
+            if is_key_pressed(KeyCode::Key0) { view.reset(); }
+        }
+
+        let event = pointer.update(&camera).filter(|_| !pinching);
+        let on_board = |p: Vec2| !on_panel && (0.0..screen_size).contains(&p.x) && (0.0..screen_size).contains(&p.y);
+        let square_at = |p: Vec2| ((p.x / square_size) as usize, (p.y / square_size) as usize);
+
+        // a square picked this frame with the mouse, a finger, a drop at the end of a drag or enter on the cursor
+        let mut picked = match event {
+            Some(PointerEvent::Press(p) | PointerEvent::Drop(p)) if on_board(p) => {
+                let square = square_at(p);
+
+                // a finger covers more than a square, so a touch close to a legal destination lands there
+                match selected_piece.filter(|_| pointer.touch) {
+                    Some((x, y)) => {
+                        let moves = || game.legal_moves_from(Square::new(board_square(x, y)));
+                        let center = |(sx, sy): (usize, usize)| vec2((sx as f32 + 0.5) * square_size, (sy as f32 + 0.5) * square_size);
+
+                        if moves().any(|x| x.to.index() == board_square(square.0, square.1)) {
+                            Some(square)
+                        } else {
+                            moves()
+                                .map(|mv| bp(mv.to.index()))
+                                .filter(|x| center(*x).distance(p) < square_size * 0.9)
+                                .min_by(|a, b| center(*a).distance(p).total_cmp(&center(*b).distance(p)))
+                                .or(Some(square))
+                        }
                     }
+                    None => { Some(square) }
+                }
+            }
+            _ if is_key_pressed(KeyCode::Enter) && !typing => { Some(cursor) }
+            _ => { None }
+        };
+
+        // hot seat: the board is covered for a moment once it has turned, a tap skips ahead
+        if pass_time > 0.0 {
+            pass_time -= clock.frame_time();
+            if picked.take().is_some() { pass_time = 0.0; }
+
+            draw_rectangle(0.0, 0.0, screen_size, screen_size, Color { a: 0.85, ..theme.banner });
+
+            let text = match game.turn {
+                chess::Color::White => { tr("game.pass_white") }
+                chess::Color::Black => { tr("game.pass_black") }
+            };
+            let size = measure_text(text, None, 48, 1.0);
+            draw_text(text, (screen_size - size.width) / 2.0, screen_size / 2.0, 48.0, WHITE);
+        }
+
+        if let Some(square) = picked { cursor = square; }
+        // nothing can be played on an earlier position
+        if browsing.is_some() { picked = None; }
+
+        // long press or right click marks a square, to point things out
+        if let Some(PointerEvent::LongPress(p)) = event.filter(|_| promotion_square.is_none()) {
+            if on_board(p) {
+                let (x, y) = square_at(p);
+                let pos = board_square(x, y);
+
+                match marks.iter().position(|x| *x == pos) {
+                    Some(i) => { marks.remove(i); }
+                    None => { marks.push(pos); }
                 }
+                selected_piece = None;
+            }
+        }
+
+        if is_key_pressed(KeyCode::Escape) && !typing && promotion_square.is_none() {
+            return None;
+        }
+
+        // the finished game as a picture of where it ended, or a gif of all of it
+        let finished = result.is_some();
+        let export = if finished && root_ui().button(vec2(10.0, 100.0), tr("game.save_picture")) { Some(false) }
+            else if finished && root_ui().button(vec2(10.0, 150.0), tr("game.save_gif")) { Some(true) }
+            else { None };
+        if let Some(gif) = export {
+            let name = saved_game.as_ref().and_then(|x| x.path.file_stem()).map(|x| x.to_string_lossy().into_owned()).unwrap_or_else(|| "game".to_string());
+
+            export_status = Some(match gui::export::export_game(assets, &history, &name, gif).await {
+                Ok(path) => { trf("game.saved", &[&path.display().to_string()]) }
+                Err(err) => { trf("game.save_failed", &[&err]) }
+            });
+
+            next_frame().await;
+            continue;
+        }
+        if finished && (root_ui().button(vec2(10.0, 200.0), tr("game.rematch")) || (is_key_pressed(KeyCode::R) && !typing)) {
+            return Some(NextGame::Rematch);
+        }
+        // the players, event and round of the archived game can still be changed
+        if let Some(saved) = saved_game.as_mut().filter(|_| finished && root_ui().button(vec2(10.0, 250.0), tr("game.details"))) {
+            let mut edited = GameInfo::from_pgn(&saved.pgn);
+            if gui::archive::edit_game_info(&mut edited).await {
+                edited.apply(&mut saved.pgn);
+                if let Err(err) = saved.save() { export_status = Some(trf("game.save_failed", &[&err.to_string()])); }
+            }
+            request_new_screen_size(screen_size + PANEL, screen_size);
+
+            next_frame().await;
+            continue;
+        }
+        if let Some(status) = export_status.as_ref().filter(|_| finished) {
+            draw_text(status, 10.0, 330.0, 24.0, WHITE);
+        }
 
-                if let Some(piece) = piece {
-                    draw_texture(get_texture(piece), dx, dy, WHITE);
+        if result.is_some() && (root_ui().button(vec2(10.0, 50.0), tr("game.analyse")) || (is_key_pressed(KeyCode::A) && !typing)) {
+            if let Some(ply) = gui::report::analysis_report(history.clone(), saved_game.as_mut()).await {
+                // the position picked on the evaluation graph is shown in the archive's replay
+                let mut reviewed = saved_game.clone().unwrap_or_else(|| ArchivedGame { path: Default::default(), pgn: Pgn::new(history.clone()) });
+                let exit = gui::archive::review_game(assets, &mut reviewed, saved_game.is_some(), NodePath::main_line(ply)).await;
+
+                match exit {
+                    ReviewExit::Play(position) => { return Some(NextGame::From(position)); }
+                    ReviewExit::Delete if Archive::open(ARCHIVE_DIR).and_then(|x| x.delete(&reviewed)).is_ok() => { saved_game = None; }
+                    _ if saved_game.is_some() => { saved_game = Some(reviewed); }
+                    _ => {}
                 }
             }
+            request_new_screen_size(screen_size + PANEL, screen_size);
+
+            next_frame().await;
+            continue;
         }
 
-        if let Some(pos) = promotion_square {
-            let color = game.board[pos].unwrap().color();
+        if let Some((from, pos)) = promotion_square {
+            let color = game.turn;
 
-            let mut promotions: HashMap<usize, Piece> = HashMap::new();
+            let mut promotions: HashMap<usize, Promotion> = HashMap::new();
 
-            if (color == chess::Color::White && !flipped) || (color == chess::Color::Black && flipped) {
+            if (color == chess::Color::White && !flipped.get()) || (color == chess::Color::Black && flipped.get()) {
                 let (dx, mut dy) = rp(pos);
 
                 draw_rectangle(dx, dy, square_size, square_size * 4.0, WHITE);
@@ -262,11 +1253,10 @@ async fn play_game(two_player: bool, player_color: chess::Color, flipped: bool)
                 let mut of = 32;
                 for i in PROMOTIONS {
                     let piece = Piece::from_promotion(i, color);
-                    draw_texture(get_texture(piece),
-                                 dx, dy, WHITE);
+                    pieces.draw(piece, dx, dy, WHITE);
 
                     of -= 8;
-                    promotions.insert(pos - of, piece);
+                    promotions.insert(pos - of, i);
 
                     dy -= square_size;
                 }
@@ -279,42 +1269,34 @@ async fn play_game(two_player: bool, player_color: chess::Color, flipped: bool)
                 let mut of = 32;
                 for i in PROMOTIONS {
                     let piece = Piece::from_promotion(i, color);
-                    draw_texture(get_texture(piece),
-                                 dx, dy, WHITE);
+                    pieces.draw(piece, dx, dy, WHITE);
 
                     of -= 8;
-                    promotions.insert(pos + of, piece);
+                    promotions.insert(pos + of, i);
 
                     dy += square_size;
                 }
             }
 
-            if is_mouse_button_pressed(MouseButton::Left) {
-                let (x1, y1) = mouse_position();
+            if is_key_pressed(KeyCode::Escape) {
+                promotion_square = None;
+            } else if let Some((px, py)) = picked {
+                // anywhere but a choice cancels the move
+                promotion_square = None;
 
-                let px = (x1 / square_size).floor() as usize;
-                let py = (y1 / square_size).floor() as usize;
+                if let Some(promotion) = promotions.remove(&(board_square(px, py))) {
+                    let mv = Move::new(from, pos, Some(promotion));
 
-                let c_pos = yc(py) * 8 + xc(px);
+                    let a1 = primary_animation(&game, from, pos, move_time, rp, bp);
+                    let a2 = secondary_animation(&game, from, pos, a1.as_ref(), move_time, rp, bp);
+                    let sound = get_sound(&game, from, pos, sounds);
 
-                if let Some(promotion) = promotions.remove(&c_pos) {
-                    game.board[pos] = Some(promotion);
-                    promotion_square = None;
-                }
-
-                if game.is_in_checkmate(game.turn) { winner = Some(!game.turn); }
-                else if game.is_in_check(game.turn) {
-                    let pos = game.find_king(game.turn).unwrap();
-
-                    let px = xc(pos % 8);
-                    let py = yc(pos / 8);
+                    if let Ok(res) = game.make_move(mv) {
+                        let _ = history.push_timed(mv, clock.since(thinking_since));
+                        handle_move(a1, a2, sound, res, &history, &mut animations, &mut result);
 
-                    let ca = check_animation(game.turn, ((px as f32 + 0.5) * square_size, (py as f32 + 0.5) * square_size), square_size / 2.0);
-                    animations.push(ca);
-
-                    play_sound_once(check_sound);
-                } else if game.is_draw() || game.is_stalemate() {
-                    draw = true;
+                        if engine_moves(game.turn) && result.is_none() { sf.recommend_move(&history, limits); }
+                    }
                 }
             }
 
@@ -323,243 +1305,135 @@ async fn play_game(two_player: bool, player_color: chess::Color, flipped: bool)
         }
 
         // handle moving a piece
-        if is_mouse_button_pressed(MouseButton::Left) && selected_piece.is_some() && !draw && winner.is_none() {
-            if let Some((x, y)) = selected_piece {
-                let (x1, y1) = mouse_position();
+        if picked.is_some() && selected_piece.is_some() && result.is_none() {
+            if let (Some((x, y)), Some((px, py))) = (selected_piece, picked) {
+                let s_pos = board_square(x, y);
+                let e_pos = board_square(px, py);
 
-                let px = (x1 / square_size).floor() as usize;
-                let py = (y1 / square_size).floor() as usize;
-
-                let s_pos = yc(y) * 8 + xc(x);
-                let e_pos = yc(py) * 8 + xc(px);
-
-                let a1 = primary_animation(&game, s_pos, e_pos, rp, bp);
-                let a2 = secondary_animation(&game, s_pos, e_pos, rp, bp);
+                let a1 = primary_animation(&game, s_pos, e_pos, move_time, rp, bp);
+                let a2 = secondary_animation(&game, s_pos, e_pos, a1.as_ref(), move_time, rp, bp);
                 let mut sound = get_sound(&game, s_pos, e_pos, sounds);
 
                 let res = game.move_checked(s_pos, e_pos, None);
                 if res.is_ok() {
-                    if !two_player { sf.recommend_move(game, limits); }
+                    let _ = history.push_timed(Move::new(s_pos, e_pos, None), clock.since(thinking_since));
+                    handle_move(a1, a2, sound, res, &history, &mut animations, &mut result);
 
-                    handle_move(a1, a2, sound, res, &game, &mut animations, &mut winner, &mut draw);
+                    if engine_moves(game.turn) && result.is_none() { sf.recommend_move(&history, limits); }
                     selected_piece = None;
                 } else if res == MoveResult::MissingPromotion && game.is_legal_move(s_pos, e_pos, Some(Promotion::Queen)).is_ok() {
-                    let o_pawn = game.board[s_pos];
-                    game.move_checked(s_pos, e_pos, Some(Promotion::Queen));
-                    game.board[e_pos] = o_pawn;
-
-                    promotion_square = Some(e_pos);
+                    promotion_square = Some((s_pos, e_pos));
                     selected_piece = None;
                 } else {
-                    let px = (x1 / square_size).floor() as usize;
-                    let py = (y1 / square_size).floor() as usize;
+                    let pos = board_square(px, py);
 
-                    let pos = yc(py) * 8 + xc(px);
-
-                    if game.board[pos].some_and(|x| x.color() == game.turn) {
+                    if game.board[pos].is_some_and(|x| x.color() == game.turn) {
                         selected_piece = Some((px, py));
-                    } else { selected_piece = None; }
+                    } else {
+                        // clicking the piece again just puts it down, anywhere else it was told no
+                        if s_pos != e_pos {
+                            animations.extend(reject_animation(&game, s_pos, e_pos, theme.check, move_time, rp, bp));
+                            play_sound_once(error_sound);
+                            if let Some(reason) = game.explain_illegal(Square::new(s_pos), Square::new(e_pos)) {
+                                let at = layout.screen_to_world(mouse_position().into());
+                                rejection = Some((tr(illegal_key(reason)).to_string(), at, get_time()));
+                            }
+                        }
+                        selected_piece = None;
+                    }
                 }
             }
         }
-        else if is_mouse_button_pressed(MouseButton::Left) && (game.turn == player_color || two_player) {
-            let (x, y) = mouse_position();
+        else if let Some((px, py)) = picked.filter(|_| human_moves(game.turn)) {
+            let pos = board_square(px, py);
 
-            let px = (x / square_size).floor() as usize;
-            let py = (y / square_size).floor() as usize;
-
-            let pos = yc(py) * 8 + xc(px);
-
-            if game.board[pos].some_and(|x| x.color() == game.turn) {
+            if game.board[pos].is_some_and(|x| x.color() == game.turn) {
                 selected_piece = Some((px, py));
             }
         }
 
         if let Some((x, y)) = selected_piece {
             // render circle on piece, render possible moves in little circles
-            let g_pos = yc(y) * 8 + xc(x);
+            let g_pos = board_square(x, y);
 
-            draw_circle((x as f32 + 0.5) * square_size, (y as f32 + 0.5) * square_size, square_size / 2.0 - square_size / 5.0, TL_GRAY);
+            draw_circle((x as f32 + 0.5) * square_size, (y as f32 + 0.5) * square_size, square_size / 2.0 - square_size / 5.0, theme.selected);
 
-            for pos in game.all_legal_moves(g_pos) {
-                let y = yc(pos / 8);
-                let x = xc(pos % 8);
+            // one dot for the four promotions
+            for mv in game.legal_moves_from(Square::new(g_pos)).filter(|x| x.promotion.is_none_or(|x| x == Promotion::Queen)) {
+                let (x, y) = bp(mv.to.index());
 
-                if game.board[pos].is_some() || (game.en_passant.some_and(|x| x.location() == pos)
-                    && game.board[g_pos].some_and(|x| *x == Piece::BPawn || *x == Piece::WPawn)) {
-                    draw_circle((x as f32 + 0.5) * square_size, (y as f32 + 0.5) * square_size, square_size / 10.0, TD_RED);
+                if mv.is_capture() {
+                    draw_circle((x as f32 + 0.5) * square_size, (y as f32 + 0.5) * square_size, square_size / 10.0, theme.capture_dot);
                 } else {
-                    draw_circle((x as f32 + 0.5) * square_size, (y as f32 + 0.5) * square_size, square_size / 10.0, TD_GRAY);
+                    draw_circle((x as f32 + 0.5) * square_size, (y as f32 + 0.5) * square_size, square_size / 10.0, theme.move_dot);
                 }
             }
         }
 
-        next_frame().await;
-    }
-}
-
-#[derive(Debug)]
-enum AnimationType {
-    // end_pos, no_render_pos
-    Move(f32, f32, usize, usize),
-    // radius
-    Check(f32),
-    Disappear,
-}
-
-#[derive(Debug)]
-struct Animation {
-    animation_type: AnimationType,
-    piece: Piece,
-    position: (f32, f32),
-    remaining_time: f32,
-    total_time: f32
-}
-
-impl Animation {
-    fn draw_frame(&mut self, texture_provider: impl FnOnce(Piece) -> Texture2D) -> bool {
-        self.remaining_time -= get_frame_time();
-
-        if 0.0 >= self.remaining_time {
-            // animation is over
-            return false;
+        if let Some((text, at, _)) = rejection.as_ref().filter(|x| get_time() - x.2 < REJECTION_TIME) {
+            set_camera(&layout);
+            let size = measure_text(text, None, 24, 1.0);
+            // kept on the board, beside the pointer
+            let x = (at.x + 16.0).min(screen_size - size.width - 16.0);
+            let y = (at.y - 16.0).max(size.height + 16.0);
+            draw_rectangle(x - 8.0, y - size.height - 8.0, size.width + 16.0, size.height + 16.0, theme.banner);
+            draw_text(text, x, y, 24.0, WHITE);
         }
 
-        let progress = (self.total_time - self.remaining_time) / self.total_time;
-
-        match self.animation_type {
-            AnimationType::Move(ex, ey, _, _) => {
-                draw_texture(texture_provider(self.piece),
-                             (ex - self.position.0) * progress + self.position.0,
-                             (ey - self.position.1) * progress + self.position.1,
-                             WHITE);
-            }
-            AnimationType::Disappear => {
-                draw_texture(texture_provider(self.piece), self.position.0, self.position.1,
-                             Color::new(1.0, 1.0, 1.0, 1.0 - progress))
-            }
-            AnimationType::Check(r) => {
-                let opacity = (0.5 - (progress - 0.5).abs()) * 2.0;
-                let mut color = TD_RED;
-                color.a = opacity;
-
-                draw_circle(self.position.0, self.position.1, r, color);
-            }
+        if show_frame_time {
+            set_camera(&layout);
+            draw_text(&format!("{} fps, {:.1} ms", get_fps(), get_frame_time() * 1000.0), 10.0, 20.0, 20.0, RED);
         }
 
-        true
-    }
-
-    fn render_exception(&self) -> Option<(usize, usize)> {
-        match self.animation_type {
-            AnimationType::Move(_, _, ux, uy) => { Some((ux, uy)) }
-            _ => { None }
-        }
+        next_frame().await;
     }
 }
 
-const ANIMATION_TIME: f32 = 0.1;
-fn primary_animation(game: &Game, from: usize, to: usize,
-                                render_location: impl FnOnce(usize) -> (f32, f32) + Copy,
-                                block_location: impl FnOnce(usize) -> (usize, usize)) -> Option<Animation> {
-    let Some(piece) = game.board[from] else { return None; };
-
-    let (ex, ey) = render_location(to);
-    let (ux, uy) = block_location(to);
-
-    Some(Animation {
-        animation_type: AnimationType::Move(ex, ey, ux, uy),
-        piece,
-        position: render_location(from),
-        remaining_time: ANIMATION_TIME,
-        total_time: ANIMATION_TIME,
-    })
-}
-
-fn secondary_animation(game: &Game, from: usize, to: usize,
-                                  render_location: impl FnOnce(usize) -> (f32, f32) + Copy,
-                                  block_location: impl FnOnce(usize) -> (usize, usize)) -> Option<Animation> {
-    let Some(piece) = game.board[from] else { return None; };
-
-    // check if move is en_passant
-    if let Some(en_passant) = game.en_passant {
-        if en_passant.location() == to && (piece == Piece::BPawn || piece == Piece::WPawn) {
-            let Some(lost) = game.board[en_passant.pawn_lost_pos()] else { return None; };
-
-            return Some(Animation {
-                animation_type: AnimationType::Disappear,
-                piece: lost,
-                position: render_location(en_passant.pawn_lost_pos()),
-                remaining_time: ANIMATION_TIME,
-                total_time: ANIMATION_TIME,
-            })
-        }
-    }
-
-    if (piece == Piece::BKing || piece == Piece::WKing) && (to % 8).abs_diff(from % 8) == 2 {
-        let (rook_from, rook_to) = if to % 8 > from % 8 {
-            (from + 3, to - 1)
-        } else {
-            (from - 4, to + 1)
-        };
-
-        let (ex, ey) = render_location(rook_to);
-        let (ux, uy) = block_location(rook_to);
-
-        let Some(rook) = game.board[rook_from] else { return None; };
-
-        return Some(Animation {
-            animation_type: AnimationType::Move(ex, ey, ux, uy),
-            piece: rook,
-            position: render_location(rook_from),
-            remaining_time: ANIMATION_TIME,
-            total_time: ANIMATION_TIME,
-        })
+// the locale key of the explanation for a refused move
+fn illegal_key(reason: IllegalReason) -> &'static str {
+    match reason {
+        IllegalReason::OffBoard => { "illegal.off_board" }
+        IllegalReason::NoPiece => { "illegal.no_piece" }
+        IllegalReason::OpponentsPiece => { "illegal.opponents_piece" }
+        IllegalReason::OwnPiece => { "illegal.own_piece" }
+        IllegalReason::KingCapture => { "illegal.king_capture" }
+        IllegalReason::WrongPattern => { "illegal.wrong_pattern" }
+        IllegalReason::PathBlocked => { "illegal.path_blocked" }
+        IllegalReason::CastlingRightsLost => { "illegal.castling_rights" }
+        IllegalReason::CastleOutOfCheck => { "illegal.castle_out_of_check" }
+        IllegalReason::CastleThroughCheck => { "illegal.castle_through_check" }
+        IllegalReason::Pinned => { "illegal.pinned" }
+        IllegalReason::StillInCheck => { "illegal.still_in_check" }
+        IllegalReason::KingInCheck => { "illegal.king_in_check" }
+        IllegalReason::MissingPromotion => { "illegal.missing_promotion" }
+        IllegalReason::GivesCheck => { "illegal.gives_check" }
+        IllegalReason::GameOver => { "illegal.game_over" }
     }
-    
-    if let Some(taken) = game.board[to] {
-        return Some(Animation {
-            animation_type: AnimationType::Disappear,
-            piece: taken,
-            position: render_location(to),
-            remaining_time: ANIMATION_TIME,
-            total_time: ANIMATION_TIME,
-        })
-    }
-    
-    None
 }
 
-fn check_animation(color: chess::Color, center: (f32, f32), radius: f32) -> Animation {
-    Animation {
-        animation_type: AnimationType::Check(radius),
-        piece: match color {
-            chess::Color::White => { Piece::WKing }
-            chess::Color::Black => { Piece::BKing }
-        },
-        position: center,
-        remaining_time: ANIMATION_TIME * 5.0,
-        total_time: ANIMATION_TIME * 5.0,
-    }
-}
+fn get_sound(game: &Game, from: usize, to: usize, sounds: Sounds) -> Sound {
+    let Some(piece) = game.board[from] else { return sounds.default; };
+    let pawn = piece == Piece::BPawn || piece == Piece::WPawn;
 
-fn get_sound(game: &Game, from: usize, to: usize, sounds: [Sound; 3]) -> Sound {
-    let Some(piece) = game.board[from] else { return sounds[0]; };
+    if pawn && !(8..56).contains(&to) {
+        return sounds.promotion;
+    }
 
     // check if move is en_passant
     if let Some(en_passant) = game.en_passant {
-        if en_passant.location() == to && (piece == Piece::BPawn || piece == Piece::WPawn) {
-            return sounds[1];
+        if en_passant.index() == to && pawn {
+            return sounds.capture;
         }
     }
 
-    if (piece == Piece::BKing || piece == Piece::WKing) && (to % 8).abs_diff(from % 8) == 2 {
-        return sounds[2];
+    if (piece == Piece::BKing || piece == Piece::WKing) && Square::new(to).file().distance(Square::new(from).file()) == 2 {
+        return sounds.castle;
     }
 
     if let Some(taken) = game.board[to] {
-        return sounds[1];
+        return sounds.capture;
     }
 
-    sounds[0]
+    // the same for both colours, pawn to king
+    sounds.pieces[piece as usize % 6]
 }
\ No newline at end of file
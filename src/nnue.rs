@@ -0,0 +1,141 @@
+//! NNUE evaluation for the built-in engine: a small network whose first layer is kept up to date move by
+//! move instead of being worked out again for every position.
+//!
+//! The network is 768 inputs (a piece of either side on one of the squares, seen from each side) to a
+//! hidden layer of any size, twice over for the two sides, to one output. A net file holds little endian
+//! `i16`s, padded with zeros to a multiple of 64 bytes: the input weights (768 rows of the hidden size),
+//! the hidden biases, the output weights (the side to move's half first) and the output bias. Hidden
+//! values are clipped to 0..=255 and the output is scaled by 400 / (255 * 64), the usual quantisation of
+//! nets trained with bullet.
+
+use std::path::Path;
+use crate::chess::{Color, Game, Piece};
+use crate::error::NetworkError;
+use crate::square::Square;
+
+const INPUTS: usize = 768;
+// hidden values are quantised to 0..=QA
+const QA: i32 = 255;
+const QB: i32 = 64;
+const SCALE: i32 = 400;
+
+/// A loaded network, see the module docs for the file format.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Network {
+    hidden: usize,
+    // INPUTS rows of `hidden`
+    input_weights: Vec<i16>,
+    hidden_bias: Vec<i16>,
+    // the side to move's `hidden` first, then the other side's
+    output_weights: Vec<i16>,
+    output_bias: i16,
+}
+
+/// The hidden layer before clipping, from both sides' point of view.
+#[derive(PartialEq, Eq, Debug)]
+pub struct Accumulator {
+    white: Vec<i16>,
+    black: Vec<i16>,
+}
+
+impl Clone for Accumulator {
+    fn clone(&self) -> Self {
+        Accumulator { white: self.white.clone(), black: self.black.clone() }
+    }
+
+    // keeps the buffers, the engine does this for every move it makes
+    fn clone_from(&mut self, source: &Self) {
+        self.white.clone_from(&source.white);
+        self.black.clone_from(&source.black);
+    }
+}
+
+impl Network {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, NetworkError> {
+        Network::from_bytes(&std::fs::read(path).map_err(NetworkError::Io)?)
+    }
+
+    /// Reads a net file, the hidden layer size is whatever fits its length.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, NetworkError> {
+        // each hidden value has its input weights, a bias and two output weights, then there's one output bias
+        let hidden = (bytes.len() / 2).saturating_sub(1) / (INPUTS + 3);
+        let used = (hidden * (INPUTS + 3) + 1) * 2;
+        if hidden == 0 || bytes.len() != used.div_ceil(64) * 64 { return Err(NetworkError::Size(bytes.len())); }
+
+        let mut values = bytes.chunks_exact(2).map(|x| i16::from_le_bytes([x[0], x[1]]));
+        let mut take = |count: usize| values.by_ref().take(count).collect::<Vec<i16>>();
+
+        Ok(Network {
+            hidden,
+            input_weights: take(INPUTS * hidden),
+            hidden_bias: take(hidden),
+            output_weights: take(2 * hidden),
+            output_bias: take(1)[0],
+        })
+    }
+
+    pub fn hidden_size(&self) -> usize {
+        self.hidden
+    }
+
+    /// The accumulator of `game`, from scratch.
+    pub fn accumulator(&self, game: &Game) -> Accumulator {
+        let mut accumulator = Accumulator { white: self.hidden_bias.clone(), black: self.hidden_bias.clone() };
+        for square in Square::all() {
+            if let Some(piece) = game.board[square] { self.toggle(&mut accumulator, piece, square, 1); }
+        }
+
+        accumulator
+    }
+
+    /// Turns the accumulator of `before` into that of `after`, a position a move (or a null move) later.
+    /// Only the squares the move changed are looked at again.
+    pub fn update(&self, accumulator: &mut Accumulator, before: &Game, after: &Game) {
+        for square in Square::all() {
+            let (old, new) = (before.board[square], after.board[square]);
+            if old == new { continue; }
+
+            if let Some(piece) = old { self.toggle(accumulator, piece, square, -1); }
+            if let Some(piece) = new { self.toggle(accumulator, piece, square, 1); }
+        }
+    }
+
+    /// The evaluation in centipawns of the position `accumulator` is for, with `turn` to move.
+    pub fn output(&self, accumulator: &Accumulator, turn: Color) -> i32 {
+        let (us, them) = match turn {
+            Color::White => { (&accumulator.white, &accumulator.black) }
+            Color::Black => { (&accumulator.black, &accumulator.white) }
+        };
+
+        let sum: i32 = us.iter().chain(them).zip(&self.output_weights)
+            .map(|(value, weight)| i32::from(*value).clamp(0, QA) * i32::from(*weight))
+            .sum();
+
+        // the output bias is quantised by QA * QB like the sum
+        (sum + i32::from(self.output_bias)) * SCALE / (QA * QB)
+    }
+
+    /// The evaluation of `game` in centipawns from the side to move's point of view, without an accumulator
+    /// to start from.
+    pub fn evaluate(&self, game: &Game) -> i32 {
+        self.output(&self.accumulator(game), game.turn)
+    }
+
+    // adds (or takes away, with -1) `piece` on `square` for both sides
+    fn toggle(&self, accumulator: &mut Accumulator, piece: Piece, square: Square, sign: i16) {
+        for (values, view) in [(&mut accumulator.white, Color::White), (&mut accumulator.black, Color::Black)] {
+            let row = feature(piece, square, view) * self.hidden;
+            for (value, weight) in values.iter_mut().zip(&self.input_weights[row..row + self.hidden]) {
+                *value = value.wrapping_add(sign.wrapping_mul(*weight));
+            }
+        }
+    }
+}
+
+// the input for `piece` on `square` seen by `view`: its own pieces first, and its side of the board at the bottom
+fn feature(piece: Piece, square: Square, view: Color) -> usize {
+    let theirs = if piece.color() == view { 0 } else { 384 };
+    let square = if view == Color::White { square.index() } else { square.index() ^ 56 };
+
+    theirs + 64 * (piece as usize % 6) + square
+}
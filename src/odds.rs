@@ -0,0 +1,57 @@
+use std::fmt::{Display, Formatter};
+use crate::chess::{Color, Game};
+use crate::square::{File, Rank, Square};
+
+/// A handicap the stronger side gives at the start of the game.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Odds {
+    #[default]
+    None,
+    Knight,
+    Rook,
+    Queen,
+    // the other side moves first
+    Move,
+}
+
+impl Odds {
+    pub const ALL: [Odds; 5] = [Odds::None, Odds::Knight, Odds::Rook, Odds::Queen, Odds::Move];
+    pub const NAMES: [&'static str; 5] = ["None", "Knight", "Rook", "Queen", "Move"];
+
+    /// The starting position with `giver`'s handicap taken off. Knight and rook odds take the queen's side
+    /// piece, and the rook takes its castling right with it.
+    pub fn start(self, giver: Color) -> Game {
+        let mut game = Game::default();
+        let back_rank = if giver == Color::White { Rank::FIRST } else { Rank::EIGHTH };
+        let square = |file: char| Square::at(File::from_char(file).expect("a file letter"), back_rank);
+
+        match self {
+            Odds::None => {}
+            Odds::Knight => { game.remove_piece(square('b')); }
+            Odds::Rook => { game.remove_piece(square('a')); }
+            Odds::Queen => { game.remove_piece(square('d')); }
+            Odds::Move => { game.turn = !giver; }
+        }
+
+        game
+    }
+
+    /// The value of a pgn `Handicap` tag for `giver` giving these odds, None without odds.
+    pub fn tag(self, giver: Color) -> Option<String> {
+        let side = if giver == Color::White { "White" } else { "Black" };
+        (self != Odds::None).then(|| format!("{} gives {}", side, self))
+    }
+}
+
+impl Display for Odds {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Odds::None => { write!(f, "no odds") }
+            Odds::Knight => { write!(f, "knight odds") }
+            Odds::Rook => { write!(f, "rook odds") }
+            Odds::Queen => { write!(f, "queen odds") }
+            Odds::Move => { write!(f, "the move") }
+        }
+    }
+}
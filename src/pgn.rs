@@ -0,0 +1,610 @@
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+use crate::chess::{Color, Game, Move, MoveResult, Piece, Promotion};
+use crate::error::{PgnError, PgnErrorReason};
+use crate::history::History;
+use crate::square::Square;
+
+/// A game in Portable Game Notation: its tag pairs, moves and their annotations.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Pgn {
+    // kept in insertion order, the seven tag roster comes first
+    pub tags: Vec<(String, String)>,
+    pub history: History,
+    // comment before the first move
+    pub comment: Option<String>,
+    // annotations of each move of the history, moves past the end have none
+    pub notes: Vec<MoveNotes>,
+}
+
+/// The annotations of a move: a comment, glyphs like `!?`, and lines that could have been played instead.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct MoveNotes {
+    pub comment: Option<String>,
+    // numeric annotation glyphs, $1 is `!`, $2 `?`, $3 `!!`, $4 `??`, $5 `!?` and $6 `?!`
+    pub nags: Vec<u8>,
+    pub variations: Vec<Variation>,
+}
+
+impl MoveNotes {
+    pub fn is_empty(&self) -> bool {
+        self.comment.is_none() && self.nags.is_empty() && self.variations.is_empty()
+    }
+}
+
+/// A line played instead of the move it belongs to, starting from the same position.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Variation {
+    pub moves: Vec<VariationMove>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct VariationMove {
+    pub mv: Move,
+    pub notes: MoveNotes,
+}
+
+impl Pgn {
+    /// Creates a pgn with the seven tag roster filled with unknown values.
+    pub fn new(history: History) -> Self {
+        let mut pgn = Pgn { tags: Vec::new(), history, comment: None, notes: Vec::new() };
+
+        for tag in ["Event", "Site", "Date", "Round", "White", "Black"] {
+            pgn.set_tag(tag, "?");
+        }
+        pgn.set_tag("Result", "*");
+
+        if pgn.history.start() != Game::default() {
+            let fen = pgn.history.start().as_fen();
+
+            pgn.set_tag("SetUp", "1");
+            pgn.set_tag("FEN", fen);
+        }
+
+        pgn
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter().find(|x| x.0 == name).map(|x| x.1.as_str())
+    }
+
+    /// Sets a tag, replacing the old value if the tag exists.
+    pub fn set_tag(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        let (name, value) = (name.into(), value.into());
+
+        if let Some(tag) = self.tags.iter_mut().find(|x| x.0 == name) {
+            tag.1 = value;
+        } else {
+            self.tags.push((name, value));
+        }
+    }
+
+    /// The annotations of the move at `ply`, if it has any.
+    pub fn notes(&self, ply: usize) -> Option<&MoveNotes> {
+        self.notes.get(ply).filter(|x| !x.is_empty())
+    }
+
+    /// The annotations of the move at `ply`, to edit them.
+    pub fn notes_mut(&mut self, ply: usize) -> &mut MoveNotes {
+        if self.notes.len() <= ply {
+            self.notes.resize(ply + 1, MoveNotes::default());
+        }
+
+        &mut self.notes[ply]
+    }
+
+    // drops empty notes at the end, so equal games compare equal however they were annotated
+    pub(crate) fn trim_notes(&mut self) {
+        self.notes.truncate(self.history.len());
+        while self.notes.last().is_some_and(MoveNotes::is_empty) {
+            self.notes.pop();
+        }
+    }
+
+    /// Parses a single game, see [`Pgn::parse_many`] for files with several games.
+    pub fn parse(pgn: &str) -> Result<Pgn, PgnError> {
+        let mut games = Pgn::parse_many(pgn)?;
+
+        if games.is_empty() {
+            return Err(PgnError::new(1, PgnErrorReason::Empty));
+        }
+
+        Ok(games.swap_remove(0))
+    }
+
+    /// Parses every game in `text`.
+    pub fn parse_many(text: &str) -> Result<Vec<Pgn>, PgnError> {
+        Pgn::parse_each(text).into_iter().collect()
+    }
+
+    /// Parses every game in `text` on its own, so one broken game doesn't hide the others.
+    pub fn parse_each(text: &str) -> Vec<Result<Pgn, PgnError>> {
+        let mut games = Vec::new();
+        // the first problem of the game being read
+        let mut error = None;
+
+        let mut tags = Vec::new();
+        // (line, token), comments are one token starting with `{`
+        let mut tokens = Vec::new();
+
+        // comments and variations may span several lines
+        let mut in_comment = false;
+        let mut variation_depth = 0;
+        let (mut comment, mut comment_line) = (String::new(), 0);
+
+        for (line_no, line) in text.lines().enumerate() {
+            let line_no = line_no + 1;
+            let trimmed = line.trim();
+
+            if !in_comment && variation_depth == 0 && trimmed.starts_with('[') {
+                // a tag after movetext starts the next game
+                if !tokens.is_empty() {
+                    games.push(Pgn::finish(std::mem::take(&mut tags), std::mem::take(&mut tokens), error.take()));
+                }
+
+                match parse_tag(trimmed) {
+                    Some(tag) => { tags.push(tag); }
+                    None => { error = error.or(Some(PgnError::new(line_no, PgnErrorReason::InvalidTag))); }
+                }
+                continue;
+            }
+
+            let mut token = String::new();
+            for c in line.chars() {
+                if in_comment {
+                    if c == '}' {
+                        in_comment = false;
+                        tokens.push((comment_line, std::mem::take(&mut comment)));
+                    } else {
+                        comment.push(c);
+                    }
+                    continue;
+                }
+
+                match c {
+                    '{' => {
+                        in_comment = true;
+                        (comment, comment_line) = ("{".to_string(), line_no);
+                    }
+                    '(' | ')' => {
+                        if !token.is_empty() { tokens.push((line_no, std::mem::take(&mut token))); }
+                        variation_depth += if c == '(' { 1 } else { -1 };
+                        tokens.push((line_no, c.to_string()));
+                        continue;
+                    }
+                    // rest of line comment
+                    ';' => { break; }
+                    c if c.is_whitespace() => {}
+                    c => {
+                        token.push(c);
+                        continue;
+                    }
+                }
+
+                if !token.is_empty() { tokens.push((line_no, std::mem::take(&mut token))); }
+            }
+
+            if !token.is_empty() { tokens.push((line_no, token)); }
+            if in_comment { comment.push(' '); }
+        }
+
+        if !tags.is_empty() || !tokens.is_empty() || error.is_some() {
+            games.push(Pgn::finish(tags, tokens, error));
+        }
+
+        games
+    }
+
+    fn finish(tags: Vec<(String, String)>, tokens: Vec<(usize, String)>, error: Option<PgnError>) -> Result<Pgn, PgnError> {
+        match error {
+            Some(error) => { Err(error) }
+            None => { Pgn::from_parts(tags, tokens) }
+        }
+    }
+
+    fn from_parts(mut tags: Vec<(String, String)>, tokens: Vec<(usize, String)>) -> Result<Pgn, PgnError> {
+        let start = match tags.iter().find(|x| x.0 == "FEN") {
+            Some((_, fen)) => {
+                let line = tokens.first().map(|x| x.0).unwrap_or(1);
+                Game::from_fen(fen).map_err(|x| PgnError::new(line, PgnErrorReason::Fen(x)))?
+            }
+            None => { Game::default() }
+        };
+
+        let mut position = 0;
+        let line = parse_line(&tokens, &mut position, start)?;
+
+        if let Some(result) = line.result.filter(|_| !tags.iter().any(|x| x.0 == "Result")) {
+            tags.push(("Result".to_string(), result));
+        }
+
+        let mut history = History::new(start);
+        let mut notes = Vec::new();
+        for (ply, parsed) in line.moves.into_iter().enumerate() {
+            history.push(parsed.mv).map_err(|_| PgnError::new(parsed.line, PgnErrorReason::UnknownMove(san(&history.current(), parsed.mv))))?;
+            history.set_times(ply, parsed.time, parsed.clock);
+            notes.push(parsed.notes);
+        }
+
+        let mut pgn = Pgn { tags, history, comment: line.comment, notes };
+        pgn.trim_notes();
+
+        Ok(pgn)
+    }
+}
+
+// a sequence of moves, the main line or a variation
+#[derive(Default)]
+struct ParsedLine {
+    moves: Vec<ParsedMove>,
+    comment: Option<String>,
+    result: Option<String>,
+}
+
+struct ParsedMove {
+    line: usize,
+    mv: Move,
+    notes: MoveNotes,
+    time: Option<Duration>,
+    clock: Option<Duration>,
+}
+
+impl From<ParsedLine> for Variation {
+    fn from(value: ParsedLine) -> Self {
+        Variation { moves: value.moves.into_iter().map(|x| VariationMove { mv: x.mv, notes: x.notes }).collect() }
+    }
+}
+
+// reads moves from `tokens[*position]` on, until the end or the `)` closing a variation
+fn parse_line(tokens: &[(usize, String)], position: &mut usize, start: Game) -> Result<ParsedLine, PgnError> {
+    let nested = *position > 0 && tokens[*position - 1].1 == "(";
+    let mut line = ParsedLine::default();
+    // variations start from the position before the move they replace
+    let (mut before, mut game) = (start, start);
+
+    while let Some((line_no, token)) = tokens.get(*position) {
+        *position += 1;
+
+        if let Some(comment) = token.strip_prefix('{') {
+            let (time, clock) = (clock_command(comment, "%emt"), clock_command(comment, "%clk"));
+            let text = strip_commands(comment);
+
+            match line.moves.last_mut() {
+                Some(last) => {
+                    (last.time, last.clock) = (time.or(last.time), clock.or(last.clock));
+                    append_comment(&mut last.notes.comment, &text);
+                }
+                None => { append_comment(&mut line.comment, &text); }
+            }
+            continue;
+        }
+
+        match token.as_str() {
+            "(" => {
+                let variation = parse_line(tokens, position, before)?;
+                if let Some(last) = line.moves.last_mut() {
+                    last.notes.variations.push(variation.into());
+                }
+                continue;
+            }
+            ")" if nested => { break; }
+            ")" => { continue; }
+            "1-0" | "0-1" | "1/2-1/2" | "*" => {
+                line.result = Some(token.clone());
+                if nested { continue; } else { break; }
+            }
+            _ => {}
+        }
+
+        if let Some(nag) = token.strip_prefix('$') {
+            if let (Ok(nag), Some(last)) = (nag.parse(), line.moves.last_mut()) { last.notes.nags.push(nag); }
+            continue;
+        }
+
+        // move numbers can be attached to the move, as in 1.e4, and glyphs follow it, as in e4!?
+        let san = token.rfind('.').map(|x| &token[x + 1..]).unwrap_or(token);
+        let glyph = san.trim_start_matches(|c: char| c != '!' && c != '?');
+        let san = &san[..san.len() - glyph.len()];
+
+        if !san.is_empty() {
+            let unknown = || PgnError::new(*line_no, PgnErrorReason::UnknownMove(token.clone()));
+            let mv = parse_san(&game, san).ok_or_else(unknown)?;
+
+            before = game;
+            game.make_move(mv).map_err(|_| unknown())?;
+            line.moves.push(ParsedMove { line: *line_no, mv, notes: MoveNotes::default(), time: None, clock: None });
+        }
+
+        if let (Some(nag), Some(last)) = (glyph_nag(glyph), line.moves.last_mut()) {
+            last.notes.nags.push(nag);
+        }
+    }
+
+    Ok(line)
+}
+
+fn glyph_nag(glyph: &str) -> Option<u8> {
+    match glyph {
+        "!" => { Some(1) }
+        "?" => { Some(2) }
+        "!!" => { Some(3) }
+        "??" => { Some(4) }
+        "!?" => { Some(5) }
+        "?!" => { Some(6) }
+        _ => { None }
+    }
+}
+
+// the comment without its `[%command ...]`s, which are kept elsewhere
+fn strip_commands(comment: &str) -> String {
+    let mut text = comment.to_string();
+
+    while let Some(start) = text.find("[%") {
+        let Some(end) = text[start..].find(']') else { break; };
+        text.replace_range(start..start + end + 1, "");
+    }
+
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn append_comment(comment: &mut Option<String>, text: &str) {
+    if text.is_empty() { return; }
+
+    match comment {
+        Some(comment) => {
+            comment.push(' ');
+            comment.push_str(text);
+        }
+        None => { *comment = Some(text.to_string()); }
+    }
+}
+
+impl Display for Pgn {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (name, value) in &self.tags {
+            writeln!(f, "[{} \"{}\"]", name, value.replace('\\', "\\\\").replace('"', "\\\""))?;
+        }
+        writeln!(f)?;
+
+        let mut words = Vec::new();
+        if let Some(comment) = &self.comment {
+            push_comment(&mut words, comment);
+        }
+
+        let empty = MoveNotes::default();
+        let moves: Vec<LineMove> = self.history.entries().iter().enumerate()
+            .map(|(i, entry)| (entry.mv, self.notes.get(i).unwrap_or(&empty), entry.time, entry.clock))
+            .collect();
+        line_words(&mut words, self.history.start(), &moves);
+
+        words.push(self.tag("Result").unwrap_or("*").to_string());
+
+        // movetext lines are kept under 80 characters
+        let mut line_len = 0;
+        for word in words {
+            if line_len != 0 && line_len + word.len() + 1 > 79 {
+                writeln!(f)?;
+                line_len = 0;
+            }
+
+            if line_len != 0 {
+                write!(f, " ")?;
+                line_len += 1;
+            }
+
+            write!(f, "{}", word)?;
+            line_len += word.len();
+        }
+
+        writeln!(f)
+    }
+}
+
+// (move, notes, think time, clock)
+type LineMove<'a> = (Move, &'a MoveNotes, Option<Duration>, Option<Duration>);
+
+// the movetext of a line starting at `game`, with its variations in parentheses
+fn line_words(words: &mut Vec<String>, mut game: Game, moves: &[LineMove]) {
+    // black's moves get a number after anything that interrupts the line
+    let mut numbered = true;
+
+    for &(mv, notes, time, clock) in moves {
+        if game.turn == Color::White {
+            words.push(format!("{}.", game.fm_clock()));
+        } else if numbered {
+            words.push(format!("{}...", game.fm_clock()));
+        }
+
+        words.push(san(&game, mv));
+        words.extend(notes.nags.iter().map(|x| format!("${}", x)));
+        numbered = false;
+
+        let mut comment = Vec::new();
+        if let Some(text) = &notes.comment { comment.push(text.clone()); }
+        if let Some(clock) = clock { comment.push(format!("[%clk {}]", format_clock(clock))); }
+        if let Some(time) = time { comment.push(format!("[%emt {}]", format_clock(time))); }
+
+        if !comment.is_empty() {
+            push_comment(words, &comment.join(" "));
+            numbered = true;
+        }
+
+        let before = game;
+        game.move_checked(mv.from.index(), mv.to.index(), mv.promotion);
+
+        for variation in notes.variations.iter().filter(|x| !x.moves.is_empty()) {
+            let moves: Vec<LineMove> = variation.moves.iter().map(|x| (x.mv, &x.notes, None, None)).collect();
+            let first = words.len();
+
+            line_words(words, before, &moves);
+            words[first].insert(0, '(');
+            if let Some(last) = words.last_mut() { last.push(')'); }
+            numbered = true;
+        }
+    }
+}
+
+// split like any other words so long lines still wrap
+fn push_comment(words: &mut Vec<String>, text: &str) {
+    let comment = format!("{{{}}}", text.replace('}', ""));
+    words.extend(comment.split(' ').map(String::from));
+}
+
+/// A duration as `h:mm:ss`, with tenths of a second when there are any, as in `%clk` and `%emt` commands.
+pub fn format_clock(time: Duration) -> String {
+    let tenths = time.as_millis() / 100;
+    let (hours, minutes, seconds) = (tenths / 36_000, tenths / 600 % 60, tenths / 10 % 60);
+
+    match tenths % 10 {
+        0 => { format!("{}:{:02}:{:02}", hours, minutes, seconds) }
+        tenth => { format!("{}:{:02}:{:02}.{}", hours, minutes, seconds, tenth) }
+    }
+}
+
+/// Reads an `h:mm:ss` duration, the hours and a fraction of a second are optional.
+pub fn parse_clock(text: &str) -> Option<Duration> {
+    let mut seconds = 0.0;
+
+    for field in text.split(':') {
+        seconds = seconds * 60.0 + field.parse::<f64>().ok().filter(|x| *x >= 0.0)?;
+    }
+
+    Some(Duration::from_secs_f64(seconds))
+}
+
+// the duration after `[%name ...]` in a comment
+fn clock_command(comment: &str, name: &str) -> Option<Duration> {
+    let start = comment.find(&format!("[{} ", name))? + name.len() + 2;
+    let end = comment[start..].find(']')? + start;
+
+    parse_clock(comment[start..end].trim())
+}
+
+pub(crate) fn parse_tag(line: &str) -> Option<(String, String)> {
+    let inner = line.strip_prefix('[')?.strip_suffix(']')?;
+    let (name, value) = inner.split_once(' ')?;
+
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+    Some((name.to_string(), value.replace("\\\"", "\"").replace("\\\\", "\\")))
+}
+
+fn piece_letter(piece: Piece) -> Option<char> {
+    match piece {
+        Piece::WPawn | Piece::BPawn => { None }
+        Piece::WKnight | Piece::BKnight => { Some('N') }
+        Piece::WBishop | Piece::BBishop => { Some('B') }
+        Piece::WRook | Piece::BRook => { Some('R') }
+        Piece::WQueen | Piece::BQueen => { Some('Q') }
+        Piece::WKing | Piece::BKing => { Some('K') }
+    }
+}
+
+fn promotion_letter(promotion: Promotion) -> char {
+    match promotion {
+        Promotion::Knight => { 'N' }
+        Promotion::Bishop => { 'B' }
+        Promotion::Rook => { 'R' }
+        Promotion::Queen => { 'Q' }
+    }
+}
+
+/// Standard algebraic notation for `mv`, which must be legal in `game`.
+pub fn san(game: &Game, mv: Move) -> String {
+    let (from, to) = (mv.from, mv.to);
+    let Some(piece) = game.board[from] else { return String::new(); };
+    let pawn = piece == Piece::WPawn || piece == Piece::BPawn;
+    let king = piece == Piece::WKing || piece == Piece::BKing;
+
+    let mut san = String::new();
+
+    if king && to.file().distance(from.file()) == 2 {
+        san.push_str(if to.file() > from.file() { "O-O" } else { "O-O-O" });
+    } else {
+        let en_passant = pawn && game.en_passant == Some(to);
+        let capture = game.board[to].is_some() || en_passant;
+
+        if let Some(letter) = piece_letter(piece) {
+            san.push(letter);
+
+            // other pieces of the same type that could also move here
+            let others: Vec<Square> = Square::all()
+                .filter(|x| *x != from && game.board[*x] == Some(piece))
+                .filter(|x| game.legal_moves_from(*x).any(|x| x.to == to))
+                .collect();
+
+            if !others.is_empty() {
+                let file = others.iter().any(|x| x.file() == from.file());
+                let rank = others.iter().any(|x| x.rank() == from.rank());
+
+                if !file {
+                    san.push(from.file().to_char());
+                } else if !rank {
+                    san.push(from.rank().to_char());
+                } else {
+                    san.push_str(&from.to_string());
+                }
+            }
+        } else if capture {
+            san.push(from.file().to_char());
+        }
+
+        if capture { san.push('x'); }
+        san.push_str(&to.to_string());
+
+        if let Some(promotion) = mv.promotion.filter(|_| pawn) {
+            san.push('=');
+            san.push(promotion_letter(promotion));
+        }
+    }
+
+    match game.is_legal_move(from.index(), to.index(), mv.promotion) {
+        MoveResult::Checkmate => { san.push('#'); }
+        MoveResult::Check => { san.push('+'); }
+        _ => {}
+    }
+
+    san
+}
+
+/// `moves`, played in order from `game`, in standard algebraic notation with move numbers: `1. e4 e5 2. Nf3`,
+/// or `1... e5 2. Nf3` from a position with black to move. Stops before the first illegal move.
+pub fn san_line(game: &Game, moves: &[Move]) -> String {
+    let mut position = *game;
+    let mut words = Vec::new();
+
+    for mv in moves {
+        let (before, text) = (position, san(&position, *mv));
+        if position.make_move(*mv).is_err() { break; }
+
+        match before.turn {
+            Color::White => { words.push(format!("{}.", before.fm_clock())); }
+            Color::Black if words.is_empty() => { words.push(format!("{}...", before.fm_clock())); }
+            Color::Black => {}
+        }
+        words.push(text);
+    }
+
+    words.join(" ")
+}
+
+/// Finds the legal move written as `san` in `game`, annotations like + or ! are ignored.
+pub fn parse_san(game: &Game, text: &str) -> Option<Move> {
+    let normalize = |x: &str| -> String {
+        x.trim_end_matches(['+', '#', '!', '?']).replace('0', "O").replace('=', "")
+    };
+
+    let wanted = normalize(text);
+    let moves = game.generate_legal_moves();
+
+    let castle = wanted.starts_with("O-O");
+    let to = if castle { None } else {
+        // destination is the last square mentioned, before a promotion letter
+        let end = wanted.rfind(|x: char| ('1'..='8').contains(&x))?;
+        Some(wanted.get(end - 1..=end)?.parse::<Square>().ok()?)
+    };
+
+    moves.into_iter()
+        .filter(|x| to.is_none_or(|to| x.to == to))
+        .find(|x| normalize(&san(game, *x)) == wanted)
+}
+
@@ -0,0 +1,67 @@
+//! The few things that differ in the browser (wasm32): there are no threads, processes or system clock.
+
+use std::time::Duration;
+
+/// Runs `task` on a new thread, or right away in the browser.
+/// Engine work fails as soon as it tries to start a process there, so nothing blocks for long.
+pub fn spawn(task: impl FnOnce() + Send + 'static) {
+    #[cfg(not(target_arch = "wasm32"))]
+    std::thread::spawn(task);
+
+    #[cfg(target_arch = "wasm32")]
+    task();
+}
+
+/// Time since the unix epoch, None where there is no clock to ask.
+pub fn since_epoch() -> Option<Duration> {
+    #[cfg(not(target_arch = "wasm32"))]
+    return std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).ok();
+
+    #[cfg(target_arch = "wasm32")]
+    None
+}
+
+/// Shows a desktop notification: notify-send on linux, osascript on macos and a balloon tip from
+/// powershell on windows. Fails where none of them can be run, like in the browser.
+pub fn notify(title: &str, body: &str) -> std::io::Result<()> {
+    #[cfg(all(unix, not(target_os = "macos")))]
+    let mut command = {
+        let mut command = std::process::Command::new("notify-send");
+        command.args([title, body]);
+        command
+    };
+
+    #[cfg(target_os = "macos")]
+    let mut command = {
+        let mut command = std::process::Command::new("osascript");
+        let quote = |x: &str| x.replace('\\', "\\\\").replace('"', "\\\"");
+        command.args(["-e", &format!("display notification \"{}\" with title \"{}\"", quote(body), quote(title))]);
+        command
+    };
+
+    #[cfg(windows)]
+    let mut command = {
+        use std::os::windows::process::CommandExt;
+
+        let quote = |x: &str| x.replace('\'', "''");
+        let script = format!("Add-Type -AssemblyName System.Windows.Forms; $n = New-Object System.Windows.Forms.NotifyIcon; \
+                              $n.Icon = [System.Drawing.SystemIcons]::Information; $n.Visible = $true; \
+                              $n.ShowBalloonTip(10000, '{}', '{}', 'Info'); Start-Sleep -Seconds 10; $n.Dispose()", quote(title), quote(body));
+        let mut command = std::process::Command::new("powershell");
+        command.args(["-NoProfile", "-Command", &script]).creation_flags(0x08000000);
+        command
+    };
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        // not waited for, the windows one stays up for a while
+        command.stdin(std::process::Stdio::null()).stdout(std::process::Stdio::null()).stderr(std::process::Stdio::null()).spawn()?;
+        Ok(())
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        let _ = (title, body);
+        Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "no notifications in the browser"))
+    }
+}
@@ -0,0 +1,123 @@
+//! Finding a position in the archive: which games reached it, and what was played from it.
+//!
+//! A [`PositionIndex`] keys every position of every archived game by its Zobrist hash, so transpositions
+//! are found too. [`PositionIndex::search`] gathers the games that reached a position and the moves
+//! played next, with how the games went after each.
+
+use std::collections::HashMap;
+use crate::archive::{Archive, ArchivedGame};
+use crate::chess::{Color, Game, Move};
+
+/// Where a position came up: the game, as an index into [`PositionIndex::games`], and the number of
+/// half moves played before it.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Occurrence {
+    pub game: usize,
+    pub ply: usize,
+    // the move played from the position, None if the game ended there
+    pub next: Option<Move>,
+}
+
+/// How the games went after one move from the searched position.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Continuation {
+    pub mv: Move,
+    pub games: usize,
+    pub white_wins: usize,
+    pub draws: usize,
+    pub black_wins: usize,
+}
+
+impl Continuation {
+    /// The points `color` got on average in the finished games, from 0 to 1. None if none are finished.
+    pub fn score(&self, color: Color) -> Option<f64> {
+        let finished = self.white_wins + self.draws + self.black_wins;
+        if finished == 0 { return None; }
+
+        let wins = if color == Color::White { self.white_wins } else { self.black_wins };
+        Some((wins as f64 + self.draws as f64 / 2.0) / finished as f64)
+    }
+}
+
+/// The archived games a position came up in, and the moves played from it, most played first.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct PositionSearch {
+    pub occurrences: Vec<Occurrence>,
+    pub continuations: Vec<Continuation>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct PositionIndex {
+    pub games: Vec<ArchivedGame>,
+    // zobrist hash of the position to where it came up, once per game
+    positions: HashMap<u64, Vec<Occurrence>>,
+}
+
+impl PositionIndex {
+    /// Indexes every position of `games`.
+    pub fn new(games: Vec<ArchivedGame>) -> Self {
+        let mut positions: HashMap<u64, Vec<Occurrence>> = HashMap::new();
+
+        for (i, game) in games.iter().enumerate() {
+            let history = &game.pgn.history;
+            let mut position = history.start();
+
+            for ply in 0..=history.len() {
+                let next = history.entries().get(ply).map(|x| x.mv);
+                let occurrences = positions.entry(position.zobrist()).or_default();
+
+                // a repeated position counts the first time only
+                if occurrences.last().is_none_or(|x| x.game != i) {
+                    occurrences.push(Occurrence { game: i, ply, next });
+                }
+                if let Some(mv) = next { position.move_checked(mv.from.index(), mv.to.index(), mv.promotion); }
+            }
+        }
+
+        PositionIndex { games, positions }
+    }
+
+    /// Indexes the games of `archive`.
+    pub fn from_archive(archive: &Archive) -> std::io::Result<Self> {
+        Ok(PositionIndex::new(archive.games()?))
+    }
+
+    /// The number of the move the game was at when `occurrence` came up, as in the pgn.
+    pub fn move_number(&self, occurrence: &Occurrence) -> usize {
+        let start = self.games[occurrence.game].pgn.history.start();
+        let black_first = if start.turn == Color::Black { 1 } else { 0 };
+
+        start.fm_clock() as usize + (occurrence.ply + black_first) / 2
+    }
+
+    /// The games that reached `position`, in archive order, and what was played from it.
+    pub fn search(&self, position: &Game) -> PositionSearch {
+        let Some(occurrences) = self.positions.get(&position.zobrist()) else { return PositionSearch::default(); };
+        let mut continuations: Vec<Continuation> = Vec::new();
+
+        for occurrence in occurrences {
+            let Some(mv) = occurrence.next else { continue; };
+
+            let i = match continuations.iter().position(|x| x.mv == mv) {
+                Some(i) => { i }
+                None => {
+                    continuations.push(Continuation { mv, games: 0, white_wins: 0, draws: 0, black_wins: 0 });
+                    continuations.len() - 1
+                }
+            };
+
+            let continuation = &mut continuations[i];
+            continuation.games += 1;
+            match self.games[occurrence.game].pgn.tag("Result") {
+                Some("1-0") => { continuation.white_wins += 1; }
+                Some("0-1") => { continuation.black_wins += 1; }
+                Some("1/2-1/2") => { continuation.draws += 1; }
+                _ => {}
+            }
+        }
+
+        // stable, so equally played moves stay in the order they were first seen
+        continuations.sort_by_key(|x| std::cmp::Reverse(x.games));
+        PositionSearch { occurrences: occurrences.clone(), continuations }
+    }
+}
@@ -0,0 +1,115 @@
+use std::f64::consts::PI;
+
+// converts between the glicko and glicko-2 scales
+const SCALE: f64 = 173.7178;
+// how much the volatility can change, smaller values keep it steadier
+const TAU: f64 = 0.5;
+const EPSILON: f64 = 0.000_001;
+
+/// A Glicko-2 rating: the rating itself, how unsure it is, and how erratic the results have been.
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rating {
+    pub rating: f64,
+    pub deviation: f64,
+    pub volatility: f64,
+}
+
+impl Default for Rating {
+    /// A new player, 1500 with a deviation of 350.
+    fn default() -> Self {
+        Rating { rating: 1500.0, deviation: 350.0, volatility: 0.06 }
+    }
+}
+
+impl Rating {
+    /// An opponent whose strength is known well, like an engine set to a fixed elo.
+    pub fn fixed(rating: f64) -> Self {
+        Rating { rating, deviation: 50.0, volatility: 0.06 }
+    }
+
+    /// The expected score against `opponent`, from 0 to 1.
+    pub fn expected_score(&self, opponent: &Rating) -> f64 {
+        let (mu, _) = self.scaled();
+        let (mu_j, phi_j) = opponent.scaled();
+
+        expected(mu, mu_j, phi_j)
+    }
+
+    /// The rating after one rating period with `games`, (opponent, score from 0 to 1) pairs.
+    /// With no games the deviation grows, as the rating gets less certain.
+    pub fn update(&self, games: &[(Rating, f64)]) -> Rating {
+        let (mu, phi) = self.scaled();
+
+        if games.is_empty() {
+            let phi = (phi * phi + self.volatility * self.volatility).sqrt();
+            return Rating { deviation: phi * SCALE, ..*self };
+        }
+
+        let mut v_inverse = 0.0;
+        let mut improvement = 0.0;
+        for (opponent, score) in games {
+            let (mu_j, phi_j) = opponent.scaled();
+            let (g, e) = (g(phi_j), expected(mu, mu_j, phi_j));
+
+            v_inverse += g * g * e * (1.0 - e);
+            improvement += g * (score - e);
+        }
+
+        let v = 1.0 / v_inverse;
+        let delta = v * improvement;
+        let volatility = new_volatility(phi, v, delta, self.volatility);
+
+        let phi_star = (phi * phi + volatility * volatility).sqrt();
+        let phi = 1.0 / (1.0 / (phi_star * phi_star) + 1.0 / v).sqrt();
+        let mu = mu + phi * phi * improvement;
+
+        Rating { rating: mu * SCALE + 1500.0, deviation: phi * SCALE, volatility }
+    }
+
+    // (mu, phi) on the glicko-2 scale
+    fn scaled(&self) -> (f64, f64) {
+        ((self.rating - 1500.0) / SCALE, self.deviation / SCALE)
+    }
+}
+
+fn g(phi: f64) -> f64 {
+    1.0 / (1.0 + 3.0 * phi * phi / (PI * PI)).sqrt()
+}
+
+fn expected(mu: f64, mu_j: f64, phi_j: f64) -> f64 {
+    1.0 / (1.0 + (-g(phi_j) * (mu - mu_j)).exp())
+}
+
+// step 5 of the glicko-2 paper, the Illinois algorithm
+fn new_volatility(phi: f64, v: f64, delta: f64, sigma: f64) -> f64 {
+    let a = (sigma * sigma).ln();
+    let f = |x: f64| {
+        let ex = x.exp();
+        ex * (delta * delta - phi * phi - v - ex) / (2.0 * (phi * phi + v + ex).powi(2)) - (x - a) / (TAU * TAU)
+    };
+
+    let mut big_a = a;
+    let mut big_b = if delta * delta > phi * phi + v {
+        (delta * delta - phi * phi - v).ln()
+    } else {
+        let mut k = 1.0;
+        while f(a - k * TAU) < 0.0 { k += 1.0; }
+        a - k * TAU
+    };
+
+    let (mut f_a, mut f_b) = (f(big_a), f(big_b));
+    while (big_b - big_a).abs() > EPSILON {
+        let big_c = big_a + (big_a - big_b) * f_a / (f_b - f_a);
+        let f_c = f(big_c);
+
+        if f_c * f_b <= 0.0 {
+            (big_a, f_a) = (big_b, f_b);
+        } else {
+            f_a /= 2.0;
+        }
+        (big_b, f_b) = (big_c, f_c);
+    }
+
+    (big_a / 2.0).exp()
+}
@@ -0,0 +1,164 @@
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use crate::chess::{Color, Move};
+use crate::pgn::Pgn;
+use crate::tree::NodePath;
+use crate::uci::parse_move_string;
+
+// a failed line comes back after this many seconds
+const RELEARN_DELAY: u64 = 10 * 60;
+const DAY: f64 = 86_400.0;
+const MIN_EASE: f64 = 1.3;
+
+/// One line of a repertoire and when to train it next, spaced out like SM-2 flashcards:
+/// every line played without a mistake waits `ease` times longer, a mistake starts it over.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Card {
+    // from the start of the repertoire to the player's last move of the line
+    pub moves: Vec<Move>,
+    // seconds since the unix epoch
+    pub due: u64,
+    // days
+    pub interval: f64,
+    pub ease: f64,
+    pub reviews: u32,
+}
+
+impl Card {
+    fn new(moves: Vec<Move>) -> Card {
+        Card { moves, due: 0, interval: 0.0, ease: 2.5, reviews: 0 }
+    }
+}
+
+/// A repertoire to train: the moves of the player's color are the answers, the other side's
+/// moves (every variation of them) are the questions.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Repertoire {
+    pub pgn: Pgn,
+    pub color: Color,
+    pub cards: Vec<Card>,
+}
+
+/// How a move played in training compares to the repertoire.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Answer {
+    // the move of the line being trained
+    Correct,
+    // another move the repertoire has here, the line goes on differently
+    OtherLine,
+    Wrong,
+}
+
+impl Repertoire {
+    /// A card for every line of `pgn` that has a move of `color`, all of them due.
+    pub fn new(pgn: Pgn, color: Color) -> Self {
+        let mut lines = Vec::new();
+        collect_lines(&pgn, color, &NodePath::default(), &mut Vec::new(), &mut lines);
+
+        let mut cards: Vec<Card> = Vec::new();
+        for line in lines {
+            if !line.is_empty() && !cards.iter().any(|x| x.moves == line) { cards.push(Card::new(line)); }
+        }
+
+        Repertoire { pgn, color, cards }
+    }
+
+    /// The file the schedule of the repertoire at `pgn` is kept in, `openings.pgn` has `openings.train.txt`.
+    pub fn schedule_path(pgn: &Path) -> PathBuf {
+        pgn.with_extension("train.txt")
+    }
+
+    /// Takes the schedule of every line still in the repertoire from a schedule written by [`Repertoire::schedule`].
+    pub fn load_schedule(&mut self, text: &str) {
+        for line in text.lines() {
+            let mut fields = line.split_whitespace();
+            let (Some(due), Some(interval), Some(ease), Some(reviews)) = (fields.next(), fields.next(), fields.next(), fields.next()) else { continue; };
+            let Some(moves) = fields.map(parse_move_string).collect::<Option<Vec<Move>>>() else { continue; };
+
+            if let (Some(card), Ok(due), Ok(interval), Ok(ease), Ok(reviews)) =
+                   (self.cards.iter_mut().find(|x| x.moves == moves), due.parse(), interval.parse(), ease.parse(), reviews.parse()) {
+                (card.due, card.interval, card.ease, card.reviews) = (due, interval, ease, reviews);
+            }
+        }
+    }
+
+    /// The schedule as `due interval ease reviews moves...` lines, moves in long algebraic notation.
+    pub fn schedule(&self) -> String {
+        self.cards.iter()
+            .map(|x| {
+                let moves: Vec<String> = x.moves.iter().map(|x| x.to_string()).collect();
+                format!("{} {} {} {} {}\n", x.due, x.interval, x.ease, x.reviews, moves.join(" "))
+            })
+            .collect()
+    }
+
+    /// The card to train at `now` (seconds since the epoch): the most overdue one, or the one due soonest.
+    pub fn next_card(&self, now: u64) -> Option<usize> {
+        let overdue = (0..self.cards.len()).filter(|x| self.cards[*x].due <= now).min_by_key(|x| self.cards[*x].due);
+        overdue.or_else(|| (0..self.cards.len()).min_by_key(|x| self.cards[*x].due))
+    }
+
+    pub fn due(&self, now: u64) -> usize {
+        self.cards.iter().filter(|x| x.due <= now).count()
+    }
+
+    /// Checks `mv`, played after the first `ply` moves of `card`'s line.
+    pub fn check(&self, card: usize, ply: usize, mv: Move) -> Answer {
+        let line = &self.cards[card].moves;
+
+        if line.get(ply) == Some(&mv) {
+            Answer::Correct
+        } else if self.pgn.find_path(&line[..ply.min(line.len())]).is_some_and(|x| self.pgn.children(&x).iter().any(|x| x.0 == mv)) {
+            Answer::OtherLine
+        } else {
+            Answer::Wrong
+        }
+    }
+
+    /// Schedules `card` after it was trained at `now`, `correct` if no move was wrong.
+    pub fn review(&mut self, card: usize, correct: bool, now: u64) {
+        let card = &mut self.cards[card];
+        card.reviews += 1;
+
+        if correct {
+            card.interval = if card.interval < 1.0 { 1.0 } else { card.interval * card.ease };
+            card.ease += 0.1;
+            card.due = now + (card.interval * DAY) as u64;
+        } else {
+            card.interval = 0.0;
+            card.ease = (card.ease - 0.2).max(MIN_EASE);
+            card.due = now + RELEARN_DELAY;
+        }
+    }
+}
+
+// every line from `path` to a leaf, cut after the last move of `color`
+fn collect_lines(pgn: &Pgn, color: Color, path: &NodePath, moves: &mut Vec<Move>, lines: &mut Vec<Vec<Move>>) {
+    let children = pgn.children(path);
+    if children.is_empty() {
+        let start = pgn.history.start().turn;
+        // the player's moves are every other one, starting with the first when they move first
+        let own = |ply: usize| ply.is_multiple_of(2) == (start == color);
+        let end = (0..moves.len()).rev().find(|x| own(*x)).map(|x| x + 1).unwrap_or(0);
+
+        lines.push(moves[..end].to_vec());
+        return;
+    }
+
+    for (mv, child) in children {
+        moves.push(mv);
+        collect_lines(pgn, color, &child, moves, lines);
+        moves.pop();
+    }
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Answer::Correct => { write!(f, "Correct") }
+            Answer::OtherLine => { write!(f, "In the repertoire, but not this line") }
+            Answer::Wrong => { write!(f, "Not in the repertoire") }
+        }
+    }
+}
@@ -0,0 +1,201 @@
+//! How a game ended: who won and why.
+//!
+//! A [`GameResult`] is what every finished game comes down to, played on the board, in a tournament,
+//! by correspondence or stopped by the [adjudicator](crate::adjudication::Adjudicator). It is written
+//! to the `Result` and `Termination` tags of a pgn and read back from them.
+
+use std::fmt::{Display, Formatter};
+use crate::adjudication::AdjudicationReason;
+use crate::chess::{Color, DrawReason};
+use crate::history::History;
+use crate::pgn::Pgn;
+
+/// Why a game ended.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TerminationReason {
+    Checkmate,
+    Resignation,
+    /// The loser's clock ran out.
+    Timeout,
+    Stalemate,
+    /// The same position came up a third time.
+    Repetition,
+    FiftyMoveRule,
+    InsufficientMaterial,
+    /// Locked pawns keep either side from ever mating.
+    DeadPosition,
+    /// The players agreed to a draw.
+    Agreement,
+    /// The loser left the game.
+    Abandonment,
+    Adjudication(AdjudicationReason),
+    /// The loser, an engine, sent a move that isn't legal.
+    IllegalMove,
+}
+
+impl TerminationReason {
+    /// The value of the PGN `Termination` tag.
+    pub fn pgn_tag(self) -> &'static str {
+        match self {
+            TerminationReason::Adjudication(_) => { "adjudication" }
+            TerminationReason::IllegalMove => { "rules infraction" }
+            TerminationReason::Timeout => { "time forfeit" }
+            TerminationReason::Abandonment => { "abandoned" }
+            _ => { "normal" }
+        }
+    }
+}
+
+impl From<DrawReason> for TerminationReason {
+    fn from(reason: DrawReason) -> Self {
+        match reason {
+            DrawReason::FiftyMoveRule => { TerminationReason::FiftyMoveRule }
+            DrawReason::InsufficientMaterial => { TerminationReason::InsufficientMaterial }
+            DrawReason::DeadPosition => { TerminationReason::DeadPosition }
+        }
+    }
+}
+
+impl Display for TerminationReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TerminationReason::Checkmate => { write!(f, "checkmate") }
+            TerminationReason::Resignation => { write!(f, "resignation") }
+            TerminationReason::Timeout => { write!(f, "lost on time") }
+            TerminationReason::Stalemate => { write!(f, "stalemate") }
+            TerminationReason::Repetition => { write!(f, "threefold repetition") }
+            TerminationReason::FiftyMoveRule => { write!(f, "fifty move rule") }
+            TerminationReason::InsufficientMaterial => { write!(f, "insufficient material") }
+            TerminationReason::DeadPosition => { write!(f, "dead position") }
+            TerminationReason::Agreement => { write!(f, "agreement") }
+            TerminationReason::Abandonment => { write!(f, "abandoned") }
+            TerminationReason::Adjudication(AdjudicationReason::Resignation) => { write!(f, "adjudicated resignation") }
+            TerminationReason::Adjudication(AdjudicationReason::DrawScore) => { write!(f, "adjudicated draw") }
+            TerminationReason::Adjudication(AdjudicationReason::Tablebase) => { write!(f, "tablebase") }
+            TerminationReason::IllegalMove => { write!(f, "illegal move") }
+        }
+    }
+}
+
+/// A finished game, `winner` is `None` for a draw.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameResult {
+    pub winner: Option<Color>,
+    pub reason: TerminationReason,
+}
+
+impl GameResult {
+    pub fn win(winner: Color, reason: TerminationReason) -> Self {
+        GameResult { winner: Some(winner), reason }
+    }
+
+    pub fn draw(reason: TerminationReason) -> Self {
+        GameResult { winner: None, reason }
+    }
+
+    /// How the game is over on the board after `history`, None if it goes on: mate, stalemate, a draw
+    /// by rule or a threefold repetition.
+    pub fn of(history: &History) -> Option<GameResult> {
+        let game = history.current();
+
+        if game.is_in_check(game.turn) && game.is_in_checkmate(game.turn) {
+            Some(GameResult::win(!game.turn, TerminationReason::Checkmate))
+        } else if game.is_stalemate() {
+            Some(GameResult::draw(TerminationReason::Stalemate))
+        } else if let Some(reason) = game.draw_reason() {
+            Some(GameResult::draw(reason.into()))
+        } else if repetitions(history) >= 3 {
+            Some(GameResult::draw(TerminationReason::Repetition))
+        } else {
+            None
+        }
+    }
+
+    /// The result in the tags of `pgn`, None if the game isn't finished. Where the tags don't say why,
+    /// the end of the game on the board does, or else it was resigned or agreed drawn.
+    pub fn from_pgn(pgn: &Pgn) -> Option<GameResult> {
+        let winner = match pgn.tag("Result")? {
+            "1-0" => { Some(Color::White) }
+            "0-1" => { Some(Color::Black) }
+            "1/2-1/2" => { None }
+            _ => { return None; }
+        };
+
+        let termination = pgn.tag("Termination").unwrap_or_default().to_lowercase();
+        let reason = match (termination.as_str(), winner) {
+            ("time forfeit", _) => { TerminationReason::Timeout }
+            ("rules infraction", _) => { TerminationReason::IllegalMove }
+            ("abandoned", _) => { TerminationReason::Abandonment }
+            ("adjudication", Some(_)) => { TerminationReason::Adjudication(AdjudicationReason::Resignation) }
+            ("adjudication", None) => { TerminationReason::Adjudication(AdjudicationReason::DrawScore) }
+            _ => match GameResult::of(&pgn.history).filter(|x| x.winner == winner) {
+                Some(result) => { result.reason }
+                None if winner.is_some() => { TerminationReason::Resignation }
+                None => { TerminationReason::Agreement }
+            }
+        };
+
+        Some(GameResult { winner, reason })
+    }
+
+    /// The points `color` got, 1 for a win, 0.5 for a draw.
+    pub fn score(self, color: Color) -> f32 {
+        match self.winner {
+            Some(winner) if winner == color => { 1.0 }
+            Some(_) => { 0.0 }
+            None => { 0.5 }
+        }
+    }
+
+    /// "1-0", "0-1" or "1/2-1/2".
+    pub fn pgn_result(self) -> &'static str {
+        match self.winner {
+            Some(Color::White) => { "1-0" }
+            Some(Color::Black) => { "0-1" }
+            None => { "1/2-1/2" }
+        }
+    }
+
+    /// Sets the `Result` and `Termination` tags of `pgn`.
+    pub fn write_tags(self, pgn: &mut Pgn) {
+        pgn.set_tag("Result", self.pgn_result());
+        pgn.set_tag("Termination", self.reason.pgn_tag());
+    }
+}
+
+impl Display for GameResult {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let Some(winner) = self.winner else {
+            return match self.reason {
+                TerminationReason::Adjudication(AdjudicationReason::DrawScore) => { write!(f, "Drawn by the engines' evaluations") }
+                reason => { write!(f, "Drawn by {}", reason) }
+            };
+        };
+        let loser = !winner;
+
+        match self.reason {
+            TerminationReason::Resignation | TerminationReason::Adjudication(AdjudicationReason::Resignation) => { write!(f, "{:?} resigns", loser) }
+            TerminationReason::Timeout => { write!(f, "{:?} lost on time", loser) }
+            TerminationReason::Abandonment => { write!(f, "{:?} abandoned the game", loser) }
+            TerminationReason::IllegalMove => { write!(f, "{:?} played an illegal move", loser) }
+            reason => { write!(f, "{:?} wins by {}", winner, reason) }
+        }
+    }
+}
+
+// how often the current position came up, itself included
+fn repetitions(history: &History) -> usize {
+    let mut game = history.start();
+    let mut positions = vec![game.zobrist()];
+
+    // the moves were legal when they were played
+    for mv in history.moves() {
+        game.play_unchecked(mv);
+        positions.push(game.zobrist());
+    }
+
+    let last = positions[positions.len() - 1];
+    positions.iter().filter(|x| **x == last).count()
+}
@@ -0,0 +1,377 @@
+//! Custom rules: a [`Variant`] with modifiers on top, like chess without castling where kings can be
+//! taken, or horde with gravity.
+//!
+//! [`Rules`] is a variant and the [`RuleModifier`]s changing it, which combine freely. A [`RulesGame`]
+//! plays a game under them and writes it as a pgn with the rules in its `Variant` and `Rules` tags, so
+//! reading it back replays every move under the rules it was played with. The moves are written like
+//! e2e4, since san leaves out what chess's rules make clear and these rules may not.
+
+use std::fmt::{Display, Formatter};
+use crate::chess::{Color, Game, Move, MoveFlags, MoveResult, Piece};
+use crate::error::{IllegalMove, PgnError, PgnErrorReason};
+use crate::pgn::parse_tag;
+use crate::uci::parse_move_string;
+use crate::variant::{Variant, VariantEnd};
+
+/// A change to the rules of a variant.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RuleModifier {
+    /// Neither side may castle.
+    NoCastling,
+    /// Kings may be left attacked, and taking the other king wins.
+    KingCapture,
+    /// After every move, every piece but the pawns falls toward the first rank until it lands on another.
+    Gravity,
+    /// The side checked this many times loses.
+    ChecksToLose(u8),
+}
+
+impl RuleModifier {
+    /// The name in a pgn `Rules` tag, like `gravity` or `3-check`.
+    pub fn name(self) -> String {
+        match self {
+            RuleModifier::NoCastling => { "no-castling".to_string() }
+            RuleModifier::KingCapture => { "king-capture".to_string() }
+            RuleModifier::Gravity => { "gravity".to_string() }
+            RuleModifier::ChecksToLose(checks) => { format!("{}-check", checks) }
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<RuleModifier> {
+        let name = name.trim().to_lowercase();
+        if let Some(checks) = name.strip_suffix("-check") {
+            return checks.parse().ok().filter(|x| *x > 0).map(RuleModifier::ChecksToLose);
+        }
+
+        [RuleModifier::NoCastling, RuleModifier::KingCapture, RuleModifier::Gravity].into_iter().find(|x| x.name() == name)
+    }
+}
+
+impl Display for RuleModifier {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// A variant and the modifiers played on top of it.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rules {
+    pub variant: Variant,
+    // in the order they were added, each kind once
+    pub modifiers: Vec<RuleModifier>,
+}
+
+impl Rules {
+    pub fn new(variant: Variant) -> Self {
+        Rules { variant, modifiers: Vec::new() }
+    }
+
+    /// Adds `modifier`, in place of one of the same kind, like another number of checks.
+    pub fn with(mut self, modifier: RuleModifier) -> Self {
+        self.modifiers.retain(|x| std::mem::discriminant(x) != std::mem::discriminant(&modifier));
+        self.modifiers.push(modifier);
+
+        self
+    }
+
+    pub fn has(&self, modifier: RuleModifier) -> bool {
+        self.modifiers.contains(&modifier)
+    }
+
+    /// How many checks lose, None if there is no limit.
+    pub fn checks_to_lose(&self) -> Option<u8> {
+        self.modifiers.iter().find_map(|x| match x {
+            RuleModifier::ChecksToLose(checks) => { Some(*checks) }
+            _ => { None }
+        })
+    }
+
+    /// The value of the pgn `Rules` tag, the names of the modifiers separated by commas.
+    pub fn tag(&self) -> String {
+        self.modifiers.iter().map(|x| x.name()).collect::<Vec<_>>().join(", ")
+    }
+
+    /// Reads the `Variant` and `Rules` tags back, a missing tag is chess without modifiers. None if
+    /// either names something unknown.
+    pub fn from_tags(variant: Option<&str>, rules: Option<&str>) -> Option<Rules> {
+        let mut parsed = Rules::new(variant.map_or(Some(Variant::Standard), Variant::from_name)?);
+
+        for name in rules.unwrap_or_default().split(',').filter(|x| !x.trim().is_empty()) {
+            parsed = parsed.with(RuleModifier::from_name(name)?);
+        }
+
+        Some(parsed)
+    }
+
+    /// Every move the rules allow the side to move.
+    pub fn legal_moves(&self, game: &Game) -> Vec<Move> {
+        let king_capture = self.has(RuleModifier::KingCapture);
+        let mut moves = if king_capture { self.variant.pseudo_legal_moves(game) } else { self.variant.legal_moves(game) };
+
+        if self.has(RuleModifier::NoCastling) {
+            moves.retain(|x| !x.flags.contains(MoveFlags::CASTLE));
+        }
+
+        if self.has(RuleModifier::Gravity) {
+            // a falling piece can open a line onto either king
+            moves.retain(|mv| {
+                let mut after = *game;
+                self.apply(&mut after, *mv);
+
+                (king_capture || !after.is_in_check(game.turn)) && (self.variant != Variant::RacingKings || !after.is_in_check(!game.turn))
+            });
+        }
+
+        moves
+    }
+
+    /// Plays `mv`, which has to be one of [`Rules::legal_moves`], and lets the pieces fall under gravity.
+    pub fn apply(&self, game: &mut Game, mv: Move) {
+        game.play_unchecked(mv);
+        if self.has(RuleModifier::Gravity) { fall(game); }
+    }
+}
+
+impl Display for Rules {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.variant)?;
+
+        for modifier in &self.modifiers {
+            write!(f, ", {}", modifier)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A game played under [`Rules`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RulesGame {
+    rules: Rules,
+    start: Game,
+    moves: Vec<Move>,
+    // position after the last move
+    position: Game,
+    // how often each side was checked, white first
+    checks: [u8; 2],
+    end: Option<VariantEnd>,
+}
+
+impl RulesGame {
+    /// A game from the starting position of the rules' variant.
+    pub fn new(rules: Rules) -> Self {
+        let start = rules.variant.start();
+        RulesGame::from_position(rules, start)
+    }
+
+    /// A game from `start`, which should be a position of the rules' variant.
+    pub fn from_position(rules: Rules, start: Game) -> Self {
+        let mut game = RulesGame { rules, start, moves: Vec::new(), position: start, checks: [0; 2], end: None };
+        game.end = game.find_end(None);
+
+        game
+    }
+
+    /// Plays the moves of `moves` in order, failing at the first the rules don't allow.
+    pub fn replay(rules: Rules, start: Game, moves: &[Move]) -> Result<Self, IllegalMove> {
+        let mut game = RulesGame::from_position(rules, start);
+
+        for mv in moves {
+            game.play(*mv)?;
+        }
+
+        Ok(game)
+    }
+
+    /// Plays `mv` if the rules allow it, and tells whether that ended the game.
+    pub fn play(&mut self, mv: Move) -> Result<Option<VariantEnd>, IllegalMove> {
+        let legal = self.legal_moves().into_iter().find(|x| *x == mv);
+
+        let Some(mv) = legal else {
+            // the variant can tell what's wrong, unless only a modifier forbids the move
+            let explanation = match self.rules.variant.make_move(&mut self.position.clone(), mv) {
+                Err(err) if self.end.is_none() => { err.explanation }
+                _ => { None }
+            };
+            return Err(IllegalMove { mv, reason: explanation.map_or(MoveResult::Illegal, |x| x.move_result()), explanation });
+        };
+
+        let (mover, taken) = (self.position.turn, self.position.board[mv.to]);
+        self.rules.apply(&mut self.position, mv);
+        self.moves.push(mv);
+
+        if self.position.is_in_check(!mover) {
+            self.checks[side(!mover)] = self.checks[side(!mover)].saturating_add(1);
+        }
+
+        self.end = self.find_end(taken);
+        Ok(self.end)
+    }
+
+    /// Every move the rules allow in the current position, nothing once the game is over.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        if self.end.is_some() { return Vec::new(); }
+        self.rules.legal_moves(&self.position)
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    pub fn start(&self) -> Game {
+        self.start
+    }
+
+    pub fn position(&self) -> Game {
+        self.position
+    }
+
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// How often `color` was checked.
+    pub fn checks(&self, color: Color) -> u8 {
+        self.checks[side(color)]
+    }
+
+    /// How the game ended, None while it goes on.
+    pub fn end(&self) -> Option<VariantEnd> {
+        self.end
+    }
+
+    // how the game is over after a move that took `taken`
+    fn find_end(&self, taken: Option<Piece>) -> Option<VariantEnd> {
+        let (position, mover) = (&self.position, !self.position.turn);
+
+        if taken.is_some_and(|x| x == Piece::WKing || x == Piece::BKing) { return Some(VariantEnd::KingTaken(mover)); }
+        if self.rules.checks_to_lose().is_some_and(|x| self.checks[side(!mover)] >= x) { return Some(VariantEnd::ChecksGiven(mover)); }
+        if let Some(end) = self.rules.variant.goal(position) { return Some(end); }
+
+        if self.rules.legal_moves(position).is_empty() {
+            return Some(if position.is_in_check(position.turn) { VariantEnd::Checkmate(mover) } else { VariantEnd::Stalemate });
+        }
+
+        self.rules.variant.draw(position)
+    }
+
+    /// The game as a pgn, the rules in its `Variant` and `Rules` tags. Read it back with [`RulesGame::parse`].
+    pub fn to_pgn(&self) -> String {
+        let mut tags: Vec<(&str, String)> = ["Event", "Site", "Date", "Round", "White", "Black"].into_iter().map(|x| (x, "?".to_string())).collect();
+        tags.push(("Result", self.end.map_or("*", VariantEnd::pgn_result).to_string()));
+
+        if self.rules.variant != Variant::Standard { tags.push(("Variant", self.rules.variant.name().to_string())); }
+        if !self.rules.modifiers.is_empty() { tags.push(("Rules", self.rules.tag())); }
+        if self.start != self.rules.variant.start() {
+            tags.push(("SetUp", "1".to_string()));
+            tags.push(("FEN", self.start.as_fen()));
+        }
+        if let Some(end) = self.end { tags.push(("Termination", end.to_string())); }
+
+        let mut text = String::new();
+        for (name, value) in tags {
+            text.push_str(&format!("[{} \"{}\"]\n", name, value.replace('\\', "\\\\").replace('"', "\\\"")));
+        }
+        text.push('\n');
+
+        let mut words = Vec::new();
+        let mut position = self.start;
+        for mv in &self.moves {
+            match position.turn {
+                Color::White => { words.push(format!("{}.", position.fm_clock())); }
+                Color::Black if words.is_empty() => { words.push(format!("{}...", position.fm_clock())); }
+                Color::Black => {}
+            }
+            words.push(mv.to_string());
+            self.rules.apply(&mut position, *mv);
+        }
+        words.push(self.end.map_or("*", VariantEnd::pgn_result).to_string());
+
+        // movetext lines are kept under 80 characters, like other pgns
+        let mut line_len = 0;
+        for word in words {
+            if line_len != 0 && line_len + word.len() + 1 > 79 {
+                text.push('\n');
+                line_len = 0;
+            } else if line_len != 0 {
+                text.push(' ');
+                line_len += 1;
+            }
+
+            text.push_str(&word);
+            line_len += word.len();
+        }
+        text.push('\n');
+
+        text
+    }
+
+    /// Reads a game written by [`RulesGame::to_pgn`], replaying its moves under its rules.
+    pub fn parse(text: &str) -> Result<RulesGame, PgnError> {
+        // (line, name, value)
+        let mut tags = Vec::new();
+        let mut tokens = Vec::new();
+
+        for (line_no, line) in text.lines().enumerate() {
+            let (line_no, line) = (line_no + 1, line.trim());
+
+            if line.starts_with('[') {
+                let (name, value) = parse_tag(line).ok_or(PgnError::new(line_no, PgnErrorReason::InvalidTag))?;
+                tags.push((line_no, name, value));
+            } else {
+                tokens.extend(line.split_whitespace().map(|x| (line_no, x)));
+            }
+        }
+
+        if tags.is_empty() && tokens.is_empty() { return Err(PgnError::new(1, PgnErrorReason::Empty)); }
+
+        let tag = |name: &str| tags.iter().find(|x| x.1 == name).map(|x| (x.0, x.2.as_str()));
+        let unknown = |(line, value): (usize, &str)| PgnError::new(line, PgnErrorReason::UnknownRules(value.to_string()));
+
+        let variant = tag("Variant").map_or(Ok(Variant::Standard), |x| Variant::from_name(x.1).ok_or_else(|| unknown(x)))?;
+        let rules = tag("Rules").map_or(Some(Rules::new(variant)), |x| Rules::from_tags(Some(variant.name()), Some(x.1)))
+            .ok_or_else(|| unknown(tag("Rules").unwrap_or_default()))?;
+
+        let start = match tag("FEN") {
+            Some((line, fen)) => { rules.variant.from_fen(fen).map_err(|x| PgnError::new(line, PgnErrorReason::Fen(x)))? }
+            None => { rules.variant.start() }
+        };
+
+        let mut game = RulesGame::from_position(rules, start);
+        for (line, token) in tokens {
+            if token.ends_with('.') || ["1-0", "0-1", "1/2-1/2", "*"].contains(&token) { continue; }
+
+            let unknown = || PgnError::new(line, PgnErrorReason::UnknownMove(token.to_string()));
+            game.play(parse_move_string(token).ok_or_else(unknown)?).map_err(|_| unknown())?;
+        }
+
+        Ok(game)
+    }
+}
+
+fn side(color: Color) -> usize {
+    if color == Color::White { 0 } else { 1 }
+}
+
+// drops every piece but the pawns down its file as far as it goes
+fn fall(game: &mut Game) {
+    // from the bottom up, so pieces land on the ones that already fell
+    for from in 8..64 {
+        let Some(piece) = game.board[from].filter(|x| *x != Piece::WPawn && *x != Piece::BPawn) else { continue; };
+
+        let mut to = from;
+        while to >= 8 && game.board[to - 8].is_none() {
+            to -= 8;
+        }
+
+        if to != from {
+            game.board[from] = None;
+            game.board[to] = Some(piece);
+        }
+    }
+
+    game.drop_lost_castling();
+}
@@ -0,0 +1,60 @@
+//! Links to a position or a game, to send to someone else.
+//!
+//! A lichess analysis link opens the position on lichess. A `chess://` link opens this app on the
+//! game: the position it started from as a FEN and its moves in long algebraic notation, like
+//! `chess://game?fen=rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR_w_KQkq_-_0_1&moves=e2e4,e7e5`.
+
+use crate::chess::Game;
+use crate::error::LinkError;
+use crate::history::History;
+use crate::uci::parse_move_string;
+
+/// The scheme of links this app opens.
+pub const SCHEME: &str = "chess://";
+
+/// The position on lichess' analysis board.
+pub fn lichess_url(game: &Game) -> String {
+    format!("https://lichess.org/analysis/standard/{}", game.as_fen().replace(' ', "_"))
+}
+
+/// A `chess://` link to the game in `history`, which [`parse_link`] reads back.
+pub fn game_link(history: &History) -> String {
+    let mut link = format!("{}game?fen={}", SCHEME, history.start().as_fen().replace(' ', "_"));
+
+    if !history.is_empty() {
+        let moves: Vec<String> = history.moves().map(|x| x.to_string()).collect();
+        link.push_str(&format!("&moves={}", moves.join(",")));
+    }
+
+    link
+}
+
+/// Reads a link made by [`game_link`]. A link without a FEN starts from the usual position, one without
+/// moves is just the position.
+pub fn parse_link(link: &str) -> Result<History, LinkError> {
+    let query = link.trim().strip_prefix(SCHEME).and_then(|x| x.strip_prefix("game")).ok_or(LinkError::Scheme)?;
+    let query = query.strip_prefix('?').unwrap_or(query);
+
+    let (mut fen, mut moves) = (None, None);
+    for pair in query.split('&').filter(|x| !x.is_empty()) {
+        match pair.split_once('=') {
+            Some(("fen", value)) => { fen = Some(value.replace(['_', '+'], " ").replace("%20", " ")); }
+            Some(("moves", value)) => { moves = Some(value); }
+            // other fields are left for newer versions
+            _ => {}
+        }
+    }
+
+    let start = match fen {
+        Some(fen) => { Game::from_fen(fen).map_err(LinkError::Fen)? }
+        None => { Game::default() }
+    };
+
+    let mut history = History::new(start);
+    for text in moves.into_iter().flat_map(|x| x.split(',')).filter(|x| !x.is_empty()) {
+        let mv = parse_move_string(text).ok_or_else(|| LinkError::Move(text.to_string()))?;
+        history.push(mv).map_err(|_| LinkError::Move(text.to_string()))?;
+    }
+
+    Ok(history)
+}
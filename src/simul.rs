@@ -0,0 +1,132 @@
+//! A simultaneous exhibition: one player against the engine on several boards at once.
+//!
+//! Each [`SimulBoard`] is a game of its own with the side the player has in it. [`Simul`] keeps them all
+//! and knows which are waiting on the player, so a GUI can move from one to the next while the engine
+//! thinks on the others.
+
+use crate::archive::pgn_date;
+use crate::chess::{Color, Game, Move, MoveResult};
+use crate::error::IllegalMove;
+use crate::history::History;
+use crate::pgn::Pgn;
+
+/// How a board ended, for the player.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Outcome {
+    Won,
+    Drawn,
+    Lost,
+}
+
+#[derive(Clone, Debug)]
+pub struct SimulBoard {
+    pub history: History,
+    // the side the player has on this board
+    pub player: Color,
+    // None while the game goes on
+    pub outcome: Option<Outcome>,
+}
+
+impl SimulBoard {
+    pub fn new(start: Game, player: Color) -> Self {
+        SimulBoard { history: History::new(start), player, outcome: None }
+    }
+
+    pub fn game(&self) -> Game {
+        self.history.current()
+    }
+
+    /// The game goes on and the player is to move.
+    pub fn is_players_turn(&self) -> bool {
+        self.outcome.is_none() && self.game().turn == self.player
+    }
+
+    /// The game goes on and the engine is to move.
+    pub fn is_engines_turn(&self) -> bool {
+        self.outcome.is_none() && self.game().turn != self.player
+    }
+
+    /// Plays `mv` for whoever is to move, ending the game on a mate or a draw.
+    pub fn play(&mut self, mv: Move) -> Result<MoveResult, IllegalMove> {
+        let mover = self.game().turn;
+        let result = self.history.push(mv)?;
+
+        self.outcome = match result {
+            MoveResult::Checkmate if mover == self.player => { Some(Outcome::Won) }
+            MoveResult::Checkmate => { Some(Outcome::Lost) }
+            MoveResult::Stalemate | MoveResult::Draw => { Some(Outcome::Drawn) }
+            _ => { self.outcome }
+        };
+
+        Ok(result)
+    }
+
+    /// Gives the game up, if it isn't over yet.
+    pub fn resign(&mut self) {
+        self.outcome.get_or_insert(Outcome::Lost);
+    }
+
+    /// The game as a PGN, with the player and `engine` as the players and the result if it's over.
+    pub fn pgn(&self, engine: &str) -> Pgn {
+        let mut pgn = Pgn::new(self.history.clone());
+        let (white, black) = if self.player == Color::White { ("Player", engine) } else { (engine, "Player") };
+
+        pgn.set_tag("Event", "Simul");
+        pgn.set_tag("Date", pgn_date());
+        pgn.set_tag("White", white);
+        pgn.set_tag("Black", black);
+        pgn.set_tag("Result", match (self.outcome, self.player) {
+            (Some(Outcome::Drawn), _) => { "1/2-1/2" }
+            (Some(Outcome::Won), Color::White) | (Some(Outcome::Lost), Color::Black) => { "1-0" }
+            (Some(_), _) => { "0-1" }
+            (None, _) => { "*" }
+        });
+
+        pgn
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Simul {
+    pub boards: Vec<SimulBoard>,
+}
+
+impl Simul {
+    /// `count` boards from `start`, the player white on all of them, or on every other one if `alternate`.
+    pub fn new(count: usize, start: Game, alternate: bool) -> Self {
+        let boards = (0..count).map(|i| {
+            let player = if alternate && i % 2 == 1 { Color::Black } else { Color::White };
+            SimulBoard::new(start, player)
+        }).collect();
+
+        Simul { boards }
+    }
+
+    /// The boards waiting on the player, in order.
+    pub fn waiting(&self) -> impl Iterator<Item = usize> + '_ {
+        self.boards.iter().enumerate().filter(|(_, x)| x.is_players_turn()).map(|(i, _)| i)
+    }
+
+    /// The first board after `current` that waits on the player, going round to the start. `current`
+    /// itself comes last.
+    pub fn next_waiting(&self, current: usize) -> Option<usize> {
+        let count = self.boards.len();
+        (1..=count).map(|i| (current + i) % count).find(|x| self.boards[*x].is_players_turn())
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.boards.iter().all(|x| x.outcome.is_some())
+    }
+
+    /// Boards (won, drawn, lost) so far.
+    pub fn tally(&self) -> (usize, usize, usize) {
+        let count = |outcome| self.boards.iter().filter(|x| x.outcome == Some(outcome)).count();
+        (count(Outcome::Won), count(Outcome::Drawn), count(Outcome::Lost))
+    }
+
+    /// The player's points: one a win and a half a draw.
+    pub fn points(&self) -> f32 {
+        let (won, drawn, _) = self.tally();
+        won as f32 + drawn as f32 / 2.0
+    }
+}
@@ -0,0 +1,99 @@
+//! Games played out without a board: two [`MovePicker`]s take turns until the game is over.
+//!
+//! Engines and [bots](crate::bots::Bot) are move pickers, and so is anything else that can pick a move
+//! from a position, like a test player checking every position it is shown. [`simulate`] plays one game
+//! and tells how it ended, [`play_out`] also runs clocks and adjudication and hands back the game, the
+//! [tournament](crate::tournament) runner plays its games with it.
+
+use std::time::{Duration, Instant};
+use crate::adjudication::{Adjudication, Adjudicator};
+use crate::bots::Bot;
+use crate::chess::{Color, Game};
+use crate::error::UciError;
+use crate::history::History;
+use crate::result::{GameResult, TerminationReason};
+use crate::uci::{Evaluation, Limits, Uci};
+
+// what engines get for each move in `simulate`
+const MOVE_TIME: u64 = 100;
+
+/// A player: picks the move to play in a game.
+pub trait MovePicker {
+    /// The move to play from the end of `history` within `limits`, with its score from the mover's
+    /// side if the picker has one. An error ends the game, see [`simulate`] and [`play_out`].
+    fn pick(&mut self, history: &History, limits: Limits) -> Result<Evaluation, UciError>;
+}
+
+impl MovePicker for Uci {
+    fn pick(&mut self, history: &History, limits: Limits) -> Result<Evaluation, UciError> {
+        self.evaluate(history, limits)
+    }
+}
+
+impl MovePicker for Bot {
+    fn pick(&mut self, history: &History, _: Limits) -> Result<Evaluation, UciError> {
+        let best = self.choose(&history.current()).ok_or(UciError::NoMove)?;
+        Ok(Evaluation { best, score: None })
+    }
+}
+
+/// Plays `white` against `black` from `game` until the game is over on the board. A picker that picks
+/// an illegal move loses, one that fails loses by abandoning the game. Engines think for 100ms a move.
+pub fn simulate(game: Game, white: &mut dyn MovePicker, black: &mut dyn MovePicker) -> GameResult {
+    let mut history = History::new(game);
+    let limits = Limits::default().time(MOVE_TIME);
+
+    loop {
+        if let Some(result) = GameResult::of(&history) { return result; }
+
+        let turn = history.current().turn;
+        let picker: &mut dyn MovePicker = if turn == Color::White { &mut *white } else { &mut *black };
+
+        match picker.pick(&history, limits) {
+            Ok(evaluation) if history.push(evaluation.best).is_ok() => {}
+            Ok(_) => { return GameResult::win(!turn, TerminationReason::IllegalMove); }
+            Err(_) => { return GameResult::win(!turn, TerminationReason::Abandonment); }
+        }
+    }
+}
+
+/// Plays `white` against `black` from the end of `history` until the game is over. If `limits` has
+/// clocks they run: each move's time comes off the mover's clock and its increment goes back on, the
+/// moves keep the clock left after them and a side whose clock runs out loses.
+pub fn play_out(white: &mut dyn MovePicker, black: &mut dyn MovePicker, mut history: History, mut limits: Limits,
+                adjudication: Option<Adjudication>) -> Result<(History, GameResult), UciError> {
+    let mut adjudicator = adjudication.map(Adjudicator::new);
+    // milliseconds left, white's and black's
+    let mut clocks = [limits.clock(Color::White), limits.clock(Color::Black)];
+
+    loop {
+        let game = history.current();
+        if let Some(result) = GameResult::of(&history) {
+            return Ok((history, result));
+        }
+
+        if let [Some(white), Some(black)] = clocks { limits.set_time(white.max(1), black.max(1)); }
+
+        let picker: &mut dyn MovePicker = if game.turn == Color::White { &mut *white } else { &mut *black };
+        let started = Instant::now();
+        let evaluation = picker.pick(&history, limits)?;
+        let elapsed = started.elapsed();
+
+        let clock = &mut clocks[if game.turn == Color::White { 0 } else { 1 }];
+        if let Some(left) = clock {
+            let spent = elapsed.as_millis() as u64;
+            if spent > *left { return Ok((history, GameResult::win(!game.turn, TerminationReason::Timeout))); }
+
+            *left = *left - spent + limits.increment(game.turn);
+        }
+
+        if history.push_timed(evaluation.best, elapsed).is_err() {
+            return Ok((history, GameResult::win(!game.turn, TerminationReason::IllegalMove)));
+        }
+        history.set_times(history.len() - 1, Some(elapsed), clock.map(Duration::from_millis));
+
+        if let Some(verdict) = adjudicator.as_mut().and_then(|x| x.update(&history.current(), evaluation.score)) {
+            return Ok((history, verdict));
+        }
+    }
+}
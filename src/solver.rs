@@ -0,0 +1,97 @@
+//! A deterministic mate search, to check mate puzzles and prove the mates engines report.
+//!
+//! Every move of the attacker is tried against every reply, checks first, so the same position always
+//! gives the same line. Positions already shown to have no mate at a depth are remembered by their
+//! [`Game::zobrist`] hash.
+
+use std::collections::HashSet;
+use crate::chess::{Game, Move, MoveResult};
+
+/// The fastest forced mate for the side to move, in at most `n` of its moves. The line alternates the
+/// attacker's moves with the defence that holds out longest and ends in mate, None if there is no mate.
+pub fn find_mate(game: &Game, n: u32) -> Option<Vec<Move>> {
+    let mut search = Search::default();
+    let depth = (1..=n).find(|x| search.mating_move(game, *x).is_some())?;
+
+    Some(search.line(game, depth))
+}
+
+/// Every first move that forces mate in at most `n` moves, a sound mate puzzle has just one.
+pub fn mating_moves(game: &Game, n: u32) -> Vec<Move> {
+    let mut search = Search::default();
+    game.legal_moves().filter(|x| search.forces_mate(game, *x, n)).collect()
+}
+
+#[derive(Default)]
+struct Search {
+    // (position, depth) with the attacker to move and no mate in that many moves
+    refuted: HashSet<(u64, u32)>,
+}
+
+impl Search {
+    // the first move that mates in at most `depth` moves
+    fn mating_move(&mut self, game: &Game, depth: u32) -> Option<Move> {
+        let key = (game.zobrist(), depth);
+        if self.refuted.contains(&key) { return None; }
+
+        // only a check can mate right away, and checks are the likeliest to mate later too
+        let mut moves: Vec<Move> = game.legal_moves().filter(|x| depth > 1 || game.gives_check(*x)).collect();
+        moves.sort_by_key(|x| !game.gives_check(*x));
+
+        let found = moves.into_iter().find(|x| self.forces_mate(game, *x, depth));
+        if found.is_none() { self.refuted.insert(key); }
+
+        found
+    }
+
+    // whether every reply to `mv` still loses to a mate within `depth` moves, `mv` included
+    fn forces_mate(&mut self, game: &Game, mv: Move, depth: u32) -> bool {
+        let mut after = *game;
+
+        match after.make_move(mv) {
+            Ok(MoveResult::Checkmate) => { true }
+            Ok(MoveResult::Valid | MoveResult::Check) if depth > 1 => {
+                let replies: Vec<Move> = after.legal_moves().collect();
+                replies.into_iter().all(|reply| self.reply_loses(&after, reply, depth - 1))
+            }
+            _ => { false }
+        }
+    }
+
+    // whether the attacker still mates in `depth` moves after the defender's `reply`
+    fn reply_loses(&mut self, game: &Game, reply: Move, depth: u32) -> bool {
+        let mut after = *game;
+
+        match after.make_move(reply) {
+            Ok(MoveResult::Valid | MoveResult::Check) => { self.mating_move(&after, depth).is_some() }
+            _ => { false }
+        }
+    }
+
+    // the mate from `game`, which is known to mate in `depth` moves but no fewer
+    fn line(&mut self, game: &Game, depth: u32) -> Vec<Move> {
+        let mv = self.mating_move(game, depth).expect("the position mates in depth moves");
+        let mut after = *game;
+        after.make_move(mv).expect("legal moves can be played");
+
+        let mut line = vec![mv];
+        if depth == 1 { return line; }
+
+        // the reply that puts the mate off the longest
+        let replies: Vec<Move> = after.legal_moves().collect();
+        let longest = replies.into_iter().map(|reply| {
+            let mut position = after;
+            position.make_move(reply).expect("legal moves can be played");
+
+            let left = (1..depth).find(|x| self.mating_move(&position, *x).is_some()).unwrap_or(depth - 1);
+            (left, reply, position)
+        }).max_by_key(|x| x.0);
+
+        if let Some((left, reply, position)) = longest {
+            line.push(reply);
+            line.extend(self.line(&position, left));
+        }
+
+        line
+    }
+}
@@ -0,0 +1,99 @@
+use std::fmt::{Display, Formatter};
+
+/// A sequential probability ratio test between two engines: does the first one gain `elo1`
+/// over the second (H1), or at most `elo0` (H0)?
+///
+/// `alpha` and `beta` are the accepted chances of wrongly accepting H1 and H0.
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+impl Default for Sprt {
+    fn default() -> Self {
+        Sprt { elo0: 0.0, elo1: 5.0, alpha: 0.05, beta: 0.05 }
+    }
+}
+
+/// Which hypothesis the test settled on.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SprtVerdict {
+    AcceptH0,
+    AcceptH1,
+}
+
+impl Display for SprtVerdict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SprtVerdict::AcceptH0 => { write!(f, "H0 accepted") }
+            SprtVerdict::AcceptH1 => { write!(f, "H1 accepted") }
+        }
+    }
+}
+
+impl Sprt {
+    pub fn new(elo0: f64, elo1: f64) -> Self {
+        Sprt { elo0, elo1, ..Sprt::default() }
+    }
+
+    pub fn alpha(mut self, alpha: f64) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    pub fn beta(mut self, beta: f64) -> Self {
+        self.beta = beta;
+        self
+    }
+
+    /// The log likelihood ratios where H0 and H1 are accepted.
+    pub fn bounds(&self) -> (f64, f64) {
+        ((self.beta / (1.0 - self.alpha)).ln(), ((1.0 - self.beta) / self.alpha).ln())
+    }
+
+    /// The log likelihood ratio of H1 over H0 after the first engine's `wins`, `draws` and `losses`,
+    /// using the normal approximation of the trinomial model.
+    pub fn llr(&self, wins: u32, draws: u32, losses: u32) -> f64 {
+        let n = (wins + draws + losses) as f64;
+        if n == 0.0 { return 0.0; }
+
+        let score = (wins as f64 + draws as f64 / 2.0) / n;
+        let variance = (wins as f64 * (1.0 - score).powi(2) + draws as f64 * (0.5 - score).powi(2) + losses as f64 * score.powi(2)) / n;
+
+        // all games ended the same way so far, there is nothing to go on
+        if variance <= 0.0 { return 0.0; }
+
+        let (s0, s1) = (expected_score(self.elo0), expected_score(self.elo1));
+        n * (s1 - s0) * (2.0 * score - s0 - s1) / (2.0 * variance)
+    }
+
+    /// The verdict once the ratio leaves the bounds, `None` while the test goes on.
+    pub fn verdict(&self, llr: f64) -> Option<SprtVerdict> {
+        let (lower, upper) = self.bounds();
+
+        if llr <= lower {
+            Some(SprtVerdict::AcceptH0)
+        } else if llr >= upper {
+            Some(SprtVerdict::AcceptH1)
+        } else {
+            None
+        }
+    }
+}
+
+// the score expected from an engine `elo` points stronger than its opponent
+pub(crate) fn expected_score(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+impl Display for Sprt {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (lower, upper) = self.bounds();
+        write!(f, "SPRT elo0={} elo1={} alpha={} beta={} ({:.2}, {:.2})", self.elo0, self.elo1, self.alpha, self.beta, lower, upper)
+    }
+}
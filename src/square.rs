@@ -0,0 +1,176 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use crate::error::ParseSquareError;
+
+/// A column of the board, a is 0 and h is 7.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct File(u8);
+
+/// A row of the board, the first (white's back rank) is 0 and the eighth is 7.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rank(u8);
+
+/// A square of the board, a1 is 0 and h8 is 63.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Square(u8);
+
+impl File {
+    pub const A: File = File(0);
+    pub const H: File = File(7);
+
+    /// The file with index `index`, panics if it is off the board.
+    pub const fn new(index: usize) -> Self {
+        assert!(index < 8, "file index out of range");
+        File(index as u8)
+    }
+
+    pub const fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    /// The file of a letter from a to h.
+    pub fn from_char(letter: char) -> Option<File> {
+        ('a'..='h').contains(&letter).then(|| File(letter as u8 - b'a'))
+    }
+
+    pub fn to_char(self) -> char {
+        char::from(b'a' + self.0)
+    }
+
+    /// How many files apart `self` and `other` are.
+    pub fn distance(self, other: File) -> usize {
+        self.0.abs_diff(other.0) as usize
+    }
+}
+
+impl Rank {
+    pub const FIRST: Rank = Rank(0);
+    pub const EIGHTH: Rank = Rank(7);
+
+    /// The rank with index `index`, panics if it is off the board.
+    pub const fn new(index: usize) -> Self {
+        assert!(index < 8, "rank index out of range");
+        Rank(index as u8)
+    }
+
+    pub const fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    /// The rank of a digit from 1 to 8.
+    pub fn from_char(digit: char) -> Option<Rank> {
+        ('1'..='8').contains(&digit).then(|| Rank(digit as u8 - b'1'))
+    }
+
+    pub fn to_char(self) -> char {
+        char::from(b'1' + self.0)
+    }
+
+    /// How many ranks apart `self` and `other` are.
+    pub fn distance(self, other: Rank) -> usize {
+        self.0.abs_diff(other.0) as usize
+    }
+}
+
+impl Square {
+    /// The square with index `index`, panics if it is off the board.
+    pub const fn new(index: usize) -> Self {
+        assert!(index < 64, "square index out of range");
+        Square(index as u8)
+    }
+
+    pub const fn at(file: File, rank: Rank) -> Self {
+        Square(rank.0 * 8 + file.0)
+    }
+
+    pub const fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    pub const fn file(self) -> File {
+        File(self.0 % 8)
+    }
+
+    pub const fn rank(self) -> Rank {
+        Rank(self.0 / 8)
+    }
+
+    /// The square `files` to the right and `ranks` up (towards black), None if that is off the board.
+    pub fn offset(self, files: isize, ranks: isize) -> Option<Square> {
+        let file = self.file().0 as isize + files;
+        let rank = self.rank().0 as isize + ranks;
+
+        ((0..8).contains(&file) && (0..8).contains(&rank)).then(|| Square((rank * 8 + file) as u8))
+    }
+
+    /// Every square, a1 first.
+    pub fn all() -> impl Iterator<Item = Square> {
+        (0..64).map(Square)
+    }
+}
+
+macro_rules! squares {
+    ($($name:ident = $index:expr),* $(,)?) => {
+        impl Square {
+            $(pub const $name: Square = Square($index);)*
+        }
+    };
+}
+
+squares! {
+    A1 = 0, B1 = 1, C1 = 2, D1 = 3, E1 = 4, F1 = 5, G1 = 6, H1 = 7,
+    A2 = 8, B2 = 9, C2 = 10, D2 = 11, E2 = 12, F2 = 13, G2 = 14, H2 = 15,
+    A3 = 16, B3 = 17, C3 = 18, D3 = 19, E3 = 20, F3 = 21, G3 = 22, H3 = 23,
+    A4 = 24, B4 = 25, C4 = 26, D4 = 27, E4 = 28, F4 = 29, G4 = 30, H4 = 31,
+    A5 = 32, B5 = 33, C5 = 34, D5 = 35, E5 = 36, F5 = 37, G5 = 38, H5 = 39,
+    A6 = 40, B6 = 41, C6 = 42, D6 = 43, E6 = 44, F6 = 45, G6 = 46, H6 = 47,
+    A7 = 48, B7 = 49, C7 = 50, D7 = 51, E7 = 52, F7 = 53, G7 = 54, H7 = 55,
+    A8 = 56, B8 = 57, C8 = 58, D8 = 59, E8 = 60, F8 = 61, G8 = 62, H8 = 63,
+}
+
+impl From<usize> for Square {
+    fn from(index: usize) -> Self {
+        Square::new(index)
+    }
+}
+
+impl From<Square> for usize {
+    fn from(square: Square) -> Self {
+        square.index()
+    }
+}
+
+impl FromStr for Square {
+    type Err = ParseSquareError;
+
+    /// Reads a square name like `e4`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+
+        match (chars.next().and_then(File::from_char), chars.next().and_then(Rank::from_char), chars.next()) {
+            (Some(file), Some(rank), None) => { Ok(Square::at(file, rank)) }
+            _ => { Err(ParseSquareError(s.to_string())) }
+        }
+    }
+}
+
+impl Display for File {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+impl Display for Rank {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+impl Display for Square {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.file(), self.rank())
+    }
+}
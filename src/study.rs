@@ -0,0 +1,203 @@
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use crate::chess::Move;
+use crate::json::{parse_json, Json};
+use crate::square::Square;
+use crate::uci::parse_move_string;
+
+/// The colors arrows and highlights come in, written as their first letter like in `[%cal Ge2e4]`.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MarkColor {
+    Green,
+    Red,
+    Yellow,
+    Blue,
+}
+
+impl MarkColor {
+    pub const ALL: [MarkColor; 4] = [MarkColor::Green, MarkColor::Red, MarkColor::Yellow, MarkColor::Blue];
+
+    pub fn letter(self) -> char {
+        match self {
+            MarkColor::Green => { 'G' }
+            MarkColor::Red => { 'R' }
+            MarkColor::Yellow => { 'Y' }
+            MarkColor::Blue => { 'B' }
+        }
+    }
+
+    pub fn from_letter(letter: char) -> Option<MarkColor> {
+        MarkColor::ALL.into_iter().find(|x| x.letter() == letter)
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Arrow {
+    pub from: usize,
+    pub to: usize,
+    pub color: MarkColor,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Highlight {
+    pub square: usize,
+    pub color: MarkColor,
+}
+
+/// The arrows and highlighted squares drawn at one node of a game.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Markup {
+    pub arrows: Vec<Arrow>,
+    pub highlights: Vec<Highlight>,
+}
+
+impl Markup {
+    pub fn is_empty(&self) -> bool {
+        self.arrows.is_empty() && self.highlights.is_empty()
+    }
+
+    /// Adds the arrow, or removes it if it is already there in that color. Other colors are replaced.
+    pub fn toggle_arrow(&mut self, arrow: Arrow) {
+        let existing = self.arrows.iter().position(|x| x.from == arrow.from && x.to == arrow.to);
+
+        if let Some(i) = existing {
+            if self.arrows.remove(i).color == arrow.color { return; }
+        }
+        self.arrows.push(arrow);
+    }
+
+    /// Like [`Markup::toggle_arrow`], for a square.
+    pub fn toggle_highlight(&mut self, highlight: Highlight) {
+        let existing = self.highlights.iter().position(|x| x.square == highlight.square);
+
+        if let Some(i) = existing {
+            if self.highlights.remove(i).color == highlight.color { return; }
+        }
+        self.highlights.push(highlight);
+    }
+}
+
+/// The markup of a game's nodes. PGN has no standard place for it, so it is kept in
+/// a JSON file next to the game (see [`Study::sidecar_path`]), while notes go in the PGN's comments.
+///
+/// Nodes are found by the moves leading to them, so editing the variation tree doesn't lose them.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Study {
+    pub nodes: Vec<(Vec<Move>, Markup)>,
+}
+
+impl Study {
+    /// The sidecar of `game.pgn` is `game.study.json`.
+    pub fn sidecar_path(pgn: &Path) -> PathBuf {
+        pgn.with_extension("study.json")
+    }
+
+    /// Reads the sidecar of the pgn file at `pgn`, a game without one has an empty study.
+    pub fn load(pgn: &Path) -> std::io::Result<Study> {
+        match std::fs::read_to_string(Study::sidecar_path(pgn)) {
+            Ok(text) => { Ok(Study::parse(&text)) }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => { Ok(Study::default()) }
+            Err(err) => { Err(err) }
+        }
+    }
+
+    /// Writes the sidecar of the pgn file at `pgn`, removing it once the study is empty.
+    pub fn save(&self, pgn: &Path) -> std::io::Result<()> {
+        let path = Study::sidecar_path(pgn);
+
+        if self.nodes.iter().all(|x| x.1.is_empty()) {
+            return match std::fs::remove_file(path) {
+                Err(err) if err.kind() != std::io::ErrorKind::NotFound => { Err(err) }
+                _ => { Ok(()) }
+            };
+        }
+
+        std::fs::write(path, self.to_string())
+    }
+
+    /// Reads a study written by its `Display`, nodes and marks that can't be read are skipped.
+    pub fn parse(text: &str) -> Study {
+        let mut chars = text.chars().peekable();
+        let Some(Json::Object(root)) = parse_json(&mut chars) else { return Study::default(); };
+        let Some((_, Json::Array(nodes))) = root.into_iter().find(|x| x.0 == "nodes") else { return Study::default(); };
+
+        let nodes = nodes.into_iter().filter_map(|node| {
+            let Json::Object(fields) = node else { return None; };
+            let field = |name: &str| fields.iter().find(|x| x.0 == name).map(|x| &x.1);
+
+            let Some(Json::String(moves)) = field("moves") else { return None; };
+            let moves: Option<Vec<Move>> = moves.split_whitespace().map(parse_move_string).collect();
+
+            let strings = |name: &str| match field(name) {
+                Some(Json::Array(values)) => {
+                    values.iter().filter_map(|x| if let Json::String(x) = x { Some(x.as_str()) } else { None }).collect()
+                }
+                _ => { Vec::new() }
+            };
+
+            let arrows = strings("arrows").into_iter().filter_map(|x| {
+                let color = MarkColor::from_letter(x.chars().next()?)?;
+                let squares = parse_move_string(x.get(1..)?)?;
+
+                Some(Arrow { from: squares.from.index(), to: squares.to.index(), color })
+            }).collect();
+
+            let highlights = strings("highlights").into_iter().filter_map(|x| {
+                let color = MarkColor::from_letter(x.chars().next()?)?;
+                let square = x.get(1..)?.parse::<Square>().ok()?.index();
+
+                Some(Highlight { square, color })
+            }).collect();
+
+            Some((moves?, Markup { arrows, highlights }))
+        }).collect();
+
+        Study { nodes }
+    }
+
+    /// The markup of the node reached by `moves` from the start of the game.
+    pub fn markup(&self, moves: &[Move]) -> Option<&Markup> {
+        self.nodes.iter().find(|x| x.0 == moves).map(|x| &x.1)
+    }
+
+    pub fn markup_mut(&mut self, moves: &[Move]) -> &mut Markup {
+        let i = match self.nodes.iter().position(|x| x.0 == moves) {
+            Some(i) => { i }
+            None => {
+                self.nodes.push((moves.to_vec(), Markup::default()));
+                self.nodes.len() - 1
+            }
+        };
+
+        &mut self.nodes[i].1
+    }
+}
+
+impl Display for Study {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{{")?;
+        writeln!(f, "  \"nodes\": [")?;
+
+        let nodes: Vec<_> = self.nodes.iter().filter(|x| !x.1.is_empty()).collect();
+        for (i, (moves, markup)) in nodes.iter().enumerate() {
+            let moves: Vec<String> = moves.iter().map(|x| x.to_string()).collect();
+            let arrows: Vec<String> = markup.arrows.iter()
+                .map(|x| format!("\"{}{}{}\"", x.color.letter(), Square::new(x.from), Square::new(x.to)))
+                .collect();
+            let highlights: Vec<String> = markup.highlights.iter()
+                .map(|x| format!("\"{}{}\"", x.color.letter(), Square::new(x.square)))
+                .collect();
+
+            let comma = if i + 1 < nodes.len() { "," } else { "" };
+            writeln!(f, "    {{\"moves\": \"{}\", \"arrows\": [{}], \"highlights\": [{}]}}{}",
+                     moves.join(" "), arrows.join(", "), highlights.join(", "), comma)?;
+        }
+
+        writeln!(f, "  ]")?;
+        writeln!(f, "}}")
+    }
+}
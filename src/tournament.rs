@@ -0,0 +1,809 @@
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
+use std::sync::{Arc, Mutex};
+use crate::adjudication::Adjudication;
+use crate::archive::pgn_date;
+use crate::chess::Color;
+use crate::epd::Epd;
+use crate::error::{ChessError, UciError};
+use crate::history::History;
+use crate::json;
+use crate::pgn::{san, Pgn};
+use crate::result::GameResult;
+use crate::simulate::play_out;
+use crate::sprt::{expected_score, Sprt, SprtVerdict};
+use crate::uci::{EngineConfig, Limits, Uci};
+
+/// A position engine games start from, with the moves that led to it.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Opening {
+    pub name: String,
+    pub history: History,
+}
+
+impl Opening {
+    /// The moves of `pgn`, named by its `Opening` or `ECO` tag, or else by the moves themselves.
+    pub fn from_pgn(pgn: &Pgn) -> Self {
+        let name = pgn.tag("Opening").or(pgn.tag("ECO")).filter(|x| *x != "?")
+            .map(String::from)
+            .unwrap_or_else(|| move_text(&pgn.history));
+
+        Opening { name, history: pgn.history.clone() }
+    }
+
+    /// The position of `epd`, named by its `id` or else its FEN.
+    pub fn from_epd(epd: &Epd) -> Self {
+        let name = epd.id().map(String::from).unwrap_or_else(|| epd.game.as_fen());
+
+        Opening { name, history: History::new(epd.game) }
+    }
+}
+
+// "1. e4 c5 2. Nf3", or "startpos" if no moves were played
+fn move_text(history: &History) -> String {
+    let mut words = Vec::new();
+    let mut game = history.start();
+
+    for (i, mv) in history.moves().enumerate() {
+        if game.turn == Color::White {
+            words.push(format!("{}.", game.fm_clock()));
+        } else if i == 0 {
+            words.push(format!("{}...", game.fm_clock()));
+        }
+
+        words.push(san(&game, mv));
+        game.move_checked(mv.from.index(), mv.to.index(), mv.promotion);
+    }
+
+    if words.is_empty() { "startpos".to_string() } else { words.join(" ") }
+}
+
+/// The openings a match is played from, each one twice with the colors reversed.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct OpeningSuite {
+    pub openings: Vec<Opening>,
+}
+
+impl OpeningSuite {
+    /// Every game of a PGN file, played up to its last move.
+    pub fn from_pgn(text: &str) -> Result<Self, ChessError> {
+        let games = Pgn::parse_many(text)?;
+        Ok(OpeningSuite { openings: games.iter().map(Opening::from_pgn).collect() })
+    }
+
+    /// Every position of an EPD file.
+    pub fn from_epd(text: &str) -> Result<Self, ChessError> {
+        let epds = Epd::parse_many(text)?;
+        Ok(OpeningSuite { openings: epds.iter().map(Opening::from_epd).collect() })
+    }
+
+    /// Reads either format, PGN files are told apart by their tag pairs or move numbers.
+    pub fn parse(text: &str) -> Result<Self, ChessError> {
+        let first = text.lines().map(str::trim).find(|x| !x.is_empty()).unwrap_or_default();
+
+        if first.starts_with('[') || first.starts_with("1.") {
+            OpeningSuite::from_pgn(text)
+        } else {
+            OpeningSuite::from_epd(text)
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.openings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.openings.is_empty()
+    }
+}
+
+/// A finished engine game.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct GameRecord {
+    pub round: usize,
+    // indices into the tournament's engines
+    pub white: usize,
+    pub black: usize,
+    // name of the opening the game started from, if the tournament had a suite
+    pub opening: Option<String>,
+    pub result: GameResult,
+    pub pgn: Pgn,
+}
+
+impl GameRecord {
+    /// The points `engine` got from this game, nothing if it didn't play.
+    pub fn score(&self, engine: usize) -> f32 {
+        let color = if engine == self.white { Color::White } else if engine == self.black { Color::Black } else { return 0.0; };
+
+        self.result.score(color)
+    }
+}
+
+/// One game of the schedule, `opening` indexes the suite.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Pairing {
+    pub round: usize,
+    pub white: usize,
+    pub black: usize,
+    pub opening: Option<usize>,
+}
+
+/// A round robin between UCI engines, an engine match is one with two engines.
+#[derive(Clone)]
+pub struct Tournament {
+    pub engines: Vec<EngineConfig>,
+    pub openings: OpeningSuite,
+    pub rounds: usize,
+    pub limits: Limits,
+    pub adjudication: Option<Adjudication>,
+    // games played at the same time, each by its own engine processes
+    pub concurrency: usize,
+    // stops the tournament once the first engine is shown better or not better than the second
+    pub sprt: Option<Sprt>,
+}
+
+impl Tournament {
+    /// One round from the start position at 100 ms a move, one game at a time, with no adjudication.
+    pub fn new(engines: Vec<EngineConfig>) -> Self {
+        Tournament {
+            engines,
+            openings: OpeningSuite::default(),
+            rounds: 1,
+            limits: Limits::default().time(100),
+            adjudication: None,
+            concurrency: 1,
+            sprt: None,
+        }
+    }
+
+    pub fn openings(mut self, openings: OpeningSuite) -> Self {
+        self.openings = openings;
+        self
+    }
+
+    pub fn rounds(mut self, rounds: usize) -> Self {
+        self.rounds = rounds;
+        self
+    }
+
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn adjudication(mut self, adjudication: Option<Adjudication>) -> Self {
+        self.adjudication = adjudication;
+        self
+    }
+
+    /// Plays up to `concurrency` games at once, at least one.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Ends the tournament early once `sprt` settles between the first two engines,
+    /// the schedule is then the most games that will be played.
+    pub fn sprt(mut self, sprt: Option<Sprt>) -> Self {
+        self.sprt = sprt;
+        self
+    }
+
+    /// Every game in playing order. Each pair of engines plays every opening twice per round,
+    /// once from each side.
+    pub fn schedule(&self) -> Vec<Pairing> {
+        let openings: Vec<Option<usize>> = if self.openings.is_empty() { vec![None] } else { (0..self.openings.len()).map(Some).collect() };
+        let mut pairings = Vec::new();
+
+        for round in 1..=self.rounds {
+            for a in 0..self.engines.len() {
+                for b in a + 1..self.engines.len() {
+                    for &opening in &openings {
+                        pairings.push(Pairing { round, white: a, black: b, opening });
+                        pairings.push(Pairing { round, white: b, black: a, opening });
+                    }
+                }
+            }
+        }
+
+        pairings
+    }
+
+    /// Plays a single game of the schedule with the engines given.
+    pub fn play(&self, pairing: Pairing, white: &mut Uci, black: &mut Uci) -> Result<GameRecord, UciError> {
+        let opening = pairing.opening.and_then(|x| self.openings.openings.get(x));
+        let start = opening.map(|x| x.history.clone()).unwrap_or_default();
+
+        white.new_game()?;
+        black.new_game()?;
+        let (history, result) = play_out(white, black, start, self.limits, self.adjudication)?;
+
+        let mut pgn = Pgn::new(history);
+        pgn.set_tag("Event", "Engine tournament");
+        pgn.set_tag("Date", pgn_date());
+        pgn.set_tag("Round", pairing.round.to_string());
+        pgn.set_tag("White", self.engines[pairing.white].name.as_str());
+        pgn.set_tag("Black", self.engines[pairing.black].name.as_str());
+        pgn.set_tag("TimeControl", self.limits.pgn_time_control());
+
+        // what the engines call themselves, the names above are the ones they were set up with
+        for (side, engine) in [("White", &*white), ("Black", &*black)] {
+            let Some((name, version)) = engine.name_and_version() else { continue; };
+            pgn.set_tag(format!("{}Engine", side), name);
+            if let Some(version) = version { pgn.set_tag(format!("{}EngineVersion", side), version); }
+        }
+
+        let mut record = GameRecord {
+            round: pairing.round,
+            white: pairing.white,
+            black: pairing.black,
+            opening: opening.map(|x| x.name.clone()),
+            result,
+            pgn,
+        };
+
+        result.write_tags(&mut record.pgn);
+        if let Some(name) = &record.opening { record.pgn.set_tag("Opening", name.as_str()); }
+
+        Ok(record)
+    }
+
+    /// Plays the whole schedule, `on_game` is called as each game finishes.
+    pub fn run(self, mut on_game: impl FnMut(&GameRecord)) -> Result<TournamentResults, UciError> {
+        let running = self.start();
+
+        for message in &running.receiver {
+            on_game(&message?);
+        }
+
+        Ok(running.results())
+    }
+
+    /// Starts playing the schedule in the background and returns right away.
+    ///
+    /// Each of the `concurrency` workers takes the next game of the schedule and plays it with
+    /// engine processes of its own, so searches of different games never share an engine.
+    pub fn start(self) -> RunningTournament {
+        let schedule = self.schedule();
+        let workers = self.concurrency.max(1).min(schedule.len());
+        let (sender, receiver) = channel();
+
+        let running = RunningTournament {
+            receiver,
+            results: Arc::new(Mutex::new(TournamentResults::new(&self))),
+            stopped: Arc::new(AtomicBool::new(false)),
+            total: schedule.len(),
+        };
+
+        let tournament = Arc::new(self);
+        let schedule = Arc::new(schedule);
+        let next = Arc::new(AtomicUsize::new(0));
+
+        for _ in 0..workers {
+            let (tournament, schedule, next, sender) = (tournament.clone(), schedule.clone(), next.clone(), sender.clone());
+            let (results, stopped) = (running.results.clone(), running.stopped.clone());
+
+            crate::platform::spawn(move || {
+                // started the first time this worker needs them
+                let mut engines: Vec<Option<Uci>> = tournament.engines.iter().map(|_| None).collect();
+
+                while !stopped.load(Ordering::SeqCst) {
+                    let Some(&pairing) = schedule.get(next.fetch_add(1, Ordering::SeqCst)) else { break; };
+
+                    let record = pair_mut(&mut engines, pairing.white, pairing.black, |i| Uci::with_config(&tournament.engines[i]))
+                        .and_then(|(white, black)| tournament.play(pairing, white, black));
+
+                    match record {
+                        Ok(record) => {
+                            // a poisoned lock only means another worker panicked, the results are still whole
+                            if results.lock().unwrap_or_else(|x| x.into_inner()).record(record.clone()).is_some() {
+                                stopped.store(true, Ordering::SeqCst);
+                            }
+                            let _ = sender.send(Ok(record));
+                        }
+                        Err(err) => {
+                            // the other games would only be scored against a missing engine
+                            stopped.store(true, Ordering::SeqCst);
+                            let _ = sender.send(Err(err));
+                        }
+                    }
+                }
+            });
+        }
+
+        running
+    }
+}
+
+// both engines of a game, borrowed from the same list and started if they aren't yet
+fn pair_mut(engines: &mut [Option<Uci>], a: usize, b: usize,
+            start: impl Fn(usize) -> Result<Uci, UciError>) -> Result<(&mut Uci, &mut Uci), UciError> {
+    for i in [a, b] {
+        if engines[i].is_none() { engines[i] = Some(start(i)?); }
+    }
+
+    let (first, second) = if a < b {
+        let (low, high) = engines.split_at_mut(b);
+        (&mut low[a], &mut high[0])
+    } else {
+        let (low, high) = engines.split_at_mut(a);
+        (&mut high[0], &mut low[b])
+    };
+
+    Ok((first.as_mut().unwrap(), second.as_mut().unwrap()))
+}
+
+/// A tournament playing in the background, see [`Tournament::start`].
+pub struct RunningTournament {
+    receiver: Receiver<Result<GameRecord, UciError>>,
+    results: Arc<Mutex<TournamentResults>>,
+    stopped: Arc<AtomicBool>,
+    total: usize,
+}
+
+impl RunningTournament {
+    /// The next game that finished, or the error that stopped the tournament.
+    pub fn try_game(&self) -> Option<Result<GameRecord, UciError>> {
+        match self.receiver.try_recv() {
+            Ok(message) => { Some(message) }
+            Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => { None }
+        }
+    }
+
+    /// The games finished so far.
+    pub fn results(&self) -> TournamentResults {
+        self.results.lock().unwrap_or_else(|x| x.into_inner()).clone()
+    }
+
+    /// Number of games in the schedule.
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Whether every game was played, or the tournament was stopped and its last games finished.
+    pub fn is_finished(&self) -> bool {
+        let played = self.results.lock().unwrap_or_else(|x| x.into_inner()).games.len();
+        played == self.total || (self.stopped.load(Ordering::SeqCst) && Arc::strong_count(&self.results) == 1)
+    }
+
+    /// Starts no more games, the ones being played are finished first.
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+}
+
+impl Drop for RunningTournament {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// The games of a tournament as they finished.
+#[derive(Clone, PartialEq, Debug)]
+pub struct TournamentResults {
+    pub names: Vec<String>,
+    pub games: Vec<GameRecord>,
+    pub sprt: Option<Sprt>,
+    // the log likelihood ratio after each game between the first two engines
+    pub llr: Vec<f64>,
+    pub verdict: Option<SprtVerdict>,
+}
+
+impl TournamentResults {
+    pub fn new(tournament: &Tournament) -> Self {
+        TournamentResults {
+            names: tournament.engines.iter().map(|x| x.name.clone()).collect(),
+            games: Vec::new(),
+            sprt: tournament.sprt,
+            llr: Vec::new(),
+            verdict: None,
+        }
+    }
+
+    /// Adds a finished game and runs the sprt on it, returning its verdict once there is one.
+    pub fn record(&mut self, game: GameRecord) -> Option<SprtVerdict> {
+        let between = [game.white, game.black];
+        self.games.push(game);
+
+        if let Some(sprt) = self.sprt.filter(|_| between.contains(&0) && between.contains(&1)) {
+            let (wins, draws, losses) = self.wdl(0, 1);
+            let llr = sprt.llr(wins, draws, losses);
+
+            self.llr.push(llr);
+            self.verdict = self.verdict.or(sprt.verdict(llr));
+        }
+
+        self.verdict
+    }
+
+    /// Wins, draws and losses of `engine` against `opponent`.
+    pub fn wdl(&self, engine: usize, opponent: usize) -> (u32, u32, u32) {
+        let mut wdl = (0, 0, 0);
+
+        for game in self.games.iter().filter(|x| (x.white, x.black) == (engine, opponent) || (x.white, x.black) == (opponent, engine)) {
+            let color = if game.white == engine { Color::White } else { Color::Black };
+
+            match game.result.winner {
+                Some(winner) if winner == color => { wdl.0 += 1; }
+                Some(_) => { wdl.2 += 1; }
+                None => { wdl.1 += 1; }
+            }
+        }
+
+        wdl
+    }
+
+    /// Points `engine` scored over all its games.
+    pub fn score(&self, engine: usize) -> f32 {
+        self.games.iter().map(|x| x.score(engine)).sum()
+    }
+
+    /// Number of games `engine` played.
+    pub fn played(&self, engine: usize) -> usize {
+        self.games.iter().filter(|x| x.white == engine || x.black == engine).count()
+    }
+
+    /// Points every engine scored against every other.
+    pub fn crosstable(&self) -> Crosstable {
+        let mut crosstable = Crosstable::new(self.names.clone());
+        for game in &self.games {
+            crosstable.add(game);
+        }
+
+        crosstable
+    }
+
+    /// Every engine's rating, fitted to all the games at once the way ordo does: the ratings under which
+    /// each engine's expected points are the points it scored, averaging 0 over the engines that played.
+    /// An engine that won or lost everything is held at [`MAX_ELO`] from the average.
+    pub fn ratings(&self) -> Vec<EloEstimate> {
+        let n = self.names.len();
+        let crosstable = self.crosstable();
+        let mut elo = vec![0.0; n];
+        let played: Vec<usize> = (0..n).filter(|x| self.played(*x) > 0).collect();
+
+        // (expected points, fisher information) of `engine` at the current ratings
+        let expectation = |elo: &[f64], engine: usize| {
+            let mut sum = (0.0, 0.0);
+            for opponent in (0..n).filter(|x| *x != engine) {
+                let (games, expected) = (crosstable.games(engine, opponent) as f64, expected_score(elo[engine] - elo[opponent]));
+
+                sum.0 += games * expected;
+                sum.1 += games * expected * (1.0 - expected) * ELO_SLOPE * ELO_SLOPE;
+            }
+            sum
+        };
+
+        // newton steps one engine at a time, moving them all at once overshoots
+        for _ in 0..FIT_ITERATIONS {
+            let before = elo.clone();
+
+            for &engine in &played {
+                let (expected, information) = expectation(&elo, engine);
+                if information <= 0.0 { continue; }
+
+                elo[engine] += ((crosstable.total(engine) as f64 - expected) * ELO_SLOPE / information).clamp(-100.0, 100.0);
+            }
+
+            let mean = played.iter().map(|x| elo[*x]).sum::<f64>() / played.len().max(1) as f64;
+            for &engine in &played {
+                elo[engine] = (elo[engine] - mean).clamp(-MAX_ELO, MAX_ELO);
+            }
+
+            if elo.iter().zip(&before).all(|(a, b)| (a - b).abs() < 1e-6) { break; }
+        }
+
+        (0..n).map(|engine| {
+            let (_, information) = expectation(&elo, engine);
+            let error = if information > 0.0 { 1.96 / information.sqrt() } else { f64::INFINITY };
+
+            EloEstimate { elo: elo[engine], error }
+        }).collect()
+    }
+
+    /// The engines best rated first, by points where the ratings are equal.
+    pub fn standings(&self) -> Vec<usize> {
+        let ratings = self.ratings();
+        let mut standings: Vec<usize> = (0..self.names.len()).collect();
+        standings.sort_by(|a, b| ratings[*b].elo.total_cmp(&ratings[*a].elo).then(self.score(*b).total_cmp(&self.score(*a))));
+
+        standings
+    }
+
+    /// The score of every pair of engines that played each other, from the first engine's side.
+    pub fn pairs(&self) -> Vec<PairScore> {
+        let n = self.names.len();
+        let mut pairs = Vec::new();
+
+        for engine in 0..n {
+            for opponent in engine + 1..n {
+                let (wins, draws, losses) = self.wdl(engine, opponent);
+                if wins + draws + losses > 0 { pairs.push(PairScore { engine, opponent, wins, draws, losses }); }
+            }
+        }
+
+        pairs
+    }
+
+    /// The standings as csv: rank, name, elo, error, points, games and score in percent.
+    pub fn to_csv(&self) -> String {
+        let ratings = self.ratings();
+        let mut csv = String::from("rank,name,elo,error,points,games,score\n");
+
+        for (rank, engine) in self.standings().into_iter().enumerate() {
+            let games = self.played(engine);
+            csv += &format!("{},{},{:.1},{:.1},{},{},{:.1}\n", rank + 1, csv_field(&self.names[engine]), ratings[engine].elo,
+                            ratings[engine].error, self.score(engine), games, percent(self.score(engine), games));
+        }
+
+        csv
+    }
+
+    /// The pair scores as csv, with the elo difference each implies.
+    pub fn pairs_csv(&self) -> String {
+        let mut csv = String::from("engine,opponent,wins,draws,losses,points,games,elo,error\n");
+
+        for pair in self.pairs() {
+            let elo = pair.elo();
+            csv += &format!("{},{},{},{},{},{},{},{:.1},{:.1}\n", csv_field(&self.names[pair.engine]), csv_field(&self.names[pair.opponent]),
+                            pair.wins, pair.draws, pair.losses, pair.points(), pair.games(), elo.elo, elo.error);
+        }
+
+        csv
+    }
+
+    /// Everything in one json object: `engines` in the order of the standings, `pairs`, the `crosstable`
+    /// with a row of points for each engine (null against itself) and the `games`.
+    pub fn to_json(&self) -> String {
+        let ratings = self.ratings();
+        let crosstable = self.crosstable();
+        let mut json = String::from("{\n  \"engines\": [\n");
+
+        let engines: Vec<String> = self.standings().into_iter().map(|engine| {
+            format!("    {{\"name\": {}, \"elo\": {}, \"error\": {}, \"points\": {}, \"games\": {}}}", json::quote(&self.names[engine]),
+                    json_number(ratings[engine].elo), json_number(ratings[engine].error), self.score(engine), self.played(engine))
+        }).collect();
+        json += &engines.join(",\n");
+
+        json += "\n  ],\n  \"pairs\": [\n";
+        let pairs: Vec<String> = self.pairs().into_iter().map(|pair| {
+            let elo = pair.elo();
+            format!("    {{\"engine\": {}, \"opponent\": {}, \"wins\": {}, \"draws\": {}, \"losses\": {}, \"elo\": {}, \"error\": {}}}",
+                    json::quote(&self.names[pair.engine]), json::quote(&self.names[pair.opponent]), pair.wins, pair.draws, pair.losses,
+                    json_number(elo.elo), json_number(elo.error))
+        }).collect();
+        json += &pairs.join(",\n");
+
+        json += "\n  ],\n  \"crosstable\": [\n";
+        let rows: Vec<String> = (0..self.names.len()).map(|engine| {
+            let cells: Vec<String> = (0..self.names.len())
+                .map(|opponent| if engine == opponent { "null".to_string() } else { crosstable.score(engine, opponent).to_string() })
+                .collect();
+            format!("    [{}]", cells.join(", "))
+        }).collect();
+        json += &rows.join(",\n");
+
+        json += "\n  ],\n  \"games\": [\n";
+        let games: Vec<String> = self.games.iter().map(|game| {
+            let opening = game.opening.as_deref().map(json::quote).unwrap_or_else(|| "null".to_string());
+            format!("    {{\"round\": {}, \"white\": {}, \"black\": {}, \"result\": \"{}\", \"termination\": \"{}\", \"opening\": {}}}",
+                    game.round, json::quote(&self.names[game.white]), json::quote(&self.names[game.black]), game.result.pgn_result(),
+                    game.result.reason.pgn_tag(), opening)
+        }).collect();
+        json += &games.join(",\n");
+
+        json += "\n  ]\n}\n";
+        json
+    }
+}
+
+impl Display for TournamentResults {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, game) in self.games.iter().enumerate() {
+            write!(f, "{}. {} - {} {} ({})", i + 1, self.names[game.white], self.names[game.black], game.result.pgn_result(), game.result.reason)?;
+
+            if let Some(opening) = &game.opening {
+                write!(f, " {}", opening)?;
+            }
+            writeln!(f)?;
+        }
+
+        // the rating list the way ordo prints it
+        let ratings = self.ratings();
+        let width = self.names.iter().map(|x| x.len()).max().unwrap_or(0).max("PLAYER".len());
+
+        writeln!(f, "{:>4} {:width$} : {:>7} {:>6} {:>7} {:>6} {:>6}", "#", "PLAYER", "RATING", "ERROR", "POINTS", "PLAYED", "(%)")?;
+        for (rank, engine) in self.standings().into_iter().enumerate() {
+            let games = self.played(engine);
+            writeln!(f, "{:>4} {:width$} : {:>7.1} {:>6.1} {:>7} {:>6} {:>6.1}", rank + 1, self.names[engine], ratings[engine].elo,
+                     ratings[engine].error, self.score(engine), games, percent(self.score(engine), games))?;
+        }
+
+        if self.names.len() > 2 {
+            write!(f, "{}", self.crosstable())?;
+        }
+
+        for pair in self.pairs() {
+            writeln!(f, "{} - {}: {}", self.names[pair.engine], self.names[pair.opponent], pair)?;
+        }
+
+        if let Some(sprt) = self.sprt {
+            writeln!(f, "{}", sprt)?;
+
+            let llr: Vec<String> = self.llr.iter().map(|x| format!("{:.2}", x)).collect();
+            writeln!(f, "LLR: {}", llr.join(" "))?;
+
+            if let Some(verdict) = self.verdict {
+                writeln!(f, "{}", verdict)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Points each engine scored against each other engine.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Crosstable {
+    pub names: Vec<String>,
+    // [engine][opponent]
+    scores: Vec<Vec<f32>>,
+    games: Vec<Vec<usize>>,
+}
+
+impl Crosstable {
+    pub fn new(names: Vec<String>) -> Self {
+        let n = names.len();
+        Crosstable { names, scores: vec![vec![0.0; n]; n], games: vec![vec![0; n]; n] }
+    }
+
+    pub fn add(&mut self, game: &GameRecord) {
+        let (white, black) = (game.white, game.black);
+
+        self.scores[white][black] += game.score(white);
+        self.scores[black][white] += game.score(black);
+        self.games[white][black] += 1;
+        self.games[black][white] += 1;
+    }
+
+    /// Points `engine` scored against `opponent`.
+    pub fn score(&self, engine: usize, opponent: usize) -> f32 {
+        self.scores[engine][opponent]
+    }
+
+    /// Games `engine` and `opponent` played against each other.
+    pub fn games(&self, engine: usize, opponent: usize) -> usize {
+        self.games[engine][opponent]
+    }
+
+    pub fn total(&self, engine: usize) -> f32 {
+        self.scores[engine].iter().sum()
+    }
+
+    /// The crosstable as csv, a header of engine names and then a row of points for each engine, empty
+    /// against itself, with its total at the end.
+    pub fn to_csv(&self) -> String {
+        let names: Vec<String> = self.names.iter().map(|x| csv_field(x)).collect();
+        let mut csv = format!(",{},total\n", names.join(","));
+
+        for (engine, name) in names.iter().enumerate() {
+            let cells: Vec<String> = (0..self.names.len())
+                .map(|opponent| if engine == opponent { String::new() } else { self.score(engine, opponent).to_string() })
+                .collect();
+            csv += &format!("{},{},{}\n", name, cells.join(","), self.total(engine));
+        }
+
+        csv
+    }
+}
+
+impl Display for Crosstable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let width = self.names.iter().map(|x| x.len()).max().unwrap_or(0);
+
+        write!(f, "{:width$}", "")?;
+        for i in 0..self.names.len() {
+            write!(f, " {:>5}", i + 1)?;
+        }
+        writeln!(f, " {:>6}", "Total")?;
+
+        for (engine, name) in self.names.iter().enumerate() {
+            write!(f, "{:width$}", name)?;
+
+            for opponent in 0..self.names.len() {
+                if engine == opponent {
+                    write!(f, " {:>5}", "-")?;
+                } else {
+                    write!(f, " {:>5}", self.score(engine, opponent))?;
+                }
+            }
+            writeln!(f, " {:>6}", self.total(engine))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// How far from the average the ratings fit by [`TournamentResults::ratings`] can go.
+pub const MAX_ELO: f64 = 1_000.0;
+
+const FIT_ITERATIONS: usize = 1_000;
+// the expected score of a rating difference `d` is the logistic function of `d * ELO_SLOPE`
+const ELO_SLOPE: f64 = std::f64::consts::LN_10 / 400.0;
+
+/// An elo estimate with the half width of its 95% confidence interval, infinite without games.
+#[derive(Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EloEstimate {
+    pub elo: f64,
+    pub error: f64,
+}
+
+impl Display for EloEstimate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:+.1} +/- {:.1}", self.elo, self.error)
+    }
+}
+
+/// The games between two engines, from `engine`'s side.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PairScore {
+    pub engine: usize,
+    pub opponent: usize,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl PairScore {
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    pub fn points(&self) -> f32 {
+        self.wins as f32 + self.draws as f32 / 2.0
+    }
+
+    /// The elo difference the score implies, the error from how much the results of the games spread.
+    pub fn elo(&self) -> EloEstimate {
+        let games = self.games() as f64;
+        if games == 0.0 { return EloEstimate { elo: 0.0, error: f64::INFINITY }; }
+
+        let p = self.points() as f64 / games;
+        let variance = (self.wins as f64 * (1.0 - p).powi(2) + self.draws as f64 * (0.5 - p).powi(2)
+            + self.losses as f64 * p.powi(2)) / games;
+
+        // held off 0 and 1, where the difference is infinite
+        let clamped = p.clamp(1e-3, 1.0 - 1e-3);
+        let elo = (-400.0 * (1.0 / clamped - 1.0).log10()).clamp(-MAX_ELO, MAX_ELO);
+        let error = 1.96 * (variance / games).sqrt() / (ELO_SLOPE * clamped * (1.0 - clamped));
+
+        EloEstimate { elo, error }
+    }
+}
+
+impl Display for PairScore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "+{} ={} -{} ({}/{}, {})", self.wins, self.draws, self.losses, self.points(), self.games(), self.elo())
+    }
+}
+
+fn percent(points: f32, games: usize) -> f64 {
+    if games == 0 { 0.0 } else { points as f64 * 100.0 / games as f64 }
+}
+
+// quoted if it holds a comma, a quote or a line break
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// json has no infinity, an error without games is null
+fn json_number(number: f64) -> String {
+    if number.is_finite() { format!("{:.1}", number) } else { "null".to_string() }
+}
@@ -0,0 +1,247 @@
+use crate::chess::{Game, Move};
+use crate::history::History;
+use crate::pgn::{MoveNotes, Pgn, Variation, VariationMove};
+
+/// A node of a game's variation tree: the position after `ply` moves of a line.
+///
+/// The line is found by following `branches` from the main line, each is
+/// (index of the move the variation replaces in its line, index of the variation).
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct NodePath {
+    pub branches: Vec<(usize, usize)>,
+    pub ply: usize,
+}
+
+impl NodePath {
+    /// The position after `ply` moves of the main line.
+    pub fn main_line(ply: usize) -> Self {
+        NodePath { branches: Vec::new(), ply }
+    }
+
+    pub fn is_main_line(&self) -> bool {
+        self.branches.is_empty()
+    }
+
+    // the node where the last variation branches off, just before the move it replaces
+    fn parent(&self) -> Option<NodePath> {
+        let (&(ply, _), branches) = self.branches.split_last()?;
+        Some(NodePath { branches: branches.to_vec(), ply })
+    }
+}
+
+impl Pgn {
+    /// The main line as a variation tree, the history's moves with their notes.
+    pub fn main_line(&self) -> Vec<VariationMove> {
+        self.history.moves().enumerate()
+            .map(|(ply, mv)| VariationMove { mv, notes: self.notes.get(ply).cloned().unwrap_or_default() })
+            .collect()
+    }
+
+    /// The moves of the line `path` is on, with their annotations and variations.
+    pub fn line(&self, path: &NodePath) -> Option<Vec<VariationMove>> {
+        let main = self.main_line();
+        let line = line(&main, &path.branches)?;
+
+        (path.ply <= line.len()).then(|| line.to_vec())
+    }
+
+    /// Every move from the start of the game to `path`.
+    pub fn moves_to(&self, path: &NodePath) -> Option<Vec<Move>> {
+        let main = self.main_line();
+        let mut line: &[VariationMove] = &main;
+        let mut moves = Vec::new();
+
+        for &(ply, variation) in &path.branches {
+            moves.extend(line.get(..ply)?.iter().map(|x| x.mv));
+            line = &line.get(ply)?.notes.variations.get(variation)?.moves;
+        }
+        moves.extend(line.get(..path.ply)?.iter().map(|x| x.mv));
+
+        Some(moves)
+    }
+
+    /// The position at `path`, `None` if the path isn't in the tree.
+    pub fn position_at(&self, path: &NodePath) -> Option<Game> {
+        let mut game = self.history.start();
+        for mv in self.moves_to(path)? {
+            game.make_move(mv).ok()?;
+        }
+
+        Some(game)
+    }
+
+    /// The comment at `path`: on the move leading to it, or the game's comment at the start.
+    pub fn comment_at(&self, path: &NodePath) -> Option<String> {
+        match path.ply.checked_sub(1) {
+            Some(ply) => { self.line(path)?.get(ply)?.notes.comment.clone() }
+            None if path.is_main_line() => { self.comment.clone() }
+            None => { None }
+        }
+    }
+
+    /// Replaces the comment at `path`, see [`Pgn::comment_at`]. `false` if the path isn't in the tree.
+    pub fn set_comment_at(&mut self, path: &NodePath, comment: Option<String>) -> bool {
+        let comment = comment.filter(|x| !x.trim().is_empty());
+        let Some(ply) = path.ply.checked_sub(1) else {
+            if path.is_main_line() { self.comment = comment; }
+            return path.is_main_line();
+        };
+
+        let mut main = self.main_line();
+        let Some(notes) = line_mut(&mut main, &path.branches).and_then(|x| x.get_mut(ply)).map(|x| &mut x.notes) else { return false; };
+        *notes = MoveNotes { comment, ..std::mem::take(notes) };
+
+        self.set_main_line(main);
+        true
+    }
+
+    /// The node after the next move of the line.
+    pub fn next(&self, path: &NodePath) -> Option<NodePath> {
+        let len = self.line(path)?.len();
+        (path.ply < len).then(|| NodePath { ply: path.ply + 1, ..path.clone() })
+    }
+
+    /// The moves that can follow `path` in the tree and the nodes after them, the line's own move first.
+    pub fn children(&self, path: &NodePath) -> Vec<(Move, NodePath)> {
+        let Some(line) = self.line(path) else { return Vec::new(); };
+        let Some(next) = line.get(path.ply) else { return Vec::new(); };
+
+        let mut children = vec![(next.mv, NodePath { ply: path.ply + 1, ..path.clone() })];
+        for (i, variation) in next.notes.variations.iter().enumerate() {
+            let Some(first) = variation.moves.first() else { continue; };
+
+            let mut branches = path.branches.clone();
+            branches.push((path.ply, i));
+            children.push((first.mv, NodePath { branches, ply: 1 }));
+        }
+
+        children
+    }
+
+    /// The node reached by playing `moves` from the start, through whichever lines have them.
+    pub fn find_path(&self, moves: &[Move]) -> Option<NodePath> {
+        let mut path = NodePath::default();
+        for mv in moves {
+            path = self.children(&path).into_iter().find(|x| x.0 == *mv)?.1;
+        }
+
+        Some(path)
+    }
+
+    /// The node before, leaving the variation after its first move.
+    pub fn previous(&self, path: &NodePath) -> Option<NodePath> {
+        match path.parent() {
+            Some(parent) if path.ply <= 1 => { Some(parent) }
+            _ if path.ply == 0 => { None }
+            _ => { Some(NodePath { ply: path.ply - 1, ..path.clone() }) }
+        }
+    }
+
+    /// Plays `mv` at `path` and returns the node after it. It follows the line or one of the
+    /// variations if they already have the move, or else starts a new variation (or extends the line at its end).
+    /// `None` if the move is illegal there.
+    pub fn play_at(&mut self, path: &NodePath, mv: Move) -> Option<NodePath> {
+        self.position_at(path)?.make_move(mv).ok()?;
+
+        let mut main = self.main_line();
+        let line = line_mut(&mut main, &path.branches)?;
+
+        let next = match line.get_mut(path.ply) {
+            None => {
+                line.push(VariationMove { mv, notes: Default::default() });
+                NodePath { ply: path.ply + 1, ..path.clone() }
+            }
+            Some(next) if next.mv == mv => { NodePath { ply: path.ply + 1, ..path.clone() } }
+            Some(next) => {
+                let variations = &mut next.notes.variations;
+                let variation = match variations.iter().position(|x| x.moves.first().is_some_and(|x| x.mv == mv)) {
+                    Some(variation) => { variation }
+                    None => {
+                        variations.push(Variation { moves: vec![VariationMove { mv, notes: Default::default() }] });
+                        variations.len() - 1
+                    }
+                };
+
+                let mut branches = path.branches.clone();
+                branches.push((path.ply, variation));
+                NodePath { branches, ply: 1 }
+            }
+        };
+
+        self.set_main_line(main);
+        Some(next)
+    }
+
+    /// Swaps the variation `path` is on with the line it branches from, returning where `path` ends up.
+    /// The line's moves from the branch on become a variation in the promoted one's place.
+    pub fn promote(&mut self, path: &NodePath) -> Option<NodePath> {
+        let (&(ply, variation), branches) = path.branches.split_last()?;
+
+        let mut main = self.main_line();
+        let parent = line_mut(&mut main, branches)?;
+        if parent.get(ply)?.notes.variations.get(variation)?.moves.is_empty() { return None; }
+
+        let mut replaced = parent.split_off(ply);
+        let mut siblings = std::mem::take(&mut replaced[0].notes.variations);
+        let mut promoted = siblings.remove(variation).moves;
+        siblings.insert(variation, Variation { moves: replaced });
+
+        // the alternatives to the replaced move are alternatives to the promoted one now
+        siblings.append(&mut promoted[0].notes.variations);
+        promoted[0].notes.variations = siblings;
+        parent.append(&mut promoted);
+
+        self.set_main_line(main);
+        Some(NodePath { branches: branches.to_vec(), ply: ply + path.ply })
+    }
+
+    /// Removes the variation `path` is on and returns the node it branched from.
+    pub fn delete_variation(&mut self, path: &NodePath) -> Option<NodePath> {
+        let parent = path.parent()?;
+        let &(ply, variation) = path.branches.last()?;
+
+        let mut main = self.main_line();
+        let variations = &mut line_mut(&mut main, &parent.branches)?.get_mut(ply)?.notes.variations;
+        if variation >= variations.len() { return None; }
+        variations.remove(variation);
+
+        self.set_main_line(main);
+        Some(parent)
+    }
+
+    // rebuilds the history from edited moves, keeping the times of the moves that stayed
+    fn set_main_line(&mut self, moves: Vec<VariationMove>) {
+        let start = self.history.start();
+        let old = std::mem::replace(&mut self.history, History::new(start));
+        let kept = old.moves().zip(&moves).take_while(|(a, b)| *a == b.mv).count();
+
+        self.notes.clear();
+        for (ply, mv) in moves.into_iter().enumerate() {
+            // every move was checked when it was played
+            if self.history.push(mv.mv).is_err() { break; }
+            if ply < kept {
+                let entry = &old.entries()[ply];
+                self.history.set_times(ply, entry.time, entry.clock);
+            }
+            self.notes.push(mv.notes);
+        }
+
+        self.trim_notes();
+    }
+}
+
+fn line<'a>(mut line: &'a [VariationMove], branches: &[(usize, usize)]) -> Option<&'a [VariationMove]> {
+    for &(ply, variation) in branches {
+        line = &line.get(ply)?.notes.variations.get(variation)?.moves;
+    }
+
+    Some(line)
+}
+
+fn line_mut<'a>(mut line: &'a mut Vec<VariationMove>, branches: &[(usize, usize)]) -> Option<&'a mut Vec<VariationMove>> {
+    for &(ply, variation) in branches {
+        line = &mut line.get_mut(ply)?.notes.variations.get_mut(variation)?.moves;
+    }
+
+    Some(line)
+}
@@ -0,0 +1,196 @@
+//! Lessons on the rules for someone new to chess, played out on the board.
+//!
+//! A [`Lesson`] is a position and the [`Step`]s asked of the player in it, one move each. A [`Scenario`]
+//! plays a lesson: it checks each move against what the step asks for and then makes the step's scripted
+//! reply, or hands the turn straight back if there is none, so the player always has the white pieces.
+
+use crate::chess::{Game, Move, MoveFlags, MoveResult};
+use crate::uci::parse_move_string;
+
+/// What a step asks the player to do.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Goal {
+    /// Play exactly the step's hint.
+    Exact,
+    Capture,
+    Check,
+    Checkmate,
+    Castle,
+    EnPassant,
+    Promotion,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Step {
+    pub prompt: &'static str,
+    pub goal: Goal,
+    /// A move that does what's asked, shown when the player gets stuck.
+    pub hint: Move,
+    /// Black's answer, played once the step is done.
+    pub reply: Option<Move>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Lesson {
+    pub title: &'static str,
+    pub fen: &'static str,
+    pub steps: Vec<Step>,
+}
+
+/// How a move went down with the step it was played for.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Attempt {
+    /// It was played, and the scenario is on to the next step (or finished).
+    Correct,
+    /// A legal move, but not what the step asks for. Nothing was played.
+    Wrong,
+    /// Not a legal move. Nothing was played.
+    Illegal,
+}
+
+/// A lesson being played.
+#[derive(Clone, Debug)]
+pub struct Scenario {
+    lesson: Lesson,
+    game: Game,
+    step: usize,
+}
+
+impl Scenario {
+    pub fn new(lesson: Lesson) -> Self {
+        let game = Game::from_fen(lesson.fen).expect("lesson positions are valid fen");
+        Scenario { lesson, game, step: 0 }
+    }
+
+    pub fn lesson(&self) -> &Lesson {
+        &self.lesson
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// The step being asked for, None once the lesson is done.
+    pub fn step(&self) -> Option<&Step> {
+        self.lesson.steps.get(self.step)
+    }
+
+    /// Steps done so far.
+    pub fn progress(&self) -> usize {
+        self.step
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.step >= self.lesson.steps.len()
+    }
+
+    /// Back to the lesson's first step and position.
+    pub fn restart(&mut self) {
+        *self = Scenario::new(self.lesson.clone());
+    }
+
+    /// Plays `mv` if it does what the current step asks.
+    pub fn try_move(&mut self, mv: Move) -> Attempt {
+        let Some(step) = self.step().cloned() else { return Attempt::Illegal; };
+
+        let mut after = self.game;
+        let Ok(result) = after.make_move(mv) else { return Attempt::Illegal; };
+
+        let flags = self.game.move_flags(mv);
+        let done = match step.goal {
+            Goal::Exact => { (mv.from, mv.to, mv.promotion) == (step.hint.from, step.hint.to, step.hint.promotion) }
+            Goal::Capture => { self.game.board[mv.to].is_some() || flags.contains(MoveFlags::EN_PASSANT) }
+            Goal::Check => { matches!(result, MoveResult::Check | MoveResult::Checkmate) }
+            Goal::Checkmate => { result == MoveResult::Checkmate }
+            Goal::Castle => { flags.contains(MoveFlags::CASTLE) }
+            Goal::EnPassant => { flags.contains(MoveFlags::EN_PASSANT) }
+            Goal::Promotion => { mv.promotion.is_some() }
+        };
+        if !done { return Attempt::Wrong; }
+
+        self.step += 1;
+        match step.reply {
+            Some(reply) => { after.make_move(reply).expect("scripted replies are legal"); }
+            // white goes again, en passant goes with the move given up
+            None if !self.is_finished() => { (after.turn, after.en_passant) = (!after.turn, None); }
+            None => {}
+        }
+        self.game = after;
+
+        Attempt::Correct
+    }
+}
+
+fn step(prompt: &'static str, goal: Goal, hint: &str, reply: Option<&str>) -> Step {
+    let parse = |x: &str| parse_move_string(x).expect("lesson moves are long algebraic");
+    Step { prompt, goal, hint: parse(hint), reply: reply.map(parse) }
+}
+
+/// The built in lessons, from how the pieces move to promotion.
+pub fn lessons() -> Vec<Lesson> {
+    vec![
+        Lesson {
+            title: "Pawns",
+            fen: "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1",
+            steps: vec![
+                step("Pawns move straight ahead, and two squares at once from where they start. Push the pawn to e4", Goal::Exact, "e2e4", None),
+                step("After that they move one square at a time. Push it to e5", Goal::Exact, "e4e5", None),
+            ],
+        },
+        Lesson {
+            title: "The pieces",
+            fen: "4k3/8/8/8/8/8/8/RN1QKB2 w - - 0 1",
+            steps: vec![
+                step("Rooks move any distance along a rank or a file. Move the rook to a5", Goal::Exact, "a1a5", None),
+                step("Bishops move any distance diagonally. Move the bishop to c4", Goal::Exact, "f1c4", None),
+                step("The queen moves like a rook or a bishop. Move the queen to d5", Goal::Exact, "d1d5", None),
+                step("Knights jump in an L, two squares one way and one to the side, over anything in between. Jump to c3", Goal::Exact, "b1c3", None),
+                step("The king moves one square in any direction. Step it to e2", Goal::Exact, "e1e2", None),
+            ],
+        },
+        Lesson {
+            title: "Captures",
+            fen: "4k3/8/2n5/3p4/4P3/8/8/2R1K3 w - - 0 1",
+            steps: vec![
+                step("A piece captures by moving onto an enemy piece. Pawns capture one square diagonally forward: take the pawn on d5", Goal::Capture, "e4d5", None),
+                step("Every other piece captures the way it moves. Take the knight with the rook", Goal::Capture, "c1c6", None),
+            ],
+        },
+        Lesson {
+            title: "Check",
+            fen: "4k3/8/8/8/8/8/8/R3K3 w - - 0 1",
+            steps: vec![
+                step("A move that attacks the king is check, and the king has to get out of it straight away. Check the black king", Goal::Check, "a1a8", Some("e8e7")),
+                step("The king stepped out of the check. Check it again", Goal::Check, "a8a7", None),
+            ],
+        },
+        Lesson {
+            title: "Checkmate",
+            fen: "6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1",
+            steps: vec![
+                step("A check that can't be escaped, blocked or taken is checkmate, and wins the game. The black king is hemmed in by its own pawns: mate it", Goal::Checkmate, "a1a8", None),
+            ],
+        },
+        Lesson {
+            title: "Castling",
+            fen: "4k3/8/8/8/8/8/8/4K2R w K - 0 1",
+            steps: vec![
+                step("Once a game, if neither has moved, the king can move two squares towards a rook, which jumps over it. The king can't be in check or cross an attacked square. Castle by moving the king to g1", Goal::Castle, "e1g1", None),
+            ],
+        },
+        Lesson {
+            title: "En passant",
+            fen: "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1",
+            steps: vec![
+                step("Black's pawn just moved two squares, right past your pawn. On the very next move your pawn can take it as if it had moved one: capture it on d6", Goal::EnPassant, "e5d6", None),
+            ],
+        },
+        Lesson {
+            title: "Promotion",
+            fen: "4k3/1P6/8/8/8/8/8/4K3 w - - 0 1",
+            steps: vec![
+                step("A pawn that reaches the last rank turns into a queen, rook, bishop or knight, nearly always a queen. Promote the pawn", Goal::Promotion, "b7b8q", None),
+            ],
+        },
+    ]
+}
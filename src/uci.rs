@@ -1,235 +1,1293 @@
-use std::io::{BufRead, BufReader, Write};
-use std::num::{NonZeroU64, NonZeroU8};
-use std::os::windows::process::CommandExt;
-use std::process::{Child, Command, Stdio};
-use std::sync::mpsc::{Receiver, Sender};
-// use std::thread::JoinHandle;
-use std::time::{Duration, Instant};
-use crate::{Game, chess::Promotion};
-
-pub struct ThreadedUci {
-    sender: Sender<Message>,
-    receiver: Receiver<ResultMessage>,
-    // handle: JoinHandle<()>
-}
-
-pub(crate) enum Message {
-    RecommendMove(Game, Limits)
-}
-
-pub(crate) enum ResultMessage {
-    Result((usize, usize, Option<Promotion>, String))
-}
-
-impl ThreadedUci {
-    pub(crate) fn new() -> Self {
-        let (s, rx) = std::sync::mpsc::channel();
-        let (s2, rx2) = std::sync::mpsc::channel();
-
-        let _thread = std::thread::spawn(move || {
-            let mut uci = Uci::new();
-
-            while let Ok(message) = rx.recv() {
-                match message {
-                    Message::RecommendMove(game, limits) => {
-                        let ret = uci.recommend_move(&game, limits);
-                        s2.send(ResultMessage::Result(ret)).unwrap();
-                    }
-                }
-            }
-        });
-
-        Self {
-            sender: s,
-            // handle: thread,
-            receiver: rx2
-        }
-    }
-
-    pub(crate) fn new_delay(min_time: Duration) -> Self {
-        let (s, rx) = std::sync::mpsc::channel();
-        let (s2, rx2) = std::sync::mpsc::channel();
-
-        let _thread = std::thread::spawn(move || {
-            let mut uci = Uci::new();
-
-            while let Ok(message) = rx.recv() {
-                match message {
-                    Message::RecommendMove(game, limits) => {
-                        let time = Instant::now();
-                        let ret = uci.recommend_move(&game, limits);
-
-                        if min_time > time.elapsed() {
-                            std::thread::sleep(min_time - time.elapsed());
-                        }
-
-                        s2.send(ResultMessage::Result(ret)).unwrap();
-                    }
-                }
-            }
-        });
-
-        Self {
-            sender: s,
-            // handle: thread,
-            receiver: rx2
-        }
-    }
-
-    pub(crate) fn recommend_move(&self, game: Game, limits: Limits) {
-        self.sender.send(Message::RecommendMove(game, limits)).unwrap();
-    }
-
-    pub(crate) fn try_result(&self) -> Option<(usize, usize, Option<Promotion>, String)> {
-        if let Ok(ResultMessage::Result(ret)) = self.receiver.try_recv() {
-            return Some(ret);
-        }
-
-        None
-    }
-}
-
-pub struct Uci {
-    process: Child
-}
-
-impl Uci {
-    pub(crate) fn new() -> Self {
-        let mut child = Command::new("cmd")
-            .args(["/C", "uci.bat"])
-            .creation_flags(0x08000000)
-            .stdout(Stdio::piped())
-            .stdin(Stdio::piped())
-            .spawn().unwrap();
-
-        writeln!(child.stdin.as_mut().unwrap(), "uci").unwrap();
-        Uci {
-            process: child
-        }
-    }
-
-    pub(crate) fn recommend_move(&mut self, game: &Game, limits: Limits) -> (usize, usize, Option<Promotion>, String) {
-        let stdin = self.process.stdin.as_mut().unwrap();
-        let fen = game.as_fen();
-
-        writeln!(stdin, "position fen {}", fen).unwrap();
-        writeln!(stdin, "go {}", limits.into_limit_string()).unwrap();
-
-        let mut stdout = BufReader::new(self.process.stdout.as_mut().unwrap());
-
-        loop {
-            let mut string = String::new();
-            stdout.read_line(&mut string).unwrap();
-
-            if string.starts_with("bestmove") {
-                let mut parts = string.split(' ');
-
-                let alg_move = parts.nth(1).unwrap();
-                let mut iter = alg_move.chars();
-
-                let x1 = iter.next().unwrap() as usize - 'a' as usize;
-                let y1 = (iter.next().unwrap() as usize - '1' as usize) * 8;
-
-                let x2 = iter.next().unwrap() as usize - 'a' as usize;
-                let y2 = (iter.next().unwrap() as usize - '1' as usize) * 8;
-
-                let promotion = if let Some(p) = iter.next() {
-                    match p {
-                        'q' => { Some(Promotion::Queen) }
-                        'n' => { Some(Promotion::Knight) }
-                        'r' => { Some(Promotion::Rook) }
-                        'b' => { Some(Promotion::Bishop)}
-                        c => {
-                            eprintln!("Unknown promotion letter, '{}'", c);
-                            None
-                        }
-                    }
-                } else { None };
-
-                return (y1 + x1, y2 + x2, promotion, alg_move.to_string());
-            }
-        }
-    }
-}
-
-#[derive(Default, Copy, Clone)]
-pub struct Limits {
-    time: Option<NonZeroU64>,
-    depth: Option<NonZeroU8>,
-    w_time: Option<NonZeroU64>,
-    b_time: Option<NonZeroU64>,
-    w_inc: Option<NonZeroU64>,
-    b_inc: Option<NonZeroU64>
-}
-
-impl Limits {
-    pub fn time(mut self, time: u64) -> Self {
-        self.time = NonZeroU64::new(time);
-        self
-    }
-
-    pub fn depth(mut self, depth: u8) -> Self {
-        self.depth = NonZeroU8::new(depth);
-        self
-    }
-
-    pub fn w_time(mut self, w_time: u64) -> Self {
-        self.w_time = NonZeroU64::new(w_time);
-        self
-    }
-
-    pub fn b_time(mut self, b_time: u64) -> Self {
-        self.b_time = NonZeroU64::new(b_time);
-        self
-    }
-
-    pub fn set_time(&mut self, w_time: u64, b_time: u64)  {
-        self.w_time = NonZeroU64::new(w_time);
-        self.b_time = NonZeroU64::new(b_time);
-    }
-
-    pub fn w_inc(mut self, w_inc: u64) -> Self {
-        self.w_inc = NonZeroU64::new(w_inc);
-        self
-    }
-
-    pub fn b_inc(mut self, b_inc: u64) -> Self {
-        self.b_inc = NonZeroU64::new(b_inc);
-        self
-    }
-
-    fn into_limit_string(self) -> String {
-        let mut ret = String::new();
-
-        if let Some(time) = self.time {
-            ret.push_str(&format!(" movetime {}", time));
-        }
-
-        if let Some(depth) = self.depth {
-            ret.push_str(&format!(" depth {}", depth));
-        }
-
-        if let Some(w_time) = self.w_time {
-            ret.push_str(&format!(" wtime {}", w_time));
-        }
-
-        if let Some(b_time) = self.b_time {
-            ret.push_str(&format!(" btime {}", b_time));
-        }
-
-        if let Some(w_inc) = self.w_inc {
-            ret.push_str(&format!(" winc {}", w_inc));
-        }
-
-        if let Some(b_inc) = self.b_inc {
-            ret.push_str(&format!(" binc {}", b_inc));
-        }
-
-        // default limit will be depth 20
-        if ret.is_empty() {
-            ret.push_str("depth 20");
-        }
-
-        ret
-    }
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{Display, Formatter};
+use std::io::{BufRead, BufReader, Write};
+use std::num::{NonZeroU64, NonZeroU8};
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStderr, ChildStdin, ChildStdout};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender, TryRecvError};
+// use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use crate::{Color, Game, History, Move, chess::Promotion};
+use crate::error::UciError;
+use crate::square::Square;
+use crate::variant::Variant;
+
+/// A [`Uci`] engine running on its own thread, so searches don't block the caller.
+pub struct ThreadedUci {
+    sender: Sender<Message>,
+    receiver: Receiver<ResultMessage>,
+    info_receiver: Receiver<(u64, UciInfo)>,
+    // filled in by the engine thread once the engine is up
+    stopper: Arc<Mutex<Option<UciStopper>>>,
+    // bumped by cancel, anything tagged with an older generation is stale
+    generation: Arc<AtomicU64>,
+    // results are held back and reply delays stand still while set
+    paused: Arc<AtomicBool>,
+    // the engine's `id name`, filled in with the stopper
+    name: Arc<Mutex<Option<String>>>,
+    // handle: JoinHandle<()>
+}
+
+pub(crate) enum Message {
+    // generation, position, limits
+    RecommendMove(u64, Box<UciPosition>, Limits),
+    NewGame,
+    // name, value
+    SetOption(String, String),
+}
+
+pub(crate) enum ResultMessage {
+    // generation of the request
+    Result(u64, Result<Move, UciError>)
+}
+
+impl ThreadedUci {
+    /// Starts the engine thread.
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        ThreadedUciBuilder::new().build()
+    }
+
+    /// Starts the engine thread, results are held back until at least `min_time` has passed.
+    pub fn new_delay(min_time: Duration) -> Self {
+        ThreadedUciBuilder::new().min_delay(min_time).build()
+    }
+
+    /// Like [`ThreadedUci::new_delay`], starting the engine from `config` instead of the uci script.
+    pub fn with_config(config: EngineConfig, min_time: Duration) -> Self {
+        ThreadedUciBuilder::new().config(config).min_delay(min_time).build()
+    }
+
+    fn spawn(config: Option<EngineConfig>, min_delay: Duration, random_delay: Option<(Duration, Duration)>) -> Self {
+        let (s, rx) = std::sync::mpsc::channel();
+        let (s2, rx2) = std::sync::mpsc::channel();
+        let (s3, rx3) = std::sync::mpsc::channel();
+        let stopper = Arc::new(Mutex::new(None));
+        let thread_stopper = stopper.clone();
+        let generation = Arc::new(AtomicU64::new(0));
+        let current = generation.clone();
+        let paused = Arc::new(AtomicBool::new(false));
+        let held = paused.clone();
+        let name = Arc::new(Mutex::new(None));
+        let thread_name = name.clone();
+
+        crate::platform::spawn(move || {
+            let uci = Uci::with_config(config.as_ref().unwrap_or(&EngineConfig::uci_script()));
+
+            let mut uci = match uci {
+                Ok(uci) => { uci }
+                Err(err) => {
+                    let _ = s2.send(ResultMessage::Result(current.load(Ordering::SeqCst), Err(err)));
+                    return;
+                }
+            };
+
+            *thread_stopper.lock().unwrap_or_else(|x| x.into_inner()) = Some(uci.stopper());
+            *thread_name.lock().unwrap_or_else(|x| x.into_inner()) = uci.name().map(str::to_string);
+            let mut seed = random_seed();
+
+            while let Ok(message) = rx.recv() {
+                match message {
+                    Message::RecommendMove(id, position, limits) => {
+                        // cancelled while it waited in the queue
+                        if id != current.load(Ordering::SeqCst) { continue; }
+
+                        let time = Instant::now();
+                        let ret = uci.search_with_info(*position, limits, |info| { let _ = s3.send((id, info.clone())); });
+
+                        let wait = match random_delay {
+                            Some((low, high)) => { min_delay + random_between(&mut seed, low, high) }
+                            None => { min_delay }
+                        };
+
+                        // time spent paused doesn't count towards the delay
+                        let mut paused_for = Duration::ZERO;
+                        while id == current.load(Ordering::SeqCst) {
+                            if held.load(Ordering::SeqCst) {
+                                let pause = Instant::now();
+                                while held.load(Ordering::SeqCst) && id == current.load(Ordering::SeqCst) {
+                                    std::thread::sleep(PAUSE_STEP);
+                                }
+                                paused_for += pause.elapsed();
+                                continue;
+                            }
+
+                            let waited = time.elapsed().saturating_sub(paused_for);
+                            if waited >= wait { break; }
+                            std::thread::sleep((wait - waited).min(PAUSE_STEP));
+                        }
+
+                        if s2.send(ResultMessage::Result(id, ret.map(|x| x.best))).is_err() { return; }
+                    }
+                    Message::NewGame => {
+                        if let Err(err) = uci.new_game() {
+                            if s2.send(ResultMessage::Result(current.load(Ordering::SeqCst), Err(err))).is_err() { return; }
+                        }
+                    }
+                    Message::SetOption(name, value) => {
+                        if let Err(err) = uci.set_option(&name, &value) {
+                            if s2.send(ResultMessage::Result(current.load(Ordering::SeqCst), Err(err))).is_err() { return; }
+                        }
+                    }
+                }
+            }
+        });
+
+        Self {
+            sender: s,
+            // handle: thread,
+            receiver: rx2,
+            info_receiver: rx3,
+            stopper,
+            generation,
+            paused,
+            name,
+        }
+    }
+
+    /// The name the engine gave in the handshake, None until it has started (or if it gave none).
+    pub fn engine_name(&self) -> Option<String> {
+        self.name.lock().unwrap_or_else(|x| x.into_inner()).clone()
+    }
+
+    /// Starts a search on `position`, the result is picked up with [`ThreadedUci::try_result`].
+    pub fn recommend_move(&self, position: impl Into<UciPosition>, limits: Limits) {
+        // if the engine thread is gone, try_result reports it
+        let id = self.generation.load(Ordering::SeqCst);
+        let _ = self.sender.send(Message::RecommendMove(id, Box::new(position.into()), limits));
+    }
+
+    /// Stops the running search and forgets every pending request, their results never arrive.
+    /// Use this before the position changes under the engine, like on a takeback.
+    pub fn cancel(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        self.stop();
+    }
+
+    /// Cuts the running search short, its move still arrives through [`ThreadedUci::try_result`].
+    pub fn stop(&self) {
+        if let Some(stopper) = self.stopper.lock().unwrap_or_else(|x| x.into_inner()).as_ref() {
+            let _ = stopper.stop();
+        }
+    }
+
+    /// Holds results back while `paused`, for a game that is paused or out of sight. The reply delay
+    /// doesn't run down in the meantime, so a move doesn't turn up the moment the game is resumed.
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::SeqCst);
+    }
+
+    /// Cancels any search and tells the engine the following searches are from a new game.
+    pub fn new_game(&self) {
+        self.cancel();
+        let _ = self.sender.send(Message::NewGame);
+    }
+
+    /// Sets a UCI option on the engine, taking effect from the next search on.
+    pub fn set_option(&self, name: &str, value: &str) {
+        let _ = self.sender.send(Message::SetOption(name.to_string(), value.to_string()));
+    }
+
+    /// The result of the last search, if it has finished. Results of cancelled requests are skipped.
+    pub fn try_result(&self) -> Option<Result<Move, UciError>> {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(ResultMessage::Result(id, ret)) => {
+                    if id == self.generation.load(Ordering::SeqCst) { return Some(ret); }
+                }
+                Err(TryRecvError::Disconnected) => { return Some(Err(UciError::Disconnected)); }
+                Err(TryRecvError::Empty) => { return None; }
+            }
+        }
+    }
+
+    /// The next `info` line the engine sent while searching, oldest first.
+    pub fn try_info(&self) -> Option<UciInfo> {
+        loop {
+            let (id, info) = self.info_receiver.try_recv().ok()?;
+            if id == self.generation.load(Ordering::SeqCst) { return Some(info); }
+        }
+    }
+}
+
+/// A finished search kept by an [`AnalysisCache`]: the last scored info line and the best move.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct CachedAnalysis {
+    pub info: Option<UciInfo>,
+    pub best: Move,
+}
+
+impl CachedAnalysis {
+    pub fn depth(&self) -> u32 {
+        self.info.as_ref().and_then(|x| x.depth).unwrap_or(0)
+    }
+}
+
+/// Finished searches by the [Zobrist hash](Game::zobrist) of their position, so going back to a
+/// position (or reaching it by another move order) shows its evaluation again without a new search.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct AnalysisCache {
+    entries: HashMap<u64, CachedAnalysis>,
+}
+
+impl AnalysisCache {
+    pub fn new() -> Self {
+        AnalysisCache::default()
+    }
+
+    pub fn get(&self, position: &Game) -> Option<&CachedAnalysis> {
+        self.entries.get(&position.zobrist())
+    }
+
+    /// Keeps `analysis` of `position`, unless a deeper search of it is already kept.
+    pub fn insert(&mut self, position: &Game, analysis: CachedAnalysis) {
+        let entry = self.entries.entry(position.zobrist()).or_insert_with(|| analysis.clone());
+        if analysis.depth() >= entry.depth() { *entry = analysis; }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Where a [`Uci`] session is in the protocol.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum UciState {
+    // handshake done and the engine answered `isready`
+    Ready,
+    // a `go` was sent and its `bestmove` has not arrived yet
+    Searching,
+}
+
+/// Sets up a [`ThreadedUci`]: which engine to start and how long to hold its replies back,
+/// so moves don't come in inhumanly fast. Replies are instant unless a delay is set.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct ThreadedUciBuilder {
+    config: Option<EngineConfig>,
+    min_delay: Duration,
+    random_delay: Option<(Duration, Duration)>,
+}
+
+impl ThreadedUciBuilder {
+    pub fn new() -> Self {
+        ThreadedUciBuilder::default()
+    }
+
+    /// Start the engine from `config` instead of the uci script.
+    pub fn config(mut self, config: EngineConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Replies arrive no sooner than `delay` after the search started.
+    pub fn min_delay(mut self, delay: Duration) -> Self {
+        self.min_delay = delay;
+        self
+    }
+
+    /// Adds a random wait between `low` and `high` to the minimum delay, picked anew for each reply.
+    pub fn random_delay(mut self, low: Duration, high: Duration) -> Self {
+        self.random_delay = Some((low, high.max(low)));
+        self
+    }
+
+    /// Pass replies on as soon as the engine sends them, dropping any delay set so far.
+    pub fn instant(mut self) -> Self {
+        self.min_delay = Duration::ZERO;
+        self.random_delay = None;
+        self
+    }
+
+    /// Starts the engine thread.
+    pub fn build(self) -> ThreadedUci {
+        ThreadedUci::spawn(self.config, self.min_delay, self.random_delay)
+    }
+}
+
+// xorshift, good enough to vary reply times
+fn random_seed() -> u64 {
+    let nanos = crate::platform::since_epoch().map(|x| x.as_nanos()).unwrap_or(0);
+    (nanos as u64) | 1
+}
+
+fn random_between(seed: &mut u64, low: Duration, high: Duration) -> Duration {
+    *seed ^= *seed << 13;
+    *seed ^= *seed >> 7;
+    *seed ^= *seed << 17;
+
+    let fraction = (*seed >> 11) as f64 / (1u64 << 53) as f64;
+    low + (high - low).mul_f64(fraction)
+}
+
+impl Drop for ThreadedUci {
+    fn drop(&mut self) {
+        // don't keep the engine busy on a search nobody will read
+        self.stop();
+    }
+}
+
+/// How long an engine gets to answer a command, and to send its move past the time it was given.
+pub const RESPONSE_TIMEOUT: Duration = Duration::from_secs(10);
+// how long an engine gets to exit after `quit`
+const QUIT_TIMEOUT: Duration = Duration::from_secs(1);
+// how often a reply held back by a pause looks again
+const PAUSE_STEP: Duration = Duration::from_millis(10);
+/// How many of its last stderr lines a [`Uci`] engine keeps.
+pub const STDERR_LINES: usize = 100;
+
+/// A UCI engine process, started from `uci.bat` (or `uci.sh` outside of windows). An engine that stops
+/// answering fails with [`UciError::Timeout`] instead of blocking forever.
+pub struct Uci {
+    process: Child,
+    // shared with any UciStopper, which writes from other threads
+    stdin: Arc<Mutex<ChildStdin>>,
+    // the engine's output lines, read on their own thread so waiting for them can time out
+    stdout: Receiver<std::io::Result<String>>,
+    // the last STDERR_LINES the engine wrote to stderr, filled in by another thread
+    stderr: Arc<Mutex<VecDeque<String>>>,
+    timeout: Duration,
+    state: UciState,
+    name: Option<String>,
+    // the values of the engine's UCI_Variant option, empty if it only plays chess
+    variants: Vec<String>,
+}
+
+/// Sends `stop` to a [`Uci`] engine from another thread, while a search blocks its owner.
+#[derive(Clone)]
+pub struct UciStopper {
+    stdin: Arc<Mutex<ChildStdin>>,
+}
+
+impl UciStopper {
+    /// Asks the engine to finish its search, it still answers with its best move so far.
+    pub fn stop(&self) -> Result<(), UciError> {
+        send(&self.stdin, "stop")
+    }
+}
+
+fn send(stdin: &Mutex<ChildStdin>, command: &str) -> Result<(), UciError> {
+    // a poisoned lock only means another writer panicked, the pipe is fine
+    let mut stdin = stdin.lock().unwrap_or_else(|x| x.into_inner());
+    writeln!(stdin, "{}", command)?;
+    stdin.flush()?;
+
+    Ok(())
+}
+
+impl Uci {
+    /// Spawns the engine and starts the UCI session.
+    pub fn new() -> Result<Self, UciError> {
+        Uci::with_config(&EngineConfig::uci_script())
+    }
+
+    /// Spawns the engine described by `config`, waits for `uciok`, sets its options
+    /// and waits until the engine is ready.
+    pub fn with_config(config: &EngineConfig) -> Result<Self, UciError> {
+        let mut process = config.spawn()?;
+
+        let stdin = process.stdin.take().ok_or(UciError::EngineExited)?;
+        let stdout = process.stdout.take().ok_or(UciError::EngineExited)?;
+        let stderr = Arc::new(Mutex::new(VecDeque::new()));
+        if let Some(pipe) = process.stderr.take() { keep_stderr(pipe, stderr.clone(), STDERR_LINES); }
+
+        let mut uci = Uci {
+            process,
+            stdin: Arc::new(Mutex::new(stdin)),
+            stdout: read_lines(stdout),
+            stderr,
+            timeout: RESPONSE_TIMEOUT,
+            state: UciState::Ready,
+            name: None,
+            variants: Vec::new(),
+        };
+
+        uci.send("uci")?;
+        loop {
+            let line = uci.read_line(uci.deadline())?;
+
+            if let Some(name) = line.strip_prefix("id name ") {
+                uci.name = Some(name.to_string());
+            } else if let Some(option) = line.strip_prefix("option name UCI_Variant ") {
+                // type combo default chess var chess var horde ...
+                let words: Vec<&str> = option.split_whitespace().collect();
+                uci.variants = words.windows(2).filter(|x| x[0] == "var").map(|x| x[1].to_string()).collect();
+            } else if line == "uciok" {
+                break;
+            }
+        }
+
+        for (name, value) in &config.options {
+            uci.send(&format!("setoption name {} value {}", name, value))?;
+        }
+
+        uci.sync()?;
+        Ok(uci)
+    }
+
+    /// The name the engine gave in the handshake.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// The engine's name and version from its `id name`, see [`split_engine_id`].
+    pub fn name_and_version(&self) -> Option<(&str, Option<&str>)> {
+        self.name().map(split_engine_id)
+    }
+
+    pub fn state(&self) -> UciState {
+        self.state
+    }
+
+    /// The variants the engine's `UCI_Variant` option lists, by their UCI names. Empty for an engine that
+    /// only plays chess.
+    pub fn variants(&self) -> &[String] {
+        &self.variants
+    }
+
+    /// Sets the engine's `UCI_Variant` to `variant`. Chess needs nothing from an engine without the
+    /// option, any other variant has to be in its list.
+    pub fn set_variant(&mut self, variant: Variant) -> Result<(), UciError> {
+        if variant == Variant::Standard && self.variants.is_empty() { return Ok(()); }
+        if !self.variants.iter().any(|x| x == variant.uci_name()) { return Err(UciError::UnsupportedVariant(variant.name().to_string())); }
+
+        self.set_option("UCI_Variant", variant.uci_name())
+    }
+
+    /// What the engine last wrote to stderr, oldest line first. Engines often say there why they stopped.
+    pub fn stderr(&self) -> Vec<String> {
+        self.stderr.lock().unwrap_or_else(|x| x.into_inner()).iter().cloned().collect()
+    }
+
+    /// A handle that can stop this engine's searches from another thread.
+    pub fn stopper(&self) -> UciStopper {
+        UciStopper { stdin: self.stdin.clone() }
+    }
+
+    /// How long the engine gets to answer from now on, [`RESPONSE_TIMEOUT`] unless set.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Sends `isready` and waits for `readyok`, skipping whatever the engine sends before it.
+    pub fn sync(&mut self) -> Result<(), UciError> {
+        self.send("isready")?;
+
+        let deadline = self.deadline();
+        while self.read_line(deadline)? != "readyok" {}
+
+        Ok(())
+    }
+
+    /// Tells the engine the next position is from another game, stopping any search first.
+    pub fn new_game(&mut self) -> Result<(), UciError> {
+        self.stop()?;
+        self.send("ucinewgame")?;
+        self.sync()
+    }
+
+    /// Sends `setoption`, stopping any search first, and waits for the engine to take it.
+    pub fn set_option(&mut self, name: &str, value: &str) -> Result<(), UciError> {
+        self.stop()?;
+        self.send(&format!("setoption name {} value {}", name, value))?;
+        self.sync()
+    }
+
+    /// Aborts the running search, if any, and throws its best move away.
+    pub fn stop(&mut self) -> Result<(), UciError> {
+        if self.state == UciState::Searching {
+            self.send("stop")?;
+
+            let deadline = self.deadline();
+            while self.state == UciState::Searching {
+                self.read_line(deadline)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn send(&mut self, command: &str) -> Result<(), UciError> {
+        send(&self.stdin, command)
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        Some(Instant::now() + self.timeout)
+    }
+
+    // the next line, trimmed, noting the end of a search. None waits as long as the engine is running
+    fn read_line(&mut self, deadline: Option<Instant>) -> Result<String, UciError> {
+        let line = match deadline {
+            Some(deadline) => {
+                match self.stdout.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                    Ok(line) => { line }
+                    Err(RecvTimeoutError::Timeout) => { return Err(UciError::Timeout); }
+                    Err(RecvTimeoutError::Disconnected) => { return Err(UciError::EngineExited); }
+                }
+            }
+            None => { self.stdout.recv().map_err(|_| UciError::EngineExited)? }
+        };
+        let string = line?;
+
+        if string.split_whitespace().next() == Some("bestmove") {
+            self.state = UciState::Ready;
+        }
+
+        Ok(string.trim().to_string())
+    }
+
+    /// Searches `position` and returns the engine's best move.
+    pub fn recommend_move(&mut self, position: impl Into<UciPosition>, limits: Limits) -> Result<Move, UciError> {
+        self.evaluate(position, limits).map(|x| x.best)
+    }
+
+    /// Searches `position` and returns the best move along with the engine's last reported score.
+    pub fn evaluate(&mut self, position: impl Into<UciPosition>, limits: Limits) -> Result<Evaluation, UciError> {
+        self.search_with_info(position, limits, |_| {})
+    }
+
+    /// Like [`Uci::evaluate`], calling `on_info` for every `info` line while the engine thinks.
+    pub fn search_with_info(&mut self, position: impl Into<UciPosition>, limits: Limits,
+                            mut on_info: impl FnMut(&UciInfo)) -> Result<Evaluation, UciError> {
+        // a search left running would answer for this one
+        self.stop()?;
+
+        let position = position.into();
+        self.send(&position.command())?;
+        self.send(&format!("go {}", limits.into_limit_string()))?;
+        self.state = UciState::Searching;
+
+        // searches to a depth can take any time, timed ones get the timeout on top of their time
+        let deadline = limits.longest_search(position.game().turn).map(|x| Instant::now() + Duration::from_millis(x) + self.timeout);
+        let mut score = None;
+
+        // anything that isn't an info line or the move is skipped
+        loop {
+            let string = self.read_line(deadline)?;
+
+            if let Some(info) = UciInfo::parse(&string) {
+                on_info(&info);
+                score = info.score.or(score);
+            } else if string.split_whitespace().next() == Some("bestmove") {
+                let alg_move = string.split_whitespace().nth(1).unwrap_or_default();
+                // engines answer one of these when the position has no moves
+                if matches!(alg_move, "(none)" | "0000" | "none") { return Err(UciError::NoMove); }
+
+                let mv = parse_move_string(alg_move).ok_or_else(|| UciError::InvalidMove(alg_move.to_string()))?;
+                return Ok(Evaluation { best: mv, score });
+            }
+        }
+    }
+}
+
+impl Drop for Uci {
+    fn drop(&mut self) {
+        // let the engine exit on its own, one that doesn't is killed so no process is left behind
+        let _ = self.send("quit");
+
+        let deadline = Instant::now() + QUIT_TIMEOUT;
+        while Instant::now() < deadline {
+            match self.process.try_wait() {
+                Ok(Some(_)) => { return; }
+                Ok(None) => { std::thread::sleep(Duration::from_millis(10)); }
+                Err(_) => { break; }
+            }
+        }
+
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+/// Splits an engine's `id name` into its name and version, the version being everything from the first
+/// word that starts with a digit, or a `v` and a digit: "Stockfish 16.1" is ("Stockfish", Some("16.1")).
+pub fn split_engine_id(id: &str) -> (&str, Option<&str>) {
+    let id = id.trim();
+    let is_version = |word: &str| word.strip_prefix(['v', 'V']).unwrap_or(word).starts_with(|c: char| c.is_ascii_digit());
+
+    match id.match_indices(' ').map(|(i, _)| i + 1).find(|i| is_version(&id[*i..])) {
+        Some(i) => { (id[..i].trim_end(), Some(&id[i..])) }
+        None => { (id, None) }
+    }
+}
+
+/// What an engine searches: a bare position, or the start of a game and the moves played since,
+/// which lets the engine see repetitions and follow its opening book.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum UciPosition {
+    Fen(Game),
+    Moves(History),
+}
+
+impl UciPosition {
+    /// The position searched, after any moves.
+    pub fn game(&self) -> Game {
+        match self {
+            UciPosition::Fen(game) => { *game }
+            UciPosition::Moves(history) => { history.current() }
+        }
+    }
+
+    /// Parses a `position` command like "position startpos moves e2e4 e7e5", None if the fen or
+    /// one of the moves isn't valid.
+    pub fn parse(command: &str) -> Option<UciPosition> {
+        let mut parts = command.split_whitespace();
+        if parts.next()? != "position" { return None; }
+
+        let start = match parts.next()? {
+            "startpos" => { Game::default() }
+            "fen" => { Game::from_fen(parts.by_ref().take_while(|x| *x != "moves").collect::<Vec<_>>().join(" ")).ok()? }
+            _ => { return None; }
+        };
+
+        // after a fen the "moves" was taken with it
+        let mut history = History::new(start);
+        for text in parts.skip_while(|x| *x == "moves") {
+            history.push(parse_move_string(text)?).ok()?;
+        }
+
+        Some(UciPosition::Moves(history))
+    }
+
+    // the `position` command
+    fn command(&self) -> String {
+        match self {
+            UciPosition::Fen(game) => { format!("position fen {}", game.as_fen()) }
+            UciPosition::Moves(history) => {
+                let mut command = if history.start() == Game::default() {
+                    String::from("position startpos")
+                } else {
+                    format!("position fen {}", history.start().as_fen())
+                };
+
+                if !history.is_empty() {
+                    command.push_str(" moves");
+                    for mv in history.moves() {
+                        command.push(' ');
+                        command.push_str(&mv.to_string());
+                    }
+                }
+
+                command
+            }
+        }
+    }
+}
+
+impl From<Game> for UciPosition {
+    fn from(value: Game) -> Self {
+        UciPosition::Fen(value)
+    }
+}
+
+impl From<&Game> for UciPosition {
+    fn from(value: &Game) -> Self {
+        UciPosition::Fen(*value)
+    }
+}
+
+impl From<History> for UciPosition {
+    fn from(value: History) -> Self {
+        UciPosition::Moves(value)
+    }
+}
+
+impl From<&History> for UciPosition {
+    fn from(value: &History) -> Self {
+        UciPosition::Moves(value.clone())
+    }
+}
+
+// the move from its long algebraic notation, anything after the promotion is ignored
+pub(crate) fn parse_move_string(text: &str) -> Option<Move> {
+    let from: Square = text.get(0..2)?.parse().ok()?;
+    let to: Square = text.get(2..4)?.parse().ok()?;
+
+    let promotion = match text.chars().nth(4) {
+        Some('q') => { Some(Promotion::Queen) }
+        Some('n') => { Some(Promotion::Knight) }
+        Some('r') => { Some(Promotion::Rook) }
+        Some('b') => { Some(Promotion::Bishop) }
+        None => { None }
+        Some(_) => { return None; }
+    };
+
+    Some(Move::new(from, to, promotion))
+}
+
+/// How to start a UCI engine: the program, its arguments, where it runs and the options to set on it.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct EngineConfig {
+    pub name: String,
+    pub path: String,
+    pub args: Vec<String>,
+    // (name, value) sent with setoption
+    pub options: Vec<(String, String)>,
+    // the directory the engine runs in, for engines that load nets or books from it. None for this
+    // program's own
+    pub working_dir: Option<PathBuf>,
+    // (name, value) environment variables set for the engine on top of this program's
+    pub env: Vec<(String, String)>,
+}
+
+impl EngineConfig {
+    pub fn new(name: impl Into<String>, path: impl Into<String>) -> Self {
+        EngineConfig { name: name.into(), path: path.into(), ..Default::default() }
+    }
+
+    /// The engine started from `uci.bat` (or `uci.sh` outside of windows), used when none is set up.
+    pub fn uci_script() -> Self {
+        #[cfg(windows)]
+        return EngineConfig::new("uci.bat", "cmd").args(["/C", "uci.bat"]);
+
+        #[cfg(not(windows))]
+        EngineConfig::new("uci.sh", "sh").args(["uci.sh"])
+    }
+
+    pub fn args<S: Into<String>>(mut self, args: impl IntoIterator<Item = S>) -> Self {
+        self.args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn option(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.options.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn working_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.working_dir = Some(dir.into());
+        self
+    }
+
+    pub fn env(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((name.into(), value.into()));
+        self
+    }
+
+    // the program to run: a relative path is from this program's directory even with another working
+    // directory, which platforms don't agree on otherwise
+    fn program(&self) -> PathBuf {
+        let path = Path::new(&self.path);
+        let relative = path.is_relative() && path.components().count() > 1;
+
+        match std::env::current_dir() {
+            Ok(dir) if relative && self.working_dir.is_some() => { dir.join(path) }
+            _ => { path.to_path_buf() }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn spawn(&self) -> Result<Child, UciError> {
+        let mut command = std::process::Command::new(self.program());
+        command.args(&self.args).envs(self.env.iter().map(|(name, value)| (name, value)));
+        if let Some(dir) = &self.working_dir { command.current_dir(dir); }
+
+        #[cfg(windows)]
+        command.creation_flags(0x08000000);
+
+        command.stdout(std::process::Stdio::piped()).stdin(std::process::Stdio::piped()).stderr(std::process::Stdio::piped())
+            .spawn().map_err(UciError::Spawn)
+    }
+
+    // browsers can't start processes
+    #[cfg(target_arch = "wasm32")]
+    fn spawn(&self) -> Result<Child, UciError> {
+        Err(UciError::Unsupported)
+    }
+
+    /// Starts the engine, waits up to `timeout` for `uciok` and stops it again.
+    /// Returns the name the engine reports for itself.
+    pub fn validate(&self, timeout: Duration) -> Result<String, UciError> {
+        let mut child = self.spawn()?;
+
+        writeln!(child.stdin.as_mut().ok_or(UciError::EngineExited)?, "uci")?;
+
+        let rx = read_lines(child.stdout.take().ok_or(UciError::EngineExited)?);
+
+        let deadline = Instant::now() + timeout;
+        let mut name = self.name.clone();
+
+        let result = loop {
+            match rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok(Ok(line)) if line.trim() == "uciok" => { break Ok(name); }
+                Ok(Ok(line)) => {
+                    if let Some(id) = line.trim().strip_prefix("id name ") { name = id.to_string(); }
+                }
+                Ok(Err(err)) => { break Err(UciError::Io(err)); }
+                Err(RecvTimeoutError::Timeout) => { break Err(UciError::Timeout); }
+                Err(RecvTimeoutError::Disconnected) => { break Err(UciError::EngineExited); }
+            }
+        };
+
+        let _ = child.kill();
+        let _ = child.wait();
+
+        result
+    }
+
+    /// Starts the engine and goes through the handshake like [`Uci::with_config`], waiting up to `timeout`
+    /// for each answer, then stops it. Everything said on the way is kept, to find out why an engine
+    /// doesn't work.
+    pub fn diagnose(&self, timeout: Duration) -> EngineDiagnostics {
+        let env = self.env.iter().map(|(name, value)| format!("{}={}", name, value));
+        let mut diagnostics = EngineDiagnostics {
+            command: env.chain(std::iter::once(self.path.clone())).chain(self.args.iter().cloned()).collect::<Vec<_>>().join(" "),
+            working_dir: std::env::current_dir().ok().map(|x| self.working_dir.as_ref().map(|dir| x.join(dir)).unwrap_or(x)),
+            resolved_path: resolve_program(&self.program()),
+            transcript: Vec::new(),
+            stderr: Vec::new(),
+            outcome: Err(UciError::Timeout),
+        };
+
+        let mut child = match self.spawn() {
+            Ok(child) => { child }
+            Err(err) => {
+                diagnostics.outcome = Err(err);
+                return diagnostics;
+            }
+        };
+
+        let stderr = Arc::new(Mutex::new(VecDeque::new()));
+        let stderr_reader = child.stderr.take().map(|x| keep_stderr(x, stderr.clone(), usize::MAX));
+        let (Some(mut stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            diagnostics.outcome = Err(UciError::EngineExited);
+            return diagnostics;
+        };
+        let rx = read_lines(stdout);
+
+        let mut say = |command: &str, transcript: &mut Vec<String>| -> Result<(), UciError> {
+            transcript.push(format!("> {}", command));
+            writeln!(stdin, "{}", command)?;
+            Ok(stdin.flush()?)
+        };
+        let mut name = self.name.clone();
+
+        // (command, the answer that ends it), options are set before the engine's asked if it's ready
+        let mut steps = vec![(String::from("uci"), Some("uciok"))];
+        steps.extend(self.options.iter().map(|(name, value)| (format!("setoption name {} value {}", name, value), None)));
+        steps.push((String::from("isready"), Some("readyok")));
+
+        let mut outcome = Ok(());
+        'steps: for (command, answer) in steps {
+            if let Err(err) = say(&command, &mut diagnostics.transcript) {
+                outcome = Err(err);
+                break;
+            }
+            let Some(answer) = answer else { continue; };
+
+            let deadline = Instant::now() + timeout;
+            loop {
+                let line = match rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                    Ok(Ok(line)) => { line }
+                    Ok(Err(err)) => { outcome = Err(UciError::Io(err)); break 'steps; }
+                    Err(RecvTimeoutError::Timeout) => { outcome = Err(UciError::Timeout); break 'steps; }
+                    Err(RecvTimeoutError::Disconnected) => { outcome = Err(UciError::EngineExited); break 'steps; }
+                };
+
+                diagnostics.transcript.push(format!("< {}", line));
+                if let Some(id) = line.trim().strip_prefix("id name ") { name = id.to_string(); }
+                if line.trim() == answer { break; }
+            }
+        }
+
+        let _ = child.kill();
+        let exit = child.wait();
+        // the pipe closes with the engine, so everything it wrote is in by now
+        if let Some(reader) = stderr_reader { let _ = reader.join(); }
+
+        diagnostics.stderr = stderr.lock().unwrap_or_else(|x| x.into_inner()).drain(..).collect();
+        if let (Err(UciError::EngineExited), Ok(status)) = (&outcome, exit) {
+            diagnostics.transcript.push(format!("(the engine exited, {})", status));
+        }
+        diagnostics.outcome = outcome.map(|_| name);
+
+        diagnostics
+    }
+}
+
+/// What [`EngineConfig::diagnose`] found out about starting an engine.
+#[derive(Debug)]
+pub struct EngineDiagnostics {
+    // the environment variables set, the program and its arguments
+    pub command: String,
+    // where the engine was started from
+    pub working_dir: Option<PathBuf>,
+    // the program that runs, None if it can't be found
+    pub resolved_path: Option<PathBuf>,
+    // the handshake, "> " before what was sent and "< " before what came back
+    pub transcript: Vec<String>,
+    pub stderr: Vec<String>,
+    // the engine's name if the handshake went through
+    pub outcome: Result<String, UciError>,
+}
+
+// the file `program` runs: a path as it is, a bare name looked up on PATH like the shell does
+fn resolve_program(path: &Path) -> Option<PathBuf> {
+    if path.components().count() > 1 || path.is_absolute() {
+        return path.canonicalize().ok();
+    }
+    let program = &*path.to_string_lossy();
+
+    // windows also runs programs named without their extension
+    let extensions: &[&str] = if cfg!(windows) { &["", ".exe", ".bat", ".cmd"] } else { &[""] };
+    let dirs = std::env::var_os("PATH").map(|x| std::env::split_paths(&x).collect::<Vec<_>>()).unwrap_or_default();
+
+    let candidates = dirs.iter().flat_map(|dir| extensions.iter().map(move |ext| dir.join(format!("{}{}", program, ext))));
+    // windows looks in the current directory first
+    let local = if cfg!(windows) { std::env::current_dir().ok().map(|x| x.join(path)) } else { None };
+
+    local.into_iter().chain(candidates).find(|x| x.is_file())
+}
+
+// collects the lines of `stderr` into `lines` on another thread, keeping the last `max`
+fn keep_stderr(stderr: ChildStderr, lines: Arc<Mutex<VecDeque<String>>>, max: usize) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+            let mut lines = lines.lock().unwrap_or_else(|x| x.into_inner());
+            lines.push_back(line);
+            if lines.len() > max { lines.pop_front(); }
+        }
+    })
+}
+
+// the lines of `stdout` as they come, read on another thread so a silent engine can be timed out. The
+// channel closes when the engine exits
+fn read_lines(stdout: ChildStdout) -> Receiver<std::io::Result<String>> {
+    let (s, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stdout).lines() {
+            if s.send(line).is_err() { return; }
+        }
+    });
+
+    rx
+}
+
+/// An engine score, from the point of view of the side to move.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Score {
+    Centipawns(i32),
+    // moves until mate, negative if the side to move is getting mated
+    Mate(i32),
+}
+
+impl Score {
+    /// The score as centipawns, mates are scored as ±10000 minus the distance to mate.
+    pub fn centipawns(self) -> i32 {
+        match self {
+            Score::Centipawns(cp) => { cp }
+            Score::Mate(n) if n > 0 => { 10_000 - n }
+            Score::Mate(n) => { -10_000 - n }
+        }
+    }
+
+    /// The score from white's point of view, for a position with `turn` to move.
+    pub fn white_view(self, turn: Color) -> Score {
+        match (turn, self) {
+            (Color::White, score) => { score }
+            (Color::Black, Score::Centipawns(cp)) => { Score::Centipawns(-cp) }
+            (Color::Black, Score::Mate(n)) => { Score::Mate(-n) }
+        }
+    }
+}
+
+impl Display for Score {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Score::Centipawns(cp) => { write!(f, "{:+.2}", *cp as f32 / 100.0) }
+            Score::Mate(n) => { write!(f, "#{}", n) }
+        }
+    }
+}
+
+/// One `info` line from the engine, fields it did not send are left empty.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct UciInfo {
+    pub depth: Option<u32>,
+    pub seldepth: Option<u32>,
+    pub multipv: Option<u32>,
+    pub score: Option<Score>,
+    pub nodes: Option<u64>,
+    pub nps: Option<u64>,
+    // milliseconds searched
+    pub time: Option<u64>,
+    // moves in long algebraic notation, like "e2e4"
+    pub pv: Vec<String>,
+    // free text sent with "info string"
+    pub string: Option<String>,
+}
+
+impl UciInfo {
+    /// The principal variation played out from `position`, up to the first move that isn't legal there.
+    pub fn pv_moves(&self, position: &Game) -> Vec<Move> {
+        let mut game = *position;
+        let mut moves = Vec::new();
+
+        for text in &self.pv {
+            let Some(mv) = parse_move_string(text) else { break; };
+            if game.make_move(mv).is_err() { break; }
+
+            moves.push(mv);
+        }
+
+        moves
+    }
+
+    /// Parses an info line like "info depth 10 score cp 31 nodes 2041 pv e2e4 e7e5", None for any other line.
+    pub fn parse(line: &str) -> Option<UciInfo> {
+        let mut parts = line.split_whitespace();
+        if parts.next()? != "info" { return None; }
+
+        let mut info = UciInfo::default();
+        while let Some(part) = parts.next() {
+            match part {
+                "depth" => { info.depth = parts.next().and_then(|x| x.parse().ok()); }
+                "seldepth" => { info.seldepth = parts.next().and_then(|x| x.parse().ok()); }
+                "multipv" => { info.multipv = parts.next().and_then(|x| x.parse().ok()); }
+                "nodes" => { info.nodes = parts.next().and_then(|x| x.parse().ok()); }
+                "nps" => { info.nps = parts.next().and_then(|x| x.parse().ok()); }
+                "time" => { info.time = parts.next().and_then(|x| x.parse().ok()); }
+                "score" => {
+                    let kind = parts.next();
+                    let value = parts.next().and_then(|x| x.parse().ok());
+
+                    info.score = match (kind, value) {
+                        (Some("cp"), Some(cp)) => { Some(Score::Centipawns(cp)) }
+                        (Some("mate"), Some(n)) => { Some(Score::Mate(n)) }
+                        _ => { None }
+                    };
+                }
+                // the pv and string run to the end of the line
+                "pv" => { info.pv = parts.by_ref().map(String::from).collect(); }
+                "string" => { info.string = Some(parts.by_ref().collect::<Vec<_>>().join(" ")); }
+                _ => {}
+            }
+        }
+
+        Some(info)
+    }
+
+    /// The info line a UCI engine sends, the other way round from [`UciInfo::parse`].
+    pub fn to_uci(&self) -> String {
+        let mut line = String::from("info");
+        if let Some(string) = &self.string { return format!("{} string {}", line, string); }
+
+        let fields = [("depth", self.depth.map(u64::from)), ("seldepth", self.seldepth.map(u64::from)),
+            ("multipv", self.multipv.map(u64::from)), ("nodes", self.nodes), ("nps", self.nps), ("time", self.time)];
+        for (name, value) in fields {
+            if let Some(value) = value { line.push_str(&format!(" {} {}", name, value)); }
+        }
+
+        match self.score {
+            Some(Score::Centipawns(cp)) => { line.push_str(&format!(" score cp {}", cp)); }
+            Some(Score::Mate(n)) => { line.push_str(&format!(" score mate {}", n)); }
+            None => {}
+        }
+        if !self.pv.is_empty() { line.push_str(&format!(" pv {}", self.pv.join(" "))); }
+
+        line
+    }
+}
+
+impl Display for UciInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(string) = &self.string { return write!(f, "{}", string); }
+
+        let mut fields = Vec::new();
+        match (self.depth, self.seldepth) {
+            (Some(depth), Some(seldepth)) => { fields.push(format!("depth {}/{}", depth, seldepth)); }
+            (Some(depth), None) => { fields.push(format!("depth {}", depth)); }
+            _ => {}
+        }
+        if let Some(score) = self.score { fields.push(format!("score {}", score)); }
+        if let Some(nodes) = self.nodes { fields.push(format!("nodes {}", nodes)); }
+        if let Some(nps) = self.nps { fields.push(format!("nps {}", nps)); }
+        if let Some(time) = self.time { fields.push(format!("time {}ms", time)); }
+        if !self.pv.is_empty() { fields.push(format!("pv {}", self.pv.join(" "))); }
+
+        write!(f, "{}", fields.join(" "))
+    }
+}
+
+/// The result of a search: the best move and the last score the engine reported.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Evaluation {
+    pub best: Move,
+    pub score: Option<Score>,
+}
+
+/// Search limits passed to the engine's `go` command, times are in milliseconds.
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Limits {
+    time: Option<NonZeroU64>,
+    depth: Option<NonZeroU8>,
+    w_time: Option<NonZeroU64>,
+    b_time: Option<NonZeroU64>,
+    w_inc: Option<NonZeroU64>,
+    b_inc: Option<NonZeroU64>
+}
+
+impl Limits {
+    pub fn time(mut self, time: u64) -> Self {
+        self.time = NonZeroU64::new(time);
+        self
+    }
+
+    pub fn depth(mut self, depth: u8) -> Self {
+        self.depth = NonZeroU8::new(depth);
+        self
+    }
+
+    pub fn w_time(mut self, w_time: u64) -> Self {
+        self.w_time = NonZeroU64::new(w_time);
+        self
+    }
+
+    pub fn b_time(mut self, b_time: u64) -> Self {
+        self.b_time = NonZeroU64::new(b_time);
+        self
+    }
+
+    pub fn set_time(&mut self, w_time: u64, b_time: u64)  {
+        self.w_time = NonZeroU64::new(w_time);
+        self.b_time = NonZeroU64::new(b_time);
+    }
+
+    pub fn w_inc(mut self, w_inc: u64) -> Self {
+        self.w_inc = NonZeroU64::new(w_inc);
+        self
+    }
+
+    pub fn b_inc(mut self, b_inc: u64) -> Self {
+        self.b_inc = NonZeroU64::new(b_inc);
+        self
+    }
+
+    /// The milliseconds on `turn`'s clock, None if the limits have no clock for it.
+    pub fn clock(&self, turn: Color) -> Option<u64> {
+        match turn {
+            Color::White => { self.w_time.map(NonZeroU64::get) }
+            Color::Black => { self.b_time.map(NonZeroU64::get) }
+        }
+    }
+
+    /// The milliseconds `turn` gets back after each move.
+    pub fn increment(&self, turn: Color) -> u64 {
+        match turn {
+            Color::White => { self.w_inc.map(NonZeroU64::get).unwrap_or(0) }
+            Color::Black => { self.b_inc.map(NonZeroU64::get).unwrap_or(0) }
+        }
+    }
+
+    /// The PGN `TimeControl` tag for games played at these limits: `seconds+increment` for white's clock,
+    /// `1/seconds` (a move each period) for a fixed move time and `-` for searches to a depth.
+    pub fn pgn_time_control(&self) -> String {
+        // seconds, floats print without a fraction when there is none
+        let seconds = |ms: u64| (ms as f64 / 1_000.0).to_string();
+
+        match (self.clock(Color::White), self.time) {
+            (Some(clock), _) => { format!("{}+{}", seconds(clock), seconds(self.increment(Color::White))) }
+            (None, Some(time)) => { format!("1/{}", seconds(time.get())) }
+            (None, None) => { "-".to_string() }
+        }
+    }
+
+    pub(crate) fn max_depth(&self) -> Option<u8> {
+        self.depth.map(NonZeroU8::get)
+    }
+
+    // milliseconds to think about one move of `turn`: the fixed move time, or a share of the clock and
+    // most of the increment. None when neither is set
+    pub(crate) fn move_time(&self, turn: Color) -> Option<u64> {
+        if let Some(time) = self.time { return Some(time.get()); }
+
+        let (clock, increment) = match turn {
+            Color::White => { (self.w_time, self.w_inc) }
+            Color::Black => { (self.b_time, self.b_inc) }
+        };
+        let (clock, increment) = (clock?.get(), increment.map(NonZeroU64::get).unwrap_or(0));
+
+        // never more than half of what's left
+        Some((clock / 30 + increment * 3 / 4).min(clock / 2).max(1))
+    }
+
+    /// Parses a `go` command like "go wtime 60000 btime 60000 winc 1000", anything it doesn't know
+    /// (like `infinite` or `nodes`) is ignored.
+    pub fn parse(command: &str) -> Limits {
+        let mut parts = command.split_whitespace().skip_while(|x| *x == "go");
+        let mut limits = Limits::default();
+
+        while let Some(part) = parts.next() {
+            let mut value = || parts.next().and_then(|x| x.parse::<u64>().ok()).unwrap_or(0);
+
+            match part {
+                "movetime" => { limits.time = NonZeroU64::new(value()); }
+                "depth" => { limits.depth = NonZeroU8::new(value().min(u8::MAX as u64) as u8); }
+                "wtime" => { limits.w_time = NonZeroU64::new(value()); }
+                "btime" => { limits.b_time = NonZeroU64::new(value()); }
+                "winc" => { limits.w_inc = NonZeroU64::new(value()); }
+                "binc" => { limits.b_inc = NonZeroU64::new(value()); }
+                _ => {}
+            }
+        }
+
+        limits
+    }
+
+    // milliseconds a search of `turn` may take at most: the move time, or all of its clock. None for
+    // searches that only have a depth
+    pub(crate) fn longest_search(&self, turn: Color) -> Option<u64> {
+        let clock = match turn {
+            Color::White => { self.w_time }
+            Color::Black => { self.b_time }
+        };
+
+        self.time.or(clock).map(NonZeroU64::get)
+    }
+
+    fn into_limit_string(self) -> String {
+        let mut ret = String::new();
+
+        if let Some(time) = self.time {
+            ret.push_str(&format!(" movetime {}", time));
+        }
+
+        if let Some(depth) = self.depth {
+            ret.push_str(&format!(" depth {}", depth));
+        }
+
+        if let Some(w_time) = self.w_time {
+            ret.push_str(&format!(" wtime {}", w_time));
+        }
+
+        if let Some(b_time) = self.b_time {
+            ret.push_str(&format!(" btime {}", b_time));
+        }
+
+        if let Some(w_inc) = self.w_inc {
+            ret.push_str(&format!(" winc {}", w_inc));
+        }
+
+        if let Some(b_inc) = self.b_inc {
+            ret.push_str(&format!(" binc {}", b_inc));
+        }
+
+        // default limit will be depth 20
+        if ret.is_empty() {
+            ret.push_str("depth 20");
+        }
+
+        ret
+    }
 }
\ No newline at end of file
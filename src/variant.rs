@@ -0,0 +1,277 @@
+//! Chess variants: the same pieces under other rules.
+//!
+//! A [`Variant`] knows the rules a [`Game`] doesn't: where the game starts, which positions are allowed,
+//! which moves are legal and when the game is over. Positions stay plain `Game`s, so everything that only
+//! looks at the board works for every variant. Engines that play variants list them in their
+//! `UCI_Variant` option, see [`Uci::set_variant`](crate::uci::Uci::set_variant).
+
+use std::fmt::{Display, Formatter};
+use crate::chess::{Color, DrawReason, Game, Move, MoveFlags, MoveResult, Piece};
+use crate::error::{FenError, FenErrorReason, IllegalMove, IllegalReason, PositionProblem};
+use crate::square::{Rank, Square};
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Variant {
+    #[default]
+    Standard,
+    /// White has 36 pawns and no king against the usual army. Pawns on the first rank can move two
+    /// squares, and black wins by taking every white piece.
+    Horde,
+    /// Both armies side by side on the first two ranks, without pawns. No side may give check, and the
+    /// first king to reach the eighth rank wins, unless black's king gets there on the very next move.
+    RacingKings,
+}
+
+/// How a variant game ended.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VariantEnd {
+    /// The side given checkmated the other.
+    Checkmate(Color),
+    Stalemate,
+    Draw(DrawReason),
+    /// Horde: black took the whole horde.
+    HordeTaken,
+    /// Racing kings: the king of the side given reached the eighth rank first.
+    KingReachedGoal(Color),
+    /// Racing kings: black's king followed white's there right away.
+    BothKingsReachedGoal,
+    /// Custom rules: the side given took the other king, see [`RuleModifier::KingCapture`](crate::rules::RuleModifier::KingCapture).
+    KingTaken(Color),
+    /// Custom rules: the side given checked the other as often as the rules allow.
+    ChecksGiven(Color),
+}
+
+impl VariantEnd {
+    /// The side that won, None for a draw.
+    pub fn winner(self) -> Option<Color> {
+        match self {
+            VariantEnd::Checkmate(color) | VariantEnd::KingReachedGoal(color) | VariantEnd::KingTaken(color) | VariantEnd::ChecksGiven(color) => {
+                Some(color)
+            }
+            VariantEnd::HordeTaken => { Some(Color::Black) }
+            VariantEnd::Stalemate | VariantEnd::Draw(_) | VariantEnd::BothKingsReachedGoal => { None }
+        }
+    }
+
+    /// "1-0", "0-1" or "1/2-1/2".
+    pub fn pgn_result(self) -> &'static str {
+        match self.winner() {
+            Some(Color::White) => { "1-0" }
+            Some(Color::Black) => { "0-1" }
+            None => { "1/2-1/2" }
+        }
+    }
+}
+
+impl Display for VariantEnd {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VariantEnd::Checkmate(color) => { write!(f, "{:?} checkmated", color) }
+            VariantEnd::Stalemate => { write!(f, "stalemate") }
+            VariantEnd::Draw(reason) => { write!(f, "draw by {:?}", reason) }
+            VariantEnd::HordeTaken => { write!(f, "black took the horde") }
+            VariantEnd::KingReachedGoal(color) => { write!(f, "{:?}'s king reached the eighth rank", color) }
+            VariantEnd::BothKingsReachedGoal => { write!(f, "both kings reached the eighth rank") }
+            VariantEnd::KingTaken(color) => { write!(f, "{:?} took the king", color) }
+            VariantEnd::ChecksGiven(color) => { write!(f, "{:?} gave the last check", color) }
+        }
+    }
+}
+
+impl Variant {
+    pub const ALL: [Variant; 3] = [Variant::Standard, Variant::Horde, Variant::RacingKings];
+
+    /// The name in a PGN `Variant` tag.
+    pub fn name(self) -> &'static str {
+        match self {
+            Variant::Standard => { "Standard" }
+            Variant::Horde => { "Horde" }
+            Variant::RacingKings => { "Racing Kings" }
+        }
+    }
+
+    /// The value of the `UCI_Variant` option.
+    pub fn uci_name(self) -> &'static str {
+        match self {
+            Variant::Standard => { "chess" }
+            Variant::Horde => { "horde" }
+            Variant::RacingKings => { "racingkings" }
+        }
+    }
+
+    /// The variant named `name`, either of its names, ignoring case and spaces.
+    pub fn from_name(name: &str) -> Option<Variant> {
+        let name: String = name.chars().filter(|x| !x.is_whitespace()).collect::<String>().to_lowercase();
+        Variant::ALL.into_iter().find(|x| x.uci_name() == name || x.name().replace(' ', "").to_lowercase() == name)
+    }
+
+    pub fn start(self) -> Game {
+        match self {
+            Variant::Standard => { Game::default() }
+            Variant::Horde => { self.from_fen(HORDE_START).expect("the start is a horde position") }
+            Variant::RacingKings => { self.from_fen(RACING_KINGS_START).expect("the start is a racing kings position") }
+        }
+    }
+
+    /// Parses a FEN of this variant, like [`Game::from_fen`] but allowing what the variant allows.
+    pub fn from_fen(self, fen: impl AsRef<str>) -> Result<Game, FenError> {
+        // horde pawns moving up from the first rank pass the second
+        let en_passant_ranks: &[Rank] = match self {
+            Variant::Horde => { &[Rank::new(1), Rank::new(2), Rank::new(5)] }
+            _ => { &[Rank::new(2), Rank::new(5)] }
+        };
+        let game = Game::parse_fen(fen.as_ref(), en_passant_ranks)?;
+
+        let problems = self.validate(&game);
+        if !problems.is_empty() { return Err(FenError::new(0, FenErrorReason::IllegalPosition(problems))); }
+
+        Ok(game)
+    }
+
+    /// Everything that makes `game` impossible in this variant, see [`Game::validate`].
+    pub fn validate(self, game: &Game) -> Vec<PositionProblem> {
+        let mut problems = game.validate();
+
+        match self {
+            Variant::Standard => {}
+            Variant::Horde => {
+                let first_rank_push = game.en_passant.filter(|_| game.turn == Color::Black).is_some_and(|x| {
+                    let to = x.index();
+                    (8..16).contains(&to) && game.board[to + 8] == Some(Piece::WPawn) && game.board[to].is_none() && game.board[to - 8].is_none()
+                });
+
+                problems.retain(|problem| match problem {
+                    PositionProblem::KingCount(Color::White, 0) | PositionProblem::TooManyPawns(Color::White) => { false }
+                    PositionProblem::PawnOnBackRank(square) => { !(*square < 8 && game.board[*square] == Some(Piece::WPawn)) }
+                    PositionProblem::EnPassantWithoutPawn => { !first_rank_push }
+                    _ => { true }
+                });
+
+                // without a white king, validate doesn't look at checks
+                if game.find_king(Color::White).is_none() && game.turn == Color::White && game.is_in_check(Color::Black) {
+                    problems.push(PositionProblem::OpponentInCheck);
+                }
+            }
+            Variant::RacingKings => {
+                if game.is_in_check(game.turn) { problems.push(PositionProblem::ChecksNotAllowed(game.turn)); }
+            }
+        }
+
+        problems
+    }
+
+    /// Every legal move for the side to move.
+    pub fn legal_moves(self, game: &Game) -> Vec<Move> {
+        match self {
+            Variant::Standard => { game.generate_legal_moves() }
+            Variant::Horde => {
+                let pushes = first_rank_pushes(game).filter(|mv| {
+                    let mut after = *game;
+                    after.play_unchecked(*mv);
+                    !after.is_in_check(Color::White)
+                });
+
+                game.legal_moves().chain(pushes).collect()
+            }
+            Variant::RacingKings => { game.legal_moves().filter(|x| !game.gives_check(*x)).collect() }
+        }
+    }
+
+    // the moves of legal_moves, also those leaving the mover's king attacked
+    pub(crate) fn pseudo_legal_moves(self, game: &Game) -> Vec<Move> {
+        match self {
+            Variant::Standard => { game.pseudo_legal_moves().collect() }
+            Variant::Horde => { game.pseudo_legal_moves().chain(first_rank_pushes(game)).collect() }
+            Variant::RacingKings => { game.pseudo_legal_moves().filter(|x| !game.gives_check(*x)).collect() }
+        }
+    }
+
+    pub fn is_legal(self, game: &Game, mv: Move) -> bool {
+        self.legal_moves(game).contains(&mv)
+    }
+
+    /// Plays `mv` if it is legal here, and tells whether that ended the game.
+    pub fn make_move(self, game: &mut Game, mv: Move) -> Result<Option<VariantEnd>, IllegalMove> {
+        if self == Variant::Standard {
+            game.make_move(mv)?;
+            return Ok(self.end(game));
+        }
+
+        if !self.is_legal(game, mv) {
+            let checks = self == Variant::RacingKings && game.explain_illegal(mv.from, mv.to).is_none();
+            let explanation = if checks { Some(IllegalReason::GivesCheck) } else { game.explain_illegal(mv.from, mv.to) };
+
+            return Err(IllegalMove { mv, reason: explanation.map_or(MoveResult::Illegal, IllegalReason::move_result), explanation });
+        }
+
+        game.play_unchecked(mv);
+        Ok(self.end(game))
+    }
+
+    /// How the game is over in `game`, None if it goes on.
+    pub fn end(self, game: &Game) -> Option<VariantEnd> {
+        if let Some(end) = self.goal(game) { return Some(end); }
+
+        if self.legal_moves(game).is_empty() {
+            return Some(if game.is_in_check(game.turn) { VariantEnd::Checkmate(!game.turn) } else { VariantEnd::Stalemate });
+        }
+
+        self.draw(game)
+    }
+
+    // the ends a variant has on top of mate and stalemate
+    pub(crate) fn goal(self, game: &Game) -> Option<VariantEnd> {
+        match self {
+            Variant::Standard => { None }
+            Variant::Horde => {
+                let horde = Square::all().all(|x| game.board[x].is_none_or(|x| x.color() != Color::White));
+                horde.then_some(VariantEnd::HordeTaken)
+            }
+            Variant::RacingKings => {
+                let home = |color| game.find_king(color).is_some_and(|x| Square::new(x).rank() == Rank::new(7));
+
+                match (home(Color::White), home(Color::Black)) {
+                    (true, true) => { Some(VariantEnd::BothKingsReachedGoal) }
+                    (false, true) => { Some(VariantEnd::KingReachedGoal(Color::Black)) }
+                    (true, false) => {
+                        // black gets one move to draw by getting there too
+                        let king = game.find_king(Color::Black);
+                        let catches_up = game.turn == Color::Black
+                            && self.legal_moves(game).iter().any(|x| Some(x.from.index()) == king && x.to.rank() == Rank::new(7));
+
+                        (!catches_up).then_some(VariantEnd::KingReachedGoal(Color::White))
+                    }
+                    (false, false) => { None }
+                }
+            }
+        }
+    }
+
+    // draws by rule, the game isn't over by mate or stalemate yet
+    pub(crate) fn draw(self, game: &Game) -> Option<VariantEnd> {
+        // the material rules are chess's, the variants only draw by the fifty move rule
+        match self {
+            Variant::Standard => { game.draw_reason().map(VariantEnd::Draw) }
+            _ if game.hm_clock() >= 100 => { Some(VariantEnd::Draw(DrawReason::FiftyMoveRule)) }
+            _ => { None }
+        }
+    }
+}
+
+impl Display for Variant {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+// white's pawns on the first rank moving two squares, whatever that does to white's king
+fn first_rank_pushes(game: &Game) -> impl Iterator<Item = Move> + '_ {
+    (0..8).filter(|_| game.turn == Color::White)
+        .filter(|from| game.board[*from] == Some(Piece::WPawn) && game.board[from + 8].is_none() && game.board[from + 16].is_none())
+        .map(|from| Move::new(Square::new(from), Square::new(from + 16), None).with_flags(MoveFlags::DOUBLE_PUSH))
+}
+
+const HORDE_START: &str = "rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/PPPPPPPP w kq - 0 1";
+const RACING_KINGS_START: &str = "8/8/8/8/8/8/krbnNBRK/qrbnNBRQ w - - 0 1";
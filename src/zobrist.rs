@@ -0,0 +1,35 @@
+//! Random keys for Zobrist hashing, see [`Game::zobrist`](crate::Game::zobrist).
+//!
+//! The keys are made at compile time from a fixed seed, so a hash is the same on every run and can be stored.
+
+const fn split_mix(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+
+    (state, z ^ (z >> 31))
+}
+
+const fn keys<const N: usize>(seed: u64) -> [u64; N] {
+    let mut keys = [0; N];
+    let mut state = seed;
+
+    let mut i = 0;
+    while i < N {
+        let (next, key) = split_mix(state);
+        (state, keys[i]) = (next, key);
+        i += 1;
+    }
+
+    keys
+}
+
+// one per piece (in the order of `Piece`) and square, piece * 64 + square
+pub(crate) const PIECE_SQUARE: [u64; 12 * 64] = keys(0x5EED_0001);
+// one per combination of castling rights
+pub(crate) const CASTLING: [u64; 16] = keys(0x5EED_0002);
+// one per file of an en passant square a pawn can actually take on
+pub(crate) const EN_PASSANT_FILE: [u64; 8] = keys(0x5EED_0003);
+// xored in when black is to move
+pub(crate) const BLACK_TO_MOVE: u64 = keys::<1>(0x5EED_0004)[0];
@@ -0,0 +1,49 @@
+use chess::adaptive::{Adaptive, EASE_OFF, LEVELS, TIGHTEN};
+use chess::uci::{Limits, Score};
+
+#[test]
+fn eases_off_when_the_player_is_losing() {
+    let mut adaptive = Adaptive::default();
+    let start = adaptive.index();
+
+    // the engine's score, so a good one for it is a bad one for the player
+    assert!(adaptive.update(Score::Centipawns(EASE_OFF)));
+    assert_eq!(adaptive.index(), start - 1);
+    assert!(adaptive.level().elo < LEVELS[start].elo);
+
+    assert!(!adaptive.update(Score::Centipawns(EASE_OFF - 1)));
+    assert_eq!(adaptive.index(), start - 1);
+}
+
+#[test]
+fn tightens_when_the_player_is_winning() {
+    let mut adaptive = Adaptive::default();
+    let start = adaptive.index();
+
+    assert!(adaptive.update(Score::Centipawns(-TIGHTEN)));
+    assert!(adaptive.update(Score::Mate(-3)));
+    assert_eq!(adaptive.index(), start + 2);
+}
+
+#[test]
+fn levels_stop_at_the_ends() {
+    let mut weakest = Adaptive::new(0);
+    assert!(!weakest.update(Score::Mate(2)));
+    assert_eq!(weakest.index(), 0);
+
+    let mut strongest = Adaptive::new(100);
+    assert_eq!(strongest.index(), LEVELS.len() - 1);
+    assert!(!strongest.update(Score::Centipawns(-900)));
+}
+
+#[test]
+fn options_and_limits_follow_the_level() {
+    let adaptive = Adaptive::new(2);
+    let [(limit, on), (elo, value)] = adaptive.options();
+
+    assert_eq!((limit, on.as_str()), ("UCI_LimitStrength", "true"));
+    assert_eq!((elo, value), ("UCI_Elo", LEVELS[2].elo.to_string()));
+
+    let go = format!("go movetime {} depth {}", LEVELS[2].time, LEVELS[2].depth);
+    assert_eq!(adaptive.limits(), Limits::parse(&go));
+}
@@ -0,0 +1,36 @@
+use chess::archive::GameInfo;
+use chess::{History, Pgn};
+
+fn info(white: &str, black: &str, event: &str, round: &str) -> GameInfo {
+    GameInfo { white: white.to_string(), black: black.to_string(), event: event.to_string(), round: round.to_string() }
+}
+
+#[test]
+fn game_info_round_trips_through_tags() {
+    let mut pgn = Pgn::new(History::default());
+    info("Alice", "Stockfish 16", "Club night", "3").apply(&mut pgn);
+
+    assert_eq!(pgn.tag("White"), Some("Alice"));
+    assert_eq!(pgn.tag("Round"), Some("3"));
+
+    let parsed = Pgn::parse(&pgn.to_string()).unwrap();
+    assert_eq!(GameInfo::from_pgn(&parsed), info("Alice", "Stockfish 16", "Club night", "3"));
+}
+
+#[test]
+fn empty_fields_are_unknown() {
+    let mut pgn = Pgn::new(History::default());
+    assert_eq!(GameInfo::from_pgn(&pgn), GameInfo::default());
+
+    info("Alice", " ", "", "").apply(&mut pgn);
+    assert_eq!(pgn.tag("Black"), Some("?"));
+    assert_eq!(pgn.tag("Event"), Some("?"));
+}
+
+#[test]
+fn defaults_fill_empty_fields() {
+    let filled = info("Alice", "", "  ", "2").or(info("Player", "Engine", "Casual game", ""));
+    assert_eq!(filled, info("Alice", "Engine", "Casual game", "2"));
+
+    assert_eq!(filled.swapped(), info("Engine", "Alice", "Casual game", "2"));
+}
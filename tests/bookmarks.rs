@@ -0,0 +1,56 @@
+mod common;
+
+use chess::bookmarks::{Bookmark, BookmarkLibrary};
+use chess::epd::Epd;
+use chess::Game;
+use common::TempDir;
+
+fn bookmark(name: &str, tags: &[&str], fen: &str) -> Bookmark {
+    Bookmark::new(name, tags.iter().map(|x| x.to_string()).collect(), Game::from_fen(fen).unwrap())
+}
+
+#[test]
+fn bookmarks_are_epd_lines() {
+    let lucena = bookmark("Lucena position", &["endgame", "rook ending"], "1K1k4/1P6/8/8/8/8/r7/2R5 w - - 12 60");
+    let epd = lucena.to_epd();
+    assert_eq!(epd.to_string(), "1K1k4/1P6/8/8/8/8/r7/2R5 w - - id \"Lucena position\"; c0 endgame \"rook ending\"; hmvc 12; fmvn 60;");
+    assert_eq!(Bookmark::from_epd(&Epd::parse(&epd.to_string()).unwrap()), lucena);
+
+    // a plain EPD line is named by its position
+    let plain = Bookmark::from_epd(&Epd::parse("8/8/8/8/8/8/8/K1k5 w - -").unwrap());
+    assert_eq!(plain.name, "8/8/8/8/8/8/8/K1k5 w - - 0 1");
+    assert!(plain.tags.is_empty());
+}
+
+#[test]
+fn filters_match_every_word() {
+    let lucena = bookmark("Lucena position", &["Endgame", "rook ending"], "1K1k4/1P6/8/8/8/8/r7/2R5 w - - 0 1");
+
+    assert!(lucena.matches(""));
+    assert!(lucena.matches("lucena ROOK"));
+    assert!(lucena.matches("endgame pos"));
+    assert!(!lucena.matches("lucena pawn"));
+}
+
+#[test]
+fn libraries_are_saved_as_they_change() {
+    let dir = TempDir::new("bookmarks-library");
+    let path = dir.join("bookmarks.epd");
+
+    let mut library = BookmarkLibrary::open(&path).unwrap();
+    assert!(library.bookmarks.is_empty());
+    library.add(bookmark("Lucena", &["endgame", "rook"], "1K1k4/1P6/8/8/8/8/r7/2R5 w - - 0 1")).unwrap();
+    library.add(bookmark("Start", &[], "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")).unwrap();
+    library.add(bookmark("Philidor", &["rook", "defence"], "4k3/8/r7/4PK2/8/8/8/1R6 b - - 0 1")).unwrap();
+    library.remove(1).unwrap();
+    assert_eq!(library.tags(), ["defence", "endgame", "rook"]);
+
+    // lines that aren't bookmarks are skipped
+    let mut text = std::fs::read_to_string(&path).unwrap();
+    text.push_str("\nnot a position\n");
+    std::fs::write(&path, text).unwrap();
+
+    let read = BookmarkLibrary::open(&path).unwrap();
+    assert_eq!(read.bookmarks, library.bookmarks);
+    assert_eq!(read.path(), path);
+}
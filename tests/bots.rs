@@ -0,0 +1,71 @@
+use std::io::Cursor;
+use chess::bots::{run_bot, Bot, Personality};
+use chess::{Game, MoveResult, Square};
+
+#[test]
+fn bots_play_legal_games() {
+    for personality in Personality::ALL {
+        let mut bot = Bot::with_seed(personality, 7);
+        let mut game = Game::default();
+
+        for _ in 0..200 {
+            let Some(mv) = bot.choose(&game) else { break; };
+            let result = game.make_move(mv).unwrap_or_else(|_| panic!("{} played {}", personality.name(), mv));
+            if !matches!(result, MoveResult::Valid | MoveResult::Check) { break; }
+        }
+    }
+}
+
+#[test]
+fn greedy_takes_the_biggest_piece() {
+    let game = Game::from_fen("4k3/8/8/1q1r4/2P5/8/8/4K3 w - - 0 1").unwrap();
+
+    for seed in 1..20 {
+        let mv = Bot::with_seed(Personality::Greedy, seed).choose(&game).unwrap();
+        assert_eq!((mv.from, mv.to), (Square::C4, Square::B5));
+    }
+}
+
+#[test]
+fn bots_take_mate_in_one() {
+    let game = Game::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+
+    for personality in [Personality::Greedy, Personality::Sacrificer, Personality::Martin] {
+        let mv = Bot::with_seed(personality, 3).choose(&game).unwrap();
+        let mut after = game;
+        assert_eq!(after.make_move(mv), Ok(MoveResult::Checkmate), "{}", personality.name());
+    }
+}
+
+#[test]
+fn sacrificer_checks() {
+    let game = Game::from_fen("4k3/pppp4/8/8/8/8/8/3QK3 w - - 0 1").unwrap();
+
+    for seed in 1..20 {
+        let mv = Bot::with_seed(Personality::Sacrificer, seed).choose(&game).unwrap();
+        assert!(game.gives_check(mv), "{}", mv);
+    }
+}
+
+#[test]
+fn run_bot_answers_go() {
+    let input = Cursor::new("uci\nisready\nposition startpos moves e2e4\ngo movetime 100\nquit\n");
+    let mut output = Vec::new();
+    run_bot(Personality::Random, input, &mut output).unwrap();
+
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("id name Random mover\n") && output.contains("uciok\nreadyok\n"));
+
+    let best = output.lines().find_map(|x| x.strip_prefix("bestmove ")).unwrap();
+    let mut game = Game::default();
+    game.make_move(chess::Move::new(Square::E2, Square::E4, None)).unwrap();
+    assert!(game.legal_moves().any(|x| x.to_string() == best), "{}", best);
+}
+
+#[test]
+fn personalities_round_trip_their_keys() {
+    for personality in Personality::ALL {
+        assert_eq!(Personality::from_key(personality.key()), Some(personality));
+    }
+    assert_eq!(Personality::from_key("stockfish"), None);
+}
@@ -0,0 +1,29 @@
+use chess::classics::classics;
+use chess::Color;
+
+#[test]
+fn classics_are_played_to_the_end() {
+    let games = classics();
+    assert_eq!(games.len(), 4);
+
+    for game in &games {
+        let end = game.history.position(game.history.len());
+        let result = game.tag("Result").unwrap();
+        let loser = if result == "1-0" { Color::Black } else { Color::White };
+
+        // every one of them ends in mate
+        assert!(end.is_in_checkmate(loser), "{}", game.tag("Annotator").unwrap_or_default());
+    }
+}
+
+#[test]
+fn classics_keep_their_annotations() {
+    let games = classics();
+    let opera = games.iter().find(|x| x.tag("Annotator") == Some("The Opera Game")).unwrap();
+
+    // 3... Bg4 is questionable, with 3... exd4 given instead
+    let notes = opera.notes(5).unwrap();
+    assert_eq!(notes.nags, vec![2]);
+    assert!(notes.comment.is_some());
+    assert_eq!(notes.variations.len(), 1);
+}
@@ -0,0 +1,50 @@
+//! Helpers shared by the integration tests, a test file pulls them in with `mod common;`.
+
+// every test file uses only some of them
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+use chess::{Game, Move, Square};
+
+/// A fresh directory under the system's temp dir, removed with everything in it when dropped,
+/// so a failing assert doesn't leave files behind.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    /// `name` has to be unique among the tests of one test file, they share a process id.
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("chess-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+
+        TempDir(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    pub fn join(&self, file: &str) -> PathBuf {
+        self.0.join(file)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+pub fn mv(from: Square, to: Square) -> Move {
+    Move::new(from, to, None)
+}
+
+/// The game after playing `moves` from the start position, none of them promotions.
+pub fn play(moves: &[(Square, Square)]) -> Game {
+    let mut game = Game::default();
+    for (from, to) in moves {
+        game.make_move(mv(*from, *to)).unwrap();
+    }
+
+    game
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use chess::coordinates::{CoordinateScore, HighScores, MAX_SCORES};
+use common::TempDir;
+
+fn table(dir: &TempDir) -> HighScores {
+    HighScores::open(dir.join("scores.txt")).unwrap()
+}
+
+fn score(hits: u32, misses: u32, time: u64) -> CoordinateScore {
+    CoordinateScore { hits, misses, time }
+}
+
+#[test]
+fn scores_are_ranked() {
+    let dir = TempDir::new("coordinates-ranked");
+    let mut scores = table(&dir);
+
+    assert_eq!(scores.add(score(20, 3, 1)).unwrap(), Some(0));
+    assert_eq!(scores.add(score(25, 5, 2)).unwrap(), Some(0));
+    // fewer mistakes break a tie, then the older round stays ahead
+    assert_eq!(scores.add(score(20, 1, 3)).unwrap(), Some(1));
+    assert_eq!(scores.add(score(20, 1, 4)).unwrap(), Some(2));
+
+    let hits: Vec<(u32, u64)> = scores.scores.iter().map(|x| (x.hits, x.time)).collect();
+    assert_eq!(hits, vec![(25, 2), (20, 3), (20, 4), (20, 1)]);
+}
+
+#[test]
+fn only_the_best_are_kept() {
+    let dir = TempDir::new("coordinates-best");
+    let mut scores = table(&dir);
+
+    for hits in 1..=MAX_SCORES as u32 {
+        scores.add(score(hits, 0, 0)).unwrap();
+    }
+    assert_eq!(scores.add(score(0, 0, 0)).unwrap(), None);
+    assert_eq!(scores.add(score(30, 0, 0)).unwrap(), Some(0));
+
+    assert_eq!(scores.scores.len(), MAX_SCORES);
+    assert_eq!(scores.scores.last().unwrap().hits, 2);
+}
+
+#[test]
+fn scores_are_saved() {
+    let dir = TempDir::new("coordinates-saved");
+    let mut scores = table(&dir);
+    scores.add(score(12, 2, 1_700_000_000)).unwrap();
+    scores.add(score(18, 0, 1_700_000_100)).unwrap();
+
+    let read = HighScores::open(scores.path()).unwrap();
+    assert_eq!(read.scores, scores.scores);
+}
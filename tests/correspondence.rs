@@ -0,0 +1,112 @@
+mod common;
+
+use chess::correspondence::{Correspondence, CorrespondenceGame, OpponentKind, ReplyWatcher};
+use chess::error::IllegalReason;
+use chess::result::{GameResult, TerminationReason};
+use chess::{Color, Game, Square};
+use common::{mv, TempDir};
+
+const DAY: u64 = 86_400;
+
+#[test]
+fn games_round_trip_through_pgn() {
+    let mut game = CorrespondenceGame::new(Color::Black, "Greedy", OpponentKind::Engine, 3, 1_000);
+    game.play(mv(Square::E2, Square::E4), 2_000).unwrap();
+
+    let parsed = CorrespondenceGame::parse("game.pgn", &game.to_pgn()).unwrap();
+    assert_eq!(parsed.player, Color::Black);
+    assert_eq!(parsed.opponent, "Greedy");
+    assert_eq!(parsed.kind, OpponentKind::Engine);
+    assert_eq!(parsed.days_per_move, 3);
+    assert_eq!(parsed.last_move, 2_000);
+    assert_eq!(parsed.game(), game.game());
+    assert!(parsed.awaits_player());
+
+    // an ordinary pgn isn't a correspondence game
+    assert!(CorrespondenceGame::parse("other.pgn", "[Event \"?\"]\n\n1. e4 *\n").is_none());
+}
+
+#[test]
+fn mate_and_time_end_the_game() {
+    let mut game = CorrespondenceGame::new(Color::White, "Someone", OpponentKind::Remote, 1, 0);
+    assert!(!game.awaits_engine());
+
+    // fool's mate
+    for (from, to) in [(Square::F2, Square::F3), (Square::E7, Square::E5), (Square::G2, Square::G4), (Square::D8, Square::H4)] {
+        game.play(mv(from, to), 10).unwrap();
+    }
+    assert!(game.is_over());
+    assert_eq!(game.pgn.tag("Result"), Some("0-1"));
+
+    let mut game = CorrespondenceGame::new(Color::White, "Someone", OpponentKind::Remote, 2, 0);
+    assert_eq!(game.time_left(DAY).as_secs(), DAY);
+    assert!(!game.check_time(2 * DAY));
+
+    // white, to move, ran out of time
+    assert!(game.check_time(2 * DAY + 1));
+    assert_eq!(game.pgn.tag("Result"), Some("0-1"));
+    assert_eq!(game.pgn.tag("Termination"), Some("time forfeit"));
+    assert!(!game.awaits_player());
+}
+
+#[test]
+fn finished_games_take_no_moves() {
+    let mut game = CorrespondenceGame::new(Color::White, "Someone", OpponentKind::Remote, 1, 0);
+    game.resign();
+    let err = game.play(mv(Square::E2, Square::E4), 10).unwrap_err();
+    assert_eq!(err.explanation, Some(IllegalReason::GameOver));
+    assert_eq!(game.game(), Game::default());
+
+    // a move after the deadline loses on time instead of being played
+    let mut game = CorrespondenceGame::new(Color::White, "Someone", OpponentKind::Remote, 1, 0);
+    assert!(game.play(mv(Square::E2, Square::E4), DAY + 1).is_err());
+    assert_eq!(game.game(), Game::default());
+    assert_eq!(game.result(), Some(GameResult::win(Color::Black, TerminationReason::Timeout)));
+}
+
+#[test]
+fn games_waiting_on_the_player_come_first() {
+    let dir = TempDir::new("correspondence-order");
+    let store = Correspondence::open(dir.path()).unwrap();
+
+    let mut theirs = CorrespondenceGame::new(Color::Black, "Martin", OpponentKind::Engine, 1, 0);
+    let mut finished = CorrespondenceGame::new(Color::White, "Someone", OpponentKind::Remote, 1, 0);
+    finished.resign();
+    let mut mine = CorrespondenceGame::new(Color::White, "Someone", OpponentKind::Remote, 5, 0);
+    for game in [&mut theirs, &mut finished, &mut mine] {
+        store.create(game).unwrap();
+    }
+    std::fs::write(store.dir().join("notes.txt"), "not a game").unwrap();
+
+    let games = store.games().unwrap();
+    assert_eq!(games.iter().map(|x| &x.path).collect::<Vec<_>>(), [&mine.path, &theirs.path, &finished.path]);
+
+    store.delete(&finished).unwrap();
+    assert_eq!(store.games().unwrap().len(), 2);
+}
+
+#[test]
+fn watcher_sees_replies() {
+    let dir = TempDir::new("correspondence-watcher");
+    let store = Correspondence::open(dir.path()).unwrap();
+    let mut game = CorrespondenceGame::new(Color::White, "Someone", OpponentKind::Remote, 1, 0);
+    store.create(&mut game).unwrap();
+
+    let mut watcher = ReplyWatcher::new();
+    // the first look only learns where the games are
+    assert!(watcher.replies(&store.games().unwrap()).is_empty());
+
+    game.play(mv(Square::E2, Square::E4), 1).unwrap();
+    store.save(&game).unwrap();
+    // the player's own move isn't a reply
+    assert!(watcher.replies(&store.games().unwrap()).is_empty());
+
+    game.play(mv(Square::E7, Square::E5), 2).unwrap();
+    store.save(&game).unwrap();
+    let games = store.games().unwrap();
+    let replies = watcher.replies(&games);
+    assert_eq!(replies.len(), 1);
+    assert_eq!(replies[0].path, game.path);
+
+    assert!(watcher.replies(&store.games().unwrap()).is_empty());
+}
@@ -0,0 +1,51 @@
+use chess::{Color, DrawReason, Game};
+
+fn position(fen: &str) -> Game {
+    Game::from_fen(fen).unwrap()
+}
+
+#[test]
+fn knights_need_help_to_mate() {
+    // two knights can mate a king that blunders into it
+    assert!(!position("4k3/8/8/8/8/8/8/2N1KN2 w - - 0 1").insufficient_material(Color::White));
+
+    // a lone knight mates a king walled in by its own rook, not one next to its queen
+    assert!(!position("4k2r/8/8/8/8/8/8/4KN2 w - - 0 1").insufficient_material(Color::White));
+    assert!(position("3qk3/8/8/8/8/8/8/4KN2 w - - 0 1").insufficient_material(Color::White));
+}
+
+#[test]
+fn bishops_on_one_square_color() {
+    let same = position("4k3/8/8/8/8/8/B1B5/1B2K3 w - - 0 1");
+    assert!(same.insufficient_material(Color::White));
+    assert_eq!(same.draw_reason(), Some(DrawReason::InsufficientMaterial));
+
+    // opposite colored bishops can mate in a corner
+    let opposite = position("4kb2/8/8/8/8/8/8/4KB2 w - - 0 1");
+    assert!(!opposite.insufficient_material(Color::White));
+    assert!(!opposite.insufficient_material(Color::Black));
+    assert_eq!(opposite.draw_reason(), None);
+
+    // a knight can block its own king's escape
+    assert!(!position("4kn2/8/8/8/8/8/8/4KB2 w - - 0 1").insufficient_material(Color::White));
+}
+
+#[test]
+fn locked_pawns_with_the_pieces_shut_out_are_dead() {
+    // neither king can cross the wall, and each bishop is on the squares the enemy pawns can't attack
+    let game = position("4kb2/8/8/p1p1p1p1/PpPpPpPp/1P1P1P1P/8/4KB2 w - - 0 1");
+    assert!(game.is_dead_position());
+    assert_eq!(game.draw_reason(), Some(DrawReason::DeadPosition));
+
+    // pawns alone aren't locked when a king can walk over to an undefended one
+    let open = position("4k3/8/8/4p3/4P3/8/8/4K3 w - - 0 1");
+    assert!(!open.is_dead_position());
+    assert_eq!(open.draw_reason(), None);
+}
+
+#[test]
+fn a_bishop_that_can_be_taken_keeps_the_game_alive() {
+    // from c1 the bishop reaches a3, where the b4 pawn can take it
+    let game = position("4kb2/8/8/p1p1p1p1/PpPpPpPp/1P1P1P1P/8/2B1K3 w - - 0 1");
+    assert!(!game.is_dead_position());
+}
@@ -0,0 +1,92 @@
+use std::io::Cursor;
+use chess::engine::{evaluate, run_uci, Engine, EngineOptions};
+use chess::error::EngineOptionError;
+use chess::uci::{Limits, Score};
+use chess::Game;
+
+fn best(engine: &mut Engine, fen: &str, depth: u8) -> (String, Option<Score>) {
+    let game = Game::from_fen(fen).unwrap();
+    let result = engine.search(game, Limits::default().depth(depth), |_| {}).unwrap();
+
+    (result.best.to_string(), result.score)
+}
+
+#[test]
+fn finds_mates_and_free_material() {
+    let mut engine = Engine::default();
+
+    assert_eq!(best(&mut engine, "6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", 4), ("a1a8".to_string(), Some(Score::Mate(1))));
+    // the queen on d5 is hanging
+    assert_eq!(best(&mut engine, "4k3/8/8/3q4/8/8/8/3QK3 w - - 0 1", 4).0, "d1d5");
+    // 1... Kb8 2. Rh8#, whatever black plays
+    assert_eq!(best(&mut engine, "k7/8/1K6/8/8/8/8/7R b - - 0 1", 4), ("a8b8".to_string(), Some(Score::Mate(-1))));
+}
+
+#[test]
+fn every_feature_can_be_turned_off() {
+    let fen = "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3";
+
+    for (name, value) in [("NullMove", "false"), ("LMR", "false"), ("Aspiration", "false"), ("QuiescenceDepth", "0")] {
+        let mut engine = Engine::new(EngineOptions { hash_mb: 1, ..Default::default() });
+        engine.set_option(name, value).unwrap();
+
+        let game = Game::from_fen(fen).unwrap();
+        let mut depths = Vec::new();
+        let result = engine.search(game, Limits::default().depth(4), |x| depths.push(x.depth.unwrap())).unwrap();
+
+        assert!(game.generate_legal_moves().contains(&result.best), "{} {}", name, value);
+        assert_eq!(depths, vec![1, 2, 3, 4]);
+    }
+}
+
+#[test]
+fn options_are_set_by_uci_name() {
+    let mut options = EngineOptions::default();
+
+    options.set_option("hash", "64").unwrap();
+    options.set_option("LMR", "false").unwrap();
+    options.set_option("AspirationWindow", "25").unwrap();
+    assert_eq!((options.hash_mb, options.lmr, options.aspiration_window), (64, false, 25));
+
+    assert_eq!(options.set_option("Hash", "0"), Err(EngineOptionError::InvalidValue("Hash".to_string(), "0".to_string())));
+    assert_eq!(options.set_option("NullMove", "yes"), Err(EngineOptionError::InvalidValue("NullMove".to_string(), "yes".to_string())));
+    assert_eq!(options.set_option("Ponder", "true"), Err(EngineOptionError::Unknown("Ponder".to_string())));
+
+    assert_eq!(options.uci_options().len(), 7);
+    assert!(options.uci_options().contains(&"option name Hash type spin default 64 min 1 max 1024".to_string()));
+}
+
+#[test]
+fn no_moves_no_result() {
+    let mut engine = Engine::default();
+    let mated = Game::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1").unwrap();
+
+    assert_eq!(engine.search(mated, Limits::default().depth(3), |_| {}), None);
+}
+
+#[test]
+fn evaluation_is_symmetric() {
+    assert_eq!(evaluate(&Game::default()), 0);
+
+    let white_up = Game::from_fen("4k3/8/8/8/8/8/8/3QK3 w - - 0 1").unwrap();
+    let black_to_move = Game::from_fen("4k3/8/8/8/8/8/8/3QK3 b - - 0 1").unwrap();
+    assert!(evaluate(&white_up) > 800);
+    assert_eq!(evaluate(&black_to_move), -evaluate(&white_up));
+}
+
+#[test]
+fn speaks_uci() {
+    let input = "uci\nsetoption name Hash value 4\nsetoption name Contempt value 10\nisready\n\
+        position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1\ngo depth 3\nposition startpos moves f2f3 e7e5 g2g4 d8h4\ngo movetime 50\nquit\n";
+    let mut output = Vec::new();
+    run_uci(Cursor::new(input), &mut output).unwrap();
+
+    let output = String::from_utf8(output).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+
+    assert_eq!(lines.iter().filter(|x| x.starts_with("option name")).count(), EngineOptions::default().uci_options().len());
+    for expected in ["uciok", "info string no option named 'Contempt'", "readyok", "bestmove a1a8", "bestmove 0000"] {
+        assert!(lines.contains(&expected), "{} is missing from\n{}", expected, output);
+    }
+    assert!(lines.iter().any(|x| x.starts_with("info depth") && x.contains("score mate 1 pv a1a8")));
+}
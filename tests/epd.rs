@@ -0,0 +1,83 @@
+use chess::engine::Engine;
+use chess::epd::{run_engine_suite, run_suite, Epd};
+use chess::error::EpdErrorReason;
+use chess::{Game, Move, Square};
+
+const SUITE: &str = "6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - bm Ra8#; id \"back rank\";
+r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - am Ng5; id \"no early knight\";
+rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - id \"nothing to solve\"; c0 \"just a comment\";
+7k/8/8/8/8/8/8/K7 b - - bm Kg7 Kh7; hmvc 12; fmvn 40;
+";
+
+#[test]
+fn operations_are_parsed() {
+    let epds = Epd::parse_many(SUITE).unwrap();
+    assert_eq!(epds.len(), 4);
+
+    assert_eq!(epds[0].id(), Some("back rank"));
+    assert_eq!(epds[0].best_moves(), [Move::new(Square::A1, Square::A8, None)]);
+    assert_eq!(epds[1].avoid_moves(), [Move::new(Square::F3, Square::G5, None)]);
+    assert_eq!(epds[2].operation("c0"), Some(&["just a comment".to_string()][..]));
+    assert_eq!(epds[3].best_moves().len(), 2);
+
+    // the clocks come from hmvc and fmvn
+    assert_eq!(epds[3].game, Game::from_fen("7k/8/8/8/8/8/8/K7 b - - 12 40").unwrap());
+    assert_eq!(epds[2].game, Game::default());
+}
+
+#[test]
+fn epds_round_trip() {
+    for (line, epd) in SUITE.lines().zip(Epd::parse_many(SUITE).unwrap()) {
+        assert_eq!(epd.to_string(), line);
+        assert_eq!(Epd::parse(&epd.to_string()).unwrap(), epd);
+    }
+
+    let mut epd = Epd::new(Game::default());
+    epd.set_operation("bm", vec!["e4".to_string()]);
+    epd.set_operation("c0", vec!["two words".to_string(), String::new()]);
+    epd.set_operation("bm", vec!["d4".to_string()]);
+    assert_eq!(epd.to_string(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - bm d4; c0 \"two words\" \"\";");
+}
+
+#[test]
+fn bad_lines_name_their_line() {
+    let err = Epd::parse_many("6k1/8/8/8/8/8/8/6K1 w - - bm Kf1;\n\n6k1/8/8/8/8/8/8/6K1 w - - id \"open").unwrap_err();
+    assert_eq!(err.line, 3);
+    assert!(matches!(err.reason, EpdErrorReason::UnterminatedString));
+
+    let err = Epd::parse("6k1/8/8/8/8/8/8/6K1 x - - bm Kf1;").unwrap_err();
+    assert!(matches!(err.reason, EpdErrorReason::Fen(_)));
+}
+
+#[test]
+fn suites_score_best_and_avoid_moves() {
+    let epds = Epd::parse_many(SUITE).unwrap();
+
+    // a search that plays the first legal move, which solves none of the positions
+    let mut searched = 0;
+    let report = run_suite(&epds, |game| {
+        searched += 1;
+        Ok::<_, ()>(game.legal_moves().next().unwrap())
+    }).unwrap();
+    // the position without bm or am isn't searched
+    assert_eq!(searched, 3);
+    assert_eq!(report.results.iter().map(|x| x.id.as_deref()).collect::<Vec<_>>(), [Some("back rank"), Some("no early knight"), None]);
+
+    // one that knows the answers passes every position, playing an avoided move fails it
+    let answers = [Move::new(Square::A1, Square::A8, None), Move::new(Square::F3, Square::G5, None), Move::new(Square::H8, Square::H7, None)];
+    let mut answer = answers.iter();
+    let report = run_suite(&epds, |_| Ok::<_, ()>(*answer.next().unwrap())).unwrap();
+    assert_eq!(report.results.iter().map(|x| x.passed).collect::<Vec<_>>(), [true, false, true]);
+    assert_eq!(report.passed(), 2);
+    assert_eq!(report.to_string(), "back rank: Ra8# ok\nno early knight: Ng5 failed\n#3: Kh7 ok\n2/3 passed");
+
+    // the search's error stops the suite
+    assert_eq!(run_suite(&epds, |_| Err("no engine")), Err("no engine"));
+}
+
+#[test]
+fn the_built_in_engine_runs_suites() {
+    let epds = Epd::parse_many(SUITE).unwrap();
+    let report = run_engine_suite(&mut Engine::default(), &epds[..1], 100).unwrap();
+    assert_eq!(report.passed(), 1);
+}
@@ -0,0 +1,29 @@
+use chess::explorer::{parse_lichess_moves, LichessDatabase};
+use chess::{Game, Move, Pgn, Square};
+
+#[test]
+fn explorer_replies_are_read() {
+    let position = Pgn::parse("1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5 *\n").unwrap().history.current();
+    let reply = r#"{"white":1200,"draws":300,"black":500,"moves":[
+        {"uci":"e1h1","san":"O-O","averageRating":1800,"white":900,"draws":200,"black":300,"game":null},
+        {"uci":"c2c3","san":"c3","averageRating":1750,"white":300,"draws":100,"black":200,"game":null}
+    ],"topGames":[],"opening":{"eco":"C50","name":"Italian Game: Giuoco Piano"}}"#;
+
+    let moves = parse_lichess_moves(&position, reply).unwrap();
+    assert_eq!(moves.len(), 2);
+
+    // castling comes as the king taking its rook
+    assert_eq!(moves[0].mv, Move::new(Square::E1, Square::G1, None));
+    assert_eq!((moves[0].games, moves[0].white_wins, moves[0].draws, moves[0].black_wins), (1400, 900, 200, 300));
+    assert_eq!(moves[1].mv, Move::new(Square::C2, Square::C3, None));
+
+    assert!(parse_lichess_moves(&position, "Too many requests").is_none());
+    assert_eq!(parse_lichess_moves(&position, r#"{"moves":[]}"#), Some(Vec::new()));
+}
+
+#[test]
+fn explorer_urls_carry_the_fen() {
+    let url = LichessDatabase::Masters.url(&Game::default());
+    assert!(url.starts_with("https://explorer.lichess.ovh/masters?fen=rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR%20w%20KQkq%20-%200%201&"));
+    assert!(LichessDatabase::Lichess.url(&Game::default()).contains("/lichess?variant=standard&"));
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use chess::error::PositionProblem;
+use chess::{Game, Move, Square};
+use common::play;
+
+#[test]
+fn en_passant_square_after_white_push() {
+    let game = play(&[(Square::E2, Square::E4)]);
+
+    assert_eq!(game.en_passant, Some(Square::E3));
+    assert_eq!(game.en_passant_pawn(), Some(Square::E4));
+    assert_eq!(game.as_fen(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+}
+
+#[test]
+fn en_passant_square_after_black_push() {
+    let game = play(&[(Square::E2, Square::E4), (Square::A7, Square::A6), (Square::E4, Square::E5), (Square::F7, Square::F5)]);
+
+    assert_eq!(game.en_passant, Some(Square::F6));
+    assert_eq!(game.en_passant_pawn(), Some(Square::F5));
+    assert_eq!(game.as_fen(), "rnbqkbnr/1pppp1pp/p7/4Pp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3");
+
+    let mut taken = game;
+    taken.make_move(Move::new(Square::E5, Square::F6, None)).unwrap();
+    assert_eq!(taken.board[Square::F5], None);
+    assert_eq!(taken.en_passant, None);
+}
+
+#[test]
+fn en_passant_fen_round_trip() {
+    for fen in ["rnbqkbnr/1pppp1pp/p7/4Pp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3", "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"] {
+        assert_eq!(Game::from_fen(fen).unwrap().as_fen(), fen);
+    }
+}
+
+#[test]
+fn en_passant_off_the_third_and_sixth_ranks() {
+    for square in ["e4", "e2", "e", "e33", "x3"] {
+        let fen = format!("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq {} 0 1", square);
+        assert!(Game::from_fen(&fen).is_err(), "{}", square);
+    }
+}
+
+#[test]
+fn en_passant_on_the_back_ranks_is_a_problem() {
+    // set on the field, which FEN parsing would never allow
+    for (fen, square) in [("4k3/8/8/8/8/8/8/4K3 w - - 0 1", Square::A1), ("4k3/8/8/8/8/8/8/4K3 b - - 0 1", Square::H8)] {
+        let mut game = Game::from_fen(fen).unwrap();
+        game.en_passant = Some(square);
+        assert_eq!(game.validate(), [PositionProblem::EnPassantWithoutPawn], "{}", square);
+    }
+}
+
+#[test]
+fn clocks_at_their_limits_dont_overflow() {
+    let game = Game::from_fen("4k3/8/8/8/8/8/8/4K3 b - - 255 65535").unwrap();
+    assert_eq!(game.legal_moves().count(), 5);
+
+    let mut moved = game;
+    moved.make_move(Move::new(Square::E8, Square::D8, None)).unwrap();
+    assert_eq!((moved.hm_clock(), moved.fm_clock()), (255, 65535));
+}
+
+#[test]
+fn shredder_fen_round_trip() {
+    // a chess960 start with the king on e1 and the rooks in the corners, which castles like chess
+    let fen = "rnqbknbr/pppppppp/8/8/8/8/PPPPPPPP/RNQBKNBR w HAha - 0 1";
+    let game = Game::from_fen(fen).unwrap();
+    assert_eq!(game.as_shredder_fen(), fen);
+    assert_eq!(game.as_fen(), "rnqbknbr/pppppppp/8/8/8/8/PPPPPPPP/RNQBKNBR w KQkq - 0 1");
+
+    let mut moved = game;
+    moved.make_move(Move::new(Square::H2, Square::H4, None)).unwrap();
+    moved.make_move(Move::new(Square::A7, Square::A5, None)).unwrap();
+    moved.make_move(Move::new(Square::H1, Square::H3, None)).unwrap();
+    moved.make_move(Move::new(Square::A8, Square::A6, None)).unwrap();
+    assert_eq!(moved.as_shredder_fen(), "1nqbknbr/1ppppppp/r7/p7/7P/7R/PPPPPPP1/RNQBKNB1 w Ah - 2 3");
+    assert_eq!(Game::from_fen(moved.as_shredder_fen()).unwrap(), moved);
+}
+
+#[test]
+fn castling_letters_and_rook_files_are_the_same_rights() {
+    for (standard, shredder) in [("KQkq", "HAha"), ("Kq", "Ha"), ("Qk", "Ah"), ("-", "-")] {
+        let fen = |castling| format!("r3k2r/8/8/8/8/8/8/R3K2R w {} - 0 1", castling);
+        let a = Game::from_fen(fen(standard)).unwrap();
+        let b = Game::from_fen(fen(shredder)).unwrap();
+
+        assert_eq!(a, b, "{}", shredder);
+        assert_eq!(a.as_shredder_fen(), fen(shredder));
+        assert_eq!(b.as_fen(), fen(standard));
+    }
+}
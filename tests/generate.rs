@@ -0,0 +1,47 @@
+use chess::generate::{invariant_violations, PositionConstraints, Rng};
+use chess::{Color, Game, Piece};
+
+#[test]
+fn random_positions_keep_the_invariants() {
+    let mut rng = Rng::new(1);
+
+    for _ in 0..300 {
+        let game = Game::random_legal_position(&mut rng, PositionConstraints::default());
+        assert_eq!(invariant_violations(&game), Vec::<String>::new(), "{}", game.as_fen());
+    }
+}
+
+#[test]
+fn constraints_are_kept() {
+    let constraints = PositionConstraints {
+        min_pieces: 4, max_pieces: 6, pawns: false, checks: false, turn: Some(Color::Black), ..PositionConstraints::default()
+    };
+    let mut rng = Rng::new(2);
+
+    for _ in 0..100 {
+        let game = Game::random_legal_position(&mut rng, constraints);
+        let pieces = (0..64).filter(|x| game.board[*x].is_some()).count() - 2;
+
+        assert!((4..=6).contains(&pieces), "{}", game.as_fen());
+        assert!(game.pieces(Piece::WPawn).is_empty() && game.pieces(Piece::BPawn).is_empty());
+        assert_eq!(game.turn, Color::Black);
+        assert!(!game.is_in_check(Color::Black));
+    }
+}
+
+#[test]
+fn castling_and_en_passant_come_up() {
+    let mut rng = Rng::new(3);
+    let fens: Vec<String> = (0..500).map(|_| Game::random_legal_position(&mut rng, PositionConstraints { max_pieces: 30, ..PositionConstraints::default() }).as_fen()).collect();
+
+    assert!(fens.iter().any(|x| x.split(' ').nth(2) != Some("-")));
+    assert!(fens.iter().any(|x| x.split(' ').nth(3) != Some("-")));
+}
+
+#[test]
+fn seeds_repeat_positions() {
+    let generate = |seed| Game::random_legal_position(&mut Rng::new(seed), PositionConstraints::default());
+
+    assert_eq!(generate(42), generate(42));
+    assert_ne!(generate(42), generate(43));
+}
@@ -0,0 +1,35 @@
+use std::time::Duration;
+use chess::{Color, Game, History, Move, Square};
+
+#[test]
+fn take_backs_return_the_side_to_its_move() {
+    let mut history = History::default();
+    history.push_timed(Move::new(Square::E2, Square::E4, None), Duration::from_secs(3)).unwrap();
+    history.push(Move::new(Square::E7, Square::E5, None)).unwrap();
+    let after_one = history.current();
+    history.push_timed(Move::new(Square::G1, Square::F3, None), Duration::from_secs(7)).unwrap();
+
+    // white's move, black hasn't replied yet
+    let taken = history.take_back(Color::White);
+    assert_eq!(taken.len(), 1);
+    assert_eq!(taken[0].time, Some(Duration::from_secs(7)));
+    assert_eq!(history.current(), after_one);
+
+    // black's reply goes too
+    let taken = history.take_back(Color::White);
+    assert_eq!(taken.iter().map(|x| x.mv).collect::<Vec<_>>(), [Move::new(Square::E7, Square::E5, None), Move::new(Square::E2, Square::E4, None)]);
+    assert_eq!(history.current(), Game::default());
+
+    assert!(history.take_back(Color::Black).is_empty());
+    assert!(history.is_empty());
+}
+
+#[test]
+fn take_backs_follow_the_side_that_started() {
+    let mut history = History::new(Game::from_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 0 1").unwrap());
+    history.push(Move::new(Square::E8, Square::D8, None)).unwrap();
+    history.push(Move::new(Square::E2, Square::E4, None)).unwrap();
+
+    assert_eq!(history.take_back(Color::Black).len(), 2);
+    assert_eq!(history.current().turn, Color::Black);
+}
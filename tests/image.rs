@@ -0,0 +1,135 @@
+use chess::image::{write_png, GifWriter};
+
+// pixels from the palette's corners and middle, so they come back exactly
+fn picture(width: usize, height: usize) -> Vec<u8> {
+    let colors = [[0, 0, 0], [255, 255, 255], [255, 0, 0], [0, 255, 0], [0, 0, 255], [255, 255, 0]];
+    let mut seed = 0x2545_f491u32;
+
+    (0..width * height).flat_map(|i| {
+        // long runs and noise, the noise fills the lzw table
+        seed ^= seed << 13;
+        seed ^= seed >> 17;
+        seed ^= seed << 5;
+        let color = if i < width * height / 2 { colors[i / 97 % colors.len()] } else { colors[seed as usize % colors.len()] };
+        [color[0], color[1], color[2], 255]
+    }).collect()
+}
+
+// the codes of a gif frame back to palette indices
+fn unlzw(data: &[u8], min_size: u32) -> Vec<u8> {
+    let (clear, end) = (1u16 << min_size, (1u16 << min_size) + 1);
+    let mut table: Vec<Vec<u8>> = Vec::new();
+    let reset = |table: &mut Vec<Vec<u8>>| {
+        *table = (0..clear).map(|x| vec![x as u8]).collect();
+        table.extend([Vec::new(), Vec::new()]);
+    };
+    reset(&mut table);
+
+    let (mut size, mut bit) = (min_size + 1, 0usize);
+    let mut previous: Option<Vec<u8>> = None;
+    let mut out = Vec::new();
+
+    loop {
+        let code = (0..size).map(|i| (((data[(bit + i as usize) / 8] >> ((bit + i as usize) % 8)) & 1) as u16) << i).sum::<u16>();
+        bit += size as usize;
+
+        if code == clear {
+            reset(&mut table);
+            (size, previous) = (min_size + 1, None);
+            continue;
+        }
+        if code == end { return out; }
+
+        let entry = match (table.get(code as usize), &previous) {
+            (Some(entry), _) => { entry.clone() }
+            (None, Some(previous)) => { [previous.clone(), vec![previous[0]]].concat() }
+            (None, None) => { panic!("code {} before any other", code); }
+        };
+
+        if let Some(previous) = previous {
+            table.push([previous, vec![entry[0]]].concat());
+            if table.len() == 1 << size && size < 12 { size += 1; }
+        }
+
+        out.extend(&entry);
+        previous = Some(entry);
+    }
+}
+
+#[test]
+fn gif_frames_decode_to_the_picture() {
+    let (width, height) = (200, 160);
+    let pixels = picture(width, height);
+
+    let mut gif = GifWriter::new(Vec::new(), width as u16, height as u16).unwrap();
+    gif.frame(&pixels, 50).unwrap();
+    gif.frame(&pixels[..pixels.len() / 2].repeat(2), 50).unwrap();
+    let bytes = gif.finish().unwrap();
+
+    assert_eq!(&bytes[..6], b"GIF89a");
+    assert_eq!(*bytes.last().unwrap(), 0x3b);
+    let palette = &bytes[13..13 + 768];
+
+    // after the palette and the looping extension
+    let mut at = 13 + 768 + 19;
+    let mut frames = Vec::new();
+    while bytes[at] == 0x21 {
+        assert_eq!(&bytes[at..at + 4], [0x21, 0xf9, 4, 4]);
+        assert_eq!(u16::from_le_bytes([bytes[at + 4], bytes[at + 5]]), 50);
+        at += 8;
+
+        assert_eq!(bytes[at], 0x2c);
+        at += 10;
+        let min_size = bytes[at] as u32;
+        at += 1;
+
+        let mut data = Vec::new();
+        while bytes[at] != 0 {
+            let length = bytes[at] as usize;
+            data.extend(&bytes[at + 1..at + 1 + length]);
+            at += 1 + length;
+        }
+        at += 1;
+
+        let colors: Vec<u8> = unlzw(&data, min_size).iter()
+            .flat_map(|x| [palette[*x as usize * 3], palette[*x as usize * 3 + 1], palette[*x as usize * 3 + 2], 255])
+            .collect();
+        frames.push(colors);
+    }
+
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0], pixels);
+    assert_eq!(frames[1], pixels[..pixels.len() / 2].repeat(2));
+}
+
+#[test]
+fn pngs_keep_the_pixels() {
+    let (width, height) = (300, 400);
+    let pixels = picture(width, height);
+
+    let mut bytes = Vec::new();
+    write_png(&mut bytes, width as u32, height as u32, &pixels).unwrap();
+
+    assert_eq!(&bytes[..8], b"\x89PNG\r\n\x1a\n");
+    assert_eq!(&bytes[12..16], b"IHDR");
+    assert_eq!(u32::from_be_bytes(bytes[16..20].try_into().unwrap()), 300);
+    // the end chunk is always the same, crc included
+    assert_eq!(&bytes[bytes.len() - 12..], [0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]);
+
+    // the stored blocks after the zlib header, each with its length
+    let length = u32::from_be_bytes(bytes[33..37].try_into().unwrap()) as usize;
+    let data = &bytes[41..41 + length];
+    let (mut at, mut raw) = (2, Vec::new());
+    loop {
+        let last = data[at] == 1;
+        let size = u16::from_le_bytes([data[at + 1], data[at + 2]]) as usize;
+        raw.extend(&data[at + 5..at + 5 + size]);
+        at += 5 + size;
+        if last { break; }
+    }
+
+    let rows: Vec<u8> = raw.chunks(width * 4 + 1).flat_map(|x| { assert_eq!(x[0], 0); x[1..].to_vec() }).collect();
+    assert_eq!(rows, pixels);
+
+    assert!(write_png(Vec::new(), 2, 2, &[0; 15]).is_err());
+}
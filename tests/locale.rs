@@ -0,0 +1,36 @@
+use chess::locale::{fill, Locale};
+
+#[test]
+fn locale_files_are_parsed() {
+    let locale = Locale::parse("# a comment\nlanguage = Test\n\nmenu.play = Play = go\nnot a text\n");
+
+    assert_eq!(locale.name(), "Test");
+    assert_eq!(locale.get("menu.play"), Some("Play = go"));
+    assert_eq!(locale.get("not a text"), None);
+    assert_eq!(locale.keys().count(), 2);
+}
+
+#[test]
+fn placeholders_are_filled_in_order() {
+    assert_eq!(fill("Saved {} to {}", &["game", "exports"]), "Saved game to exports");
+    assert_eq!(fill("{} and {}", &["one"]), "one and {}");
+    assert_eq!(fill("no placeholders", &["unused"]), "no placeholders");
+}
+
+#[test]
+fn built_in_locales_have_every_text() {
+    let locales = Locale::built_in();
+    let english = &locales[0];
+    assert_eq!(english.name(), "English");
+
+    for locale in &locales[1..] {
+        let mut missing: Vec<&str> = english.keys().filter(|x| locale.get(x).is_none()).collect();
+        missing.sort();
+        assert!(missing.is_empty(), "{} has no {:?}", locale.name(), missing);
+
+        // the same arguments fit both
+        for key in english.keys() {
+            assert_eq!(english.get(key).unwrap().matches("{}").count(), locale.get(key).unwrap().matches("{}").count(), "{}", key);
+        }
+    }
+}
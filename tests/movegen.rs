@@ -0,0 +1,218 @@
+use chess::{Color, Game, Move, MoveResult, Promotion, Square};
+
+fn perft(game: &Game, depth: u32) -> u64 {
+    let moves = game.generate_legal_moves();
+    if depth <= 1 { return moves.len() as u64; }
+
+    moves.iter().map(|mv| {
+        let mut game = *game;
+        game.make_move(*mv).unwrap();
+        perft(&game, depth - 1)
+    }).sum()
+}
+
+fn position(fen: &str) -> Game {
+    Game::from_fen(fen).unwrap()
+}
+
+// the counts from https://www.chessprogramming.org/Perft_Results
+#[test]
+fn perft_start() {
+    assert_eq!(perft(&Game::default(), 3), 8_902);
+}
+
+#[test]
+fn perft_kiwipete() {
+    let game = position("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+    assert_eq!(perft(&game, 2), 2_039);
+}
+
+#[test]
+fn perft_en_passant_pins() {
+    let game = position("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1");
+    assert_eq!(perft(&game, 4), 43_238);
+}
+
+#[test]
+fn perft_promotions_and_castling() {
+    let game = position("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1");
+    assert_eq!(perft(&game, 3), 9_467);
+}
+
+#[test]
+fn perft_discovered_checks() {
+    let game = position("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8");
+    assert_eq!(perft(&game, 2), 1_486);
+}
+
+#[test]
+fn smothered_mate() {
+    let game = position("6rk/5Npp/8/8/8/8/8/6K1 b - - 0 1");
+    assert!(game.is_in_checkmate(Color::Black));
+}
+
+#[test]
+fn double_check_mate() {
+    // taking the knight leaves the rook's check
+    let game = position("rnbqkb1r/pppp1ppp/3N4/8/8/8/8/4RK2 b kq - 0 1");
+    assert!(game.is_in_checkmate(Color::Black));
+}
+
+#[test]
+fn en_passant_evasion() {
+    // the pawn that checks can only be taken en passant
+    let game = position("5r1k/8/1n6/1n1pP3/4K3/r7/8/8 w - d6 0 1");
+    assert!(game.is_in_check(Color::White));
+    assert!(!game.is_in_checkmate(Color::White));
+    assert_eq!(game.generate_legal_moves(), vec![Move::new(36, 43, None)]);
+}
+
+#[test]
+fn distant_capture_of_checker() {
+    // the rook has to slide past squares that don't answer the check to take the knight
+    let game = position("8/5p2/p1BP4/k3P1p1/2N2r1P/2P4p/2b5/1R2K3 b - - 0 93");
+    assert!(!game.is_in_checkmate(Color::Black));
+    assert!(game.generate_legal_moves().contains(&Move::new(29, 26, None)));
+}
+
+#[test]
+fn promotion_mate() {
+    let game = position("k7/2P5/1K6/8/8/8/8/8 w - - 0 1");
+    assert_eq!(game.is_legal_move(50, 58, Some(Promotion::Queen)), MoveResult::Checkmate);
+    assert_eq!(game.is_legal_move(50, 58, Some(Promotion::Rook)), MoveResult::Checkmate);
+    // king and knight can't mate
+    assert_eq!(game.is_legal_move(50, 58, Some(Promotion::Knight)), MoveResult::Draw);
+}
+
+#[test]
+fn stalemate() {
+    let game = position("k7/8/1Q6/8/8/8/8/7K b - - 0 1");
+    assert!(game.is_stalemate());
+    assert!(!game.is_in_checkmate(Color::Black));
+}
+
+#[test]
+fn moves_from_a_square() {
+    let game = Game::default();
+    let knight: Vec<Move> = game.legal_moves_from(Square::G1).collect();
+    assert_eq!(knight, vec![Move::new(Square::G1, Square::F3, None), Move::new(Square::G1, Square::H3, None)]);
+
+    // empty squares and the other side's pieces have none
+    assert_eq!(game.legal_moves_from(Square::E4).count(), 0);
+    assert_eq!(game.legal_moves_from(Square::E7).count(), 0);
+
+    let promotion = position("8/3P4/8/8/8/8/8/k1K5 w - - 0 1");
+    assert_eq!(promotion.legal_moves_from(Square::D7).count(), 4);
+    assert_eq!(promotion.legal_moves().count(), promotion.generate_legal_moves().len());
+}
+
+#[test]
+fn free_moves_ignore_the_rules() {
+    let mut game = Game::default();
+
+    // a rook through its own pawn onto the other side's, which loses it the right to castle there
+    assert_eq!(game.free_move(Square::H1, Square::H7), Some(Some(chess::Piece::BPawn)));
+    assert_eq!(game.turn, Color::Black);
+    assert_eq!(game.as_fen(), "rnbqkbnr/pppppppR/8/8/8/8/PPPPPPPP/RNBQKBN1 b Qkq - 0 1");
+
+    // black moves again, out of turn
+    game.turn = Color::White;
+    assert_eq!(game.free_move(Square::E8, Square::E4), Some(None));
+    assert_eq!(game.as_fen(), "rnbq1bnr/pppppppR/8/8/4k3/8/PPPPPPPP/RNBQKBN1 w Q - 1 2");
+
+    assert_eq!(game.free_move(Square::E5, Square::E6), None);
+}
+
+#[test]
+fn illegal_moves_are_explained() {
+    use chess::error::IllegalReason;
+
+    let explain = |fen: &str, from: Square, to: Square| position(fen).explain_illegal(from, to);
+    let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+    assert_eq!(explain(start, Square::E2, Square::E4), None);
+    assert_eq!(explain(start, Square::E2, Square::E5), Some(IllegalReason::WrongPattern));
+    assert_eq!(explain(start, Square::A1, Square::A3), Some(IllegalReason::PathBlocked));
+    assert_eq!(explain(start, Square::E4, Square::E5), Some(IllegalReason::NoPiece));
+    assert_eq!(explain(start, Square::E7, Square::E5), Some(IllegalReason::OpponentsPiece));
+    assert_eq!(explain(start, Square::E1, Square::E2), Some(IllegalReason::OwnPiece));
+
+    assert_eq!(explain("4k3/4r3/8/8/8/8/4B3/4K3 w - - 0 1", Square::E2, Square::D3), Some(IllegalReason::Pinned));
+    assert_eq!(explain("4k3/4r3/8/8/8/8/P7/4K3 w - - 0 1", Square::A2, Square::A3), Some(IllegalReason::StillInCheck));
+    assert_eq!(explain("4k3/4r3/8/8/8/8/P7/4K3 w - - 0 1", Square::E1, Square::E2), Some(IllegalReason::KingInCheck));
+
+    assert_eq!(explain("4k3/8/8/8/8/8/8/R3K2R w Q - 0 1", Square::E1, Square::G1), Some(IllegalReason::CastlingRightsLost));
+    assert_eq!(explain("4k3/8/8/8/8/8/8/RN2K2R w KQ - 0 1", Square::E1, Square::C1), Some(IllegalReason::PathBlocked));
+    assert_eq!(explain("4k3/8/8/8/8/8/5r2/R3K2R w KQ - 0 1", Square::E1, Square::G1), Some(IllegalReason::CastleThroughCheck));
+    assert_eq!(explain("4k3/4r3/8/8/8/8/8/R3K2R w KQ - 0 1", Square::E1, Square::G1), Some(IllegalReason::CastleOutOfCheck));
+}
+
+#[test]
+fn rejected_moves_carry_the_explanation() {
+    let mut game = Game::default();
+    let err = game.make_move(Move::new(Square::E2, Square::E5, None)).unwrap_err();
+
+    assert_eq!(err.reason, MoveResult::Illegal);
+    assert_eq!(err.explanation, Some(chess::error::IllegalReason::WrongPattern));
+    assert!(err.to_string().ends_with("the piece doesn't move that way"));
+    assert_eq!(game, Game::default());
+}
+
+#[test]
+fn pins_along_ranks_files_and_diagonals() {
+    let pins = |fen: &str, color: Color| position(fen).pinned_pieces(color);
+    let pair = |pinned: Square, pinner: Square| (pinned.index(), pinner.index());
+
+    assert_eq!(pins("4k3/8/8/8/8/8/8/K2N3r w - - 0 1", Color::White), [pair(Square::D1, Square::H1)]);
+    assert_eq!(pins("4k3/8/8/8/8/8/8/K2N3r w - - 0 1", Color::Black), []);
+    assert_eq!(pins("4r1k1/8/8/8/4R3/8/8/4K3 w - - 0 1", Color::White), [pair(Square::E4, Square::E8)]);
+    assert_eq!(pins("6k1/8/8/8/8/2q5/1B6/K7 w - - 0 1", Color::White), [pair(Square::B2, Square::C3)]);
+    assert_eq!(pins("k7/1n6/8/8/8/8/6Q1/7K b - - 0 1", Color::Black), [pair(Square::B7, Square::G2)]);
+
+    // two pieces in the way, or a piece that doesn't move along the line, make no pin
+    assert_eq!(pins("4k3/8/8/8/8/8/8/K1NN3r w - - 0 1", Color::White), []);
+    assert_eq!(pins("4k3/8/8/8/8/8/8/K2N3b w - - 0 1", Color::White), []);
+}
+
+#[test]
+fn en_passant_pins() {
+    // the pinned pawn may take en passant along the pin, not push off it
+    let diagonal = position("7b/8/8/4Pp2/8/8/8/K6k w - f6 0 1");
+    assert_eq!(diagonal.pinned_pieces(Color::White), [(Square::E5.index(), Square::H8.index())]);
+    assert!(diagonal.legal_moves().any(|x| x == Move::new(Square::E5, Square::F6, None)));
+    assert!(!diagonal.legal_moves().any(|x| x == Move::new(Square::E5, Square::E6, None)));
+
+    // taking en passant clears both pawns off the rank at once, neither of them is pinned alone
+    let rank = position("8/8/8/KPp4r/8/8/8/4k3 w - c6 0 1");
+    assert_eq!(rank.pinned_pieces(Color::White), []);
+    assert!(!rank.legal_moves().any(|x| x == Move::new(Square::B5, Square::C6, None)));
+    assert!(rank.legal_moves().any(|x| x == Move::new(Square::B5, Square::B6, None)));
+}
+
+// every legal move down to `depth`, checked against playing it
+fn check_gives_check(game: &Game, depth: u32) {
+    for mv in game.generate_legal_moves() {
+        let mut after = *game;
+        after.make_move(mv).unwrap();
+        assert_eq!(game.gives_check(mv), after.is_in_check(after.turn), "{} in {}", mv, game.as_fen());
+
+        if depth > 1 { check_gives_check(&after, depth - 1); }
+    }
+}
+
+#[test]
+fn gives_check_agrees_with_playing_the_move() {
+    for (fen, depth) in [
+        ("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", 2),
+        ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 3),
+        ("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1", 2),
+        ("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8", 2),
+        // discovered checks by en passant, along the rank and along the diagonal
+        ("8/8/8/R2pP2k/8/8/8/K7 w - d6 0 1", 3),
+        ("6k1/8/8/3pP3/8/1B6/8/6K1 w - d6 0 1", 3),
+        // castling into check with the rook
+        ("5k2/8/8/8/8/8/8/4K2R w K - 0 1", 3),
+    ] {
+        check_gives_check(&position(fen), depth);
+    }
+}
@@ -0,0 +1,82 @@
+mod common;
+
+use chess::engine::{Engine, EngineOptions};
+use chess::error::{EngineOptionError, NetworkError};
+use chess::nnue::Network;
+use chess::uci::Limits;
+use chess::{Game, Move, Promotion, Square};
+use common::TempDir;
+
+// a net of small random weights, in the file format
+fn random_net(hidden: usize) -> Vec<u8> {
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    let count = hidden * 771 + 1;
+
+    let mut bytes: Vec<u8> = (0..count).flat_map(|_| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        ((state % 129) as i16 - 64).to_le_bytes()
+    }).collect();
+    bytes.resize(bytes.len().div_ceil(64) * 64, 0);
+
+    bytes
+}
+
+fn temp_net(dir: &TempDir) -> std::path::PathBuf {
+    let path = dir.join("net.bin");
+    std::fs::write(&path, random_net(16)).unwrap();
+    path
+}
+
+#[test]
+fn the_hidden_size_comes_from_the_length() {
+    assert_eq!(Network::from_bytes(&random_net(32)).unwrap().hidden_size(), 32);
+
+    let mut short = random_net(16);
+    short.truncate(short.len() - 64);
+    assert!(matches!(Network::from_bytes(&short), Err(NetworkError::Size(_))));
+    assert!(matches!(Network::from_bytes(&[]), Err(NetworkError::Size(0))));
+    assert!(matches!(Network::open("no such net.bin"), Err(NetworkError::Io(_))));
+}
+
+#[test]
+fn updates_match_a_fresh_accumulator() {
+    let network = Network::from_bytes(&random_net(16)).unwrap();
+
+    // castling, en passant and a capture promoting to a knight
+    let mut game = Game::from_fen("r3k2r/1P6/8/8/5p2/8/4P3/R3K2R w KQkq - 0 1").unwrap();
+    let mut accumulator = network.accumulator(&game);
+    let moves = [(Square::E1, Square::G1, None), (Square::E8, Square::G8, None), (Square::E2, Square::E4, None),
+        (Square::F4, Square::E3, None), (Square::B7, Square::A8, Some(Promotion::Knight))];
+    for (from, to, promotion) in moves {
+        let before = game;
+        game.make_move(Move::new(from, to, promotion)).unwrap();
+        network.update(&mut accumulator, &before, &game);
+
+        assert_eq!(accumulator, network.accumulator(&game), "after {}{}", from, to);
+        assert_eq!(network.output(&accumulator, game.turn), network.evaluate(&game));
+    }
+    assert_eq!(game.board[Square::A8].map(|x| x.value()), Some(3));
+}
+
+#[test]
+fn the_engine_loads_nets_by_option() {
+    let dir = TempDir::new("nnue-option");
+    let path = temp_net(&dir);
+    let mut engine = Engine::default();
+
+    let missing = engine.set_option("EvalFile", "no such net.bin");
+    assert!(matches!(missing, Err(EngineOptionError::Network(..))));
+    assert!(engine.network().is_none() && engine.options().eval_file.is_none());
+
+    engine.set_option("EvalFile", path.to_str().unwrap()).unwrap();
+    assert_eq!(engine.network().map(Network::hidden_size), Some(16));
+    assert!(engine.search(Game::default(), Limits::default().depth(3), |_| {}).is_some());
+
+    engine.set_option("EvalFile", "<empty>").unwrap();
+    assert!(engine.network().is_none());
+
+    let options = EngineOptions { eval_file: Some(path.clone()), ..Default::default() };
+    assert!(Engine::new(options).network().is_some());
+}
@@ -0,0 +1,37 @@
+use chess::odds::Odds;
+use chess::{Color, Game, Piece, Square};
+
+#[test]
+fn piece_odds_positions() {
+    assert_eq!(Odds::Knight.start(Color::White).as_fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/R1BQKBNR w KQkq - 0 1");
+    assert_eq!(Odds::Queen.start(Color::Black).as_fen(), "rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    assert_eq!(Odds::None.start(Color::White), Game::default());
+}
+
+#[test]
+fn rook_odds_lose_castling() {
+    assert_eq!(Odds::Rook.start(Color::White).as_fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/1NBQKBNR w Kkq - 0 1");
+    assert_eq!(Odds::Rook.start(Color::Black).as_fen(), "1nbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQk - 0 1");
+    assert!(Odds::Rook.start(Color::Black).validate().is_empty());
+}
+
+#[test]
+fn move_odds() {
+    assert_eq!(Odds::Move.start(Color::White).turn, Color::Black);
+    assert_eq!(Odds::Move.start(Color::White).as_fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1");
+}
+
+#[test]
+fn removing_pieces() {
+    let mut game = Game::default();
+    assert_eq!(game.remove_piece(Square::H8), Some(Piece::BRook));
+    assert_eq!(game.remove_piece(Square::E1), Some(Piece::WKing));
+    assert_eq!(game.remove_piece(Square::E4), None);
+    assert!(game.as_fen().contains(" w q - "));
+}
+
+#[test]
+fn handicap_tags() {
+    assert_eq!(Odds::Knight.tag(Color::Black).as_deref(), Some("Black gives knight odds"));
+    assert_eq!(Odds::None.tag(Color::White), None);
+}
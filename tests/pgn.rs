@@ -0,0 +1,65 @@
+mod common;
+
+use std::time::Duration;
+use chess::{Pgn, Square};
+use common::mv;
+
+// the movetext `text` is written back as, after the tags
+fn round_trip(text: &str) -> (Pgn, String) {
+    let pgn = Pgn::parse(text).unwrap();
+    let written = pgn.to_string();
+    assert_eq!(Pgn::parse(&written).unwrap(), pgn, "{}", written);
+
+    let movetext = written.lines().skip_while(|x| !x.is_empty()).skip(1).collect::<Vec<_>>().join(" ");
+    (pgn, movetext)
+}
+
+#[test]
+fn comments() {
+    let (pgn, movetext) = round_trip("{A quiet game} 1. e4 {the king's pawn,\nas usual} e5 2. Nf3 { } Nc6 *");
+
+    assert_eq!(pgn.comment.as_deref(), Some("A quiet game"));
+    assert_eq!(pgn.notes(0).unwrap().comment.as_deref(), Some("the king's pawn, as usual"));
+    // an empty comment is no comment
+    assert_eq!(pgn.notes(2), None);
+    assert_eq!(movetext, "{A quiet game} 1. e4 {the king's pawn, as usual} 1... e5 2. Nf3 Nc6 *");
+}
+
+#[test]
+fn glyphs_and_nags() {
+    let (pgn, movetext) = round_trip("1. e4!? e5 $2 2. Qh5?! $14 Nc6 3. Bc4 Nf6?? 4. Qxf7# 1-0");
+
+    let nags: Vec<Vec<u8>> = (0..7).map(|x| pgn.notes(x).map(|x| x.nags.clone()).unwrap_or_default()).collect();
+    assert_eq!(nags, [vec![5], vec![2], vec![6, 14], vec![], vec![], vec![4], vec![]]);
+    assert_eq!(movetext, "1. e4 $5 e5 $2 2. Qh5 $6 $14 Nc6 3. Bc4 Nf6 $4 4. Qxf7# 1-0");
+}
+
+#[test]
+fn nested_variations() {
+    let text = "1. e4 (1. d4 d5) 1... e5 (1... c5 2. Nf3 (2. c3 d5) 2... d6) 2. Nf3 *";
+    let (pgn, movetext) = round_trip(text);
+    assert_eq!(movetext, text);
+
+    let first = &pgn.notes(0).unwrap().variations[0];
+    assert_eq!(first.moves.iter().map(|x| x.mv).collect::<Vec<_>>(), [mv(Square::D2, Square::D4), mv(Square::D7, Square::D5)]);
+
+    // the variation on black's first move starts from the position after 1. e4, and has one of its own
+    let sicilian = &pgn.notes(1).unwrap().variations[0];
+    assert_eq!(sicilian.moves[0].mv, mv(Square::C7, Square::C5));
+    assert_eq!(sicilian.moves[1].notes.variations[0].moves[0].mv, mv(Square::C2, Square::C3));
+    assert_eq!(pgn.history.len(), 3);
+}
+
+#[test]
+fn clock_commands_are_kept_out_of_comments() {
+    let (pgn, movetext) = round_trip("1. e4 {[%clk 0:05:00] [%emt 0:00:02]} e5 {book [%clk 0:04:59.5]} 2. Nf3 {[%emt 0:00:01.2]} *");
+
+    let entries = pgn.history.entries();
+    assert_eq!((entries[0].clock, entries[0].time), (Some(Duration::from_secs(300)), Some(Duration::from_secs(2))));
+    assert_eq!((entries[1].clock, entries[1].time), (Some(Duration::from_millis(299_500)), None));
+    assert_eq!((entries[2].clock, entries[2].time), (None, Some(Duration::from_millis(1_200))));
+
+    assert_eq!(pgn.notes(0), None);
+    assert_eq!(pgn.notes(1).unwrap().comment.as_deref(), Some("book"));
+    assert_eq!(movetext, "1. e4 {[%clk 0:05:00] [%emt 0:00:02]} 1... e5 {book [%clk 0:04:59.5]} 2. Nf3 {[%emt 0:00:01.2]} *");
+}
@@ -0,0 +1,59 @@
+use chess::archive::ArchivedGame;
+use chess::position_index::PositionIndex;
+use chess::{Color, Game, Pgn};
+
+fn archived(moves: &str, result: &str) -> ArchivedGame {
+    let pgn = Pgn::parse(&format!("[Result \"{}\"]\n\n{} {}\n", result, moves, result)).unwrap();
+    ArchivedGame { path: "game.pgn".into(), pgn }
+}
+
+fn after(moves: &str) -> Game {
+    let pgn = Pgn::parse(&format!("{} *\n", moves)).unwrap();
+    pgn.history.current()
+}
+
+#[test]
+fn transpositions_are_found() {
+    let index = PositionIndex::new(vec![
+        archived("1. d4 d5 2. Nf3 Nf6", "1-0"),
+        archived("1. Nf3 d5 2. d4 e6", "0-1"),
+        archived("1. e4 e5", "1/2-1/2"),
+    ]);
+
+    let search = index.search(&after("1. d4 d5 2. Nf3"));
+    assert_eq!(search.occurrences.iter().map(|x| (x.game, x.ply)).collect::<Vec<_>>(), [(0, 3), (1, 3)]);
+    assert_eq!(index.move_number(&search.occurrences[0]), 2);
+
+    assert_eq!(search.continuations.len(), 2);
+    assert!(search.continuations.iter().all(|x| x.games == 1));
+    assert_eq!(search.continuations[0].score(Color::Black), Some(0.0));
+    assert_eq!(search.continuations[1].score(Color::Black), Some(1.0));
+
+    assert!(index.search(&after("1. c4")).occurrences.is_empty());
+}
+
+#[test]
+fn continuations_are_counted_once_per_game() {
+    let index = PositionIndex::new(vec![
+        // the knights go out and back, so the start comes up twice
+        archived("1. Nf3 Nf6 2. Ng1 Ng8 3. e4", "1-0"),
+        archived("1. e4 c5", "*"),
+        archived("1. e4 e5", "1/2-1/2"),
+    ]);
+
+    let search = index.search(&Game::default());
+    assert_eq!(search.occurrences.len(), 3);
+    assert_eq!(search.occurrences[0].ply, 0);
+
+    // e4 is the most played, unfinished games count as played but not in the score
+    let e4 = search.continuations[0];
+    assert_eq!(e4.mv.to_string(), "e2e4");
+    assert_eq!((e4.games, e4.white_wins, e4.draws, e4.black_wins), (2, 0, 1, 0));
+    assert_eq!(e4.score(Color::White), Some(0.5));
+    assert_eq!(search.continuations[1].mv.to_string(), "g1f3");
+
+    // the end of a game has no continuation
+    let end = index.search(&after("1. e4 e5"));
+    assert_eq!(end.occurrences.len(), 1);
+    assert!(end.continuations.is_empty());
+}
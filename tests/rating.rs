@@ -0,0 +1,37 @@
+use chess::rating::Rating;
+
+fn rating(rating: f64, deviation: f64) -> Rating {
+    Rating { rating, deviation, volatility: 0.06 }
+}
+
+// the example from Glickman's "Example of the Glicko-2 system"
+#[test]
+fn glickmans_example() {
+    let player = rating(1500.0, 200.0);
+    let games = [(rating(1400.0, 30.0), 1.0), (rating(1550.0, 100.0), 0.0), (rating(1700.0, 300.0), 0.0)];
+
+    let updated = player.update(&games);
+    assert!((updated.rating - 1464.06).abs() < 0.01, "{:?}", updated);
+    assert!((updated.deviation - 151.52).abs() < 0.01, "{:?}", updated);
+    assert!((updated.volatility - 0.05999).abs() < 0.00001, "{:?}", updated);
+}
+
+#[test]
+fn a_period_without_games_only_widens_the_deviation() {
+    let player = rating(1700.0, 50.0);
+    let idle = player.update(&[]);
+
+    assert_eq!((idle.rating, idle.volatility), (1700.0, 0.06));
+    assert!(idle.deviation > 50.0 && idle.deviation < 60.0);
+}
+
+#[test]
+fn expected_scores() {
+    let player = Rating::default();
+    assert_eq!(player.expected_score(&player), 0.5);
+
+    // the weaker player's expectation is the rest of the stronger one's
+    let (a, b) = (Rating::fixed(1800.0), Rating::fixed(1600.0));
+    assert!(a.expected_score(&b) > 0.7);
+    assert!((a.expected_score(&b) + b.expected_score(&a) - 1.0).abs() < 1e-9);
+}
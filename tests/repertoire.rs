@@ -0,0 +1,74 @@
+mod common;
+
+use std::path::Path;
+use chess::repertoire::{Answer, Repertoire};
+use chess::{Color, Pgn, Square};
+use common::mv;
+
+const DAY: u64 = 86_400;
+
+fn repertoire(color: Color) -> Repertoire {
+    Repertoire::new(Pgn::parse("1. e4 e5 (1... c5 2. Nf3) 2. Nf3 (2. Bc4) 2... Nc6 3. Bb5 *").unwrap(), color)
+}
+
+#[test]
+fn every_line_is_a_card() {
+    let white = repertoire(Color::White);
+    let lines: Vec<usize> = white.cards.iter().map(|x| x.moves.len()).collect();
+    assert_eq!(lines, [5, 3, 3]);
+    assert_eq!(white.cards[1].moves[2], mv(Square::F1, Square::C4));
+    assert_eq!(white.due(0), 3);
+
+    // black's lines stop after black's last move, the one with 2. Bc4 already at 1... e5
+    let black = repertoire(Color::Black);
+    let lines: Vec<usize> = black.cards.iter().map(|x| x.moves.len()).collect();
+    assert_eq!(lines, [4, 2, 2]);
+    assert_eq!(black.cards[2].moves[1], mv(Square::C7, Square::C5));
+}
+
+#[test]
+fn moves_are_checked_against_the_line() {
+    let white = repertoire(Color::White);
+
+    assert_eq!(white.check(0, 0, mv(Square::E2, Square::E4)), Answer::Correct);
+    assert_eq!(white.check(0, 2, mv(Square::G1, Square::F3)), Answer::Correct);
+    assert_eq!(white.check(2, 2, mv(Square::G1, Square::F3)), Answer::Correct);
+    assert_eq!(white.check(0, 2, mv(Square::F1, Square::C4)), Answer::OtherLine);
+    assert_eq!(white.check(0, 0, mv(Square::D2, Square::D4)), Answer::Wrong);
+    assert_eq!(white.check(0, 4, mv(Square::F1, Square::C4)), Answer::Wrong);
+}
+
+#[test]
+fn reviews_space_out_the_cards() {
+    let mut white = repertoire(Color::White);
+
+    white.review(0, true, 0);
+    assert_eq!((white.cards[0].interval, white.cards[0].due), (1.0, DAY));
+    white.review(0, true, DAY);
+    assert!((white.cards[0].interval - 2.6).abs() < 1e-9);
+
+    // a mistake brings the card back in ten minutes, a little harder
+    white.review(1, false, 0);
+    assert_eq!((white.cards[1].interval, white.cards[1].due), (0.0, 600));
+    assert!((white.cards[1].ease - 2.3).abs() < 1e-9);
+
+    assert_eq!(white.next_card(0), Some(2));
+    white.review(2, true, 0);
+    assert_eq!(white.next_card(0), Some(1));
+    assert_eq!(white.due(DAY), 2);
+}
+
+#[test]
+fn schedules_are_kept() {
+    assert_eq!(Repertoire::schedule_path(Path::new("dir/openings.pgn")), Path::new("dir/openings.train.txt"));
+
+    let mut trained = repertoire(Color::White);
+    trained.review(0, true, 100);
+    trained.review(2, false, 200);
+
+    // lines that left the repertoire and broken lines are skipped
+    let schedule = format!("{}1 1 2.5 1 d2d4\nnot a schedule line\n", trained.schedule());
+    let mut loaded = repertoire(Color::White);
+    loaded.load_schedule(&schedule);
+    assert_eq!(loaded.cards, trained.cards);
+}
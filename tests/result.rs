@@ -0,0 +1,59 @@
+use chess::adjudication::{Adjudication, AdjudicationReason, Adjudicator};
+use chess::result::{GameResult, TerminationReason};
+use chess::uci::Score;
+use chess::{Color, History, Pgn};
+
+fn history(moves: &str) -> History {
+    Pgn::parse(&format!("{} *\n", moves)).unwrap().history
+}
+
+#[test]
+fn mate_and_repetition_end_the_game() {
+    let mated = GameResult::of(&history("1. f3 e5 2. g4 Qh4#"));
+    assert_eq!(mated, Some(GameResult::win(Color::Black, TerminationReason::Checkmate)));
+
+    let shuffled = history("1. Nf3 Nf6 2. Ng1 Ng8 3. Nf3 Nf6 4. Ng1");
+    assert_eq!(GameResult::of(&shuffled), None);
+
+    let repeated = history("1. Nf3 Nf6 2. Ng1 Ng8 3. Nf3 Nf6 4. Ng1 Ng8");
+    assert_eq!(GameResult::of(&repeated), Some(GameResult::draw(TerminationReason::Repetition)));
+}
+
+#[test]
+fn results_round_trip_through_tags() {
+    let mut pgn = Pgn::new(history("1. e4 e5"));
+    assert_eq!(GameResult::from_pgn(&pgn), None);
+
+    GameResult::win(Color::White, TerminationReason::Timeout).write_tags(&mut pgn);
+    assert_eq!(pgn.tag("Termination"), Some("time forfeit"));
+    assert_eq!(GameResult::from_pgn(&pgn), Some(GameResult::win(Color::White, TerminationReason::Timeout)));
+}
+
+#[test]
+fn untold_endings_come_from_the_board() {
+    let mated = Pgn::parse("[Result \"0-1\"]\n\n1. f3 e5 2. g4 Qh4# 0-1\n").unwrap();
+    assert_eq!(GameResult::from_pgn(&mated).unwrap().reason, TerminationReason::Checkmate);
+
+    let resigned = Pgn::parse("[Result \"1-0\"]\n\n1. e4 e5 1-0\n").unwrap();
+    let result = GameResult::from_pgn(&resigned).unwrap();
+    assert_eq!(result.reason, TerminationReason::Resignation);
+    assert_eq!(result.to_string(), "Black resigns");
+
+    let agreed = Pgn::parse("[Result \"1/2-1/2\"]\n\n1. e4 e5 1/2-1/2\n").unwrap();
+    assert_eq!(GameResult::from_pgn(&agreed).unwrap().to_string(), "Drawn by agreement");
+}
+
+#[test]
+fn adjudication_gives_a_game_result() {
+    let rules = Adjudication { resign_moves: 1, ..Adjudication::default() };
+    let mut adjudicator = Adjudicator::new(rules);
+    let game = history("1. e4").current();
+
+    // white just moved and is losing, then black agrees
+    assert_eq!(adjudicator.update(&game, Some(Score::Centipawns(-2_000))), None);
+    let verdict = adjudicator.update(&history("1. e4 e5").current(), Some(Score::Centipawns(2_000)));
+
+    let resigned = GameResult::win(Color::Black, TerminationReason::Adjudication(AdjudicationReason::Resignation));
+    assert_eq!(verdict, Some(resigned));
+    assert_eq!(resigned.score(Color::White), 0.0);
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use chess::error::PgnErrorReason;
+use chess::rules::{RuleModifier, Rules, RulesGame};
+use chess::variant::{Variant, VariantEnd};
+use chess::{Color, Game, Piece, Square};
+use common::mv;
+
+#[test]
+fn modifiers_combine() {
+    let rules = Rules::new(Variant::Horde).with(RuleModifier::Gravity).with(RuleModifier::ChecksToLose(3)).with(RuleModifier::ChecksToLose(5));
+    assert_eq!(rules.modifiers, [RuleModifier::Gravity, RuleModifier::ChecksToLose(5)]);
+    assert_eq!(rules.checks_to_lose(), Some(5));
+    assert_eq!(rules.tag(), "gravity, 5-check");
+    assert_eq!(rules.to_string(), "Horde, gravity, 5-check");
+
+    assert_eq!(Rules::from_tags(Some("Horde"), Some("gravity, 5-check")), Some(rules));
+    assert_eq!(Rules::from_tags(None, None), Some(Rules::default()));
+    assert_eq!(Rules::from_tags(None, Some("gravity, antigravity")), None);
+    assert_eq!(RuleModifier::from_name("0-check"), None);
+}
+
+#[test]
+fn castling_can_be_turned_off() {
+    let start = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    let castle = mv(Square::E1, Square::G1);
+
+    assert!(Rules::default().legal_moves(&start).contains(&castle));
+    let mut game = RulesGame::from_position(Rules::default().with(RuleModifier::NoCastling), start);
+    assert!(!game.legal_moves().contains(&castle));
+    assert!(game.play(castle).is_err());
+    assert!(game.play(mv(Square::E1, Square::F1)).is_ok());
+}
+
+#[test]
+fn kings_can_be_taken() {
+    // white is in check and ignores it
+    let start = Game::from_fen("4k3/8/8/8/8/8/8/r3K2N w - - 0 1").unwrap();
+    let mut game = RulesGame::from_position(Rules::default().with(RuleModifier::KingCapture), start);
+
+    assert_eq!(game.play(mv(Square::H1, Square::G3)), Ok(None));
+    assert_eq!(game.play(mv(Square::A1, Square::E1)), Ok(Some(VariantEnd::KingTaken(Color::Black))));
+    assert!(game.legal_moves().is_empty());
+}
+
+#[test]
+fn pieces_fall_under_gravity() {
+    let rules = Rules::default().with(RuleModifier::Gravity);
+    let mut game = RulesGame::new(rules.clone());
+    for (from, to) in [(Square::E2, Square::E4), (Square::E7, Square::E5), (Square::F1, Square::C4)] {
+        game.play(mv(from, to)).unwrap();
+    }
+    // the bishop lands on the pawn in front of it
+    assert_eq!(game.position().board[Square::C3], Some(Piece::WBishop));
+    assert_eq!(game.position().board[Square::C4], None);
+
+    // a rook that falls off its square takes its castling with it
+    let start = Game::from_fen("r3k2r/ppppppp1/8/8/8/8/PPPPPPPP/R3K2R w KQkq - 0 1").unwrap();
+    let mut game = RulesGame::from_position(rules, start);
+    game.play(mv(Square::A2, Square::A3)).unwrap();
+    assert_eq!(game.position().as_fen(), "r3k3/ppppppp1/8/8/8/P6r/1PPPPPPP/R3K2R b KQq - 0 1");
+}
+
+#[test]
+fn checks_lose() {
+    let mut game = RulesGame::new(Rules::default().with(RuleModifier::ChecksToLose(1)));
+    for (from, to) in [(Square::E2, Square::E4), (Square::F7, Square::F6)] {
+        assert_eq!(game.play(mv(from, to)), Ok(None));
+    }
+
+    assert_eq!(game.play(mv(Square::D1, Square::H5)), Ok(Some(VariantEnd::ChecksGiven(Color::White))));
+    assert_eq!(game.checks(Color::Black), 1);
+    assert_eq!(game.checks(Color::White), 0);
+}
+
+#[test]
+fn games_keep_their_rules_in_pgn() {
+    let rules = Rules::new(Variant::Horde).with(RuleModifier::Gravity).with(RuleModifier::ChecksToLose(3));
+    let mut game = RulesGame::new(rules);
+    for (from, to) in [(Square::D4, Square::D5), (Square::E7, Square::E6), (Square::D5, Square::E6), (Square::D7, Square::E6)] {
+        game.play(mv(from, to)).unwrap();
+    }
+
+    let pgn = game.to_pgn();
+    assert!(pgn.contains("[Variant \"Horde\"]\n[Rules \"gravity, 3-check\"]\n"));
+    assert!(pgn.contains("1. d4d5 e7e6 2. d5e6 d7e6 *"));
+    assert!(!pgn.contains("[FEN"));
+    assert_eq!(RulesGame::parse(&pgn), Ok(game.clone()));
+
+    // without its rules the game doesn't read
+    let err = RulesGame::parse(&pgn.replace("gravity", "levity")).unwrap_err();
+    assert_eq!(err.reason, PgnErrorReason::UnknownRules("levity, 3-check".to_string()));
+    assert_eq!(err.line, 9);
+
+    // the black king would fall to e6, into check from the pawn on d5
+    let err = RulesGame::parse(&pgn.replace("e7e6", "e7e5")).unwrap_err();
+    assert_eq!(err.reason, PgnErrorReason::UnknownMove("e7e5".to_string()));
+}
@@ -0,0 +1,47 @@
+use chess::{Color, Game, Move, Promotion, Square};
+
+fn see(fen: &str, mv: &str) -> i32 {
+    let game = Game::from_fen(fen).unwrap();
+    let (from, to) = (mv[..2].parse::<Square>().unwrap(), mv[2..4].parse::<Square>().unwrap());
+    let promotion = mv[4..].chars().next().map(|_| Promotion::Queen);
+
+    game.see(Move::new(from, to, promotion))
+}
+
+#[test]
+fn winning_and_losing_captures() {
+    // an undefended pawn
+    assert_eq!(see("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "e1e5"), 100);
+    // a defended pawn taken by a rook
+    assert_eq!(see("1k1r4/1ppn3p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "e1e5"), -400);
+    // a knight for a knight
+    assert_eq!(see("4k3/8/3p4/4n3/8/5N2/8/4K3 w - - 0 1", "f3e5"), 0);
+    // a pawn takes a defended queen
+    assert_eq!(see("4k3/8/3p4/4q3/3P4/8/8/4K3 w - - 0 1", "d4e5"), 800);
+}
+
+#[test]
+fn pieces_behind_join_the_exchange() {
+    // the queen behind the rook backs it up, the pawn on d6 is taken twice
+    assert_eq!(see("3rk3/3r4/3p4/8/8/8/3R4/3QK3 w - - 0 1", "d2d6"), -400);
+    assert_eq!(see("4k3/4r3/3p4/8/8/8/3R4/3QK3 w - - 0 1", "d2d6"), 100);
+}
+
+#[test]
+fn quiet_moves_and_promotions() {
+    // a knight moved where a pawn takes it
+    assert_eq!(see("4k3/8/3p4/8/8/5N2/8/4K3 w - - 0 1", "f3e5"), -300);
+    assert_eq!(see("4k3/8/8/8/8/5N2/8/4K3 w - - 0 1", "f3e5"), 0);
+    // promoting on a square nobody guards
+    assert_eq!(see("4k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7a8q"), 800);
+}
+
+#[test]
+fn hanging_pieces_use_the_exchange() {
+    // the knight is defended once and attacked once, but by a pawn
+    let game = Game::from_fen("4k3/8/4n3/3P4/8/8/8/4K3 b - - 0 1").unwrap();
+    assert_eq!(game.hanging_pieces(Color::Black), vec![Square::E6.index()]);
+
+    let game = Game::from_fen("4k3/3p4/4n3/8/8/8/4R3/4K3 b - - 0 1").unwrap();
+    assert!(game.hanging_pieces(Color::Black).is_empty());
+}
@@ -0,0 +1,31 @@
+use chess::error::LinkError;
+use chess::share::{game_link, lichess_url, parse_link};
+use chess::{Game, History, Move, Promotion, Square};
+
+#[test]
+fn game_links_open_the_same_game() {
+    let mut history = History::new(Game::from_fen("8/P7/8/8/8/8/k7/4K3 w - - 0 1").unwrap());
+    history.push(Move::new(Square::A7, Square::A8, Some(Promotion::Knight))).unwrap();
+    history.push(Move::new(Square::A2, Square::B3, None)).unwrap();
+
+    let link = game_link(&history);
+    assert_eq!(link, "chess://game?fen=8/P7/8/8/8/8/k7/4K3_w_-_-_0_1&moves=a7a8n,a2b3");
+    assert_eq!(parse_link(&link).unwrap(), history);
+
+    // just a position, or nothing at all
+    assert_eq!(parse_link("chess://game").unwrap(), History::default());
+    assert_eq!(parse_link("chess://game?fen=8/P7/8/8/8/8/k7/4K3%20w%20-%20-%200%201").unwrap().current(), history.start());
+}
+
+#[test]
+fn broken_links_say_why() {
+    assert!(matches!(parse_link("https://lichess.org/analysis"), Err(LinkError::Scheme)));
+    assert!(matches!(parse_link("chess://game?fen=8/8/8_w_-_-_0_1"), Err(LinkError::Fen(_))));
+    assert!(matches!(parse_link("chess://game?moves=e2e4,e2e4"), Err(LinkError::Move(x)) if x == "e2e4"));
+    assert!(matches!(parse_link("chess://game?moves=e9e4"), Err(LinkError::Move(_))));
+}
+
+#[test]
+fn lichess_links_carry_the_fen() {
+    assert_eq!(lichess_url(&Game::default()), "https://lichess.org/analysis/standard/rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR_w_KQkq_-_0_1");
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use chess::simul::{Outcome, Simul};
+use chess::{Color, Game, Square};
+use common::mv;
+
+#[test]
+fn boards_alternate_colors() {
+    let simul = Simul::new(4, Game::default(), true);
+    let players: Vec<Color> = simul.boards.iter().map(|x| x.player).collect();
+    assert_eq!(players, [Color::White, Color::Black, Color::White, Color::Black]);
+
+    // white moves first, so only the white boards wait on the player
+    assert_eq!(simul.waiting().collect::<Vec<_>>(), [0, 2]);
+    assert!(simul.boards[1].is_engines_turn());
+}
+
+#[test]
+fn next_waiting_goes_round() {
+    let mut simul = Simul::new(3, Game::default(), false);
+    assert_eq!(simul.next_waiting(0), Some(1));
+
+    simul.boards[1].play(mv(Square::E2, Square::E4)).unwrap();
+    assert_eq!(simul.next_waiting(0), Some(2));
+    assert_eq!(simul.next_waiting(2), Some(0));
+
+    simul.boards[0].play(mv(Square::D2, Square::D4)).unwrap();
+    simul.boards[2].play(mv(Square::C2, Square::C4)).unwrap();
+    assert_eq!(simul.next_waiting(0), None);
+}
+
+#[test]
+fn outcomes_are_from_the_players_side() {
+    let mut simul = Simul::new(3, Game::default(), false);
+
+    // fool's mate, the engine mates the player
+    for (from, to) in [(Square::F2, Square::F3), (Square::E7, Square::E5), (Square::G2, Square::G4), (Square::D8, Square::H4)] {
+        simul.boards[0].play(mv(from, to)).unwrap();
+    }
+    assert_eq!(simul.boards[0].outcome, Some(Outcome::Lost));
+    assert_eq!(simul.boards[0].pgn("Engine").tag("Result"), Some("0-1"));
+
+    let mate = Game::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+    simul.boards[1] = chess::simul::SimulBoard::new(mate, Color::White);
+    simul.boards[1].play(mv(Square::A1, Square::A8)).unwrap();
+    assert_eq!(simul.boards[1].outcome, Some(Outcome::Won));
+
+    assert!(!simul.is_finished());
+    simul.boards[2].resign();
+    assert!(simul.is_finished());
+    assert_eq!(simul.tally(), (1, 0, 2));
+    assert_eq!(simul.points(), 1.0);
+}
@@ -0,0 +1,74 @@
+use chess::bots::{Bot, Personality};
+use chess::error::UciError;
+use chess::result::{GameResult, TerminationReason};
+use chess::simulate::{play_out, simulate, MovePicker};
+use chess::uci::{Evaluation, Limits};
+use chess::{Color, Game, History, Move, Square};
+
+// a random player that checks every position it is shown and every move it picks
+struct Checked {
+    bot: Bot,
+    moves: usize,
+}
+
+impl MovePicker for Checked {
+    fn pick(&mut self, history: &History, limits: Limits) -> Result<Evaluation, UciError> {
+        let game = history.current();
+        assert_eq!(game.validate(), [], "{}", game.as_fen());
+
+        let evaluation = self.bot.pick(history, limits)?;
+        assert!(game.legal_moves().any(|x| x == evaluation.best), "{} in {}", evaluation.best, game.as_fen());
+
+        self.moves += 1;
+        Ok(evaluation)
+    }
+}
+
+struct Failing(Result<Move, UciError>);
+
+impl MovePicker for Failing {
+    fn pick(&mut self, _: &History, _: Limits) -> Result<Evaluation, UciError> {
+        match &self.0 {
+            Ok(best) => { Ok(Evaluation { best: *best, score: None }) }
+            Err(_) => { Err(UciError::EngineExited) }
+        }
+    }
+}
+
+#[test]
+fn random_games_stay_legal() {
+    for seed in 0..6 {
+        let mut white = Checked { bot: Bot::with_seed(Personality::Random, seed), moves: 0 };
+        let mut black = Checked { bot: Bot::with_seed(Personality::Random, seed + 100), moves: 0 };
+
+        let result = simulate(Game::default(), &mut white, &mut black);
+        assert!(!matches!(result.reason, TerminationReason::IllegalMove | TerminationReason::Abandonment), "{:?}", result);
+        assert!(white.moves > 0 && white.moves - black.moves <= 1);
+    }
+}
+
+#[test]
+fn seeded_games_repeat() {
+    let play = || simulate(Game::default(), &mut Bot::with_seed(Personality::Greedy, 7), &mut Bot::with_seed(Personality::Random, 8));
+    assert_eq!(play(), play());
+}
+
+#[test]
+fn broken_pickers_lose() {
+    let mut bot = Bot::with_seed(Personality::Random, 1);
+
+    let mut illegal = Failing(Ok(Move::new(Square::new(12), Square::new(36), None)));
+    assert_eq!(simulate(Game::default(), &mut illegal, &mut bot), GameResult::win(Color::Black, TerminationReason::IllegalMove));
+
+    let mut gone = Failing(Err(UciError::EngineExited));
+    assert_eq!(simulate(Game::default(), &mut bot, &mut gone), GameResult::win(Color::White, TerminationReason::Abandonment));
+}
+
+#[test]
+fn played_out_games_end_on_the_board() {
+    let (mut white, mut black) = (Bot::with_seed(Personality::Sacrificer, 3), Bot::with_seed(Personality::Greedy, 4));
+    let (history, result) = play_out(&mut white, &mut black, History::default(), Limits::default(), None).unwrap();
+
+    assert_eq!(GameResult::of(&history), Some(result));
+    assert!(history.entries().iter().all(|x| x.time.is_some()));
+}
@@ -0,0 +1,48 @@
+use chess::pgn::san_line;
+use chess::solver::mating_moves;
+use chess::{Game, Move};
+
+fn line(game: &Game, n: u32) -> Option<Vec<String>> {
+    game.find_mate_in(n).map(|x| x.iter().map(Move::to_string).collect())
+}
+
+#[test]
+fn mate_in_one() {
+    // back rank mate
+    let game = Game::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+    assert_eq!(line(&game, 1), Some(vec!["a1a8".to_string()]));
+    assert_eq!(line(&game, 3), Some(vec!["a1a8".to_string()]));
+}
+
+#[test]
+fn mate_in_two_takes_the_longest_defence() {
+    // quiet first moves, like 1. Kg6 Kg8 2. Ra8# or 1. Kf7 Kh7 2. Rh1#
+    let game = Game::from_fen("7k/8/5K2/8/8/8/8/R7 w - - 0 1").unwrap();
+    assert_eq!(line(&game, 1), None);
+
+    let mate = game.find_mate_in(2).unwrap();
+    assert_eq!(mate.len(), 3);
+
+    let mut end = game;
+    for mv in &mate { end.make_move(*mv).unwrap(); }
+    assert!(end.is_in_checkmate(end.turn));
+
+    let written = san_line(&game, &mate);
+    assert!(written.starts_with("1. ") && written.contains(" 2. ") && written.ends_with('#'), "{}", written);
+}
+
+#[test]
+fn no_mate_without_material() {
+    let game = Game::from_fen("4k3/8/8/8/8/8/8/4K2N w - - 0 1").unwrap();
+    assert_eq!(line(&game, 3), None);
+}
+
+#[test]
+fn puzzles_have_one_solution() {
+    let game = Game::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+    assert_eq!(mating_moves(&game, 1).len(), 1);
+
+    // either rook mates on the back rank
+    let game = Game::from_fen("6k1/5ppp/8/8/8/8/8/RR4K1 w - - 0 1").unwrap();
+    assert_eq!(mating_moves(&game, 1).len(), 2);
+}
@@ -0,0 +1,42 @@
+use chess::sprt::{Sprt, SprtVerdict};
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() < 1e-3
+}
+
+#[test]
+fn bounds_come_from_alpha_and_beta() {
+    let (lower, upper) = Sprt::default().bounds();
+    assert!(close(lower, -2.944) && close(upper, 2.944));
+
+    let (lower, upper) = Sprt::new(0.0, 5.0).alpha(0.05).beta(0.1).bounds();
+    assert!(close(lower, -2.251) && close(upper, 2.890));
+    assert_eq!(Sprt::default().to_string(), "SPRT elo0=0 elo1=5 alpha=0.05 beta=0.05 (-2.94, 2.94)");
+}
+
+#[test]
+fn llr_of_known_results() {
+    let sprt = Sprt::new(0.0, 5.0);
+    assert!(close(sprt.llr(120, 200, 100), 0.468));
+    assert!(close(sprt.llr(100, 200, 120), -0.635));
+    // ten times the games with the same score go ten times as far
+    assert!(close(sprt.llr(1200, 2000, 1000), 4.685));
+
+    // nothing to go on without games, or when every game ended the same
+    assert_eq!(sprt.llr(0, 0, 0), 0.0);
+    assert_eq!(sprt.llr(10, 0, 0), 0.0);
+    assert_eq!(sprt.llr(0, 7, 0), 0.0);
+}
+
+#[test]
+fn verdicts_outside_the_bounds() {
+    let sprt = Sprt::new(0.0, 5.0);
+    assert_eq!(sprt.verdict(sprt.llr(120, 200, 100)), None);
+    assert_eq!(sprt.verdict(sprt.llr(1200, 2000, 1000)), Some(SprtVerdict::AcceptH1));
+    assert_eq!(sprt.verdict(sprt.llr(1000, 2000, 1200)), Some(SprtVerdict::AcceptH0));
+
+    // right on a bound counts
+    let (lower, upper) = sprt.bounds();
+    assert_eq!(sprt.verdict(lower), Some(SprtVerdict::AcceptH0));
+    assert_eq!(sprt.verdict(upper), Some(SprtVerdict::AcceptH1));
+}
@@ -0,0 +1,35 @@
+use chess::{File, Rank, Square};
+
+#[test]
+fn names_round_trip() {
+    for square in Square::all() {
+        assert_eq!(square.to_string().parse::<Square>().unwrap(), square);
+    }
+
+    assert_eq!("e4".parse::<Square>().unwrap(), Square::E4);
+    assert_eq!(Square::H8.to_string(), "h8");
+}
+
+#[test]
+fn bad_names() {
+    for name in ["", "e", "e9", "i1", "e44", "E4"] {
+        assert!(name.parse::<Square>().is_err(), "{}", name);
+    }
+}
+
+#[test]
+fn files_and_ranks() {
+    assert_eq!(Square::at(File::A, Rank::FIRST), Square::A1);
+    assert_eq!(Square::G7.file(), File::from_char('g').unwrap());
+    assert_eq!(Square::G7.rank(), Rank::new(6));
+    assert_eq!(Square::C2.index(), 10);
+    assert_eq!(File::A.distance(File::H), 7);
+}
+
+#[test]
+fn offsets() {
+    assert_eq!(Square::E4.offset(1, 2), Some(Square::F6));
+    assert_eq!(Square::H1.offset(1, 0), None);
+    assert_eq!(Square::A8.offset(0, 1), None);
+    assert_eq!(Square::A8.offset(7, -7), Some(Square::H1));
+}
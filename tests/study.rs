@@ -0,0 +1,76 @@
+mod common;
+
+use chess::study::{Arrow, Highlight, MarkColor, Markup, Study};
+use chess::tree::NodePath;
+use chess::{Pgn, Square};
+use common::{mv, TempDir};
+
+fn arrow(from: Square, to: Square, color: MarkColor) -> Arrow {
+    Arrow { from: from.index(), to: to.index(), color }
+}
+
+#[test]
+fn notes_survive_the_pgn() {
+    let mut pgn = Pgn::parse("1. e4 e5 (1... c5 2. Nf3) 2. Nf3 *").unwrap();
+    let sicilian = NodePath { branches: vec![(1, 0)], ply: 2 };
+
+    pgn.set_comment_at(&NodePath::main_line(0), Some("Before the game".to_string()));
+    pgn.set_comment_at(&NodePath::main_line(2), Some("symmetrical".to_string()));
+    // a closing brace would end the comment early
+    pgn.set_comment_at(&sicilian, Some("the open sicilian {with 2. Nf3}".to_string()));
+
+    let read = Pgn::parse(&pgn.to_string()).unwrap();
+    assert_eq!(read.comment_at(&NodePath::main_line(0)).as_deref(), Some("Before the game"));
+    assert_eq!(read.comment_at(&NodePath::main_line(2)).as_deref(), Some("symmetrical"));
+    assert_eq!(read.comment_at(&sicilian).as_deref(), Some("the open sicilian {with 2. Nf3"));
+    assert_eq!(read.comment_at(&NodePath::main_line(1)), None);
+}
+
+#[test]
+fn marks_toggle() {
+    let mut markup = Markup::default();
+
+    markup.toggle_arrow(arrow(Square::E2, Square::E4, MarkColor::Green));
+    markup.toggle_arrow(arrow(Square::G1, Square::F3, MarkColor::Red));
+    // the same arrow in another color replaces it, in its own color removes it
+    markup.toggle_arrow(arrow(Square::E2, Square::E4, MarkColor::Blue));
+    markup.toggle_arrow(arrow(Square::G1, Square::F3, MarkColor::Red));
+    assert_eq!(markup.arrows, [arrow(Square::E2, Square::E4, MarkColor::Blue)]);
+
+    let highlight = Highlight { square: Square::D5.index(), color: MarkColor::Yellow };
+    markup.toggle_highlight(highlight);
+    assert_eq!(markup.highlights, [highlight]);
+    markup.toggle_highlight(highlight);
+    assert!(markup.highlights.is_empty() && !markup.is_empty());
+}
+
+#[test]
+fn markup_is_saved_next_to_the_game() {
+    let dir = TempDir::new("study-sidecar");
+    let game = dir.join("game.pgn");
+    assert_eq!(Study::sidecar_path(&game), dir.join("game.study.json"));
+    assert_eq!(Study::load(&game).unwrap(), Study::default());
+
+    let moves = [mv(Square::E2, Square::E4), mv(Square::C7, Square::C5)];
+    let mut study = Study::default();
+    study.markup_mut(&moves).toggle_arrow(arrow(Square::G1, Square::F3, MarkColor::Green));
+    study.markup_mut(&moves).toggle_highlight(Highlight { square: Square::D4.index(), color: MarkColor::Red });
+    study.markup_mut(&[]).toggle_highlight(Highlight { square: Square::E4.index(), color: MarkColor::Blue });
+    // a node whose marks were all taken off isn't written
+    study.markup_mut(&moves[..1]);
+    study.save(&game).unwrap();
+
+    let read = Study::load(&game).unwrap();
+    assert_eq!(read.nodes.len(), 2);
+    assert_eq!(read.markup(&moves), study.markup(&moves));
+    assert_eq!(read.markup(&[]), study.markup(&[]));
+
+    // nodes are found by their moves, wherever an edit of the tree put them
+    let mut pgn = Pgn::parse("1. e4 e5 (1... c5) *").unwrap();
+    let promoted = pgn.promote(&NodePath { branches: vec![(1, 0)], ply: 1 }).unwrap();
+    assert!(read.markup(&pgn.moves_to(&promoted).unwrap()).is_some());
+
+    // an empty study takes the file away
+    Study::default().save(&game).unwrap();
+    assert!(!Study::sidecar_path(&game).exists());
+}
@@ -0,0 +1,103 @@
+use chess::result::{GameResult, TerminationReason};
+use chess::sprt::{Sprt, SprtVerdict};
+use chess::tournament::{GameRecord, OpeningSuite, Tournament, TournamentResults};
+use chess::uci::EngineConfig;
+use chess::{Color, Game, History, Pgn};
+
+fn tally(names: &[&str], games: &[(usize, usize, Option<Color>)]) -> TournamentResults {
+    let engines = names.iter().map(|x| EngineConfig::new(*x, "engine")).collect();
+    let mut results = TournamentResults::new(&Tournament::new(engines));
+
+    for (round, &(white, black, winner)) in games.iter().enumerate() {
+        let reason = if winner.is_some() { TerminationReason::Checkmate } else { TerminationReason::Stalemate };
+        let pgn = Pgn::new(History::new(Game::default()));
+        results.record(GameRecord { round: round + 1, white, black, opening: None, result: GameResult { winner, reason }, pgn });
+    }
+
+    results
+}
+
+#[test]
+fn ratings_fit_every_game_at_once() {
+    // a beats b 3-1, b beats c 3-1 and a beats c 3-1
+    let mut games = Vec::new();
+    for (winner, loser) in [(0, 1), (1, 2), (0, 2)] {
+        games.extend([(winner, loser, Some(Color::White)); 3]);
+        games.push((winner, loser, Some(Color::Black)));
+    }
+    let results = tally(&["a", "b", "c"], &games);
+
+    let ratings = results.ratings();
+    assert!(ratings.iter().map(|x| x.elo).sum::<f64>().abs() < 1e-6);
+    assert!(ratings[0].elo > ratings[1].elo && ratings[1].elo > ratings[2].elo);
+    // b's score is even, so it sits in the middle
+    assert!(ratings[1].elo.abs() < 1e-6);
+    assert!(ratings.iter().all(|x| x.error.is_finite() && x.error > 0.0));
+    assert_eq!(results.standings(), [0, 1, 2]);
+
+    let pairs = results.pairs();
+    assert_eq!(pairs.len(), 3);
+    assert_eq!((pairs[0].wins, pairs[0].draws, pairs[0].losses), (3, 0, 1));
+    // a 75% score is worth about 191 elo
+    assert!((pairs[0].elo().elo - 190.8).abs() < 0.1);
+}
+
+#[test]
+fn two_engines_match_their_score() {
+    let results = tally(&["a", "b"], &[(0, 1, Some(Color::White)), (1, 0, None), (0, 1, None), (1, 0, Some(Color::Black))]);
+
+    // 3/4 for a means a 191 elo gap, split around 0
+    let ratings = results.ratings();
+    assert!((ratings[0].elo - ratings[1].elo - 190.8).abs() < 0.1);
+    assert!((ratings[0].elo - 95.4).abs() < 0.1);
+
+    // an engine that lost every game is held at the limit
+    let results = tally(&["a", "b"], &[(0, 1, Some(Color::White)); 4]);
+    let ratings = results.ratings();
+    assert_eq!(ratings[0].elo, chess::tournament::MAX_ELO);
+    assert_eq!(ratings[1].elo, -chess::tournament::MAX_ELO);
+}
+
+#[test]
+fn results_export_as_csv_and_json() {
+    let results = tally(&["a", "b, \"the second\""], &[(0, 1, Some(Color::White)), (1, 0, None)]);
+
+    let csv = results.to_csv();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "rank,name,elo,error,points,games,score");
+    assert!(lines[1].starts_with("1,a,"));
+    assert!(lines[2].starts_with("2,\"b, \"\"the second\"\"\","));
+    assert!(lines[1].ends_with(",1.5,2,75.0"));
+
+    assert!(results.pairs_csv().lines().nth(1).unwrap().starts_with("a,\"b, \"\"the second\"\"\",1,1,0,1.5,2,"));
+    assert_eq!(results.crosstable().to_csv().lines().nth(1), Some("a,,1.5,1.5"));
+
+    let json = results.to_json();
+    assert!(json.contains("{\"name\": \"b, \\\"the second\\\"\", \"elo\": "));
+    assert!(json.contains("\"crosstable\": [\n    [null, 1.5],\n    [0.5, null]\n  ]"));
+    assert!(json.contains("{\"round\": 2, \"white\": \"b, \\\"the second\\\"\", \"black\": \"a\", \"result\": \"1/2-1/2\", \"termination\": \"normal\", \"opening\": null}"));
+
+    // the report lists the ratings and the pair score
+    let report = results.to_string();
+    assert!(report.contains("PLAYER"));
+    assert!(report.contains("a - b, \"the second\": +1 =1 -0 (1.5/2, "));
+}
+
+#[cfg(unix)]
+#[test]
+fn a_verdict_stops_the_schedule() {
+    // every game starts out finished, so the engines only have to answer the handshake
+    let script = "while read line; do case $line in uci) echo uciok;; isready) echo readyok;; quit) exit 0;; esac; done";
+    let engines = ["a", "b"].map(|x| EngineConfig::new(x, "sh").args(["-c", script])).to_vec();
+    let openings = OpeningSuite::from_epd("6k1/8/8/8/8/8/5PPP/r5K1 w - - id \"white mated\";\n4k3/8/8/8/8/8/8/4K3 w - - id \"bare kings\";").unwrap();
+
+    // a round is a win and a loss for each engine and two draws, an even score that settles on H0 quickly
+    let tournament = Tournament::new(engines).openings(openings).rounds(10).concurrency(1).sprt(Some(Sprt::new(0.0, 400.0)));
+    assert_eq!(tournament.schedule().len(), 40);
+
+    let results = tournament.run(|_| {}).unwrap();
+    assert_eq!(results.verdict, Some(SprtVerdict::AcceptH0));
+    assert_eq!(results.games.len(), 5);
+    assert_eq!(results.llr.len(), 5);
+    assert!(results.llr[..4].iter().all(|x| *x > Sprt::new(0.0, 400.0).bounds().0));
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use chess::tree::NodePath;
+use chess::{Pgn, Square};
+use common::mv;
+
+fn movetext(pgn: &Pgn) -> String {
+    pgn.to_string().lines().skip_while(|x| !x.is_empty()).skip(1).collect::<Vec<_>>().join(" ")
+}
+
+#[test]
+fn moves_off_the_line_start_variations() {
+    let mut pgn = Pgn::parse("1. e4 e5 2. Nf3 *").unwrap();
+
+    // the move of the line is followed, another one branches off, the end of the line is extended
+    assert_eq!(pgn.play_at(&NodePath::main_line(1), mv(Square::E7, Square::E5)), Some(NodePath::main_line(2)));
+    let sicilian = pgn.play_at(&NodePath::main_line(1), mv(Square::C7, Square::C5)).unwrap();
+    assert_eq!(sicilian, NodePath { branches: vec![(1, 0)], ply: 1 });
+    assert_eq!(pgn.play_at(&NodePath::main_line(3), mv(Square::B8, Square::C6)), Some(NodePath::main_line(4)));
+
+    let deeper = pgn.play_at(&sicilian, mv(Square::G1, Square::F3)).unwrap();
+    assert_eq!(pgn.moves_to(&deeper).unwrap(), [mv(Square::E2, Square::E4), mv(Square::C7, Square::C5), mv(Square::G1, Square::F3)]);
+    assert_eq!(pgn.previous(&sicilian), Some(NodePath::main_line(1)));
+    assert_eq!(pgn.find_path(&pgn.moves_to(&deeper).unwrap()), Some(deeper));
+
+    // an illegal move changes nothing
+    assert_eq!(pgn.play_at(&NodePath::main_line(0), mv(Square::E2, Square::E5)), None);
+    assert_eq!(movetext(&pgn), "1. e4 e5 (1... c5 2. Nf3) 2. Nf3 Nc6 *");
+}
+
+#[test]
+fn promoting_a_variation_swaps_it_with_its_line() {
+    let mut pgn = Pgn::parse("1. e4 e5 (1... c5 2. Nf3 d6) (1... e6) 2. Nf3 *").unwrap();
+    let path = NodePath { branches: vec![(1, 0)], ply: 2 };
+
+    // the node keeps its moves, now on the main line
+    let promoted = pgn.promote(&path).unwrap();
+    assert_eq!(promoted, NodePath::main_line(3));
+    assert_eq!(movetext(&pgn), "1. e4 c5 (1... e5 2. Nf3) (1... e6) 2. Nf3 d6 *");
+    assert_eq!(pgn.history.len(), 4);
+
+    // and back again
+    pgn.promote(&NodePath { branches: vec![(1, 0)], ply: 1 }).unwrap();
+    assert_eq!(movetext(&pgn), "1. e4 e5 (1... c5 2. Nf3 d6) (1... e6) 2. Nf3 *");
+
+    // the main line has nothing to be promoted over
+    assert_eq!(pgn.promote(&NodePath::main_line(2)), None);
+}
+
+#[test]
+fn deleting_a_variation() {
+    let mut pgn = Pgn::parse("1. e4 e5 (1... c5 2. Nf3 (2. c3)) (1... e6) 2. Nf3 *").unwrap();
+
+    let nested = NodePath { branches: vec![(1, 0), (1, 0)], ply: 1 };
+    assert_eq!(pgn.delete_variation(&nested), Some(NodePath { branches: vec![(1, 0)], ply: 1 }));
+    assert_eq!(movetext(&pgn), "1. e4 e5 (1... c5 2. Nf3) (1... e6) 2. Nf3 *");
+
+    assert_eq!(pgn.delete_variation(&NodePath { branches: vec![(1, 0)], ply: 2 }), Some(NodePath::main_line(1)));
+    assert_eq!(movetext(&pgn), "1. e4 e5 (1... e6) 2. Nf3 *");
+
+    // the main line can't be deleted, nor a variation that isn't there
+    assert_eq!(pgn.delete_variation(&NodePath::main_line(1)), None);
+    assert_eq!(pgn.delete_variation(&NodePath { branches: vec![(1, 3)], ply: 1 }), None);
+}
+
+#[test]
+fn comments_at_nodes() {
+    let mut pgn = Pgn::parse("1. e4 e5 (1... c5) 2. Nf3 *").unwrap();
+    let sicilian = NodePath { branches: vec![(1, 0)], ply: 1 };
+
+    assert!(pgn.set_comment_at(&NodePath::main_line(0), Some("Open game".to_string())));
+    assert!(pgn.set_comment_at(&sicilian, Some("sharper".to_string())));
+    assert!(pgn.set_comment_at(&NodePath::main_line(3), Some("  ".to_string())));
+    assert!(!pgn.set_comment_at(&NodePath::main_line(7), Some("past the end".to_string())));
+
+    assert_eq!(pgn.comment_at(&sicilian).as_deref(), Some("sharper"));
+    assert_eq!(pgn.comment_at(&NodePath::main_line(3)), None);
+    assert_eq!(movetext(&pgn), "{Open game} 1. e4 e5 (1... c5 {sharper}) 2. Nf3 *");
+}
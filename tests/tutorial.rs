@@ -0,0 +1,39 @@
+use chess::tutorial::{lessons, Attempt, Scenario};
+use chess::{Color, Move, Promotion, Square};
+
+#[test]
+fn every_hint_finishes_its_lesson() {
+    for lesson in lessons() {
+        let mut scenario = Scenario::new(lesson.clone());
+
+        for step in &lesson.steps {
+            assert_eq!(scenario.game().turn, Color::White, "{}", lesson.title);
+            assert_eq!(scenario.try_move(step.hint), Attempt::Correct, "{}: {}", lesson.title, step.prompt);
+        }
+        assert!(scenario.is_finished(), "{}", lesson.title);
+    }
+}
+
+#[test]
+fn other_moves_are_wrong_or_illegal() {
+    let mut scenario = Scenario::new(lessons()[0].clone());
+
+    assert_eq!(scenario.try_move(Move::new(Square::E2, Square::E3, None)), Attempt::Wrong);
+    assert_eq!(scenario.try_move(Move::new(Square::E2, Square::E5, None)), Attempt::Illegal);
+    assert_eq!(scenario.progress(), 0);
+    assert_eq!(scenario.game().as_fen(), lessons()[0].fen);
+}
+
+#[test]
+fn goals_accept_any_move_that_meets_them() {
+    let promotion = lessons().into_iter().find(|x| x.title == "Promotion").unwrap();
+    let mut scenario = Scenario::new(promotion);
+
+    // the hint promotes to a queen, a knight is still a promotion
+    assert_eq!(scenario.try_move(Move::new(Square::E1, Square::E2, None)), Attempt::Wrong);
+    assert_eq!(scenario.try_move(Move::new(Square::B7, Square::B8, Some(Promotion::Knight))), Attempt::Correct);
+    assert!(scenario.is_finished());
+
+    scenario.restart();
+    assert_eq!(scenario.progress(), 0);
+}
@@ -0,0 +1,264 @@
+use std::time::{Duration, Instant};
+use chess::error::UciError;
+use chess::uci::{split_engine_id, EngineConfig, Limits, Score, ThreadedUciBuilder, Uci, UciInfo, UciPosition};
+use chess::{Color, Game, History, Move, Promotion, Square};
+use chess::variant::Variant;
+
+#[test]
+fn pv_moves_are_played_out() {
+    let info = UciInfo::parse("info depth 12 score cp 30 pv e2e4 e7e5 g1f3").unwrap();
+    let moves = info.pv_moves(&Game::default());
+
+    assert_eq!(moves, vec![Move::new(Square::E2, Square::E4, None), Move::new(Square::E7, Square::E5, None), Move::new(Square::G1, Square::F3, None)]);
+}
+
+#[test]
+fn pv_moves_stop_at_an_illegal_move() {
+    // e2e4 twice can't be right, the line is from another position
+    let info = UciInfo::parse("info depth 12 score cp 30 pv e2e4 e7e5 e2e4 b8c6").unwrap();
+    assert_eq!(info.pv_moves(&Game::default()).len(), 2);
+
+    let promotion = Game::from_fen("8/4P3/8/8/8/8/8/k1K5 w - - 0 1").unwrap();
+    let info = UciInfo::parse("info depth 3 score mate 2 pv e7e8q a1a2").unwrap();
+    assert_eq!(info.pv_moves(&promotion)[0], Move::new(Square::E7, Square::E8, Some(Promotion::Queen)));
+}
+
+#[test]
+fn position_commands_are_parsed() {
+    let mut history = History::new(Game::default());
+    history.push(Move::new(Square::E2, Square::E4, None)).unwrap();
+    history.push(Move::new(Square::E7, Square::E5, None)).unwrap();
+    assert_eq!(UciPosition::parse("position startpos moves e2e4 e7e5"), Some(UciPosition::Moves(history)));
+
+    let fen = "8/4P3/8/8/8/8/8/k1K5 w - - 0 1";
+    let parsed = UciPosition::parse(&format!("position fen {} moves e7e8q", fen));
+    let Some(UciPosition::Moves(history)) = parsed else { panic!("the position is valid") };
+    assert_eq!(history.start(), Game::from_fen(fen).unwrap());
+    assert_eq!(history.moves().next(), Some(Move::new(Square::E7, Square::E8, Some(Promotion::Queen))));
+
+    // e2e4 can't be played twice
+    assert_eq!(UciPosition::parse("position startpos moves e2e4 e2e4"), None);
+    assert_eq!(UciPosition::parse("position fen not a fen"), None);
+}
+
+#[test]
+fn info_lines_go_back_to_uci() {
+    let line = "info depth 12 seldepth 15 nodes 2041 time 30 score mate -3 pv e2e4 e7e5";
+    assert_eq!(UciInfo::parse(line).unwrap().to_uci(), line);
+}
+
+#[test]
+fn scores_turn_to_whites_view() {
+    assert_eq!(Score::Centipawns(35).white_view(Color::Black), Score::Centipawns(-35));
+    assert_eq!(Score::Mate(-2).white_view(Color::Black), Score::Mate(2));
+    assert_eq!(Score::Mate(3).white_view(Color::White), Score::Mate(3));
+
+    let position = UciPosition::parse("position startpos moves e2e4").unwrap();
+    assert_eq!(position.game().turn, Color::Black);
+}
+
+// an engine in sh that answers the handshake, and `go` with `on_go`
+#[cfg(unix)]
+fn fake_engine(on_go: &str) -> Uci {
+    let script = format!("while read line; do case $line in uci) echo 'id name Fake'; echo uciok;; isready) echo readyok;; go*) {};; quit) exit 0;; esac; done", on_go);
+    let mut uci = Uci::with_config(&EngineConfig::new("Fake", "sh").args(["-c", &script])).unwrap();
+    uci.set_timeout(Duration::from_millis(200));
+
+    uci
+}
+
+#[cfg(unix)]
+#[test]
+fn odd_engine_output_is_skipped() {
+    let mut uci = fake_engine("echo 'hello there'; echo 'info depth x score cp'; echo 'info depth 3 score cp 25'; echo 'bestmove e2e4 ponder e7e5'");
+    let evaluation = uci.evaluate(Game::default(), Limits::default().time(50)).unwrap();
+
+    assert_eq!(evaluation.best, Move::new(Square::E2, Square::E4, None));
+    assert_eq!(evaluation.score, Some(Score::Centipawns(25)));
+    assert_eq!(uci.name(), Some("Fake"));
+}
+
+#[cfg(unix)]
+#[test]
+fn engines_that_ignore_quit_are_killed() {
+    let uci = fake_engine("");
+    // this one hangs instead of quitting
+    let script = "while read line; do case $line in uci) echo uciok;; isready) echo readyok;; quit) sleep 30;; esac; done";
+    let stubborn = Uci::with_config(&EngineConfig::new("Stubborn", "sh").args(["-c", script])).unwrap();
+
+    let started = Instant::now();
+    drop(stubborn);
+    drop(uci);
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[cfg(unix)]
+#[test]
+fn failing_engines_end_in_errors() {
+    let mated = Game::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1").unwrap();
+    assert!(matches!(fake_engine("echo 'bestmove (none)'").evaluate(mated, Limits::default().depth(5)), Err(UciError::NoMove)));
+
+    let started = Instant::now();
+    assert!(matches!(fake_engine("true").evaluate(Game::default(), Limits::default().time(50)), Err(UciError::Timeout)));
+    assert!(started.elapsed() < Duration::from_secs(5));
+
+    assert!(matches!(fake_engine("exit 1").evaluate(Game::default(), Limits::default().depth(5)), Err(UciError::EngineExited)));
+    assert!(matches!(fake_engine("echo 'bestmove e9'").evaluate(Game::default(), Limits::default().depth(5)), Err(UciError::InvalidMove(_))));
+}
+
+#[cfg(unix)]
+#[test]
+fn paused_engines_hold_their_replies() {
+    let script = "while read line; do case $line in uci) echo uciok;; isready) echo readyok;; go*) echo 'bestmove e2e4';; esac; done";
+    let engine = ThreadedUciBuilder::new().config(EngineConfig::new("Fake", "sh").args(["-c", script])).min_delay(Duration::from_millis(100)).build();
+
+    engine.set_paused(true);
+    engine.recommend_move(Game::default(), Limits::default().time(50));
+    std::thread::sleep(Duration::from_millis(500));
+    assert!(engine.try_result().is_none());
+
+    // the delay still has to run once resumed
+    engine.set_paused(false);
+    let started = Instant::now();
+    let result = loop {
+        if let Some(result) = engine.try_result() { break result; }
+        assert!(started.elapsed() < Duration::from_secs(5));
+        std::thread::sleep(Duration::from_millis(10));
+    };
+    assert_eq!(result.unwrap(), Move::new(Square::E2, Square::E4, None));
+    assert!(started.elapsed() >= Duration::from_millis(50));
+}
+
+#[cfg(unix)]
+#[test]
+fn diagnostics_keep_the_handshake_and_stderr() {
+    let script = "echo 'loading net.bin' >&2; read line; echo 'id name Noisy'; echo uciok; read line; read line; echo 'no such option' >&2; echo readyok; read line";
+    let config = EngineConfig::new("", "sh").args(["-c", script]).option("Threads", "2");
+    let diagnostics = config.diagnose(Duration::from_secs(5));
+
+    assert_eq!(diagnostics.outcome.unwrap(), "Noisy");
+    assert_eq!(diagnostics.transcript, ["> uci", "< id name Noisy", "< uciok", "> setoption name Threads value 2", "> isready", "< readyok"]);
+    assert_eq!(diagnostics.stderr, ["loading net.bin", "no such option"]);
+    assert!(diagnostics.resolved_path.is_some_and(|x| x.ends_with("sh")));
+    assert_eq!(diagnostics.working_dir, std::env::current_dir().ok());
+
+    // dies before answering
+    let diagnostics = EngineConfig::new("", "sh").args(["-c", "echo 'missing weights' >&2; exit 3"]).diagnose(Duration::from_secs(5));
+    assert!(matches!(diagnostics.outcome, Err(UciError::EngineExited)));
+    assert_eq!(diagnostics.stderr, ["missing weights"]);
+
+    let diagnostics = EngineConfig::new("", "./no-such-engine").diagnose(Duration::from_secs(5));
+    assert!(matches!(diagnostics.outcome, Err(UciError::Spawn(_))));
+    assert_eq!(diagnostics.resolved_path, None);
+}
+
+#[cfg(unix)]
+#[test]
+fn engines_keep_their_stderr() {
+    let mut uci = fake_engine("echo 'searching' >&2; echo 'bestmove e2e4'");
+    uci.evaluate(Game::default(), Limits::default().depth(1)).unwrap();
+
+    // written on another thread, give it a moment
+    let started = Instant::now();
+    while uci.stderr().is_empty() && started.elapsed() < Duration::from_secs(2) { std::thread::sleep(Duration::from_millis(10)); }
+    assert_eq!(uci.stderr(), ["searching"]);
+}
+
+#[cfg(unix)]
+#[test]
+fn engines_run_where_and_how_they_are_told() {
+    let dir = std::env::temp_dir().canonicalize().unwrap();
+    let script = "read line; echo \"id name $ENGINE_NET in $(pwd)\"; echo uciok";
+    let config = EngineConfig::new("", "sh").args(["-c", script]).working_dir(&dir).env("ENGINE_NET", "big.nnue");
+
+    assert_eq!(config.validate(Duration::from_secs(5)).unwrap(), format!("big.nnue in {}", dir.display()));
+    let diagnostics = config.diagnose(Duration::from_secs(5));
+    assert_eq!(diagnostics.working_dir, Some(dir));
+    assert!(diagnostics.command.starts_with("ENGINE_NET=big.nnue sh -c"));
+}
+
+#[cfg(unix)]
+#[test]
+fn options_set_mid_game_reach_the_engine() {
+    // plays e2e4 until it is told its elo, d2d4 after
+    let script = "elo=; while read line; do case $line in uci) echo uciok;; isready) echo readyok;; \"setoption name UCI_Elo value 1500\") elo=1;; go*) if [ -n \"$elo\" ]; then echo 'bestmove d2d4'; else echo 'bestmove e2e4'; fi;; esac; done";
+    let engine = ThreadedUciBuilder::new().config(EngineConfig::new("Fake", "sh").args(["-c", script])).build();
+
+    let wait = || {
+        let started = Instant::now();
+        loop {
+            if let Some(result) = engine.try_result() { break result.unwrap(); }
+            assert!(started.elapsed() < Duration::from_secs(5));
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    };
+
+    engine.recommend_move(Game::default(), Limits::default().time(50));
+    assert_eq!(wait(), Move::new(Square::E2, Square::E4, None));
+
+    engine.set_option("UCI_Elo", "1500");
+    engine.recommend_move(Game::default(), Limits::default().time(50));
+    assert_eq!(wait(), Move::new(Square::D2, Square::D4, None));
+}
+
+#[test]
+fn engine_ids_split_into_name_and_version() {
+    assert_eq!(split_engine_id("Stockfish 16.1"), ("Stockfish", Some("16.1")));
+    assert_eq!(split_engine_id("Komodo Dragon v3.2 64-bit"), ("Komodo Dragon", Some("v3.2 64-bit")));
+    assert_eq!(split_engine_id("Fake"), ("Fake", None));
+    assert_eq!(split_engine_id("Very Slow Engine"), ("Very Slow Engine", None));
+}
+
+#[test]
+fn limits_name_their_time_control() {
+    assert_eq!(Limits::default().w_time(60_000).b_time(60_000).w_inc(500).b_inc(500).pgn_time_control(), "60+0.5");
+    assert_eq!(Limits::default().time(1_500).pgn_time_control(), "1/1.5");
+    assert_eq!(Limits::default().depth(8).pgn_time_control(), "-");
+}
+
+#[cfg(unix)]
+#[test]
+fn engine_games_keep_clocks_and_ids() {
+    use chess::tournament::{Pairing, Tournament};
+
+    // white moves at once, black takes longer than its whole clock
+    let configs = [("Quick 2.0", "echo 'bestmove e2e4'"), ("Slow", "sleep 0.3; echo 'bestmove e7e5'")].map(|(name, on_go)| {
+        let script = format!("while read line; do case $line in uci) echo 'id name {}'; echo uciok;; isready) echo readyok;; go*) {};; quit) exit 0;; esac; done", name, on_go);
+        EngineConfig::new(name, "sh").args(["-c", &script])
+    });
+    let tournament = Tournament::new(configs.to_vec()).limits(Limits::default().w_time(1_000).b_time(200).w_inc(100).b_inc(100));
+
+    let (mut white, mut black) = (Uci::with_config(&configs[0]).unwrap(), Uci::with_config(&configs[1]).unwrap());
+    let record = tournament.play(Pairing { round: 1, white: 0, black: 1, opening: None }, &mut white, &mut black).unwrap();
+
+    assert_eq!(record.result.winner, Some(Color::White));
+    assert_eq!(record.pgn.tag("Termination"), Some("time forfeit"));
+    assert_eq!(record.pgn.tag("TimeControl"), Some("1+0.1"));
+    assert_eq!(record.pgn.tag("WhiteEngine"), Some("Quick"));
+    assert_eq!(record.pgn.tag("WhiteEngineVersion"), Some("2.0"));
+    assert_eq!(record.pgn.tag("BlackEngine"), Some("Slow"));
+    assert_eq!(record.pgn.tag("BlackEngineVersion"), None);
+
+    // the increment came back on after white's move
+    let clock = record.pgn.history.entries()[0].clock.unwrap();
+    assert!(clock > Duration::from_millis(1_000) && clock <= Duration::from_millis(1_100));
+    assert!(record.pgn.to_string().contains("[%clk 0:00:01"));
+}
+
+#[cfg(unix)]
+#[test]
+fn variants_go_to_engines_that_play_them() {
+    let script = "while read line; do case $line in uci) echo \"option name UCI_Variant type combo default chess var chess var horde\"; echo uciok;; isready) echo readyok;; quit) exit 0;; esac; done";
+    let mut uci = Uci::with_config(&EngineConfig::new("Variants", "sh").args(["-c", script])).unwrap();
+    uci.set_timeout(Duration::from_millis(200));
+
+    assert_eq!(uci.variants(), ["chess", "horde"]);
+    assert!(uci.set_variant(Variant::Horde).is_ok());
+    assert!(matches!(uci.set_variant(Variant::RacingKings), Err(UciError::UnsupportedVariant(x)) if x == "Racing Kings"));
+
+    // an engine without the option plays chess only
+    let mut plain = fake_engine("");
+    assert!(plain.variants().is_empty());
+    assert!(plain.set_variant(Variant::Standard).is_ok());
+    assert!(plain.set_variant(Variant::Horde).is_err());
+}
@@ -0,0 +1,87 @@
+use chess::variant::{Variant, VariantEnd};
+use chess::{Color, Game, Move, Square};
+
+fn perft(variant: Variant, game: &Game, depth: u32) -> usize {
+    if depth == 0 { return 1; }
+
+    variant.legal_moves(game).into_iter().map(|mv| {
+        let mut after = *game;
+        variant.make_move(&mut after, mv).unwrap();
+        perft(variant, &after, depth - 1)
+    }).sum()
+}
+
+#[test]
+fn variants_start_where_they_should() {
+    assert_eq!(Variant::Standard.start(), Game::default());
+    assert_eq!(Variant::Horde.start().as_fen(), "rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/PPPPPPPP w kq - 0 1");
+    assert_eq!(Variant::RacingKings.start().as_fen(), "8/8/8/8/8/8/krbnNBRK/qrbnNBRQ w - - 0 1");
+
+    // known move counts from the starts
+    assert_eq!(perft(Variant::Horde, &Variant::Horde.start(), 2), 128);
+    assert_eq!(perft(Variant::RacingKings, &Variant::RacingKings.start(), 1), 21);
+    assert_eq!(perft(Variant::RacingKings, &Variant::RacingKings.start(), 2), 421);
+
+    assert_eq!(Variant::from_name("Racing Kings"), Some(Variant::RacingKings));
+    assert_eq!(Variant::from_name("racingkings"), Some(Variant::RacingKings));
+    assert_eq!(Variant::from_name("HORDE"), Some(Variant::Horde));
+    assert_eq!(Variant::from_name("crazyhouse"), None);
+}
+
+#[test]
+fn horde_pawns_move_up_from_the_first_rank() {
+    let variant = Variant::Horde;
+    let mut game = variant.from_fen("rnbqkbnr/pppppppp/8/8/8/8/1PPPPPPP/PPPPPPPP w kq - 0 1").unwrap();
+    // a chess fen can't have so many pawns, or a side without a king
+    assert!(Game::from_fen(game.as_fen()).is_err());
+
+    let push = Move::new(Square::A1, Square::A3, None);
+    assert!(variant.is_legal(&game, push));
+    assert_eq!(variant.make_move(&mut game, push), Ok(None));
+    assert_eq!(game.en_passant, Some(Square::A2));
+
+    // the fen of the position after it reads back
+    assert_eq!(variant.from_fen(game.as_fen()).unwrap(), game);
+    // no pawn can double from the first rank in chess
+    assert!(Variant::Standard.make_move(&mut Game::default(), Move::new(Square::A2, Square::A5, None)).is_err());
+}
+
+#[test]
+fn taking_the_horde_wins() {
+    let variant = Variant::Horde;
+    let mut game = variant.from_fen("4k3/8/8/8/8/8/8/3qP3 b - - 0 1").unwrap();
+
+    let end = variant.make_move(&mut game, Move::new(Square::D1, Square::E1, None)).unwrap();
+    assert_eq!(end, Some(VariantEnd::HordeTaken));
+    assert_eq!(end.unwrap().winner(), Some(Color::Black));
+    assert_eq!(end.unwrap().pgn_result(), "0-1");
+}
+
+#[test]
+fn racing_kings_has_no_checks() {
+    let variant = Variant::RacingKings;
+    let mut game = variant.from_fen("8/8/8/8/2k5/8/8/R3K3 w - - 0 1").unwrap();
+
+    // the rook can't go to the c file or the fourth rank
+    let check = Move::new(Square::A1, Square::C1, None);
+    assert!(!variant.is_legal(&game, check));
+    assert!(variant.is_legal(&game, Move::new(Square::A1, Square::B1, None)));
+    assert_eq!(variant.make_move(&mut game, check).unwrap_err().explanation, Some(chess::error::IllegalReason::GivesCheck));
+
+    // and a position with a king in check can't come up
+    assert!(variant.from_fen("8/8/8/8/8/2k5/8/2R1K3 b - - 0 1").is_err());
+}
+
+#[test]
+fn racing_kings_ends_on_the_eighth_rank() {
+    let variant = Variant::RacingKings;
+
+    let mut game = variant.from_fen("8/1K6/8/8/8/8/6k1/8 w - - 0 1").unwrap();
+    let end = variant.make_move(&mut game, Move::new(Square::B7, Square::B8, None)).unwrap();
+    assert_eq!(end, Some(VariantEnd::KingReachedGoal(Color::White)));
+
+    // black, a move from the goal too, gets to make it a draw
+    let mut game = variant.from_fen("8/1K4k1/8/8/8/8/8/8 w - - 0 1").unwrap();
+    assert_eq!(variant.make_move(&mut game, Move::new(Square::B7, Square::B8, None)), Ok(None));
+    assert_eq!(variant.make_move(&mut game, Move::new(Square::G7, Square::G8, None)), Ok(Some(VariantEnd::BothKingsReachedGoal)));
+}
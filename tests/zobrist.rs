@@ -0,0 +1,54 @@
+mod common;
+
+use chess::uci::{AnalysisCache, CachedAnalysis, UciInfo};
+use chess::{Game, Move, Square};
+use common::play;
+
+#[test]
+fn transpositions_hash_the_same() {
+    let a = play(&[(Square::G1, Square::F3), (Square::G8, Square::F6), (Square::B1, Square::C3)]);
+    let b = play(&[(Square::B1, Square::C3), (Square::G8, Square::F6), (Square::G1, Square::F3)]);
+    assert_eq!(a.zobrist(), b.zobrist());
+
+    // the same pieces with the other side to move
+    let c = play(&[(Square::G1, Square::F3), (Square::G8, Square::F6), (Square::F3, Square::G1), (Square::F6, Square::G8)]);
+    assert_eq!(c.as_fen().split(' ').next(), Game::default().as_fen().split(' ').next());
+    assert_eq!(c.zobrist(), Game::default().zobrist());
+    assert_ne!(a.zobrist(), Game::default().zobrist());
+}
+
+#[test]
+fn side_castling_and_en_passant() {
+    let white = Game::from_fen("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1").unwrap();
+    let black = Game::from_fen("4k3/8/8/8/8/8/8/R3K3 b Q - 0 1").unwrap();
+    let no_castling = Game::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+    assert_ne!(white.zobrist(), black.zobrist());
+    assert_ne!(white.zobrist(), no_castling.zobrist());
+
+    // only an en passant square a pawn can take on counts
+    let takeable = Game::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+    let gone = Game::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - - 0 1").unwrap();
+    assert_ne!(takeable.zobrist(), gone.zobrist());
+
+    let lone = Game::from_fen("4k3/8/8/3p4/8/8/8/4K3 w - d6 0 1").unwrap();
+    let lone_gone = Game::from_fen("4k3/8/8/3p4/8/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(lone.zobrist(), lone_gone.zobrist());
+}
+
+#[test]
+fn cache_keeps_the_deeper_search() {
+    let game = Game::default();
+    let analysis = |depth, best| CachedAnalysis { info: Some(UciInfo { depth: Some(depth), ..UciInfo::default() }), best };
+    let (e4, d4) = (Move::new(Square::E2, Square::E4, None), Move::new(Square::D2, Square::D4, None));
+
+    let mut cache = AnalysisCache::new();
+    assert!(cache.get(&game).is_none());
+
+    cache.insert(&game, analysis(20, e4));
+    cache.insert(&game, analysis(10, d4));
+    assert_eq!(cache.get(&game).map(|x| x.best), Some(e4));
+
+    cache.insert(&game, analysis(24, d4));
+    assert_eq!(cache.get(&game).map(|x| x.depth()), Some(24));
+    assert_eq!(cache.len(), 1);
+}